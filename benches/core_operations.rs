@@ -0,0 +1,144 @@
+//! Throughput benchmarks for core engine operations.
+//!
+//! Each run initializes a temporary repository with [`ExampleEnvironment`],
+//! preloads a deterministic synthetic dataset from
+//! [`testing::datasets`](sz_rust_sdk::testing::datasets), then measures:
+//!
+//! | Benchmark | Measures |
+//! |-----------|----------|
+//! | `add_record` | Adding records not yet in the repository, then re-adding them once the pool is exhausted |
+//! | `search_by_attributes` | Searching with the attributes of preloaded records |
+//! | `get_entity` | Reading the entity of a preloaded record by record key |
+//! | `export` | A full JSON entity export, fetched to the end |
+//!
+//! Every call goes through the FFI layer and its string conversions, so
+//! regressions there show up as lower throughput. Compare against a saved
+//! baseline:
+//!
+//! ```text
+//! cargo bench --bench core_operations -- --save-baseline main
+//! # ...make changes...
+//! cargo bench --bench core_operations -- --baseline main
+//! ```
+//!
+//! | Variable | Default | Meaning |
+//! |----------|---------|---------|
+//! | `BENCH_RECORDS` | 1000 | Records preloaded before the read benchmarks |
+//! | `BENCH_ADD_POOL` | 2000 | Distinct records the `add_record` benchmark cycles through |
+//! | `BENCH_SEED` | 42 | Dataset seed |
+
+use criterion::{Criterion, Throughput};
+use std::hint::black_box;
+use std::process::ExitCode;
+use sz_rust_sdk::export::{ExportFormat, SzExporter};
+use sz_rust_sdk::helpers::ExampleEnvironment;
+use sz_rust_sdk::prelude::*;
+use sz_rust_sdk::testing::datasets::{DatasetSpec, SyntheticRecord};
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+fn main() -> ExitCode {
+    let mut criterion = Criterion::default().configure_from_args();
+    match run(&mut criterion) {
+        Ok(()) => {
+            criterion.final_summary();
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("benchmark setup failed: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(criterion: &mut Criterion) -> SzResult<()> {
+    let preload = env_or("BENCH_RECORDS", 1000usize).max(1);
+    let add_pool = env_or("BENCH_ADD_POOL", 2000usize).max(1);
+    let dataset = DatasetSpec::new(env_or("BENCH_SEED", 42))
+        .with_people((preload + add_pool) * 4 / 5)
+        .with_organizations((preload + add_pool) / 5)
+        .generate()?;
+    // Interleave people and organizations so both pools get some of each
+    let mut records: Vec<&SyntheticRecord> = dataset.records().iter().collect();
+    records.sort_by(|a, b| a.record_id[1..].cmp(&b.record_id[1..]));
+    let (preloaded, to_add) = records.split_at(preload.min(records.len()));
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-bench")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    for record in preloaded {
+        engine.add_record(
+            &record.data_source,
+            &record.record_id,
+            &record.definition,
+            None,
+        )?;
+    }
+
+    let mut group = criterion.benchmark_group("core");
+    group.throughput(Throughput::Elements(1));
+
+    if !to_add.is_empty() {
+        let mut next = to_add.iter().cycle();
+        group.bench_function("add_record", |b| {
+            b.iter(|| {
+                let record = next.next().expect("cycle never ends");
+                engine
+                    .add_record(
+                        &record.data_source,
+                        &record.record_id,
+                        &record.definition,
+                        None,
+                    )
+                    .expect("add_record")
+            })
+        });
+    }
+
+    let mut next = preloaded.iter().cycle();
+    group.bench_function("search_by_attributes", |b| {
+        b.iter(|| {
+            let record = next.next().expect("cycle never ends");
+            engine
+                .search_by_attributes(&record.definition, None, None)
+                .expect("search_by_attributes")
+        })
+    });
+
+    let mut next = preloaded.iter().cycle();
+    group.bench_function("get_entity", |b| {
+        b.iter(|| {
+            let record = next.next().expect("cycle never ends");
+            engine
+                .get_entity(
+                    EntityRef::Record {
+                        data_source: &record.data_source,
+                        record_id: &record.record_id,
+                    },
+                    None,
+                )
+                .expect("get_entity")
+        })
+    });
+    group.finish();
+
+    let exporter = SzExporter::new(env.clone());
+    let mut group = criterion.benchmark_group("export");
+    group.sample_size(10);
+    group.bench_function("export", |b| {
+        b.iter(|| {
+            let written = exporter
+                .export_to_writer(std::io::sink(), ExportFormat::JsonLines, None)
+                .expect("export");
+            black_box(written)
+        })
+    });
+    group.finish();
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)
+}
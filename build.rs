@@ -1,27 +1,65 @@
 use std::env;
+use std::fs;
 use std::path::Path;
 
 fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=SENZING_LIB_PATH");
+    println!("cargo:rerun-if-env-changed=SENZING_DIR");
+
+    #[cfg(feature = "sz-grpc-server")]
+    compile_protos();
+
+    // stub-ffi builds without libSz at all: nothing is linked and every
+    // native function is replaced by a stub that panics. It takes precedence
+    // over runtime-dlopen and the link features.
+    if env::var_os("CARGO_FEATURE_STUB_FFI").is_some() {
+        write_wrappers("stub_wrappers.rs", stub_body);
+        return;
+    }
+    // runtime-dlopen loads libSz when the environment first initializes, so
+    // nothing is linked and wrappers resolving each symbol are generated.
+    if env::var_os("CARGO_FEATURE_RUNTIME_DLOPEN").is_some() {
+        write_wrappers("dlopen_wrappers.rs", dlopen_body);
+        return;
+    }
+    let link_kind = if env::var_os("CARGO_FEATURE_LINK_STATIC").is_some() {
+        "static"
+    } else {
+        "dylib"
+    };
+
     let lib_name = "Sz";
+    // cfg!(target_os) describes the host running this script, not the target
+    let target_os = env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
 
-    // Priority: SENZING_LIB_PATH > SENZING_DIR > platform-specific auto-detection
-    let senzing_lib_path = env::var("SENZING_LIB_PATH")
-        .ok()
-        .or_else(|| env::var("SENZING_DIR").ok().map(|d| format!("{d}/lib")))
-        .or_else(detect_senzing_lib)
-        .unwrap_or_else(|| {
-            if cfg!(target_os = "windows") {
-                "C:\\Program Files\\Senzing\\er\\lib".to_string()
-            } else {
-                "/opt/senzing/er/lib".to_string()
-            }
-        });
+    // Priority: SENZING_LIB_PATH > SENZING_DIR > platform-specific auto-detection.
+    // SENZING_LIB_PATH may list several directories, separated as in PATH.
+    let search_dirs: Vec<String> = match env::var_os("SENZING_LIB_PATH") {
+        Some(paths) => env::split_paths(&paths)
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(|dir| dir.display().to_string())
+            .collect(),
+        None => Vec::new(),
+    };
+    let search_dirs = if search_dirs.is_empty() {
+        let senzing_lib = env::var("SENZING_DIR")
+            .ok()
+            .map(|d| Path::new(&d).join("lib").display().to_string())
+            .or_else(|| detect_senzing_lib(&target_os))
+            .unwrap_or_else(|| default_lib_dir(&target_os));
+        vec![senzing_lib]
+    } else {
+        search_dirs
+    };
 
-    println!("cargo:rustc-link-search=native={senzing_lib_path}");
-    println!("cargo:rustc-link-lib=dylib={lib_name}");
+    for dir in &search_dirs {
+        println!("cargo:rustc-link-search=native={dir}");
+    }
+    println!("cargo:rustc-link-lib={link_kind}={lib_name}");
 
     // macOS 4.3 cask is missing rpath entries for openssl and sqlite3
-    if cfg!(target_os = "macos")
+    if target_os == "macos"
         && let Some(prefix) = homebrew_prefix()
     {
         for formula in ["sqlite", "openssl@3"] {
@@ -31,30 +69,64 @@ fn main() {
             }
         }
     }
+}
 
-    println!("cargo:rerun-if-changed=build.rs");
-    println!("cargo:rerun-if-env-changed=SENZING_LIB_PATH");
-    println!("cargo:rerun-if-env-changed=SENZING_DIR");
+/// Generates the gRPC server code for the protos in `proto/`, with the
+/// vendored protoc so no protoc install is needed.
+#[cfg(feature = "sz-grpc-server")]
+fn compile_protos() {
+    println!("cargo:rerun-if-changed=proto");
+    let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc is unavailable");
+    // SAFETY: the build script is single-threaded
+    unsafe { env::set_var("PROTOC", protoc) };
+    tonic_prost_build::configure()
+        .build_client(false)
+        .compile_protos(
+            &["proto/szengine.proto", "proto/szproduct.proto"],
+            &["proto"],
+        )
+        .expect("failed to compile the gRPC protos");
 }
 
-fn detect_senzing_lib() -> Option<String> {
-    // macOS: Homebrew official cask, then legacy unofficial tap
-    for base in [
-        "/opt/homebrew/opt/senzing/er/lib",
-        "/usr/local/opt/senzing/er/lib",
-        "/opt/homebrew/opt/senzing/runtime/er/lib",
-        "/usr/local/opt/senzing/runtime/er/lib",
-    ] {
-        if Path::new(base).exists() {
-            return Some(base.to_string());
+/// Library directories of the standard installations for `target_os`, in
+/// the order `native::install_roots` documents.
+fn platform_lib_dirs(target_os: &str) -> Vec<String> {
+    match target_os {
+        "macos" => [
+            "/opt/homebrew/opt/senzing/er/lib",
+            "/usr/local/opt/senzing/er/lib",
+            "/opt/homebrew/opt/senzing/runtime/er/lib",
+            "/usr/local/opt/senzing/runtime/er/lib",
+        ]
+        .map(String::from)
+        .to_vec(),
+        "windows" => {
+            let program_files =
+                env::var("ProgramFiles").unwrap_or_else(|_| "C:\\Program Files".to_string());
+            let mut dirs = vec![
+                format!("{program_files}\\Senzing\\er\\lib"),
+                "C:\\Senzing\\er\\lib".to_string(),
+            ];
+            if let Ok(home) = env::var("USERPROFILE") {
+                dirs.push(format!("{home}\\scoop\\apps\\senzingsdk\\current\\er\\lib"));
+            }
+            dirs
         }
+        _ => vec!["/opt/senzing/er/lib".to_string()],
     }
-    // Linux standard
-    let linux = "/opt/senzing/er/lib";
-    if Path::new(linux).exists() {
-        return Some(linux.to_string());
-    }
-    None
+}
+
+fn detect_senzing_lib(target_os: &str) -> Option<String> {
+    platform_lib_dirs(target_os)
+        .into_iter()
+        .find(|dir| Path::new(dir).exists())
+}
+
+fn default_lib_dir(target_os: &str) -> String {
+    platform_lib_dirs(target_os)
+        .into_iter()
+        .next()
+        .unwrap_or_default()
 }
 
 fn homebrew_prefix() -> Option<String> {
@@ -65,3 +137,107 @@ fn homebrew_prefix() -> Option<String> {
     }
     None
 }
+
+/// Writes the wrappers generated from the FFI bindings to `OUT_DIR/file`.
+fn write_wrappers(file: &str, body: fn(&str, &str, &str, &str) -> String) {
+    let bindings = "src/ffi/bindings_generated.rs";
+    println!("cargo:rerun-if-changed={bindings}");
+    let source = fs::read_to_string(bindings).expect("read FFI bindings");
+    let out = Path::new(&env::var("OUT_DIR").expect("OUT_DIR")).join(file);
+    fs::write(out, wrappers(&source, body)).expect("write FFI wrappers");
+}
+
+/// Generates one wrapper per `pub fn` in the bindgen output. Each wrapper has
+/// the extern function's signature and a body produced by `body` from the
+/// function name, parameter types, argument names and return type. Defined
+/// in the `ffi` module, the wrappers shadow the glob-imported extern
+/// declarations, which are then never referenced.
+fn wrappers(bindings: &str, body: fn(&str, &str, &str, &str) -> String) -> String {
+    let mut out = String::from("// Generated by build.rs from src/ffi/bindings_generated.rs\n");
+    let mut rest = bindings;
+    while let Some(start) = rest.find("pub fn ") {
+        rest = &rest[start + "pub fn ".len()..];
+        let open = rest.find('(').expect("parameter list");
+        let name = rest[..open].trim();
+        let close = open + matching_paren(&rest[open..]);
+        let params = &rest[open + 1..close];
+        let end = close + rest[close..].find(';').expect("end of declaration");
+        let ret = rest[close + 1..end].trim();
+        rest = &rest[end..];
+
+        let params: Vec<(&str, &str)> = split_top_level(params)
+            .into_iter()
+            .map(|param| {
+                let (name, ty) = param.split_once(':').expect("typed parameter");
+                (name.trim(), ty.trim())
+            })
+            .collect();
+        let decl: Vec<String> = params.iter().map(|(n, t)| format!("{n}: {t}")).collect();
+        let types: Vec<&str> = params.iter().map(|(_, t)| *t).collect();
+        let args: Vec<&str> = params.iter().map(|(n, _)| *n).collect();
+        out.push_str(&format!(
+            "#[allow(non_snake_case, dead_code, unused_variables, clippy::too_many_arguments)]\n\
+             pub(crate) unsafe fn {name}({decl}) {ret} {{\n{body}}}\n",
+            decl = decl.join(", "),
+            body = body(name, &types.join(", "), &args.join(", "), ret),
+        ));
+    }
+    out
+}
+
+/// Calls the symbol looked up from the runtime-loaded library.
+fn dlopen_body(name: &str, types: &str, args: &str, ret: &str) -> String {
+    format!(
+        "    type F = unsafe extern \"C\" fn({types}) {ret};\n    \
+             static SYMBOL: std::sync::OnceLock<F> = std::sync::OnceLock::new();\n    \
+             let f = *SYMBOL.get_or_init(|| unsafe {{ dynamic::symbol::<F>(\"{name}\") }});\n    \
+             unsafe {{ f({args}) }}\n"
+    )
+}
+
+/// Panics: there is no native library to call.
+fn stub_body(name: &str, _types: &str, _args: &str, _ret: &str) -> String {
+    format!("    stub::unavailable(\"{name}\")\n")
+}
+
+/// Returns the index of the parenthesis closing the one at index 0.
+fn matching_paren(s: &str) -> usize {
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return i;
+                }
+            }
+            _ => {}
+        }
+    }
+    panic!("unbalanced parentheses in FFI bindings");
+}
+
+/// Splits a parameter list at commas outside parentheses and generics.
+fn split_top_level(params: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    let mut prev = ' ';
+    for (i, c) in params.char_indices() {
+        match c {
+            '(' | '<' => depth += 1,
+            ')' => depth -= 1,
+            // `->` is not a closing bracket
+            '>' if prev != '-' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(&params[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+        prev = c;
+    }
+    parts.push(&params[start..]);
+    parts.into_iter().filter(|p| !p.trim().is_empty()).collect()
+}
@@ -28,7 +28,7 @@ fn main() -> SzResult<()> {
     // Step 1: Check current default configuration (if any)
     println!("1. Checking for existing default configuration...");
     let current_default = config_manager.get_default_config_id()?;
-    if current_default > 0 {
+    if current_default.get() > 0 {
         println!("   Current default configuration ID: {}", current_default);
     } else {
         println!("   No default configuration currently set");
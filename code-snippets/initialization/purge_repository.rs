@@ -67,7 +67,7 @@ fn main() -> SzResult<()> {
     let diagnostic = environment.get_diagnostic()?;
 
     // Perform the purge operation
-    diagnostic.purge_repository()?;
+    diagnostic.purge_repository(PurgeConfirmation::I_UNDERSTAND_THIS_DELETES_ALL_DATA)?;
 
     println!("✅ Repository purge completed successfully!");
     println!("All data has been removed from the Senzing datastore.");
@@ -15,6 +15,7 @@ use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::thread;
 use std::time::{Duration, Instant};
+use sz_rust_sdk::models::{RedoReason, RedoRecord};
 use sz_rust_sdk::prelude::*;
 
 /// Initialize the Senzing environment for the redo with info continuous example
@@ -199,9 +200,7 @@ fn process_redo_records_with_info(
                     // makes Senzing return the resolution info document that
                     // this "with info" example analyzes below; without it, the
                     // non-info entry point is used and no info is returned.
-                    match engine
-                        .process_redo_record(&redo_record, Some(SzFlags::WITH_INFO))
-                    {
+                    match engine.process_redo_record(&redo_record, Some(SzFlags::WITH_INFO)) {
                         Ok(result) => {
                             println!(
                                 "✅ Processed redo record {}/{}: {} bytes result",
@@ -241,50 +240,42 @@ fn process_redo_records_with_info(
 
 /// Analyze redo record JSON for detailed information
 fn analyze_redo_record(redo_record: &str, info_tracker: &mut RedoProcessingInfo) -> SzResult<()> {
-    // Parse the redo record JSON to extract detailed information
-    match serde_json::from_str::<Value>(redo_record) {
-        Ok(redo_data) => {
-            // Extract data source information
-            if let Some(data_source) = redo_data.get("DATA_SOURCE").and_then(|v| v.as_str()) {
-                *info_tracker
-                    .by_data_source
-                    .entry(data_source.to_string())
-                    .or_insert(0) += 1;
-                println!("📝 Redo record from data source: {}", data_source);
-            }
-
-            // Extract entity information if available
-            if let Some(entity_id) = redo_data.get("ENTITY_ID").and_then(|v| v.as_i64()) {
-                info_tracker.total_entities_affected += 1;
-                println!("🔗 Entity affected: {}", entity_id);
-            }
-
-            // Extract record information
-            if let Some(record_id) = redo_data.get("RECORD_ID").and_then(|v| v.as_str()) {
-                println!("📄 Record ID: {}", record_id);
-            }
-
-            // Extract redo reason/type
-            if let Some(redo_reason) = redo_data
-                .get("REDO_REASON")
-                .or_else(|| redo_data.get("REASON"))
-                .and_then(|v| v.as_str())
-            {
-                *info_tracker
-                    .by_entity_type
-                    .entry(redo_reason.to_string())
-                    .or_insert(0) += 1;
-                println!("🔄 Redo reason: {}", redo_reason);
-            }
-
-            Ok(())
-        }
+    let redo = match RedoRecord::from_json(redo_record) {
+        Ok(redo) => redo,
         Err(e) => {
-            println!("⚠️ Could not parse redo record JSON (may be normal): {}", e);
-            // Don't fail on JSON parsing errors - redo records may have different formats
-            Ok(())
+            println!("⚠️ Could not parse redo record JSON: {}", e);
+            return Ok(());
         }
+    };
+
+    if let Some((data_source, record_id)) = redo.record_key() {
+        *info_tracker
+            .by_data_source
+            .entry(data_source.to_string())
+            .or_insert(0) += 1;
+        println!("📝 Redo record {}:{}", data_source, record_id);
     }
+
+    if let Some(entity_id) = redo.entity_id {
+        info_tracker.total_entities_affected += 1;
+        println!("🔗 Entity affected: {}", entity_id);
+    }
+
+    let reason = match &redo.reason {
+        RedoReason::FeatureWentGeneric { .. } => "feature went generic".to_string(),
+        RedoReason::DeferredDelete => "deferred delete".to_string(),
+        RedoReason::Umf(name) => format!("UMF {}", name),
+        RedoReason::Other(reason) => reason.clone(),
+        _ => "unknown".to_string(),
+    };
+    println!(
+        "🔄 Redo reason: {} (reevaluates: {})",
+        reason,
+        redo.requires_reevaluation()
+    );
+    *info_tracker.by_entity_type.entry(reason).or_insert(0) += 1;
+
+    Ok(())
 }
 
 /// Analyze processing result for additional insights
@@ -79,7 +79,11 @@ fn perform_why_search_analysis(engine: &Box<dyn SzEngine>) -> SzResult<()> {
 
         // Analyze each entity
         for (index, entity) in resolved_entities.iter().enumerate() {
-            if let Some(entity_id) = entity.get("ENTITY_ID").and_then(|v| v.as_i64()) {
+            if let Some(entity_id) = entity
+                .get("ENTITY_ID")
+                .and_then(|v| v.as_i64())
+                .map(EntityId)
+            {
                 println!(
                     "\n--- Analysis for Entity {} (ID: {}) ---",
                     index + 1,
@@ -100,7 +104,7 @@ fn perform_why_search_analysis(engine: &Box<dyn SzEngine>) -> SzResult<()> {
 /// Analyze why a specific entity matched the search criteria
 fn analyze_why_entity_matched(
     engine: &Box<dyn SzEngine>,
-    entity_id: i64,
+    entity_id: EntityId,
     search_attributes: &str,
 ) -> SzResult<()> {
     // Get why records information
@@ -208,7 +212,7 @@ fn display_match_analysis(match_info: &Value) {
 }
 
 /// Get additional why records analysis
-fn get_why_records_analysis(engine: &Box<dyn SzEngine>, entity_id: i64) -> SzResult<()> {
+fn get_why_records_analysis(engine: &Box<dyn SzEngine>, entity_id: EntityId) -> SzResult<()> {
     println!("\n--- Record-level Analysis ---");
 
     // Get entity details to analyze individual records
@@ -69,10 +69,12 @@ fn main() -> SzResult<()> {
 
     let entity_id1 = info1_json["AFFECTED_ENTITIES"][0]["ENTITY_ID"]
         .as_i64()
+        .map(EntityId)
         .ok_or_else(|| SzError::bad_input("Could not extract entity ID from record 1"))?;
 
     let entity_id2 = info2_json["AFFECTED_ENTITIES"][0]["ENTITY_ID"]
         .as_i64()
+        .map(EntityId)
         .ok_or_else(|| SzError::bad_input("Could not extract entity ID from record 2"))?;
 
     println!("\n2. Entity IDs after loading:");
@@ -217,7 +217,7 @@ fn main() -> SzResult<()> {
 }
 
 /// Extract entity ID from load/delete info JSON
-fn extract_entity_id(info_json: &str, record_description: &str) -> SzResult<i64> {
+fn extract_entity_id(info_json: &str, record_description: &str) -> SzResult<EntityId> {
     let info: Value = serde_json::from_str(info_json).map_err(|e| {
         SzError::bad_input(format!(
             "Failed to parse info for {}: {}",
@@ -234,6 +234,7 @@ fn extract_entity_id(info_json: &str, record_description: &str) -> SzResult<i64>
                 .get("ENTITY_ID")?
                 .as_i64()
         })
+        .map(EntityId)
         .ok_or_else(|| {
             SzError::bad_input(format!(
                 "Could not extract entity ID from {}",
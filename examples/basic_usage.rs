@@ -15,13 +15,14 @@ fn main() -> SzResult<()> {
     }
 
     // Find path between entities
-    match engine.find_path_by_entity_id(1, 2, 3, None, None, None) {
+    match engine.find_path_by_entity_id(EntityId(1), EntityId(2), 3, None, None, None) {
         Ok(path_result) => println!("Path: {path_result}"),
         Err(e) => println!("Find path failed: {e} (expected - no entities loaded)"),
     }
 
     // Network analysis
-    match engine.find_network_by_entity_id(&[1, 2, 3], 2, 1, 10, None) {
+    match engine.find_network_by_entity_id(&[EntityId(1), EntityId(2), EntityId(3)], 2, 1, 10, None)
+    {
         Ok(network_result) => println!("Network: {network_result}"),
         Err(e) => println!("Network analysis failed: {e} (expected - no entities loaded)"),
     }
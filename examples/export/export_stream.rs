@@ -0,0 +1,51 @@
+//! Streaming entity export
+//!
+//! Shows `SzExporter`, which runs the export fetch loop on a background thread
+//! and yields typed entities through an iterator. The fetch thread stays a
+//! bounded number of entities ahead, so writing output and fetching overlap.
+//!
+//! Run with: cargo run --example export_stream
+
+use std::io::Write;
+use sz_rust_sdk::export::SzExporter;
+use sz_rust_sdk::helpers::ExampleEnvironment;
+use sz_rust_sdk::prelude::*;
+
+fn main() -> SzResult<()> {
+    let env = ExampleEnvironment::initialize("export-stream-example")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+
+    for i in 0..50 {
+        engine.add_record(
+            "TEST",
+            &format!("{}", 1000 + i),
+            &format!(
+                r#"{{"NAME_FULL":"Customer {}","PHONE_NUMBER":"555-{:04}"}}"#,
+                i % 40,
+                i % 40
+            ),
+            None,
+        )?;
+    }
+    println!("Loaded 50 records");
+
+    let exporter = SzExporter::new(env.clone()).with_prefetch(128);
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    let mut entities = 0;
+    for entity in exporter.export_entities_stream(None)? {
+        let entity = entity?.resolved_entity;
+        entities += 1;
+        let _ = writeln!(
+            out,
+            "entity {} ({} records)",
+            entity.entity_id,
+            entity.records.len()
+        );
+    }
+    println!("Exported {entities} entities");
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
@@ -0,0 +1,80 @@
+//! Profile Calls Example
+//!
+//! This example shows where engine call latency comes from: the native
+//! library or the SDK around it.
+//!
+//! ## Senzing Operations Demonstrated
+//!
+//! * Load synthetic records and run searches and entity reads
+//! * Read per-call timings with `SzDiagnostic::recent_call_timings`
+//! * Write `tracing` spans to a folded-stack file with `tracing-flame`
+//!
+//! ## Usage
+//!
+//! ```bash
+//! cargo run --example profile_calls --features profiling-tracing
+//! inferno-flamegraph < sz-calls.folded > sz-calls.svg
+//! ```
+
+use std::collections::BTreeMap;
+use std::time::Duration;
+use sz_rust_sdk::helpers::ExampleEnvironment;
+use sz_rust_sdk::prelude::*;
+use sz_rust_sdk::testing::datasets::DatasetSpec;
+use tracing_flame::FlameLayer;
+use tracing_subscriber::prelude::*;
+
+fn main() -> SzResult<()> {
+    let (flame_layer, flush_guard) = FlameLayer::with_file("sz-calls.folded")
+        .map_err(|e| SzError::configuration(format!("Cannot create sz-calls.folded: {e}")))?;
+    tracing_subscriber::registry().with(flame_layer).init();
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-profile-calls")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+
+    let dataset = DatasetSpec::new(7).with_people(200).generate()?;
+    for record in dataset.records() {
+        engine.add_record(
+            &record.data_source,
+            &record.record_id,
+            &record.definition,
+            None,
+        )?;
+    }
+    for record in dataset.records().iter().take(50) {
+        engine.search_by_attributes(&record.definition, None, None)?;
+        engine.get_entity(
+            EntityRef::Record {
+                data_source: &record.data_source,
+                record_id: &record.record_id,
+            },
+            None,
+        )?;
+    }
+
+    // Sum the timings per operation
+    let mut totals: BTreeMap<&str, (u32, Duration, Duration)> = BTreeMap::new();
+    for timing in env.get_diagnostic()?.recent_call_timings() {
+        let entry = totals.entry(timing.operation).or_default();
+        entry.0 += 1;
+        entry.1 += timing.native;
+        entry.2 += timing.sdk();
+    }
+    println!(
+        "{:<24} {:>6} {:>14} {:>14}",
+        "operation", "calls", "native avg", "sdk avg"
+    );
+    for (operation, (calls, native, sdk)) in totals {
+        println!(
+            "{operation:<24} {calls:>6} {:>14?} {:>14?}",
+            native / calls,
+            sdk / calls
+        );
+    }
+
+    drop(flush_guard);
+    println!("\nWrote sz-calls.folded");
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)
+}
@@ -49,7 +49,7 @@ fn main() -> SzResult<()> {
                     .and_then(|re| re.get("ENTITY_ID"))
                     .and_then(|id| id.as_i64())
             {
-                entity_ids.push(entity_id);
+                entity_ids.push(EntityId(entity_id));
 
                 let entity_name = entity
                     .get("RESOLVED_ENTITY")
@@ -104,7 +104,7 @@ fn main() -> SzResult<()> {
 
         // Demonstrate why search with a hypothetical entity ID
         println!("\n2. Demonstrating why search with example entity ID:");
-        let example_entity_id = 1;
+        let example_entity_id = EntityId(1);
 
         match engine.why_search(
             search_criteria,
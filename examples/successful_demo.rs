@@ -34,7 +34,7 @@ fn main() -> SzResult<()> {
 
     // Test find path operation
     println!("🔗 Testing find path operation...");
-    match engine.find_path_by_entity_id(1, 2, 3, None, None, None) {
+    match engine.find_path_by_entity_id(EntityId(1), EntityId(2), 3, None, None, None) {
         Ok(path_result) => {
             println!("✅ Find path completed successfully");
             println!("   Path: {path_result}");
@@ -46,7 +46,8 @@ fn main() -> SzResult<()> {
 
     // Test network analysis
     println!("\n🕸️  Testing network analysis...");
-    match engine.find_network_by_entity_id(&[1, 2, 3], 2, 1, 10, None) {
+    match engine.find_network_by_entity_id(&[EntityId(1), EntityId(2), EntityId(3)], 2, 1, 10, None)
+    {
         Ok(network_result) => {
             println!("✅ Network analysis completed");
             println!("   Network: {network_result}");
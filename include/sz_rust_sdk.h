@@ -0,0 +1,73 @@
+/*
+ * C ABI of the Senzing Rust SDK (cargo feature `capi`).
+ *
+ * Link against the cdylib built with `cargo build --release --features capi`.
+ * See the `capi` module documentation for the calling conventions:
+ * functions return 0 on success or an error code, sz_capi_last_error()
+ * describes the last failure on the calling thread, and strings returned
+ * through out_* pointers are released with sz_capi_free_string().
+ *
+ * Flags arguments are SzFlags bits. Pass SZ_FLAGS_DEFAULT for the method's
+ * default flags; 0 is an explicit empty flag set, not the defaults.
+ */
+
+#ifndef SZ_RUST_SDK_H
+#define SZ_RUST_SDK_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Flags argument asking for the method's default flags. */
+#define SZ_FLAGS_DEFAULT UINT64_MAX
+
+/* An initialized environment and its engine. */
+typedef struct SzCapiEnvironment SzCapiEnvironment;
+
+int64_t sz_capi_environment_init(const char *module_name,
+                                 const char *settings,
+                                 bool verbose_logging,
+                                 SzCapiEnvironment **out_env);
+
+int64_t sz_capi_environment_destroy(SzCapiEnvironment *env);
+
+int64_t sz_capi_add_record(const SzCapiEnvironment *env,
+                           const char *data_source_code,
+                           const char *record_id,
+                           const char *record_definition,
+                           uint64_t flags,
+                           char **out_info);
+
+int64_t sz_capi_get_entity_by_record_id(const SzCapiEnvironment *env,
+                                        const char *data_source_code,
+                                        const char *record_id,
+                                        uint64_t flags,
+                                        char **out_json);
+
+int64_t sz_capi_search_by_attributes(const SzCapiEnvironment *env,
+                                     const char *attributes,
+                                     uint64_t flags,
+                                     char **out_json);
+
+int64_t sz_capi_load_file(const SzCapiEnvironment *env,
+                          const char *path,
+                          uint64_t *out_records_loaded);
+
+int64_t sz_capi_redo_drain(const SzCapiEnvironment *env,
+                           int64_t count_below,
+                           size_t max_workers,
+                           uint64_t *out_processed);
+
+const char *sz_capi_last_error(void);
+
+void sz_capi_free_string(char *value);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SZ_RUST_SDK_H */
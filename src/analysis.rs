@@ -0,0 +1,1158 @@
+//! Comparisons of engine responses.
+//!
+//! [`diff_entities`] compares two `get_entity` responses: the entities on
+//! either side of an unexpected merge, or one entity before and after a
+//! reevaluation. Features are only compared when the responses were
+//! requested with feature flags (e.g. `SzFlags::ENTITY_INCLUDE_ALL_FEATURES`),
+//! and relationships only with relation flags.
+//!
+//! [`membership_change`] compares just the record keys of an entity, as
+//! returned by `SzEngine::get_entity_record_keys`, and
+//! [`membership_moves`] follows records across several entities.
+//!
+//! [`why_pairs`] runs `why_records` over many record pairs in parallel, for
+//! batch match audits.
+//!
+//! [`sample_entities`] draws a stratified sample of entities from an export
+//! for manual review, and [`write_review_csv`] writes it as a compact file
+//! for reviewers to fill in.
+//!
+//! [`estimate_config_impact`] previews a sample of records under a candidate
+//! configuration and reports the features and candidate keys that would
+//! change, before the configuration is made the default.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::analysis::diff_entities;
+//! use sz_rust_sdk::prelude::*;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_diff_entities")?;
+//! let engine = env.get_engine()?;
+//! let flags = Some(SzFlags::ENTITY_DEFAULT_FLAGS | SzFlags::ENTITY_INCLUDE_ALL_FEATURES);
+//! let before = engine.get_entity(EntityRef::Id(EntityId(1)), flags)?;
+//! engine.reevaluate_entity(EntityId(1), None)?;
+//! let after = engine.get_entity(EntityRef::Id(EntityId(1)), flags)?;
+//!
+//! let diff = diff_entities(&before, &after)?;
+//! if !diff.is_empty() {
+//!     println!("{diff}");
+//! }
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::config_tools;
+use crate::core::SzEnvironmentCore;
+use crate::error::{SzError, SzResult};
+use crate::export;
+use crate::flags::SzFlags;
+use crate::models::{EntityRecord, ExportedEntity, RecordsExplanation, RelatedEntity};
+use crate::redact;
+use crate::report::{csv_field, flush, write_line};
+use crate::traits::{SzEngine, SzEnvironment};
+use crate::types::{ConfigId, EntityId, RecordKey};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fmt;
+use std::io::Write;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Differences between two entities.
+///
+/// "A" and "B" are the first and second responses passed to
+/// [`diff_entities`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityDiff {
+    /// Entity ID of A
+    pub entity_a: EntityId,
+    /// Entity ID of B
+    pub entity_b: EntityId,
+    /// Feature types whose values differ, ordered by feature type
+    pub features: Vec<FeatureDiff>,
+    /// Records in A but not in B
+    pub records_only_in_a: Vec<EntityRecord>,
+    /// Records in B but not in A
+    pub records_only_in_b: Vec<EntityRecord>,
+    /// Related entities that differ, ordered by related entity ID
+    pub relationships: Vec<RelationshipDiff>,
+}
+
+/// Values of one feature type that only one entity has.
+#[derive(Clone, PartialEq, Eq)]
+pub struct FeatureDiff {
+    /// Feature type code, e.g. `NAME` or `PHONE`
+    pub feature_type: String,
+    /// Feature descriptions only A has
+    pub only_in_a: Vec<String>,
+    /// Feature descriptions only B has
+    pub only_in_b: Vec<String>,
+}
+
+impl fmt::Debug for FeatureDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FeatureDiff")
+            .field("feature_type", &self.feature_type)
+            .field("only_in_a", &redact::each(&self.only_in_a))
+            .field("only_in_b", &redact::each(&self.only_in_b))
+            .finish()
+    }
+}
+
+/// A related entity that only one side has, or that both have with a
+/// different match level or match key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RelationshipDiff {
+    /// The related entity
+    pub entity_id: EntityId,
+    /// The relationship in A, if any
+    pub in_a: Option<RelatedEntity>,
+    /// The relationship in B, if any
+    pub in_b: Option<RelatedEntity>,
+}
+
+impl EntityDiff {
+    /// Returns whether the entities have the same features, records and
+    /// relationships. Entity IDs are not compared.
+    pub fn is_empty(&self) -> bool {
+        self.features.is_empty()
+            && self.records_only_in_a.is_empty()
+            && self.records_only_in_b.is_empty()
+            && self.relationships.is_empty()
+    }
+}
+
+/// Compares two `get_entity` responses.
+///
+/// Related entities are matched by entity ID; a relationship differs when
+/// its match level code or match key changed. A relationship between A and
+/// B themselves is left out, since it is not a difference between them.
+///
+/// # Errors
+///
+/// `SzError::Json` if either response is not a `get_entity` document.
+pub fn diff_entities(entity_a_json: &str, entity_b_json: &str) -> SzResult<EntityDiff> {
+    let a: ExportedEntity = serde_json::from_str(entity_a_json)?;
+    let b: ExportedEntity = serde_json::from_str(entity_b_json)?;
+    let document_a: Value = serde_json::from_str(entity_a_json)?;
+    let document_b: Value = serde_json::from_str(entity_b_json)?;
+    let features_a = features(&document_a["RESOLVED_ENTITY"]["FEATURES"]);
+    let features_b = features(&document_b["RESOLVED_ENTITY"]["FEATURES"]);
+
+    let entity_a = a.resolved_entity.entity_id;
+    let entity_b = b.resolved_entity.entity_id;
+
+    let record_key = |r: &EntityRecord| (r.data_source.clone(), r.record_id.clone());
+    let keys_a: BTreeSet<_> = a.resolved_entity.records.iter().map(record_key).collect();
+    let keys_b: BTreeSet<_> = b.resolved_entity.records.iter().map(record_key).collect();
+    let records_only_in_a = a
+        .resolved_entity
+        .records
+        .iter()
+        .filter(|r| !keys_b.contains(&record_key(r)))
+        .cloned()
+        .collect();
+    let records_only_in_b = b
+        .resolved_entity
+        .records
+        .iter()
+        .filter(|r| !keys_a.contains(&record_key(r)))
+        .cloned()
+        .collect();
+
+    let related = |entity: ExportedEntity| -> BTreeMap<EntityId, RelatedEntity> {
+        entity
+            .related_entities
+            .into_iter()
+            .filter(|r| r.entity_id != entity_a && r.entity_id != entity_b)
+            .map(|r| (r.entity_id, r))
+            .collect()
+    };
+    let mut related_a = related(a);
+    let mut related_b = related(b);
+    let related_ids: BTreeSet<EntityId> =
+        related_a.keys().chain(related_b.keys()).copied().collect();
+    let mut relationships = Vec::new();
+    for entity_id in related_ids {
+        let in_a = related_a.remove(&entity_id);
+        let in_b = related_b.remove(&entity_id);
+        let same = match (&in_a, &in_b) {
+            (Some(ra), Some(rb)) => {
+                ra.match_level_code == rb.match_level_code && ra.match_key == rb.match_key
+            }
+            _ => false,
+        };
+        if !same {
+            relationships.push(RelationshipDiff {
+                entity_id,
+                in_a,
+                in_b,
+            });
+        }
+    }
+
+    Ok(EntityDiff {
+        entity_a,
+        entity_b,
+        features: feature_diffs(&features_a, &features_b),
+        records_only_in_a,
+        records_only_in_b,
+        relationships,
+    })
+}
+
+type Features = BTreeMap<String, BTreeSet<String>>;
+
+/// Collects a `FEATURES` object as feature type → descriptions.
+fn features(features: &Value) -> Features {
+    let Some(features) = features.as_object() else {
+        return BTreeMap::new();
+    };
+    features
+        .iter()
+        .map(|(feature_type, values)| {
+            let descriptions = values
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|value| value["FEAT_DESC"].as_str())
+                .map(str::to_string)
+                .collect();
+            (feature_type.clone(), descriptions)
+        })
+        .collect()
+}
+
+/// The feature types whose descriptions differ, ordered by feature type.
+fn feature_diffs(features_a: &Features, features_b: &Features) -> Vec<FeatureDiff> {
+    let feature_types: BTreeSet<&String> = features_a.keys().chain(features_b.keys()).collect();
+    let empty = BTreeSet::new();
+    let mut diffs = Vec::new();
+    for feature_type in feature_types {
+        let values_a = features_a.get(feature_type).unwrap_or(&empty);
+        let values_b = features_b.get(feature_type).unwrap_or(&empty);
+        if values_a != values_b {
+            diffs.push(FeatureDiff {
+                feature_type: feature_type.clone(),
+                only_in_a: values_a.difference(values_b).cloned().collect(),
+                only_in_b: values_b.difference(values_a).cloned().collect(),
+            });
+        }
+    }
+    diffs
+}
+
+/// Records that joined or left an entity between two points in time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MembershipChange {
+    /// Keys present only in the later set, in key order
+    pub added: BTreeSet<RecordKey>,
+    /// Keys present only in the earlier set, in key order
+    pub removed: BTreeSet<RecordKey>,
+}
+
+impl MembershipChange {
+    /// Returns whether the entity has the same records.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// Compares the record keys of an entity taken at two points in time.
+pub fn membership_change(
+    before: &HashSet<RecordKey>,
+    after: &HashSet<RecordKey>,
+) -> MembershipChange {
+    MembershipChange {
+        added: after.difference(before).cloned().collect(),
+        removed: before.difference(after).cloned().collect(),
+    }
+}
+
+/// A record that resolves to a different entity than before.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordMove {
+    /// The record
+    pub record: RecordKey,
+    /// Entity the record was in, if it was in one of the earlier entities
+    pub from: Option<EntityId>,
+    /// Entity the record is in, if it is in one of the later entities
+    pub to: Option<EntityId>,
+}
+
+/// Compares the record keys of several entities taken at two points in time,
+/// e.g. the entities affected by a load before and after it, and returns the
+/// records whose entity changed, in key order.
+///
+/// A record missing on one side has `None` there: it was added or deleted,
+/// or resolves to an entity that was not looked up.
+pub fn membership_moves(
+    before: &BTreeMap<EntityId, HashSet<RecordKey>>,
+    after: &BTreeMap<EntityId, HashSet<RecordKey>>,
+) -> Vec<RecordMove> {
+    let owners = |entities: &BTreeMap<EntityId, HashSet<RecordKey>>| {
+        let mut owners: BTreeMap<RecordKey, EntityId> = BTreeMap::new();
+        for (entity_id, keys) in entities {
+            for key in keys {
+                owners.insert(key.clone(), *entity_id);
+            }
+        }
+        owners
+    };
+    let owners_before = owners(before);
+    let owners_after = owners(after);
+    let records: BTreeSet<&RecordKey> = owners_before.keys().chain(owners_after.keys()).collect();
+    records
+        .into_iter()
+        .filter_map(|record| {
+            let from = owners_before.get(record).copied();
+            let to = owners_after.get(record).copied();
+            (from != to).then(|| RecordMove {
+                record: record.clone(),
+                from,
+                to,
+            })
+        })
+        .collect()
+}
+
+/// The `why_records` result for one pair passed to [`why_pairs`].
+#[derive(Debug)]
+pub struct PairExplanation {
+    /// First record
+    pub record_1: RecordKey,
+    /// Second record
+    pub record_2: RecordKey,
+    /// Why the records resolved together or not, or why the call failed
+    pub result: SzResult<RecordsExplanation>,
+}
+
+/// Runs `why_records` with its default flags over `pairs` of record keys,
+/// on up to `concurrency` threads.
+///
+/// Results come back in the order of `pairs`. A failing pair, e.g. one with
+/// a missing record, does not stop the others; its error is in its
+/// [`PairExplanation::result`].
+///
+/// # Example
+///
+/// ```no_run
+/// # use sz_rust_sdk::helpers::ExampleEnvironment;
+/// use sz_rust_sdk::analysis::why_pairs;
+/// use sz_rust_sdk::prelude::*;
+///
+/// # let env = ExampleEnvironment::initialize("doctest_why_pairs")?;
+/// let engine = env.get_engine()?;
+/// let key = |id: &str| RecordKey::new("CUSTOMERS", id);
+/// let pairs = [(key("1001")?, key("1002")?), (key("1001")?, key("1003")?)];
+/// for pair in why_pairs(engine.as_ref(), &pairs, 4) {
+///     match pair.result {
+///         Ok(why) => println!("{} {}: {:?}", pair.record_1, pair.record_2, why.why_key),
+///         Err(e) => eprintln!("{} {}: {e}", pair.record_1, pair.record_2),
+///     }
+/// }
+/// # Ok::<(), SzError>(())
+/// ```
+pub fn why_pairs(
+    engine: &dyn SzEngine,
+    pairs: &[(RecordKey, RecordKey)],
+    concurrency: usize,
+) -> Vec<PairExplanation> {
+    type Slot = Mutex<Option<SzResult<RecordsExplanation>>>;
+    let results: Vec<Slot> = pairs.iter().map(|_| Mutex::new(None)).collect();
+    let next = AtomicUsize::new(0);
+
+    std::thread::scope(|scope| {
+        for _ in 0..concurrency.max(1).min(pairs.len()) {
+            scope.spawn(|| {
+                loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some((record_1, record_2)) = pairs.get(index) else {
+                        break;
+                    };
+                    let result = engine
+                        .why_records(
+                            record_1.data_source.as_str(),
+                            &record_1.record_id,
+                            record_2.data_source.as_str(),
+                            &record_2.record_id,
+                            None,
+                        )
+                        .and_then(|json| RecordsExplanation::from_json(&json));
+                    let mut slot = match results[index].lock() {
+                        Ok(guard) => guard,
+                        Err(poisoned) => poisoned.into_inner(),
+                    };
+                    *slot = Some(result);
+                }
+            });
+        }
+    });
+
+    pairs
+        .iter()
+        .zip(results)
+        .map(|((record_1, record_2), slot)| {
+            let result = match slot.into_inner() {
+                Ok(result) => result,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            PairExplanation {
+                record_1: record_1.clone(),
+                record_2: record_2.clone(),
+                result: result.unwrap_or_else(|| {
+                    Err(crate::error::SzError::unknown("why_records was not run"))
+                }),
+            }
+        })
+        .collect()
+}
+
+/// How [`sample_entities`] groups entities before sampling.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SampleStrategy {
+    /// One group: every entity is equally likely to be drawn
+    #[default]
+    Uniform,
+    /// By record count: `1`, `2`, `3-5`, `6-10`, `11-100` and `101+`
+    ByEntitySize,
+    /// By the data sources of the entity's records, e.g. `CRM+WEB`
+    ByDataSource,
+    /// By the match level code of the entity's strongest relationship, or
+    /// `NONE` for entities without relationships
+    ByMatchLevel,
+}
+
+/// Columns of the file written by [`write_review_csv`]. `verdict` and
+/// `notes` are left empty for the reviewer.
+pub const REVIEW_COLUMNS: [&str; 8] = [
+    "entity_id",
+    "stratum",
+    "weight",
+    "entity_name",
+    "record_count",
+    "records",
+    "verdict",
+    "notes",
+];
+
+/// An entity drawn by [`sample_entities`].
+#[derive(Clone, PartialEq)]
+pub struct SampledEntity {
+    /// The entity
+    pub entity_id: EntityId,
+    /// Best name of the entity
+    pub entity_name: Option<String>,
+    /// Records of the entity, with the match key that brought each in
+    pub records: Vec<EntityRecord>,
+    /// Group the entity was drawn from
+    pub stratum: String,
+    /// Entities in the group per entity drawn from it; weighting review
+    /// outcomes by it estimates rates over the whole repository
+    pub weight: f64,
+}
+
+impl fmt::Debug for SampledEntity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SampledEntity")
+            .field("entity_id", &self.entity_id)
+            .field("entity_name", &redact::option(&self.entity_name))
+            .field("records", &self.records)
+            .field("stratum", &self.stratum)
+            .field("weight", &self.weight)
+            .finish()
+    }
+}
+
+/// Draws up to `n` entities from a complete export on `engine`, grouped by
+/// `strategy`.
+///
+/// The sample is split evenly across the groups, so small groups such as
+/// very large entities are reviewed as often as common ones; a group with
+/// fewer entities than its share gives the rest to the other groups. Each
+/// entity's [`weight`](SampledEntity::weight) undoes that bias when review
+/// outcomes are totalled.
+///
+/// Entities are drawn by a fixed hash of their entity ID, so the same
+/// repository always gives the same sample. The result is ordered by group,
+/// then by draw order.
+///
+/// # Errors
+///
+/// Any error from the export.
+///
+/// # Example
+///
+/// ```no_run
+/// # use sz_rust_sdk::helpers::ExampleEnvironment;
+/// use sz_rust_sdk::analysis::{self, SampleStrategy};
+/// use sz_rust_sdk::prelude::*;
+///
+/// # let env = ExampleEnvironment::initialize("doctest_sample_entities")?;
+/// let engine = env.get_engine()?;
+/// let sample = analysis::sample_entities(&*engine, SampleStrategy::ByEntitySize, 200)?;
+/// let file = std::fs::File::create("/tmp/review.csv")
+///     .map_err(|e| SzError::bad_input(e.to_string()))?;
+/// analysis::write_review_csv(&sample, std::io::BufWriter::new(file))?;
+/// # Ok::<(), SzError>(())
+/// ```
+pub fn sample_entities(
+    engine: &dyn SzEngine,
+    strategy: SampleStrategy,
+    n: usize,
+) -> SzResult<Vec<SampledEntity>> {
+    let mut flags = SzFlags::EXPORT_INCLUDE_ALL_ENTITIES
+        | SzFlags::ENTITY_INCLUDE_ENTITY_NAME
+        | SzFlags::ENTITY_INCLUDE_RECORD_DATA
+        | SzFlags::ENTITY_INCLUDE_RECORD_MATCHING_INFO;
+    if strategy == SampleStrategy::ByMatchLevel {
+        flags |= SzFlags::ENTITY_INCLUDE_ALL_RELATIONS;
+    }
+    let mut strata = BTreeMap::new();
+    export::for_each_entity(engine, flags, |entity| {
+        let stratum: &mut Stratum = strata.entry(stratum(strategy, &entity)).or_default();
+        stratum.offer(entity, n);
+        Ok(())
+    })?;
+    Ok(draw(strata, n))
+}
+
+/// Entities of one group seen so far, keeping the `n` with the lowest
+/// draw priority.
+#[derive(Default)]
+struct Stratum {
+    population: u64,
+    kept: BTreeMap<u64, ExportedEntity>,
+}
+
+impl Stratum {
+    fn offer(&mut self, entity: ExportedEntity, n: usize) {
+        self.population += 1;
+        self.kept
+            .insert(draw_priority(entity.resolved_entity.entity_id), entity);
+        if self.kept.len() > n {
+            self.kept.pop_last();
+        }
+    }
+}
+
+/// Splits `n` across the groups, smallest group first, and takes each
+/// group's share in draw order.
+fn draw(strata: BTreeMap<String, Stratum>, n: usize) -> Vec<SampledEntity> {
+    let mut by_size: Vec<(String, Stratum)> = strata.into_iter().collect();
+    by_size.sort_by_key(|(_, stratum)| stratum.kept.len());
+    let mut remaining = n;
+    let groups = by_size.len();
+    let mut sample = Vec::new();
+    for (index, (name, stratum)) in by_size.into_iter().enumerate() {
+        let take = (remaining / (groups - index)).min(stratum.kept.len());
+        remaining -= take;
+        let weight = stratum.population as f64 / take.max(1) as f64;
+        sample.extend(stratum.kept.into_values().take(take).map(|entity| {
+            let entity = entity.resolved_entity;
+            SampledEntity {
+                entity_id: entity.entity_id,
+                entity_name: entity.entity_name,
+                records: entity.records,
+                stratum: name.clone(),
+                weight,
+            }
+        }));
+    }
+    sample.sort_by(|a, b| {
+        a.stratum
+            .cmp(&b.stratum)
+            .then_with(|| draw_priority(a.entity_id).cmp(&draw_priority(b.entity_id)))
+    });
+    sample
+}
+
+fn stratum(strategy: SampleStrategy, entity: &ExportedEntity) -> String {
+    match strategy {
+        SampleStrategy::Uniform => "ALL".to_string(),
+        SampleStrategy::ByEntitySize => match entity.resolved_entity.records.len() {
+            0 | 1 => "1",
+            2 => "2",
+            3..=5 => "3-5",
+            6..=10 => "6-10",
+            11..=100 => "11-100",
+            _ => "101+",
+        }
+        .to_string(),
+        SampleStrategy::ByDataSource => {
+            let sources: BTreeSet<&str> = entity
+                .resolved_entity
+                .records
+                .iter()
+                .map(|record| record.data_source.as_str())
+                .collect();
+            sources.into_iter().collect::<Vec<_>>().join("+")
+        }
+        SampleStrategy::ByMatchLevel => entity
+            .related_entities
+            .iter()
+            .filter(|related| related.match_level_code.is_some())
+            .min_by_key(|related| related.match_level.unwrap_or(i64::MAX))
+            .and_then(|related| related.match_level_code.clone())
+            .unwrap_or_else(|| "NONE".to_string()),
+    }
+}
+
+/// Order in which entities are drawn: SplitMix64 of the entity ID, which
+/// is a bijection, so no two entities tie.
+fn draw_priority(entity_id: EntityId) -> u64 {
+    let mut z = (entity_id.0 as u64).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Writes a sample as CSV with the columns in [`REVIEW_COLUMNS`], one row
+/// per entity. Records are listed as `DATA_SOURCE:RECORD_ID`, separated by
+/// spaces. Returns the number of rows written.
+///
+/// # Errors
+///
+/// `SzError::BadInput` if writing fails.
+pub fn write_review_csv<W: Write>(sample: &[SampledEntity], mut writer: W) -> SzResult<usize> {
+    write_line(&mut writer, &REVIEW_COLUMNS.join(","))?;
+    for entity in sample {
+        let records: Vec<String> = entity
+            .records
+            .iter()
+            .map(|record| format!("{}:{}", record.data_source, record.record_id))
+            .collect();
+        let fields = [
+            entity.entity_id.to_string(),
+            entity.stratum.clone(),
+            format!("{:.2}", entity.weight),
+            entity.entity_name.clone().unwrap_or_default(),
+            entity.records.len().to_string(),
+            records.join(" "),
+            String::new(),
+            String::new(),
+        ];
+        let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        write_line(&mut writer, &row.join(","))?;
+    }
+    flush(&mut writer)?;
+    Ok(sample.len())
+}
+
+/// How a candidate configuration changes the features of a sample of
+/// records, from [`estimate_config_impact`].
+///
+/// In each [`FeatureDiff`], "A" is the current configuration and "B" the
+/// candidate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfigImpact {
+    /// Configuration that was active, and is active again afterwards
+    pub current_config_id: ConfigId,
+    /// ID the candidate configuration was registered under
+    pub candidate_config_id: ConfigId,
+    /// Number of records previewed under both configurations
+    pub records_sampled: usize,
+    /// Sampled records whose features differ, in sample order
+    pub records: Vec<RecordImpact>,
+    /// Number of changed records per differing feature type
+    pub feature_types: BTreeMap<String, usize>,
+}
+
+/// Features of one sampled record that differ between the current and the
+/// candidate configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordImpact {
+    /// The record
+    pub record: RecordKey,
+    /// Differing feature types that are not used for candidate selection
+    pub features: Vec<FeatureDiff>,
+    /// Differing feature types used for candidate selection by either
+    /// configuration. A change here changes which entities the record is
+    /// compared with, so it may resolve differently.
+    pub candidate_keys: Vec<FeatureDiff>,
+}
+
+impl ConfigImpact {
+    /// Returns whether no sampled record's features differ.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Number of sampled records whose candidate keys differ.
+    pub fn candidate_key_changes(&self) -> usize {
+        self.records
+            .iter()
+            .filter(|record| !record.candidate_keys.is_empty())
+            .count()
+    }
+}
+
+/// Estimates how `new_config_json` would change the features and candidate
+/// keys of the records in the repository, before it becomes the default.
+///
+/// Up to `sample_size` entities are drawn from an export, as
+/// [`sample_entities`] draws them, and the first record of each is
+/// previewed with `get_record_preview` under the active configuration. The
+/// candidate is then registered, but not made the default, the environment
+/// is reinitialized with it to preview the same records again, and the
+/// previous configuration is reinitialized whatever the outcome.
+///
+/// While the candidate is active, every engine of the process uses it; run
+/// the estimate from a process that is not loading or serving requests.
+/// The registered candidate stays in the registry; pass
+/// [`candidate_config_id`](ConfigImpact::candidate_config_id) to
+/// `set_default_config_id` to adopt it as estimated.
+///
+/// # Errors
+///
+/// * `SzError::BadInput` - A sampled record has no stored `JSON_DATA`
+/// * `SzError::Json` - A configuration is not valid JSON
+/// * Any error from the export, the previews, registering the candidate or
+///   reinitializing
+///
+/// # Example
+///
+/// ```no_run
+/// # use sz_rust_sdk::helpers::ExampleEnvironment;
+/// use sz_rust_sdk::analysis::estimate_config_impact;
+/// use sz_rust_sdk::prelude::*;
+///
+/// # let env = ExampleEnvironment::initialize("doctest_estimate_config_impact")?;
+/// let candidate = std::fs::read_to_string("candidate_config.json")
+///     .map_err(|e| SzError::bad_input(e.to_string()))?;
+/// let impact = estimate_config_impact(&env, &candidate, 500)?;
+/// print!("{impact}");
+/// if impact.candidate_key_changes() == 0 {
+///     env.get_config_manager()?
+///         .set_default_config_id(impact.candidate_config_id)?;
+/// }
+/// # Ok::<(), SzError>(())
+/// ```
+pub fn estimate_config_impact(
+    env: &SzEnvironmentCore,
+    new_config_json: &str,
+    sample_size: usize,
+) -> SzResult<ConfigImpact> {
+    let engine = env.get_engine()?;
+    let config_mgr = env.get_config_manager()?;
+    let current_config_id = env.get_active_config_id()?;
+    let current_config = config_mgr
+        .create_config_from_id(current_config_id)?
+        .export()?;
+    let mut candidate_types = config_tools::candidate_feature_types(&current_config)?;
+    candidate_types.extend(config_tools::candidate_feature_types(new_config_json)?);
+
+    let sample = sample_records(engine.as_ref(), sample_size)?;
+    let current = preview_all(engine.as_ref(), &sample)?;
+    let candidate_config_id =
+        config_mgr.register_config(new_config_json, Some("Configuration impact estimate"))?;
+    env.reinitialize(candidate_config_id)?;
+    let candidate = preview_all(engine.as_ref(), &sample);
+    env.reinitialize(current_config_id)?;
+    let candidate = candidate?;
+
+    let mut impact = ConfigImpact {
+        current_config_id,
+        candidate_config_id,
+        records_sampled: sample.len(),
+        records: Vec::new(),
+        feature_types: BTreeMap::new(),
+    };
+    for (((record, _), current), candidate) in sample.into_iter().zip(current).zip(candidate) {
+        if let Some(record) = record_impact(record, &current, &candidate, &candidate_types) {
+            for diff in record.features.iter().chain(&record.candidate_keys) {
+                *impact
+                    .feature_types
+                    .entry(diff.feature_type.clone())
+                    .or_default() += 1;
+            }
+            impact.records.push(record);
+        }
+    }
+    Ok(impact)
+}
+
+/// The stored definitions of the first record of up to `n` entities, drawn
+/// by [`draw_priority`].
+fn sample_records(engine: &dyn SzEngine, n: usize) -> SzResult<Vec<(RecordKey, String)>> {
+    let mut sample = Stratum::default();
+    export::for_each_entity(
+        engine,
+        SzFlags::EXPORT_INCLUDE_ALL_ENTITIES | SzFlags::ENTITY_INCLUDE_RECORD_DATA,
+        |entity| {
+            sample.offer(entity, n);
+            Ok(())
+        },
+    )?;
+    let mut records = Vec::new();
+    for entity in sample.kept.into_values() {
+        let Some(record) = entity.resolved_entity.records.into_iter().next() else {
+            continue;
+        };
+        let key = RecordKey::new(&record.data_source, &record.record_id)?;
+        let stored: Value = serde_json::from_str(&engine.get_record(
+            &record.data_source,
+            &record.record_id,
+            Some(SzFlags::ENTITY_INCLUDE_RECORD_JSON_DATA),
+        )?)?;
+        let Some(definition) = stored.get("JSON_DATA") else {
+            return Err(SzError::bad_input(format!(
+                "Record {key} has no stored JSON_DATA"
+            )));
+        };
+        records.push((key, definition.to_string()));
+    }
+    Ok(records)
+}
+
+/// Previews each sampled record, including internal features such as
+/// candidate keys.
+fn preview_all(engine: &dyn SzEngine, sample: &[(RecordKey, String)]) -> SzResult<Vec<Value>> {
+    let flags =
+        SzFlags::ENTITY_INCLUDE_RECORD_FEATURE_DETAILS | SzFlags::ENTITY_INCLUDE_INTERNAL_FEATURES;
+    sample
+        .iter()
+        .map(|(_, definition)| {
+            let preview = engine.get_record_preview(definition, Some(flags))?;
+            Ok(serde_json::from_str(&preview)?)
+        })
+        .collect()
+}
+
+/// Compares the `FEATURES` of two previews of `record`, or `None` if they
+/// have the same features.
+fn record_impact(
+    record: RecordKey,
+    current: &Value,
+    candidate: &Value,
+    candidate_types: &BTreeSet<String>,
+) -> Option<RecordImpact> {
+    let diffs = feature_diffs(
+        &features(&current["FEATURES"]),
+        &features(&candidate["FEATURES"]),
+    );
+    if diffs.is_empty() {
+        return None;
+    }
+    let (candidate_keys, features) = diffs
+        .into_iter()
+        .partition(|diff| candidate_types.contains(&diff.feature_type));
+    Some(RecordImpact {
+        record,
+        features,
+        candidate_keys,
+    })
+}
+
+impl fmt::Display for EntityDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "entity {} vs entity {}", self.entity_a, self.entity_b)?;
+        if self.is_empty() {
+            return writeln!(f, "  no differences");
+        }
+        for feature in &self.features {
+            writeln!(f, "  feature {}:", feature.feature_type)?;
+            for value in &feature.only_in_a {
+                writeln!(f, "    - {value}")?;
+            }
+            for value in &feature.only_in_b {
+                writeln!(f, "    + {value}")?;
+            }
+        }
+        if !self.records_only_in_a.is_empty() || !self.records_only_in_b.is_empty() {
+            writeln!(f, "  records:")?;
+            for record in &self.records_only_in_a {
+                writeln!(f, "    - {}:{}", record.data_source, record.record_id)?;
+            }
+            for record in &self.records_only_in_b {
+                writeln!(f, "    + {}:{}", record.data_source, record.record_id)?;
+            }
+        }
+        if !self.relationships.is_empty() {
+            writeln!(f, "  relationships:")?;
+            let describe = |related: &RelatedEntity| {
+                format!(
+                    "{} {}",
+                    related.match_level_code.as_deref().unwrap_or("?"),
+                    related.match_key.as_deref().unwrap_or("")
+                )
+            };
+            for relationship in &self.relationships {
+                let id = relationship.entity_id;
+                match (&relationship.in_a, &relationship.in_b) {
+                    (Some(a), Some(b)) => writeln!(
+                        f,
+                        "    ~ entity {id}: {} -> {}",
+                        describe(a).trim_end(),
+                        describe(b).trim_end()
+                    )?,
+                    (Some(a), None) => {
+                        writeln!(f, "    - entity {id}: {}", describe(a).trim_end())?
+                    }
+                    (None, Some(b)) => {
+                        writeln!(f, "    + entity {id}: {}", describe(b).trim_end())?
+                    }
+                    (None, None) => {}
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ConfigImpact {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "config {} -> {}: {} of {} sampled records change, {} in candidate keys",
+            self.current_config_id,
+            self.candidate_config_id,
+            self.records.len(),
+            self.records_sampled,
+            self.candidate_key_changes()
+        )?;
+        for (feature_type, records) in &self.feature_types {
+            writeln!(f, "  {feature_type}: {records} records")?;
+        }
+        for record in &self.records {
+            writeln!(f, "  record {}:", record.record)?;
+            let kinds = [
+                ("candidate key", &record.candidate_keys),
+                ("feature", &record.features),
+            ];
+            for (kind, diffs) in kinds {
+                for diff in diffs {
+                    writeln!(f, "    {kind} {}:", diff.feature_type)?;
+                    for value in &diff.only_in_a {
+                        writeln!(f, "      - {value}")?;
+                    }
+                    for value in &diff.only_in_b {
+                        writeln!(f, "      + {value}")?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BEFORE: &str = r#"{
+        "RESOLVED_ENTITY": {
+            "ENTITY_ID": 1,
+            "FEATURES": {
+                "NAME": [{"FEAT_DESC": "Robert Smith"}],
+                "PHONE": [{"FEAT_DESC": "555-1212"}]
+            },
+            "RECORDS": [
+                {"DATA_SOURCE": "CUSTOMERS", "RECORD_ID": "1001"},
+                {"DATA_SOURCE": "CUSTOMERS", "RECORD_ID": "1002"}
+            ]
+        },
+        "RELATED_ENTITIES": [
+            {"ENTITY_ID": 7, "MATCH_LEVEL_CODE": "POSSIBLY_SAME", "MATCH_KEY": "+NAME"},
+            {"ENTITY_ID": 9, "MATCH_LEVEL_CODE": "POSSIBLY_RELATED", "MATCH_KEY": "+ADDRESS"}
+        ]
+    }"#;
+
+    const AFTER: &str = r#"{
+        "RESOLVED_ENTITY": {
+            "ENTITY_ID": 1,
+            "FEATURES": {
+                "NAME": [{"FEAT_DESC": "Robert Smith"}, {"FEAT_DESC": "Bob Smith"}],
+                "PHONE": [{"FEAT_DESC": "555-1212"}]
+            },
+            "RECORDS": [
+                {"DATA_SOURCE": "CUSTOMERS", "RECORD_ID": "1001"},
+                {"DATA_SOURCE": "CUSTOMERS", "RECORD_ID": "1003"}
+            ]
+        },
+        "RELATED_ENTITIES": [
+            {"ENTITY_ID": 7, "MATCH_LEVEL_CODE": "POSSIBLY_RELATED", "MATCH_KEY": "+NAME"},
+            {"ENTITY_ID": 12, "MATCH_LEVEL_CODE": "POSSIBLY_SAME", "MATCH_KEY": "+PHONE"}
+        ]
+    }"#;
+
+    #[test]
+    fn test_diff_entities() {
+        let diff = diff_entities(BEFORE, AFTER).unwrap();
+        assert!(!diff.is_empty());
+        assert_eq!(
+            diff.features,
+            vec![FeatureDiff {
+                feature_type: "NAME".to_string(),
+                only_in_a: vec![],
+                only_in_b: vec!["Bob Smith".to_string()],
+            }]
+        );
+        assert_eq!(diff.records_only_in_a[0].record_id, "1002");
+        assert_eq!(diff.records_only_in_b[0].record_id, "1003");
+
+        let ids: Vec<_> = diff.relationships.iter().map(|r| r.entity_id).collect();
+        assert_eq!(ids, vec![7, 9, 12]);
+        assert!(diff.relationships[1].in_b.is_none());
+        assert!(diff.relationships[2].in_a.is_none());
+
+        let report = diff.to_string();
+        assert!(report.contains("+ Bob Smith"));
+        assert!(report.contains("~ entity 7: POSSIBLY_SAME +NAME -> POSSIBLY_RELATED +NAME"));
+    }
+
+    #[test]
+    fn test_membership_change_and_moves() {
+        let key = |id: &str| RecordKey::new("CUSTOMERS", id).unwrap();
+        let before: HashSet<RecordKey> = [key("1001"), key("1002")].into();
+        let after: HashSet<RecordKey> = [key("1001"), key("1003")].into();
+        let change = membership_change(&before, &after);
+        assert_eq!(change.added, [key("1003")].into());
+        assert_eq!(change.removed, [key("1002")].into());
+        assert!(membership_change(&before, &before).is_empty());
+
+        let before = BTreeMap::from([(EntityId(1), before), (EntityId(2), [key("1003")].into())]);
+        let after = BTreeMap::from([(EntityId(1), after), (EntityId(3), [key("1002")].into())]);
+        assert_eq!(
+            membership_moves(&before, &after),
+            vec![
+                RecordMove {
+                    record: key("1002"),
+                    from: Some(EntityId(1)),
+                    to: Some(EntityId(3)),
+                },
+                RecordMove {
+                    record: key("1003"),
+                    from: Some(EntityId(2)),
+                    to: Some(EntityId(1)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_record_impact() {
+        let current = serde_json::json!({"FEATURES": {
+            "NAME": [{"FEAT_DESC": "Robert Smith"}],
+            "NAME_KEY": [{"FEAT_DESC": "RPRT|SM0"}],
+            "PHONE": [{"FEAT_DESC": "555-1212"}]
+        }});
+        let candidate = serde_json::json!({"FEATURES": {
+            "NAME": [{"FEAT_DESC": "Robert Smith"}],
+            "NAME_KEY": [{"FEAT_DESC": "RPRT|SM0"}, {"FEAT_DESC": "RPRT|SMT"}],
+            "PHONE": [{"FEAT_DESC": "555-1212"}],
+            "PHONE_KEY": [{"FEAT_DESC": "5551212"}]
+        }});
+        let candidate_types = BTreeSet::from(["NAME_KEY".to_string(), "PHONE_KEY".to_string()]);
+        let key = RecordKey::new("CUSTOMERS", "1001").unwrap();
+        assert!(record_impact(key.clone(), &current, &current, &candidate_types).is_none());
+
+        let record = record_impact(key, &current, &candidate, &candidate_types).unwrap();
+        assert!(record.features.is_empty());
+        let types: Vec<&str> = record
+            .candidate_keys
+            .iter()
+            .map(|diff| diff.feature_type.as_str())
+            .collect();
+        assert_eq!(types, vec!["NAME_KEY", "PHONE_KEY"]);
+        assert_eq!(record.candidate_keys[0].only_in_b, vec!["RPRT|SMT"]);
+
+        let impact = ConfigImpact {
+            current_config_id: ConfigId(1),
+            candidate_config_id: ConfigId(2),
+            records_sampled: 10,
+            records: vec![record],
+            feature_types: BTreeMap::from([
+                ("NAME_KEY".to_string(), 1),
+                ("PHONE_KEY".to_string(), 1),
+            ]),
+        };
+        assert_eq!(impact.candidate_key_changes(), 1);
+        let report = impact.to_string();
+        assert!(
+            report
+                .starts_with("config 1 -> 2: 1 of 10 sampled records change, 1 in candidate keys")
+        );
+        assert!(report.contains("    candidate key PHONE_KEY:\n      + 5551212\n"));
+    }
+
+    #[test]
+    fn test_identical_entities() {
+        let diff = diff_entities(BEFORE, BEFORE).unwrap();
+        assert!(diff.is_empty());
+        assert!(diff.to_string().contains("no differences"));
+    }
+
+    #[test]
+    fn test_relationship_between_compared_entities_is_ignored() {
+        let a = r#"{"RESOLVED_ENTITY":{"ENTITY_ID":1},"RELATED_ENTITIES":[{"ENTITY_ID":2,"MATCH_LEVEL_CODE":"POSSIBLY_SAME"}]}"#;
+        let b = r#"{"RESOLVED_ENTITY":{"ENTITY_ID":2},"RELATED_ENTITIES":[{"ENTITY_ID":1,"MATCH_LEVEL_CODE":"POSSIBLY_SAME"}]}"#;
+        assert!(diff_entities(a, b).unwrap().relationships.is_empty());
+    }
+
+    fn exported(entity_id: i64, records: usize, data_source: &str) -> ExportedEntity {
+        let records: Vec<Value> = (0..records)
+            .map(|i| serde_json::json!({"DATA_SOURCE": data_source, "RECORD_ID": format!("{entity_id}-{i}")}))
+            .collect();
+        serde_json::from_value(serde_json::json!({
+            "RESOLVED_ENTITY": {"ENTITY_ID": entity_id, "ENTITY_NAME": "Ann, Lee", "RECORDS": records},
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_stratified_sample() {
+        let mut strata: BTreeMap<String, Stratum> = BTreeMap::new();
+        let entities = (1..=20)
+            .map(|id| exported(id, 1, "CRM"))
+            .chain([exported(21, 12, "CRM")]);
+        for entity in entities {
+            strata
+                .entry(stratum(SampleStrategy::ByEntitySize, &entity))
+                .or_default()
+                .offer(entity, 4);
+        }
+        assert_eq!(strata["1"].population, 20);
+        assert_eq!(strata["1"].kept.len(), 4);
+
+        let sample = draw(strata, 4);
+        assert_eq!(sample.len(), 4);
+        assert_eq!(sample[0].stratum, "1");
+        assert_eq!(sample[0].weight, 20.0 / 3.0);
+        let large = sample
+            .iter()
+            .find(|entity| entity.stratum == "11-100")
+            .unwrap();
+        assert_eq!(large.entity_id, EntityId(21));
+        assert_eq!(large.weight, 1.0);
+
+        let mut csv = Vec::new();
+        assert_eq!(write_review_csv(&sample[3..], &mut csv).unwrap(), 1);
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with(
+            "entity_id,stratum,weight,entity_name,record_count,records,verdict,notes\n"
+        ));
+        assert!(csv.contains("21,11-100,1.00,\"Ann, Lee\",12,CRM:21-0 CRM:21-1 "));
+        assert!(csv.ends_with(",,\n"));
+    }
+
+    #[test]
+    fn test_draw_is_deterministic() {
+        let offer = || {
+            let mut stratum = Stratum::default();
+            for id in 1..=100 {
+                stratum.offer(exported(id, 1, "CRM"), 5);
+            }
+            BTreeMap::from([("ALL".to_string(), stratum)])
+        };
+        let ids = |sample: Vec<SampledEntity>| {
+            sample
+                .iter()
+                .map(|entity| entity.entity_id)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(draw(offer(), 5)), ids(draw(offer(), 5)));
+        assert_eq!(draw(offer(), 5).len(), 5);
+    }
+}
@@ -0,0 +1,415 @@
+//! Cached `why_entities` and `how_entity` responses.
+//!
+//! Why and how analysis is expensive, and review tools ask for the same
+//! entities again and again. An [`AnalysisCache`] added with
+//! `SzEnvironmentCore::add_interceptor` answers repeated `why_entities` and
+//! `how_entity` calls from memory for a time-to-live. Entries are keyed by
+//! the call's entity IDs and flags and by the active configuration ID, so a
+//! reinitialized environment does not see answers from the previous
+//! configuration.
+//!
+//! A cached answer goes stale as soon as one of its entities changes. Pass
+//! the entity events of an `events::EntityChangeStream` to
+//! [`invalidate_event`](AnalysisCache::invalidate_event), or `WITH_INFO`
+//! responses to [`invalidate_info`](AnalysisCache::invalidate_info), to drop
+//! the answers involving the changed entities. A call in flight when an
+//! invalidation happens is not cached.
+//!
+//! [`stats`](AnalysisCache::stats) counts hits and misses for metrics.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use std::time::Duration;
+//! use sz_rust_sdk::analysis_cache::AnalysisCache;
+//! use sz_rust_sdk::prelude::*;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_analysis_cache")?;
+//! let cache = AnalysisCache::new(Duration::from_secs(300));
+//! env.add_interceptor(Box::new(cache.clone()));
+//! let engine = env.get_engine()?;
+//!
+//! engine.why_entities(EntityId(1), EntityId(2), None)?;
+//! engine.why_entities(EntityId(1), EntityId(2), None)?;
+//!
+//! let info = engine.add_record("TEST", "1001", r#"{"NAME_FULL": "Ann Lee"}"#, Some(SzFlags::WITH_INFO))?;
+//! cache.invalidate_info(&info)?;
+//! println!("{:?}", cache.stats());
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::core::SzEnvironmentCore;
+use crate::error::SzResult;
+use crate::events::EntityEvent;
+use crate::intercept::{CallOutcome, EngineCall, Intercept, SzInterceptor};
+use crate::models::WithInfoResult;
+use crate::traits::SzEnvironment;
+use crate::types::{ConfigId, EntityId};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread::ThreadId;
+use std::time::{Duration, Instant};
+
+/// Default for [`AnalysisCache::with_max_entries`].
+pub const DEFAULT_MAX_ENTRIES: usize = 10_000;
+
+/// Operations whose responses are cached.
+const OPERATIONS: [&str; 2] = ["why_entities", "how_entity"];
+
+/// Counters of an [`AnalysisCache`], cumulative since it was created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct AnalysisCacheStats {
+    /// Calls answered from the cache
+    pub hits: u64,
+    /// Calls passed on to the engine
+    pub misses: u64,
+    /// Entries dropped by invalidation
+    pub invalidated: u64,
+    /// Entries currently cached, including expired ones not yet dropped
+    pub entries: usize,
+}
+
+impl AnalysisCacheStats {
+    /// Share of calls answered from the cache, if there were any calls.
+    pub fn hit_ratio(&self) -> Option<f64> {
+        let total = self.hits + self.misses;
+        (total > 0).then(|| self.hits as f64 / total as f64)
+    }
+}
+
+/// A time-to-live cache of why and how responses; see the
+/// [module documentation](self).
+///
+/// Clones share the cache, so keep one to read its stats and invalidate it
+/// after adding another to the environment.
+#[derive(Clone)]
+pub struct AnalysisCache {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    ttl: Duration,
+    max_entries: AtomicUsize,
+    config_id: fn() -> Option<ConfigId>,
+    entries: Mutex<HashMap<Key, Entry>>,
+    /// Misses in flight per calling thread, with the generation they started in
+    pending: Mutex<HashMap<ThreadId, (Key, u64)>>,
+    /// Incremented by every invalidation
+    generation: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    invalidated: AtomicU64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Key {
+    operation: &'static str,
+    entity_1: EntityId,
+    entity_2: EntityId,
+    flags: Option<u64>,
+    config_id: ConfigId,
+}
+
+struct Entry {
+    response: String,
+    stored: Instant,
+}
+
+impl std::fmt::Debug for AnalysisCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnalysisCache")
+            .field("ttl", &self.inner.ttl)
+            .field("stats", &self.stats())
+            .finish()
+    }
+}
+
+impl AnalysisCache {
+    /// Creates a cache whose entries expire `ttl` after they were stored.
+    pub fn new(ttl: Duration) -> Self {
+        Self::with_config_source(ttl, active_config_id)
+    }
+
+    fn with_config_source(ttl: Duration, config_id: fn() -> Option<ConfigId>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                ttl,
+                max_entries: AtomicUsize::new(DEFAULT_MAX_ENTRIES),
+                config_id,
+                entries: Mutex::new(HashMap::new()),
+                pending: Mutex::new(HashMap::new()),
+                generation: AtomicU64::new(0),
+                hits: AtomicU64::new(0),
+                misses: AtomicU64::new(0),
+                invalidated: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Caps the number of entries. When the cache is full, expired entries
+    /// are dropped, and new responses are not cached until there is room.
+    pub fn with_max_entries(self, max_entries: usize) -> Self {
+        self.inner.max_entries.store(max_entries, Ordering::Relaxed);
+        self
+    }
+
+    /// Drops the entries involving `entity_id` and returns how many there
+    /// were.
+    pub fn invalidate_entity(&self, entity_id: EntityId) -> usize {
+        self.invalidate(&[entity_id])
+    }
+
+    /// Drops the entries involving the entities of `event`.
+    pub fn invalidate_event(&self, event: &EntityEvent) -> usize {
+        self.invalidate(&event.entity_ids())
+    }
+
+    /// Drops the entries involving the `AFFECTED_ENTITIES` of a `WITH_INFO`
+    /// response.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::Json` if `info` is not a `WITH_INFO` document.
+    pub fn invalidate_info(&self, info: &str) -> SzResult<usize> {
+        let info = WithInfoResult::from_json(info)?;
+        Ok(self.invalidate(&info.affected_entity_ids()))
+    }
+
+    /// Drops every entry.
+    pub fn clear(&self) {
+        let mut entries = self.entries();
+        self.inner.generation.fetch_add(1, Ordering::Relaxed);
+        self.inner
+            .invalidated
+            .fetch_add(entries.len() as u64, Ordering::Relaxed);
+        entries.clear();
+    }
+
+    /// Hit, miss and invalidation counts, and the current size.
+    pub fn stats(&self) -> AnalysisCacheStats {
+        AnalysisCacheStats {
+            hits: self.inner.hits.load(Ordering::Relaxed),
+            misses: self.inner.misses.load(Ordering::Relaxed),
+            invalidated: self.inner.invalidated.load(Ordering::Relaxed),
+            entries: self.entries().len(),
+        }
+    }
+
+    fn invalidate(&self, entity_ids: &[EntityId]) -> usize {
+        let mut entries = self.entries();
+        self.inner.generation.fetch_add(1, Ordering::Relaxed);
+        let before = entries.len();
+        entries.retain(|key, _| {
+            !entity_ids.contains(&key.entity_1) && !entity_ids.contains(&key.entity_2)
+        });
+        let removed = before - entries.len();
+        self.inner
+            .invalidated
+            .fetch_add(removed as u64, Ordering::Relaxed);
+        removed
+    }
+
+    /// The cache key of `call`, or `None` for calls that are not cached.
+    fn key(&self, call: &EngineCall<'_>) -> Option<Key> {
+        let operation = OPERATIONS
+            .into_iter()
+            .find(|operation| *operation == call.operation)?;
+        let entity = |name: &str| call.parameters.get(name)?.as_i64().map(EntityId);
+        let (entity_1, entity_2) = match operation {
+            "how_entity" => (entity("entity_id")?, entity("entity_id")?),
+            _ => (entity("entity_id1")?, entity("entity_id2")?),
+        };
+        Some(Key {
+            operation,
+            entity_1,
+            entity_2,
+            flags: call
+                .parameters
+                .get("flags")
+                .and_then(|flags| flags.as_u64()),
+            config_id: (self.inner.config_id)()?,
+        })
+    }
+
+    fn entries(&self) -> MutexGuard<'_, HashMap<Key, Entry>> {
+        lock(&self.inner.entries)
+    }
+}
+
+impl SzInterceptor for AnalysisCache {
+    fn before(&self, call: &EngineCall<'_>) -> SzResult<Intercept> {
+        let Some(key) = self.key(call) else {
+            return Ok(Intercept::Proceed);
+        };
+        {
+            let mut entries = self.entries();
+            match entries.get(&key) {
+                Some(entry) if entry.stored.elapsed() < self.inner.ttl => {
+                    self.inner.hits.fetch_add(1, Ordering::Relaxed);
+                    return Ok(Intercept::Respond(entry.response.clone()));
+                }
+                Some(_) => {
+                    entries.remove(&key);
+                }
+                None => {}
+            }
+        }
+        self.inner.misses.fetch_add(1, Ordering::Relaxed);
+        let generation = self.inner.generation.load(Ordering::Relaxed);
+        lock(&self.inner.pending).insert(std::thread::current().id(), (key, generation));
+        Ok(Intercept::Proceed)
+    }
+
+    fn after(&self, call: &EngineCall<'_>, outcome: &CallOutcome<'_>) {
+        if !OPERATIONS.contains(&call.operation) {
+            return;
+        }
+        let Some((key, generation)) =
+            lock(&self.inner.pending).remove(&std::thread::current().id())
+        else {
+            return;
+        };
+        let Some(response) = outcome.response.filter(|_| outcome.error.is_none()) else {
+            return;
+        };
+        let mut entries = self.entries();
+        // Checked under the entries lock, which invalidations hold while
+        // they increment it
+        if self.inner.generation.load(Ordering::Relaxed) != generation {
+            return;
+        }
+        let max_entries = self.inner.max_entries.load(Ordering::Relaxed);
+        if entries.len() >= max_entries {
+            entries.retain(|_, entry| entry.stored.elapsed() < self.inner.ttl);
+        }
+        if entries.len() < max_entries {
+            entries.insert(
+                key,
+                Entry {
+                    response: response.to_string(),
+                    stored: Instant::now(),
+                },
+            );
+        }
+    }
+}
+
+/// The configuration the process's environment is running with, if it is
+/// initialized.
+fn active_config_id() -> Option<ConfigId> {
+    SzEnvironmentCore::try_get_instance()?
+        .get_active_config_id()
+        .ok()
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{Map, Value, json};
+
+    fn why(entity_1: i64, entity_2: i64) -> Map<String, Value> {
+        match json!({"entity_id1": entity_1, "entity_id2": entity_2, "flags": null}) {
+            Value::Object(map) => map,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Runs `call` through the cache as the interceptor chain would, with
+    /// `response` as the engine's answer. Returns whether it was a hit.
+    fn run(
+        cache: &AnalysisCache,
+        operation: &'static str,
+        parameters: &Map<String, Value>,
+    ) -> bool {
+        let call = EngineCall {
+            operation,
+            parameters,
+        };
+        let hit = matches!(cache.before(&call).unwrap(), Intercept::Respond(_));
+        let outcome = CallOutcome {
+            duration: Duration::ZERO,
+            result_code: 0,
+            error: None,
+            response: Some(r#"{"WHY_RESULTS":[]}"#),
+        };
+        cache.after(&call, &outcome);
+        hit
+    }
+
+    #[test]
+    fn test_hits_misses_and_invalidation() {
+        let cache =
+            AnalysisCache::with_config_source(Duration::from_secs(60), || Some(ConfigId(1)));
+        assert!(!run(&cache, "why_entities", &why(1, 2)));
+        assert!(run(&cache, "why_entities", &why(1, 2)));
+        assert!(!run(&cache, "why_entities", &why(2, 1)));
+        let how = match json!({"entity_id": 3, "flags": 0}) {
+            Value::Object(map) => map,
+            _ => unreachable!(),
+        };
+        assert!(!run(&cache, "how_entity", &how));
+        assert!(run(&cache, "how_entity", &how));
+        assert!(!run(&cache, "get_entity", &how));
+
+        let event = EntityEvent::EntityMerged {
+            survivor: EntityId(2),
+            absorbed: vec![EntityId(9)],
+        };
+        assert_eq!(cache.invalidate_event(&event), 2);
+        assert_eq!(
+            cache
+                .invalidate_info(r#"{"AFFECTED_ENTITIES":[{"ENTITY_ID":3}]}"#)
+                .unwrap(),
+            1
+        );
+        assert!(!run(&cache, "why_entities", &why(1, 2)));
+        assert_eq!(
+            cache.stats(),
+            AnalysisCacheStats {
+                hits: 2,
+                misses: 4,
+                invalidated: 3,
+                entries: 1,
+            }
+        );
+        assert_eq!(cache.stats().hit_ratio(), Some(2.0 / 6.0));
+    }
+
+    #[test]
+    fn test_expiry_and_invalidation_in_flight() {
+        let cache = AnalysisCache::with_config_source(Duration::ZERO, || Some(ConfigId(1)));
+        assert!(!run(&cache, "why_entities", &why(1, 2)));
+        assert!(!run(&cache, "why_entities", &why(1, 2)));
+
+        let cache =
+            AnalysisCache::with_config_source(Duration::from_secs(60), || Some(ConfigId(1)));
+        let parameters = why(1, 2);
+        let call = EngineCall {
+            operation: "why_entities",
+            parameters: &parameters,
+        };
+        cache.before(&call).unwrap();
+        cache.invalidate_entity(EntityId(7));
+        let outcome = CallOutcome {
+            duration: Duration::ZERO,
+            result_code: 0,
+            error: None,
+            response: Some("{}"),
+        };
+        cache.after(&call, &outcome);
+        assert_eq!(cache.stats().entries, 0);
+
+        let uninitialized = AnalysisCache::with_config_source(Duration::from_secs(60), || None);
+        assert!(!run(&uninitialized, "why_entities", &why(1, 2)));
+        assert_eq!(uninitialized.stats(), AnalysisCacheStats::default());
+    }
+}
@@ -0,0 +1,344 @@
+//! Audit trail of mutating operations.
+//!
+//! With an [`AuditSink`] set through `SzEnvironmentCore::set_audit_sink`,
+//! every operation that changes the repository or its configuration records
+//! an [`AuditEvent`]: when it happened, who asked for it, what it was called
+//! with and how it ended. Auditing is off until a sink is set.
+//!
+//! | Component | Audited operations |
+//! |-----------|--------------------|
+//! | `SzEngine` | `add_record`, `delete_record`, `reevaluate_record`, `reevaluate_entity`, `process_redo_record` |
+//! | `SzConfigManager` | `register_config`, `set_default_config_id`, `replace_default_config_id` |
+//! | `SzEnvironment` | `reinitialize` |
+//! | `SzDiagnostic` | `purge_repository` |
+//!
+//! Adds rejected by a data source policy's validation are audited as failed
+//! `add_record` events. Record definitions and redo records are not part of
+//! the event: they can be large and usually hold personal data. Calls made while the environment is in dry-run
+//! mode change nothing and are not audited.
+//!
+//! The actor is whatever the caller names with [`with_actor`] on the calling
+//! thread, e.g. the user behind a service request. [`JsonLinesAuditSink`]
+//! appends events to a file; implement [`AuditSink`] to send them anywhere
+//! else, such as a database table. With the `encryption` feature,
+//! `JsonLinesAuditSink::with_cipher` encrypts each line before it is written.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use std::sync::Arc;
+//! use sz_rust_sdk::audit::{self, JsonLinesAuditSink};
+//! use sz_rust_sdk::prelude::*;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_audit")?;
+//! env.set_audit_sink(Some(Arc::new(JsonLinesAuditSink::append("audit.jsonl")?)));
+//! let engine = env.get_engine()?;
+//! audit::with_actor("alice@example.com", || {
+//!     engine.add_record("TEST", "1001", r#"{"NAME_FULL": "Ann Lee"}"#, None)
+//! })?;
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::core::settings::EngineSettings;
+use crate::error::{SzError, SzResult};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::cell::RefCell;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Result code of an operation that failed without a native error code,
+/// e.g. an argument the SDK rejected before calling the engine.
+pub const SDK_ERROR_CODE: i64 = -1;
+
+/// One audited operation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AuditEvent {
+    /// When the operation finished, in milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    /// Actor named with [`with_actor`] on the calling thread
+    pub actor: Option<String>,
+    /// Method name, e.g. `add_record`
+    pub operation: &'static str,
+    /// Identifying arguments of the call, e.g. data source and record ID
+    pub parameters: Map<String, Value>,
+    /// 0 on success, the native error code on failure, or
+    /// [`SDK_ERROR_CODE`] for failures without one
+    pub result_code: i64,
+    /// Error message on failure
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl AuditEvent {
+    /// Whether the operation succeeded.
+    pub fn succeeded(&self) -> bool {
+        self.result_code == 0
+    }
+}
+
+/// Destination of audit events.
+///
+/// Called on the thread that made the audited call, after the call returns.
+/// A sink that fails does not fail the operation, which has already taken
+/// effect; the failure is counted in `SzEnvironmentCore::audit_failures`.
+pub trait AuditSink: Send + Sync {
+    /// Stores one event.
+    fn record(&self, event: &AuditEvent) -> SzResult<()>;
+}
+
+/// Writes each event as one line of JSON.
+pub struct JsonLinesAuditSink {
+    writer: Mutex<Box<dyn Write + Send>>,
+    #[cfg(feature = "encryption")]
+    cipher: Option<crate::encryption::PayloadCipher>,
+}
+
+impl JsonLinesAuditSink {
+    /// Writes to `writer`, flushing after every event.
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            writer: Mutex::new(Box::new(writer)),
+            #[cfg(feature = "encryption")]
+            cipher: None,
+        }
+    }
+
+    /// Encrypts every line with `cipher`; read the log back with
+    /// `PayloadCipher::open_line`.
+    #[cfg(feature = "encryption")]
+    pub fn with_cipher(mut self, cipher: crate::encryption::PayloadCipher) -> Self {
+        self.cipher = Some(cipher);
+        self
+    }
+
+    /// Appends to the file at `path`, creating it if needed.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the file cannot be opened.
+    pub fn append(path: impl AsRef<Path>) -> SzResult<Self> {
+        let path = path.as_ref();
+        let file: File = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| SzError::bad_input(format!("Failed opening {}: {e}", path.display())))?;
+        Ok(Self::new(file))
+    }
+}
+
+impl AuditSink for JsonLinesAuditSink {
+    fn record(&self, event: &AuditEvent) -> SzResult<()> {
+        let line = serde_json::to_string(event)?;
+        #[cfg(feature = "encryption")]
+        let line = match &self.cipher {
+            Some(cipher) => cipher.seal_line(&line)?,
+            None => line,
+        };
+        let mut writer = match self.writer.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        writeln!(writer, "{line}")
+            .and_then(|()| writer.flush())
+            .map_err(|e| SzError::bad_input(format!("Failed writing audit event: {e}")))
+    }
+}
+
+/// Keeps events in memory, e.g. for tests or for forwarding in batches.
+#[derive(Debug, Default)]
+pub struct MemoryAuditSink {
+    events: Mutex<Vec<AuditEvent>>,
+}
+
+impl MemoryAuditSink {
+    /// Creates an empty sink.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the events recorded so far.
+    pub fn events(&self) -> Vec<AuditEvent> {
+        self.lock().clone()
+    }
+
+    /// Removes and returns the events recorded so far.
+    pub fn take(&self) -> Vec<AuditEvent> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<AuditEvent>> {
+        match self.events.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+impl AuditSink for MemoryAuditSink {
+    fn record(&self, event: &AuditEvent) -> SzResult<()> {
+        self.lock().push(event.clone());
+        Ok(())
+    }
+}
+
+thread_local! {
+    static ACTOR: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Runs `f` with `actor` recorded as the actor of the operations it audits
+/// on this thread. Calls nest; the previous actor is restored afterwards.
+pub fn with_actor<T>(actor: impl Into<String>, f: impl FnOnce() -> T) -> T {
+    struct Restore(Option<String>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            ACTOR.with(|current| *current.borrow_mut() = self.0.take());
+        }
+    }
+
+    let previous = ACTOR.with(|current| current.borrow_mut().replace(actor.into()));
+    let _restore = Restore(previous);
+    f()
+}
+
+/// Returns the actor set with [`with_actor`] on this thread.
+pub fn current_actor() -> Option<String> {
+    ACTOR.with(|current| current.borrow().clone())
+}
+
+/// Shared handle to the environment's sink.
+#[derive(Clone)]
+pub(crate) struct SinkHandle(pub(crate) Arc<dyn AuditSink>);
+
+impl std::fmt::Debug for SinkHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("AuditSink")
+    }
+}
+
+/// Records `result` of `operation` in the environment's sink, if one is set.
+/// `parameters` is only evaluated when auditing is on.
+pub(crate) fn record<T>(
+    settings: &EngineSettings,
+    operation: &'static str,
+    parameters: impl FnOnce() -> Value,
+    result: &SzResult<T>,
+) {
+    let Some(sink) = settings.audit_sink() else {
+        return;
+    };
+    let event = event(operation, parameters(), result);
+    if sink.0.record(&event).is_err() {
+        settings.audit_failed();
+    }
+}
+
+fn event<T>(operation: &'static str, parameters: Value, result: &SzResult<T>) -> AuditEvent {
+    let parameters = match parameters {
+        Value::Object(map) => map,
+        _ => Map::new(),
+    };
+    let (result_code, error) = match result {
+        Ok(_) => (0, None),
+        Err(e) => (
+            e.error_code().unwrap_or(SDK_ERROR_CODE),
+            Some(e.to_string()),
+        ),
+    };
+    AuditEvent {
+        timestamp_ms: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_millis() as u64),
+        actor: current_actor(),
+        operation,
+        parameters,
+        result_code,
+        error,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_with_actor_nests_and_restores() {
+        assert_eq!(current_actor(), None);
+        with_actor("outer", || {
+            assert_eq!(current_actor().as_deref(), Some("outer"));
+            with_actor("inner", || {
+                assert_eq!(current_actor().as_deref(), Some("inner"));
+            });
+            assert_eq!(current_actor().as_deref(), Some("outer"));
+        });
+        assert_eq!(current_actor(), None);
+    }
+
+    #[test]
+    fn test_event_result_codes() {
+        let ok: SzResult<()> = Ok(());
+        let success = with_actor("ops", || {
+            event("add_record", json!({"record_id": "1"}), &ok)
+        });
+        assert!(success.succeeded());
+        assert_eq!(success.actor.as_deref(), Some("ops"));
+        assert_eq!(success.parameters["record_id"], "1");
+
+        let failed: SzResult<()> = Err(SzError::bad_input("no"));
+        let failure = event("delete_record", json!({}), &failed);
+        assert_eq!(failure.result_code, SDK_ERROR_CODE);
+        assert!(failure.error.is_some());
+    }
+
+    #[test]
+    fn test_json_lines_sink() {
+        #[derive(Clone, Default)]
+        struct Shared(Arc<Mutex<Vec<u8>>>);
+        impl Write for Shared {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let buffer = Shared::default();
+        let sink = JsonLinesAuditSink::new(buffer.clone());
+        let ok: SzResult<()> = Ok(());
+        sink.record(&event("reinitialize", json!({"config_id": 7}), &ok))
+            .unwrap();
+        let written = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        let line: Value = serde_json::from_str(written.trim_end()).unwrap();
+        assert_eq!(line["operation"], "reinitialize");
+        assert_eq!(line["parameters"]["config_id"], 7);
+        assert_eq!(line["result_code"], 0);
+        assert!(line.get("error").is_none());
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encrypted_json_lines_sink() {
+        use crate::encryption::PayloadCipher;
+
+        let path = std::env::temp_dir().join(format!("sz_audit_{}.jsonl", std::process::id()));
+        let cipher = PayloadCipher::new(&[9u8; 32]).unwrap();
+        let sink = JsonLinesAuditSink::append(&path)
+            .unwrap()
+            .with_cipher(cipher.clone());
+        let ok: SzResult<()> = Ok(());
+        sink.record(&event("purge_repository", json!({}), &ok))
+            .unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert!(!written.contains("purge_repository"));
+        let line: Value = serde_json::from_str(&cipher.open_line(&written).unwrap()).unwrap();
+        assert_eq!(line["operation"], "purge_repository");
+    }
+}
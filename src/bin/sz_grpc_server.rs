@@ -0,0 +1,45 @@
+//! Serves the Senzing engine and product gRPC protos backed by this SDK.
+//!
+//! ```text
+//! SENZING_ENGINE_CONFIGURATION_JSON='{...}' sz-grpc-server [ADDRESS]
+//! ```
+//!
+//! `ADDRESS` defaults to `0.0.0.0:8261`. See the `grpc` module for the
+//! services and request conventions.
+
+use std::net::SocketAddr;
+use std::process::ExitCode;
+use sz_rust_sdk::prelude::*;
+
+const DEFAULT_ADDRESS: &str = "0.0.0.0:8261";
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let address = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_ADDRESS.to_string());
+    let address: SocketAddr = match address.parse() {
+        Ok(address) => address,
+        Err(e) => {
+            eprintln!("Invalid address {address:?}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let Ok(settings) = std::env::var("SENZING_ENGINE_CONFIGURATION_JSON") else {
+        eprintln!("SENZING_ENGINE_CONFIGURATION_JSON is not set");
+        return ExitCode::FAILURE;
+    };
+    match run(&settings, address).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run(settings: &str, address: SocketAddr) -> SzResult<()> {
+    let environment = SzEnvironmentCore::get_instance("sz-grpc-server", settings, false)?;
+    eprintln!("Serving Senzing gRPC on {address}");
+    sz_rust_sdk::grpc::serve(environment, address).await
+}
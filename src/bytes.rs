@@ -0,0 +1,253 @@
+//! Owned engine response buffers.
+//!
+//! Engine methods that return [`JsonString`](crate::types::JsonString) copy the
+//! native response into a Rust `String` and free the native buffer. For large
+//! responses in hot loops that copy is measurable. The `*_bytes` engine methods
+//! return an [`SzBytes`] instead, which keeps the native buffer and frees it
+//! when dropped, so the response can be parsed or written out without an
+//! intermediate copy.
+
+use crate::error::{SzError, SzResult};
+use libc::c_char;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::borrow::Cow;
+use std::ffi::CStr;
+use std::fmt;
+use std::ops::Deref;
+use std::ptr::NonNull;
+
+/// A response buffer returned by the engine.
+///
+/// Dereferences to `[u8]` (without the trailing NUL). The buffer is either the
+/// native allocation, freed on drop, or an owned `Vec<u8>` when the response
+/// came from a code path that already produced a Rust string.
+///
+/// # Example
+///
+/// ```
+/// # use sz_rust_sdk::helpers::ExampleEnvironment;
+/// use sz_rust_sdk::prelude::*;
+///
+/// # let env = ExampleEnvironment::initialize("doctest_sz_bytes")?;
+/// let engine = env.get_engine()?;
+/// # engine.add_record("TEST", "BYTES_1", r#"{"NAME_FULL": "John Smith"}"#, None)?;
+/// let entity = engine.get_entity_bytes(EntityRef::from_record("TEST", "BYTES_1"), None)?;
+/// let value: serde_json::Value = entity.parse()?;
+/// assert!(value.get("RESOLVED_ENTITY").is_some());
+/// # Ok::<(), SzError>(())
+/// ```
+pub struct SzBytes {
+    inner: Inner,
+}
+
+enum Inner {
+    Native { ptr: NonNull<c_char>, len: usize },
+    Owned(Vec<u8>),
+}
+
+// SAFETY: the native buffer is exclusively owned by this value and is only
+// read through shared references; freeing it does not depend on the thread.
+unsafe impl Send for SzBytes {}
+unsafe impl Sync for SzBytes {}
+
+impl SzBytes {
+    /// Takes ownership of a native, NUL-terminated response buffer.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be null or a NUL-terminated buffer allocated by the Senzing
+    /// library that is not freed or used elsewhere afterwards.
+    pub(crate) unsafe fn from_native(ptr: *mut c_char) -> Self {
+        match NonNull::new(ptr) {
+            Some(ptr) => {
+                crate::ffi::helpers::note_native_buffer_taken();
+                let len = unsafe { CStr::from_ptr(ptr.as_ptr()) }.count_bytes();
+                Self {
+                    inner: Inner::Native { ptr, len },
+                }
+            }
+            None => Self {
+                inner: Inner::Owned(Vec::new()),
+            },
+        }
+    }
+
+    /// Returns the response bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        match &self.inner {
+            // SAFETY: ptr is valid for len bytes until drop.
+            Inner::Native { ptr, len } => unsafe {
+                std::slice::from_raw_parts(ptr.as_ptr().cast::<u8>(), *len)
+            },
+            Inner::Owned(bytes) => bytes,
+        }
+    }
+
+    /// Returns the response bytes mutably, for in-place parsing.
+    #[cfg_attr(not(feature = "fast-json"), allow(dead_code))]
+    fn as_bytes_mut(&mut self) -> &mut [u8] {
+        match &mut self.inner {
+            // SAFETY: ptr is valid for len bytes until drop and exclusively owned.
+            Inner::Native { ptr, len } => unsafe {
+                std::slice::from_raw_parts_mut(ptr.as_ptr().cast::<u8>(), *len)
+            },
+            Inner::Owned(bytes) => bytes,
+        }
+    }
+
+    /// Returns the response as `&str`, failing on invalid UTF-8.
+    pub fn to_str(&self) -> SzResult<&str> {
+        std::str::from_utf8(self.as_bytes())
+            .map_err(|e| SzError::bad_input(format!("Response is not valid UTF-8: {e}")))
+    }
+
+    /// Returns the response as text, replacing invalid UTF-8 sequences with U+FFFD.
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self.as_bytes())
+    }
+
+    /// Copies the response into a `String`, failing on invalid UTF-8.
+    pub fn into_string(mut self) -> SzResult<String> {
+        match &mut self.inner {
+            Inner::Owned(bytes) => String::from_utf8(std::mem::take(bytes))
+                .map_err(|e| SzError::bad_input(format!("Response is not valid UTF-8: {e}"))),
+            Inner::Native { .. } => self.to_str().map(str::to_string),
+        }
+    }
+
+    /// Deserializes the response directly from the buffer.
+    pub fn parse<T: DeserializeOwned>(&self) -> SzResult<T> {
+        serde_json::from_slice(self.as_bytes()).map_err(SzError::from)
+    }
+
+    /// Deserializes the response, allowing the result to borrow from the buffer.
+    ///
+    /// Use with the borrowed models in [`models::borrowed`](crate::models::borrowed)
+    /// to avoid allocating a `String` for every field. With the `fast-json`
+    /// feature the response is parsed by `simd-json`, which unescapes strings
+    /// in place: the buffer no longer holds the original JSON afterwards.
+    /// Without the feature this uses `serde_json` and leaves the buffer as is.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sz_rust_sdk::SzBytes;
+    /// use sz_rust_sdk::models::borrowed::ResolvedEntity;
+    ///
+    /// let mut bytes = SzBytes::from(
+    ///     r#"{"ENTITY_ID":1,"ENTITY_NAME":"Ann Lee","RECORDS":[]}"#.to_string(),
+    /// );
+    /// let entity: ResolvedEntity<'_> = bytes.parse_in_place()?;
+    /// assert_eq!(entity.entity_name.as_deref(), Some("Ann Lee"));
+    /// # Ok::<(), sz_rust_sdk::SzError>(())
+    /// ```
+    pub fn parse_in_place<'a, T: Deserialize<'a>>(&'a mut self) -> SzResult<T> {
+        #[cfg(feature = "fast-json")]
+        {
+            simd_json::serde::from_slice(self.as_bytes_mut())
+                .map_err(|e| SzError::bad_input(format!("Failed to parse response: {e}")))
+        }
+        #[cfg(not(feature = "fast-json"))]
+        {
+            serde_json::from_slice(self.as_bytes()).map_err(SzError::from)
+        }
+    }
+
+    /// Converts to a `JsonString` the way the string-returning engine methods
+    /// do: invalid UTF-8 is hex-encoded rather than rejected.
+    pub(crate) fn into_json_string(self) -> String {
+        match std::str::from_utf8(self.as_bytes()) {
+            Ok(s) => s.to_string(),
+            Err(_) => hex::encode(self.as_bytes()),
+        }
+    }
+}
+
+impl Drop for SzBytes {
+    fn drop(&mut self) {
+        if let Inner::Native { ptr, .. } = self.inner {
+            unsafe { crate::ffi::helpers::sz_free(ptr.as_ptr()) };
+        }
+    }
+}
+
+impl Deref for SzBytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl AsRef<[u8]> for SzBytes {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl From<String> for SzBytes {
+    fn from(value: String) -> Self {
+        Self {
+            inner: Inner::Owned(value.into_bytes()),
+        }
+    }
+}
+
+impl From<Vec<u8>> for SzBytes {
+    fn from(value: Vec<u8>) -> Self {
+        Self {
+            inner: Inner::Owned(value),
+        }
+    }
+}
+
+impl fmt::Debug for SzBytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SzBytes")
+            .field("native", &matches!(self.inner, Inner::Native { .. }))
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_owned_bytes() {
+        let bytes = SzBytes::from(r#"{"A":1}"#.to_string());
+        assert_eq!(bytes.len(), 7);
+        assert_eq!(bytes.to_str().unwrap(), r#"{"A":1}"#);
+        let value: serde_json::Value = bytes.parse().unwrap();
+        assert_eq!(value["A"], 1);
+        assert_eq!(bytes.into_string().unwrap(), r#"{"A":1}"#);
+    }
+
+    #[test]
+    fn test_invalid_utf8() {
+        let bytes = SzBytes::from(vec![b'a', 0xff, b'b']);
+        assert!(bytes.to_str().is_err());
+        assert_eq!(bytes.to_string_lossy(), "a\u{fffd}b");
+        assert_eq!(bytes.into_json_string(), "61ff62");
+    }
+
+    #[test]
+    fn test_parse_in_place_borrows() {
+        use crate::models::borrowed::EntityRecord;
+        use std::borrow::Cow;
+
+        let mut bytes = SzBytes::from(r#"{"DATA_SOURCE":"TEST","RECORD_ID":"a\"b"}"#.to_string());
+        let record: EntityRecord<'_> = bytes.parse_in_place().unwrap();
+        assert!(matches!(record.data_source, Cow::Borrowed("TEST")));
+        assert_eq!(record.record_id, "a\"b");
+    }
+
+    #[test]
+    fn test_null_native_is_empty() {
+        let bytes = unsafe { SzBytes::from_native(std::ptr::null_mut()) };
+        assert!(bytes.is_empty());
+        assert_eq!(bytes.into_string().unwrap(), "");
+    }
+}
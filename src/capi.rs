@@ -0,0 +1,351 @@
+//! C ABI over the high-level SDK.
+//!
+//! The `cdylib` build of this crate exports these functions when the `capi`
+//! feature is on, so other languages can embed the Rust layer (environment
+//! lifecycle, engine calls, the bulk loader and the redo processor) rather
+//! than only the raw Senzing library. `include/sz_rust_sdk.h` declares them
+//! for C.
+//!
+//! Conventions:
+//!
+//! - Strings are NUL-terminated UTF-8 and are borrowed for the duration of
+//!   the call.
+//! - Every function except the `free` functions returns 0 on success, or the
+//!   native error code, or `audit::SDK_ERROR_CODE` (-1) for failures without
+//!   one. [`sz_capi_last_error`] then describes the failure.
+//! - Strings returned through `out_*` pointers belong to the caller and are
+//!   released with [`sz_capi_free_string`].
+//! - Flags are `SzFlags` bits. [`SZ_FLAGS_DEFAULT`] (all bits set, `-1` as a
+//!   signed value) uses the method's default flags; 0 is an explicit empty
+//!   flag set.
+//! - A panic inside the SDK is caught and reported as an error; it never
+//!   unwinds into the caller.
+//!
+//! An [`SzCapiEnvironment`] may be shared between threads. Each thread has
+//! its own last error.
+
+use crate::audit::SDK_ERROR_CODE;
+use crate::core::SzEnvironmentCore;
+use crate::error::{SzError, SzResult};
+use crate::flags::SzFlags;
+use crate::ingest;
+use crate::redo::SzRedoProcessor;
+use crate::traits::{SzEngine, SzEnvironment};
+use std::cell::RefCell;
+use std::ffi::{CStr, CString, c_char};
+use std::io::BufReader;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::ptr;
+use std::sync::Arc;
+
+/// Flags argument asking for the method's default flags
+/// (`SZ_FLAGS_DEFAULT` in the header).
+pub const SZ_FLAGS_DEFAULT: u64 = u64::MAX;
+
+/// An initialized environment and its engine, as seen from C.
+pub struct SzCapiEnvironment {
+    env: Arc<SzEnvironmentCore>,
+    engine: Box<dyn SzEngine>,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Initializes the Senzing environment and returns a handle to it in
+/// `out_env`.
+///
+/// # Safety
+///
+/// `module_name` and `settings` must be valid NUL-terminated strings and
+/// `out_env` a valid pointer to write the handle to.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sz_capi_environment_init(
+    module_name: *const c_char,
+    settings: *const c_char,
+    verbose_logging: bool,
+    out_env: *mut *mut SzCapiEnvironment,
+) -> i64 {
+    run("sz_capi_environment_init", || {
+        let module_name = unsafe { str_arg(module_name, "module_name") }?;
+        let settings = unsafe { str_arg(settings, "settings") }?;
+        if out_env.is_null() {
+            return Err(SzError::bad_input("out_env must not be null"));
+        }
+        let env = SzEnvironmentCore::get_instance(module_name, settings, verbose_logging)?;
+        let engine = env.get_engine()?;
+        let handle = Box::new(SzCapiEnvironment { env, engine });
+        unsafe { *out_env = Box::into_raw(handle) };
+        Ok(())
+    })
+}
+
+/// Destroys the environment and releases the handle, which must not be used
+/// afterwards.
+///
+/// # Safety
+///
+/// `env` must be a handle from [`sz_capi_environment_init`] that has not
+/// been destroyed, or null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sz_capi_environment_destroy(env: *mut SzCapiEnvironment) -> i64 {
+    run("sz_capi_environment_destroy", || {
+        if env.is_null() {
+            return Ok(());
+        }
+        let handle = unsafe { Box::from_raw(env) };
+        let SzCapiEnvironment { env, engine } = *handle;
+        drop(engine);
+        env.destroy()
+    })
+}
+
+/// Adds or replaces a record. With `SZ_WITH_INFO` in `flags` the info
+/// document is returned in `out_info`, which may otherwise be null.
+///
+/// # Safety
+///
+/// `env` must be a live handle, the strings valid NUL-terminated strings,
+/// and `out_info` null or a valid pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sz_capi_add_record(
+    env: *const SzCapiEnvironment,
+    data_source_code: *const c_char,
+    record_id: *const c_char,
+    record_definition: *const c_char,
+    flags: u64,
+    out_info: *mut *mut c_char,
+) -> i64 {
+    run("sz_capi_add_record", || {
+        let handle = unsafe { env_arg(env) }?;
+        let info = handle.engine.add_record(
+            unsafe { str_arg(data_source_code, "data_source_code") }?,
+            unsafe { str_arg(record_id, "record_id") }?,
+            unsafe { str_arg(record_definition, "record_definition") }?,
+            flags_arg(flags),
+        )?;
+        if out_info.is_null() {
+            return Ok(());
+        }
+        unsafe { out_string(out_info, info) }
+    })
+}
+
+/// Returns the entity containing a record in `out_json`.
+///
+/// # Safety
+///
+/// `env` must be a live handle, the strings valid NUL-terminated strings,
+/// and `out_json` a valid pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sz_capi_get_entity_by_record_id(
+    env: *const SzCapiEnvironment,
+    data_source_code: *const c_char,
+    record_id: *const c_char,
+    flags: u64,
+    out_json: *mut *mut c_char,
+) -> i64 {
+    run("sz_capi_get_entity_by_record_id", || {
+        let handle = unsafe { env_arg(env) }?;
+        let entity = handle.engine.get_entity(
+            crate::types::EntityRef::Record {
+                data_source: unsafe { str_arg(data_source_code, "data_source_code") }?,
+                record_id: unsafe { str_arg(record_id, "record_id") }?,
+            },
+            flags_arg(flags),
+        )?;
+        unsafe { out_string(out_json, entity) }
+    })
+}
+
+/// Searches for entities matching a JSON attribute document and returns the
+/// response in `out_json`.
+///
+/// # Safety
+///
+/// `env` must be a live handle, `attributes` a valid NUL-terminated string,
+/// and `out_json` a valid pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sz_capi_search_by_attributes(
+    env: *const SzCapiEnvironment,
+    attributes: *const c_char,
+    flags: u64,
+    out_json: *mut *mut c_char,
+) -> i64 {
+    run("sz_capi_search_by_attributes", || {
+        let handle = unsafe { env_arg(env) }?;
+        let response = handle.engine.search_by_attributes(
+            unsafe { str_arg(attributes, "attributes") }?,
+            None,
+            flags_arg(flags),
+        )?;
+        unsafe { out_string(out_json, response) }
+    })
+}
+
+/// Loads every record of a JSON-lines file with the bulk loader (see the
+/// `ingest` module) and writes the number of records loaded to
+/// `out_records_loaded`, which may be null.
+///
+/// # Safety
+///
+/// `env` must be a live handle, `path` a valid NUL-terminated string, and
+/// `out_records_loaded` null or a valid pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sz_capi_load_file(
+    env: *const SzCapiEnvironment,
+    path: *const c_char,
+    out_records_loaded: *mut u64,
+) -> i64 {
+    run("sz_capi_load_file", || {
+        let handle = unsafe { env_arg(env) }?;
+        let path = unsafe { str_arg(path, "path") }?;
+        let file = std::fs::File::open(path)
+            .map_err(|e| SzError::bad_input(format!("Failed opening {path}: {e}")))?;
+        let summary = ingest::load_from_reader(handle.engine.as_ref(), BufReader::new(file))?;
+        if !out_records_loaded.is_null() {
+            unsafe { *out_records_loaded = summary.records_loaded };
+        }
+        Ok(())
+    })
+}
+
+/// Processes redo records with up to `max_workers` threads until fewer than
+/// `count_below` remain (see `redo::SzRedoProcessor::drain_until`), and
+/// writes the number processed to `out_processed`, which may be null.
+///
+/// # Safety
+///
+/// `env` must be a live handle and `out_processed` null or a valid pointer.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sz_capi_redo_drain(
+    env: *const SzCapiEnvironment,
+    count_below: i64,
+    max_workers: usize,
+    out_processed: *mut u64,
+) -> i64 {
+    run("sz_capi_redo_drain", || {
+        let handle = unsafe { env_arg(env) }?;
+        let summary = SzRedoProcessor::new(handle.env.clone())
+            .with_workers(1, max_workers)
+            .drain_until(count_below)?;
+        if !out_processed.is_null() {
+            unsafe { *out_processed = summary.processed };
+        }
+        Ok(())
+    })
+}
+
+/// Describes the last failed call on this thread, or returns null if the
+/// last call succeeded. The string stays valid until the next call on this
+/// thread and must not be freed.
+#[unsafe(no_mangle)]
+pub extern "C" fn sz_capi_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map_or(ptr::null(), |e| e.as_ptr()))
+}
+
+/// Releases a string returned through an `out_*` pointer. Null is ignored.
+///
+/// # Safety
+///
+/// `value` must be a string returned by this library that was not freed
+/// before, or null.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sz_capi_free_string(value: *mut c_char) {
+    if !value.is_null() {
+        drop(unsafe { CString::from_raw(value) });
+    }
+}
+
+/// Runs `call`, recording its error as the thread's last error and catching
+/// panics. Returns the C result code.
+fn run(operation: &str, call: impl FnOnce() -> SzResult<()>) -> i64 {
+    let result = catch_unwind(AssertUnwindSafe(call))
+        .unwrap_or_else(|_| Err(SzError::unhandled(format!("panic during {operation}"))));
+    let (code, message) = match result {
+        Ok(()) => (0, None),
+        Err(e) => (
+            e.error_code().unwrap_or(SDK_ERROR_CODE),
+            // A message with an interior NUL is cut at the NUL.
+            Some(CString::new(e.to_string()).unwrap_or_else(|nul| {
+                let end = nul.nul_position();
+                CString::new(&nul.into_vec()[..end]).unwrap_or_default()
+            })),
+        ),
+    };
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+    code
+}
+
+unsafe fn str_arg<'a>(value: *const c_char, name: &str) -> SzResult<&'a str> {
+    if value.is_null() {
+        return Err(SzError::bad_input(format!("{name} must not be null")));
+    }
+    unsafe { CStr::from_ptr(value) }
+        .to_str()
+        .map_err(|e| SzError::bad_input(format!("{name} is not UTF-8: {e}")))
+}
+
+unsafe fn env_arg<'a>(env: *const SzCapiEnvironment) -> SzResult<&'a SzCapiEnvironment> {
+    unsafe { env.as_ref() }.ok_or_else(|| SzError::bad_input("env must not be null"))
+}
+
+unsafe fn out_string(out: *mut *mut c_char, value: String) -> SzResult<()> {
+    if out.is_null() {
+        return Err(SzError::bad_input("output pointer must not be null"));
+    }
+    let value = CString::new(value)?;
+    unsafe { *out = value.into_raw() };
+    Ok(())
+}
+
+fn flags_arg(flags: u64) -> Option<SzFlags> {
+    (flags != SZ_FLAGS_DEFAULT).then(|| SzFlags::from_bits_retain(flags))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_errors_reach_last_error() {
+        let mut out = ptr::null_mut();
+        let code =
+            unsafe { sz_capi_search_by_attributes(ptr::null(), c"{}".as_ptr(), 0, &mut out) };
+        assert_eq!(code, SDK_ERROR_CODE);
+        assert!(out.is_null());
+        let message = unsafe { CStr::from_ptr(sz_capi_last_error()) };
+        assert!(message.to_str().unwrap().contains("env must not be null"));
+
+        assert_eq!(run("test", || Ok(())), 0);
+        assert!(sz_capi_last_error().is_null());
+
+        assert_eq!(run("test", || panic!("boom")), SDK_ERROR_CODE);
+        let message = unsafe { CStr::from_ptr(sz_capi_last_error()) };
+        assert!(message.to_str().unwrap().contains("panic during test"));
+    }
+
+    #[test]
+    fn test_out_string_round_trip() {
+        let mut out = ptr::null_mut();
+        unsafe { out_string(&mut out, "{\"A\":1}".to_string()) }.unwrap();
+        assert_eq!(
+            unsafe { CStr::from_ptr(out) }.to_str().unwrap(),
+            "{\"A\":1}"
+        );
+        unsafe { sz_capi_free_string(out) };
+        unsafe { sz_capi_free_string(ptr::null_mut()) };
+        assert!(unsafe { out_string(&mut out, "a\0b".to_string()) }.is_err());
+    }
+
+    #[test]
+    fn test_flags_sentinel() {
+        assert_eq!(flags_arg(SZ_FLAGS_DEFAULT), None);
+        assert_eq!(flags_arg(-1i64 as u64), None);
+        assert_eq!(flags_arg(0), Some(SzFlags::empty()));
+        assert_eq!(
+            flags_arg(SzFlags::WITH_INFO.bits()),
+            Some(SzFlags::WITH_INFO)
+        );
+    }
+}
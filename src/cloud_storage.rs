@@ -0,0 +1,305 @@
+//! Blocking access to cloud object storage for the `object-store` feature.
+//!
+//! [`ObjectLocation`] resolves an `s3://`, `gs://`, `az://` or `file://`
+//! URL to an `object_store` store and drives it from synchronous code on a
+//! private tokio runtime. Credentials and regions come from the provider's
+//! usual environment variables (`AWS_*`, `GOOGLE_*`, `AZURE_*`).
+//!
+//! Uploads are multipart: [`ObjectLocation::upload`] hands out a `Write`
+//! that sends each filled part while the caller keeps writing, and the
+//! upload is completed only when the caller succeeds, or aborted otherwise.
+//! Downloads are streamed through [`ObjectLocation::download`], so neither
+//! direction holds the whole object in memory.
+
+use crate::error::{SzError, SzResult};
+use bytes::Bytes;
+use futures_util::StreamExt;
+use futures_util::stream::BoxStream;
+use object_store::aws::AmazonS3Builder;
+use object_store::azure::MicrosoftAzureBuilder;
+use object_store::gcp::GoogleCloudStorageBuilder;
+use object_store::local::LocalFileSystem;
+use object_store::path::Path;
+use object_store::{ObjectStore, ObjectStoreScheme, WriteMultipart};
+use std::io::{self, Read, Write};
+use tokio::runtime::Runtime;
+use url::Url;
+
+/// Size of each uploaded part; S3 requires at least 5 MiB for all but the last.
+const PART_SIZE: usize = 5 * 1024 * 1024;
+
+/// Parts uploaded at once before writes wait for one to finish.
+const MAX_PARTS_IN_FLIGHT: usize = 4;
+
+/// An object addressed by URL, with the store and runtime to reach it.
+pub(crate) struct ObjectLocation {
+    runtime: Runtime,
+    store: Box<dyn ObjectStore>,
+    path: Path,
+    part_size: usize,
+}
+
+impl ObjectLocation {
+    /// Resolves `url` to its store, reading credentials from the environment.
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::BadInput` - The URL does not parse or is not an `s3://`,
+    ///   `gs://`, `az://` or `file://` URL
+    /// * `SzError::Configuration` - The store could not be configured, e.g.
+    ///   for missing credentials
+    pub(crate) fn parse(url: &str) -> SzResult<Self> {
+        let parsed = Url::parse(url)
+            .map_err(|e| SzError::bad_input(format!("Invalid object store URL {url}: {e}")))?;
+        let (scheme, path) = ObjectStoreScheme::parse(&parsed)
+            .map_err(|e| SzError::bad_input(format!("Invalid object store URL {url}: {e}")))?;
+        let store: Box<dyn ObjectStore> = match scheme {
+            ObjectStoreScheme::AmazonS3 => Box::new(
+                AmazonS3Builder::from_env()
+                    .with_url(url)
+                    .build()
+                    .map_err(|e| configuration_error(url, &e))?,
+            ),
+            ObjectStoreScheme::GoogleCloudStorage => Box::new(
+                GoogleCloudStorageBuilder::from_env()
+                    .with_url(url)
+                    .build()
+                    .map_err(|e| configuration_error(url, &e))?,
+            ),
+            ObjectStoreScheme::MicrosoftAzure => Box::new(
+                MicrosoftAzureBuilder::from_env()
+                    .with_url(url)
+                    .build()
+                    .map_err(|e| configuration_error(url, &e))?,
+            ),
+            ObjectStoreScheme::Local => Box::new(LocalFileSystem::new()),
+            _ => {
+                return Err(SzError::bad_input(format!(
+                    "Unsupported object store URL {url}: expected s3://, gs://, az:// or file://"
+                )));
+            }
+        };
+        let runtime = tokio::runtime::Builder::new_multi_thread()
+            .worker_threads(2)
+            .thread_name("sz-object-store")
+            .enable_all()
+            .build()
+            .map_err(|e| SzError::unknown(format!("Failed to start object store runtime: {e}")))?;
+        Ok(Self {
+            runtime,
+            store,
+            path,
+            part_size: PART_SIZE,
+        })
+    }
+
+    /// Uploads the object as a multipart upload filled by `write`.
+    ///
+    /// The upload is completed when `write` returns `Ok` and aborted when it
+    /// returns an error, so a failed export never leaves a partial object.
+    pub(crate) fn upload<T>(
+        &self,
+        write: impl FnOnce(&mut MultipartWriter<'_>) -> SzResult<T>,
+    ) -> SzResult<T> {
+        let upload = self
+            .runtime
+            .block_on(self.store.put_multipart(&self.path))
+            .map_err(|e| storage_error(&e))?;
+        let mut writer = MultipartWriter {
+            runtime: &self.runtime,
+            upload: WriteMultipart::new_with_chunk_size(upload, self.part_size),
+        };
+        match write(&mut writer) {
+            Ok(value) => {
+                self.runtime
+                    .block_on(writer.upload.finish())
+                    .map_err(|e| storage_error(&e))?;
+                Ok(value)
+            }
+            Err(e) => {
+                let _ = self.runtime.block_on(writer.upload.abort());
+                Err(e)
+            }
+        }
+    }
+
+    /// Opens the object for streaming reads.
+    pub(crate) fn download(&self) -> SzResult<ObjectReader<'_>> {
+        let result = self
+            .runtime
+            .block_on(self.store.get(&self.path))
+            .map_err(|e| storage_error(&e))?;
+        Ok(ObjectReader {
+            runtime: &self.runtime,
+            stream: result.into_stream(),
+            chunk: Bytes::new(),
+        })
+    }
+}
+
+impl std::fmt::Debug for ObjectLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ObjectLocation")
+            .field("store", &self.store.to_string())
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+/// `Write` side of a multipart upload; see [`ObjectLocation::upload`].
+pub(crate) struct MultipartWriter<'a> {
+    runtime: &'a Runtime,
+    upload: WriteMultipart,
+}
+
+impl Write for MultipartWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        {
+            // Filled parts are spawned onto the runtime as they are written.
+            let _context = self.runtime.enter();
+            self.upload.write(buf);
+        }
+        self.runtime
+            .block_on(self.upload.wait_for_capacity(MAX_PARTS_IN_FLIGHT))
+            .map_err(io::Error::other)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// `Read` side of a streamed download; see [`ObjectLocation::download`].
+pub(crate) struct ObjectReader<'a> {
+    runtime: &'a Runtime,
+    stream: BoxStream<'static, object_store::Result<Bytes>>,
+    chunk: Bytes,
+}
+
+impl Read for ObjectReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.chunk.is_empty() {
+            match self.runtime.block_on(self.stream.next()) {
+                Some(chunk) => self.chunk = chunk.map_err(io::Error::other)?,
+                None => return Ok(0),
+            }
+        }
+        let n = buf.len().min(self.chunk.len());
+        buf[..n].copy_from_slice(&self.chunk.split_to(n));
+        Ok(n)
+    }
+}
+
+/// Maps a failed object store request. Request failures left after the
+/// client's own retries (network errors, timeouts, server errors) are
+/// retryable; a missing object is not found.
+pub(crate) fn storage_error(e: &object_store::Error) -> SzError {
+    let message = format!("Object store request failed: {e}");
+    match e {
+        object_store::Error::Generic { .. } => SzError::retryable(message),
+        object_store::Error::NotFound { .. } => SzError::not_found(message),
+        _ => SzError::unknown(message),
+    }
+}
+
+/// The object store failure behind an I/O error of a [`MultipartWriter`] or
+/// [`ObjectReader`], mapped by [`storage_error`].
+pub(crate) fn io_storage_error(e: &io::Error) -> Option<SzError> {
+    e.get_ref()?
+        .downcast_ref::<object_store::Error>()
+        .map(storage_error)
+}
+
+fn configuration_error(url: &str, e: &object_store::Error) -> SzError {
+    SzError::configuration(format!("Cannot configure object store for {url}: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_url(name: &str) -> (std::path::PathBuf, String) {
+        let path =
+            std::env::temp_dir().join(format!("sz_object_store_{name}_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let url = Url::from_file_path(&path).unwrap().to_string();
+        (path, url)
+    }
+
+    #[test]
+    fn test_multipart_upload_round_trip() {
+        let (path, url) = temp_url("round_trip");
+        let mut location = ObjectLocation::parse(&url).unwrap();
+        location.part_size = 16;
+        let data: String = (0..100).map(|i| format!("{{\"line\":{i}}}\n")).collect();
+
+        let written = location
+            .upload(|writer| {
+                for line in data.lines() {
+                    writeln!(writer, "{line}").unwrap();
+                }
+                Ok(data.len())
+            })
+            .unwrap();
+        assert_eq!(written, data.len());
+
+        let mut read = String::new();
+        location
+            .download()
+            .unwrap()
+            .read_to_string(&mut read)
+            .unwrap();
+        assert_eq!(read, data);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_failed_upload_is_aborted() {
+        let (path, url) = temp_url("aborted");
+        let mut location = ObjectLocation::parse(&url).unwrap();
+        location.part_size = 4;
+
+        let result: SzResult<()> = location.upload(|writer| {
+            writer.write_all(b"partial export").unwrap();
+            Err(SzError::unknown("export failed"))
+        });
+        assert!(result.is_err());
+        assert!(!path.exists());
+        assert!(location.download().is_err());
+    }
+
+    #[test]
+    fn test_storage_errors() {
+        let generic = object_store::Error::Generic {
+            store: "S3",
+            source: "connection reset".into(),
+        };
+        assert!(storage_error(&generic).is_retryable());
+        let missing = object_store::Error::NotFound {
+            path: "exports/entities.jsonl".to_string(),
+            source: "404".into(),
+        };
+        assert!(matches!(storage_error(&missing), SzError::NotFound(_)));
+
+        let io = io::Error::other(generic);
+        assert!(io_storage_error(&io).is_some_and(|e| e.is_retryable()));
+        assert!(io_storage_error(&io::Error::other("disk full")).is_none());
+    }
+
+    #[test]
+    fn test_parse_rejects_unsupported_urls() {
+        assert!(
+            ObjectLocation::parse("not a url")
+                .unwrap_err()
+                .is_bad_input()
+        );
+        assert!(
+            ObjectLocation::parse("ftp://host/data.jsonl")
+                .unwrap_err()
+                .is_bad_input()
+        );
+        assert!(ObjectLocation::parse("s3://bucket/exports/entities.jsonl").is_ok());
+        assert!(ObjectLocation::parse("gs://bucket/exports/entities.jsonl").is_ok());
+    }
+}
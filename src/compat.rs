@@ -0,0 +1,476 @@
+//! Compatibility shims for code ported from the Senzing v3 (G2) SDKs.
+//!
+//! Teams moving from the G2 Java and Python SDKs can port call sites one at
+//! a time:
+//!
+//! - [`G2EngineCompat`] adds the G2 engine method names to every
+//!   `SzEngine`, such as `add_record_with_info` and
+//!   `get_entity_by_record_id`. Each is `#[deprecated]` with a note naming
+//!   its v4 replacement, so the compiler lists what is left to port.
+//! - [`replacement`] looks up the v4 replacement of a G2 method name, for
+//!   porting scripts and code review.
+//! - [`upgrade_settings`] rewrites a v3 engine settings document for v4 and
+//!   returns a warning for every change.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::compat::{self, G2EngineCompat};
+//! use sz_rust_sdk::prelude::*;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_compat")?;
+//! let engine = env.get_engine()?;
+//! #[allow(deprecated)]
+//! let info = engine.add_record_with_info("TEST", "1", r#"{"NAME_FULL": "Ann Lee"}"#)?;
+//! println!("{info}");
+//!
+//! let name = compat::replacement("addRecordWithInfo").unwrap();
+//! assert_eq!(name.replacement, "SzEngine::add_record with SzFlags::WITH_INFO");
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::error::{SzError, SzResult};
+use crate::flags::SzFlags;
+use crate::traits::SzEngine;
+use crate::types::{EntityId, EntityRef, ExportHandle, JsonString};
+use serde_json::{Map, Value};
+
+/// A G2 SDK method and its v4 replacement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LegacyName {
+    /// G2 class and method, e.g. `"G2Engine.addRecordWithInfo"`
+    pub legacy: &'static str,
+    /// What to call instead in this SDK
+    pub replacement: &'static str,
+}
+
+const fn name(legacy: &'static str, replacement: &'static str) -> LegacyName {
+    LegacyName {
+        legacy,
+        replacement,
+    }
+}
+
+/// The G2 methods with a v4 replacement, by class.
+pub const LEGACY_NAMES: &[LegacyName] = &[
+    name("G2Engine.addRecord", "SzEngine::add_record"),
+    name(
+        "G2Engine.addRecordWithInfo",
+        "SzEngine::add_record with SzFlags::WITH_INFO",
+    ),
+    name("G2Engine.deleteRecord", "SzEngine::delete_record"),
+    name(
+        "G2Engine.deleteRecordWithInfo",
+        "SzEngine::delete_record with SzFlags::WITH_INFO",
+    ),
+    name("G2Engine.reevaluateRecord", "SzEngine::reevaluate_record"),
+    name(
+        "G2Engine.reevaluateRecordWithInfo",
+        "SzEngine::reevaluate_record with SzFlags::WITH_INFO",
+    ),
+    name("G2Engine.reevaluateEntity", "SzEngine::reevaluate_entity"),
+    name(
+        "G2Engine.reevaluateEntityWithInfo",
+        "SzEngine::reevaluate_entity with SzFlags::WITH_INFO",
+    ),
+    name(
+        "G2Engine.getEntityByEntityID",
+        "SzEngine::get_entity with EntityRef::Id",
+    ),
+    name(
+        "G2Engine.getEntityByRecordID",
+        "SzEngine::get_entity with EntityRef::from_record",
+    ),
+    name("G2Engine.getRecord", "SzEngine::get_record"),
+    name(
+        "G2Engine.searchByAttributes",
+        "SzEngine::search_by_attributes",
+    ),
+    name(
+        "G2Engine.findPathByEntityID",
+        "SzEngine::find_path_by_entity_id",
+    ),
+    name(
+        "G2Engine.findPathByRecordID",
+        "SzEngine::find_path_by_record_id",
+    ),
+    name(
+        "G2Engine.findNetworkByEntityID",
+        "SzEngine::find_network_by_entity_id",
+    ),
+    name(
+        "G2Engine.findNetworkByRecordID",
+        "SzEngine::find_network_by_record_id",
+    ),
+    name(
+        "G2Engine.findInterestingEntitiesByEntityID",
+        "SzEngine::find_interesting_entities with EntityRef::Id",
+    ),
+    name(
+        "G2Engine.findInterestingEntitiesByRecordID",
+        "SzEngine::find_interesting_entities with EntityRef::from_record",
+    ),
+    name("G2Engine.whyEntities", "SzEngine::why_entities"),
+    name("G2Engine.whyRecords", "SzEngine::why_records"),
+    name(
+        "G2Engine.whyEntityByRecordID",
+        "SzEngine::why_record_in_entity",
+    ),
+    name("G2Engine.howEntityByEntityID", "SzEngine::how_entity"),
+    name(
+        "G2Engine.getVirtualEntityByRecordID",
+        "SzEngine::get_virtual_entity",
+    ),
+    name("G2Engine.getRedoRecord", "SzEngine::get_redo_record"),
+    name("G2Engine.countRedoRecords", "SzEngine::count_redo_records"),
+    name(
+        "G2Engine.processRedoRecord",
+        "SzEngine::process_redo_record",
+    ),
+    name(
+        "G2Engine.processRedoRecordWithInfo",
+        "SzEngine::process_redo_record with SzFlags::WITH_INFO",
+    ),
+    name(
+        "G2Engine.exportJSONEntityReport",
+        "SzEngine::export_json_entity_report",
+    ),
+    name(
+        "G2Engine.exportCSVEntityReport",
+        "SzEngine::export_csv_entity_report",
+    ),
+    name("G2Engine.fetchNext", "SzEngine::fetch_next"),
+    name("G2Engine.closeExport", "SzEngine::close_export_report"),
+    name("G2Engine.stats", "SzEngine::get_stats"),
+    name("G2Engine.primeEngine", "SzEngine::prime_engine"),
+    name(
+        "G2Engine.getActiveConfigID",
+        "SzEnvironment::get_active_config_id",
+    ),
+    name("G2Engine.reinit", "SzEnvironment::reinitialize"),
+    name("G2Engine.purgeRepository", "SzDiagnostic::purge_repository"),
+    name("G2Product.version", "SzProduct::get_version"),
+    name("G2Product.license", "SzProduct::get_license"),
+    name(
+        "G2Diagnostic.checkDBPerf",
+        "SzDiagnostic::check_repository_performance",
+    ),
+    name(
+        "G2Diagnostic.getDatastoreInfo",
+        "SzDiagnostic::get_repository_info",
+    ),
+    name("G2Diagnostic.getFeature", "SzDiagnostic::get_feature"),
+    name("G2Config.create", "SzConfigManager::create_config"),
+    name(
+        "G2Config.load",
+        "SzConfigManager::create_config_from_definition",
+    ),
+    name("G2Config.save", "SzConfig::export"),
+    name("G2Config.addDataSource", "SzConfig::register_data_source"),
+    name(
+        "G2Config.deleteDataSource",
+        "SzConfig::unregister_data_source",
+    ),
+    name(
+        "G2Config.listDataSources",
+        "SzConfig::get_data_source_registry",
+    ),
+    name("G2ConfigMgr.addConfig", "SzConfigManager::register_config"),
+    name(
+        "G2ConfigMgr.getConfig",
+        "SzConfigManager::create_config_from_id, then SzConfig::export",
+    ),
+    name(
+        "G2ConfigMgr.getConfigList",
+        "SzConfigManager::get_config_registry",
+    ),
+    name(
+        "G2ConfigMgr.getDefaultConfigID",
+        "SzConfigManager::get_default_config_id",
+    ),
+    name(
+        "G2ConfigMgr.setDefaultConfigID",
+        "SzConfigManager::set_default_config_id",
+    ),
+    name(
+        "G2ConfigMgr.replaceDefaultConfigID",
+        "SzConfigManager::replace_default_config_id",
+    ),
+];
+
+/// Looks up the v4 replacement of a G2 method.
+///
+/// `method` may include the class (`"G2Engine.addRecordWithInfo"`) or not,
+/// and may be written in camel case or, as in the G2 Python SDK, in snake
+/// case (`"add_record_with_info"`). Without a class the first class with
+/// that method is used.
+pub fn replacement(method: &str) -> Option<&'static LegacyName> {
+    let (class, method) = match method.rsplit_once('.') {
+        Some((class, method)) => (Some(class), method),
+        None => (None, method),
+    };
+    let wanted = normalized(method);
+    LEGACY_NAMES.iter().find(|name| {
+        let (legacy_class, legacy_method) = name.legacy.split_once('.').unwrap_or_default();
+        class.is_none_or(|class| class.eq_ignore_ascii_case(legacy_class))
+            && normalized(legacy_method) == wanted
+    })
+}
+
+/// Lowercases `method` and drops underscores, so camel and snake case compare
+/// equal.
+fn normalized(method: &str) -> String {
+    method
+        .chars()
+        .filter(|&c| c != '_')
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Engine settings keys that v4 does not read, with their replacement and
+/// why.
+pub const LEGACY_SETTINGS: &[(&str, Option<&str>, &str)] = &[(
+    "SQL.G2CONFIGFILE",
+    None,
+    "the configuration is stored in the repository; register it with SzConfigManager",
+)];
+
+/// Result of [`upgrade_settings`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SettingsUpgrade {
+    /// Settings JSON to pass to `SzEnvironmentCore::new` or `get_instance`
+    pub settings: String,
+    /// One message per change, empty if the settings needed none
+    pub warnings: Vec<String>,
+}
+
+/// Rewrites a v3 engine settings document for v4.
+///
+/// Section and key names are uppercased, as v4 expects them, and the keys in
+/// [`LEGACY_SETTINGS`] are renamed or removed. Values are kept as they are.
+///
+/// # Errors
+///
+/// * `SzError::BadInput` - `settings` is not a JSON object of sections
+///
+/// # Example
+///
+/// ```
+/// use sz_rust_sdk::compat;
+///
+/// let upgrade = compat::upgrade_settings(
+///     r#"{"pipeline": {"configPath": "/etc/opt/senzing"},
+///         "SQL": {"CONNECTION": "internal://", "G2CONFIGFILE": "/etc/g2config.json"}}"#,
+/// )?;
+/// assert_eq!(
+///     upgrade.settings,
+///     r#"{"PIPELINE":{"CONFIGPATH":"/etc/opt/senzing"},"SQL":{"CONNECTION":"internal://"}}"#
+/// );
+/// assert_eq!(upgrade.warnings.len(), 3);
+/// # Ok::<(), sz_rust_sdk::error::SzError>(())
+/// ```
+pub fn upgrade_settings(settings: &str) -> SzResult<SettingsUpgrade> {
+    let Value::Object(sections) = serde_json::from_str(settings)? else {
+        return Err(SzError::bad_input("engine settings must be a JSON object"));
+    };
+    let mut warnings = Vec::new();
+    let mut upgraded = Map::new();
+    for (section, keys) in sections {
+        let section_name = section.to_uppercase();
+        if section_name != section {
+            warnings.push(format!("section {section} renamed to {section_name}"));
+        }
+        let Value::Object(keys) = keys else {
+            upgraded.insert(section_name, keys);
+            continue;
+        };
+        let target = upgraded
+            .entry(section_name.clone())
+            .or_insert_with(|| Value::Object(Map::new()));
+        for (key, value) in keys {
+            let mut path = format!("{section_name}.{}", key.to_uppercase());
+            if let Some((_, renamed, reason)) =
+                LEGACY_SETTINGS.iter().find(|(legacy, ..)| *legacy == path)
+            {
+                match renamed {
+                    Some(renamed) => {
+                        warnings.push(format!("{section}.{key} renamed to {renamed}: {reason}"));
+                        path = renamed.to_string();
+                    }
+                    None => {
+                        warnings.push(format!("{section}.{key} removed: {reason}"));
+                        continue;
+                    }
+                }
+            } else if path != format!("{section_name}.{key}") {
+                warnings.push(format!("{section}.{key} renamed to {path}"));
+            }
+            let (_, upgraded_key) = path.split_once('.').unwrap_or_default();
+            if let Value::Object(target) = target {
+                target.insert(upgraded_key.to_string(), value);
+            }
+        }
+    }
+    Ok(SettingsUpgrade {
+        settings: Value::Object(upgraded).to_string(),
+        warnings,
+    })
+}
+
+/// G2 engine method names on top of `SzEngine`.
+///
+/// Implemented for every engine. Each method calls its v4 replacement and
+/// is deprecated, so ported code builds and the warnings list the calls left
+/// to change.
+pub trait G2EngineCompat: SzEngine {
+    /// `G2Engine.addRecordWithInfo`
+    #[deprecated(note = "use SzEngine::add_record with Some(SzFlags::WITH_INFO)")]
+    fn add_record_with_info(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        record_definition: &str,
+    ) -> SzResult<JsonString> {
+        self.add_record(
+            data_source_code,
+            record_id,
+            record_definition,
+            Some(SzFlags::WITH_INFO),
+        )
+    }
+
+    /// `G2Engine.deleteRecordWithInfo`
+    #[deprecated(note = "use SzEngine::delete_record with Some(SzFlags::WITH_INFO)")]
+    fn delete_record_with_info(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+    ) -> SzResult<JsonString> {
+        self.delete_record(data_source_code, record_id, Some(SzFlags::WITH_INFO))
+    }
+
+    /// `G2Engine.reevaluateRecordWithInfo`
+    #[deprecated(note = "use SzEngine::reevaluate_record with Some(SzFlags::WITH_INFO)")]
+    fn reevaluate_record_with_info(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+    ) -> SzResult<JsonString> {
+        self.reevaluate_record(data_source_code, record_id, Some(SzFlags::WITH_INFO))
+    }
+
+    /// `G2Engine.reevaluateEntityWithInfo`
+    #[deprecated(note = "use SzEngine::reevaluate_entity with Some(SzFlags::WITH_INFO)")]
+    fn reevaluate_entity_with_info(&self, entity_id: EntityId) -> SzResult<JsonString> {
+        self.reevaluate_entity(entity_id, Some(SzFlags::WITH_INFO))
+    }
+
+    /// `G2Engine.getEntityByEntityID`
+    #[deprecated(note = "use SzEngine::get_entity with EntityRef::Id")]
+    fn get_entity_by_entity_id(
+        &self,
+        entity_id: EntityId,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.get_entity(EntityRef::Id(entity_id), flags)
+    }
+
+    /// `G2Engine.getEntityByRecordID`
+    #[deprecated(note = "use SzEngine::get_entity with EntityRef::from_record")]
+    fn get_entity_by_record_id(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.get_entity(EntityRef::from_record(data_source_code, record_id), flags)
+    }
+
+    /// `G2Engine.whyEntityByRecordID`
+    #[deprecated(note = "use SzEngine::why_record_in_entity")]
+    fn why_entity_by_record_id(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.why_record_in_entity(data_source_code, record_id, flags)
+    }
+
+    /// `G2Engine.howEntityByEntityID`
+    #[deprecated(note = "use SzEngine::how_entity")]
+    fn how_entity_by_entity_id(
+        &self,
+        entity_id: EntityId,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.how_entity(entity_id, flags)
+    }
+
+    /// `G2Engine.processRedoRecordWithInfo`
+    #[deprecated(note = "use SzEngine::process_redo_record with Some(SzFlags::WITH_INFO)")]
+    fn process_redo_record_with_info(&self, redo_record: &str) -> SzResult<JsonString> {
+        self.process_redo_record(redo_record, Some(SzFlags::WITH_INFO))
+    }
+
+    /// `G2Engine.stats`
+    #[deprecated(note = "use SzEngine::get_stats")]
+    fn stats(&self) -> SzResult<JsonString> {
+        self.get_stats()
+    }
+
+    /// `G2Engine.closeExport`
+    #[deprecated(note = "use SzEngine::close_export_report")]
+    fn close_export(&self, export_handle: ExportHandle) -> SzResult<()> {
+        self.close_export_report(export_handle)
+    }
+}
+
+impl<E: SzEngine + ?Sized> G2EngineCompat for E {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replacement_lookup() {
+        let expected = "SzEngine::get_entity with EntityRef::from_record";
+        for method in [
+            "getEntityByRecordID",
+            "G2Engine.getEntityByRecordID",
+            "get_entity_by_record_id",
+            "g2engine.GETENTITYBYRECORDID",
+        ] {
+            assert_eq!(
+                replacement(method).map(|name| name.replacement),
+                Some(expected),
+                "{method}"
+            );
+        }
+        assert!(replacement("G2Product.getEntityByRecordID").is_none());
+        assert!(replacement("addRecordWithoutInfo").is_none());
+        assert_eq!(replacement("save").unwrap().legacy, "G2Config.save");
+    }
+
+    #[test]
+    fn test_upgrade_settings() {
+        let current = r#"{"PIPELINE":{"CONFIGPATH":"/etc"},"SQL":{"CONNECTION":"internal://"}}"#;
+        let upgrade = upgrade_settings(current).unwrap();
+        assert_eq!(upgrade.settings, current);
+        assert!(upgrade.warnings.is_empty());
+
+        let upgrade = upgrade_settings(r#"{"sql":{"g2configfile":"/g2.json"}}"#).unwrap();
+        assert_eq!(upgrade.settings, r#"{"SQL":{}}"#);
+        assert_eq!(
+            upgrade.warnings,
+            [
+                "section sql renamed to SQL",
+                "sql.g2configfile removed: the configuration is stored in the repository; \
+                 register it with SzConfigManager",
+            ]
+        );
+
+        assert!(upgrade_settings("[]").unwrap_err().is_bad_input());
+    }
+}
@@ -0,0 +1,123 @@
+//! Idempotent default-configuration setup.
+//!
+//! A fresh repository has no default configuration, and an application
+//! usually needs a few data sources registered before it can load records.
+//! [`SzBootstrap::ensure_default_config`] brings the repository to that state
+//! at startup and does nothing when it is already there, so every replica of
+//! a service can call it unconditionally.
+
+use crate::config_tools::registered_data_sources;
+use crate::core::SzEnvironmentCore;
+use crate::error::{SzError, SzResult};
+use crate::traits::{SzConfig, SzConfigManager, SzEnvironment};
+use crate::types::ConfigId;
+
+/// Number of times a default-config update is retried after another process
+/// replaced the default first.
+const MAX_ATTEMPTS: usize = 5;
+
+/// Startup setup for a repository's default configuration.
+pub struct SzBootstrap;
+
+impl SzBootstrap {
+    /// Makes sure the repository has a default configuration with the given
+    /// data sources registered, and that the environment uses it.
+    ///
+    /// * When there is no default configuration, one is created from the
+    ///   template configuration with `data_sources` registered.
+    /// * When the default configuration lacks some of `data_sources`, a new
+    ///   version adding them is registered and swapped in with
+    ///   [`compare_and_set_default_config_id`](SzConfigManager::compare_and_set_default_config_id).
+    ///   If another process replaced the default in the meantime, the check
+    ///   is repeated against the new default.
+    /// * Finally the environment is reinitialized if its active configuration
+    ///   is not the default.
+    ///
+    /// Data source codes are compared case-insensitively. Returns the
+    /// default configuration ID.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::config_tools::SzBootstrap;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_bootstrap")?;
+    /// let config_id = SzBootstrap::ensure_default_config(&env, &["CUSTOMERS", "WATCHLIST"])?;
+    /// assert_eq!(env.get_active_config_id()?, config_id);
+    /// # Ok::<(), SzError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::ReplaceConflict` - The default kept changing concurrently
+    ///   and the update did not succeed after several attempts
+    /// * Any error from the configuration manager or from reinitializing
+    pub fn ensure_default_config(
+        env: &SzEnvironmentCore,
+        data_sources: &[&str],
+    ) -> SzResult<ConfigId> {
+        let config_mgr = env.get_config_manager()?;
+        let config_id = ensure_data_sources(config_mgr.as_ref(), data_sources)?;
+        if env.get_active_config_id()? != config_id {
+            env.reinitialize(config_id)?;
+        }
+        Ok(config_id)
+    }
+}
+
+/// Brings the default configuration up to date and returns its ID.
+fn ensure_data_sources(
+    config_mgr: &dyn SzConfigManager,
+    data_sources: &[&str],
+) -> SzResult<ConfigId> {
+    let mut conflict = None;
+    for _ in 0..MAX_ATTEMPTS {
+        let current = config_mgr.get_default_config_id()?;
+        if current == 0 {
+            let config = config_mgr.create_config()?;
+            register_missing(config.as_ref(), data_sources)?;
+            let config_id =
+                config_mgr.register_config(&config.export()?, Some("Default configuration"))?;
+            // There is no compare-and-set from "no default"; a replica that
+            // races us here is reconciled on the next pass.
+            config_mgr.set_default_config_id(config_id)?;
+            continue;
+        }
+
+        let config = config_mgr.create_config_from_id(current)?;
+        let added = register_missing(config.as_ref(), data_sources)?;
+        if added.is_empty() {
+            return Ok(current);
+        }
+        let comment = format!("Added data sources: {}", added.join(", "));
+        let config_id = config_mgr.register_config(&config.export()?, Some(&comment))?;
+        match config_mgr.compare_and_set_default_config_id(current, config_id)? {
+            Ok(()) => return Ok(config_id),
+            Err(replaced) => conflict = Some(replaced),
+        }
+    }
+    Err(match conflict {
+        Some(replaced) => replaced.into(),
+        None => SzError::replace_conflict("Default configuration changed during bootstrap"),
+    })
+}
+
+/// Registers the data sources `config` does not have yet and returns them.
+fn register_missing(config: &dyn SzConfig, data_sources: &[&str]) -> SzResult<Vec<String>> {
+    let registered: Vec<String> = registered_data_sources(config)?
+        .into_iter()
+        .map(|code| code.to_uppercase())
+        .collect();
+    let mut added: Vec<String> = Vec::new();
+    for code in data_sources {
+        let code = code.to_uppercase();
+        if registered.contains(&code) || added.contains(&code) {
+            continue;
+        }
+        config.register_data_source(&code)?;
+        added.push(code);
+    }
+    Ok(added)
+}
@@ -0,0 +1,147 @@
+//! Helpers for building and inspecting configurations.
+//!
+//! These work on top of the [`SzConfig`](crate::traits::SzConfig) API and the
+//! exported configuration JSON.
+
+pub mod bootstrap;
+pub mod schema;
+pub mod templates;
+pub mod validation;
+
+pub use bootstrap::SzBootstrap;
+pub use schema::attribute_schema;
+pub use validation::{ConfigFinding, ConfigValidationReport};
+
+use crate::error::SzResult;
+use crate::traits::SzConfig;
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Deserialize)]
+struct DataSourceRegistry {
+    #[serde(rename = "DATA_SOURCES", default)]
+    data_sources: Vec<DataSourceEntry>,
+}
+
+#[derive(Deserialize)]
+struct DataSourceEntry {
+    #[serde(rename = "DSRC_CODE")]
+    code: String,
+}
+
+/// Returns the codes of the data sources registered in `config`.
+///
+/// # Example
+///
+/// ```
+/// # use sz_rust_sdk::helpers::ExampleEnvironment;
+/// use sz_rust_sdk::config_tools;
+/// use sz_rust_sdk::prelude::*;
+///
+/// # let env = ExampleEnvironment::initialize("doctest_registered_data_sources")?;
+/// let config = env.get_config_manager()?.create_config()?;
+/// config.register_data_source("CUSTOMERS")?;
+/// assert!(config_tools::registered_data_sources(&*config)?.contains("CUSTOMERS"));
+/// # Ok::<(), SzError>(())
+/// ```
+pub fn registered_data_sources(config: &dyn SzConfig) -> SzResult<BTreeSet<String>> {
+    let registry: DataSourceRegistry = serde_json::from_str(&config.get_data_source_registry()?)?;
+    Ok(registry
+        .data_sources
+        .into_iter()
+        .map(|ds| ds.code)
+        .collect())
+}
+
+/// Returns the feature type codes (`CFG_FTYPE`) defined in an exported
+/// configuration document.
+pub fn feature_types(config_json: &str) -> SzResult<BTreeSet<String>> {
+    let config: serde_json::Value = serde_json::from_str(config_json)?;
+    Ok(config["G2_CONFIG"]["CFG_FTYPE"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|ftype| ftype["FTYPE_CODE"].as_str())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Returns the feature type codes (`CFG_FTYPE`) an exported configuration
+/// document uses for candidate selection (`USED_FOR_CAND` is `Yes`), such as
+/// `NAME_KEY` or `SSN`.
+pub fn candidate_feature_types(config_json: &str) -> SzResult<BTreeSet<String>> {
+    let config: serde_json::Value = serde_json::from_str(config_json)?;
+    Ok(config["G2_CONFIG"]["CFG_FTYPE"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter(|ftype| {
+            ftype["USED_FOR_CAND"]
+                .as_str()
+                .is_some_and(|used| used.eq_ignore_ascii_case("yes"))
+        })
+        .filter_map(|ftype| ftype["FTYPE_CODE"].as_str())
+        .map(str::to_string)
+        .collect())
+}
+
+/// Returns the description of each feature type (`CFG_FTYPE`) in an
+/// exported configuration document, keyed by feature type code, e.g.
+/// `"DOB"` to `"Date of birth"`. Types without a description map to their
+/// code.
+///
+/// Pass the result to [`MatchKey::describe`](crate::types::MatchKey::describe)
+/// to explain match keys in the configuration's terms.
+pub fn feature_type_names(config_json: &str) -> SzResult<BTreeMap<String, String>> {
+    let config: serde_json::Value = serde_json::from_str(config_json)?;
+    Ok(config["G2_CONFIG"]["CFG_FTYPE"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|ftype| {
+            let code = ftype["FTYPE_CODE"].as_str()?;
+            let name = ftype["FTYPE_DESC"]
+                .as_str()
+                .filter(|desc| !desc.trim().is_empty())
+                .unwrap_or(code);
+            Some((code.to_string(), name.to_string()))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_feature_types() {
+        let config = r#"{"G2_CONFIG":{"CFG_FTYPE":[
+            {"FTYPE_ID":1,"FTYPE_CODE":"NAME"},{"FTYPE_ID":2,"FTYPE_CODE":"ADDRESS"}]}}"#;
+        let types = feature_types(config).unwrap();
+        assert!(types.contains("NAME"));
+        assert!(types.contains("ADDRESS"));
+        assert_eq!(types.len(), 2);
+        assert!(feature_types("{}").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_candidate_feature_types() {
+        let config = r#"{"G2_CONFIG":{"CFG_FTYPE":[
+            {"FTYPE_CODE":"NAME","USED_FOR_CAND":"No"},{"FTYPE_CODE":"NAME_KEY","USED_FOR_CAND":"Yes"},
+            {"FTYPE_CODE":"SSN","USED_FOR_CAND":"Yes"},{"FTYPE_CODE":"DOB"}]}}"#;
+        let types = candidate_feature_types(config).unwrap();
+        assert_eq!(
+            types,
+            BTreeSet::from(["NAME_KEY".to_string(), "SSN".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_feature_type_names() {
+        let config = r#"{"G2_CONFIG":{"CFG_FTYPE":[
+            {"FTYPE_CODE":"DOB","FTYPE_DESC":"Date of birth"},{"FTYPE_CODE":"SSN","FTYPE_DESC":""}]}}"#;
+        let names = feature_type_names(config).unwrap();
+        assert_eq!(names["DOB"], "Date of birth");
+        assert_eq!(names["SSN"], "SSN");
+    }
+}
@@ -0,0 +1,257 @@
+//! JSON Schema and Rust struct generation for record attributes.
+//!
+//! The attributes a record may use are defined by the configuration's
+//! `CFG_ATTR` table. [`attribute_schema`] turns that table into a JSON Schema
+//! (draft 2020-12) so records can be validated before they reach the engine,
+//! and [`record_struct_source`] turns the schema into a Rust struct for
+//! producers that build records in code.
+//!
+//! The schema lists every non-internal attribute as an optional string
+//! property. Other keys stay allowed, since records may carry payload
+//! attributes and lists of attribute groups (`"NAMES": [{"NAME_FULL": ...}]`);
+//! a list's items are checked against the same schema.
+//!
+//! # Example
+//!
+//! ```
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::config_tools;
+//! use sz_rust_sdk::prelude::*;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_attribute_schema")?;
+//! let config = env.get_config_manager()?.create_config()?;
+//! let schema = config_tools::attribute_schema(&*config)?;
+//! assert!(schema["properties"].get("NAME_FULL").is_some());
+//! # Ok::<(), SzError>(())
+//! ```
+//!
+//! # Generating structs in a build script
+//!
+//! Generation works from an exported configuration document and makes no
+//! native calls:
+//!
+//! ```no_run
+//! // build.rs
+//! use sz_rust_sdk::config_tools::schema;
+//!
+//! let config_json = std::fs::read_to_string("senzing_config.json")?;
+//! let schema = schema::attribute_schema_from_definition(&config_json)?;
+//! let source = schema::record_struct_source(&schema, "SenzingRecord");
+//! let out_dir = std::env::var("OUT_DIR")?;
+//! std::fs::write(format!("{out_dir}/senzing_record.rs"), source)?;
+//! println!("cargo::rerun-if-changed=senzing_config.json");
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::error::{SzError, SzResult};
+use crate::traits::SzConfig;
+use serde_json::{Map, Value, json};
+use std::fmt::Write;
+
+/// JSON Schema dialect of the generated schemas.
+pub const SCHEMA_DIALECT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// Returns a JSON Schema for records under `config`.
+pub fn attribute_schema(config: &dyn SzConfig) -> SzResult<Value> {
+    attribute_schema_from_definition(&config.export()?)
+}
+
+/// Returns a JSON Schema for records under an exported configuration
+/// document.
+///
+/// # Errors
+///
+/// `SzError::BadInput` if the document has no `G2_CONFIG.CFG_ATTR` table.
+pub fn attribute_schema_from_definition(config_json: &str) -> SzResult<Value> {
+    let config: Value = serde_json::from_str(config_json)?;
+    let attributes = config["G2_CONFIG"]["CFG_ATTR"]
+        .as_array()
+        .ok_or_else(|| SzError::bad_input("configuration has no G2_CONFIG.CFG_ATTR table"))?;
+
+    let mut properties = Map::new();
+    for attribute in attributes {
+        let Some(code) = attribute["ATTR_CODE"].as_str().filter(|c| !c.is_empty()) else {
+            continue;
+        };
+        if attribute["INTERNAL"]
+            .as_str()
+            .is_some_and(|internal| internal.eq_ignore_ascii_case("yes"))
+        {
+            continue;
+        }
+        properties.insert(
+            code.to_string(),
+            json!({
+                "type": "string",
+                "description": describe(attribute),
+            }),
+        );
+    }
+
+    Ok(json!({
+        "$schema": SCHEMA_DIALECT,
+        "title": "Senzing record",
+        "type": "object",
+        "properties": properties,
+        "additionalProperties": {
+            "anyOf": [
+                { "type": ["string", "number", "boolean", "null"] },
+                { "type": "array", "items": { "$ref": "#" } },
+            ]
+        },
+    }))
+}
+
+fn describe(attribute: &Value) -> String {
+    let field = |name: &str| attribute[name].as_str().filter(|v| !v.is_empty());
+    let mut description = match (field("FTYPE_CODE"), field("FELEM_CODE")) {
+        (Some(ftype), Some(felem)) => format!("{ftype} feature, {felem} element"),
+        (Some(ftype), None) => format!("{ftype} feature"),
+        _ => field("ATTR_CLASS").unwrap_or("OTHER").to_string(),
+    };
+    if field("FTYPE_CODE").is_some()
+        && let Some(required) = field("FELEM_REQ")
+        && required.eq_ignore_ascii_case("yes")
+    {
+        description.push_str(" (required for the feature)");
+    }
+    description
+}
+
+/// Returns Rust source for a struct with one optional field per schema
+/// property.
+///
+/// The struct derives `serde::Serialize` and `serde::Deserialize`, renames
+/// each field to its attribute code, skips unset fields when serializing,
+/// and keeps any other keys in a flattened `extra` map.
+pub fn record_struct_source(schema: &Value, struct_name: &str) -> String {
+    let mut source = String::new();
+    let _ = writeln!(
+        source,
+        "/// Record attributes generated from a Senzing configuration."
+    );
+    let _ = writeln!(
+        source,
+        "#[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]"
+    );
+    let _ = writeln!(source, "pub struct {struct_name} {{");
+    if let Some(properties) = schema["properties"].as_object() {
+        for (code, property) in properties {
+            if let Some(description) = property["description"].as_str() {
+                let _ = writeln!(source, "    /// {description}");
+            }
+            let _ = writeln!(
+                source,
+                "    #[serde(rename = \"{code}\", skip_serializing_if = \"Option::is_none\")]"
+            );
+            let _ = writeln!(source, "    pub {}: Option<String>,", field_name(code));
+        }
+    }
+    let _ = writeln!(source, "    /// Payload attributes and attribute groups");
+    let _ = writeln!(source, "    #[serde(flatten)]");
+    let _ = writeln!(
+        source,
+        "    pub extra: serde_json::Map<String, serde_json::Value>,"
+    );
+    let _ = writeln!(source, "}}");
+    source
+}
+
+fn field_name(code: &str) -> String {
+    let mut name: String = code
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if name.is_empty() || name.starts_with(|c: char| c.is_ascii_digit()) || name == "extra" {
+        name.insert(0, '_');
+    }
+    if matches!(
+        name.as_str(),
+        "as" | "async"
+            | "const"
+            | "enum"
+            | "fn"
+            | "impl"
+            | "in"
+            | "loop"
+            | "match"
+            | "mod"
+            | "move"
+            | "ref"
+            | "static"
+            | "struct"
+            | "trait"
+            | "type"
+            | "use"
+            | "where"
+    ) {
+        name.insert_str(0, "r#");
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"{"G2_CONFIG":{"CFG_ATTR":[
+        {"ATTR_CODE":"DATA_SOURCE","ATTR_CLASS":"OBSERVATION","FTYPE_CODE":null,"FELEM_CODE":null,"FELEM_REQ":"Yes","INTERNAL":"No"},
+        {"ATTR_CODE":"NAME_FULL","ATTR_CLASS":"NAME","FTYPE_CODE":"NAME","FELEM_CODE":"FULL_NAME","FELEM_REQ":"Any","INTERNAL":"No"},
+        {"ATTR_CODE":"PASSPORT_NUMBER","ATTR_CLASS":"IDENTIFIER","FTYPE_CODE":"PASSPORT","FELEM_CODE":"ID_NUM","FELEM_REQ":"Yes","INTERNAL":"No"},
+        {"ATTR_CODE":"TYPE","ATTR_CLASS":"OTHER","FTYPE_CODE":"","FELEM_CODE":"","FELEM_REQ":"No","INTERNAL":"No"},
+        {"ATTR_CODE":"NAME_HASH","ATTR_CLASS":"NAME","FTYPE_CODE":"NAME","FELEM_CODE":"HASH","FELEM_REQ":"No","INTERNAL":"Yes"}]}}"#;
+
+    #[test]
+    fn test_schema_lists_public_attributes() {
+        let schema = attribute_schema_from_definition(CONFIG).unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+        assert_eq!(
+            properties.keys().collect::<Vec<_>>(),
+            vec!["DATA_SOURCE", "NAME_FULL", "PASSPORT_NUMBER", "TYPE"]
+        );
+        assert_eq!(
+            properties["NAME_FULL"]["description"],
+            "NAME feature, FULL_NAME element"
+        );
+        assert_eq!(
+            properties["PASSPORT_NUMBER"]["description"],
+            "PASSPORT feature, ID_NUM element (required for the feature)"
+        );
+        assert_eq!(properties["DATA_SOURCE"]["description"], "OBSERVATION");
+        assert_eq!(schema["$schema"], SCHEMA_DIALECT);
+    }
+
+    #[test]
+    fn test_schema_requires_attribute_table() {
+        assert!(matches!(
+            attribute_schema_from_definition(r#"{"G2_CONFIG":{}}"#),
+            Err(SzError::BadInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_record_struct_source() {
+        let schema = attribute_schema_from_definition(CONFIG).unwrap();
+        let source = record_struct_source(&schema, "Record");
+        assert!(source.contains("pub struct Record {"));
+        assert!(source.contains(
+            "#[serde(rename = \"NAME_FULL\", skip_serializing_if = \"Option::is_none\")]\n    pub name_full: Option<String>,"
+        ));
+        assert!(source.contains("pub r#type: Option<String>,"));
+        assert!(source.contains("pub extra: serde_json::Map<String, serde_json::Value>,"));
+    }
+
+    #[test]
+    fn test_field_names() {
+        assert_eq!(field_name("ADDR_LINE1"), "addr_line1");
+        assert_eq!(field_name("1ST_NAME"), "_1st_name");
+        assert_eq!(field_name("EXTRA"), "_extra");
+        assert_eq!(field_name("REF"), "r#ref");
+    }
+}
@@ -0,0 +1,174 @@
+//! Ready-made configuration templates for common entity types.
+//!
+//! A [`ConfigTemplate`] names the data sources to register for a domain and
+//! the feature types its records rely on. The Senzing default configuration
+//! already defines those feature types; [`ConfigTemplate::apply`] checks that
+//! they are present and registers the data sources, so a new deployment can
+//! go from an empty configuration to loading records in one call. Each
+//! template also carries an example record showing the attribute mapping.
+//!
+//! # Example
+//!
+//! ```
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::config_tools::templates;
+//! use sz_rust_sdk::prelude::*;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_config_templates")?;
+//! let config = env.get_config_manager()?.create_config()?;
+//! let registered = templates::PEOPLE.apply(&*config)?;
+//! assert_eq!(registered, vec!["PEOPLE".to_string()]);
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::error::{SzError, SzResult};
+use crate::traits::SzConfig;
+
+/// Data sources and required feature types for one kind of entity.
+///
+/// The built-in templates are constants in this module; custom templates can
+/// be built the same way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigTemplate {
+    /// Short template name
+    pub name: &'static str,
+    /// What the template is for
+    pub description: &'static str,
+    /// Data source codes to register
+    pub data_sources: &'static [&'static str],
+    /// Feature types the template's records map to
+    pub feature_types: &'static [&'static str],
+    /// Example record in Senzing JSON format
+    pub example_record: &'static str,
+}
+
+/// Individuals: names, dates of birth, contact details, and identifiers.
+pub const PEOPLE: ConfigTemplate = ConfigTemplate {
+    name: "people",
+    description: "Individuals with names, dates of birth, addresses, contact details, and identity documents",
+    data_sources: &["PEOPLE"],
+    feature_types: &[
+        "NAME",
+        "DOB",
+        "ADDRESS",
+        "PHONE",
+        "EMAIL",
+        "SSN",
+        "PASSPORT",
+        "DRLIC",
+        "NATIONAL_ID",
+    ],
+    example_record: r#"{"RECORD_TYPE":"PERSON","NAME_FIRST":"Robert","NAME_LAST":"Smith","DATE_OF_BIRTH":"1978-12-11","ADDR_FULL":"123 Main St, Las Vegas NV 89132","PHONE_NUMBER":"702-919-1300","EMAIL_ADDRESS":"bsmith@work.com"}"#,
+};
+
+/// Companies and other organizations: names, addresses, and registry numbers.
+pub const ORGANIZATIONS: ConfigTemplate = ConfigTemplate {
+    name: "organizations",
+    description: "Companies and other organizations with names, addresses, tax IDs, and registry numbers",
+    data_sources: &["ORGANIZATIONS"],
+    feature_types: &[
+        "NAME",
+        "ADDRESS",
+        "PHONE",
+        "TAX_ID",
+        "DUNS_NUMBER",
+        "LEI_NUMBER",
+        "WEBSITE",
+    ],
+    example_record: r#"{"RECORD_TYPE":"ORGANIZATION","NAME_ORG":"Acme Tire Inc.","ADDR_FULL":"4150 Rainbow Blvd, Las Vegas NV 89103","PHONE_NUMBER":"702-555-0144","DUNS_NUMBER":"123456789","WEBSITE_ADDRESS":"acmetire.com"}"#,
+};
+
+/// Vessels: names and registry numbers such as IMO or MMSI.
+///
+/// Registry numbers map to `OTHER_ID` with the numbering scheme as
+/// `OTHER_ID_TYPE` and the flag state as `OTHER_ID_COUNTRY`.
+pub const VESSELS: ConfigTemplate = ConfigTemplate {
+    name: "vessels",
+    description: "Ships with names and registry numbers (IMO, MMSI, call sign)",
+    data_sources: &["VESSELS"],
+    feature_types: &["NAME", "OTHER_ID"],
+    example_record: r#"{"RECORD_TYPE":"VESSEL","NAME_ORG":"Ever Given","OTHER_ID_TYPE":"IMO","OTHER_ID_NUMBER":"9811000","OTHER_ID_COUNTRY":"PA"}"#,
+};
+
+/// Addresses with no other identifying features, for address standardization
+/// and deduplication.
+pub const ADDRESSES_ONLY: ConfigTemplate = ConfigTemplate {
+    name: "addresses-only",
+    description: "Locations identified only by postal address",
+    data_sources: &["ADDRESSES"],
+    feature_types: &["ADDRESS"],
+    example_record: r#"{"ADDR_LINE1":"123 Main St","ADDR_CITY":"Las Vegas","ADDR_STATE":"NV","ADDR_POSTAL_CODE":"89132"}"#,
+};
+
+/// Returns all built-in templates.
+pub fn all() -> &'static [ConfigTemplate] {
+    &[PEOPLE, ORGANIZATIONS, VESSELS, ADDRESSES_ONLY]
+}
+
+/// Looks up a built-in template by name.
+pub fn by_name(name: &str) -> Option<ConfigTemplate> {
+    all().iter().find(|template| template.name == name).copied()
+}
+
+impl ConfigTemplate {
+    /// Applies the template to `config` and returns the data sources that
+    /// were newly registered.
+    ///
+    /// Data sources that are already registered are left alone, so applying
+    /// a template twice is harmless. The configuration is only modified in
+    /// memory; register it with the config manager to make it active.
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::Configuration` - The configuration lacks one of the
+    ///   template's feature types
+    /// * Any error from exporting the configuration or registering a data source
+    pub fn apply(&self, config: &dyn SzConfig) -> SzResult<Vec<String>> {
+        let available = super::feature_types(&config.export()?)?;
+        let missing: Vec<&str> = self
+            .feature_types
+            .iter()
+            .copied()
+            .filter(|ftype| !available.contains(*ftype))
+            .collect();
+        if !missing.is_empty() {
+            return Err(SzError::configuration(format!(
+                "Template '{}' requires feature types missing from the configuration: {}",
+                self.name,
+                missing.join(", ")
+            )));
+        }
+
+        let existing = super::registered_data_sources(config)?;
+        let mut registered = Vec::new();
+        for data_source in self.data_sources {
+            if !existing.contains(*data_source) {
+                config.register_data_source(data_source)?;
+                registered.push(data_source.to_string());
+            }
+        }
+        Ok(registered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_templates_are_well_formed() {
+        for template in all() {
+            assert!(!template.data_sources.is_empty(), "{}", template.name);
+            assert!(!template.feature_types.is_empty(), "{}", template.name);
+            let record: serde_json::Value = serde_json::from_str(template.example_record)
+                .unwrap_or_else(|e| panic!("{}: {e}", template.name));
+            assert!(record.is_object());
+        }
+    }
+
+    #[test]
+    fn test_by_name() {
+        assert_eq!(by_name("vessels"), Some(VESSELS));
+        assert_eq!(by_name("nope"), None);
+    }
+}
@@ -0,0 +1,300 @@
+//! Structured configuration validation.
+//!
+//! [`check_definition`] inspects a configuration document for the common
+//! mistakes that otherwise surface as a single opaque native error: malformed
+//! JSON, missing sections, duplicate codes, and references to undefined
+//! feature types. `SzConfigManager::validate_config` combines these checks
+//! with a dry-run load of the document by the native library.
+
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fmt;
+
+/// One problem found in a configuration document.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ConfigFinding {
+    /// The document is not valid JSON
+    InvalidJson {
+        /// 1-based line of the syntax error
+        line: usize,
+        /// 1-based column of the syntax error
+        column: usize,
+        /// Parser message
+        message: String,
+    },
+    /// A required section is missing or has the wrong JSON type
+    MissingSection {
+        /// JSON path of the section, e.g. `G2_CONFIG.CFG_DSRC`
+        path: String,
+    },
+    /// A data source code is defined more than once
+    DuplicateDataSource {
+        /// JSON path of the duplicate entry
+        path: String,
+        /// The duplicated code
+        code: String,
+    },
+    /// A feature type code is defined more than once
+    DuplicateFeatureType {
+        /// JSON path of the duplicate entry
+        path: String,
+        /// The duplicated code
+        code: String,
+    },
+    /// An entry refers to a feature type that is not defined in `CFG_FTYPE`
+    UnknownFeatureType {
+        /// JSON path of the reference
+        path: String,
+        /// The feature type code or ID referred to
+        feature_type: String,
+    },
+    /// The native library refused to load the document
+    Rejected {
+        /// Senzing error code, when available
+        error_code: Option<i64>,
+        /// Native error message
+        message: String,
+    },
+}
+
+impl fmt::Display for ConfigFinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidJson {
+                line,
+                column,
+                message,
+            } => write!(f, "invalid JSON at line {line}, column {column}: {message}"),
+            Self::MissingSection { path } => write!(f, "{path}: missing or not the expected type"),
+            Self::DuplicateDataSource { path, code } => {
+                write!(f, "{path}: duplicate data source {code}")
+            }
+            Self::DuplicateFeatureType { path, code } => {
+                write!(f, "{path}: duplicate feature type {code}")
+            }
+            Self::UnknownFeatureType { path, feature_type } => {
+                write!(f, "{path}: unknown feature type {feature_type}")
+            }
+            Self::Rejected {
+                error_code: Some(code),
+                message,
+            } => write!(f, "rejected by the engine (SENZ{code}): {message}"),
+            Self::Rejected {
+                error_code: None,
+                message,
+            } => write!(f, "rejected by the engine: {message}"),
+        }
+    }
+}
+
+/// Result of validating a configuration document.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigValidationReport {
+    /// Problems found, in document order; native rejections come last
+    pub findings: Vec<ConfigFinding>,
+}
+
+impl ConfigValidationReport {
+    /// Returns `true` when no problems were found.
+    pub fn is_valid(&self) -> bool {
+        self.findings.is_empty()
+    }
+
+    /// Returns `true` when the document could not be parsed as JSON.
+    pub(crate) fn has_invalid_json(&self) -> bool {
+        self.findings
+            .iter()
+            .any(|finding| matches!(finding, ConfigFinding::InvalidJson { .. }))
+    }
+}
+
+impl fmt::Display for ConfigValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.findings.is_empty() {
+            return write!(f, "configuration is valid");
+        }
+        for (i, finding) in self.findings.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{finding}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks a configuration document without involving the native library.
+pub fn check_definition(config_definition: &str) -> ConfigValidationReport {
+    let mut findings = Vec::new();
+    let document: Value = match serde_json::from_str(config_definition) {
+        Ok(document) => document,
+        Err(e) => {
+            findings.push(ConfigFinding::InvalidJson {
+                line: e.line(),
+                column: e.column(),
+                message: e.to_string(),
+            });
+            return ConfigValidationReport { findings };
+        }
+    };
+
+    let Some(config) = document.get("G2_CONFIG").filter(|c| c.is_object()) else {
+        findings.push(ConfigFinding::MissingSection {
+            path: "G2_CONFIG".to_string(),
+        });
+        return ConfigValidationReport { findings };
+    };
+
+    let section = |name: &str, findings: &mut Vec<ConfigFinding>| -> Vec<Value> {
+        match config.get(name).and_then(Value::as_array) {
+            Some(entries) => entries.clone(),
+            None => {
+                findings.push(ConfigFinding::MissingSection {
+                    path: format!("G2_CONFIG.{name}"),
+                });
+                Vec::new()
+            }
+        }
+    };
+    let data_sources = section("CFG_DSRC", &mut findings);
+    let feature_types = section("CFG_FTYPE", &mut findings);
+    let attributes = section("CFG_ATTR", &mut findings);
+
+    let mut seen = HashSet::new();
+    for (i, entry) in data_sources.iter().enumerate() {
+        if let Some(code) = entry["DSRC_CODE"].as_str()
+            && !seen.insert(code.to_uppercase())
+        {
+            findings.push(ConfigFinding::DuplicateDataSource {
+                path: format!("G2_CONFIG.CFG_DSRC[{i}].DSRC_CODE"),
+                code: code.to_string(),
+            });
+        }
+    }
+
+    let mut ftype_codes = HashSet::new();
+    let mut ftype_ids = HashSet::new();
+    for (i, entry) in feature_types.iter().enumerate() {
+        if let Some(id) = entry["FTYPE_ID"].as_i64() {
+            ftype_ids.insert(id);
+        }
+        if let Some(code) = entry["FTYPE_CODE"].as_str()
+            && !ftype_codes.insert(code.to_uppercase())
+        {
+            findings.push(ConfigFinding::DuplicateFeatureType {
+                path: format!("G2_CONFIG.CFG_FTYPE[{i}].FTYPE_CODE"),
+                code: code.to_string(),
+            });
+        }
+    }
+
+    for (i, entry) in attributes.iter().enumerate() {
+        if let Some(code) = entry["FTYPE_CODE"].as_str()
+            && !code.is_empty()
+            && !ftype_codes.contains(&code.to_uppercase())
+        {
+            findings.push(ConfigFinding::UnknownFeatureType {
+                path: format!("G2_CONFIG.CFG_ATTR[{i}].FTYPE_CODE"),
+                feature_type: code.to_string(),
+            });
+        }
+    }
+
+    if let Some(elements) = config.get("CFG_FBOM").and_then(Value::as_array) {
+        for (i, entry) in elements.iter().enumerate() {
+            if let Some(id) = entry["FTYPE_ID"].as_i64()
+                && !ftype_ids.contains(&id)
+            {
+                findings.push(ConfigFinding::UnknownFeatureType {
+                    path: format!("G2_CONFIG.CFG_FBOM[{i}].FTYPE_ID"),
+                    feature_type: id.to_string(),
+                });
+            }
+        }
+    }
+
+    ConfigValidationReport { findings }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID: &str = r#"{"G2_CONFIG":{
+        "CFG_DSRC":[{"DSRC_ID":1,"DSRC_CODE":"TEST"}],
+        "CFG_FTYPE":[{"FTYPE_ID":1,"FTYPE_CODE":"NAME"}],
+        "CFG_ATTR":[{"ATTR_CODE":"NAME_FULL","FTYPE_CODE":"NAME"},{"ATTR_CODE":"DATA_SOURCE","FTYPE_CODE":""}],
+        "CFG_FBOM":[{"FTYPE_ID":1,"FELEM_ID":2}]}}"#;
+
+    #[test]
+    fn test_valid_definition() {
+        let report = check_definition(VALID);
+        assert!(report.is_valid(), "{report}");
+    }
+
+    #[test]
+    fn test_invalid_json_reports_position() {
+        let report = check_definition("{\n  \"G2_CONFIG\": [,\n}");
+        assert!(report.has_invalid_json());
+        assert!(matches!(
+            report.findings[0],
+            ConfigFinding::InvalidJson { line: 2, .. }
+        ));
+    }
+
+    #[test]
+    fn test_missing_sections() {
+        let report = check_definition(r#"{"G2_CONFIG":{"CFG_DSRC":[]}}"#);
+        assert_eq!(
+            report.findings,
+            vec![
+                ConfigFinding::MissingSection {
+                    path: "G2_CONFIG.CFG_FTYPE".to_string()
+                },
+                ConfigFinding::MissingSection {
+                    path: "G2_CONFIG.CFG_ATTR".to_string()
+                },
+            ]
+        );
+        assert_eq!(
+            check_definition("{}").findings,
+            vec![ConfigFinding::MissingSection {
+                path: "G2_CONFIG".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_duplicates_and_unknown_references() {
+        let report = check_definition(
+            r#"{"G2_CONFIG":{
+            "CFG_DSRC":[{"DSRC_CODE":"TEST"},{"DSRC_CODE":"test"}],
+            "CFG_FTYPE":[{"FTYPE_ID":1,"FTYPE_CODE":"NAME"},{"FTYPE_ID":2,"FTYPE_CODE":"NAME"}],
+            "CFG_ATTR":[{"ATTR_CODE":"VESSEL_IMO","FTYPE_CODE":"IMO"}],
+            "CFG_FBOM":[{"FTYPE_ID":7,"FELEM_ID":1}]}}"#,
+        );
+        assert_eq!(
+            report.findings,
+            vec![
+                ConfigFinding::DuplicateDataSource {
+                    path: "G2_CONFIG.CFG_DSRC[1].DSRC_CODE".to_string(),
+                    code: "test".to_string(),
+                },
+                ConfigFinding::DuplicateFeatureType {
+                    path: "G2_CONFIG.CFG_FTYPE[1].FTYPE_CODE".to_string(),
+                    code: "NAME".to_string(),
+                },
+                ConfigFinding::UnknownFeatureType {
+                    path: "G2_CONFIG.CFG_ATTR[0].FTYPE_CODE".to_string(),
+                    feature_type: "IMO".to_string(),
+                },
+                ConfigFinding::UnknownFeatureType {
+                    path: "G2_CONFIG.CFG_FBOM[0].FTYPE_ID".to_string(),
+                    feature_type: "7".to_string(),
+                },
+            ]
+        );
+        assert!(report.to_string().contains("unknown feature type IMO"));
+    }
+}
@@ -0,0 +1,196 @@
+//! Flag defaults for every engine call in a scope.
+//!
+//! Frameworks often want one flag on every call without touching each call
+//! site, such as `WITH_INFO` on all changes in a staging deployment.
+//! [`SzCallContext::with_flags`] adds flags to the defaults of every engine
+//! call made on the current thread while a closure runs:
+//!
+//! - A call made with `None` runs with its method's default flags plus the
+//!   context's flags.
+//! - A call made with `Some(flags)` runs with exactly those flags. Flags
+//!   passed at the call site replace the defaults, including the context's,
+//!   so a call can always ask for less than the context adds.
+//!
+//! Scopes nest, and an inner scope adds its flags to the outer ones.
+//! Interceptors and the audit log see the flags the call runs with. Export
+//! calls are not affected, since their flags select what is exported.
+//!
+//! The context belongs to a thread. Work handed to other threads takes it
+//! along with [`SzCallContext::current`] and [`SzCallContext::scope`]; async
+//! code keeps it across `.await` points with [`SzCallContext::instrument`].
+//! `web::SzState::blocking` and the `grpc` services carry the context of the
+//! handler to the blocking thread that makes the engine call.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::context::SzCallContext;
+//! use sz_rust_sdk::prelude::*;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_call_context")?;
+//! let engine = env.get_engine()?;
+//! let info = SzCallContext::with_flags(SzFlags::WITH_INFO, || {
+//!     engine.add_record("TEST", "1001", r#"{"NAME_FULL": "Ann Lee"}"#, None)
+//! })?;
+//! assert_ne!(info, SZ_NO_INFO);
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::flags::SzFlags;
+use std::cell::Cell;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+thread_local! {
+    /// Flags added to engine calls on this thread
+    static FLAGS: Cell<SzFlags> = const { Cell::new(SzFlags::empty()) };
+}
+
+/// Flags added to the engine calls of a scope; see the
+/// [module documentation](self).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SzCallContext {
+    flags: SzFlags,
+}
+
+impl SzCallContext {
+    /// A context adding `flags`.
+    pub fn new(flags: SzFlags) -> Self {
+        Self { flags }
+    }
+
+    /// The context of the current thread.
+    pub fn current() -> Self {
+        Self::new(FLAGS.with(Cell::get))
+    }
+
+    /// Runs `f` with `flags` added to the defaults of every engine call it
+    /// makes on this thread.
+    pub fn with_flags<R>(flags: SzFlags, f: impl FnOnce() -> R) -> R {
+        Self::new(flags).scope(f)
+    }
+
+    /// The flags this context adds.
+    pub fn flags(&self) -> SzFlags {
+        self.flags
+    }
+
+    /// Runs `f` with this context's flags added to those of the current
+    /// thread.
+    pub fn scope<R>(&self, f: impl FnOnce() -> R) -> R {
+        let _restore = Restore(FLAGS.with(|current| current.replace(current.get() | self.flags)));
+        f()
+    }
+
+    /// Wraps `future` so that each poll runs in [`scope`](Self::scope), for
+    /// a context that stays with an async task across `.await` points.
+    pub fn instrument<F: Future>(self, future: F) -> Scoped<F> {
+        Scoped {
+            context: self,
+            future: Box::pin(future),
+        }
+    }
+}
+
+/// Puts the previous flags back when a scope ends, also on panic.
+struct Restore(SzFlags);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        FLAGS.with(|current| current.set(self.0));
+    }
+}
+
+/// A future polled in an [`SzCallContext`], from
+/// [`SzCallContext::instrument`].
+#[derive(Debug)]
+pub struct Scoped<F> {
+    context: SzCallContext,
+    future: Pin<Box<F>>,
+}
+
+impl<F: Future> Future for Scoped<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let this = &mut *self;
+        this.context.scope(|| this.future.as_mut().poll(cx))
+    }
+}
+
+/// The flags an engine call made with `flags` runs with, where `default`
+/// is its method's default. Explicit flags are used as they are.
+pub(crate) fn effective_flags(flags: Option<SzFlags>, default: SzFlags) -> Option<SzFlags> {
+    if flags.is_some() {
+        return flags;
+    }
+    let context = FLAGS.with(Cell::get);
+    if context.is_empty() {
+        return None;
+    }
+    Some(default | context)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::task::Waker;
+
+    #[test]
+    fn test_scopes_add_flags() {
+        let default = SzFlags::ENTITY_DEFAULT_FLAGS;
+        assert_eq!(effective_flags(None, default), None);
+        SzCallContext::with_flags(SzFlags::WITH_INFO, || {
+            assert_eq!(
+                effective_flags(None, default),
+                Some(default | SzFlags::WITH_INFO)
+            );
+            // Explicit flags replace the defaults, context included
+            assert_eq!(
+                effective_flags(Some(SzFlags::empty()), default),
+                Some(SzFlags::empty())
+            );
+            assert_eq!(
+                effective_flags(Some(SzFlags::ENTITY_INCLUDE_ENTITY_NAME), default),
+                Some(SzFlags::ENTITY_INCLUDE_ENTITY_NAME)
+            );
+            SzCallContext::with_flags(SzFlags::ENTITY_INCLUDE_ENTITY_NAME, || {
+                assert_eq!(
+                    SzCallContext::current().flags(),
+                    SzFlags::WITH_INFO | SzFlags::ENTITY_INCLUDE_ENTITY_NAME
+                );
+            });
+            let context = SzCallContext::current();
+            let on_thread = std::thread::spawn(move || {
+                (
+                    SzCallContext::current(),
+                    context.scope(SzCallContext::current),
+                )
+            })
+            .join()
+            .unwrap();
+            assert_eq!(on_thread, (SzCallContext::default(), context));
+        });
+        assert_eq!(SzCallContext::current(), SzCallContext::default());
+
+        let panicked = std::panic::catch_unwind(|| {
+            SzCallContext::with_flags(SzFlags::WITH_INFO, || panic!("in scope"))
+        });
+        assert!(panicked.is_err());
+        assert_eq!(SzCallContext::current(), SzCallContext::default());
+    }
+
+    #[test]
+    fn test_instrumented_future() {
+        let mut future = SzCallContext::new(SzFlags::WITH_INFO)
+            .instrument(async { SzCallContext::current().flags() });
+        let mut cx = Context::from_waker(Waker::noop());
+        assert_eq!(
+            Pin::new(&mut future).poll(&mut cx),
+            Poll::Ready(SzFlags::WITH_INFO)
+        );
+        assert_eq!(SzCallContext::current(), SzCallContext::default());
+    }
+}
@@ -0,0 +1,488 @@
+//! Cooperative pause, resume and stop for long-running operations.
+//!
+//! A [`ControlHandle`] is shared between the thread running an operation and
+//! the threads controlling it. The operation checks the handle between units
+//! of work (one record, one redo), so a pause or stop takes effect once the
+//! work in flight finishes; nothing is interrupted mid-call.
+//!
+//! Supported by [`ingest::load_from_reader_with_control`](crate::ingest::load_from_reader_with_control)
+//! and [`SzRedoProcessor::with_control`](crate::redo::SzRedoProcessor::with_control).
+//!
+//! The handle also carries [`Tuning`]: worker counts, batch sizes and poll
+//! intervals that a long-running operation picks up while it runs, set with
+//! [`ControlHandle::tune`].
+//!
+//! An [`SzCancellationToken`] is the one-way counterpart for shutdown: a
+//! service hands the same token to its loads, exports and redo processors
+//! (and to the environment for engine calls), and cancelling it stops them
+//! all. Operations that take a [`ControlHandle`] stop as if
+//! [`stop`](ControlHandle::stop) had been called once a token attached with
+//! [`ControlHandle::with_cancellation`] is cancelled.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::control::{ControlHandle, Deadline, RunState};
+//! use sz_rust_sdk::ingest;
+//! use sz_rust_sdk::prelude::*;
+//! use std::time::Duration;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_control")?;
+//! let engine = env.get_engine()?;
+//! let control = ControlHandle::new();
+//! let operator = control.clone();
+//! std::thread::spawn(move || {
+//!     operator.pause();
+//!     assert_eq!(operator.state(), RunState::Paused);
+//!     operator.resume();
+//!     operator.stop(Deadline::after(Duration::from_secs(30)));
+//! });
+//! let file = std::io::BufReader::new(std::fs::File::open("records.jsonl")?);
+//! let summary = ingest::load_from_reader_with_control(&*engine, file, &control)?;
+//! println!("stopped early: {}", summary.stopped);
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::error::{SzError, SzResult};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// How often a paused operation checks its cancellation token.
+const CANCELLATION_POLL: Duration = Duration::from_millis(50);
+
+/// Shared flag that asks running operations to give up.
+///
+/// Cloning the token shares the same flag, and cancelling cannot be undone.
+/// Operations check the token between units of work; the SDK cannot
+/// interrupt a native call in flight, so see each operation for how soon it
+/// notices:
+///
+/// - Loads (`ingest::LoadOptions::with_cancellation`) and redo processing
+///   (`SzRedoProcessor::with_cancellation`) stop before the next record and
+///   report it in their summary's `stopped` field.
+/// - Exports (`SzExporter::with_cancellation`) fail with
+///   `SzError::Cancelled` before the next fetch.
+/// - Engine calls of an environment with
+///   `SzEnvironmentCore::set_cancellation_token` fail with
+///   `SzError::Cancelled` without reaching the native library, and calls
+///   waiting on an operation timeout are abandoned.
+///
+/// # Example
+///
+/// ```
+/// use sz_rust_sdk::control::SzCancellationToken;
+///
+/// let token = SzCancellationToken::new();
+/// let shutdown = token.clone();
+/// std::thread::spawn(move || shutdown.cancel()).join().unwrap();
+/// assert!(token.is_cancelled());
+/// assert!(token.check("load").is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SzCancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl SzCancellationToken {
+    /// Creates a token that is not cancelled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancels every operation holding a clone of this token.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+
+    /// Whether the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
+
+    /// Returns `SzError::Cancelled` naming `operation` if the token has been
+    /// cancelled.
+    pub fn check(&self, operation: &str) -> SzResult<()> {
+        if self.is_cancelled() {
+            return Err(SzError::cancelled(format!("{operation} was cancelled")));
+        }
+        Ok(())
+    }
+}
+
+/// State of a controlled operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RunState {
+    /// Working, or not started yet
+    Running,
+    /// Waiting for [`ControlHandle::resume`] before taking the next unit of work
+    Paused,
+    /// Stop requested; finishing the work in flight
+    Stopping,
+    /// The operation has returned
+    Stopped,
+}
+
+/// Runtime parameters changed while an operation runs.
+///
+/// Fields left `None` keep the value the operation was configured with.
+/// Each operation documents which fields it honors and when it reads them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Tuning {
+    /// Number of worker threads
+    pub workers: Option<usize>,
+    /// Units of work handed to a worker at a time
+    pub batch_size: Option<usize>,
+    /// Interval between polls of queues and lag counts
+    pub poll_interval: Option<Duration>,
+}
+
+impl Tuning {
+    /// Sets the number of worker threads.
+    pub fn with_workers(mut self, workers: usize) -> Self {
+        self.workers = Some(workers);
+        self
+    }
+
+    /// Sets the batch size.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
+
+    /// Sets the poll interval.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = Some(poll_interval);
+        self
+    }
+
+    /// Returns `self` with the fields set in `other` replaced.
+    fn merged(self, other: Tuning) -> Self {
+        Self {
+            workers: other.workers.or(self.workers),
+            batch_size: other.batch_size.or(self.batch_size),
+            poll_interval: other.poll_interval.or(self.poll_interval),
+        }
+    }
+}
+
+/// How long [`ControlHandle::stop`] waits for the operation to return.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Deadline(Option<Instant>);
+
+impl Deadline {
+    /// Waits until the given instant.
+    pub fn at(instant: Instant) -> Self {
+        Self(Some(instant))
+    }
+
+    /// Waits for the given duration from now.
+    pub fn after(timeout: Duration) -> Self {
+        Self(Instant::now().checked_add(timeout))
+    }
+
+    /// Does not wait: requests the stop and returns.
+    pub fn immediate() -> Self {
+        Self(Some(Instant::now()))
+    }
+
+    /// Waits until the operation returns, however long that takes.
+    pub fn never() -> Self {
+        Self(None)
+    }
+
+    /// Time left before the deadline, or `None` when there is no deadline.
+    pub fn remaining(&self) -> Option<Duration> {
+        self.0
+            .map(|instant| instant.saturating_duration_since(Instant::now()))
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    state: Mutex<RunState>,
+    changed: Condvar,
+    tuning: Mutex<Tuning>,
+    cancellation: Mutex<Option<SzCancellationToken>>,
+}
+
+/// Shared handle for pausing, resuming and stopping an operation.
+///
+/// Cloning the handle shares the same state.
+#[derive(Debug, Clone)]
+pub struct ControlHandle {
+    inner: Arc<Inner>,
+}
+
+impl Default for ControlHandle {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                state: Mutex::new(RunState::Running),
+                changed: Condvar::new(),
+                tuning: Mutex::new(Tuning::default()),
+                cancellation: Mutex::new(None),
+            }),
+        }
+    }
+}
+
+impl ControlHandle {
+    /// Creates a handle in the [`RunState::Running`] state.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stops the operation once `token` is cancelled, as
+    /// [`stop`](Self::stop) would. Replaces a token attached earlier; clones
+    /// of the handle share it.
+    pub fn with_cancellation(self, token: SzCancellationToken) -> Self {
+        self.set_cancellation(token);
+        self
+    }
+
+    /// Attaches `token`, as [`with_cancellation`](Self::with_cancellation).
+    pub(crate) fn set_cancellation(&self, token: SzCancellationToken) {
+        *self.lock_cancellation() = Some(token);
+    }
+
+    /// Returns the current state.
+    pub fn state(&self) -> RunState {
+        *self.lock()
+    }
+
+    /// Whether a stop was requested, the attached cancellation token was
+    /// cancelled, or the operation has returned.
+    pub fn is_stop_requested(&self) -> bool {
+        self.apply_cancellation();
+        matches!(self.state(), RunState::Stopping | RunState::Stopped)
+    }
+
+    /// Pauses the operation after the work in flight. Has no effect unless
+    /// the state is [`RunState::Running`].
+    pub fn pause(&self) {
+        self.transition(RunState::Running, RunState::Paused);
+    }
+
+    /// Resumes a paused operation. Has no effect unless the state is
+    /// [`RunState::Paused`].
+    pub fn resume(&self) {
+        self.transition(RunState::Paused, RunState::Running);
+    }
+
+    /// Requests a stop and waits until the operation returns or the deadline
+    /// passes. A paused operation is woken up to stop.
+    ///
+    /// Returns `true` if the operation has returned.
+    pub fn stop(&self, deadline: Deadline) -> bool {
+        let mut state = self.lock();
+        if matches!(*state, RunState::Running | RunState::Paused) {
+            *state = RunState::Stopping;
+            self.inner.changed.notify_all();
+        }
+        loop {
+            if *state == RunState::Stopped {
+                return true;
+            }
+            state = match deadline.remaining() {
+                None => match self.inner.changed.wait(state) {
+                    Ok(guard) => guard,
+                    Err(poisoned) => poisoned.into_inner(),
+                },
+                Some(remaining) if remaining.is_zero() => return false,
+                Some(remaining) => match self.inner.changed.wait_timeout(state, remaining) {
+                    Ok((guard, _)) => guard,
+                    Err(poisoned) => poisoned.into_inner().0,
+                },
+            };
+        }
+    }
+
+    /// Changes runtime parameters of the operation. Fields set in `tuning`
+    /// replace the current values; `None` fields leave them as they are.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use sz_rust_sdk::control::{ControlHandle, Tuning};
+    ///
+    /// let control = ControlHandle::new();
+    /// control.tune(Tuning::default().with_workers(8));
+    /// control.tune(Tuning::default().with_batch_size(500));
+    /// assert_eq!(control.tuning().workers, Some(8));
+    /// assert_eq!(control.tuning().batch_size, Some(500));
+    /// ```
+    pub fn tune(&self, tuning: Tuning) {
+        let mut current = self.lock_tuning();
+        *current = current.merged(tuning);
+    }
+
+    /// Returns the current runtime parameters.
+    pub fn tuning(&self) -> Tuning {
+        *self.lock_tuning()
+    }
+
+    /// Replaces all runtime parameters; fields that are `None` return to the
+    /// operation's configured values.
+    pub fn set_tuning(&self, tuning: Tuning) {
+        *self.lock_tuning() = tuning;
+    }
+
+    /// Called by the operation before each unit of work. Blocks while
+    /// paused; returns `false` when the operation should stop.
+    pub(crate) fn checkpoint(&self) -> bool {
+        self.apply_cancellation();
+        let cancellable = self.lock_cancellation().is_some();
+        let mut state = self.lock();
+        while *state == RunState::Paused {
+            // A token cannot wake the condition variable, so poll it
+            state = if cancellable {
+                drop(state);
+                self.apply_cancellation();
+                let state = self.lock();
+                match self.inner.changed.wait_timeout(state, CANCELLATION_POLL) {
+                    Ok((guard, _)) => guard,
+                    Err(poisoned) => poisoned.into_inner().0,
+                }
+            } else {
+                match self.inner.changed.wait(state) {
+                    Ok(guard) => guard,
+                    Err(poisoned) => poisoned.into_inner(),
+                }
+            };
+        }
+        *state == RunState::Running
+    }
+
+    /// Moves to [`RunState::Stopping`] if the attached token was cancelled.
+    fn apply_cancellation(&self) {
+        let cancelled = self
+            .lock_cancellation()
+            .as_ref()
+            .is_some_and(SzCancellationToken::is_cancelled);
+        if cancelled {
+            let mut state = self.lock();
+            if matches!(*state, RunState::Running | RunState::Paused) {
+                *state = RunState::Stopping;
+                self.inner.changed.notify_all();
+            }
+        }
+    }
+
+    /// Called by the operation when it returns.
+    pub(crate) fn finished(&self) {
+        *self.lock() = RunState::Stopped;
+        self.inner.changed.notify_all();
+    }
+
+    fn transition(&self, from: RunState, to: RunState) {
+        let mut state = self.lock();
+        if *state == from {
+            *state = to;
+            self.inner.changed.notify_all();
+        }
+    }
+
+    fn lock_cancellation(&self) -> MutexGuard<'_, Option<SzCancellationToken>> {
+        match self.inner.cancellation.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn lock_tuning(&self) -> MutexGuard<'_, Tuning> {
+        match self.inner.tuning.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, RunState> {
+        match self.inner.state.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pause_resume_transitions() {
+        let control = ControlHandle::new();
+        control.resume();
+        assert_eq!(control.state(), RunState::Running);
+        control.pause();
+        assert_eq!(control.state(), RunState::Paused);
+        control.resume();
+        assert_eq!(control.state(), RunState::Running);
+        assert!(control.checkpoint());
+    }
+
+    #[test]
+    fn test_stop_wakes_paused_worker() {
+        let control = ControlHandle::new();
+        control.pause();
+        let worker = control.clone();
+        let handle = std::thread::spawn(move || {
+            let proceed = worker.checkpoint();
+            worker.finished();
+            proceed
+        });
+        assert!(control.stop(Deadline::never()));
+        assert!(!handle.join().unwrap());
+        assert_eq!(control.state(), RunState::Stopped);
+        // Pause after stopping has no effect
+        control.pause();
+        assert_eq!(control.state(), RunState::Stopped);
+    }
+
+    #[test]
+    fn test_tune_merges_fields() {
+        let control = ControlHandle::new();
+        let operator = control.clone();
+        operator.tune(
+            Tuning::default()
+                .with_workers(4)
+                .with_poll_interval(Duration::from_millis(50)),
+        );
+        operator.tune(Tuning::default().with_workers(2));
+        assert_eq!(
+            control.tuning(),
+            Tuning {
+                workers: Some(2),
+                batch_size: None,
+                poll_interval: Some(Duration::from_millis(50)),
+            }
+        );
+        control.set_tuning(Tuning::default());
+        assert_eq!(operator.tuning(), Tuning::default());
+    }
+
+    #[test]
+    fn test_cancellation_stops_paused_worker() {
+        let token = SzCancellationToken::new();
+        let control = ControlHandle::new().with_cancellation(token.clone());
+        assert!(control.checkpoint());
+        control.pause();
+        let worker = control.clone();
+        let handle = std::thread::spawn(move || worker.checkpoint());
+        token.cancel();
+        assert!(!handle.join().unwrap());
+        assert_eq!(control.state(), RunState::Stopping);
+        assert!(control.is_stop_requested());
+        assert!(matches!(token.check("redo"), Err(SzError::Cancelled(_))));
+    }
+
+    #[test]
+    fn test_stop_deadline_expires() {
+        let control = ControlHandle::new();
+        assert!(!control.stop(Deadline::after(Duration::from_millis(10))));
+        assert_eq!(control.state(), RunState::Stopping);
+        assert!(!control.checkpoint());
+        assert!(!control.stop(Deadline::immediate()));
+    }
+}
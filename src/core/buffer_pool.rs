@@ -0,0 +1,187 @@
+//! Reusable argument buffers for engine FFI calls.
+//!
+//! Every engine call that takes a record definition or search attributes
+//! copies the string into a NUL-terminated `CString`. During bulk loads that is
+//! one allocation and one free per call for the largest argument. The pool
+//! hands out previously used `Vec<u8>` buffers for those copies and takes them
+//! back when the call completes.
+
+use crate::error::{SzError, SzResult};
+use crate::types::{BufferPoolConfig, BufferPoolStats};
+use libc::c_char;
+use std::ffi::CString;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// A bounded pool of byte buffers.
+#[derive(Debug, Default)]
+pub(crate) struct BufferPool {
+    state: Mutex<PoolState>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    discarded: AtomicU64,
+}
+
+#[derive(Debug, Default)]
+struct PoolState {
+    config: BufferPoolConfig,
+    buffers: Vec<Vec<u8>>,
+}
+
+impl BufferPool {
+    fn state(&self) -> MutexGuard<'_, PoolState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Copies `s` into a pooled, NUL-terminated buffer.
+    pub(crate) fn c_string(&self, s: &str) -> SzResult<PooledCString<'_>> {
+        let mut buffer = self.take(s.len() + 1);
+        buffer.extend_from_slice(s.as_bytes());
+        let value = CString::new(buffer).map_err(SzError::from)?;
+        Ok(PooledCString {
+            value: Some(value),
+            pool: self,
+        })
+    }
+
+    /// Replaces the pool configuration, trimming idle buffers to fit it.
+    pub(crate) fn configure(&self, config: BufferPoolConfig) {
+        let mut state = self.state();
+        state.config = config;
+        let before = state.buffers.len();
+        state
+            .buffers
+            .retain(|buffer| buffer.capacity() <= config.max_buffer_bytes);
+        state.buffers.truncate(config.max_buffers);
+        let dropped = before - state.buffers.len();
+        self.discarded.fetch_add(dropped as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn config(&self) -> BufferPoolConfig {
+        self.state().config
+    }
+
+    pub(crate) fn stats(&self) -> BufferPoolStats {
+        let state = self.state();
+        BufferPoolStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            discarded: self.discarded.load(Ordering::Relaxed),
+            pooled_buffers: state.buffers.len(),
+            pooled_bytes: state.buffers.iter().map(Vec::capacity).sum(),
+        }
+    }
+
+    /// Returns an empty buffer with at least `capacity` bytes reserved.
+    fn take(&self, capacity: usize) -> Vec<u8> {
+        let pooled = self.state().buffers.pop();
+        match pooled {
+            Some(mut buffer) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                buffer.reserve(capacity);
+                buffer
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                Vec::with_capacity(capacity)
+            }
+        }
+    }
+
+    /// Returns a buffer to the pool, or drops it if it does not fit.
+    fn give(&self, mut buffer: Vec<u8>) {
+        let mut state = self.state();
+        if state.buffers.len() >= state.config.max_buffers
+            || buffer.capacity() > state.config.max_buffer_bytes
+        {
+            drop(state);
+            self.discarded.fetch_add(1, Ordering::Relaxed);
+            return;
+        }
+        buffer.clear();
+        state.buffers.push(buffer);
+    }
+}
+
+/// A `CString` whose buffer goes back to its pool when dropped.
+pub(crate) struct PooledCString<'a> {
+    value: Option<CString>,
+    pool: &'a BufferPool,
+}
+
+impl PooledCString<'_> {
+    pub(crate) fn as_ptr(&self) -> *const c_char {
+        self.value
+            .as_ref()
+            .map_or(std::ptr::null(), |value| value.as_ptr())
+    }
+}
+
+impl Drop for PooledCString<'_> {
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            self.pool.give(value.into_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn test_buffers_are_reused() {
+        let pool = BufferPool::default();
+        {
+            let first = pool.c_string("hello").unwrap();
+            let text = unsafe { CStr::from_ptr(first.as_ptr()) };
+            assert_eq!(text.to_str().unwrap(), "hello");
+        }
+        {
+            let second = pool.c_string("hi").unwrap();
+            let text = unsafe { CStr::from_ptr(second.as_ptr()) };
+            assert_eq!(text.to_str().unwrap(), "hi");
+        }
+
+        let stats = pool.stats();
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.pooled_buffers, 1);
+    }
+
+    #[test]
+    fn test_large_buffers_are_discarded() {
+        let pool = BufferPool::default();
+        pool.configure(BufferPoolConfig {
+            max_buffers: 4,
+            max_buffer_bytes: 8,
+        });
+        drop(pool.c_string("this is longer than eight bytes").unwrap());
+
+        let stats = pool.stats();
+        assert_eq!(stats.discarded, 1);
+        assert_eq!(stats.pooled_buffers, 0);
+    }
+
+    #[test]
+    fn test_disabled_pool_keeps_nothing() {
+        let pool = BufferPool::default();
+        pool.configure(BufferPoolConfig {
+            max_buffers: 0,
+            max_buffer_bytes: 1024,
+        });
+        drop(pool.c_string("a").unwrap());
+        drop(pool.c_string("b").unwrap());
+
+        let stats = pool.stats();
+        assert_eq!(stats.misses, 2);
+        assert_eq!(stats.pooled_buffers, 0);
+    }
+
+    #[test]
+    fn test_interior_nul_is_rejected() {
+        let pool = BufferPool::default();
+        assert!(pool.c_string("a\0b").is_err());
+    }
+}
@@ -1,23 +1,30 @@
 //! Core implementation of SzConfigManager trait
 
+use super::settings::EngineSettings;
 use crate::{
     error::SzResult,
+    ffi::boundary::guarded,
     ffi_call_config_mgr, process_config_mgr_long_result, process_config_mgr_result,
     traits::{SzConfig, SzConfigManager},
     types::{ConfigId, JsonString},
 };
+use serde_json::json;
+use std::sync::Arc;
 
 /// Core implementation of the SzConfigManager trait
 ///
-/// This is a zero-sized type as the config manager uses module-level
-/// functions in the native library after initialization.
-pub struct SzConfigManagerCore;
+/// The config manager uses module-level functions in the native library
+/// after initialization; it only holds the environment settings that
+/// control auditing.
+pub struct SzConfigManagerCore {
+    settings: Arc<EngineSettings>,
+}
 
 impl SzConfigManagerCore {
     /// Creates a new SzConfigManagerCore without initializing the native library.
     /// Caller must ensure SzConfigMgr_init has already been called.
-    pub(crate) fn new() -> SzResult<Self> {
-        Ok(Self)
+    pub(crate) fn with_settings(settings: Arc<EngineSettings>) -> Self {
+        Self { settings }
     }
 }
 
@@ -39,11 +46,17 @@ impl SzConfigManager for SzConfigManagerCore {
     }
 
     fn create_config_from_id(&self, config_id: ConfigId) -> SzResult<Box<dyn SzConfig>> {
-        let result = unsafe { crate::ffi::SzConfigMgr_getConfig_helper(config_id) };
-        let config_definition = process_config_mgr_result!(result)?;
+        guarded(
+            "create_config_from_id",
+            || -> SzResult<Box<dyn SzConfig>> {
+                let result = unsafe { crate::ffi::SzConfigMgr_getConfig_helper(config_id.get()) };
+                let config_definition = process_config_mgr_result!(result)?;
 
-        let config_core = super::config::SzConfigCore::new_with_definition(&config_definition)?;
-        Ok(Box::new(config_core))
+                let config_core =
+                    super::config::SzConfigCore::new_with_definition(&config_definition)?;
+                Ok(Box::new(config_core))
+            },
+        )
     }
 
     fn create_config_from_definition(
@@ -55,13 +68,17 @@ impl SzConfigManager for SzConfigManagerCore {
     }
 
     fn get_config_registry(&self) -> SzResult<JsonString> {
-        let result = unsafe { crate::ffi::SzConfigMgr_getConfigRegistry_helper() };
-        process_config_mgr_result!(result)
+        guarded("get_config_registry", || {
+            let result = unsafe { crate::ffi::SzConfigMgr_getConfigRegistry_helper() };
+            process_config_mgr_result!(result)
+        })
     }
 
     fn get_default_config_id(&self) -> SzResult<ConfigId> {
-        let result = unsafe { crate::ffi::SzConfigMgr_getDefaultConfigID_helper() };
-        process_config_mgr_long_result!(result)
+        guarded("get_default_config_id", || {
+            let result = unsafe { crate::ffi::SzConfigMgr_getDefaultConfigID_helper() };
+            process_config_mgr_long_result!(result)
+        })
     }
 
     fn register_config(
@@ -69,14 +86,26 @@ impl SzConfigManager for SzConfigManagerCore {
         config_definition: &str,
         config_comment: Option<&str>,
     ) -> SzResult<ConfigId> {
-        let config_def_c = crate::ffi::helpers::str_to_c_string(config_definition)?;
-        let comment_c = crate::ffi::helpers::str_to_c_string(config_comment.unwrap_or(""))?;
+        let result = guarded("register_config", || -> SzResult<ConfigId> {
+            let config_def_c = crate::ffi::helpers::str_to_c_string(config_definition)?;
+            let comment_c = crate::ffi::helpers::str_to_c_string(config_comment.unwrap_or(""))?;
 
-        let result = unsafe {
-            crate::ffi::SzConfigMgr_registerConfig_helper(config_def_c.as_ptr(), comment_c.as_ptr())
-        };
+            let result = unsafe {
+                crate::ffi::SzConfigMgr_registerConfig_helper(
+                    config_def_c.as_ptr(),
+                    comment_c.as_ptr(),
+                )
+            };
 
-        process_config_mgr_long_result!(result)
+            process_config_mgr_long_result!(result)
+        });
+        crate::audit::record(
+            &self.settings,
+            "register_config",
+            || json!({ "config_comment": config_comment, "config_id": result.as_ref().ok() }),
+            &result,
+        );
+        result
     }
 
     fn replace_default_config_id(
@@ -84,11 +113,25 @@ impl SzConfigManager for SzConfigManagerCore {
         current_default_config_id: ConfigId,
         new_default_config_id: ConfigId,
     ) -> SzResult<()> {
-        ffi_call_config_mgr!(crate::ffi::SzConfigMgr_replaceDefaultConfigID(
-            current_default_config_id,
-            new_default_config_id
-        ));
-        Ok(())
+        let result = guarded("replace_default_config_id", || -> SzResult<()> {
+            ffi_call_config_mgr!(crate::ffi::SzConfigMgr_replaceDefaultConfigID(
+                current_default_config_id.get(),
+                new_default_config_id.get()
+            ));
+            Ok(())
+        });
+        crate::audit::record(
+            &self.settings,
+            "replace_default_config_id",
+            || {
+                json!({
+                    "current_default_config_id": current_default_config_id,
+                    "new_default_config_id": new_default_config_id,
+                })
+            },
+            &result,
+        );
+        result
     }
 
     fn set_default_config(
@@ -102,7 +145,16 @@ impl SzConfigManager for SzConfigManagerCore {
     }
 
     fn set_default_config_id(&self, config_id: ConfigId) -> SzResult<()> {
-        ffi_call_config_mgr!(crate::ffi::SzConfigMgr_setDefaultConfigID(config_id));
-        Ok(())
+        let result = guarded("set_default_config_id", || -> SzResult<()> {
+            ffi_call_config_mgr!(crate::ffi::SzConfigMgr_setDefaultConfigID(config_id.get()));
+            Ok(())
+        });
+        crate::audit::record(
+            &self.settings,
+            "set_default_config_id",
+            || json!({ "config_id": config_id }),
+            &result,
+        );
+        result
     }
 }
@@ -1,51 +1,73 @@
 //! Core implementation of SzDiagnostic trait
 
+use super::settings::EngineSettings;
 use crate::{
-    error::SzResult,
+    error::{SzError, SzResult},
+    ffi::boundary::guarded,
     ffi_call_diagnostic, process_diagnostic_result,
     traits::SzDiagnostic,
-    types::{FeatureId, JsonString},
+    types::{FeatureId, JsonString, PurgeConfirmation},
 };
+use std::sync::Arc;
 
 /// Core implementation of the SzDiagnostic trait
 ///
-/// This is a zero-sized type as the diagnostic component uses module-level
-/// functions in the native library after environment initialization.
-pub struct SzDiagnosticCore;
+/// The diagnostic component uses module-level functions in the native library
+/// after environment initialization; the only state kept here is the
+/// environment settings that gate `purge_repository`.
+pub struct SzDiagnosticCore {
+    settings: Arc<EngineSettings>,
+}
 
 impl SzDiagnosticCore {
-    pub fn new() -> SzResult<Self> {
-        Ok(Self)
-    }
-
-    pub fn new_with_params(
-        _module_name: &str,
-        _ini_params: &str,
-        _verbose_logging: bool,
-    ) -> SzResult<Self> {
-        Self::new()
+    pub(crate) fn with_settings(settings: Arc<EngineSettings>) -> Self {
+        Self { settings }
     }
 }
 
 impl SzDiagnostic for SzDiagnosticCore {
     fn check_repository_performance(&self, seconds_to_run: i64) -> SzResult<JsonString> {
-        let result =
-            unsafe { crate::ffi::SzDiagnostic_checkRepositoryPerformance_helper(seconds_to_run) };
-        process_diagnostic_result!(result)
+        guarded("check_repository_performance", || {
+            let result = unsafe {
+                crate::ffi::SzDiagnostic_checkRepositoryPerformance_helper(seconds_to_run)
+            };
+            process_diagnostic_result!(result)
+        })
     }
 
     fn get_feature(&self, feature_id: FeatureId) -> SzResult<JsonString> {
-        let result = unsafe { crate::ffi::SzDiagnostic_getFeature_helper(feature_id) };
-        process_diagnostic_result!(result)
+        guarded("get_feature", || {
+            let result = unsafe { crate::ffi::SzDiagnostic_getFeature_helper(feature_id) };
+            process_diagnostic_result!(result)
+        })
     }
 
     fn get_repository_info(&self) -> SzResult<JsonString> {
-        let result = unsafe { crate::ffi::SzDiagnostic_getRepositoryInfo_helper() };
-        process_diagnostic_result!(result)
+        guarded("get_repository_info", || {
+            let result = unsafe { crate::ffi::SzDiagnostic_getRepositoryInfo_helper() };
+            process_diagnostic_result!(result)
+        })
     }
 
-    fn purge_repository(&self) -> SzResult<()> {
-        ffi_call_diagnostic!(crate::ffi::SzDiagnostic_purgeRepository());
-        Ok(())
+    fn purge_repository(&self, _confirmation: PurgeConfirmation) -> SzResult<()> {
+        if !self.settings.purge_enabled() {
+            return Err(SzError::bad_input(
+                "Repository purge is disabled for this environment",
+            ));
+        }
+        let result = guarded("purge_repository", || -> SzResult<()> {
+            ffi_call_diagnostic!(crate::ffi::SzDiagnostic_purgeRepository());
+            Ok(())
+        });
+        if result.is_ok() {
+            self.settings.set_cached_counts(None);
+        }
+        crate::audit::record(
+            &self.settings,
+            "purge_repository",
+            || serde_json::json!({}),
+            &result,
+        );
+        result
     }
 }
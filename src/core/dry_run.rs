@@ -0,0 +1,167 @@
+//! Dry-run handling for engine mutations
+//!
+//! With `SzEnvironmentCore::set_dry_run(true)` the core engine answers
+//! add, delete, reevaluate and redo calls here instead of calling the native
+//! mutation. Inputs are still validated (records through
+//! `get_record_preview`), and with `WITH_INFO` the response predicts the
+//! affected entities from read-only lookups: the record's current entity and,
+//! for adds, the entities the record would resolve into. The response also
+//! carries `"DRY_RUN": true`.
+
+use crate::{
+    error::{SzError, SzResult},
+    flags::SzFlags,
+    models::{RedoRecord, SearchResponse},
+    traits::SzEngine,
+    types::*,
+};
+
+pub(super) fn add_record(
+    engine: &dyn SzEngine,
+    data_source_code: &str,
+    record_id: &str,
+    record_definition: &str,
+    flags: Option<SzFlags>,
+) -> SzResult<JsonString> {
+    engine.get_record_preview(record_definition, Some(SzFlags::empty()))?;
+    if !with_info(flags) {
+        return Ok(SZ_NO_INFO.to_string());
+    }
+
+    let mut affected = record_entity(engine, data_source_code, record_id)?
+        .into_iter()
+        .collect::<Vec<_>>();
+    let search: SearchResponse = serde_json::from_str(&engine.search_by_attributes(
+        record_definition,
+        None,
+        Some(SzFlags::SEARCH_INCLUDE_RESOLVED),
+    )?)?;
+    for result in search.resolved_entities {
+        let entity_id = result.entity.resolved_entity.entity_id;
+        if result.match_info.match_level_code.as_deref() == Some("RESOLVED")
+            && !affected.contains(&entity_id)
+        {
+            affected.push(entity_id);
+        }
+    }
+    Ok(info(Some((data_source_code, record_id)), &affected))
+}
+
+pub(super) fn record_operation(
+    engine: &dyn SzEngine,
+    data_source_code: &str,
+    record_id: &str,
+    flags: Option<SzFlags>,
+) -> SzResult<JsonString> {
+    let entity = record_entity(engine, data_source_code, record_id)?;
+    if !with_info(flags) {
+        return Ok(SZ_NO_INFO.to_string());
+    }
+    let affected: Vec<_> = entity.into_iter().collect();
+    Ok(info(Some((data_source_code, record_id)), &affected))
+}
+
+pub(super) fn reevaluate_entity(
+    engine: &dyn SzEngine,
+    entity_id: EntityId,
+    flags: Option<SzFlags>,
+) -> SzResult<JsonString> {
+    let exists = entity_exists(engine, EntityRef::Id(entity_id))?;
+    if !with_info(flags) {
+        return Ok(SZ_NO_INFO.to_string());
+    }
+    let affected = if exists { vec![entity_id] } else { Vec::new() };
+    Ok(info(None, &affected))
+}
+
+pub(super) fn process_redo_record(
+    engine: &dyn SzEngine,
+    redo_record: &str,
+    flags: Option<SzFlags>,
+) -> SzResult<JsonString> {
+    let redo = RedoRecord::from_json(redo_record)?;
+    if !with_info(flags) {
+        return Ok(SZ_NO_INFO.to_string());
+    }
+    let affected = match redo.entity_ref() {
+        Some(entity_ref @ EntityRef::Record { .. }) => entity_id_of(engine, entity_ref)?,
+        Some(EntityRef::Id(id)) if entity_exists(engine, EntityRef::Id(id))? => Some(id),
+        _ => None,
+    };
+    let affected: Vec<_> = affected.into_iter().collect();
+    Ok(info(redo.record_key(), &affected))
+}
+
+fn with_info(flags: Option<SzFlags>) -> bool {
+    flags.is_some_and(|flags| flags.contains(SzFlags::WITH_INFO))
+}
+
+fn record_entity(
+    engine: &dyn SzEngine,
+    data_source: &str,
+    record_id: &str,
+) -> SzResult<Option<EntityId>> {
+    entity_id_of(
+        engine,
+        EntityRef::Record {
+            data_source,
+            record_id,
+        },
+    )
+}
+
+fn entity_id_of(engine: &dyn SzEngine, entity_ref: EntityRef) -> SzResult<Option<EntityId>> {
+    match engine.get_entity(entity_ref, Some(SzFlags::empty())) {
+        Ok(json) => {
+            let document: serde_json::Value = serde_json::from_str(&json)?;
+            Ok(document["RESOLVED_ENTITY"]["ENTITY_ID"]
+                .as_i64()
+                .and_then(EntityId::from_engine))
+        }
+        Err(SzError::NotFound(_)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+fn entity_exists(engine: &dyn SzEngine, entity_ref: EntityRef) -> SzResult<bool> {
+    Ok(entity_id_of(engine, entity_ref)?.is_some())
+}
+
+fn info(record: Option<(&str, &str)>, affected: &[EntityId]) -> JsonString {
+    let mut document = serde_json::Map::new();
+    if let Some((data_source, record_id)) = record {
+        document.insert("DATA_SOURCE".into(), data_source.into());
+        document.insert("RECORD_ID".into(), record_id.into());
+    }
+    document.insert(
+        "AFFECTED_ENTITIES".into(),
+        affected
+            .iter()
+            .map(|id| serde_json::json!({ "ENTITY_ID": id }))
+            .collect(),
+    );
+    document.insert(
+        "INTERESTING_ENTITIES".into(),
+        serde_json::json!({ "ENTITIES": [] }),
+    );
+    document.insert("DRY_RUN".into(), true.into());
+    serde_json::Value::Object(document).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_info_document() {
+        let info: serde_json::Value =
+            serde_json::from_str(&info(Some(("TEST", "1")), &[EntityId(4), EntityId(9)])).unwrap();
+        assert_eq!(info["DATA_SOURCE"], "TEST");
+        assert_eq!(info["AFFECTED_ENTITIES"][1]["ENTITY_ID"], 9);
+        assert_eq!(info["DRY_RUN"], true);
+
+        let info: serde_json::Value = serde_json::from_str(&super::info(None, &[])).unwrap();
+        assert!(info.get("RECORD_ID").is_none());
+        assert!(info["AFFECTED_ENTITIES"].as_array().unwrap().is_empty());
+    }
+}
@@ -1,165 +1,303 @@
 //! Core implementation of SzEngine trait
 
+use super::settings::EngineSettings;
+use crate::intercept;
 use crate::{
+    bytes::SzBytes,
     error::{SzError, SzResult},
     ffi_call,
     flags::SzFlags,
     process_engine_result,
-    traits::SzEngine,
+    traits::{SzEngine, SzEngineLoad, SzEngineRead},
     types::*,
 };
+use libc::c_char;
+use serde_json::json;
 use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
-/// Core implementation of the SzEngine trait
-pub struct SzEngineCore;
+/// How often a call waiting on its operation timeout checks the
+/// environment's cancellation token.
+const CANCELLATION_POLL: Duration = Duration::from_millis(50);
 
-impl SzEngineCore {
-    pub fn new() -> SzResult<Self> {
-        Ok(Self)
-    }
+/// Flags kept when a `get_entity` response is downgraded for size
+const ENTITY_REDUCED_FLAGS: SzFlags = SzFlags::ENTITY_BRIEF_DEFAULT_FLAGS;
+
+/// Flags kept when a `find_network_*` response is downgraded for size
+const NETWORK_REDUCED_FLAGS: SzFlags = SzFlags::FIND_NETWORK_INCLUDE_MATCHING_INFO;
+
+/// Core implementation of the SzEngine trait
+pub struct SzEngineCore {
+    settings: Arc<EngineSettings>,
+    /// False for the engine a timed call runs on, so it does not time itself again
+    apply_timeouts: bool,
 }
 
-impl SzEngine for SzEngineCore {
-    fn prime_engine(&self) -> SzResult<()> {
-        ffi_call!(crate::ffi::Sz_primeEngine());
-        Ok(())
+impl SzEngineCore {
+    pub(crate) fn new(settings: Arc<EngineSettings>) -> SzResult<Self> {
+        Ok(Self {
+            settings,
+            apply_timeouts: true,
+        })
     }
 
-    fn get_stats(&self) -> SzResult<JsonString> {
-        let result = unsafe { crate::ffi::Sz_stats_helper() };
-        process_engine_result!(result)
+    /// Returns the timeout to apply to `operation` on this engine, if any.
+    fn timeout_for(&self, operation: TimedOperation) -> Option<Duration> {
+        if self.apply_timeouts {
+            self.settings.operation_timeout(operation)
+        } else {
+            None
+        }
     }
 
-    fn add_record(
+    /// Runs `call` on a worker thread and waits at most `timeout` for it.
+    ///
+    /// The native library has no way to cancel a call, so on timeout the
+    /// call is abandoned: it keeps its worker until it returns and its
+    /// result is dropped. See `SzEnvironmentCore::abandoned_calls`.
+    fn run_with_timeout<T, F>(
         &self,
-        data_source_code: &str,
-        record_id: &str,
-        record_definition: &str,
-        flags: Option<SzFlags>,
-    ) -> SzResult<JsonString> {
-        let data_source_c = crate::ffi::helpers::str_to_c_string(data_source_code)?;
-        let record_id_c = crate::ffi::helpers::str_to_c_string(record_id)?;
-        let record_def_c = crate::ffi::helpers::str_to_c_string(record_definition)?;
-        let flags = flags.unwrap_or(SzFlags::ADD_RECORD_DEFAULT_FLAGS);
-
-        // Only compute (and return) the info document when the caller opts in
-        // via WITH_INFO, mirroring the Python/C# SDKs. Otherwise use the
-        // cheaper non-info entry point and return SZ_NO_INFO.
-        if flags.contains(SzFlags::WITH_INFO) {
-            let result = unsafe {
-                crate::ffi::Sz_addRecordWithInfo_helper(
-                    data_source_c.as_ptr(),
-                    record_id_c.as_ptr(),
-                    record_def_c.as_ptr(),
-                    flags.bits() as i64,
-                )
-            };
-            process_engine_result!(result)
-        } else {
-            let return_code = unsafe {
-                crate::ffi::Sz_addRecord(
-                    data_source_c.as_ptr(),
-                    record_id_c.as_ptr(),
-                    record_def_c.as_ptr(),
-                )
+        operation: &'static str,
+        timeout: Duration,
+        call: F,
+    ) -> SzResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&SzEngineCore) -> SzResult<T> + Send + 'static,
+    {
+        let engine = SzEngineCore {
+            settings: Arc::clone(&self.settings),
+            apply_timeouts: false,
+        };
+        // The worker records the call's timing; this thread only waits
+        crate::profiling::hand_off();
+        let nesting = intercept::Nesting::current();
+        let timed_call = self.settings.timed_calls.submit(operation, move || {
+            nesting.scope(|| crate::ffi::boundary::guarded(operation, || call(&engine)))
+        })?;
+
+        // Wait in slices so a cancelled token abandons the call early
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let slice = match self.settings.cancellation_token() {
+                Some(_) => remaining.min(CANCELLATION_POLL),
+                None => remaining,
             };
-            crate::ffi::helpers::check_return_code(return_code)?;
-            Ok(SZ_NO_INFO.to_string())
+            if let Some(result) = timed_call.wait(slice) {
+                return result;
+            }
+            if slice < remaining {
+                if self.settings.check_cancelled(operation).is_err() {
+                    return timed_call.abandon().unwrap_or_else(|| {
+                        Err(SzError::cancelled(format!(
+                            "{operation} was cancelled; the call was abandoned"
+                        )))
+                    });
+                }
+                continue;
+            }
+            return timed_call.abandon().unwrap_or_else(|| {
+                self.settings.record_timeout(operation, timeout);
+                Err(SzError::timeout(format!(
+                    "{operation} did not complete within {timeout:?}; the call was abandoned"
+                )))
+            });
         }
     }
 
-    fn get_record_preview(
+    /// Runs an engine call through the environment's interceptors, timing
+    /// it as `operation`. `parameters` builds the interceptors' summary of
+    /// the arguments and is only evaluated when there are interceptors.
+    ///
+    /// The whole call, hooks included, runs at the native boundary, so a
+    /// panic is returned as `SzError::Unhandled` and counts towards
+    /// poisoning the environment.
+    fn intercepted<T: intercept::Response>(
         &self,
-        record_definition: &str,
-        flags: Option<SzFlags>,
-    ) -> SzResult<JsonString> {
-        let record_def_c = crate::ffi::helpers::str_to_c_string(record_definition)?;
-        let flags_bits = flags.unwrap_or(SzFlags::RECORD_DEFAULT_FLAGS).bits() as i64;
-
-        let result =
-            unsafe { crate::ffi::Sz_getRecordPreview_helper(record_def_c.as_ptr(), flags_bits) };
+        operation: &'static str,
+        parameters: impl FnOnce() -> serde_json::Value,
+        call: impl FnOnce() -> SzResult<T>,
+    ) -> SzResult<T> {
+        let _call = crate::profiling::call(operation);
+        let parameters = intercept::parameters(&self.settings, parameters);
+        crate::ffi::boundary::guarded(operation, || {
+            intercept::run(&self.settings, operation, parameters, call)
+        })
+    }
 
-        process_engine_result!(result)
+    /// Applies the environment's response format to a JSON response.
+    fn formatted(&self, response: JsonString) -> SzResult<JsonString> {
+        self.settings.response_format().apply(response)
     }
 
-    fn delete_record(
+    /// Records a mutation in the environment's audit sink, if one is set,
+    /// and passes its result through.
+    fn audited<T>(
         &self,
-        data_source_code: &str,
-        record_id: &str,
-        flags: Option<SzFlags>,
-    ) -> SzResult<JsonString> {
-        let data_source_c = crate::ffi::helpers::str_to_c_string(data_source_code)?;
-        let record_id_c = crate::ffi::helpers::str_to_c_string(record_id)?;
-        let flags = flags.unwrap_or(SzFlags::DELETE_RECORD_DEFAULT_FLAGS);
-
-        if flags.contains(SzFlags::WITH_INFO) {
-            let result = unsafe {
-                crate::ffi::Sz_deleteRecordWithInfo_helper(
-                    data_source_c.as_ptr(),
-                    record_id_c.as_ptr(),
-                    flags.bits() as i64,
-                )
-            };
-            process_engine_result!(result)
-        } else {
-            let return_code = unsafe {
-                crate::ffi::Sz_deleteRecord(data_source_c.as_ptr(), record_id_c.as_ptr())
-            };
-            crate::ffi::helpers::check_return_code(return_code)?;
-            Ok(SZ_NO_INFO.to_string())
+        operation: &'static str,
+        parameters: impl FnOnce() -> serde_json::Value,
+        result: SzResult<T>,
+    ) -> SzResult<T> {
+        crate::audit::record(&self.settings, operation, parameters, &result);
+        result
+    }
+
+    /// Whether a data source policy puts the entity in dry-run mode: one of
+    /// its records comes from a data source whose policy is dry-run.
+    ///
+    /// Only looks the entity up when some policy is dry-run.
+    fn entity_policy_dry_run(&self, entity_id: EntityId) -> SzResult<bool> {
+        if !self.settings.has_dry_run_policy() {
+            return Ok(false);
         }
+        let json = match self.get_entity(
+            EntityRef::Id(entity_id),
+            Some(SzFlags::ENTITY_INCLUDE_RECORD_SUMMARY),
+        ) {
+            Ok(json) => json,
+            Err(SzError::NotFound(_)) => return Ok(false),
+            Err(e) => return Err(e),
+        };
+        let document: serde_json::Value = serde_json::from_str(&json)?;
+        Ok(document["RESOLVED_ENTITY"]["RECORD_SUMMARY"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|summary| summary["DATA_SOURCE"].as_str())
+            .any(|data_source| {
+                self.settings
+                    .policy(data_source)
+                    .is_some_and(|policy| policy.dry_run())
+            }))
     }
 
-    fn reevaluate_record(
+    /// Runs a native call whose response is subject to the result size limit.
+    ///
+    /// `call` receives the flag bits and returns the native return code and
+    /// response pointer. The response stays in its native buffer while its size
+    /// is checked, so an oversized response is freed without being copied.
+    fn call_with_size_limit<F>(
         &self,
-        data_source_code: &str,
-        record_id: &str,
-        flags: Option<SzFlags>,
-    ) -> SzResult<JsonString> {
-        let data_source_c = crate::ffi::helpers::str_to_c_string(data_source_code)?;
-        let record_id_c = crate::ffi::helpers::str_to_c_string(record_id)?;
-        let flags = flags.unwrap_or(SzFlags::REEVALUATE_RECORD_DEFAULT_FLAGS);
-
-        if flags.contains(SzFlags::WITH_INFO) {
-            let result = unsafe {
-                crate::ffi::Sz_reevaluateRecordWithInfo_helper(
-                    data_source_c.as_ptr(),
-                    record_id_c.as_ptr(),
-                    flags.bits() as i64,
-                )
-            };
-            process_engine_result!(result)
-        } else {
-            let return_code = unsafe {
-                crate::ffi::Sz_reevaluateRecord(
-                    data_source_c.as_ptr(),
-                    record_id_c.as_ptr(),
-                    flags.bits() as i64,
-                )
-            };
-            crate::ffi::helpers::check_return_code(return_code)?;
-            Ok(SZ_NO_INFO.to_string())
+        operation: &str,
+        flags: SzFlags,
+        reduced_mask: SzFlags,
+        call: F,
+    ) -> SzResult<SzBytes>
+    where
+        F: Fn(i64) -> (i64, *mut c_char),
+    {
+        let (return_code, response) = call(flags.bits() as i64);
+        let response = native_response(return_code, response)?;
+
+        let Some(limit) = self.settings.result_size_limit() else {
+            return Ok(response);
+        };
+        let size = response.len();
+        if size <= limit.max_bytes {
+            return Ok(response);
+        }
+        drop(response);
+
+        let reduced = reduced_flags(flags, reduced_mask);
+        if limit.action == OversizeAction::DowngradeFlags && reduced != flags {
+            let (return_code, response) = call(reduced.bits() as i64);
+            let response = native_response(return_code, response)?;
+
+            let reduced_size = response.len();
+            if reduced_size <= limit.max_bytes {
+                return Ok(response);
+            }
+
+            return Err(SzError::result_too_large(
+                format!(
+                    "{operation} response is {reduced_size} bytes even with reduced flags \
+                     (limit {} bytes)",
+                    limit.max_bytes
+                ),
+                reduced_flags(reduced, reduced_mask),
+            ));
         }
+
+        Err(SzError::result_too_large(
+            format!(
+                "{operation} response is {size} bytes (limit {} bytes)",
+                limit.max_bytes
+            ),
+            reduced,
+        ))
     }
+}
 
-    fn reevaluate_entity(
+/// Takes ownership of a native response and checks the return code.
+///
+/// The response is wrapped first so that it is freed on the error path too.
+fn native_response(return_code: i64, response: *mut c_char) -> SzResult<SzBytes> {
+    let response = unsafe { SzBytes::from_native(response) };
+    crate::ffi::helpers::check_return_code(return_code)?;
+    Ok(response)
+}
+
+/// Computes the flags to retry with when a response is too large.
+///
+/// Keeps only the requested flags that are also in `reduced_mask`. When that
+/// removes nothing, falls back to no flags at all, which asks the engine for
+/// identifiers only.
+fn reduced_flags(requested: SzFlags, reduced_mask: SzFlags) -> SzFlags {
+    let reduced = requested & reduced_mask;
+    if reduced == requested {
+        SzFlags::empty()
+    } else {
+        reduced
+    }
+}
+
+impl SzEngine for SzEngineCore {
+    fn prime_engine(&self) -> SzResult<()> {
+        self.intercepted(
+            "prime_engine",
+            || json!({}),
+            || {
+                ffi_call!(crate::ffi::Sz_primeEngine());
+                Ok(())
+            },
+        )
+    }
+}
+
+impl SzEngineRead for SzEngineCore {
+    fn get_stats(&self) -> SzResult<JsonString> {
+        self.intercepted(
+            "get_stats",
+            || json!({}),
+            || {
+                let result = crate::profiling::native(|| unsafe { crate::ffi::Sz_stats_helper() });
+                self.formatted(process_engine_result!(result)?)
+            },
+        )
+    }
+
+    fn get_record_preview(
         &self,
-        entity_id: EntityId,
+        record_definition: &str,
         flags: Option<SzFlags>,
     ) -> SzResult<JsonString> {
-        let flags = flags.unwrap_or(SzFlags::REEVALUATE_ENTITY_DEFAULT_FLAGS);
-
-        if flags.contains(SzFlags::WITH_INFO) {
-            let result = unsafe {
-                crate::ffi::Sz_reevaluateEntityWithInfo_helper(entity_id, flags.bits() as i64)
-            };
-            process_engine_result!(result)
-        } else {
-            let return_code =
-                unsafe { crate::ffi::Sz_reevaluateEntity(entity_id, flags.bits() as i64) };
-            crate::ffi::helpers::check_return_code(return_code)?;
-            Ok(SZ_NO_INFO.to_string())
-        }
+        let flags = crate::context::effective_flags(flags, SzFlags::RECORD_DEFAULT_FLAGS);
+        self.intercepted(
+            "get_record_preview",
+            || json!({ "flags": flags.map(|f| f.bits()) }),
+            || {
+                let record_def_c = self.settings.buffer_pool.c_string(record_definition)?;
+                let flags_bits = flags.unwrap_or(SzFlags::RECORD_DEFAULT_FLAGS).bits() as i64;
+
+                let result = crate::profiling::native(|| unsafe {
+                    crate::ffi::Sz_getRecordPreview_helper(record_def_c.as_ptr(), flags_bits)
+                });
+
+                self.formatted(process_engine_result!(result)?)
+            },
+        )
     }
 
     fn search_by_attributes(
@@ -168,28 +306,69 @@ impl SzEngine for SzEngineCore {
         search_profile: Option<&str>,
         flags: Option<SzFlags>,
     ) -> SzResult<JsonString> {
-        let attributes_c = crate::ffi::helpers::str_to_c_string(attributes)?;
-        let flags_bits = flags
-            .unwrap_or(SzFlags::SEARCH_BY_ATTRIBUTES_DEFAULT_FLAGS)
-            .bits() as i64;
-
-        // V2 and V3 have different result types, so handle separately
-        if let Some(profile) = search_profile {
-            let search_profile_c = crate::ffi::helpers::str_to_c_string(profile)?;
-            let result = unsafe {
-                crate::ffi::Sz_searchByAttributes_V3_helper(
-                    attributes_c.as_ptr(),
-                    search_profile_c.as_ptr(),
-                    flags_bits,
-                )
-            };
-            process_engine_result!(result)
-        } else {
-            let result = unsafe {
-                crate::ffi::Sz_searchByAttributes_V2_helper(attributes_c.as_ptr(), flags_bits)
-            };
-            process_engine_result!(result)
-        }
+        let flags =
+            crate::context::effective_flags(flags, SzFlags::SEARCH_BY_ATTRIBUTES_DEFAULT_FLAGS);
+        self.intercepted(
+            "search_by_attributes",
+            || json!({ "search_profile": search_profile, "flags": flags.map(|f| f.bits()) }),
+            || {
+                self.search_by_attributes_bytes(attributes, search_profile, flags)
+                    .and_then(|response| self.formatted(response.into_json_string()))
+            },
+        )
+    }
+
+    fn search_by_attributes_bytes(
+        &self,
+        attributes: &str,
+        search_profile: Option<&str>,
+        flags: Option<SzFlags>,
+    ) -> SzResult<SzBytes> {
+        let flags =
+            crate::context::effective_flags(flags, SzFlags::SEARCH_BY_ATTRIBUTES_DEFAULT_FLAGS);
+        self.intercepted(
+            "search_by_attributes_bytes",
+            || json!({ "search_profile": search_profile, "flags": flags.map(|f| f.bits()) }),
+            || {
+                if let Some(timeout) = self.timeout_for(TimedOperation::SearchByAttributes) {
+                    let attributes = attributes.to_string();
+                    let search_profile = search_profile.map(str::to_string);
+                    return self.run_with_timeout("search_by_attributes", timeout, move |engine| {
+                        engine.search_by_attributes_bytes(
+                            &attributes,
+                            search_profile.as_deref(),
+                            flags,
+                        )
+                    });
+                }
+
+                let attributes_c = self.settings.buffer_pool.c_string(attributes)?;
+                let flags_bits = flags
+                    .unwrap_or(SzFlags::SEARCH_BY_ATTRIBUTES_DEFAULT_FLAGS)
+                    .bits() as i64;
+
+                // V2 and V3 have different result types, so handle separately
+                if let Some(profile) = search_profile {
+                    let search_profile_c = crate::ffi::helpers::str_to_c_string(profile)?;
+                    let result = crate::profiling::native(|| unsafe {
+                        crate::ffi::Sz_searchByAttributes_V3_helper(
+                            attributes_c.as_ptr(),
+                            search_profile_c.as_ptr(),
+                            flags_bits,
+                        )
+                    });
+                    native_response(result.returnCode, result.response)
+                } else {
+                    let result = crate::profiling::native(|| unsafe {
+                        crate::ffi::Sz_searchByAttributes_V2_helper(
+                            attributes_c.as_ptr(),
+                            flags_bits,
+                        )
+                    });
+                    native_response(result.returnCode, result.response)
+                }
+            },
+        )
     }
 
     fn why_search(
@@ -199,53 +378,127 @@ impl SzEngine for SzEngineCore {
         search_profile: Option<&str>,
         flags: Option<SzFlags>,
     ) -> SzResult<JsonString> {
-        let attributes_c = crate::ffi::helpers::str_to_c_string(attributes)?;
-        let search_profile_c = search_profile
-            .map(crate::ffi::helpers::str_to_c_string)
-            .transpose()?;
-        let search_profile_ptr = search_profile_c
-            .as_ref()
-            .map(|c_str| c_str.as_ptr())
-            .unwrap_or(std::ptr::null());
-        let flags_bits = flags.unwrap_or(SzFlags::WHY_SEARCH_DEFAULT_FLAGS).bits() as i64;
-
-        let result = unsafe {
-            crate::ffi::Sz_whySearch_V2_helper(
-                attributes_c.as_ptr(),
-                entity_id,
-                search_profile_ptr,
-                flags_bits,
-            )
-        };
+        let flags = crate::context::effective_flags(flags, SzFlags::WHY_SEARCH_DEFAULT_FLAGS);
+        self.intercepted(
+            "why_search",
+            || {
+                json!({
+                    "entity_id": entity_id,
+                    "search_profile": search_profile,
+                    "flags": flags.map(|f| f.bits()),
+                })
+            },
+            || {
+                let attributes_c = crate::ffi::helpers::str_to_c_string(attributes)?;
+                let search_profile_c = search_profile
+                    .map(crate::ffi::helpers::str_to_c_string)
+                    .transpose()?;
+                let search_profile_ptr = search_profile_c
+                    .as_ref()
+                    .map(|c_str| c_str.as_ptr())
+                    .unwrap_or(std::ptr::null());
+                let flags_bits = flags.unwrap_or(SzFlags::WHY_SEARCH_DEFAULT_FLAGS).bits() as i64;
+
+                let result = crate::profiling::native(|| unsafe {
+                    crate::ffi::Sz_whySearch_V2_helper(
+                        attributes_c.as_ptr(),
+                        entity_id.get(),
+                        search_profile_ptr,
+                        flags_bits,
+                    )
+                });
 
-        process_engine_result!(result)
+                self.formatted(process_engine_result!(result)?)
+            },
+        )
     }
 
     fn get_entity(&self, entity_ref: EntityRef, flags: Option<SzFlags>) -> SzResult<JsonString> {
-        let flags_bits = flags.unwrap_or(SzFlags::ENTITY_DEFAULT_FLAGS).bits() as i64;
+        let flags = crate::context::effective_flags(flags, SzFlags::ENTITY_DEFAULT_FLAGS);
+        self.intercepted(
+            "get_entity",
+            || {
+                json!({
+                    "entity": intercept::entity_ref(&entity_ref),
+                    "flags": flags.map(|f| f.bits()),
+                })
+            },
+            || {
+                self.get_entity_bytes(entity_ref.clone(), flags)
+                    .and_then(|response| self.formatted(response.into_json_string()))
+            },
+        )
+    }
 
-        match entity_ref {
-            EntityRef::Id(entity_id) => {
-                let result =
-                    unsafe { crate::ffi::Sz_getEntityByEntityID_V2_helper(entity_id, flags_bits) };
-                process_engine_result!(result)
-            }
-            EntityRef::Record {
-                data_source,
-                record_id,
-            } => {
-                let data_source_c = crate::ffi::helpers::str_to_c_string(data_source)?;
-                let record_id_c = crate::ffi::helpers::str_to_c_string(record_id)?;
-                let result = unsafe {
-                    crate::ffi::Sz_getEntityByRecordID_V2_helper(
-                        data_source_c.as_ptr(),
-                        record_id_c.as_ptr(),
-                        flags_bits,
-                    )
-                };
-                process_engine_result!(result)
-            }
-        }
+    fn get_entity_bytes(&self, entity_ref: EntityRef, flags: Option<SzFlags>) -> SzResult<SzBytes> {
+        let flags = crate::context::effective_flags(flags, SzFlags::ENTITY_DEFAULT_FLAGS);
+        self.intercepted(
+            "get_entity_bytes",
+            || {
+                json!({
+                    "entity": intercept::entity_ref(&entity_ref),
+                    "flags": flags.map(|f| f.bits()),
+                })
+            },
+            || {
+                if let Some(timeout) = self.timeout_for(TimedOperation::GetEntity) {
+                    let key = match entity_ref {
+                        EntityRef::Id(entity_id) => Err(entity_id),
+                        EntityRef::Record {
+                            data_source,
+                            record_id,
+                        } => Ok((data_source.to_string(), record_id.to_string())),
+                    };
+                    return self.run_with_timeout("get_entity", timeout, move |engine| {
+                        let entity_ref = match &key {
+                            Err(entity_id) => EntityRef::Id(*entity_id),
+                            Ok((data_source, record_id)) => {
+                                EntityRef::from_record(data_source, record_id)
+                            }
+                        };
+                        engine.get_entity_bytes(entity_ref, flags)
+                    });
+                }
+
+                let flags = flags.unwrap_or(SzFlags::ENTITY_DEFAULT_FLAGS);
+
+                match entity_ref {
+                    EntityRef::Id(entity_id) => self.call_with_size_limit(
+                        "get_entity",
+                        flags,
+                        ENTITY_REDUCED_FLAGS,
+                        |bits| {
+                            let result = crate::profiling::native(|| unsafe {
+                                crate::ffi::Sz_getEntityByEntityID_V2_helper(entity_id.get(), bits)
+                            });
+                            (result.returnCode, result.response)
+                        },
+                    ),
+                    EntityRef::Record {
+                        data_source,
+                        record_id,
+                    } => {
+                        let data_source_c = crate::ffi::helpers::str_to_c_string(data_source)?;
+                        let record_id_c = crate::ffi::helpers::str_to_c_string(record_id)?;
+                        self.call_with_size_limit(
+                            "get_entity",
+                            flags,
+                            ENTITY_REDUCED_FLAGS,
+                            |bits| {
+                                let result = crate::profiling::native(|| unsafe {
+                                    crate::ffi::Sz_getEntityByRecordID_V2_helper(
+                                        data_source_c.as_ptr(),
+                                        record_id_c.as_ptr(),
+                                        bits,
+                                    )
+                                });
+                                (result.returnCode, result.response)
+                            },
+                        )
+                    }
+                }
+            },
+        )
     }
 
     fn get_record(
@@ -254,54 +507,108 @@ impl SzEngine for SzEngineCore {
         record_id: &str,
         flags: Option<SzFlags>,
     ) -> SzResult<JsonString> {
-        let data_source_c = crate::ffi::helpers::str_to_c_string(data_source_code)?;
-        let record_id_c = crate::ffi::helpers::str_to_c_string(record_id)?;
-        let flags_bits = flags.unwrap_or(SzFlags::RECORD_DEFAULT_FLAGS).bits() as i64;
-
-        // Use V2 helper which accepts flags
-        let result = unsafe {
-            crate::ffi::Sz_getRecord_V2_helper(
-                data_source_c.as_ptr(),
-                record_id_c.as_ptr(),
-                flags_bits,
-            )
-        };
-
-        process_engine_result!(result)
+        let flags = crate::context::effective_flags(flags, SzFlags::RECORD_DEFAULT_FLAGS);
+        self.intercepted(
+            "get_record",
+            || {
+                json!({
+                    "data_source": data_source_code,
+                    "record_id": record_id,
+                    "flags": flags.map(|f| f.bits()),
+                })
+            },
+            || {
+                self.get_record_bytes(data_source_code, record_id, flags)
+                    .and_then(|response| self.formatted(response.into_json_string()))
+            },
+        )
     }
 
-    fn find_interesting_entities(
+    fn get_record_bytes(
         &self,
-        entity_ref: EntityRef,
+        data_source_code: &str,
+        record_id: &str,
         flags: Option<SzFlags>,
-    ) -> SzResult<JsonString> {
-        let flags_bits = flags
-            .unwrap_or(SzFlags::FIND_INTERESTING_ENTITIES_DEFAULT_FLAGS)
-            .bits() as i64;
-
-        match entity_ref {
-            EntityRef::Id(entity_id) => {
-                let result = unsafe {
-                    crate::ffi::Sz_findInterestingEntitiesByEntityID_helper(entity_id, flags_bits)
-                };
-                process_engine_result!(result)
-            }
-            EntityRef::Record {
-                data_source,
-                record_id,
-            } => {
-                let data_source_c = crate::ffi::helpers::str_to_c_string(data_source)?;
+    ) -> SzResult<SzBytes> {
+        let flags = crate::context::effective_flags(flags, SzFlags::RECORD_DEFAULT_FLAGS);
+        self.intercepted(
+            "get_record_bytes",
+            || {
+                json!({
+                    "data_source": data_source_code,
+                    "record_id": record_id,
+                    "flags": flags.map(|f| f.bits()),
+                })
+            },
+            || {
+                let data_source_c = crate::ffi::helpers::str_to_c_string(data_source_code)?;
                 let record_id_c = crate::ffi::helpers::str_to_c_string(record_id)?;
-                let result = unsafe {
-                    crate::ffi::Sz_findInterestingEntitiesByRecordID_helper(
+                let flags_bits = flags.unwrap_or(SzFlags::RECORD_DEFAULT_FLAGS).bits() as i64;
+
+                // Use V2 helper which accepts flags
+                let result = crate::profiling::native(|| unsafe {
+                    crate::ffi::Sz_getRecord_V2_helper(
                         data_source_c.as_ptr(),
                         record_id_c.as_ptr(),
                         flags_bits,
                     )
-                };
-                process_engine_result!(result)
-            }
-        }
+                });
+
+                native_response(result.returnCode, result.response)
+            },
+        )
+    }
+
+    fn find_interesting_entities(
+        &self,
+        entity_ref: EntityRef,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        let flags = crate::context::effective_flags(
+            flags,
+            SzFlags::FIND_INTERESTING_ENTITIES_DEFAULT_FLAGS,
+        );
+        self.intercepted(
+            "find_interesting_entities",
+            || {
+                json!({
+                    "entity": intercept::entity_ref(&entity_ref),
+                    "flags": flags.map(|f| f.bits()),
+                })
+            },
+            || {
+                let flags_bits = flags
+                    .unwrap_or(SzFlags::FIND_INTERESTING_ENTITIES_DEFAULT_FLAGS)
+                    .bits() as i64;
+
+                match entity_ref {
+                    EntityRef::Id(entity_id) => {
+                        let result = crate::profiling::native(|| unsafe {
+                            crate::ffi::Sz_findInterestingEntitiesByEntityID_helper(
+                                entity_id.get(),
+                                flags_bits,
+                            )
+                        });
+                        self.formatted(process_engine_result!(result)?)
+                    }
+                    EntityRef::Record {
+                        data_source,
+                        record_id,
+                    } => {
+                        let data_source_c = crate::ffi::helpers::str_to_c_string(data_source)?;
+                        let record_id_c = crate::ffi::helpers::str_to_c_string(record_id)?;
+                        let result = crate::profiling::native(|| unsafe {
+                            crate::ffi::Sz_findInterestingEntitiesByRecordID_helper(
+                                data_source_c.as_ptr(),
+                                record_id_c.as_ptr(),
+                                flags_bits,
+                            )
+                        });
+                        self.formatted(process_engine_result!(result)?)
+                    }
+                }
+            },
+        )
     }
 
     fn find_path_by_entity_id(
@@ -309,23 +616,56 @@ impl SzEngine for SzEngineCore {
         start_entity_id: EntityId,
         end_entity_id: EntityId,
         max_degrees: i64,
-        _avoid_entity_ids: Option<&HashSet<EntityId>>,
-        _required_data_sources: Option<&HashSet<String>>,
+        avoid_entity_ids: Option<&HashSet<EntityId>>,
+        required_data_sources: Option<&HashSet<String>>,
         flags: Option<SzFlags>,
     ) -> SzResult<JsonString> {
-        let flags_bits = flags.unwrap_or(SzFlags::FIND_PATH_DEFAULT_FLAGS).bits() as i64;
-
-        // Use V2 helper which accepts flags
-        let result = unsafe {
-            crate::ffi::Sz_findPathByEntityID_V2_helper(
-                start_entity_id,
-                end_entity_id,
-                max_degrees,
-                flags_bits,
-            )
-        };
+        let flags = crate::context::effective_flags(flags, SzFlags::FIND_PATH_DEFAULT_FLAGS);
+        self.intercepted(
+            "find_path_by_entity_id",
+            || {
+                json!({
+                    "start_entity_id": start_entity_id,
+                    "end_entity_id": end_entity_id,
+                    "max_degrees": max_degrees,
+                    "flags": flags.map(|f| f.bits()),
+                })
+            },
+            || {
+                if let Some(timeout) = self.timeout_for(TimedOperation::FindPath) {
+                    let avoid_entity_ids = avoid_entity_ids.cloned();
+                    let required_data_sources = required_data_sources.cloned();
+                    return self.run_with_timeout(
+                        "find_path_by_entity_id",
+                        timeout,
+                        move |engine| {
+                            engine.find_path_by_entity_id(
+                                start_entity_id,
+                                end_entity_id,
+                                max_degrees,
+                                avoid_entity_ids.as_ref(),
+                                required_data_sources.as_ref(),
+                                flags,
+                            )
+                        },
+                    );
+                }
+
+                let flags_bits = flags.unwrap_or(SzFlags::FIND_PATH_DEFAULT_FLAGS).bits() as i64;
+
+                // Use V2 helper which accepts flags
+                let result = crate::profiling::native(|| unsafe {
+                    crate::ffi::Sz_findPathByEntityID_V2_helper(
+                        start_entity_id.get(),
+                        end_entity_id.get(),
+                        max_degrees,
+                        flags_bits,
+                    )
+                });
 
-        process_engine_result!(result)
+                self.formatted(process_engine_result!(result)?)
+            },
+        )
     }
 
     #[allow(clippy::too_many_arguments)]
@@ -336,29 +676,71 @@ impl SzEngine for SzEngineCore {
         end_data_source_code: &str,
         end_record_id: &str,
         max_degrees: i64,
-        _avoid_record_keys: Option<&[(&str, &str)]>,
-        _required_data_sources: Option<&HashSet<String>>,
+        avoid_record_keys: Option<&[RecordKey]>,
+        required_data_sources: Option<&HashSet<String>>,
         flags: Option<SzFlags>,
     ) -> SzResult<JsonString> {
-        let flags_bits = flags.unwrap_or(SzFlags::FIND_PATH_DEFAULT_FLAGS).bits() as i64;
-
-        let start_ds_c = crate::ffi::helpers::str_to_c_string(start_data_source_code)?;
-        let start_rid_c = crate::ffi::helpers::str_to_c_string(start_record_id)?;
-        let end_ds_c = crate::ffi::helpers::str_to_c_string(end_data_source_code)?;
-        let end_rid_c = crate::ffi::helpers::str_to_c_string(end_record_id)?;
-
-        let result = unsafe {
-            crate::ffi::Sz_findPathByRecordID_V2_helper(
-                start_ds_c.as_ptr(),
-                start_rid_c.as_ptr(),
-                end_ds_c.as_ptr(),
-                end_rid_c.as_ptr(),
-                max_degrees,
-                flags_bits,
-            )
-        };
+        let flags = crate::context::effective_flags(flags, SzFlags::FIND_PATH_DEFAULT_FLAGS);
+        self.intercepted(
+            "find_path_by_record_id",
+            || {
+                json!({
+                    "start_data_source": start_data_source_code,
+                    "start_record_id": start_record_id,
+                    "end_data_source": end_data_source_code,
+                    "end_record_id": end_record_id,
+                    "max_degrees": max_degrees,
+                    "flags": flags.map(|f| f.bits()),
+                })
+            },
+            || {
+                if let Some(timeout) = self.timeout_for(TimedOperation::FindPath) {
+                    let start = (
+                        start_data_source_code.to_string(),
+                        start_record_id.to_string(),
+                    );
+                    let end = (end_data_source_code.to_string(), end_record_id.to_string());
+                    let avoid_record_keys = avoid_record_keys.map(<[RecordKey]>::to_vec);
+                    let required_data_sources = required_data_sources.cloned();
+                    return self.run_with_timeout(
+                        "find_path_by_record_id",
+                        timeout,
+                        move |engine| {
+                            engine.find_path_by_record_id(
+                                &start.0,
+                                &start.1,
+                                &end.0,
+                                &end.1,
+                                max_degrees,
+                                avoid_record_keys.as_deref(),
+                                required_data_sources.as_ref(),
+                                flags,
+                            )
+                        },
+                    );
+                }
+
+                let flags_bits = flags.unwrap_or(SzFlags::FIND_PATH_DEFAULT_FLAGS).bits() as i64;
+
+                let start_ds_c = crate::ffi::helpers::str_to_c_string(start_data_source_code)?;
+                let start_rid_c = crate::ffi::helpers::str_to_c_string(start_record_id)?;
+                let end_ds_c = crate::ffi::helpers::str_to_c_string(end_data_source_code)?;
+                let end_rid_c = crate::ffi::helpers::str_to_c_string(end_record_id)?;
+
+                let result = crate::profiling::native(|| unsafe {
+                    crate::ffi::Sz_findPathByRecordID_V2_helper(
+                        start_ds_c.as_ptr(),
+                        start_rid_c.as_ptr(),
+                        end_ds_c.as_ptr(),
+                        end_rid_c.as_ptr(),
+                        max_degrees,
+                        flags_bits,
+                    )
+                });
 
-        process_engine_result!(result)
+                self.formatted(process_engine_result!(result)?)
+            },
+        )
     }
 
     fn find_network_by_entity_id(
@@ -369,64 +751,137 @@ impl SzEngine for SzEngineCore {
         max_entities: i64,
         flags: Option<SzFlags>,
     ) -> SzResult<JsonString> {
-        let entity_objects: Vec<serde_json::Value> = entity_list
-            .iter()
-            .map(|&id| serde_json::json!({"ENTITY_ID": id}))
-            .collect();
-
-        let entity_list_json = serde_json::json!({
-            "ENTITIES": entity_objects
-        })
-        .to_string();
-
-        let entity_list_c = crate::ffi::helpers::str_to_c_string(&entity_list_json)?;
-        let flags_bits = flags.unwrap_or(SzFlags::FIND_NETWORK_DEFAULT_FLAGS).bits() as i64;
-
-        let result = unsafe {
-            crate::ffi::Sz_findNetworkByEntityID_V2_helper(
-                entity_list_c.as_ptr(),
-                max_degrees,
-                build_out_degrees,
-                max_entities,
-                flags_bits,
-            )
-        };
-
-        process_engine_result!(result)
+        let flags = crate::context::effective_flags(flags, SzFlags::FIND_NETWORK_DEFAULT_FLAGS);
+        self.intercepted(
+            "find_network_by_entity_id",
+            || {
+                json!({
+                    "entity_ids": entity_list,
+                    "max_degrees": max_degrees,
+                    "build_out_degrees": build_out_degrees,
+                    "max_entities": max_entities,
+                    "flags": flags.map(|f| f.bits()),
+                })
+            },
+            || {
+                if let Some(timeout) = self.timeout_for(TimedOperation::FindNetwork) {
+                    let entity_list = entity_list.to_vec();
+                    return self.run_with_timeout(
+                        "find_network_by_entity_id",
+                        timeout,
+                        move |engine| {
+                            engine.find_network_by_entity_id(
+                                &entity_list,
+                                max_degrees,
+                                build_out_degrees,
+                                max_entities,
+                                flags,
+                            )
+                        },
+                    );
+                }
+
+                let entity_objects: Vec<serde_json::Value> = entity_list
+                    .iter()
+                    .map(|&id| serde_json::json!({"ENTITY_ID": id}))
+                    .collect();
+
+                let entity_list_json = serde_json::json!({
+                    "ENTITIES": entity_objects
+                })
+                .to_string();
+
+                let entity_list_c = crate::ffi::helpers::str_to_c_string(&entity_list_json)?;
+                let flags = flags.unwrap_or(SzFlags::FIND_NETWORK_DEFAULT_FLAGS);
+
+                self.call_with_size_limit(
+                    "find_network_by_entity_id",
+                    flags,
+                    NETWORK_REDUCED_FLAGS,
+                    |bits| {
+                        let result = crate::profiling::native(|| unsafe {
+                            crate::ffi::Sz_findNetworkByEntityID_V2_helper(
+                                entity_list_c.as_ptr(),
+                                max_degrees,
+                                build_out_degrees,
+                                max_entities,
+                                bits,
+                            )
+                        });
+                        (result.returnCode, result.response)
+                    },
+                )
+                .and_then(|response| self.formatted(response.into_json_string()))
+            },
+        )
     }
 
     fn find_network_by_record_id(
         &self,
-        record_keys: &[(&str, &str)],
+        record_keys: &[RecordKey],
         max_degrees: i64,
         build_out_degrees: i64,
         max_entities: i64,
         flags: Option<SzFlags>,
     ) -> SzResult<JsonString> {
-        let record_objects: Vec<serde_json::Value> = record_keys
-            .iter()
-            .map(|(ds, rid)| serde_json::json!({"DATA_SOURCE": ds, "RECORD_ID": rid}))
-            .collect();
-
-        let record_list_json = serde_json::json!({
-            "RECORDS": record_objects
-        })
-        .to_string();
-
-        let record_list_c = crate::ffi::helpers::str_to_c_string(&record_list_json)?;
-        let flags_bits = flags.unwrap_or(SzFlags::FIND_NETWORK_DEFAULT_FLAGS).bits() as i64;
-
-        let result = unsafe {
-            crate::ffi::Sz_findNetworkByRecordID_V2_helper(
-                record_list_c.as_ptr(),
-                max_degrees,
-                build_out_degrees,
-                max_entities,
-                flags_bits,
-            )
-        };
-
-        process_engine_result!(result)
+        let flags = crate::context::effective_flags(flags, SzFlags::FIND_NETWORK_DEFAULT_FLAGS);
+        self.intercepted(
+            "find_network_by_record_id",
+            || {
+                json!({
+                    "record_keys": record_keys,
+                    "max_degrees": max_degrees,
+                    "build_out_degrees": build_out_degrees,
+                    "max_entities": max_entities,
+                    "flags": flags.map(|f| f.bits()),
+                })
+            },
+            || {
+                if let Some(timeout) = self.timeout_for(TimedOperation::FindNetwork) {
+                    let record_keys = record_keys.to_vec();
+                    return self.run_with_timeout(
+                        "find_network_by_record_id",
+                        timeout,
+                        move |engine| {
+                            engine.find_network_by_record_id(
+                                &record_keys,
+                                max_degrees,
+                                build_out_degrees,
+                                max_entities,
+                                flags,
+                            )
+                        },
+                    );
+                }
+
+                let record_list_json = serde_json::json!({
+                    "RECORDS": record_keys
+                })
+                .to_string();
+
+                let record_list_c = crate::ffi::helpers::str_to_c_string(&record_list_json)?;
+                let flags = flags.unwrap_or(SzFlags::FIND_NETWORK_DEFAULT_FLAGS);
+
+                self.call_with_size_limit(
+                    "find_network_by_record_id",
+                    flags,
+                    NETWORK_REDUCED_FLAGS,
+                    |bits| {
+                        let result = crate::profiling::native(|| unsafe {
+                            crate::ffi::Sz_findNetworkByRecordID_V2_helper(
+                                record_list_c.as_ptr(),
+                                max_degrees,
+                                build_out_degrees,
+                                max_entities,
+                                bits,
+                            )
+                        });
+                        (result.returnCode, result.response)
+                    },
+                )
+                .and_then(|response| self.formatted(response.into_json_string()))
+            },
+        )
     }
 
     fn why_entities(
@@ -435,13 +890,37 @@ impl SzEngine for SzEngineCore {
         entity_id2: EntityId,
         flags: Option<SzFlags>,
     ) -> SzResult<JsonString> {
-        let flags_bits = flags.unwrap_or(SzFlags::WHY_ENTITIES_DEFAULT_FLAGS).bits() as i64;
-
-        // Use V2 helper which accepts flags
-        let result =
-            unsafe { crate::ffi::Sz_whyEntities_V2_helper(entity_id1, entity_id2, flags_bits) };
+        let flags = crate::context::effective_flags(flags, SzFlags::WHY_ENTITIES_DEFAULT_FLAGS);
+        self.intercepted(
+            "why_entities",
+            || {
+                json!({
+                    "entity_id1": entity_id1,
+                    "entity_id2": entity_id2,
+                    "flags": flags.map(|f| f.bits()),
+                })
+            },
+            || {
+                if let Some(timeout) = self.timeout_for(TimedOperation::WhyEntities) {
+                    return self.run_with_timeout("why_entities", timeout, move |engine| {
+                        engine.why_entities(entity_id1, entity_id2, flags)
+                    });
+                }
+
+                let flags_bits = flags.unwrap_or(SzFlags::WHY_ENTITIES_DEFAULT_FLAGS).bits() as i64;
+
+                // Use V2 helper which accepts flags
+                let result = crate::profiling::native(|| unsafe {
+                    crate::ffi::Sz_whyEntities_V2_helper(
+                        entity_id1.get(),
+                        entity_id2.get(),
+                        flags_bits,
+                    )
+                });
 
-        process_engine_result!(result)
+                self.formatted(process_engine_result!(result)?)
+            },
+        )
     }
 
     fn why_records(
@@ -452,24 +931,39 @@ impl SzEngine for SzEngineCore {
         record_id2: &str,
         flags: Option<SzFlags>,
     ) -> SzResult<JsonString> {
-        let data_source1_c = crate::ffi::helpers::str_to_c_string(data_source_code1)?;
-        let record_id1_c = crate::ffi::helpers::str_to_c_string(record_id1)?;
-        let data_source2_c = crate::ffi::helpers::str_to_c_string(data_source_code2)?;
-        let record_id2_c = crate::ffi::helpers::str_to_c_string(record_id2)?;
-        let flags_bits = flags.unwrap_or(SzFlags::WHY_RECORDS_DEFAULT_FLAGS).bits() as i64;
-
-        // Use V2 helper which accepts flags
-        let result = unsafe {
-            crate::ffi::Sz_whyRecords_V2_helper(
-                data_source1_c.as_ptr(),
-                record_id1_c.as_ptr(),
-                data_source2_c.as_ptr(),
-                record_id2_c.as_ptr(),
-                flags_bits,
-            )
-        };
+        let flags = crate::context::effective_flags(flags, SzFlags::WHY_RECORDS_DEFAULT_FLAGS);
+        self.intercepted(
+            "why_records",
+            || {
+                json!({
+                    "data_source1": data_source_code1,
+                    "record_id1": record_id1,
+                    "data_source2": data_source_code2,
+                    "record_id2": record_id2,
+                    "flags": flags.map(|f| f.bits()),
+                })
+            },
+            || {
+                let data_source1_c = crate::ffi::helpers::str_to_c_string(data_source_code1)?;
+                let record_id1_c = crate::ffi::helpers::str_to_c_string(record_id1)?;
+                let data_source2_c = crate::ffi::helpers::str_to_c_string(data_source_code2)?;
+                let record_id2_c = crate::ffi::helpers::str_to_c_string(record_id2)?;
+                let flags_bits = flags.unwrap_or(SzFlags::WHY_RECORDS_DEFAULT_FLAGS).bits() as i64;
+
+                // Use V2 helper which accepts flags
+                let result = crate::profiling::native(|| unsafe {
+                    crate::ffi::Sz_whyRecords_V2_helper(
+                        data_source1_c.as_ptr(),
+                        record_id1_c.as_ptr(),
+                        data_source2_c.as_ptr(),
+                        record_id2_c.as_ptr(),
+                        flags_bits,
+                    )
+                });
 
-        process_engine_result!(result)
+                self.formatted(process_engine_result!(result)?)
+            },
+        )
     }
 
     fn why_record_in_entity(
@@ -478,133 +972,573 @@ impl SzEngine for SzEngineCore {
         record_id: &str,
         flags: Option<SzFlags>,
     ) -> SzResult<JsonString> {
-        let data_source_c = crate::ffi::helpers::str_to_c_string(data_source_code)?;
-        let record_id_c = crate::ffi::helpers::str_to_c_string(record_id)?;
-        let flags_bits = flags.unwrap_or(SzFlags::WHY_RECORDS_DEFAULT_FLAGS).bits() as i64;
-
-        // Use V2 helper which accepts flags
-        let result = unsafe {
-            crate::ffi::Sz_whyRecordInEntity_V2_helper(
-                data_source_c.as_ptr(),
-                record_id_c.as_ptr(),
-                flags_bits,
-            )
-        };
+        let flags = crate::context::effective_flags(flags, SzFlags::WHY_RECORDS_DEFAULT_FLAGS);
+        self.intercepted(
+            "why_record_in_entity",
+            || {
+                json!({
+                    "data_source": data_source_code,
+                    "record_id": record_id,
+                    "flags": flags.map(|f| f.bits()),
+                })
+            },
+            || {
+                let data_source_c = crate::ffi::helpers::str_to_c_string(data_source_code)?;
+                let record_id_c = crate::ffi::helpers::str_to_c_string(record_id)?;
+                let flags_bits = flags.unwrap_or(SzFlags::WHY_RECORDS_DEFAULT_FLAGS).bits() as i64;
 
-        process_engine_result!(result)
+                // Use V2 helper which accepts flags
+                let result = crate::profiling::native(|| unsafe {
+                    crate::ffi::Sz_whyRecordInEntity_V2_helper(
+                        data_source_c.as_ptr(),
+                        record_id_c.as_ptr(),
+                        flags_bits,
+                    )
+                });
+
+                self.formatted(process_engine_result!(result)?)
+            },
+        )
     }
 
     fn how_entity(&self, entity_id: EntityId, flags: Option<SzFlags>) -> SzResult<JsonString> {
-        let flags_bits = flags.unwrap_or(SzFlags::HOW_ENTITY_DEFAULT_FLAGS).bits() as i64;
-
-        // Use V2 helper which accepts flags
-        let result = unsafe { crate::ffi::Sz_howEntityByEntityID_V2_helper(entity_id, flags_bits) };
-
-        process_engine_result!(result)
+        let flags = crate::context::effective_flags(flags, SzFlags::HOW_ENTITY_DEFAULT_FLAGS);
+        self.intercepted(
+            "how_entity",
+            || json!({ "entity_id": entity_id, "flags": flags.map(|f| f.bits()) }),
+            || {
+                if let Some(timeout) = self.timeout_for(TimedOperation::HowEntity) {
+                    return self.run_with_timeout("how_entity", timeout, move |engine| {
+                        engine.how_entity(entity_id, flags)
+                    });
+                }
+
+                let flags_bits = flags.unwrap_or(SzFlags::HOW_ENTITY_DEFAULT_FLAGS).bits() as i64;
+
+                // Use V2 helper which accepts flags
+                let result = crate::profiling::native(|| unsafe {
+                    crate::ffi::Sz_howEntityByEntityID_V2_helper(entity_id.get(), flags_bits)
+                });
+
+                self.formatted(process_engine_result!(result)?)
+            },
+        )
     }
 
     fn get_virtual_entity(
         &self,
-        record_keys: &[(String, String)],
+        record_keys: &[RecordKey],
         flags: Option<SzFlags>,
     ) -> SzResult<JsonString> {
-        if record_keys.is_empty() {
-            return Err(SzError::configuration("No record keys provided"));
-        }
-
-        let record_objects: Vec<serde_json::Value> = record_keys
-            .iter()
-            .map(|(data_source, record_id)| {
-                serde_json::json!({
-                    "DATA_SOURCE": data_source,
-                    "RECORD_ID": record_id
+        let flags = crate::context::effective_flags(flags, SzFlags::VIRTUAL_ENTITY_DEFAULT_FLAGS);
+        self.intercepted(
+            "get_virtual_entity",
+            || json!({ "record_keys": record_keys, "flags": flags.map(|f| f.bits()) }),
+            || {
+                if record_keys.is_empty() {
+                    return Err(SzError::configuration("No record keys provided"));
+                }
+
+                let record_list_json = serde_json::json!({
+                    "RECORDS": record_keys
                 })
-            })
-            .collect();
+                .to_string();
 
-        let record_list_json = serde_json::json!({
-            "RECORDS": record_objects
-        })
-        .to_string();
+                let record_list_c = crate::ffi::helpers::str_to_c_string(&record_list_json)?;
+                let flags_bits = flags
+                    .unwrap_or(SzFlags::VIRTUAL_ENTITY_DEFAULT_FLAGS)
+                    .bits() as i64;
 
-        let record_list_c = crate::ffi::helpers::str_to_c_string(&record_list_json)?;
-        let flags_bits = flags
-            .unwrap_or(SzFlags::VIRTUAL_ENTITY_DEFAULT_FLAGS)
-            .bits() as i64;
+                let result = crate::profiling::native(|| unsafe {
+                    crate::ffi::Sz_getVirtualEntityByRecordID_V2_helper(
+                        record_list_c.as_ptr(),
+                        flags_bits,
+                    )
+                });
 
-        let result = unsafe {
-            crate::ffi::Sz_getVirtualEntityByRecordID_V2_helper(record_list_c.as_ptr(), flags_bits)
-        };
+                self.formatted(process_engine_result!(result)?)
+            },
+        )
+    }
+
+    fn count_redo_records(&self) -> SzResult<i64> {
+        self.intercepted(
+            "count_redo_records",
+            || json!({}),
+            || {
+                let count =
+                    crate::profiling::native(|| unsafe { crate::ffi::Sz_countRedoRecords() });
+                Ok(count)
+            },
+        )
+    }
 
-        process_engine_result!(result)
+    fn repository_counts(&self, max_age: Duration) -> SzResult<RepositoryCounts> {
+        self.intercepted(
+            "repository_counts",
+            || json!({ "max_age_ms": max_age.as_millis() as u64 }),
+            || {
+                if let Some(counts) = self.settings.cached_counts(max_age) {
+                    return Ok(counts);
+                }
+                let counts = crate::export::count_repository(self)?;
+                self.settings.set_cached_counts(Some(counts));
+                Ok(counts)
+            },
+        )
     }
 
-    fn process_redo_record(
+    fn export_json_entity_report(&self, flags: Option<SzFlags>) -> SzResult<ExportHandle> {
+        self.intercepted(
+            "export_json_entity_report",
+            || json!({ "flags": flags.map(|f| f.bits()) }),
+            || {
+                let flags_bits = flags.unwrap_or_default().bits() as i64;
+
+                let result = crate::profiling::native(|| unsafe {
+                    crate::ffi::Sz_exportJSONEntityReport_helper(flags_bits)
+                });
+
+                crate::ffi::helpers::check_return_code(result.returnCode)?;
+                let handle = result.exportHandle as ExportHandle;
+                self.settings
+                    .exports
+                    .opened(handle, "export_json_entity_report");
+                Ok(handle)
+            },
+        )
+    }
+
+    fn export_csv_entity_report(
         &self,
-        redo_record: &str,
+        csv_column_list: &str,
         flags: Option<SzFlags>,
-    ) -> SzResult<JsonString> {
-        let redo_record_c = crate::ffi::helpers::str_to_c_string(redo_record)?;
-        let flags = flags.unwrap_or(SzFlags::REDO_DEFAULT_FLAGS);
-
-        // Neither the info nor the non-info redo entry point accepts flags in
-        // the C API; WITH_INFO only selects which variant is called.
-        if flags.contains(SzFlags::WITH_INFO) {
-            let result =
-                unsafe { crate::ffi::Sz_processRedoRecordWithInfo_helper(redo_record_c.as_ptr()) };
-            process_engine_result!(result)
-        } else {
-            let return_code = unsafe { crate::ffi::Sz_processRedoRecord(redo_record_c.as_ptr()) };
-            crate::ffi::helpers::check_return_code(return_code)?;
-            Ok(SZ_NO_INFO.to_string())
-        }
+    ) -> SzResult<ExportHandle> {
+        self.intercepted(
+            "export_csv_entity_report",
+            || json!({ "columns": csv_column_list, "flags": flags.map(|f| f.bits()) }),
+            || {
+                let csv_columns_c = crate::ffi::helpers::str_to_c_string(csv_column_list)?;
+                let flags_bits = flags.unwrap_or_default().bits() as i64;
+
+                let result = crate::profiling::native(|| unsafe {
+                    crate::ffi::Sz_exportCSVEntityReport_helper(csv_columns_c.as_ptr(), flags_bits)
+                });
+
+                crate::ffi::helpers::check_return_code(result.returnCode)?;
+                let handle = result.exportHandle as ExportHandle;
+                self.settings
+                    .exports
+                    .opened(handle, "export_csv_entity_report");
+                Ok(handle)
+            },
+        )
     }
 
-    fn get_redo_record(&self) -> SzResult<JsonString> {
-        let result = unsafe { crate::ffi::Sz_getRedoRecord_helper() };
-        process_engine_result!(result)
+    fn fetch_next(&self, export_handle: ExportHandle) -> SzResult<JsonString> {
+        self.intercepted(
+            "fetch_next",
+            || json!({ "export_handle": export_handle }),
+            || {
+                self.settings.exports.fetch(export_handle, || {
+                    let result = crate::profiling::native(|| unsafe {
+                        crate::ffi::Sz_fetchNext_helper(export_handle as usize)
+                    });
+
+                    process_engine_result!(result)
+                })
+            },
+        )
     }
 
-    fn count_redo_records(&self) -> SzResult<i64> {
-        let count = unsafe { crate::ffi::Sz_countRedoRecords() };
-        Ok(count)
+    fn close_export_report(&self, export_handle: ExportHandle) -> SzResult<()> {
+        self.intercepted(
+            "close_export_report",
+            || json!({ "export_handle": export_handle }),
+            || {
+                self.settings.exports.close(export_handle, || {
+                    ffi_call!(crate::ffi::Sz_closeExportReport_helper(
+                        export_handle as usize
+                    ));
+                    Ok(())
+                })
+            },
+        )
     }
+}
 
-    fn export_json_entity_report(&self, flags: Option<SzFlags>) -> SzResult<ExportHandle> {
-        let flags_bits = flags.unwrap_or_default().bits() as i64;
+impl SzEngineLoad for SzEngineCore {
+    fn add_record(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        record_definition: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        let policy = self.settings.policy(data_source_code);
+        let flags = match &policy {
+            Some(policy) => policy.flags_for_add(flags),
+            None => flags,
+        };
+        let flags = crate::context::effective_flags(flags, SzFlags::ADD_RECORD_DEFAULT_FLAGS);
+        self.intercepted(
+            "add_record",
+            || {
+                json!({
+                    "data_source": data_source_code,
+                    "record_id": record_id,
+                    "flags": flags.map(|f| f.bits()),
+                })
+            },
+            || {
+                let parameters = || {
+                    json!({
+                        "data_source": data_source_code,
+                        "record_id": record_id,
+                        "flags": flags.map(|f| f.bits()),
+                    })
+                };
+                // A rejected record is audited with its result code like any other failure
+                if let Some(policy) = &policy
+                    && let Err(e) =
+                        policy.validate(self, data_source_code, record_id, record_definition)
+                {
+                    return self.audited("add_record", parameters, Err(e));
+                }
+                if self.settings.dry_run() || policy.as_ref().is_some_and(|policy| policy.dry_run())
+                {
+                    return super::dry_run::add_record(
+                        self,
+                        data_source_code,
+                        record_id,
+                        record_definition,
+                        flags,
+                    )
+                    .and_then(|info| self.formatted(info));
+                }
+                let result = (|| -> SzResult<JsonString> {
+                    let data_source_c = crate::ffi::helpers::str_to_c_string(data_source_code)?;
+                    let record_id_c = crate::ffi::helpers::str_to_c_string(record_id)?;
+                    let record_def_c = self.settings.buffer_pool.c_string(record_definition)?;
+                    let flags = flags.unwrap_or(SzFlags::ADD_RECORD_DEFAULT_FLAGS);
+
+                    // Only compute (and return) the info document when the caller opts in
+                    // via WITH_INFO, mirroring the Python/C# SDKs. Otherwise use the
+                    // cheaper non-info entry point and return SZ_NO_INFO.
+                    if flags.contains(SzFlags::WITH_INFO) {
+                        let result = crate::profiling::native(|| unsafe {
+                            crate::ffi::Sz_addRecordWithInfo_helper(
+                                data_source_c.as_ptr(),
+                                record_id_c.as_ptr(),
+                                record_def_c.as_ptr(),
+                                flags.bits() as i64,
+                            )
+                        });
+                        self.formatted(process_engine_result!(result)?)
+                    } else {
+                        let return_code = crate::profiling::native(|| unsafe {
+                            crate::ffi::Sz_addRecord(
+                                data_source_c.as_ptr(),
+                                record_id_c.as_ptr(),
+                                record_def_c.as_ptr(),
+                            )
+                        });
+                        crate::ffi::helpers::check_return_code(return_code)?;
+                        Ok(SZ_NO_INFO.to_string())
+                    }
+                })();
+                let result = match &policy {
+                    Some(policy) => result.and_then(|info| policy.route_info(&info).map(|()| info)),
+                    None => result,
+                };
+                self.audited("add_record", parameters, result)
+            },
+        )
+    }
 
-        let result = unsafe { crate::ffi::Sz_exportJSONEntityReport_helper(flags_bits) };
+    fn delete_record(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        let policy = self.settings.policy(data_source_code);
+        let flags = match &policy {
+            Some(policy) => policy.flags_for_delete(flags),
+            None => flags,
+        };
+        let flags = crate::context::effective_flags(flags, SzFlags::DELETE_RECORD_DEFAULT_FLAGS);
+        self.intercepted(
+            "delete_record",
+            || {
+                json!({
+                    "data_source": data_source_code,
+                    "record_id": record_id,
+                    "flags": flags.map(|f| f.bits()),
+                })
+            },
+            || {
+                if self.settings.dry_run() || policy.as_ref().is_some_and(|policy| policy.dry_run())
+                {
+                    return super::dry_run::record_operation(
+                        self,
+                        data_source_code,
+                        record_id,
+                        flags,
+                    )
+                    .and_then(|info| self.formatted(info));
+                }
+                let result = (|| -> SzResult<JsonString> {
+                    let data_source_c = crate::ffi::helpers::str_to_c_string(data_source_code)?;
+                    let record_id_c = crate::ffi::helpers::str_to_c_string(record_id)?;
+                    let flags = flags.unwrap_or(SzFlags::DELETE_RECORD_DEFAULT_FLAGS);
+
+                    if flags.contains(SzFlags::WITH_INFO) {
+                        let result = crate::profiling::native(|| unsafe {
+                            crate::ffi::Sz_deleteRecordWithInfo_helper(
+                                data_source_c.as_ptr(),
+                                record_id_c.as_ptr(),
+                                flags.bits() as i64,
+                            )
+                        });
+                        self.formatted(process_engine_result!(result)?)
+                    } else {
+                        let return_code = crate::profiling::native(|| unsafe {
+                            crate::ffi::Sz_deleteRecord(
+                                data_source_c.as_ptr(),
+                                record_id_c.as_ptr(),
+                            )
+                        });
+                        crate::ffi::helpers::check_return_code(return_code)?;
+                        Ok(SZ_NO_INFO.to_string())
+                    }
+                })();
+                let result = match &policy {
+                    Some(policy) => result.and_then(|info| policy.route_info(&info).map(|()| info)),
+                    None => result,
+                };
+                self.audited(
+                    "delete_record",
+                    || {
+                        json!({
+                            "data_source": data_source_code,
+                            "record_id": record_id,
+                            "flags": flags.map(|f| f.bits()),
+                        })
+                    },
+                    result,
+                )
+            },
+        )
+    }
 
-        crate::ffi::helpers::check_return_code(result.returnCode)?;
-        Ok(result.exportHandle as ExportHandle)
+    fn reevaluate_record(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        let flags =
+            crate::context::effective_flags(flags, SzFlags::REEVALUATE_RECORD_DEFAULT_FLAGS);
+        self.intercepted(
+            "reevaluate_record",
+            || {
+                json!({
+                    "data_source": data_source_code,
+                    "record_id": record_id,
+                    "flags": flags.map(|f| f.bits()),
+                })
+            },
+            || {
+                if self.settings.dry_run()
+                    || self
+                        .settings
+                        .policy(data_source_code)
+                        .is_some_and(|policy| policy.dry_run())
+                {
+                    return super::dry_run::record_operation(
+                        self,
+                        data_source_code,
+                        record_id,
+                        flags,
+                    )
+                    .and_then(|info| self.formatted(info));
+                }
+                let result = (|| -> SzResult<JsonString> {
+                    let data_source_c = crate::ffi::helpers::str_to_c_string(data_source_code)?;
+                    let record_id_c = crate::ffi::helpers::str_to_c_string(record_id)?;
+                    let flags = flags.unwrap_or(SzFlags::REEVALUATE_RECORD_DEFAULT_FLAGS);
+
+                    if flags.contains(SzFlags::WITH_INFO) {
+                        let result = crate::profiling::native(|| unsafe {
+                            crate::ffi::Sz_reevaluateRecordWithInfo_helper(
+                                data_source_c.as_ptr(),
+                                record_id_c.as_ptr(),
+                                flags.bits() as i64,
+                            )
+                        });
+                        self.formatted(process_engine_result!(result)?)
+                    } else {
+                        let return_code = crate::profiling::native(|| unsafe {
+                            crate::ffi::Sz_reevaluateRecord(
+                                data_source_c.as_ptr(),
+                                record_id_c.as_ptr(),
+                                flags.bits() as i64,
+                            )
+                        });
+                        crate::ffi::helpers::check_return_code(return_code)?;
+                        Ok(SZ_NO_INFO.to_string())
+                    }
+                })();
+                self.audited(
+                    "reevaluate_record",
+                    || {
+                        json!({
+                            "data_source": data_source_code,
+                            "record_id": record_id,
+                            "flags": flags.map(|f| f.bits()),
+                        })
+                    },
+                    result,
+                )
+            },
+        )
     }
 
-    fn export_csv_entity_report(
+    fn reevaluate_entity(
         &self,
-        csv_column_list: &str,
+        entity_id: EntityId,
         flags: Option<SzFlags>,
-    ) -> SzResult<ExportHandle> {
-        let csv_columns_c = crate::ffi::helpers::str_to_c_string(csv_column_list)?;
-        let flags_bits = flags.unwrap_or_default().bits() as i64;
+    ) -> SzResult<JsonString> {
+        let flags =
+            crate::context::effective_flags(flags, SzFlags::REEVALUATE_ENTITY_DEFAULT_FLAGS);
+        self.intercepted(
+            "reevaluate_entity",
+            || json!({ "entity_id": entity_id, "flags": flags.map(|f| f.bits()) }),
+            || {
+                if self.settings.dry_run() || self.entity_policy_dry_run(entity_id)? {
+                    return super::dry_run::reevaluate_entity(self, entity_id, flags)
+                        .and_then(|info| self.formatted(info));
+                }
+                let result = (|| -> SzResult<JsonString> {
+                    let flags = flags.unwrap_or(SzFlags::REEVALUATE_ENTITY_DEFAULT_FLAGS);
+
+                    if flags.contains(SzFlags::WITH_INFO) {
+                        let result = crate::profiling::native(|| unsafe {
+                            crate::ffi::Sz_reevaluateEntityWithInfo_helper(
+                                entity_id.get(),
+                                flags.bits() as i64,
+                            )
+                        });
+                        self.formatted(process_engine_result!(result)?)
+                    } else {
+                        let return_code = crate::profiling::native(|| unsafe {
+                            crate::ffi::Sz_reevaluateEntity(entity_id.get(), flags.bits() as i64)
+                        });
+                        crate::ffi::helpers::check_return_code(return_code)?;
+                        Ok(SZ_NO_INFO.to_string())
+                    }
+                })();
+                self.audited(
+                    "reevaluate_entity",
+                    || json!({ "entity_id": entity_id, "flags": flags.map(|f| f.bits()) }),
+                    result,
+                )
+            },
+        )
+    }
 
-        let result = unsafe {
-            crate::ffi::Sz_exportCSVEntityReport_helper(csv_columns_c.as_ptr(), flags_bits)
-        };
+    fn process_redo_record(
+        &self,
+        redo_record: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        let flags = crate::context::effective_flags(flags, SzFlags::REDO_DEFAULT_FLAGS);
+        self.intercepted(
+            "process_redo_record",
+            || json!({ "flags": flags.map(|f| f.bits()) }),
+            || {
+                if self.settings.dry_run() {
+                    return super::dry_run::process_redo_record(self, redo_record, flags)
+                        .and_then(|info| self.formatted(info));
+                }
+                let result = (|| -> SzResult<JsonString> {
+                    let redo_record_c = crate::ffi::helpers::str_to_c_string(redo_record)?;
+                    let flags = flags.unwrap_or(SzFlags::REDO_DEFAULT_FLAGS);
+
+                    // Neither the info nor the non-info redo entry point accepts flags in
+                    // the C API; WITH_INFO only selects which variant is called.
+                    if flags.contains(SzFlags::WITH_INFO) {
+                        let result = crate::profiling::native(|| unsafe {
+                            crate::ffi::Sz_processRedoRecordWithInfo_helper(redo_record_c.as_ptr())
+                        });
+                        self.formatted(process_engine_result!(result)?)
+                    } else {
+                        let return_code = crate::profiling::native(|| unsafe {
+                            crate::ffi::Sz_processRedoRecord(redo_record_c.as_ptr())
+                        });
+                        crate::ffi::helpers::check_return_code(return_code)?;
+                        Ok(SZ_NO_INFO.to_string())
+                    }
+                })();
+                self.audited(
+                    "process_redo_record",
+                    || json!({ "flags": flags.map(|f| f.bits()) }),
+                    result,
+                )
+            },
+        )
+    }
 
-        crate::ffi::helpers::check_return_code(result.returnCode)?;
-        Ok(result.exportHandle as ExportHandle)
+    fn get_redo_record(&self) -> SzResult<JsonString> {
+        self.intercepted(
+            "get_redo_record",
+            || json!({}),
+            || {
+                // Taking a redo record removes it from the queue
+                if self.settings.dry_run() {
+                    return Ok(String::new());
+                }
+                let result =
+                    crate::profiling::native(|| unsafe { crate::ffi::Sz_getRedoRecord_helper() });
+                process_engine_result!(result)
+            },
+        )
     }
+}
 
-    fn fetch_next(&self, export_handle: ExportHandle) -> SzResult<JsonString> {
-        let result = unsafe { crate::ffi::Sz_fetchNext_helper(export_handle as usize) };
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reduced_flags_keeps_only_masked_flags() {
+        let reduced = reduced_flags(SzFlags::ENTITY_DEFAULT_FLAGS, ENTITY_REDUCED_FLAGS);
+        assert_eq!(
+            reduced,
+            SzFlags::ENTITY_INCLUDE_ALL_RELATIONS
+                | SzFlags::ENTITY_INCLUDE_RECORD_MATCHING_INFO
+                | SzFlags::ENTITY_INCLUDE_RELATED_MATCHING_INFO
+        );
+    }
 
-        process_engine_result!(result)
+    #[test]
+    fn test_reduced_flags_never_adds_flags() {
+        let requested = SzFlags::ENTITY_INCLUDE_RECORD_MATCHING_INFO;
+        // Nothing left to remove, so fall back to identifiers only.
+        assert_eq!(
+            reduced_flags(requested, ENTITY_REDUCED_FLAGS),
+            SzFlags::empty()
+        );
     }
 
-    fn close_export_report(&self, export_handle: ExportHandle) -> SzResult<()> {
-        ffi_call!(crate::ffi::Sz_closeExportReport_helper(
-            export_handle as usize
-        ));
-        Ok(())
+    #[test]
+    fn test_reduced_network_flags() {
+        let reduced = reduced_flags(SzFlags::FIND_NETWORK_DEFAULT_FLAGS, NETWORK_REDUCED_FLAGS);
+        assert_eq!(reduced, SzFlags::FIND_NETWORK_INCLUDE_MATCHING_INFO);
+        assert_eq!(
+            reduced_flags(reduced, NETWORK_REDUCED_FLAGS),
+            SzFlags::empty()
+        );
     }
 }
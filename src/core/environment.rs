@@ -9,6 +9,7 @@ use crate::{
 use std::mem::ManuallyDrop;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex, Once, OnceLock};
+use std::time::Instant;
 
 /// Core implementation of the SzEnvironment trait.
 ///
@@ -95,9 +96,18 @@ pub struct SzEnvironmentCore {
     product_init_once: Arc<Once>,
     /// Stores any error that occurred during SzProduct_init
     product_init_error: Arc<Mutex<Option<String>>>,
+    /// Runtime settings shared with every engine created by this environment
+    settings: Arc<super::settings::EngineSettings>,
     module_name: String,
     ini_params: String,
     verbose_logging: bool,
+    /// Process that created the environment; native state does not survive fork
+    owner_pid: u32,
+}
+
+/// Milliseconds since `start`, saturating.
+fn elapsed_ms(start: Instant) -> u64 {
+    u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX)
 }
 
 // Singleton storage for the global SzEnvironmentCore instance
@@ -115,6 +125,7 @@ impl SzEnvironmentCore {
     /// * `ini_params` - JSON string containing initialization parameters
     /// * `verbose_logging` - Whether to enable verbose logging
     pub fn new(module_name: &str, ini_params: &str, verbose_logging: bool) -> SzResult<Self> {
+        crate::ffi::boundary::set_owner_process();
         Ok(Self {
             is_destroyed: Arc::new(AtomicBool::new(false)),
             init_once: Arc::new(Once::new()),
@@ -123,9 +134,11 @@ impl SzEnvironmentCore {
             config_mgr_init_error: Arc::new(Mutex::new(None)),
             product_init_once: Arc::new(Once::new()),
             product_init_error: Arc::new(Mutex::new(None)),
+            settings: Arc::new(super::settings::EngineSettings::default()),
             module_name: module_name.to_string(),
             ini_params: ini_params.to_string(),
             verbose_logging,
+            owner_pid: std::process::id(),
         })
     }
 
@@ -227,6 +240,137 @@ impl SzEnvironmentCore {
         }
     }
 
+    /// Creates a new SzEnvironment instance like [`new`](Self::new) and
+    /// initializes its native components right away, returning what the
+    /// initialization did; see [`init_report`](Self::init_report)
+    pub fn new_with_report(
+        module_name: &str,
+        ini_params: &str,
+        verbose_logging: bool,
+    ) -> SzResult<(Self, InitReport)> {
+        let env = Self::new(module_name, ini_params, verbose_logging)?;
+        let report = env.init_report();
+        Ok((env, report))
+    }
+
+    /// Gets or creates the global singleton like
+    /// [`get_instance`](Self::get_instance) and initializes its native
+    /// components right away, returning what the initialization did; see
+    /// [`init_report`](Self::init_report)
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// let settings = std::env::var("SENZING_ENGINE_CONFIGURATION_JSON").unwrap_or_default();
+    /// let (env, report) = SzEnvironmentCore::get_instance_with_report("loader", &settings, false)?;
+    /// println!("{report}");
+    /// if !report.is_ok() {
+    ///     eprintln!("{}", serde_json::to_string_pretty(&report)?);
+    /// }
+    /// # drop(env);
+    /// # Ok::<(), SzError>(())
+    /// ```
+    pub fn get_instance_with_report(
+        module_name: &str,
+        ini_params: &str,
+        verbose_logging: bool,
+    ) -> SzResult<(Arc<Self>, InitReport)> {
+        let env = Self::get_instance(module_name, ini_params, verbose_logging)?;
+        let report = env.init_report();
+        Ok((env, report))
+    }
+
+    /// Initializes the native components that are not initialized yet and
+    /// reports the outcome
+    ///
+    /// Components are otherwise initialized on first use. The report covers
+    /// the engine, config manager and product components, the active
+    /// configuration, a `get_repository_info` call to confirm the database
+    /// is reachable, and warnings for verbose logging, an active
+    /// configuration that is not the default, and a degraded environment.
+    /// Failures are recorded in the report rather than returned.
+    pub fn init_report(&self) -> InitReport {
+        let start = Instant::now();
+        let components = vec![
+            self.init_component("engine", &self.init_once, Self::ensure_initialized),
+            self.init_component(
+                "config_manager",
+                &self.config_mgr_init_once,
+                Self::ensure_config_mgr_initialized,
+            ),
+            self.init_component(
+                "product",
+                &self.product_init_once,
+                Self::ensure_product_initialized,
+            ),
+        ];
+
+        let engine_ready = components[0].is_initialized();
+        let active_config_id = engine_ready
+            .then(|| self.get_active_config_id().ok())
+            .flatten();
+        let database = if engine_ready {
+            self.get_diagnostic()
+                .and_then(|diagnostic| diagnostic.get_repository_info())
+                .map(|_| ())
+        } else {
+            Err(SzError::unrecoverable("engine is not initialized"))
+        };
+
+        let mut warnings = Vec::new();
+        if self.verbose_logging {
+            warnings.push("verbose logging is on".to_string());
+        }
+        if let Some(active) = active_config_id
+            && components[1].is_initialized()
+            && let Ok(default) = self
+                .get_config_manager()
+                .and_then(|manager| manager.get_default_config_id())
+            && active != default
+        {
+            warnings.push(format!(
+                "active config {active} is not the default {default}"
+            ));
+        }
+        if let Some(reason) = self.degraded_reason() {
+            warnings.push(format!("environment is degraded: {reason}"));
+        }
+
+        InitReport {
+            module_name: self.module_name.clone(),
+            components,
+            active_config_id,
+            database_connected: database.is_ok(),
+            database_error: database.err().map(|e| e.to_string()),
+            elapsed_ms: elapsed_ms(start),
+            warnings,
+        }
+    }
+
+    /// Runs one component's initialization for [`init_report`](Self::init_report)
+    fn init_component(
+        &self,
+        name: &'static str,
+        once: &Once,
+        ensure: fn(&Self) -> SzResult<()>,
+    ) -> InitComponent {
+        let already_initialized = once.is_completed();
+        let start = Instant::now();
+        let result = if self.is_destroyed() {
+            Err(SzError::unrecoverable("Environment has been destroyed"))
+        } else {
+            ensure(self)
+        };
+        InitComponent {
+            name,
+            already_initialized,
+            elapsed_ms: elapsed_ms(start),
+            error: result.err().map(|e| e.to_string()),
+        }
+    }
+
     /// Gets the existing global singleton SzEnvironmentCore instance
     ///
     /// This method returns the existing singleton instance without creating a new one.
@@ -381,6 +525,14 @@ impl SzEnvironmentCore {
                 // Mark as destroyed
                 env.is_destroyed.store(true, Ordering::Relaxed);
 
+                // In a forked child the native state belongs to the parent
+                if env.owner_pid != std::process::id() {
+                    return Ok(());
+                }
+
+                // Export handles do not survive Sz_destroy
+                env.invalidate_exports("destroyed");
+
                 // Cleanup all Senzing modules
                 // Note: SzConfig_destroy() is not needed here - it manages config handles,
                 // not the config system itself. Config handles have their own lifecycle.
@@ -396,6 +548,7 @@ impl SzEnvironmentCore {
                     crate::ffi::SzProduct_clearLastException();
                     crate::ffi::SzConfigMgr_clearLastException();
                 }
+                crate::ffi::boundary::reset();
 
                 // Give the native library time to fully clean up internal state
                 std::thread::sleep(std::time::Duration::from_millis(100));
@@ -418,6 +571,150 @@ impl SzEnvironmentCore {
         }
     }
 
+    /// Checks that it is safe to fork the current process.
+    ///
+    /// The Senzing native library keeps threads, locks, and database
+    /// connections that are not valid in a child created by `fork()`. Forking
+    /// is only safe before any native module has been initialized, that is
+    /// before the first `get_engine()`, `get_config_manager()`,
+    /// `get_product()`, or `get_diagnostic()` call. Applications with
+    /// fork-based workers should call this immediately before forking and
+    /// create the environment in each child instead.
+    ///
+    /// Independently of this check, an environment refuses to initialize or
+    /// use native modules from a process other than the one that created it,
+    /// returning `SzError::Unrecoverable` instead of crashing. Call
+    /// [`after_fork_child`](Self::after_fork_child) in the child to discard
+    /// the inherited environment.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let settings = "{}";
+    /// let env = SzEnvironmentCore::get_instance("job-runner", settings, false)?;
+    /// env.prepare_fork()?;
+    /// // fork() here; each worker then calls SzEnvironmentCore::after_fork_child()
+    /// // and creates its own environment with get_instance()
+    /// # Ok::<(), SzError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::NotPermitted` - A native module has already been initialized
+    ///   in this process
+    pub fn prepare_fork(&self) -> SzResult<()> {
+        let initialized: Vec<&str> = [
+            ("engine", &self.init_once),
+            ("config manager", &self.config_mgr_init_once),
+            ("product", &self.product_init_once),
+        ]
+        .into_iter()
+        .filter(|(_, once)| once.is_completed())
+        .map(|(name, _)| name)
+        .collect();
+        if initialized.is_empty() {
+            return Ok(());
+        }
+        Err(SzError::not_permitted(format!(
+            "Cannot fork after the Senzing {} has been initialized; \
+             fork first and create the environment in each child process",
+            initialized.join(", ")
+        )))
+    }
+
+    /// Discards the environment inherited from the parent after `fork()`.
+    ///
+    /// Call this first thing in a forked child. The inherited singleton is
+    /// marked destroyed, without running native cleanup that belongs to the
+    /// parent, so stale references fail with an error and
+    /// [`get_instance`](Self::get_instance) creates a fresh environment for
+    /// the child. Does nothing when there is no environment or when called
+    /// in the process that created it.
+    pub fn after_fork_child() {
+        let Some(global_env) = GLOBAL_ENVIRONMENT.get() else {
+            return;
+        };
+        // A lock held by another thread at fork time is never released in
+        // the child, so don't wait for it.
+        let mut env_guard = match global_env.try_lock() {
+            Ok(guard) => guard,
+            Err(std::sync::TryLockError::Poisoned(poisoned)) => poisoned.into_inner(),
+            Err(std::sync::TryLockError::WouldBlock) => return,
+        };
+        if let Some(env) = env_guard.as_ref()
+            && env.owner_pid != std::process::id()
+        {
+            env.is_destroyed.store(true, Ordering::Relaxed);
+            env_guard.take();
+        }
+    }
+
+    /// Fails when called in a process forked after the environment was created.
+    fn ensure_owner_process(&self) -> SzResult<()> {
+        crate::ffi::boundary::check_owner(self.owner_pid)
+    }
+
+    /// Returns why the environment is degraded, or `None` when it is not.
+    ///
+    /// The environment becomes degraded when native calls fail with
+    /// unrecoverable errors (including panics caught at the native boundary)
+    /// several times in a row; see
+    /// [`set_poison_threshold`](Self::set_poison_threshold). The native
+    /// state is then assumed to be poisoned and `get_engine` and the other
+    /// getters fail with `SzError::Unrecoverable`. Components obtained
+    /// earlier keep working but should be dropped.
+    ///
+    /// To recover, drop all components and call [`destroy`](Self::destroy),
+    /// then create a new environment with [`get_instance`](Self::get_instance).
+    /// If the errors persist after that, restart the process.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_degraded")?;
+    /// if let Some(reason) = env.degraded_reason() {
+    ///     eprintln!("Senzing degraded ({reason}); reinitializing");
+    ///     let (ini_params, verbose) = (env.get_ini_params().to_string(), env.get_verbose_logging());
+    ///     env.destroy()?;
+    ///     let _env = SzEnvironmentCore::get_instance("my-service", &ini_params, verbose)?;
+    /// }
+    /// # Ok::<(), SzError>(())
+    /// ```
+    pub fn degraded_reason(&self) -> Option<String> {
+        crate::ffi::boundary::poisoned()
+    }
+
+    /// Returns true if the environment is degraded; see
+    /// [`degraded_reason`](Self::degraded_reason).
+    pub fn is_degraded(&self) -> bool {
+        self.degraded_reason().is_some()
+    }
+
+    /// Sets how many consecutive unrecoverable native errors make the
+    /// environment degraded (default 3). A value of 0 is treated as 1.
+    ///
+    /// The native library is process-wide, so this applies to every
+    /// environment in the process.
+    pub fn set_poison_threshold(&self, count: u32) {
+        crate::ffi::boundary::set_poison_threshold(count);
+    }
+
+    /// Fails when the native state is poisoned.
+    fn ensure_not_degraded(&self) -> SzResult<()> {
+        match self.degraded_reason() {
+            Some(reason) => Err(SzError::unrecoverable(format!(
+                "Senzing environment is degraded ({reason}); drop all components, \
+                 call destroy() and create a new environment, or restart the process"
+            ))),
+            None => Ok(()),
+        }
+    }
+
     /// Get the initialization parameters used by this environment
     pub fn get_ini_params(&self) -> &str {
         &self.ini_params
@@ -428,11 +725,448 @@ impl SzEnvironmentCore {
         self.verbose_logging
     }
 
+    /// Sets the maximum response size for entity and network retrievals
+    ///
+    /// The limit applies to `get_entity`, `find_network_by_entity_id` and
+    /// `find_network_by_record_id` on every engine created by this environment,
+    /// including engines that already exist. Responses larger than the limit
+    /// are either rejected with [`SzError::ResultTooLarge`] or retried with
+    /// reduced flags, depending on [`ResultSizeLimit::action`].
+    ///
+    /// Pass `None` to remove the limit (the default).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_set_result_size_limit")?;
+    /// env.set_result_size_limit(Some(ResultSizeLimit {
+    ///     max_bytes: 4 * 1024 * 1024,
+    ///     action: OversizeAction::Error,
+    /// }));
+    /// # Ok::<(), SzError>(())
+    /// ```
+    pub fn set_result_size_limit(&self, limit: Option<ResultSizeLimit>) {
+        self.settings.set_result_size_limit(limit);
+    }
+
+    /// Returns the current response size limit, if any
+    pub fn result_size_limit(&self) -> Option<ResultSizeLimit> {
+        self.settings.result_size_limit()
+    }
+
+    /// Sets how engines format their JSON responses
+    ///
+    /// Applies to every engine created by this environment, including
+    /// engines that already exist. [`ResponseFormat::Compact`] and
+    /// [`ResponseFormat::Pretty`] sort object keys so responses can be diffed
+    /// and stored deterministically; each response is parsed and
+    /// re-serialized, so leave the default [`ResponseFormat::Native`] on hot
+    /// paths.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_set_response_format")?;
+    /// env.set_response_format(ResponseFormat::Pretty);
+    /// let engine = env.get_engine()?;
+    /// println!("{}", engine.get_record("TEST", "1001", None)?);
+    /// # Ok::<(), SzError>(())
+    /// ```
+    pub fn set_response_format(&self, format: ResponseFormat) {
+        self.settings.set_response_format(format);
+    }
+
+    /// Returns how engines format their JSON responses
+    pub fn response_format(&self) -> ResponseFormat {
+        self.settings.response_format()
+    }
+
+    /// Configures the argument buffer pool shared by this environment's engines
+    ///
+    /// Engines copy record definitions and search attributes into pooled
+    /// buffers instead of allocating a new one per call. Idle buffers that no
+    /// longer fit the new configuration are released immediately. See
+    /// [`BufferPoolConfig`] for the defaults.
+    pub fn set_buffer_pool_config(&self, config: BufferPoolConfig) {
+        self.settings.buffer_pool.configure(config);
+    }
+
+    /// Returns the current argument buffer pool configuration
+    pub fn buffer_pool_config(&self) -> BufferPoolConfig {
+        self.settings.buffer_pool.config()
+    }
+
+    /// Sets a timeout for an engine operation
+    ///
+    /// When set, calls to the operation on any engine created by this
+    /// environment run on a separate thread, and the caller gets
+    /// [`SzError::Timeout`] if the call has not finished within `timeout`.
+    /// The native library cannot interrupt a call in progress, so a timed-out
+    /// call is abandoned: it keeps running in the background and its result is
+    /// discarded. Timed-out calls are recorded and can be listed with
+    /// [`timed_out_calls`](Self::timed_out_calls).
+    ///
+    /// Pass `None` to remove the timeout (the default).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    /// use std::time::Duration;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_set_operation_timeout")?;
+    /// env.set_operation_timeout(TimedOperation::FindNetwork, Some(Duration::from_secs(30)));
+    /// # Ok::<(), SzError>(())
+    /// ```
+    pub fn set_operation_timeout(
+        &self,
+        operation: TimedOperation,
+        timeout: Option<std::time::Duration>,
+    ) {
+        self.settings.set_operation_timeout(operation, timeout);
+    }
+
+    /// Returns the timeout configured for an engine operation, if any
+    pub fn operation_timeout(&self, operation: TimedOperation) -> Option<std::time::Duration> {
+        self.settings.operation_timeout(operation)
+    }
+
+    /// Returns the most recent calls that exceeded their timeout, oldest first
+    ///
+    /// Up to the last 100 timed-out calls are kept.
+    pub fn timed_out_calls(&self) -> Vec<TimeoutRecord> {
+        self.settings.timed_out_calls()
+    }
+
+    /// Returns how many timed-out or cancelled calls are still running in
+    /// the native library
+    ///
+    /// Calls with a timeout run on a pool of up to 8 worker threads per
+    /// environment. A call abandoned on timeout or cancellation holds its
+    /// worker until the native call returns; once every worker is held,
+    /// further timed calls fail with `SzError::Timeout` without starting.
+    pub fn abandoned_calls(&self) -> usize {
+        self.settings.timed_calls.abandoned()
+    }
+
+    /// Returns the most recent failed engine calls, oldest first
+    ///
+    /// Failures from all engines of the environment are kept, up to the
+    /// last 100 unless changed with
+    /// [`set_error_history_capacity`](Self::set_error_history_capacity).
+    /// Each record has the operation, error category, native error code and
+    /// component, and when it happened, so intermittent native failures can
+    /// be looked at after the fact. Each failed call is kept once, under the
+    /// method the caller called, also when it reads through another engine
+    /// method or runs on a timeout thread. Messages may contain record values; see
+    /// [`redact::message`](crate::redact::message).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_recent_errors")?;
+    /// let _ = env.get_engine()?.get_record("TEST", "MISSING", None);
+    /// for error in env.recent_errors() {
+    ///     eprintln!("{} failed: {:?} {}", error.operation, error.code, error.category);
+    /// }
+    /// # Ok::<(), SzError>(())
+    /// ```
+    pub fn recent_errors(&self) -> Vec<ErrorRecord> {
+        self.settings.errors_matching(&ErrorFilter::new())
+    }
+
+    /// Returns the recorded failures that match `filter`, oldest first; see
+    /// [`recent_errors`](Self::recent_errors)
+    pub fn query_errors(&self, filter: &ErrorFilter) -> Vec<ErrorRecord> {
+        self.settings.errors_matching(filter)
+    }
+
+    /// Sets how many failures are kept, dropping the oldest ones beyond it;
+    /// 0 turns the error history off
+    pub fn set_error_history_capacity(&self, capacity: usize) {
+        self.settings.set_error_history_capacity(capacity);
+    }
+
+    /// Returns how many failures are kept
+    pub fn error_history_capacity(&self) -> usize {
+        self.settings.error_history_capacity()
+    }
+
+    /// Forgets the recorded failures
+    pub fn clear_error_history(&self) {
+        self.settings.clear_error_history();
+    }
+
+    /// Returns the exports started by engines of this environment and not
+    /// yet closed, by handle
+    ///
+    /// Export handles do not survive [`reinitialize`](SzEnvironment::reinitialize)
+    /// or [`destroy`](Self::destroy). Both wait for in-flight `fetch_next`
+    /// calls to return, then close the open exports; a later `fetch_next`
+    /// with one of their handles fails with [`SzError::ExportInvalidated`],
+    /// whose message gives the number of rows fetched before the handle was
+    /// closed.
+    pub fn open_exports(&self) -> Vec<ExportSession> {
+        self.settings.exports.sessions()
+    }
+
+    /// Enables or disables `SzDiagnostic::purge_repository`
+    ///
+    /// Purge is enabled by default. Production deployments can disable it at
+    /// startup so that no code path can wipe the repository; purge calls on
+    /// any diagnostic created by this environment then fail with
+    /// [`SzError::BadInput`] without reaching the native library.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_set_purge_enabled")?;
+    /// env.set_purge_enabled(false);
+    /// let diagnostic = env.get_diagnostic()?;
+    /// let result = diagnostic.purge_repository(PurgeConfirmation::I_UNDERSTAND_THIS_DELETES_ALL_DATA);
+    /// assert!(result.is_err());
+    /// # Ok::<(), SzError>(())
+    /// ```
+    pub fn set_purge_enabled(&self, enabled: bool) {
+        self.settings.set_purge_enabled(enabled);
+    }
+
+    /// Returns whether `SzDiagnostic::purge_repository` is allowed
+    pub fn purge_enabled(&self) -> bool {
+        self.settings.purge_enabled()
+    }
+
+    /// Enables or disables dry-run mode for engine mutations
+    ///
+    /// While enabled, `add_record`, `delete_record`, `reevaluate_record`,
+    /// `reevaluate_entity` and `process_redo_record` on every engine created
+    /// by this environment leave the repository untouched. Records are still
+    /// validated with `get_record_preview`, and with `SzFlags::WITH_INFO`
+    /// the response predicts the affected entities from read-only lookups
+    /// and carries `"DRY_RUN": true`. `get_redo_record` returns an empty
+    /// string, since taking a redo record removes it from the queue. Use it
+    /// to run a pipeline against a production configuration safely.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_set_dry_run")?;
+    /// env.set_dry_run(true);
+    /// let engine = env.get_engine()?;
+    /// let info = engine.add_record(
+    ///     "TEST",
+    ///     "DRY_1",
+    ///     r#"{"NAME_FULL": "Jo Doe"}"#,
+    ///     Some(SzFlags::WITH_INFO),
+    /// )?;
+    /// assert!(info.contains("DRY_RUN"));
+    /// assert!(engine.get_record("TEST", "DRY_1", None).is_err());
+    /// # Ok::<(), SzError>(())
+    /// ```
+    pub fn set_dry_run(&self, enabled: bool) {
+        self.settings.set_dry_run(enabled);
+    }
+
+    /// Returns whether dry-run mode is enabled
+    pub fn dry_run(&self) -> bool {
+        self.settings.dry_run()
+    }
+
+    /// Sets the sink that receives an audit event for every mutating
+    /// operation, or turns auditing off with `None`
+    ///
+    /// Applies to all engines and components created by this environment.
+    /// See the [`audit`](crate::audit) module for the audited operations.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use std::sync::Arc;
+    /// use sz_rust_sdk::audit::MemoryAuditSink;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_set_audit_sink")?;
+    /// let sink = Arc::new(MemoryAuditSink::new());
+    /// env.set_audit_sink(Some(sink.clone()));
+    /// env.get_engine()?
+    ///     .delete_record("TEST", "1001", None)?;
+    /// assert_eq!(sink.events()[0].operation, "delete_record");
+    /// # Ok::<(), SzError>(())
+    /// ```
+    pub fn set_audit_sink(&self, sink: Option<Arc<dyn crate::audit::AuditSink>>) {
+        self.settings
+            .set_audit_sink(sink.map(crate::audit::SinkHandle));
+    }
+
+    /// Returns the number of audit events the sink failed to store
+    pub fn audit_failures(&self) -> u64 {
+        self.settings.audit_failures()
+    }
+
+    /// Adds an interceptor that sees every engine call made by this
+    /// environment's engines, including engines created before it was added.
+    /// See the [`intercept`](crate::intercept) module.
+    pub fn add_interceptor(&self, interceptor: Box<dyn crate::intercept::SzInterceptor>) {
+        self.settings.add_interceptor(Arc::from(interceptor));
+    }
+
+    /// Removes all interceptors.
+    pub fn clear_interceptors(&self) {
+        self.settings.clear_interceptors();
+    }
+
+    /// Sets a token that cancels this environment's engine calls, or removes
+    /// it with `None`
+    ///
+    /// Once the token is cancelled, calls on every engine of the environment
+    /// fail with `SzError::Cancelled` before reaching the native library,
+    /// except `close_export_report`. A native call already running cannot be
+    /// interrupted; a call with an operation timeout is abandoned as on a
+    /// timeout. Share the token with loaders, exporters and redo processors
+    /// to stop a service's work at once.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::control::SzCancellationToken;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_set_cancellation_token")?;
+    /// let shutdown = SzCancellationToken::new();
+    /// env.set_cancellation_token(Some(shutdown.clone()));
+    /// shutdown.cancel();
+    /// let result = env.get_engine()?.get_stats();
+    /// assert!(matches!(result, Err(SzError::Cancelled(_))));
+    /// # Ok::<(), SzError>(())
+    /// ```
+    pub fn set_cancellation_token(&self, token: Option<crate::control::SzCancellationToken>) {
+        self.settings.set_cancellation_token(token);
+    }
+
+    /// Returns the token set with
+    /// [`set_cancellation_token`](Self::set_cancellation_token)
+    pub fn cancellation_token(&self) -> Option<crate::control::SzCancellationToken> {
+        self.settings.cancellation_token()
+    }
+
+    /// Sets the policy for the records of one data source, or removes it
+    /// with `None`
+    ///
+    /// `add_record` and `delete_record` on every engine created by this
+    /// environment, including engines that already exist, apply the policy
+    /// for the call's data source. See the [`policy`](crate::policy) module.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::policy::SzPolicy;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_set_policy")?;
+    /// env.set_policy("TRIAL", Some(SzPolicy::new().with_dry_run(true)));
+    /// let engine = env.get_engine()?;
+    /// engine.add_record("TRIAL", "1", r#"{"NAME_FULL": "Jo Doe"}"#, None)?;
+    /// assert!(engine.get_record("TRIAL", "1", None).is_err());
+    /// # Ok::<(), SzError>(())
+    /// ```
+    pub fn set_policy(&self, data_source_code: &str, policy: Option<crate::policy::SzPolicy>) {
+        self.settings.set_policy(data_source_code, policy);
+    }
+
+    /// Returns the policy for a data source, if one is set
+    pub fn policy(&self, data_source_code: &str) -> Option<crate::policy::SzPolicy> {
+        self.settings
+            .policy(data_source_code)
+            .map(|policy| (*policy).clone())
+    }
+
+    /// Returns reuse counters for the argument buffer pool
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_buffer_pool_stats")?;
+    /// let stats = env.buffer_pool_stats();
+    /// println!("pool hits: {}, misses: {}", stats.hits, stats.misses);
+    /// # Ok::<(), SzError>(())
+    /// ```
+    pub fn buffer_pool_stats(&self) -> BufferPoolStats {
+        self.settings.buffer_pool.stats()
+    }
+
+    /// Returns counters for native response buffers
+    ///
+    /// The counters are process-wide. With no [`SzBytes`](crate::SzBytes)
+    /// alive, [`outstanding`](NativeBufferStats::outstanding) is 0; a value
+    /// that keeps growing means responses are being leaked.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_native_buffer_stats")?;
+    /// let stats = env.native_buffer_stats();
+    /// println!("outstanding native buffers: {}", stats.outstanding());
+    /// # Ok::<(), SzError>(())
+    /// ```
+    pub fn native_buffer_stats(&self) -> NativeBufferStats {
+        crate::ffi::helpers::native_buffer_stats()
+    }
+
+    /// Returns counters for native export and config handles (debug builds
+    /// only)
+    ///
+    /// The counters are process-wide. Handles wrapped in
+    /// [`ExportGuard`](crate::export::ExportGuard) or owned by an `SzConfig`
+    /// are closed when dropped; an [`open_exports`](NativeHandleStats::open_exports)
+    /// count that keeps growing means bare export handles are not being
+    /// closed.
+    #[cfg(debug_assertions)]
+    pub fn native_handle_stats(&self) -> NativeHandleStats {
+        crate::ffi::helpers::native_handle_stats()
+    }
+
+    /// Closes the open export handles before the native state they belong to
+    /// goes away
+    fn invalidate_exports(&self, event: &'static str) {
+        self.settings.exports.invalidate_all(event, |handle| {
+            let _ = unsafe { crate::ffi::Sz_closeExportReport_helper(handle as usize) };
+        });
+    }
+
     /// Ensures Sz_init has been called - should be called before any engine operations
     ///
     /// This method is thread-safe: the first thread to call this will run Sz_init(),
     /// and all other threads will block until initialization is complete.
     fn ensure_initialized(&self) -> SzResult<()> {
+        self.ensure_owner_process()?;
+        self.ensure_not_degraded()?;
+        crate::ffi::ensure_loaded()?;
+
         // Clone Arcs for use in closure (can't capture &self in call_once)
         let module_name = self.module_name.clone();
         let ini_params = self.ini_params.clone();
@@ -441,7 +1175,7 @@ impl SzEnvironmentCore {
 
         // call_once blocks all threads until the closure completes
         self.init_once.call_once(|| {
-            let result = (|| -> SzResult<()> {
+            let result = crate::ffi::boundary::guarded("Sz_init", || -> SzResult<()> {
                 let module_name_c = crate::ffi::helpers::str_to_c_string(&module_name)?;
                 let ini_params_c = crate::ffi::helpers::str_to_c_string(&ini_params)?;
                 let verbose = if verbose_logging { 1 } else { 0 };
@@ -452,7 +1186,7 @@ impl SzEnvironmentCore {
                     verbose as i64
                 ));
                 Ok(())
-            })();
+            });
 
             // Store any error for other threads to see
             if let Err(e) = result
@@ -477,6 +1211,10 @@ impl SzEnvironmentCore {
     /// This method is thread-safe: the first thread to call this will run SzConfigMgr_init(),
     /// and all other threads will block until initialization is complete.
     fn ensure_config_mgr_initialized(&self) -> SzResult<()> {
+        self.ensure_owner_process()?;
+        self.ensure_not_degraded()?;
+        crate::ffi::ensure_loaded()?;
+
         // Clone Arcs for use in closure (can't capture &self in call_once)
         let module_name = self.module_name.clone();
         let ini_params = self.ini_params.clone();
@@ -485,7 +1223,7 @@ impl SzEnvironmentCore {
 
         // call_once blocks all threads until the closure completes
         self.config_mgr_init_once.call_once(|| {
-            let result = (|| -> SzResult<()> {
+            let result = crate::ffi::boundary::guarded("SzConfigMgr_init", || -> SzResult<()> {
                 let module_name_c = crate::ffi::helpers::str_to_c_string(&module_name)?;
                 let ini_params_c = crate::ffi::helpers::str_to_c_string(&ini_params)?;
                 let verbose = if verbose_logging { 1 } else { 0 };
@@ -500,7 +1238,7 @@ impl SzEnvironmentCore {
                 };
                 crate::ffi::helpers::check_config_mgr_return_code(return_code)?;
                 Ok(())
-            })();
+            });
 
             // Store any error for other threads to see
             if let Err(e) = result
@@ -527,6 +1265,10 @@ impl SzEnvironmentCore {
     /// This method is thread-safe: the first thread to call this will run SzProduct_init(),
     /// and all other threads will block until initialization is complete.
     fn ensure_product_initialized(&self) -> SzResult<()> {
+        self.ensure_owner_process()?;
+        self.ensure_not_degraded()?;
+        crate::ffi::ensure_loaded()?;
+
         // Clone Arcs for use in closure (can't capture &self in call_once)
         let module_name = self.module_name.clone();
         let ini_params = self.ini_params.clone();
@@ -535,7 +1277,7 @@ impl SzEnvironmentCore {
 
         // call_once blocks all threads until the closure completes
         self.product_init_once.call_once(|| {
-            let result = (|| -> SzResult<()> {
+            let result = crate::ffi::boundary::guarded("SzProduct_init", || -> SzResult<()> {
                 let module_name_c = crate::ffi::helpers::str_to_c_string(&module_name)?;
                 let ini_params_c = crate::ffi::helpers::str_to_c_string(&ini_params)?;
                 let verbose = if verbose_logging { 1 } else { 0 };
@@ -550,7 +1292,7 @@ impl SzEnvironmentCore {
                 };
                 crate::ffi::helpers::check_product_return_code(return_code)?;
                 Ok(())
-            })();
+            });
 
             // Store any error for other threads to see
             if let Err(e) = result
@@ -586,8 +1328,20 @@ impl SzEnvironment for SzEnvironmentCore {
         // Ensure Sz_init has been called before reinitializing
         self.ensure_initialized()?;
 
-        ffi_call!(crate::ffi::Sz_reinit(config_id));
-        Ok(())
+        // Export handles do not survive Sz_reinit
+        self.invalidate_exports("reinitialized");
+
+        let result = (|| -> SzResult<()> {
+            ffi_call!(crate::ffi::Sz_reinit(config_id.get()));
+            Ok(())
+        })();
+        crate::audit::record(
+            &self.settings,
+            "reinitialize",
+            || serde_json::json!({ "config_id": config_id }),
+            &result,
+        );
+        result
     }
 
     fn get_active_config_id(&self) -> SzResult<ConfigId> {
@@ -601,7 +1355,7 @@ impl SzEnvironment for SzEnvironmentCore {
         let mut config_id: i64 = 0;
         let return_code = unsafe { crate::ffi::Sz_getActiveConfigID(&mut config_id) };
         crate::ffi::helpers::check_return_code(return_code)?;
-        Ok(config_id)
+        Ok(ConfigId(config_id))
     }
 
     fn get_product(&self) -> SzResult<Box<dyn SzProduct>> {
@@ -625,7 +1379,7 @@ impl SzEnvironment for SzEnvironmentCore {
         // Ensure Sz_init has been called before creating engine
         self.ensure_initialized()?;
 
-        let engine_core = super::engine::SzEngineCore::new()?;
+        let engine_core = super::engine::SzEngineCore::new(Arc::clone(&self.settings))?;
         Ok(Box::new(engine_core))
     }
 
@@ -640,7 +1394,8 @@ impl SzEnvironment for SzEnvironmentCore {
         self.ensure_config_mgr_initialized()?;
 
         // Create config manager instance (init already done, so this is safe)
-        let config_mgr_core = super::config_manager::SzConfigManagerCore::new()?;
+        let config_mgr_core =
+            super::config_manager::SzConfigManagerCore::with_settings(self.settings.clone());
         Ok(Box::new(config_mgr_core))
     }
 
@@ -652,11 +1407,8 @@ impl SzEnvironment for SzEnvironmentCore {
         // Ensure Sz_init has been called before creating diagnostic
         self.ensure_initialized()?;
 
-        let diagnostic_core = super::diagnostic::SzDiagnosticCore::new_with_params(
-            &self.module_name,
-            &self.ini_params,
-            self.verbose_logging,
-        )?;
+        let diagnostic_core =
+            super::diagnostic::SzDiagnosticCore::with_settings(Arc::clone(&self.settings));
         Ok(Box::new(diagnostic_core))
     }
 
@@ -0,0 +1,298 @@
+//! Bookkeeping of open export handles.
+//!
+//! The native library does not keep export handles valid across
+//! `Sz_reinit` or `Sz_destroy`; fetching from one afterwards is undefined.
+//! Engines register every handle they open here, and the environment closes
+//! the open ones before reinitializing or destroying. A later `fetch_next`
+//! with such a handle fails with `SzError::ExportInvalidated` instead of
+//! reaching the native library.
+//!
+//! No lock on the bookkeeping is held during a native call. Each open
+//! export has its own lock instead, held by a fetch or close for the
+//! duration of the native call, so calls on different handles run in
+//! parallel and a reinitialize waits for in-flight fetches to return rather
+//! than closing a handle under them.
+
+use crate::error::{SzError, SzResult};
+use crate::ffi::helpers::{NativeHandle, note_handle_closed, note_handle_opened};
+use crate::types::{ExportHandle, ExportSession};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::SystemTime;
+
+/// Open and invalidated export handles of an environment.
+#[derive(Debug, Default)]
+pub(crate) struct ExportSessions {
+    state: RwLock<SessionState>,
+}
+
+#[derive(Debug, Default)]
+struct SessionState {
+    open: HashMap<ExportHandle, Arc<OpenExport>>,
+    /// Handles closed by the SDK that the caller has not closed yet
+    invalidated: HashMap<ExportHandle, Invalidated>,
+}
+
+#[derive(Debug)]
+struct OpenExport {
+    operation: &'static str,
+    started_at: SystemTime,
+    rows_fetched: AtomicU64,
+    /// Whether the native handle was closed; held during native calls
+    closed: Mutex<bool>,
+}
+
+#[derive(Debug)]
+struct Invalidated {
+    event: &'static str,
+    export: Arc<OpenExport>,
+}
+
+impl ExportSessions {
+    fn read(&self) -> RwLockReadGuard<'_, SessionState> {
+        match self.state.read() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, SessionState> {
+        match self.state.write() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    /// The open export for `handle`, or the error for an invalidated one.
+    fn open_export(&self, handle: ExportHandle) -> SzResult<Option<Arc<OpenExport>>> {
+        let state = self.read();
+        if let Some(invalidated) = state.invalidated.get(&handle) {
+            return Err(invalidated.error(handle));
+        }
+        Ok(state.open.get(&handle).cloned())
+    }
+
+    /// Registers a handle returned by an export call.
+    pub(crate) fn opened(&self, handle: ExportHandle, operation: &'static str) {
+        note_handle_opened(NativeHandle::Export);
+        let mut state = self.write();
+        state.invalidated.remove(&handle);
+        state.open.insert(
+            handle,
+            Arc::new(OpenExport {
+                operation,
+                started_at: SystemTime::now(),
+                rows_fetched: AtomicU64::new(0),
+                closed: Mutex::new(false),
+            }),
+        );
+    }
+
+    /// Runs `fetch` for `handle` unless the handle was invalidated.
+    pub(crate) fn fetch(
+        &self,
+        handle: ExportHandle,
+        fetch: impl FnOnce() -> SzResult<String>,
+    ) -> SzResult<String> {
+        let Some(export) = self.open_export(handle)? else {
+            return fetch();
+        };
+        let closed = export.lock();
+        if *closed {
+            // Invalidated since it was looked up
+            return Err(self.open_export(handle).err().unwrap_or_else(|| {
+                SzError::export_invalidated(format!("export handle {handle} was closed"))
+            }));
+        }
+        let chunk = fetch()?;
+        if !chunk.is_empty() {
+            export.rows_fetched.fetch_add(1, Ordering::Relaxed);
+        }
+        Ok(chunk)
+    }
+
+    /// Runs `close` for `handle`, or only forgets the handle if the SDK has
+    /// closed it already.
+    pub(crate) fn close(
+        &self,
+        handle: ExportHandle,
+        close: impl FnOnce() -> SzResult<()>,
+    ) -> SzResult<()> {
+        let export = match self.open_export(handle) {
+            Ok(export) => export,
+            Err(_) => {
+                self.write().invalidated.remove(&handle);
+                return Ok(());
+            }
+        };
+        let Some(export) = export else {
+            return close();
+        };
+        let mut closed = export.lock();
+        if *closed {
+            // Invalidated since it was looked up
+            drop(closed);
+            self.write().invalidated.remove(&handle);
+            return Ok(());
+        }
+        close()?;
+        *closed = true;
+        drop(closed);
+        note_handle_closed(NativeHandle::Export);
+        let mut state = self.write();
+        // The native library may have reused the handle for a new export
+        if state
+            .open
+            .get(&handle)
+            .is_some_and(|open| Arc::ptr_eq(open, &export))
+        {
+            state.open.remove(&handle);
+        }
+        Ok(())
+    }
+
+    /// Closes every open handle with `close` and marks it invalidated by
+    /// `event` (e.g. "reinitialized"). Returns the number of handles closed.
+    pub(crate) fn invalidate_all(
+        &self,
+        event: &'static str,
+        mut close: impl FnMut(ExportHandle),
+    ) -> usize {
+        let open: Vec<_> = {
+            let mut state = self.write();
+            let open: Vec<_> = state.open.drain().collect();
+            for (handle, export) in &open {
+                state.invalidated.insert(
+                    *handle,
+                    Invalidated {
+                        event,
+                        export: Arc::clone(export),
+                    },
+                );
+            }
+            open
+        };
+        let mut count = 0;
+        for (handle, export) in open {
+            // Waits for an in-flight fetch on the handle
+            let mut closed = export.lock();
+            if !*closed {
+                close(handle);
+                *closed = true;
+                note_handle_closed(NativeHandle::Export);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// The open exports, by handle.
+    pub(crate) fn sessions(&self) -> Vec<ExportSession> {
+        let state = self.read();
+        let mut sessions: Vec<_> = state
+            .open
+            .iter()
+            .map(|(handle, export)| ExportSession {
+                handle: *handle,
+                operation: export.operation,
+                started_at: export.started_at,
+                rows_fetched: export.rows_fetched.load(Ordering::Relaxed),
+            })
+            .collect();
+        sessions.sort_by_key(|session| session.handle);
+        sessions
+    }
+}
+
+impl OpenExport {
+    fn lock(&self) -> MutexGuard<'_, bool> {
+        match self.closed.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+impl Invalidated {
+    fn error(&self, handle: ExportHandle) -> SzError {
+        SzError::export_invalidated(format!(
+            "export handle {handle} was closed when the environment was {} after {} rows \
+             had been fetched; close it, start a new export and skip the entities already \
+             processed to resume",
+            self.event,
+            self.export.rows_fetched.load(Ordering::Relaxed)
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_invalidated_handles_fail_without_native_calls() {
+        let sessions = ExportSessions::default();
+        sessions.opened(7, "export_json_entity_report");
+        sessions.opened(8, "export_csv_entity_report");
+        assert_eq!(
+            sessions.fetch(7, || Ok("{}\n".to_string())).unwrap(),
+            "{}\n"
+        );
+        assert_eq!(sessions.fetch(7, || Ok(String::new())).unwrap(), "");
+        assert_eq!(sessions.sessions()[0].rows_fetched, 1);
+
+        let mut closed = Vec::new();
+        assert_eq!(
+            sessions.invalidate_all("reinitialized", |handle| closed.push(handle)),
+            2
+        );
+        closed.sort();
+        assert_eq!(closed, [7, 8]);
+        assert!(sessions.sessions().is_empty());
+
+        let err = sessions
+            .fetch(7, || panic!("fetched from an invalidated handle"))
+            .unwrap_err();
+        assert!(matches!(err, SzError::ExportInvalidated(_)));
+        assert!(err.message().contains("reinitialized after 1 rows"));
+
+        sessions
+            .close(7, || panic!("closed an invalidated handle twice"))
+            .unwrap();
+        assert!(sessions.fetch(7, || Ok(String::new())).is_ok());
+
+        sessions.opened(8, "export_json_entity_report");
+        assert!(sessions.fetch(8, || Ok(String::new())).is_ok());
+        sessions.close(8, || Ok(())).unwrap();
+        assert!(sessions.sessions().is_empty());
+    }
+
+    #[test]
+    fn test_bookkeeping_is_not_locked_during_native_calls() {
+        let sessions = ExportSessions::default();
+        sessions.opened(7, "export_json_entity_report");
+        let chunk = sessions
+            .fetch(7, || {
+                // Another export starting while this fetch is in the native library
+                sessions.opened(8, "export_csv_entity_report");
+                Ok(format!("{}\n", sessions.sessions().len()))
+            })
+            .unwrap();
+        assert_eq!(chunk, "2\n");
+        sessions
+            .close(7, || {
+                assert_eq!(sessions.fetch(8, || Ok("{}".to_string())).unwrap(), "{}");
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(sessions.sessions().len(), 1);
+        assert_eq!(sessions.sessions()[0].rows_fetched, 1);
+
+        // A failed close leaves the export open
+        assert!(sessions.close(8, || Err(SzError::unknown("busy"))).is_err());
+        assert_eq!(sessions.sessions().len(), 1);
+        sessions.close(8, || Ok(())).unwrap();
+        assert!(sessions.sessions().is_empty());
+    }
+}
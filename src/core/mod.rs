@@ -7,13 +7,19 @@
 //! All other core types are internal implementation details accessed through
 //! trait objects.
 
+mod buffer_pool;
 mod config;
 mod config_manager;
 mod diagnostic;
+mod dry_run;
 mod engine;
+pub(crate) mod export_sessions;
 mod guard;
 mod product;
+pub(crate) mod scoped_engine;
+pub(crate) mod settings;
 mod snapshot;
+mod timed_calls;
 
 pub mod environment;
 
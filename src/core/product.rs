@@ -1,6 +1,6 @@
 //! Core implementation of SzProduct trait
 
-use crate::{error::SzResult, traits::SzProduct, types::JsonString};
+use crate::{error::SzResult, ffi::boundary::guarded, traits::SzProduct, types::JsonString};
 
 /// Core implementation of the SzProduct trait
 pub struct SzProductCore;
@@ -15,19 +15,23 @@ impl SzProductCore {
 
 impl SzProduct for SzProductCore {
     fn get_license(&self) -> SzResult<JsonString> {
-        let license_ptr = unsafe { crate::ffi::SzProduct_getLicense() };
-        if license_ptr.is_null() {
-            return Err(crate::error::SzError::unknown("Failed to get license"));
-        }
-        unsafe { crate::ffi::helpers::c_str_to_string_no_free(license_ptr) }
+        guarded("get_license", || {
+            let license_ptr = unsafe { crate::ffi::SzProduct_getLicense() };
+            if license_ptr.is_null() {
+                return Err(crate::error::SzError::unknown("Failed to get license"));
+            }
+            unsafe { crate::ffi::helpers::c_str_to_string_no_free(license_ptr) }
+        })
     }
 
     fn get_version(&self) -> SzResult<JsonString> {
-        let version_ptr = unsafe { crate::ffi::SzProduct_getVersion() };
-        if version_ptr.is_null() {
-            return Err(crate::error::SzError::unknown("Failed to get version"));
-        }
-        unsafe { crate::ffi::helpers::c_str_to_string_no_free(version_ptr) }
+        guarded("get_version", || {
+            let version_ptr = unsafe { crate::ffi::SzProduct_getVersion() };
+            if version_ptr.is_null() {
+                return Err(crate::error::SzError::unknown("Failed to get version"));
+            }
+            unsafe { crate::ffi::helpers::c_str_to_string_no_free(version_ptr) }
+        })
     }
 }
 
@@ -0,0 +1,377 @@
+//! Capability-restricted engine wrapper
+//!
+//! Returned by `SzEnvironment::get_engine_scoped` for scopes below
+//! [`EngineScope::Admin`]. Reads are allowed in every scope and delegate
+//! straight to the wrapped engine; every other method checks the scope it
+//! needs first.
+
+use crate::{
+    bytes::SzBytes,
+    error::{SzError, SzResult},
+    flags::SzFlags,
+    traits::{SzEngine, SzEngineLoad, SzEngineRead},
+    types::*,
+};
+use std::collections::HashSet;
+
+pub(crate) struct ScopedEngine {
+    inner: Box<dyn SzEngine>,
+    scope: EngineScope,
+}
+
+impl ScopedEngine {
+    pub(crate) fn new(inner: Box<dyn SzEngine>, scope: EngineScope) -> Self {
+        Self { inner, scope }
+    }
+
+    /// Fails with `SzError::NotPermitted` unless the scope covers `required`.
+    fn require(&self, required: EngineScope, method: &str) -> SzResult<()> {
+        if self.scope >= required {
+            Ok(())
+        } else {
+            Err(SzError::not_permitted(format!(
+                "{method} requires {required:?} scope; engine scope is {:?}",
+                self.scope
+            )))
+        }
+    }
+}
+
+impl SzEngine for ScopedEngine {
+    fn prime_engine(&self) -> SzResult<()> {
+        self.require(EngineScope::Admin, "prime_engine")?;
+        self.inner.prime_engine()
+    }
+}
+
+impl SzEngineRead for ScopedEngine {
+    fn get_stats(&self) -> SzResult<JsonString> {
+        self.inner.get_stats()
+    }
+
+    fn get_record_preview(
+        &self,
+        record_definition: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.inner.get_record_preview(record_definition, flags)
+    }
+
+    fn search_by_attributes(
+        &self,
+        attributes: &str,
+        search_profile: Option<&str>,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.inner
+            .search_by_attributes(attributes, search_profile, flags)
+    }
+
+    fn search_by_attributes_bytes(
+        &self,
+        attributes: &str,
+        search_profile: Option<&str>,
+        flags: Option<SzFlags>,
+    ) -> SzResult<SzBytes> {
+        self.inner
+            .search_by_attributes_bytes(attributes, search_profile, flags)
+    }
+
+    fn why_search(
+        &self,
+        attributes: &str,
+        entity_id: EntityId,
+        search_profile: Option<&str>,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.inner
+            .why_search(attributes, entity_id, search_profile, flags)
+    }
+
+    fn get_entity(&self, entity_ref: EntityRef, flags: Option<SzFlags>) -> SzResult<JsonString> {
+        self.inner.get_entity(entity_ref, flags)
+    }
+
+    fn get_entity_bytes(&self, entity_ref: EntityRef, flags: Option<SzFlags>) -> SzResult<SzBytes> {
+        self.inner.get_entity_bytes(entity_ref, flags)
+    }
+
+    fn get_record(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.inner.get_record(data_source_code, record_id, flags)
+    }
+
+    fn get_record_bytes(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<SzBytes> {
+        self.inner
+            .get_record_bytes(data_source_code, record_id, flags)
+    }
+
+    fn find_interesting_entities(
+        &self,
+        entity_ref: EntityRef,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.inner.find_interesting_entities(entity_ref, flags)
+    }
+
+    fn find_path_by_entity_id(
+        &self,
+        start_entity_id: EntityId,
+        end_entity_id: EntityId,
+        max_degrees: i64,
+        avoid_entity_ids: Option<&HashSet<EntityId>>,
+        required_data_sources: Option<&HashSet<String>>,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.inner.find_path_by_entity_id(
+            start_entity_id,
+            end_entity_id,
+            max_degrees,
+            avoid_entity_ids,
+            required_data_sources,
+            flags,
+        )
+    }
+
+    fn find_path_by_record_id(
+        &self,
+        start_data_source_code: &str,
+        start_record_id: &str,
+        end_data_source_code: &str,
+        end_record_id: &str,
+        max_degrees: i64,
+        avoid_record_keys: Option<&[RecordKey]>,
+        required_data_sources: Option<&HashSet<String>>,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.inner.find_path_by_record_id(
+            start_data_source_code,
+            start_record_id,
+            end_data_source_code,
+            end_record_id,
+            max_degrees,
+            avoid_record_keys,
+            required_data_sources,
+            flags,
+        )
+    }
+
+    fn find_network_by_entity_id(
+        &self,
+        entity_list: &[EntityId],
+        max_degrees: i64,
+        build_out_degrees: i64,
+        max_entities: i64,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.inner.find_network_by_entity_id(
+            entity_list,
+            max_degrees,
+            build_out_degrees,
+            max_entities,
+            flags,
+        )
+    }
+
+    fn find_network_by_record_id(
+        &self,
+        record_keys: &[RecordKey],
+        max_degrees: i64,
+        build_out_degrees: i64,
+        max_entities: i64,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.inner.find_network_by_record_id(
+            record_keys,
+            max_degrees,
+            build_out_degrees,
+            max_entities,
+            flags,
+        )
+    }
+
+    fn why_entities(
+        &self,
+        entity_id1: EntityId,
+        entity_id2: EntityId,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.inner.why_entities(entity_id1, entity_id2, flags)
+    }
+
+    fn why_records(
+        &self,
+        data_source_code1: &str,
+        record_id1: &str,
+        data_source_code2: &str,
+        record_id2: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.inner.why_records(
+            data_source_code1,
+            record_id1,
+            data_source_code2,
+            record_id2,
+            flags,
+        )
+    }
+
+    fn why_record_in_entity(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.inner
+            .why_record_in_entity(data_source_code, record_id, flags)
+    }
+
+    fn how_entity(&self, entity_id: EntityId, flags: Option<SzFlags>) -> SzResult<JsonString> {
+        self.inner.how_entity(entity_id, flags)
+    }
+
+    fn get_virtual_entity(
+        &self,
+        record_keys: &[RecordKey],
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.inner.get_virtual_entity(record_keys, flags)
+    }
+
+    fn count_redo_records(&self) -> SzResult<i64> {
+        self.inner.count_redo_records()
+    }
+
+    fn repository_counts(&self, max_age: std::time::Duration) -> SzResult<RepositoryCounts> {
+        self.inner.repository_counts(max_age)
+    }
+
+    fn export_json_entity_report(&self, flags: Option<SzFlags>) -> SzResult<ExportHandle> {
+        self.inner.export_json_entity_report(flags)
+    }
+
+    fn export_csv_entity_report(
+        &self,
+        csv_column_list: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<ExportHandle> {
+        self.inner.export_csv_entity_report(csv_column_list, flags)
+    }
+
+    fn fetch_next(&self, export_handle: ExportHandle) -> SzResult<JsonString> {
+        self.inner.fetch_next(export_handle)
+    }
+
+    fn close_export_report(&self, export_handle: ExportHandle) -> SzResult<()> {
+        self.inner.close_export_report(export_handle)
+    }
+}
+
+impl SzEngineLoad for ScopedEngine {
+    fn add_record(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        record_definition: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.require(EngineScope::Loader, "add_record")?;
+        self.inner
+            .add_record(data_source_code, record_id, record_definition, flags)
+    }
+
+    fn delete_record(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.require(EngineScope::Loader, "delete_record")?;
+        self.inner.delete_record(data_source_code, record_id, flags)
+    }
+
+    fn replace_record(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        record_definition: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.require(EngineScope::Loader, "replace_record")?;
+        self.inner
+            .replace_record(data_source_code, record_id, record_definition, flags)
+    }
+
+    fn add_record_if_changed(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        record_definition: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<UpsertOutcome> {
+        self.require(EngineScope::Loader, "add_record_if_changed")?;
+        self.inner
+            .add_record_if_changed(data_source_code, record_id, record_definition, flags)
+    }
+
+    fn add_record_if_changed_with_hashes(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        record_definition: &str,
+        flags: Option<SzFlags>,
+        hashes: &dyn crate::ingest::RecordHashStore,
+    ) -> SzResult<UpsertOutcome> {
+        self.require(EngineScope::Loader, "add_record_if_changed_with_hashes")?;
+        self.inner.add_record_if_changed_with_hashes(
+            data_source_code,
+            record_id,
+            record_definition,
+            flags,
+            hashes,
+        )
+    }
+
+    fn reevaluate_record(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.require(EngineScope::Loader, "reevaluate_record")?;
+        self.inner
+            .reevaluate_record(data_source_code, record_id, flags)
+    }
+
+    fn reevaluate_entity(
+        &self,
+        entity_id: EntityId,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.require(EngineScope::Loader, "reevaluate_entity")?;
+        self.inner.reevaluate_entity(entity_id, flags)
+    }
+
+    fn process_redo_record(
+        &self,
+        redo_record: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.require(EngineScope::Loader, "process_redo_record")?;
+        self.inner.process_redo_record(redo_record, flags)
+    }
+
+    fn get_redo_record(&self) -> SzResult<JsonString> {
+        self.require(EngineScope::Loader, "get_redo_record")?;
+        self.inner.get_redo_record()
+    }
+}
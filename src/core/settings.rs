@@ -0,0 +1,341 @@
+//! Environment-wide settings shared with the components it creates.
+//!
+//! `SzEnvironmentCore` owns a single [`EngineSettings`] behind an `Arc` and
+//! hands a clone of that `Arc` to every engine it creates, so a setting changed
+//! on the environment takes effect for all existing and future engines.
+
+use super::buffer_pool::BufferPool;
+use super::export_sessions::ExportSessions;
+use super::timed_calls::TimedCalls;
+use crate::audit::SinkHandle;
+use crate::control::SzCancellationToken;
+use crate::error::{SzError, SzResult};
+use crate::intercept::{Interceptors, SzInterceptor};
+use crate::policy::SzPolicy;
+use crate::types::{
+    ErrorFilter, ErrorRecord, RepositoryCounts, ResponseFormat, ResultSizeLimit, TimedOperation,
+    TimeoutRecord,
+};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock};
+use std::time::{Duration, SystemTime};
+
+/// Number of timed-out calls kept for inspection.
+const TIMEOUT_LOG_CAPACITY: usize = 100;
+
+/// Number of failed calls kept for inspection unless changed with
+/// `SzEnvironmentCore::set_error_history_capacity`.
+const ERROR_LOG_CAPACITY: usize = 100;
+
+/// Mutable runtime settings consulted by the core components.
+#[derive(Debug, Default)]
+pub(crate) struct EngineSettings {
+    result_size_limit: RwLock<Option<ResultSizeLimit>>,
+    response_format: RwLock<ResponseFormat>,
+    /// Reusable buffers for C string arguments
+    pub(crate) buffer_pool: BufferPool,
+    /// Open export handles, closed before reinitialize and destroy
+    pub(crate) exports: ExportSessions,
+    operation_timeouts: RwLock<HashMap<TimedOperation, Duration>>,
+    /// Workers that run calls with a timeout
+    pub(crate) timed_calls: TimedCalls,
+    timeout_log: Mutex<VecDeque<TimeoutRecord>>,
+    error_log: Mutex<ErrorLog>,
+    purge_disabled: AtomicBool,
+    dry_run: AtomicBool,
+    /// Last `repository_counts` result, shared by all engines
+    counts: Mutex<Option<RepositoryCounts>>,
+    audit_sink: RwLock<Option<SinkHandle>>,
+    audit_failures: AtomicU64,
+    interceptors: RwLock<Interceptors>,
+    /// Whether `interceptors` is non-empty, checked on every engine call
+    intercepting: AtomicBool,
+    cancellation: RwLock<Option<SzCancellationToken>>,
+    /// Whether `cancellation` is set, checked on every engine call
+    cancellable: AtomicBool,
+    /// Policies by uppercased data source code
+    policies: RwLock<HashMap<String, Arc<SzPolicy>>>,
+}
+
+/// Failed calls, oldest first.
+#[derive(Debug, Default)]
+struct ErrorLog {
+    records: VecDeque<ErrorRecord>,
+    /// `None` until set, meaning [`ERROR_LOG_CAPACITY`]
+    capacity: Option<usize>,
+}
+
+impl ErrorLog {
+    fn capacity(&self) -> usize {
+        self.capacity.unwrap_or(ERROR_LOG_CAPACITY)
+    }
+}
+
+impl EngineSettings {
+    pub(crate) fn result_size_limit(&self) -> Option<ResultSizeLimit> {
+        match self.result_size_limit.read() {
+            Ok(guard) => *guard,
+            Err(poisoned) => *poisoned.into_inner(),
+        }
+    }
+
+    pub(crate) fn set_result_size_limit(&self, limit: Option<ResultSizeLimit>) {
+        match self.result_size_limit.write() {
+            Ok(mut guard) => *guard = limit,
+            Err(poisoned) => *poisoned.into_inner() = limit,
+        }
+    }
+
+    pub(crate) fn response_format(&self) -> ResponseFormat {
+        match self.response_format.read() {
+            Ok(guard) => *guard,
+            Err(poisoned) => *poisoned.into_inner(),
+        }
+    }
+
+    pub(crate) fn set_response_format(&self, format: ResponseFormat) {
+        match self.response_format.write() {
+            Ok(mut guard) => *guard = format,
+            Err(poisoned) => *poisoned.into_inner() = format,
+        }
+    }
+
+    pub(crate) fn operation_timeout(&self, operation: TimedOperation) -> Option<Duration> {
+        match self.operation_timeouts.read() {
+            Ok(guard) => guard.get(&operation).copied(),
+            Err(poisoned) => poisoned.into_inner().get(&operation).copied(),
+        }
+    }
+
+    pub(crate) fn set_operation_timeout(
+        &self,
+        operation: TimedOperation,
+        timeout: Option<Duration>,
+    ) {
+        let mut guard = match self.operation_timeouts.write() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        match timeout {
+            Some(timeout) => guard.insert(operation, timeout),
+            None => guard.remove(&operation),
+        };
+    }
+
+    pub(crate) fn record_timeout(&self, operation: &'static str, timeout: Duration) {
+        let mut log = match self.timeout_log.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if log.len() == TIMEOUT_LOG_CAPACITY {
+            log.pop_front();
+        }
+        log.push_back(TimeoutRecord {
+            operation,
+            timeout,
+            occurred_at: SystemTime::now(),
+        });
+    }
+
+    pub(crate) fn timed_out_calls(&self) -> Vec<TimeoutRecord> {
+        match self.timeout_log.lock() {
+            Ok(guard) => guard.iter().cloned().collect(),
+            Err(poisoned) => poisoned.into_inner().iter().cloned().collect(),
+        }
+    }
+
+    fn error_log(&self) -> MutexGuard<'_, ErrorLog> {
+        match self.error_log.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    pub(crate) fn record_error(&self, operation: &'static str, error: &SzError) {
+        let mut log = self.error_log();
+        let capacity = log.capacity();
+        if capacity == 0 {
+            return;
+        }
+        while log.records.len() >= capacity {
+            log.records.pop_front();
+        }
+        log.records.push_back(ErrorRecord {
+            operation,
+            category: error.category(),
+            code: error.error_code(),
+            component: error.component(),
+            message: error.message().to_string(),
+            occurred_at: SystemTime::now(),
+        });
+    }
+
+    pub(crate) fn errors_matching(&self, filter: &ErrorFilter) -> Vec<ErrorRecord> {
+        self.error_log()
+            .records
+            .iter()
+            .filter(|record| filter.matches(record))
+            .cloned()
+            .collect()
+    }
+
+    pub(crate) fn error_history_capacity(&self) -> usize {
+        self.error_log().capacity()
+    }
+
+    pub(crate) fn set_error_history_capacity(&self, capacity: usize) {
+        let mut log = self.error_log();
+        log.capacity = Some(capacity);
+        let excess = log.records.len().saturating_sub(capacity);
+        log.records.drain(..excess);
+    }
+
+    pub(crate) fn clear_error_history(&self) {
+        self.error_log().records.clear();
+    }
+
+    pub(crate) fn purge_enabled(&self) -> bool {
+        !self.purge_disabled.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn set_purge_enabled(&self, enabled: bool) {
+        self.purge_disabled.store(!enabled, Ordering::Release);
+    }
+
+    pub(crate) fn dry_run(&self) -> bool {
+        self.dry_run.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn set_dry_run(&self, enabled: bool) {
+        self.dry_run.store(enabled, Ordering::Release);
+    }
+
+    pub(crate) fn audit_sink(&self) -> Option<SinkHandle> {
+        match self.audit_sink.read() {
+            Ok(guard) => guard.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    pub(crate) fn set_audit_sink(&self, sink: Option<SinkHandle>) {
+        match self.audit_sink.write() {
+            Ok(mut guard) => *guard = sink,
+            Err(poisoned) => *poisoned.into_inner() = sink,
+        }
+    }
+
+    pub(crate) fn audit_failed(&self) {
+        self.audit_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn audit_failures(&self) -> u64 {
+        self.audit_failures.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn has_interceptors(&self) -> bool {
+        self.intercepting.load(Ordering::Acquire)
+    }
+
+    pub(crate) fn interceptors(&self) -> Vec<Arc<dyn SzInterceptor>> {
+        match self.interceptors.read() {
+            Ok(guard) => guard.0.clone(),
+            Err(poisoned) => poisoned.into_inner().0.clone(),
+        }
+    }
+
+    pub(crate) fn add_interceptor(&self, interceptor: Arc<dyn SzInterceptor>) {
+        let mut guard = match self.interceptors.write() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        guard.0.push(interceptor);
+        self.intercepting.store(true, Ordering::Release);
+    }
+
+    pub(crate) fn clear_interceptors(&self) {
+        let mut guard = match self.interceptors.write() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        guard.0.clear();
+        self.intercepting.store(false, Ordering::Release);
+    }
+
+    pub(crate) fn cancellation_token(&self) -> Option<SzCancellationToken> {
+        if !self.cancellable.load(Ordering::Acquire) {
+            return None;
+        }
+        match self.cancellation.read() {
+            Ok(guard) => guard.clone(),
+            Err(poisoned) => poisoned.into_inner().clone(),
+        }
+    }
+
+    pub(crate) fn set_cancellation_token(&self, token: Option<SzCancellationToken>) {
+        let mut guard = match self.cancellation.write() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        self.cancellable.store(token.is_some(), Ordering::Release);
+        *guard = token;
+    }
+
+    /// Fails with `SzError::Cancelled` if the environment's token has been
+    /// cancelled.
+    pub(crate) fn check_cancelled(&self, operation: &str) -> SzResult<()> {
+        match self.cancellation_token() {
+            Some(token) => token.check(operation),
+            None => Ok(()),
+        }
+    }
+
+    pub(crate) fn policy(&self, data_source_code: &str) -> Option<Arc<SzPolicy>> {
+        let guard = match self.policies.read() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if guard.is_empty() {
+            return None;
+        }
+        guard.get(&data_source_code.to_uppercase()).cloned()
+    }
+
+    /// Whether any data source policy is in dry-run mode.
+    pub(crate) fn has_dry_run_policy(&self) -> bool {
+        let guard = match self.policies.read() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        guard.values().any(|policy| policy.dry_run())
+    }
+
+    pub(crate) fn set_policy(&self, data_source_code: &str, policy: Option<SzPolicy>) {
+        let mut guard = match self.policies.write() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        let code = data_source_code.to_uppercase();
+        match policy {
+            Some(policy) => guard.insert(code, Arc::new(policy)),
+            None => guard.remove(&code),
+        };
+    }
+
+    /// Returns the cached repository counts if they are at most `max_age` old.
+    pub(crate) fn cached_counts(&self, max_age: Duration) -> Option<RepositoryCounts> {
+        let guard = match self.counts.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        guard.filter(|counts| counts.age() <= max_age)
+    }
+
+    /// Replaces the cached repository counts; `None` discards them.
+    pub(crate) fn set_cached_counts(&self, counts: Option<RepositoryCounts>) {
+        match self.counts.lock() {
+            Ok(mut guard) => *guard = counts,
+            Err(poisoned) => *poisoned.into_inner() = counts,
+        }
+    }
+}
@@ -27,6 +27,7 @@
 //! `SEMANTIC_VALUE` embeddings, restore avoids recomputing those embeddings.
 
 use crate::error::{SzError, SzResult};
+use crate::export::ExportGuard;
 use crate::flags::SzFlags;
 use crate::traits::SzEnvironment;
 use serde::Serialize;
@@ -227,22 +228,18 @@ pub(crate) fn export_snapshot(env: &SzEnvironmentCore, path: &Path) -> SzResult<
     // Stream every record out of the datastore, writing each line as it arrives so
     // that even very large datastores are exported with bounded memory use.
     let flags = SzFlags::EXPORT_INCLUDE_ALL_ENTITIES | SzFlags::ENTITY_INCLUDE_RECORD_JSON_DATA;
-    let handle = engine.export_json_entity_report(Some(flags))?;
-    let result = (|| -> SzResult<()> {
-        loop {
-            let chunk = engine.fetch_next(handle)?;
-            if chunk.is_empty() {
-                break;
-            }
-            for record in records_from_entity(&chunk)? {
-                write_line(&mut writer, &record)?;
-            }
+    // The guard releases the export handle even if writing fails midway.
+    let export = ExportGuard::json(engine.as_ref(), Some(flags))?;
+    loop {
+        let chunk = export.fetch_next()?;
+        if chunk.is_empty() {
+            break;
         }
-        Ok(())
-    })();
-    // Always release the export handle, even if writing failed midway.
-    let _ = engine.close_export_report(handle);
-    result?;
+        for record in records_from_entity(&chunk)? {
+            write_line(&mut writer, &record)?;
+        }
+    }
+    drop(export);
 
     writer
         .flush()
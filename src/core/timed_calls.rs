@@ -0,0 +1,294 @@
+//! Worker threads for engine calls with a timeout.
+//!
+//! The native library has no way to cancel a call, so a timed call runs on
+//! a worker thread while the caller waits at most its timeout. The workers
+//! of an environment are a bounded pool, started on demand and stopped
+//! after a minute without work, instead of a thread per call.
+//!
+//! A call whose caller gave up keeps its worker until the native call
+//! returns. Such abandoned calls are counted; once every worker is held by
+//! one, new timed calls fail straight away with `SzError::Timeout` instead
+//! of queueing behind calls that may never return. A queued call whose
+//! caller gave up before a worker picked it up is dropped without reaching
+//! the native library.
+
+use crate::error::{SzError, SzResult};
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// Maximum number of worker threads per environment
+const MAX_WORKERS: usize = 8;
+
+/// How long an idle worker waits for work before it stops
+const IDLE_TIMEOUT: Duration = Duration::from_secs(60);
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// The timed-call workers of an environment.
+#[derive(Debug, Default)]
+pub(crate) struct TimedCalls {
+    pool: Arc<Pool>,
+}
+
+#[derive(Debug, Default)]
+struct Pool {
+    state: Mutex<PoolState>,
+    work: Condvar,
+    /// Calls whose caller gave up while the native call was running
+    abandoned: AtomicUsize,
+}
+
+#[derive(Default)]
+struct PoolState {
+    queue: VecDeque<Job>,
+    workers: usize,
+    idle: usize,
+}
+
+impl std::fmt::Debug for PoolState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PoolState")
+            .field("queued", &self.queue.len())
+            .field("workers", &self.workers)
+            .field("idle", &self.idle)
+            .finish()
+    }
+}
+
+/// Where a submitted call is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CallState {
+    Queued,
+    Running,
+    Finished,
+    Abandoned,
+}
+
+/// A call submitted to the pool, waited on by its caller.
+#[derive(Debug)]
+pub(crate) struct TimedCall<T> {
+    state: Arc<Mutex<CallState>>,
+    receiver: Receiver<SzResult<T>>,
+    pool: Arc<Pool>,
+}
+
+impl TimedCalls {
+    /// Number of timed calls abandoned by their caller whose native call has
+    /// not returned yet.
+    pub(crate) fn abandoned(&self) -> usize {
+        self.pool.abandoned.load(Ordering::Acquire)
+    }
+
+    /// Queues `call` for a worker.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::Timeout` when every worker is held by an abandoned call.
+    pub(crate) fn submit<T: Send + 'static>(
+        &self,
+        operation: &'static str,
+        call: impl FnOnce() -> SzResult<T> + Send + 'static,
+    ) -> SzResult<TimedCall<T>> {
+        let abandoned = self.abandoned();
+        if abandoned >= MAX_WORKERS {
+            return Err(SzError::timeout(format!(
+                "{operation} was not started: {abandoned} abandoned calls are still \
+                 running in the native library"
+            )));
+        }
+        let state = Arc::new(Mutex::new(CallState::Queued));
+        let (sender, receiver) = mpsc::sync_channel(1);
+        let job_state = Arc::clone(&state);
+        let pool = Arc::clone(&self.pool);
+        let job: Job = Box::new(move || {
+            {
+                let mut state = lock(&job_state);
+                if *state == CallState::Abandoned {
+                    return;
+                }
+                *state = CallState::Running;
+            }
+            let result = call();
+            let mut state = lock(&job_state);
+            if *state == CallState::Abandoned {
+                pool.abandoned.fetch_sub(1, Ordering::AcqRel);
+            } else {
+                *state = CallState::Finished;
+                let _ = sender.send(result);
+            }
+        });
+
+        let mut pool_state = lock(&self.pool.state);
+        pool_state.queue.push_back(job);
+        // Each idle worker takes one queued call; start one for the rest
+        if pool_state.queue.len() > pool_state.idle && pool_state.workers < MAX_WORKERS {
+            let pool = Arc::clone(&self.pool);
+            let started = std::thread::Builder::new()
+                .name(format!("sz-timed-{}", pool_state.workers))
+                .spawn(move || pool.work_loop());
+            if let Err(e) = started {
+                pool_state.queue.pop_back();
+                return Err(SzError::unknown(format!(
+                    "Failed to start {operation} thread: {e}"
+                )));
+            }
+            pool_state.workers += 1;
+        }
+        self.pool.work.notify_one();
+        Ok(TimedCall {
+            state,
+            receiver,
+            pool: Arc::clone(&self.pool),
+        })
+    }
+}
+
+impl Pool {
+    fn work_loop(&self) {
+        loop {
+            let mut state = lock(&self.state);
+            let job = loop {
+                if let Some(job) = state.queue.pop_front() {
+                    break job;
+                }
+                state.idle += 1;
+                let (guard, timeout) = match self.work.wait_timeout(state, IDLE_TIMEOUT) {
+                    Ok(result) => result,
+                    Err(poisoned) => poisoned.into_inner(),
+                };
+                state = guard;
+                state.idle -= 1;
+                if timeout.timed_out() && state.queue.is_empty() {
+                    state.workers -= 1;
+                    return;
+                }
+            };
+            drop(state);
+            job();
+        }
+    }
+}
+
+impl<T> TimedCall<T> {
+    /// Waits at most `timeout` for the result; `None` when it did not come.
+    pub(crate) fn wait(&self, timeout: Duration) -> Option<SzResult<T>> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(result) => Some(result),
+            Err(mpsc::RecvTimeoutError::Timeout) => None,
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                Some(Err(SzError::unknown("timed call ended without a result")))
+            }
+        }
+    }
+
+    /// Gives up on the call. Returns its result if it finished in the
+    /// meantime.
+    pub(crate) fn abandon(self) -> Option<SzResult<T>> {
+        let mut state = lock(&self.state);
+        match *state {
+            CallState::Finished => return self.receiver.try_recv().ok(),
+            CallState::Running => {
+                self.pool.abandoned.fetch_add(1, Ordering::AcqRel);
+            }
+            CallState::Queued | CallState::Abandoned => {}
+        }
+        *state = CallState::Abandoned;
+        None
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_abandoned_calls_are_counted_and_capped() {
+        let calls = TimedCalls::default();
+        let call = calls.submit("get_entity", || Ok(1)).unwrap();
+        assert_eq!(call.wait(Duration::from_secs(5)).unwrap().unwrap(), 1);
+
+        // Hold every worker with a call that blocks until released
+        let (started, on_start) = mpsc::channel();
+        let (release, released) = mpsc::channel::<()>();
+        let released = Arc::new(Mutex::new(released));
+        let mut blocked = Vec::new();
+        for _ in 0..MAX_WORKERS {
+            let started = started.clone();
+            let released = Arc::clone(&released);
+            blocked.push(
+                calls
+                    .submit("find_network", move || {
+                        started.send(()).unwrap();
+                        let _ = lock(&released).recv();
+                        Ok(())
+                    })
+                    .unwrap(),
+            );
+        }
+        for _ in 0..MAX_WORKERS {
+            on_start.recv_timeout(Duration::from_secs(5)).unwrap();
+        }
+        for call in blocked {
+            assert!(call.wait(Duration::from_millis(1)).is_none());
+            assert!(call.abandon().is_none());
+        }
+        assert_eq!(calls.abandoned(), MAX_WORKERS);
+        let err = calls.submit("get_entity", || Ok(2)).unwrap_err();
+        assert!(matches!(err, SzError::Timeout(_)));
+
+        drop(release);
+        while calls.abandoned() > 0 {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        let call = calls.submit("get_entity", || Ok(3)).unwrap();
+        assert_eq!(call.wait(Duration::from_secs(5)).unwrap().unwrap(), 3);
+        assert!(lock(&calls.pool.state).workers <= MAX_WORKERS);
+    }
+
+    #[test]
+    fn test_queued_call_abandoned_is_not_run() {
+        let calls = TimedCalls::default();
+        let (release, released) = mpsc::channel::<()>();
+        let released = Arc::new(Mutex::new(released));
+        let mut blocked = Vec::new();
+        for _ in 0..MAX_WORKERS {
+            let released = Arc::clone(&released);
+            blocked.push(
+                calls
+                    .submit("find_network", move || {
+                        let _ = lock(&released).recv();
+                        Ok(())
+                    })
+                    .unwrap(),
+            );
+        }
+        let ran = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&ran);
+        let queued = calls
+            .submit("get_entity", move || {
+                counter.fetch_add(1, Ordering::SeqCst);
+                Ok(())
+            })
+            .unwrap();
+        assert!(queued.abandon().is_none());
+        drop(release);
+        for call in blocked {
+            call.wait(Duration::from_secs(5)).unwrap().unwrap();
+        }
+        while !lock(&calls.pool.state).queue.is_empty() {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(ran.load(Ordering::SeqCst), 0);
+        assert_eq!(calls.abandoned(), 0);
+    }
+}
@@ -0,0 +1,194 @@
+//! Encryption of locally persisted payloads.
+//!
+//! Sidecar files written next to a load, such as the audit log, can hold
+//! record data. A [`PayloadCipher`] encrypts them with AES-256-GCM so they are
+//! not stored in plaintext. The key comes from an environment variable or from
+//! a callback, e.g. one that asks a KMS to decrypt a data key:
+//!
+//! - [`PayloadCipher::from_env`] reads a hex-encoded 32-byte key, by default
+//!   from [`KEY_ENV_VAR`].
+//! - [`PayloadCipher::from_provider`] calls a key provider once.
+//!
+//! Sealed lines are `szenc1:` followed by the hex-encoded nonce, ciphertext
+//! and tag, so encrypted stores stay line-oriented. Every line has a fresh
+//! random nonce.
+//!
+//! # Example
+//!
+//! ```
+//! use sz_rust_sdk::encryption::PayloadCipher;
+//!
+//! let cipher = PayloadCipher::new(&[7u8; 32])?;
+//! let sealed = cipher.seal_line(r#"{"NAME_FULL": "Ann Lee"}"#)?;
+//! assert!(!sealed.contains("Ann Lee"));
+//! assert_eq!(cipher.open_line(&sealed)?, r#"{"NAME_FULL": "Ann Lee"}"#);
+//! # Ok::<(), sz_rust_sdk::SzError>(())
+//! ```
+
+use crate::error::{SzError, SzResult};
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Nonce};
+use std::fmt;
+
+/// Environment variable [`PayloadCipher::from_env`] reads by default.
+pub const KEY_ENV_VAR: &str = "SZ_PAYLOAD_KEY";
+
+/// Key length in bytes (AES-256).
+pub const KEY_LEN: usize = 32;
+
+/// Prefix of a sealed line; the digit is the format version.
+pub const LINE_PREFIX: &str = "szenc1:";
+
+const NONCE_LEN: usize = 12;
+
+/// AES-256-GCM cipher for sidecar payloads.
+#[derive(Clone)]
+pub struct PayloadCipher {
+    cipher: Aes256Gcm,
+}
+
+impl PayloadCipher {
+    /// Creates a cipher from a raw key.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the key is not [`KEY_LEN`] bytes long.
+    pub fn new(key: &[u8]) -> SzResult<Self> {
+        let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| {
+            SzError::bad_input(format!(
+                "Payload key must be {KEY_LEN} bytes, got {}",
+                key.len()
+            ))
+        })?;
+        Ok(Self { cipher })
+    }
+
+    /// Creates a cipher from a hex-encoded key in the environment variable
+    /// `var`, or [`KEY_ENV_VAR`] if `None`.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the variable is unset, not hex, or not a
+    /// [`KEY_LEN`]-byte key.
+    pub fn from_env(var: Option<&str>) -> SzResult<Self> {
+        let var = var.unwrap_or(KEY_ENV_VAR);
+        let encoded =
+            std::env::var(var).map_err(|_| SzError::bad_input(format!("{var} is not set")))?;
+        let key = hex::decode(encoded.trim())
+            .map_err(|e| SzError::bad_input(format!("{var} is not a hex key: {e}")))?;
+        Self::new(&key)
+    }
+
+    /// Creates a cipher from the key returned by `provider`, e.g. a data key
+    /// unwrapped by a KMS. The provider is called once.
+    ///
+    /// # Errors
+    ///
+    /// The provider's error, or `SzError::BadInput` for a key of the wrong
+    /// length.
+    pub fn from_provider(provider: impl FnOnce() -> SzResult<Vec<u8>>) -> SzResult<Self> {
+        Self::new(&provider()?)
+    }
+
+    /// Encrypts `plaintext`, returning the nonce followed by ciphertext and tag.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::Unknown` if encryption fails.
+    pub fn seal(&self, plaintext: &[u8]) -> SzResult<Vec<u8>> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext)
+            .map_err(|_| SzError::unknown("Payload encryption failed"))?;
+        let mut sealed = Vec::with_capacity(NONCE_LEN + ciphertext.len());
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+
+    /// Decrypts a payload produced by [`seal`](Self::seal).
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the payload is truncated, was sealed with a
+    /// different key, or was modified.
+    pub fn open(&self, sealed: &[u8]) -> SzResult<Vec<u8>> {
+        if sealed.len() < NONCE_LEN {
+            return Err(SzError::bad_input("Sealed payload is truncated"));
+        }
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+        self.cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| SzError::bad_input("Sealed payload failed authentication"))
+    }
+
+    /// Encrypts one line of text into a sealed line without line breaks.
+    ///
+    /// # Errors
+    ///
+    /// See [`seal`](Self::seal).
+    pub fn seal_line(&self, line: &str) -> SzResult<String> {
+        Ok(format!(
+            "{LINE_PREFIX}{}",
+            hex::encode(self.seal(line.as_bytes())?)
+        ))
+    }
+
+    /// Decrypts a line produced by [`seal_line`](Self::seal_line).
+    ///
+    /// Plaintext lines are rejected rather than passed through, so a store
+    /// cannot silently mix encrypted and unencrypted entries.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the line is not a sealed line or fails to
+    /// decrypt.
+    pub fn open_line(&self, line: &str) -> SzResult<String> {
+        let Some(encoded) = line.trim_end().strip_prefix(LINE_PREFIX) else {
+            return Err(SzError::bad_input("Line is not a sealed payload"));
+        };
+        let sealed = hex::decode(encoded)
+            .map_err(|e| SzError::bad_input(format!("Sealed line is not hex: {e}")))?;
+        String::from_utf8(self.open(&sealed)?)
+            .map_err(|_| SzError::bad_input("Sealed line is not UTF-8"))
+    }
+}
+
+impl fmt::Debug for PayloadCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PayloadCipher")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_and_open() {
+        let cipher = PayloadCipher::new(&[1u8; KEY_LEN]).unwrap();
+        let first = cipher.seal_line("record").unwrap();
+        let second = cipher.seal_line("record").unwrap();
+        assert_ne!(first, second);
+        assert_eq!(cipher.open_line(&first).unwrap(), "record");
+        assert_eq!(cipher.open_line(&format!("{second}\n")).unwrap(), "record");
+
+        let other = PayloadCipher::new(&[2u8; KEY_LEN]).unwrap();
+        assert!(other.open_line(&first).is_err());
+        assert!(cipher.open_line("record").is_err());
+        assert!(cipher.open(&[0u8; 4]).is_err());
+
+        let mut tampered = cipher.seal(b"record").unwrap();
+        *tampered.last_mut().unwrap() ^= 1;
+        assert!(cipher.open(&tampered).is_err());
+    }
+
+    #[test]
+    fn test_key_sources() {
+        assert!(PayloadCipher::new(&[0u8; 16]).is_err());
+        assert!(PayloadCipher::from_provider(|| Ok(vec![3u8; KEY_LEN])).is_ok());
+        assert!(PayloadCipher::from_provider(|| Err(SzError::unknown("kms unavailable"))).is_err());
+        assert!(PayloadCipher::from_env(Some("SZ_PAYLOAD_KEY_TEST_UNSET")).is_err());
+    }
+}
@@ -181,6 +181,7 @@
 //! }
 //! ```
 
+use crate::flags::SzFlags;
 use std::ffi::{CStr, NulError};
 
 /// Senzing SDK component for error reporting
@@ -243,6 +244,11 @@ pub enum ErrorCategory {
     Configuration,
     ReplaceConflict,
     EnvironmentDestroyed,
+    ResultTooLarge,
+    Timeout,
+    NotPermitted,
+    ExportInvalidated,
+    Cancelled,
     Unknown,
 }
 
@@ -780,6 +786,11 @@ pub trait SzErrorInspect {
     /// | `Configuration` | — | `Configuration` only |
     /// | `ReplaceConflict` | — | `ReplaceConflict` only |
     /// | `EnvironmentDestroyed` | — | `EnvironmentDestroyed` only |
+    /// | `ResultTooLarge` | — | `ResultTooLarge` only |
+    /// | `Timeout` | — | `Timeout` only |
+    /// | `NotPermitted` | — | `NotPermitted` only |
+    /// | `ExportInvalidated` | — | `ExportInvalidated` only |
+    /// | `Cancelled` | — | `Cancelled` only |
     /// | `Unknown` | — | `Unknown` only |
     ///
     /// # Examples
@@ -915,6 +926,52 @@ pub enum SzError {
     /// been destroyed.
     EnvironmentDestroyed(ErrorContext),
 
+    /// Response exceeded the environment's configured result size limit
+    ///
+    /// Raised by the SDK (not the native library) when a `get_entity` or
+    /// `find_network_*` response is larger than the limit set with
+    /// [`SzEnvironmentCore::set_result_size_limit`](crate::SzEnvironmentCore::set_result_size_limit).
+    /// `suggested_flags` is a smaller flag set that is likely to fit.
+    ResultTooLarge {
+        /// Error details, including the observed and permitted sizes
+        context: ErrorContext,
+        /// Flags that request a reduced response for the same operation
+        suggested_flags: SzFlags,
+    },
+
+    /// Engine call exceeded its configured timeout
+    ///
+    /// Raised by the SDK (not the native library) when an operation runs
+    /// longer than the timeout set with
+    /// [`SzEnvironmentCore::set_operation_timeout`](crate::SzEnvironmentCore::set_operation_timeout).
+    /// The native call cannot be interrupted; it is abandoned and keeps running
+    /// in the background until it completes.
+    Timeout(ErrorContext),
+
+    /// Operation is outside the capabilities of a scoped component
+    ///
+    /// Raised by the SDK (not the native library) when a method is called on
+    /// an engine obtained with
+    /// [`SzEnvironment::get_engine_scoped`](crate::SzEnvironment::get_engine_scoped)
+    /// whose [`EngineScope`](crate::EngineScope) does not allow it.
+    NotPermitted(ErrorContext),
+
+    /// Export handle was closed by the SDK
+    ///
+    /// Raised by the SDK (not the native library) when `fetch_next` is
+    /// called with an export handle that was open while the environment was
+    /// reinitialized or destroyed. The SDK closes open exports before either,
+    /// because the native library does not keep them valid. The message says
+    /// how to resume: start a new export and skip the entities already read.
+    ExportInvalidated(ErrorContext),
+
+    /// Operation was cancelled through an `SzCancellationToken`
+    ///
+    /// Raised by the SDK (not the native library) when the token handed to
+    /// an exporter or set on the environment is cancelled. Nothing is
+    /// retried: the caller asked for the work to stop.
+    Cancelled(ErrorContext),
+
     /// FFI-related errors
     Ffi(ErrorContext),
 
@@ -945,6 +1002,11 @@ impl std::fmt::Display for SzError {
             Self::Unhandled(ctx) => write!(f, "Unhandled error: {}", ctx),
             Self::UnknownDataSource(ctx) => write!(f, "Unknown data source: {}", ctx),
             Self::EnvironmentDestroyed(ctx) => write!(f, "Environment destroyed: {}", ctx),
+            Self::ResultTooLarge { context, .. } => write!(f, "Result too large: {}", context),
+            Self::Timeout(ctx) => write!(f, "Timeout: {}", ctx),
+            Self::NotPermitted(ctx) => write!(f, "Not permitted: {}", ctx),
+            Self::ExportInvalidated(ctx) => write!(f, "Export invalidated: {}", ctx),
+            Self::Cancelled(ctx) => write!(f, "Cancelled: {}", ctx),
             Self::Ffi(ctx) => write!(f, "FFI error: {}", ctx),
             Self::Json(e) => write!(f, "JSON error: {}", e),
             Self::StringConversion(e) => write!(f, "String conversion error: {}", e),
@@ -971,6 +1033,11 @@ impl std::error::Error for SzError {
             | Self::Unhandled(ctx)
             | Self::UnknownDataSource(ctx)
             | Self::EnvironmentDestroyed(ctx)
+            | Self::ResultTooLarge { context: ctx, .. }
+            | Self::Timeout(ctx)
+            | Self::NotPermitted(ctx)
+            | Self::ExportInvalidated(ctx)
+            | Self::Cancelled(ctx)
             | Self::Ffi(ctx) => ctx.source.as_ref().map(|e| &**e as &dyn std::error::Error),
             Self::Json(e) => Some(e),
             Self::StringConversion(e) => Some(e),
@@ -1086,6 +1153,62 @@ impl SzError {
         Self::EnvironmentDestroyed(ErrorContext::new(message))
     }
 
+    /// Creates a new ResultTooLarge error carrying the flags to retry with
+    pub fn result_too_large<S: Into<String>>(message: S, suggested_flags: SzFlags) -> Self {
+        Self::ResultTooLarge {
+            context: ErrorContext::new(message),
+            suggested_flags,
+        }
+    }
+
+    /// Creates a new Timeout error
+    pub fn timeout<S: Into<String>>(message: S) -> Self {
+        Self::Timeout(ErrorContext::new(message))
+    }
+
+    /// Creates a new NotPermitted error
+    pub fn not_permitted<S: Into<String>>(message: S) -> Self {
+        Self::NotPermitted(ErrorContext::new(message))
+    }
+
+    /// Creates a new ExportInvalidated error
+    pub fn export_invalidated<S: Into<String>>(message: S) -> Self {
+        Self::ExportInvalidated(ErrorContext::new(message))
+    }
+
+    /// Creates a new Cancelled error
+    pub fn cancelled<S: Into<String>>(message: S) -> Self {
+        Self::Cancelled(ErrorContext::new(message))
+    }
+
+    /// Returns the reduced flag set suggested by a [`SzError::ResultTooLarge`] error.
+    ///
+    /// Returns `None` for every other error type.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # fn example(engine: &dyn SzEngine) -> SzResult<()> {
+    /// let entity = match engine.get_entity(EntityRef::Id(EntityId(1)), None) {
+    ///     Err(e) if e.suggested_flags().is_some() => {
+    ///         engine.get_entity(EntityRef::Id(EntityId(1)), e.suggested_flags())?
+    ///     }
+    ///     other => other?,
+    /// };
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn suggested_flags(&self) -> Option<SzFlags> {
+        match self {
+            Self::ResultTooLarge {
+                suggested_flags, ..
+            } => Some(*suggested_flags),
+            _ => None,
+        }
+    }
+
     // ========================================================================
     // Error Chain Inspection - Static Methods
     // ========================================================================
@@ -1178,6 +1301,11 @@ impl SzError {
             | Self::Unhandled(ctx)
             | Self::UnknownDataSource(ctx)
             | Self::EnvironmentDestroyed(ctx)
+            | Self::ResultTooLarge { context: ctx, .. }
+            | Self::Timeout(ctx)
+            | Self::NotPermitted(ctx)
+            | Self::ExportInvalidated(ctx)
+            | Self::Cancelled(ctx)
             | Self::Ffi(ctx) => ctx.code,
             Self::Json(_) | Self::StringConversion(_) => None,
         }
@@ -1220,6 +1348,11 @@ impl SzError {
             | Self::Unhandled(ctx)
             | Self::UnknownDataSource(ctx)
             | Self::EnvironmentDestroyed(ctx)
+            | Self::ResultTooLarge { context: ctx, .. }
+            | Self::Timeout(ctx)
+            | Self::NotPermitted(ctx)
+            | Self::ExportInvalidated(ctx)
+            | Self::Cancelled(ctx)
             | Self::Ffi(ctx) => ctx.component,
             Self::Json(_) | Self::StringConversion(_) => None,
         }
@@ -1260,6 +1393,11 @@ impl SzError {
             | Self::Unhandled(ctx)
             | Self::UnknownDataSource(ctx)
             | Self::EnvironmentDestroyed(ctx)
+            | Self::ResultTooLarge { context: ctx, .. }
+            | Self::Timeout(ctx)
+            | Self::NotPermitted(ctx)
+            | Self::ExportInvalidated(ctx)
+            | Self::Cancelled(ctx)
             | Self::Ffi(ctx) => &ctx.message,
             Self::Json(_) => "JSON error",
             Self::StringConversion(_) => "String conversion error",
@@ -1485,6 +1623,11 @@ impl SzError {
             Self::Configuration(_) => vec![ErrorCategory::Configuration],
             Self::ReplaceConflict(_) => vec![ErrorCategory::ReplaceConflict],
             Self::EnvironmentDestroyed(_) => vec![ErrorCategory::EnvironmentDestroyed],
+            Self::ResultTooLarge { .. } => vec![ErrorCategory::ResultTooLarge],
+            Self::Timeout(_) => vec![ErrorCategory::Timeout],
+            Self::NotPermitted(_) => vec![ErrorCategory::NotPermitted],
+            Self::ExportInvalidated(_) => vec![ErrorCategory::ExportInvalidated],
+            Self::Cancelled(_) => vec![ErrorCategory::Cancelled],
             Self::Unknown(_) => vec![ErrorCategory::Unknown],
 
             // FFI errors (no hierarchy)
@@ -1550,6 +1693,11 @@ impl SzError {
             Self::Unrecoverable(_) | Self::Unhandled(_) => "unrecoverable",
             Self::ReplaceConflict(_) => "replace_conflict",
             Self::EnvironmentDestroyed(_) => "environment_destroyed",
+            Self::ResultTooLarge { .. } => "result_too_large",
+            Self::Timeout(_) => "timeout",
+            Self::NotPermitted(_) => "not_permitted",
+            Self::ExportInvalidated(_) => "export_invalidated",
+            Self::Cancelled(_) => "cancelled",
             Self::Unknown(_) => "unknown",
             Self::Ffi(_) => "ffi",
             Self::Json(_) => "json",
@@ -1753,6 +1901,11 @@ impl SzError {
             | Self::Unhandled(ctx)
             | Self::UnknownDataSource(ctx)
             | Self::EnvironmentDestroyed(ctx)
+            | Self::ResultTooLarge { context: ctx, .. }
+            | Self::Timeout(ctx)
+            | Self::NotPermitted(ctx)
+            | Self::ExportInvalidated(ctx)
+            | Self::Cancelled(ctx)
             | Self::Ffi(ctx) => {
                 ctx.source = Some(Box::new(source));
             }
@@ -1763,6 +1916,43 @@ impl SzError {
     }
 }
 
+// ========================================================================
+// Default Config Replacement
+// ========================================================================
+
+/// The default configuration was not replaced because it no longer had the
+/// expected ID.
+///
+/// Returned by
+/// [`SzConfigManager::compare_and_set_default_config_id`](crate::traits::SzConfigManager::compare_and_set_default_config_id)
+/// when another process changed the default first. `actual` is the default
+/// at the time of the failure, to retry against after re-checking it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConfigReplacedError {
+    /// Default configuration ID the caller expected
+    pub expected: crate::types::ConfigId,
+    /// Default configuration ID found instead
+    pub actual: crate::types::ConfigId,
+}
+
+impl std::fmt::Display for ConfigReplacedError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Default configuration was replaced: expected {}, found {}",
+            self.expected, self.actual
+        )
+    }
+}
+
+impl std::error::Error for ConfigReplacedError {}
+
+impl From<ConfigReplacedError> for SzError {
+    fn from(err: ConfigReplacedError) -> Self {
+        Self::replace_conflict(err.to_string()).with_source(err)
+    }
+}
+
 // ========================================================================
 // Tests
 // ========================================================================
@@ -2161,6 +2351,56 @@ mod test_error_mapping {
         assert_eq!(hierarchy[1], ErrorCategory::Unrecoverable);
     }
 
+    #[test]
+    fn test_result_too_large_carries_suggested_flags() {
+        let err = SzError::result_too_large("too big", SzFlags::ENTITY_BRIEF_DEFAULT_FLAGS);
+        assert_eq!(
+            err.suggested_flags(),
+            Some(SzFlags::ENTITY_BRIEF_DEFAULT_FLAGS)
+        );
+        assert_eq!(err.hierarchy(), vec![ErrorCategory::ResultTooLarge]);
+        assert_eq!(err.category(), "result_too_large");
+        assert_eq!(err.message(), "too big");
+        assert!(!err.is_bad_input());
+        assert_eq!(SzError::not_found("x").suggested_flags(), None);
+    }
+
+    #[test]
+    fn test_timeout_is_standalone() {
+        let err = SzError::timeout("find_network_by_entity_id exceeded 5s");
+        assert_eq!(err.hierarchy(), vec![ErrorCategory::Timeout]);
+        assert_eq!(err.category(), "timeout");
+        assert!(!err.is_retryable());
+        assert!(err.to_string().starts_with("Timeout: "));
+    }
+
+    #[test]
+    fn test_not_permitted_is_standalone() {
+        let err = SzError::not_permitted("add_record is not allowed for a ReadOnly engine");
+        assert_eq!(err.hierarchy(), vec![ErrorCategory::NotPermitted]);
+        assert_eq!(err.category(), "not_permitted");
+        assert!(!err.is_retryable());
+        assert!(err.to_string().starts_with("Not permitted: "));
+    }
+
+    #[test]
+    fn test_export_invalidated_is_standalone() {
+        let err = SzError::export_invalidated("export handle 7 was closed by reinitialize");
+        assert_eq!(err.hierarchy(), vec![ErrorCategory::ExportInvalidated]);
+        assert_eq!(err.category(), "export_invalidated");
+        assert!(!err.is_retryable());
+        assert!(err.to_string().starts_with("Export invalidated: "));
+    }
+
+    #[test]
+    fn test_cancelled_is_standalone() {
+        let err = SzError::cancelled("redo processing was cancelled");
+        assert_eq!(err.hierarchy(), vec![ErrorCategory::Cancelled]);
+        assert_eq!(err.category(), "cancelled");
+        assert!(!err.is_retryable());
+        assert!(err.to_string().starts_with("Cancelled: "));
+    }
+
     #[test]
     fn test_hierarchy_configuration() {
         let err = SzError::configuration("Invalid config");
@@ -2533,6 +2773,17 @@ mod test_sz_error_inspect {
                 SzError::environment_destroyed("x"),
                 ErrorCategory::EnvironmentDestroyed,
             ),
+            (
+                SzError::result_too_large("x", SzFlags::ENTITY_BRIEF_DEFAULT_FLAGS),
+                ErrorCategory::ResultTooLarge,
+            ),
+            (SzError::timeout("x"), ErrorCategory::Timeout),
+            (SzError::not_permitted("x"), ErrorCategory::NotPermitted),
+            (
+                SzError::export_invalidated("x"),
+                ErrorCategory::ExportInvalidated,
+            ),
+            (SzError::cancelled("x"), ErrorCategory::Cancelled),
             (SzError::unknown("x"), ErrorCategory::Unknown),
         ];
 
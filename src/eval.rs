@@ -0,0 +1,415 @@
+//! Resolution quality measured against a ground truth.
+//!
+//! A [`TruthSet`] labels pairs of records as describing the same entity or
+//! not. [`evaluate`] looks up the entity each labelled record resolved to
+//! and counts, per pair, whether the engine agreed with the label, giving a
+//! [`ConfusionMatrix`] with precision, recall and F1. [`load_and_evaluate`]
+//! loads line-delimited records first, so a configuration change can be
+//! scored on a fresh repository in one call.
+//!
+//! Labels usually come from a file of pairs reviewed by analysts
+//! ([`TruthSet::from_jsonl`]) or from records grouped by a known entity key
+//! ([`TruthSet::from_clusters`], [`TruthSet::from_dataset`]), in which case
+//! every pair of records is labelled.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::eval::{self, TruthSet};
+//! use sz_rust_sdk::prelude::*;
+//! use sz_rust_sdk::testing::datasets::DatasetSpec;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_eval")?;
+//! let engine = env.get_engine()?;
+//! let dataset = DatasetSpec::new(7).with_people(200).with_duplicate_rate(0.3).generate()?;
+//! let truth = TruthSet::from_dataset(&dataset)?;
+//! let report = eval::load_and_evaluate(&*engine, dataset.to_jsonl().as_bytes(), &truth)?;
+//! println!("{report}");
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::error::{SzError, SzResult};
+use crate::flags::SzFlags;
+use crate::ingest;
+use crate::models::ExportedEntity;
+use crate::testing::datasets::Dataset;
+use crate::traits::SzEngine;
+use crate::types::{DataSourceCode, EntityId, EntityRef, RecordKey};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::hash::Hash;
+use std::io::BufRead;
+
+/// Whether two records describe the same entity.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PairLabel {
+    /// First record of the pair
+    pub record_1: RecordKey,
+    /// Second record of the pair
+    pub record_2: RecordKey,
+    /// Whether the records should resolve to the same entity
+    pub same_entity: bool,
+}
+
+/// Labelled record pairs to evaluate resolution against.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TruthSet {
+    labels: Vec<PairLabel>,
+}
+
+impl TruthSet {
+    /// Creates a truth set with no labels.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Labels a pair of records.
+    pub fn add(&mut self, record_1: RecordKey, record_2: RecordKey, same_entity: bool) {
+        self.labels.push(PairLabel {
+            record_1,
+            record_2,
+            same_entity,
+        });
+    }
+
+    /// Labels every pair of `records`: the same entity when their keys are
+    /// equal, different entities otherwise.
+    ///
+    /// The number of labels grows with the square of the number of records,
+    /// so this suits test sets of up to a few thousand records.
+    pub fn from_clusters<K: Eq + Hash>(records: impl IntoIterator<Item = (RecordKey, K)>) -> Self {
+        let records: Vec<(RecordKey, K)> = records.into_iter().collect();
+        let mut truth = Self::new();
+        for (i, (record_1, key_1)) in records.iter().enumerate() {
+            for (record_2, key_2) in &records[i + 1..] {
+                truth.add(record_1.clone(), record_2.clone(), key_1 == key_2);
+            }
+        }
+        truth
+    }
+
+    /// Labels every pair of a synthetic dataset by its ground-truth entity
+    /// keys, as in [`from_clusters`](Self::from_clusters).
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if a record has an empty data source or record ID.
+    pub fn from_dataset(dataset: &Dataset) -> SzResult<Self> {
+        let records = dataset
+            .records()
+            .iter()
+            .map(|record| {
+                Ok((
+                    RecordKey::new(&record.data_source, record.record_id.as_str())?,
+                    record.entity_key,
+                ))
+            })
+            .collect::<SzResult<Vec<_>>>()?;
+        Ok(Self::from_clusters(records))
+    }
+
+    /// Reads one [`PairLabel`] per line, e.g.
+    /// `{"record_1":{"DATA_SOURCE":"CRM","RECORD_ID":"1"},"record_2":{"DATA_SOURCE":"WEB","RECORD_ID":"9"},"same_entity":true}`.
+    /// Blank lines are skipped.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if a line cannot be read or is not a label; the
+    /// message names the 1-based line number.
+    pub fn from_jsonl<R: BufRead>(reader: R) -> SzResult<Self> {
+        let mut truth = Self::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line_number = index + 1;
+            let line = line.map_err(|e| {
+                SzError::bad_input(format!("Failed reading line {line_number}: {e}"))
+            })?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let label = serde_json::from_str(&line)
+                .map_err(|e| SzError::bad_input(format!("Line {line_number}: {e}")))?;
+            truth.labels.push(label);
+        }
+        Ok(truth)
+    }
+
+    /// The labels, in the order they were added.
+    pub fn labels(&self) -> &[PairLabel] {
+        &self.labels
+    }
+
+    /// Number of labelled pairs.
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    /// Returns whether no pair is labelled.
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// The records that appear in any label.
+    pub fn records(&self) -> BTreeSet<&RecordKey> {
+        self.labels
+            .iter()
+            .flat_map(|label| [&label.record_1, &label.record_2])
+            .collect()
+    }
+}
+
+/// Counts of labelled pairs by label and by what the engine did.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ConfusionMatrix {
+    /// Same entity, resolved together
+    pub true_positives: u64,
+    /// Different entities, resolved together
+    pub false_positives: u64,
+    /// Same entity, resolved apart
+    pub false_negatives: u64,
+    /// Different entities, resolved apart
+    pub true_negatives: u64,
+}
+
+impl ConfusionMatrix {
+    fn count(&mut self, same_entity: bool, resolved_together: bool) {
+        match (same_entity, resolved_together) {
+            (true, true) => self.true_positives += 1,
+            (false, true) => self.false_positives += 1,
+            (true, false) => self.false_negatives += 1,
+            (false, false) => self.true_negatives += 1,
+        }
+    }
+
+    /// Number of pairs counted.
+    pub fn total(&self) -> u64 {
+        self.true_positives + self.false_positives + self.false_negatives + self.true_negatives
+    }
+
+    /// Share of pairs resolved together that are the same entity; `None`
+    /// if no pair was resolved together.
+    pub fn precision(&self) -> Option<f64> {
+        ratio(
+            self.true_positives,
+            self.true_positives + self.false_positives,
+        )
+    }
+
+    /// Share of same-entity pairs that were resolved together; `None` if no
+    /// pair is labelled the same entity.
+    pub fn recall(&self) -> Option<f64> {
+        ratio(
+            self.true_positives,
+            self.true_positives + self.false_negatives,
+        )
+    }
+
+    /// Harmonic mean of precision and recall; `None` if either is
+    /// undefined or both are zero.
+    pub fn f1(&self) -> Option<f64> {
+        let (precision, recall) = (self.precision()?, self.recall()?);
+        (precision + recall > 0.0).then(|| 2.0 * precision * recall / (precision + recall))
+    }
+}
+
+fn ratio(numerator: u64, denominator: u64) -> Option<f64> {
+    (denominator > 0).then(|| numerator as f64 / denominator as f64)
+}
+
+/// Outcome of [`evaluate`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EvalReport {
+    /// Counts over every labelled pair
+    pub overall: ConfusionMatrix,
+    /// Counts per pair of data sources, ordered alphabetically within each
+    /// pair
+    pub by_data_sources: BTreeMap<(DataSourceCode, DataSourceCode), ConfusionMatrix>,
+    /// Distinct labelled records
+    pub records: usize,
+    /// Distinct entities the labelled records resolved to
+    pub entities: usize,
+}
+
+/// Looks up the entity of every labelled record and scores each label.
+///
+/// # Errors
+///
+/// * `SzError::NotFound` - A labelled record is not in the repository
+/// * Any error from `SzEngine::get_entity`
+pub fn evaluate(engine: &dyn SzEngine, truth: &TruthSet) -> SzResult<EvalReport> {
+    let mut entities = HashMap::new();
+    for record in truth.records() {
+        let entity: ExportedEntity = serde_json::from_str(&engine.get_entity(
+            EntityRef::from_record(record.data_source.as_ref(), &record.record_id),
+            Some(SzFlags::empty()),
+        )?)?;
+        entities.insert(record, entity.resolved_entity.entity_id);
+    }
+    Ok(score(truth, &entities))
+}
+
+/// Loads line-delimited JSON records as [`ingest::load_from_reader`] does,
+/// then [`evaluate`]s the result.
+///
+/// # Errors
+///
+/// Same as [`ingest::load_from_reader`] and [`evaluate`].
+pub fn load_and_evaluate<R: BufRead>(
+    engine: &dyn SzEngine,
+    reader: R,
+    truth: &TruthSet,
+) -> SzResult<EvalReport> {
+    ingest::load_from_reader(engine, reader)?;
+    evaluate(engine, truth)
+}
+
+/// Scores `truth` against the entity each record resolved to.
+fn score(truth: &TruthSet, entities: &HashMap<&RecordKey, EntityId>) -> EvalReport {
+    let mut report = EvalReport {
+        records: entities.len(),
+        entities: entities.values().collect::<BTreeSet<_>>().len(),
+        ..EvalReport::default()
+    };
+    for label in &truth.labels {
+        let resolved_together = matches!(
+            (entities.get(&label.record_1), entities.get(&label.record_2)),
+            (Some(a), Some(b)) if a == b
+        );
+        report.overall.count(label.same_entity, resolved_together);
+        let (a, b) = (&label.record_1.data_source, &label.record_2.data_source);
+        let sources = if a <= b {
+            (a.clone(), b.clone())
+        } else {
+            (b.clone(), a.clone())
+        };
+        report
+            .by_data_sources
+            .entry(sources)
+            .or_default()
+            .count(label.same_entity, resolved_together);
+    }
+    report
+}
+
+impl fmt::Display for ConfusionMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let metric = |value: Option<f64>| value.map_or("n/a".to_string(), |v| format!("{v:.3}"));
+        write!(
+            f,
+            "precision {}, recall {}, F1 {} (TP {}, FP {}, FN {}, TN {})",
+            metric(self.precision()),
+            metric(self.recall()),
+            metric(self.f1()),
+            self.true_positives,
+            self.false_positives,
+            self.false_negatives,
+            self.true_negatives
+        )
+    }
+}
+
+impl fmt::Display for EvalReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} pairs over {} records in {} entities",
+            self.overall.total(),
+            self.records,
+            self.entities
+        )?;
+        writeln!(f, "overall: {}", self.overall)?;
+        if self.by_data_sources.len() > 1 {
+            for ((a, b), matrix) in &self.by_data_sources {
+                writeln!(f, "{a} / {b}: {matrix}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(data_source: &str, record_id: &str) -> RecordKey {
+        RecordKey::new(data_source, record_id).unwrap()
+    }
+
+    #[test]
+    fn test_score_labelled_pairs() {
+        let truth = TruthSet::from_clusters([
+            (key("CRM", "1"), 'a'),
+            (key("CRM", "2"), 'a'),
+            (key("WEB", "3"), 'a'),
+            (key("WEB", "4"), 'b'),
+        ]);
+        assert_eq!(truth.len(), 6);
+
+        let (crm_1, crm_2, web_3, web_4) = (
+            key("CRM", "1"),
+            key("CRM", "2"),
+            key("WEB", "3"),
+            key("WEB", "4"),
+        );
+        // CRM 1 and 2 resolved, WEB 3 missed, WEB 4 wrongly merged
+        let entities = HashMap::from([
+            (&crm_1, EntityId(10)),
+            (&crm_2, EntityId(10)),
+            (&web_3, EntityId(11)),
+            (&web_4, EntityId(10)),
+        ]);
+        let report = score(&truth, &entities);
+        assert_eq!(report.records, 4);
+        assert_eq!(report.entities, 2);
+        assert_eq!(
+            report.overall,
+            ConfusionMatrix {
+                true_positives: 1,
+                false_positives: 2,
+                false_negatives: 2,
+                true_negatives: 1,
+            }
+        );
+        assert_eq!(report.overall.precision(), Some(1.0 / 3.0));
+        assert_eq!(report.overall.recall(), Some(1.0 / 3.0));
+        assert!((report.overall.f1().unwrap() - 1.0 / 3.0).abs() < 1e-9);
+
+        let crm_web = &report.by_data_sources[&(
+            DataSourceCode::new("CRM").unwrap(),
+            DataSourceCode::new("WEB").unwrap(),
+        )];
+        assert_eq!(crm_web.total(), 4);
+        assert!(
+            report
+                .to_string()
+                .contains("CRM / WEB: precision 0.000, recall 0.000, F1 n/a")
+        );
+    }
+
+    #[test]
+    fn test_undefined_metrics() {
+        let matrix = ConfusionMatrix {
+            true_negatives: 3,
+            ..ConfusionMatrix::default()
+        };
+        assert_eq!(matrix.precision(), None);
+        assert_eq!(matrix.f1(), None);
+        assert!(matrix.to_string().starts_with("precision n/a"));
+    }
+
+    #[test]
+    fn test_truth_set_from_jsonl() {
+        let data = concat!(
+            r#"{"record_1":{"DATA_SOURCE":"CRM","RECORD_ID":"1"},"record_2":{"DATA_SOURCE":"WEB","RECORD_ID":"9"},"same_entity":true}"#,
+            "\n\n",
+            r#"{"record_1":{"DATA_SOURCE":"CRM","RECORD_ID":"1"}}"#,
+            "\n",
+        );
+        let err = TruthSet::from_jsonl(data.as_bytes()).unwrap_err();
+        assert!(err.message().starts_with("Line 3:"));
+        let truth = TruthSet::from_jsonl(data.lines().next().unwrap().as_bytes()).unwrap();
+        assert_eq!(truth.records().len(), 2);
+        assert!(truth.labels()[0].same_entity);
+    }
+}
@@ -0,0 +1,382 @@
+//! Entity change events inferred from `WITH_INFO` responses.
+//!
+//! A `WITH_INFO` response lists the entities a record operation touched,
+//! not what happened to them. Systems that mirror entities elsewhere need
+//! to know when two entities became one, or one became several, so they can
+//! retire or re-key their copies. An [`EntityChangeStream`] looks up the
+//! records of each affected entity and compares them with the membership it
+//! learned from earlier responses, turning each response into
+//! [`EntityEvent`]s:
+//!
+//! - [`EntityMerged`](EntityEvent::EntityMerged): entities that no longer
+//!   exist and whose records all moved into the survivor.
+//! - [`EntitySplit`](EntityEvent::EntitySplit): an entity whose records
+//!   moved into other entities, whether or not it still exists.
+//! - [`EntityUpdated`](EntityEvent::EntityUpdated) and
+//!   [`EntityRemoved`](EntityEvent::EntityRemoved) for the other affected
+//!   entities.
+//!
+//! Merges and splits can only be seen for entities whose records the stream
+//! already knows, from an earlier response or from
+//! [`track`](EntityChangeStream::track). Responses must be processed in the
+//! order the operations ran; the stream keeps one entry per record seen.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::events::{EntityChangeStream, EntityEvent};
+//! use sz_rust_sdk::prelude::*;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_entity_events")?;
+//! let engine = env.get_engine()?;
+//! let mut changes = EntityChangeStream::new(&*engine);
+//! for (id, name) in [("1", "Ann Lee"), ("2", "Bob Ray"), ("3", "Ann Lee")] {
+//!     let definition = format!(r#"{{"NAME_FULL": "{name}", "PHONE_NUMBER": "555-0100"}}"#);
+//!     let info = engine.add_record("TEST", id, &definition, Some(SzFlags::WITH_INFO))?;
+//!     for event in changes.process(&info)? {
+//!         if let EntityEvent::EntityMerged { survivor, absorbed } = &event {
+//!             println!("{absorbed:?} merged into {survivor}");
+//!         }
+//!     }
+//! }
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::error::{SzError, SzResult};
+use crate::models::{ExportedEntity, WithInfoResult};
+use crate::traits::SzEngine;
+use crate::types::{EntityId, RecordKey};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fmt;
+
+/// A change to an entity, inferred by [`EntityChangeStream`].
+///
+/// Serializes as an object tagged with the event name, e.g.
+/// `{"event":"entity_merged","survivor":1,"absorbed":[2]}`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum EntityEvent {
+    /// Entities that no longer exist had all their records moved into
+    /// `survivor`
+    EntityMerged {
+        /// The entity that holds the records now
+        survivor: EntityId,
+        /// The entities that were merged away, in ID order
+        absorbed: Vec<EntityId>,
+    },
+    /// Records of `origin` moved into other entities
+    EntitySplit {
+        /// The entity the records came from; it may no longer exist
+        origin: EntityId,
+        /// The entities holding the moved records, in ID order
+        new_entities: Vec<EntityId>,
+    },
+    /// An affected entity that still exists and took part in no merge or
+    /// split
+    EntityUpdated {
+        /// The entity
+        entity_id: EntityId,
+    },
+    /// An affected entity that no longer exists and whose records did not
+    /// move into another affected entity, e.g. after its last record was
+    /// deleted
+    EntityRemoved {
+        /// The entity
+        entity_id: EntityId,
+    },
+}
+
+impl EntityEvent {
+    /// The event name used as the serialized tag, e.g. `entity_merged`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::EntityMerged { .. } => "entity_merged",
+            Self::EntitySplit { .. } => "entity_split",
+            Self::EntityUpdated { .. } => "entity_updated",
+            Self::EntityRemoved { .. } => "entity_removed",
+        }
+    }
+
+    /// The entities the event involves, in event order.
+    pub fn entity_ids(&self) -> Vec<EntityId> {
+        match self {
+            Self::EntityMerged { survivor, absorbed } => std::iter::once(*survivor)
+                .chain(absorbed.iter().copied())
+                .collect(),
+            Self::EntitySplit {
+                origin,
+                new_entities,
+            } => std::iter::once(*origin)
+                .chain(new_entities.iter().copied())
+                .collect(),
+            Self::EntityUpdated { entity_id } | Self::EntityRemoved { entity_id } => {
+                vec![*entity_id]
+            }
+        }
+    }
+}
+
+impl fmt::Display for EntityEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let list = |ids: &[EntityId]| {
+            ids.iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            Self::EntityMerged { survivor, absorbed } => {
+                write!(
+                    f,
+                    "entities {} merged into entity {survivor}",
+                    list(absorbed)
+                )
+            }
+            Self::EntitySplit {
+                origin,
+                new_entities,
+            } => write!(
+                f,
+                "entity {origin} split into entities {}",
+                list(new_entities)
+            ),
+            Self::EntityUpdated { entity_id } => write!(f, "entity {entity_id} updated"),
+            Self::EntityRemoved { entity_id } => write!(f, "entity {entity_id} removed"),
+        }
+    }
+}
+
+/// Turns consecutive `WITH_INFO` responses into [`EntityEvent`]s; see the
+/// [module documentation](self).
+pub struct EntityChangeStream<'a> {
+    engine: &'a dyn SzEngine,
+    membership: Membership,
+}
+
+/// Which entity each known record belongs to.
+#[derive(Debug, Default)]
+struct Membership {
+    owners: HashMap<RecordKey, EntityId>,
+    members: HashMap<EntityId, HashSet<RecordKey>>,
+}
+
+impl<'a> EntityChangeStream<'a> {
+    /// Creates a stream that knows no entities yet.
+    pub fn new(engine: &'a dyn SzEngine) -> Self {
+        Self {
+            engine,
+            membership: Membership::default(),
+        }
+    }
+
+    /// Records the current members of `entity_id`, so a merge or split in
+    /// the next response that affects it is recognized.
+    ///
+    /// # Errors
+    ///
+    /// Any error from `SzEngine::get_entity_record_keys`.
+    pub fn track(&mut self, entity_id: EntityId) -> SzResult<()> {
+        let records = self.engine.get_entity_record_keys(entity_id)?;
+        self.membership.remember(entity_id, records);
+        Ok(())
+    }
+
+    /// Records the members of an entity read elsewhere, such as from an
+    /// export, without looking it up.
+    pub fn observe(&mut self, entity: &ExportedEntity) {
+        let records = entity
+            .resolved_entity
+            .records
+            .iter()
+            .filter_map(|record| {
+                RecordKey::new(&record.data_source, record.record_id.as_str()).ok()
+            })
+            .collect();
+        self.membership
+            .remember(entity.resolved_entity.entity_id, records);
+    }
+
+    /// Number of records whose entity the stream knows.
+    pub fn known_records(&self) -> usize {
+        self.membership.owners.len()
+    }
+
+    /// Infers the events of one `WITH_INFO` response. An empty response
+    /// gives no events.
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::Json` - The response is not valid JSON
+    /// * Any error from `SzEngine::get_entity_record_keys` other than
+    ///   `SzError::NotFound`
+    pub fn process(&mut self, info: &str) -> SzResult<Vec<EntityEvent>> {
+        let affected = WithInfoResult::from_json(info)?.affected_entity_ids();
+        let mut current = BTreeMap::new();
+        for entity_id in &affected {
+            let records = match self.engine.get_entity_record_keys(*entity_id) {
+                Ok(records) => Some(records),
+                Err(SzError::NotFound(_)) => None,
+                Err(e) => return Err(e),
+            };
+            current.insert(*entity_id, records);
+        }
+        Ok(self.membership.apply(&affected, current))
+    }
+}
+
+impl Membership {
+    /// Compares the affected entities' records with the known membership,
+    /// then replaces it.
+    fn apply(
+        &mut self,
+        affected: &[EntityId],
+        current: BTreeMap<EntityId, Option<HashSet<RecordKey>>>,
+    ) -> Vec<EntityEvent> {
+        // Where the records of each previously known entity are now
+        let mut destinations: BTreeMap<EntityId, BTreeSet<EntityId>> = BTreeMap::new();
+        for (entity_id, records) in &current {
+            for record in records.iter().flatten() {
+                if let Some(owner) = self.owners.get(record) {
+                    destinations.entry(*owner).or_default().insert(*entity_id);
+                }
+            }
+        }
+        let exists = |entity_id: &EntityId| current.get(entity_id).is_none_or(Option::is_some);
+
+        let mut merges: BTreeMap<EntityId, Vec<EntityId>> = BTreeMap::new();
+        let mut events = Vec::new();
+        let mut involved = HashSet::new();
+        for (origin, targets) in &destinations {
+            let moved: Vec<EntityId> = targets
+                .iter()
+                .copied()
+                .filter(|target| target != origin)
+                .collect();
+            if moved.is_empty() {
+                continue;
+            }
+            if !exists(origin) && moved.len() == 1 {
+                merges.entry(moved[0]).or_default().push(*origin);
+            } else {
+                events.push(EntityEvent::EntitySplit {
+                    origin: *origin,
+                    new_entities: moved.clone(),
+                });
+            }
+            involved.insert(*origin);
+            involved.extend(moved);
+        }
+        events.splice(
+            0..0,
+            merges
+                .into_iter()
+                .map(|(survivor, absorbed)| EntityEvent::EntityMerged { survivor, absorbed }),
+        );
+        for entity_id in affected {
+            if !involved.insert(*entity_id) {
+                continue;
+            }
+            events.push(if exists(entity_id) {
+                EntityEvent::EntityUpdated {
+                    entity_id: *entity_id,
+                }
+            } else {
+                EntityEvent::EntityRemoved {
+                    entity_id: *entity_id,
+                }
+            });
+        }
+
+        for (entity_id, records) in current {
+            match records {
+                Some(records) => self.remember(entity_id, records),
+                None => self.forget(entity_id),
+            }
+        }
+        events
+    }
+
+    fn remember(&mut self, entity_id: EntityId, records: HashSet<RecordKey>) {
+        self.forget(entity_id);
+        for record in &records {
+            if let Some(previous) = self.owners.insert(record.clone(), entity_id)
+                && previous != entity_id
+                && let Some(members) = self.members.get_mut(&previous)
+            {
+                members.remove(record);
+            }
+        }
+        self.members.insert(entity_id, records);
+    }
+
+    /// Drops an entity and the records still attributed to it.
+    fn forget(&mut self, entity_id: EntityId) {
+        for record in self.members.remove(&entity_id).into_iter().flatten() {
+            if self.owners.get(&record) == Some(&entity_id) {
+                self.owners.remove(&record);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(record_id: &str) -> RecordKey {
+        RecordKey::new("TEST", record_id).unwrap()
+    }
+
+    fn records(ids: &[&str]) -> Option<HashSet<RecordKey>> {
+        Some(ids.iter().map(|id| key(id)).collect())
+    }
+
+    #[test]
+    fn test_merge_and_split_are_inferred() {
+        let mut changes = Membership::default();
+        let (e1, e2, e3) = (EntityId(1), EntityId(2), EntityId(3));
+        let events = changes.apply(&[e1], BTreeMap::from([(e1, records(&["A"]))]));
+        assert_eq!(events, [EntityEvent::EntityUpdated { entity_id: e1 }]);
+        changes.apply(&[e2], BTreeMap::from([(e2, records(&["B"]))]));
+        assert_eq!(changes.owners.len(), 2);
+
+        // C matches both: entity 2 is merged into entity 1
+        let events = changes.apply(
+            &[e1, e2],
+            BTreeMap::from([(e1, records(&["A", "B", "C"])), (e2, None)]),
+        );
+        assert_eq!(
+            events,
+            [EntityEvent::EntityMerged {
+                survivor: e1,
+                absorbed: vec![e2]
+            }]
+        );
+        assert_eq!(events[0].to_string(), "entities 2 merged into entity 1");
+        assert_eq!(
+            serde_json::to_string(&events[0]).unwrap(),
+            r#"{"event":"entity_merged","survivor":1,"absorbed":[2]}"#
+        );
+
+        // B changes and splits off into a new entity
+        let events = changes.apply(
+            &[e1, e3],
+            BTreeMap::from([(e1, records(&["A", "C"])), (e3, records(&["B"]))]),
+        );
+        assert_eq!(
+            events,
+            [EntityEvent::EntitySplit {
+                origin: e1,
+                new_entities: vec![e3]
+            }]
+        );
+
+        // Deleting B's record removes its entity
+        let events = changes.apply(&[e3], BTreeMap::from([(e3, None)]));
+        assert_eq!(events, [EntityEvent::EntityRemoved { entity_id: e3 }]);
+        assert_eq!(changes.owners.len(), 2);
+    }
+}
@@ -0,0 +1,955 @@
+//! Streaming entity export.
+//!
+//! [`SzExporter`] wraps the `export_json_entity_report` / `fetch_next` /
+//! `close_export_report` cycle in an iterator of typed [`ExportedEntity`]
+//! values. Fetching runs on a dedicated OS thread that stays up to `prefetch`
+//! entities ahead of the consumer, so the engine keeps producing while the
+//! consumer is busy writing (to a file, a socket, an upload part, ...).
+//!
+//! An [`SzCancellationToken`] given to
+//! [`with_cancellation`](SzExporter::with_cancellation) ends a running export
+//! with `SzError::Cancelled` before its next fetch, closing the handle.
+//!
+//! The SDK does not use async runtimes (see the threading notes in the crate
+//! documentation). With the `async-export` feature,
+//! `SzExporter::export_entities_async` returns an `AsyncExportStream`, a
+//! `futures::Stream` of the same entities: the fetch thread hands them over
+//! a bounded async channel, so awaiting the next entity never blocks the
+//! runtime. Without the feature, drive an [`ExportStream`] from a blocking
+//! task and forward items over your own channel.
+//!
+//! With the `object-store` feature, `SzExporter::export_to_object_store`
+//! writes an export straight to an `s3://`, `gs://` or `az://` URL as a
+//! multipart upload. The upload runs on a small tokio runtime private to the
+//! call, so callers stay synchronous.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::export::SzExporter;
+//! use sz_rust_sdk::prelude::*;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_export_stream")?;
+//! let exporter = SzExporter::new(env.clone()).with_prefetch(256);
+//! for entity in exporter.export_entities_stream(None)? {
+//!     let entity = entity?;
+//!     println!("{} has {} records", entity.resolved_entity.entity_id,
+//!         entity.resolved_entity.records.len());
+//! }
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::control::SzCancellationToken;
+use crate::core::SzEnvironmentCore;
+use crate::error::{SzError, SzResult};
+use crate::flags::{RelationshipKind, SzFlags};
+use crate::models::ExportedEntity;
+use crate::traits::{SzEngine, SzEngineRead, SzEnvironment};
+use crate::types::{DataSourceCode, ExportHandle, RepositoryCounts};
+use std::collections::BTreeSet;
+use std::io::Write;
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver};
+use std::thread::JoinHandle;
+use std::time::SystemTime;
+
+/// Default number of entities fetched ahead of the consumer.
+pub const DEFAULT_PREFETCH: usize = 64;
+
+/// An open export handle that is closed when dropped.
+///
+/// `export_json_entity_report` and `export_csv_entity_report` return a bare
+/// [`ExportHandle`] that must be passed to `close_export_report`, or the
+/// native export leaks. The guard closes it on every path, including early
+/// returns and panics; [`close`](Self::close) closes it explicitly and
+/// reports the outcome.
+///
+/// # Example
+///
+/// ```no_run
+/// # use sz_rust_sdk::helpers::ExampleEnvironment;
+/// use sz_rust_sdk::export::ExportGuard;
+/// use sz_rust_sdk::prelude::*;
+///
+/// # let env = ExampleEnvironment::initialize("doctest_export_guard")?;
+/// let engine = env.get_engine()?;
+/// let export = ExportGuard::json(engine.as_ref(), None)?;
+/// let first = export.fetch_next()?;
+/// println!("first entity: {first}");
+/// // The export is closed here, although it was not read to the end.
+/// # Ok::<(), SzError>(())
+/// ```
+pub struct ExportGuard<'a, E: SzEngineRead + ?Sized = dyn SzEngine> {
+    engine: &'a E,
+    handle: Option<ExportHandle>,
+}
+
+impl<'a, E: SzEngineRead + ?Sized> ExportGuard<'a, E> {
+    /// Starts a JSON entity export on `engine`.
+    pub fn json(engine: &'a E, flags: Option<SzFlags>) -> SzResult<Self> {
+        let handle = engine.export_json_entity_report(flags)?;
+        Ok(Self::adopt(engine, handle))
+    }
+
+    /// Starts a CSV entity export with the comma-separated
+    /// `csv_column_list` on `engine`.
+    pub fn csv(engine: &'a E, csv_column_list: &str, flags: Option<SzFlags>) -> SzResult<Self> {
+        let handle = engine.export_csv_entity_report(csv_column_list, flags)?;
+        Ok(Self::adopt(engine, handle))
+    }
+
+    /// Takes ownership of a handle opened on `engine`.
+    pub fn adopt(engine: &'a E, handle: ExportHandle) -> Self {
+        Self {
+            engine,
+            handle: Some(handle),
+        }
+    }
+
+    /// The native export handle.
+    pub fn handle(&self) -> ExportHandle {
+        self.handle.unwrap_or_default()
+    }
+
+    /// Fetches the next chunk of the export; empty when it is exhausted.
+    pub fn fetch_next(&self) -> SzResult<String> {
+        self.engine.fetch_next(self.handle())
+    }
+
+    /// Closes the export, returning the error the engine reports.
+    pub fn close(mut self) -> SzResult<()> {
+        match self.handle.take() {
+            Some(handle) => self.engine.close_export_report(handle),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<E: SzEngineRead + ?Sized> Drop for ExportGuard<'_, E> {
+    fn drop(&mut self) {
+        if let Some(handle) = self.handle.take() {
+            let _ = self.engine.close_export_report(handle);
+        }
+    }
+}
+
+impl<E: SzEngineRead + ?Sized> std::fmt::Debug for ExportGuard<'_, E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExportGuard")
+            .field("handle", &self.handle)
+            .finish()
+    }
+}
+
+/// Output format for [`SzExporter::export_to_writer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One JSON entity document per line (`export_json_entity_report`)
+    JsonLines,
+    /// CSV with the given comma-separated column list (`export_csv_entity_report`)
+    Csv(String),
+}
+
+/// Client-side conditions an exported entity must meet.
+///
+/// The engine always exports every entity; the filter is applied as results
+/// stream in, so a subset never has to be cut out of a full export file.
+/// Set the conditions through [`SzExporter::only_data_sources`],
+/// [`SzExporter::min_records`] and [`SzExporter::match_levels`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExportFilter {
+    data_sources: Option<BTreeSet<String>>,
+    min_records: usize,
+    match_levels: Option<RelationshipKind>,
+}
+
+impl ExportFilter {
+    /// Returns whether the filter lets every entity through.
+    pub fn is_empty(&self) -> bool {
+        self.data_sources.is_none() && self.min_records == 0 && self.match_levels.is_none()
+    }
+
+    /// Sets the data sources, normalized like [`DataSourceCode`]: trimmed
+    /// and uppercased. Empty codes match no record and are dropped.
+    fn set_data_sources(&mut self, data_sources: impl IntoIterator<Item = String>) {
+        self.data_sources = Some(
+            data_sources
+                .into_iter()
+                .filter_map(|code| DataSourceCode::new(code).ok())
+                .map(|code| code.as_str().to_string())
+                .collect(),
+        );
+    }
+
+    /// Returns whether `entity` meets every condition.
+    pub fn matches(&self, entity: &ExportedEntity) -> bool {
+        let records = &entity.resolved_entity.records;
+        if records.len() < self.min_records {
+            return false;
+        }
+        if let Some(data_sources) = &self.data_sources
+            && !records
+                .iter()
+                .any(|record| data_sources.contains(&record.data_source))
+        {
+            return false;
+        }
+        if let Some(kinds) = self.match_levels
+            && !entity
+                .related_entities
+                .iter()
+                .any(|related| related.relationship().intersects(kinds))
+        {
+            return false;
+        }
+        true
+    }
+}
+
+/// Runs entity exports against an environment.
+///
+/// Each export obtains its own engine from the environment and fetches on a
+/// background thread, so several exports may run at once.
+#[derive(Clone)]
+pub struct SzExporter {
+    env: Arc<SzEnvironmentCore>,
+    prefetch: usize,
+    filter: ExportFilter,
+    cancellation: Option<SzCancellationToken>,
+}
+
+impl SzExporter {
+    /// Creates an exporter for the given environment with
+    /// [`DEFAULT_PREFETCH`] entities of read-ahead.
+    pub fn new(env: Arc<SzEnvironmentCore>) -> Self {
+        Self {
+            env,
+            prefetch: DEFAULT_PREFETCH,
+            filter: ExportFilter::default(),
+            cancellation: None,
+        }
+    }
+
+    /// Sets how many entities the fetch thread may buffer ahead of the consumer.
+    ///
+    /// A value of 0 is treated as 1.
+    pub fn with_prefetch(mut self, prefetch: usize) -> Self {
+        self.prefetch = prefetch.max(1);
+        self
+    }
+
+    /// Ends exports with `SzError::Cancelled` once `token` is cancelled.
+    ///
+    /// The token is checked before each fetch; the export handle is closed
+    /// either way.
+    pub fn with_cancellation(mut self, token: SzCancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Returns the configured prefetch depth.
+    pub fn prefetch(&self) -> usize {
+        self.prefetch
+    }
+
+    /// Exports only entities with at least one record from one of
+    /// `data_sources`. Data source codes are matched case-insensitively.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::export::SzExporter;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_export_filter")?;
+    /// let exporter = SzExporter::new(env.clone())
+    ///     .only_data_sources(["CUSTOMERS"])
+    ///     .min_records(2);
+    /// for entity in exporter.export_entities_stream(None)? {
+    ///     println!("duplicate customer entity {}", entity?.resolved_entity.entity_id);
+    /// }
+    /// # Ok::<(), SzError>(())
+    /// ```
+    pub fn only_data_sources<I, S>(mut self, data_sources: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.filter
+            .set_data_sources(data_sources.into_iter().map(Into::into));
+        self
+    }
+
+    /// Exports only entities made up of at least `count` records.
+    pub fn min_records(mut self, count: usize) -> Self {
+        self.filter.min_records = count;
+        self
+    }
+
+    /// Exports only entities with at least one relationship of the given
+    /// kinds, e.g. `RelationshipKind::POSSIBLY_SAME`.
+    ///
+    /// Relationships are only in the export when the flags request them;
+    /// without a relation flag such as
+    /// `SzFlags::ENTITY_INCLUDE_ALL_RELATIONS` no entity matches.
+    pub fn match_levels(mut self, kinds: RelationshipKind) -> Self {
+        self.filter.match_levels = Some(kinds);
+        self
+    }
+
+    /// Returns the configured filter.
+    pub fn filter(&self) -> &ExportFilter {
+        &self.filter
+    }
+
+    /// Starts a JSON entity export and returns a stream of typed entities.
+    ///
+    /// The export handle is opened before this returns, so configuration and
+    /// initialization errors surface here rather than from the first item.
+    /// Errors while fetching or parsing are yielded as `Err` items, after which
+    /// the stream ends.
+    ///
+    /// # Arguments
+    ///
+    /// * `flags` - Export flags; defaults to `SzFlags::EXPORT_DEFAULT_FLAGS`
+    pub fn export_entities_stream(&self, flags: Option<SzFlags>) -> SzResult<ExportStream> {
+        let engine = self.env.get_engine()?;
+        let flags = flags.unwrap_or(SzFlags::EXPORT_DEFAULT_FLAGS);
+        let handle = engine.export_json_entity_report(Some(flags))?;
+
+        let (sender, receiver) = mpsc::sync_channel(self.prefetch);
+        let filter = self.filter.clone();
+        let cancellation = self.cancellation.clone();
+        let worker = std::thread::Builder::new()
+            .name("sz-export".to_string())
+            .spawn(move || {
+                fetch_loop(engine, handle, &filter, cancellation.as_ref(), |item| {
+                    sender.send(item).is_ok()
+                })
+            })
+            .map_err(|e| SzError::unknown(format!("Failed to start export thread: {e}")))?;
+
+        Ok(ExportStream {
+            receiver: Some(receiver),
+            worker: Some(worker),
+        })
+    }
+
+    /// Starts a JSON entity export and returns an async stream of typed
+    /// entities.
+    ///
+    /// The async counterpart of
+    /// [`export_entities_stream`](Self::export_entities_stream), with the
+    /// same filter, prefetch, cancellation and error behavior. Fetching runs
+    /// on a background thread, not on the async runtime. Dropping the stream
+    /// stops that thread after its current fetch and closes the export
+    /// handle, without waiting for it.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use futures_util::StreamExt;
+    /// use sz_rust_sdk::export::SzExporter;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # async fn run(env: std::sync::Arc<SzEnvironmentCore>) -> SzResult<()> {
+    /// let exporter = SzExporter::new(env);
+    /// let mut entities = exporter.export_entities_async(None)?;
+    /// while let Some(entity) = entities.next().await {
+    ///     println!("entity {}", entity?.resolved_entity.entity_id);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// As for [`export_entities_stream`](Self::export_entities_stream).
+    #[cfg(feature = "async-export")]
+    pub fn export_entities_async(&self, flags: Option<SzFlags>) -> SzResult<AsyncExportStream> {
+        let engine = self.env.get_engine()?;
+        let flags = flags.unwrap_or(SzFlags::EXPORT_DEFAULT_FLAGS);
+        let handle = engine.export_json_entity_report(Some(flags))?;
+
+        let (sender, receiver) = tokio::sync::mpsc::channel(self.prefetch);
+        let filter = self.filter.clone();
+        let cancellation = self.cancellation.clone();
+        std::thread::Builder::new()
+            .name("sz-export".to_string())
+            .spawn(move || {
+                fetch_loop(engine, handle, &filter, cancellation.as_ref(), |item| {
+                    sender.blocking_send(item).is_ok()
+                })
+            })
+            .map_err(|e| SzError::unknown(format!("Failed to start export thread: {e}")))?;
+
+        Ok(AsyncExportStream { receiver })
+    }
+
+    /// Starts a JSON entity export and returns a stream of Arrow record
+    /// batches, one row per record.
+    ///
+    /// The batches have the schema of `report::record_schema` and are built
+    /// as entities arrive, see `report::record_batches`. Fetching runs ahead
+    /// on a background thread as for
+    /// [`export_entities_stream`](Self::export_entities_stream), whose
+    /// filters and error handling apply as well.
+    ///
+    /// # Arguments
+    ///
+    /// * `flags` - Export flags; defaults to `SzFlags::EXPORT_DEFAULT_FLAGS`
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::export::SzExporter;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_export_entities_arrow")?;
+    /// let mut records = 0;
+    /// for batch in SzExporter::new(env.clone()).export_entities_arrow(None)? {
+    ///     records += batch?.num_rows();
+    /// }
+    /// println!("exported {records} records");
+    /// # Ok::<(), SzError>(())
+    /// ```
+    #[cfg(feature = "arrow")]
+    pub fn export_entities_arrow(
+        &self,
+        flags: Option<SzFlags>,
+    ) -> SzResult<impl Iterator<Item = SzResult<arrow_array::RecordBatch>> + use<>> {
+        Ok(crate::report::record_batches(
+            self.export_entities_stream(flags)?,
+        ))
+    }
+
+    /// Writes a complete export to `writer` and returns the number of bytes written.
+    ///
+    /// The export is copied chunk by chunk as the engine produces it, so memory
+    /// use does not grow with the size of the repository. Any `Write` target
+    /// works: a file, a socket, or a provider's multipart upload writer. With
+    /// the `object-store` feature, `export_to_object_store` does the upload
+    /// for S3, GCS and Azure Blob URLs.
+    /// The writer is flushed before returning. The export handle is closed
+    /// even when writing fails.
+    ///
+    /// With a filter set, JSON lines are parsed to apply it and the matching
+    /// lines are written unchanged; CSV exports cannot be filtered.
+    ///
+    /// # Arguments
+    ///
+    /// * `writer` - Destination for the export
+    /// * `format` - JSON lines or CSV
+    /// * `flags` - Export flags; defaults to `SzFlags::EXPORT_DEFAULT_FLAGS`
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::export::{ExportFormat, SzExporter};
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_export_to_writer")?;
+    /// let file = std::fs::File::create("/tmp/entities.jsonl")
+    ///     .map_err(|e| SzError::bad_input(e.to_string()))?;
+    /// let bytes = SzExporter::new(env.clone()).export_to_writer(
+    ///     std::io::BufWriter::new(file),
+    ///     ExportFormat::JsonLines,
+    ///     None,
+    /// )?;
+    /// println!("wrote {bytes} bytes");
+    /// # Ok::<(), SzError>(())
+    /// ```
+    pub fn export_to_writer<W: Write>(
+        &self,
+        mut writer: W,
+        format: ExportFormat,
+        flags: Option<SzFlags>,
+    ) -> SzResult<u64> {
+        let engine = self.env.get_engine()?;
+        let flags = Some(flags.unwrap_or(SzFlags::EXPORT_DEFAULT_FLAGS));
+        if !self.filter.is_empty() && matches!(format, ExportFormat::Csv(_)) {
+            return Err(SzError::bad_input(
+                "export filters apply to JSON exports only, not CSV",
+            ));
+        }
+        let export = match &format {
+            ExportFormat::JsonLines => ExportGuard::json(engine.as_ref(), flags)?,
+            ExportFormat::Csv(columns) => ExportGuard::csv(engine.as_ref(), columns, flags)?,
+        };
+
+        let result = if self.filter.is_empty() {
+            copy_export(&export, self.cancellation.as_ref(), &mut writer)
+        } else {
+            copy_filtered_export(
+                &export,
+                &self.filter,
+                self.cancellation.as_ref(),
+                &mut writer,
+            )
+        };
+        let closed = export.close();
+        let written = result?;
+        closed?;
+        writer
+            .flush()
+            .map_err(|e| write_error("Failed to flush export", e))?;
+        Ok(written)
+    }
+
+    /// Writes a complete export to cloud object storage and returns the
+    /// number of bytes written.
+    ///
+    /// `url` names the object: `s3://bucket/key`, `gs://bucket/key`,
+    /// `az://container/key` (or the providers' `https://` forms), or
+    /// `file:///path` for a local file. Credentials and regions are read from
+    /// the provider's usual environment variables (`AWS_*`, `GOOGLE_*`,
+    /// `AZURE_*`).
+    ///
+    /// The export is sent as a multipart upload in 5 MiB parts while the
+    /// engine keeps producing, as in [`export_to_writer`](Self::export_to_writer).
+    /// If the export or an upload fails, the multipart upload is aborted, so
+    /// no partial object is left at `url`.
+    ///
+    /// The upload runs on a tokio runtime owned by the call; do not call this
+    /// from inside an async task, use `spawn_blocking` instead.
+    ///
+    /// # Arguments
+    ///
+    /// * `url` - Object to write
+    /// * `format` - JSON lines or CSV
+    /// * `flags` - Export flags; defaults to `SzFlags::EXPORT_DEFAULT_FLAGS`
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::BadInput` - `url` is not an `s3://`, `gs://`, `az://` or
+    ///   `file://` URL
+    /// * `SzError::Configuration` - The store cannot be configured, e.g. for
+    ///   missing credentials
+    /// * `SzError::Retryable` - An upload request failed after the client's
+    ///   own retries, e.g. on a network error or timeout
+    /// * `SzError::Unknown` - The object store rejected a request
+    /// * Any error from the export itself, as in `export_to_writer`
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::export::{ExportFormat, SzExporter};
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_export_to_object_store")?;
+    /// let bytes = SzExporter::new(env.clone()).export_to_object_store(
+    ///     "s3://exports/entities.jsonl",
+    ///     ExportFormat::JsonLines,
+    ///     None,
+    /// )?;
+    /// println!("uploaded {bytes} bytes");
+    /// # Ok::<(), SzError>(())
+    /// ```
+    #[cfg(feature = "object-store")]
+    pub fn export_to_object_store(
+        &self,
+        url: &str,
+        format: ExportFormat,
+        flags: Option<SzFlags>,
+    ) -> SzResult<u64> {
+        crate::cloud_storage::ObjectLocation::parse(url)?
+            .upload(|writer| self.export_to_writer(writer, format, flags))
+    }
+}
+
+/// Maps a failure writing export output. A failing writer is not bad input;
+/// object store failures are mapped by their kind, so transient upload
+/// failures are retryable.
+fn write_error(context: &str, e: std::io::Error) -> SzError {
+    #[cfg(feature = "object-store")]
+    if let Some(error) = crate::cloud_storage::io_storage_error(&e) {
+        return error;
+    }
+    SzError::unknown(format!("{context}: {e}"))
+}
+
+/// Copies every chunk of an open export into `writer`.
+fn copy_export<W: Write>(
+    export: &ExportGuard<'_>,
+    cancellation: Option<&SzCancellationToken>,
+    writer: &mut W,
+) -> SzResult<u64> {
+    let mut written = 0u64;
+    loop {
+        check_cancelled(cancellation)?;
+        let chunk = export.fetch_next()?;
+        if chunk.is_empty() {
+            return Ok(written);
+        }
+        writer
+            .write_all(chunk.as_bytes())
+            .map_err(|e| write_error("Failed writing export", e))?;
+        written += chunk.len() as u64;
+    }
+}
+
+/// Copies the entities of an open JSON export that match `filter` into
+/// `writer`, one line each.
+fn copy_filtered_export<W: Write>(
+    export: &ExportGuard<'_>,
+    filter: &ExportFilter,
+    cancellation: Option<&SzCancellationToken>,
+    writer: &mut W,
+) -> SzResult<u64> {
+    let mut written = 0u64;
+    let mut pending = String::new();
+    loop {
+        check_cancelled(cancellation)?;
+        let chunk = export.fetch_next()?;
+        let done = chunk.is_empty();
+        pending.push_str(&chunk);
+        for line in take_complete_lines(&mut pending, done) {
+            let entity: ExportedEntity = serde_json::from_str(&line)?;
+            if !filter.matches(&entity) {
+                continue;
+            }
+            writer
+                .write_all(line.as_bytes())
+                .and_then(|()| writer.write_all(b"\n"))
+                .map_err(|e| write_error("Failed writing export", e))?;
+            written += line.len() as u64 + 1;
+        }
+        if done {
+            return Ok(written);
+        }
+    }
+}
+
+/// Iterator over the entities of a running export.
+///
+/// Dropping the stream before it is exhausted stops the fetch thread and
+/// closes the export handle.
+pub struct ExportStream {
+    receiver: Option<Receiver<SzResult<ExportedEntity>>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl Iterator for ExportStream {
+    type Item = SzResult<ExportedEntity>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.receiver.as_ref()?.recv().ok()
+    }
+}
+
+impl Drop for ExportStream {
+    fn drop(&mut self) {
+        // Dropping the receiver makes the worker's next send fail, which ends
+        // its loop and closes the export handle.
+        self.receiver.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+/// Async stream over the entities of a running export, from
+/// [`SzExporter::export_entities_async`].
+///
+/// Dropping the stream before it is exhausted stops the fetch thread and
+/// closes the export handle.
+#[cfg(feature = "async-export")]
+#[derive(Debug)]
+pub struct AsyncExportStream {
+    receiver: tokio::sync::mpsc::Receiver<SzResult<ExportedEntity>>,
+}
+
+#[cfg(feature = "async-export")]
+impl futures_util::Stream for AsyncExportStream {
+    type Item = SzResult<ExportedEntity>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// Fails with `SzError::Cancelled` once `cancellation` is cancelled.
+fn check_cancelled(cancellation: Option<&SzCancellationToken>) -> SzResult<()> {
+    match cancellation {
+        Some(token) => token.check("export"),
+        None => Ok(()),
+    }
+}
+
+/// Fetches export lines until the export is exhausted, the consumer goes away,
+/// the export is cancelled or an error occurs, passing the entities that
+/// match `filter` to `send`, which returns `false` once the consumer is gone.
+/// Always closes the handle.
+fn fetch_loop(
+    engine: Box<dyn SzEngine>,
+    handle: ExportHandle,
+    filter: &ExportFilter,
+    cancellation: Option<&SzCancellationToken>,
+    mut send: impl FnMut(SzResult<ExportedEntity>) -> bool,
+) {
+    let export = ExportGuard::adopt(engine.as_ref(), handle);
+    let mut pending = String::new();
+    'fetch: loop {
+        let chunk = match check_cancelled(cancellation).and_then(|()| export.fetch_next()) {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                send(Err(e));
+                break;
+            }
+        };
+        let done = chunk.is_empty();
+        pending.push_str(&chunk);
+
+        for line in take_complete_lines(&mut pending, done) {
+            let item = serde_json::from_str::<ExportedEntity>(&line).map_err(Into::into);
+            if item.as_ref().is_ok_and(|entity| !filter.matches(entity)) {
+                continue;
+            }
+            let failed = item.is_err();
+            if !send(item) || failed {
+                break 'fetch;
+            }
+        }
+
+        if done {
+            break;
+        }
+    }
+}
+
+/// Calls `visit` with every entity of a complete export on `engine`, in
+/// export order, stopping at the first error.
+pub(crate) fn for_each_entity<E: SzEngineRead + ?Sized>(
+    engine: &E,
+    flags: SzFlags,
+    mut visit: impl FnMut(ExportedEntity) -> SzResult<()>,
+) -> SzResult<()> {
+    let export = ExportGuard::json(engine, Some(flags))?;
+    let mut pending = String::new();
+    loop {
+        let chunk = export.fetch_next()?;
+        let done = chunk.is_empty();
+        pending.push_str(&chunk);
+        for line in take_complete_lines(&mut pending, done) {
+            visit(serde_json::from_str(&line)?)?;
+        }
+        if done {
+            return Ok(());
+        }
+    }
+}
+
+/// Lists the distinct record IDs of a data source, sorted, using a complete
+/// entity export on `engine`.
+///
+/// For bulk tools that need the full list before they start modifying records.
+pub(crate) fn data_source_record_ids(
+    engine: &dyn SzEngine,
+    data_source_code: &str,
+) -> SzResult<Vec<String>> {
+    let mut record_ids = Vec::new();
+    for_each_entity(engine, SzFlags::EXPORT_DEFAULT_FLAGS, |entity| {
+        record_ids.extend(records_in_data_source(&entity, data_source_code));
+        Ok(())
+    })?;
+
+    record_ids.sort();
+    record_ids.dedup();
+    Ok(record_ids)
+}
+
+/// Counts the entities, records and relationships in the repository by
+/// walking a complete entity export on `engine`.
+///
+/// Each relationship is reported by both of its entities, so it is counted
+/// once per end and halved.
+pub(crate) fn count_repository<E: SzEngineRead + ?Sized>(engine: &E) -> SzResult<RepositoryCounts> {
+    let flags = SzFlags::EXPORT_INCLUDE_ALL_ENTITIES
+        | SzFlags::ENTITY_INCLUDE_RECORD_DATA
+        | SzFlags::ENTITY_INCLUDE_ALL_RELATIONS;
+    let mut counts = RepositoryCounts {
+        entities: 0,
+        records: 0,
+        relationships: 0,
+        counted_at: SystemTime::now(),
+    };
+    let mut relationship_ends = 0i64;
+    for_each_entity(engine, flags, |entity| {
+        counts.entities += 1;
+        counts.records += entity.resolved_entity.records.len() as i64;
+        relationship_ends += entity.related_entities.len() as i64;
+        Ok(())
+    })?;
+
+    counts.relationships = relationship_ends / 2;
+    Ok(counts)
+}
+
+/// Returns the record IDs of `data_source_code` in an exported entity.
+fn records_in_data_source<'a>(
+    entity: &'a ExportedEntity,
+    data_source_code: &'a str,
+) -> impl Iterator<Item = String> + 'a {
+    entity
+        .resolved_entity
+        .records
+        .iter()
+        .filter(move |record| record.data_source == data_source_code)
+        .map(|record| record.record_id.clone())
+}
+
+/// Removes and returns the complete, non-blank lines from `buffer`.
+///
+/// A trailing partial line is left in the buffer unless `flush` is set, in
+/// which case it is returned as well.
+fn take_complete_lines(buffer: &mut String, flush: bool) -> Vec<String> {
+    let split_at = if flush {
+        buffer.len()
+    } else {
+        buffer.rfind('\n').map_or(0, |i| i + 1)
+    };
+    let rest = buffer.split_off(split_at);
+    let complete = std::mem::replace(buffer, rest);
+    complete
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_take_complete_lines_keeps_partial_line() {
+        let mut buffer = String::from("{\"a\":1}\n{\"b\":2}\n{\"c\"");
+        let lines = take_complete_lines(&mut buffer, false);
+        assert_eq!(lines, vec!["{\"a\":1}", "{\"b\":2}"]);
+        assert_eq!(buffer, "{\"c\"");
+
+        buffer.push_str(":3}\n");
+        let lines = take_complete_lines(&mut buffer, false);
+        assert_eq!(lines, vec!["{\"c\":3}"]);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_take_complete_lines_flush() {
+        let mut buffer = String::from("{\"a\":1}\n\n{\"b\":2}");
+        let lines = take_complete_lines(&mut buffer, true);
+        assert_eq!(lines, vec!["{\"a\":1}", "{\"b\":2}"]);
+        assert!(buffer.is_empty());
+    }
+
+    #[test]
+    fn test_take_complete_lines_no_newline() {
+        let mut buffer = String::from("{\"a\"");
+        assert!(take_complete_lines(&mut buffer, false).is_empty());
+        assert_eq!(buffer, "{\"a\"");
+    }
+
+    #[test]
+    fn test_export_filter() {
+        let entity: ExportedEntity = serde_json::from_str(
+            r#"{"RESOLVED_ENTITY":{"ENTITY_ID":1,"RECORDS":[
+                {"DATA_SOURCE":"CUSTOMERS","RECORD_ID":"1"},{"DATA_SOURCE":"WATCHLIST","RECORD_ID":"9"}]},
+                "RELATED_ENTITIES":[{"ENTITY_ID":2,"MATCH_LEVEL_CODE":"POSSIBLY_RELATED"}]}"#,
+        )
+        .unwrap();
+        let keeps = |filter: ExportFilter| filter.matches(&entity);
+
+        assert!(ExportFilter::default().is_empty());
+        assert!(keeps(ExportFilter::default()));
+        assert!(keeps(ExportFilter {
+            min_records: 2,
+            ..Default::default()
+        }));
+        assert!(!keeps(ExportFilter {
+            min_records: 3,
+            ..Default::default()
+        }));
+        assert!(keeps(ExportFilter {
+            data_sources: Some(BTreeSet::from(["WATCHLIST".to_string()])),
+            ..Default::default()
+        }));
+        assert!(!keeps(ExportFilter {
+            data_sources: Some(BTreeSet::from(["VENDORS".to_string()])),
+            ..Default::default()
+        }));
+        let mut filter = ExportFilter::default();
+        filter.set_data_sources([" watchlist".to_string(), String::new()]);
+        assert_eq!(
+            filter.data_sources,
+            Some(BTreeSet::from(["WATCHLIST".to_string()]))
+        );
+        assert!(keeps(filter));
+        assert!(keeps(ExportFilter {
+            match_levels: Some(
+                RelationshipKind::POSSIBLY_RELATED | RelationshipKind::POSSIBLY_SAME
+            ),
+            ..Default::default()
+        }));
+        assert!(!keeps(ExportFilter {
+            match_levels: Some(RelationshipKind::POSSIBLY_SAME),
+            ..Default::default()
+        }));
+    }
+
+    #[test]
+    fn test_records_in_data_source() {
+        let entity: ExportedEntity = serde_json::from_str(
+            r#"{"RESOLVED_ENTITY":{"ENTITY_ID":1,"RECORDS":[
+                {"DATA_SOURCE":"A","RECORD_ID":"2"},{"DATA_SOURCE":"B","RECORD_ID":"9"},
+                {"DATA_SOURCE":"A","RECORD_ID":"1"}]}}"#,
+        )
+        .unwrap();
+
+        let ids: Vec<String> = records_in_data_source(&entity, "A").collect();
+        assert_eq!(ids, vec!["2", "1"]);
+        assert_eq!(records_in_data_source(&entity, "C").count(), 0);
+    }
+
+    #[cfg(feature = "async-export")]
+    #[test]
+    fn test_async_export_stream() {
+        use futures_util::StreamExt;
+
+        let (sender, receiver) = tokio::sync::mpsc::channel(1);
+        let producer = std::thread::spawn(move || {
+            for id in 1..=3 {
+                let entity = serde_json::from_str(&format!(
+                    r#"{{"RESOLVED_ENTITY":{{"ENTITY_ID":{id},"RECORDS":[]}}}}"#
+                ))
+                .map_err(Into::into);
+                if sender.blocking_send(entity).is_err() {
+                    return false;
+                }
+            }
+            true
+        });
+        let stream = AsyncExportStream { receiver };
+        let ids: Vec<i64> = tokio_test::block_on(
+            stream
+                .map(|entity| entity.unwrap().resolved_entity.entity_id.0)
+                .collect(),
+        );
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert!(producer.join().unwrap());
+
+        // A dropped stream makes the producer's next send fail
+        let (sender, receiver) = tokio::sync::mpsc::channel::<SzResult<ExportedEntity>>(1);
+        drop(AsyncExportStream { receiver });
+        assert!(sender.blocking_send(Err(SzError::unknown("late"))).is_err());
+    }
+}
@@ -0,0 +1,204 @@
+//! Panic containment and poisoned-state detection at the native boundary
+//!
+//! Every native call of the core components runs in [`guarded`].
+//!
+//! The SDK never hands Rust callbacks to the native library, so a panic can
+//! only start in the Rust code around a native call: argument conversion,
+//! response handling, or (with `runtime-dlopen`) symbol lookup. [`guarded`]
+//! turns such a panic into `SzError::Unhandled` instead of letting it unwind
+//! through the caller, which matters most inside `Once::call_once`, where a
+//! panic would poison initialization for every later caller.
+//!
+//! Every native return code is observed here. After [`poison_threshold`]
+//! consecutive unrecoverable errors the native state is considered poisoned;
+//! the environment then refuses to hand out new components until it is
+//! destroyed and recreated (see `SzEnvironmentCore::degraded_reason`).
+//!
+//! The native state also belongs to the process that created the
+//! environment. [`ensure_owner_process`] refuses native calls in a process
+//! forked from it, where they would act on a copy of the parent's state.
+
+use crate::error::{SzError, SzResult};
+use std::any::Any;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Default number of consecutive unrecoverable errors that poison the
+/// native state.
+pub(crate) const DEFAULT_POISON_THRESHOLD: u32 = 3;
+
+static CONSECUTIVE_UNRECOVERABLE: AtomicU32 = AtomicU32::new(0);
+static POISON_THRESHOLD: AtomicU32 = AtomicU32::new(DEFAULT_POISON_THRESHOLD);
+static POISONED: Mutex<Option<String>> = Mutex::new(None);
+/// Process that created the last environment, 0 before there is one
+static OWNER_PROCESS: AtomicU32 = AtomicU32::new(0);
+
+/// Runs `call`, converting a panic into `SzError::Unhandled`.
+///
+/// A caught panic counts as an unrecoverable error for poisoning. In a
+/// process forked from the one that created the environment `call` is not
+/// run; see [`ensure_owner_process`].
+pub(crate) fn guarded<T>(operation: &str, call: impl FnOnce() -> SzResult<T>) -> SzResult<T> {
+    ensure_owner_process()?;
+    match catch_unwind(AssertUnwindSafe(call)) {
+        Ok(result) => result,
+        Err(payload) => {
+            let e = SzError::unhandled(format!(
+                "panic during {operation}: {}",
+                panic_message(payload.as_ref())
+            ));
+            observe_error(&e);
+            Err(e)
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message
+    } else {
+        "non-string panic payload"
+    }
+}
+
+/// Records a successful native call.
+pub(crate) fn observe_success() {
+    CONSECUTIVE_UNRECOVERABLE.store(0, Ordering::Relaxed);
+}
+
+/// Records a failed native call, poisoning the native state once enough
+/// unrecoverable errors occur in a row.
+pub(crate) fn observe_error(e: &SzError) {
+    if !e.is_unrecoverable() {
+        CONSECUTIVE_UNRECOVERABLE.store(0, Ordering::Relaxed);
+        return;
+    }
+    let count = CONSECUTIVE_UNRECOVERABLE.fetch_add(1, Ordering::Relaxed) + 1;
+    if count >= poison_threshold() {
+        let mut poisoned = lock_poisoned();
+        if poisoned.is_none() {
+            *poisoned = Some(format!(
+                "{count} consecutive unrecoverable errors; last: {e}"
+            ));
+        }
+    }
+}
+
+/// Returns why the native state is poisoned, if it is.
+pub(crate) fn poisoned() -> Option<String> {
+    lock_poisoned().clone()
+}
+
+/// Clears the poisoned state, after the native modules were destroyed.
+pub(crate) fn reset() {
+    CONSECUTIVE_UNRECOVERABLE.store(0, Ordering::Relaxed);
+    *lock_poisoned() = None;
+}
+
+/// Records that the calling process created an environment and owns the
+/// native state.
+pub(crate) fn set_owner_process() {
+    OWNER_PROCESS.store(std::process::id(), Ordering::Relaxed);
+}
+
+/// Fails when called in a process forked after the environment was created.
+pub(crate) fn ensure_owner_process() -> SzResult<()> {
+    match OWNER_PROCESS.load(Ordering::Relaxed) {
+        0 => Ok(()),
+        owner => check_owner(owner),
+    }
+}
+
+/// Fails unless the calling process is `owner`.
+pub(crate) fn check_owner(owner: u32) -> SzResult<()> {
+    let pid = std::process::id();
+    if pid != owner {
+        return Err(SzError::unrecoverable(format!(
+            "Senzing environment was created in process {owner} and cannot be used in \
+             forked process {pid}; call SzEnvironmentCore::after_fork_child() and \
+             create a new environment"
+        )));
+    }
+    Ok(())
+}
+
+/// Number of consecutive unrecoverable errors that poison the native state.
+pub(crate) fn poison_threshold() -> u32 {
+    POISON_THRESHOLD.load(Ordering::Relaxed)
+}
+
+/// Sets the poisoning threshold. A value of 0 is treated as 1.
+pub(crate) fn set_poison_threshold(count: u32) {
+    POISON_THRESHOLD.store(count.max(1), Ordering::Relaxed);
+}
+
+fn lock_poisoned() -> std::sync::MutexGuard<'static, Option<String>> {
+    match POISONED.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // One test, since the poisoning state is process-wide
+    #[test]
+    fn test_guarded_panics_poison_native_state() {
+        reset();
+        set_poison_threshold(2);
+
+        let result: SzResult<()> = guarded("test_op", || panic!("boom"));
+        let err = result.unwrap_err();
+        assert!(matches!(err, SzError::Unhandled(_)));
+        assert!(err.message().contains("test_op: boom"));
+        assert_eq!(poisoned(), None);
+
+        // Only consecutive unrecoverable errors count
+        observe_success();
+        observe_error(&SzError::unrecoverable("first"));
+        assert_eq!(poisoned(), None);
+        observe_error(&SzError::bad_input("not counted"));
+        observe_error(&SzError::unrecoverable("second"));
+        assert_eq!(guarded("test_op", || Ok(5)).unwrap(), 5);
+        observe_error(&SzError::unrecoverable("third"));
+        assert!(poisoned().unwrap().contains("third"));
+
+        reset();
+        set_poison_threshold(DEFAULT_POISON_THRESHOLD);
+        assert_eq!(poisoned(), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_forked_process_is_refused() {
+        set_owner_process();
+        assert!(ensure_owner_process().is_ok());
+        // SAFETY: the child only checks the owner and exits
+        match unsafe { libc::fork() } {
+            -1 => panic!("fork failed"),
+            0 => {
+                let refused = matches!(ensure_owner_process(), Err(SzError::Unrecoverable(_)));
+                unsafe { libc::_exit(if refused { 0 } else { 1 }) }
+            }
+            child => {
+                let mut status = 0;
+                assert_eq!(unsafe { libc::waitpid(child, &mut status, 0) }, child);
+                assert!(libc::WIFEXITED(status));
+                assert_eq!(libc::WEXITSTATUS(status), 0);
+            }
+        }
+        assert!(ensure_owner_process().is_ok());
+    }
+
+    #[test]
+    fn test_panic_message_payloads() {
+        assert_eq!(panic_message(&"static"), "static");
+        assert_eq!(panic_message(&String::from("owned")), "owned");
+        assert_eq!(panic_message(&42), "non-string panic payload");
+    }
+}
@@ -0,0 +1,99 @@
+//! Runtime loading of the native library (`runtime-dlopen` feature)
+//!
+//! Instead of linking against libSz, the library is opened the first time
+//! the environment initializes a native module. The FFI wrappers generated
+//! by build.rs look their symbols up here.
+
+use crate::error::{SzError, SzResult};
+use libloading::Library;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+static LIBRARY: OnceLock<Result<Library, String>> = OnceLock::new();
+
+/// Returns the loaded native library, loading it on first use.
+pub(crate) fn library() -> SzResult<&'static Library> {
+    LIBRARY
+        .get_or_init(load)
+        .as_ref()
+        .map_err(|message| SzError::configuration(message.clone()))
+}
+
+/// Looks up a function in the native library.
+///
+/// # Safety
+///
+/// `F` must be the function pointer type of the exported symbol `name`.
+///
+/// # Panics
+///
+/// If the library cannot be loaded or does not export `name`. The wrappers
+/// only run after [`library`] succeeded, so this means a library version
+/// without the function.
+pub(crate) unsafe fn symbol<F: Copy>(name: &str) -> F {
+    let library = match library() {
+        Ok(library) => library,
+        Err(e) => panic!("{e}"),
+    };
+    match unsafe { library.get::<F>(name.as_bytes()) } {
+        Ok(symbol) => *symbol,
+        Err(e) => panic!("Senzing library does not export {name}: {e}"),
+    }
+}
+
+fn load() -> Result<Library, String> {
+    let file_name = libloading::library_filename("Sz");
+    let mut candidates: Vec<PathBuf> = crate::native::library_search_dirs()
+        .into_iter()
+        .map(|dir| dir.join(&file_name))
+        .filter(|path| path.exists())
+        .collect();
+    // Finally let the platform loader search its default paths
+    candidates.push(PathBuf::from(&file_name));
+
+    let mut failures = Vec::new();
+    for candidate in candidates {
+        // SAFETY: loading libSz runs its initializers, which have no
+        // preconditions beyond its own dependencies being loadable.
+        match unsafe { open(&candidate) } {
+            Ok(library) => return Ok(library),
+            Err(e) => failures.push(format!("{}: {e}", candidate.display())),
+        }
+    }
+    Err(format!(
+        "Senzing native library could not be loaded ({}). Install Senzing or set \
+         SENZING_LIB_PATH to the directory containing {}",
+        failures.join("; "),
+        file_name.to_string_lossy()
+    ))
+}
+
+/// Opens the library at `path`.
+///
+/// # Safety
+///
+/// See `Library::new`.
+#[cfg(not(windows))]
+unsafe fn open(path: &Path) -> Result<Library, libloading::Error> {
+    unsafe { Library::new(path) }
+}
+
+/// Opens the library at `path`. `Sz.dll` is opened with its own directory
+/// first on the DLL search path, so its dependencies installed next to it
+/// are found without adding the directory to `PATH`.
+///
+/// # Safety
+///
+/// See `Library::new`.
+#[cfg(windows)]
+unsafe fn open(path: &Path) -> Result<Library, libloading::Error> {
+    use libloading::os::windows::{LOAD_WITH_ALTERED_SEARCH_PATH, Library as WindowsLibrary};
+
+    // The altered search order only applies to absolute paths
+    let flags = if path.is_absolute() {
+        LOAD_WITH_ALTERED_SEARCH_PATH
+    } else {
+        0
+    };
+    unsafe { WindowsLibrary::load_with_flags(path, flags) }.map(Library::from)
+}
@@ -4,6 +4,70 @@ use crate::error::{SzError, SzResult};
 use libc::{c_char, c_void, size_t};
 use std::ffi::{CStr, CString};
 use std::ptr;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NATIVE_BUFFERS_TAKEN: AtomicU64 = AtomicU64::new(0);
+static NATIVE_BUFFERS_FREED: AtomicU64 = AtomicU64::new(0);
+
+/// Records that a native response buffer was received.
+#[inline]
+pub(crate) fn note_native_buffer_taken() {
+    NATIVE_BUFFERS_TAKEN.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Returns the native response buffer counters.
+pub(crate) fn native_buffer_stats() -> crate::types::NativeBufferStats {
+    crate::types::NativeBufferStats {
+        taken: NATIVE_BUFFERS_TAKEN.load(Ordering::Relaxed),
+        freed: NATIVE_BUFFERS_FREED.load(Ordering::Relaxed),
+    }
+}
+
+/// Kinds of native handles counted for leak detection.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum NativeHandle {
+    Export,
+    Config,
+}
+
+/// Opened and closed counts per [`NativeHandle`] kind, kept in debug builds.
+#[cfg(debug_assertions)]
+static NATIVE_HANDLES: [[AtomicU64; 2]; 2] = [
+    [AtomicU64::new(0), AtomicU64::new(0)],
+    [AtomicU64::new(0), AtomicU64::new(0)],
+];
+
+/// Records that a native handle was opened; a no-op in release builds.
+#[inline]
+pub(crate) fn note_handle_opened(kind: NativeHandle) {
+    #[cfg(debug_assertions)]
+    NATIVE_HANDLES[kind as usize][0].fetch_add(1, Ordering::Relaxed);
+    #[cfg(not(debug_assertions))]
+    let _ = kind;
+}
+
+/// Records that a native handle was closed; a no-op in release builds.
+#[inline]
+pub(crate) fn note_handle_closed(kind: NativeHandle) {
+    #[cfg(debug_assertions)]
+    NATIVE_HANDLES[kind as usize][1].fetch_add(1, Ordering::Relaxed);
+    #[cfg(not(debug_assertions))]
+    let _ = kind;
+}
+
+/// Returns the native handle counters.
+#[cfg(debug_assertions)]
+pub(crate) fn native_handle_stats() -> crate::types::NativeHandleStats {
+    let count = |kind: NativeHandle, closed: bool| {
+        NATIVE_HANDLES[kind as usize][closed as usize].load(Ordering::Relaxed)
+    };
+    crate::types::NativeHandleStats {
+        exports_opened: count(NativeHandle::Export, false),
+        exports_closed: count(NativeHandle::Export, true),
+        configs_opened: count(NativeHandle::Config, false),
+        configs_closed: count(NativeHandle::Config, true),
+    }
+}
 
 /// Converts a Rust string to a C string (Internal)
 pub(crate) fn str_to_c_string(s: &str) -> SzResult<CString> {
@@ -18,6 +82,7 @@ pub(crate) fn str_to_c_string(s: &str) -> SzResult<CString> {
 pub(crate) unsafe fn sz_free(ptr: *mut c_char) {
     if !ptr.is_null() {
         unsafe { super::SzHelper_free(ptr as *mut c_void) };
+        NATIVE_BUFFERS_FREED.fetch_add(1, Ordering::Relaxed);
     }
 }
 
@@ -31,6 +96,7 @@ pub(crate) unsafe fn c_str_to_string(ptr: *mut c_char) -> SzResult<String> {
     if ptr.is_null() {
         return Ok(String::new());
     }
+    note_native_buffer_taken();
 
     let c_str = unsafe { CStr::from_ptr(ptr) };
     let result = match c_str.to_str() {
@@ -79,6 +145,7 @@ pub(crate) unsafe fn c_str_to_bytes(ptr: *mut c_char) -> SzResult<Vec<u8>> {
     if ptr.is_null() {
         return Ok(Vec::new());
     }
+    note_native_buffer_taken();
 
     let c_str = unsafe { CStr::from_ptr(ptr) };
     let bytes = c_str.to_bytes().to_vec();
@@ -126,66 +193,71 @@ impl Drop for ResponseBuffer {
 /// Checks the return code from Senzing Engine FFI functions
 pub(crate) fn check_return_code(return_code: i64) -> SzResult<()> {
     if return_code == 0 {
+        super::boundary::observe_success();
         return Ok(());
     }
 
     let actual_error_code = unsafe { super::Sz_getLastExceptionCode() };
-    Err(SzError::from_code_with_message(
-        actual_error_code,
-        crate::error::SzComponent::Engine,
-    ))
+    let error =
+        SzError::from_code_with_message(actual_error_code, crate::error::SzComponent::Engine);
+    super::boundary::observe_error(&error);
+    Err(error)
 }
 
 /// Checks the return code from Senzing Config FFI functions
 pub(crate) fn check_config_return_code(return_code: i64) -> SzResult<()> {
     if return_code == 0 {
+        super::boundary::observe_success();
         return Ok(());
     }
 
     let actual_error_code = unsafe { super::SzConfig_getLastExceptionCode() };
-    Err(SzError::from_code_with_message(
-        actual_error_code,
-        crate::error::SzComponent::Config,
-    ))
+    let error =
+        SzError::from_code_with_message(actual_error_code, crate::error::SzComponent::Config);
+    super::boundary::observe_error(&error);
+    Err(error)
 }
 
 /// Checks the return code from Senzing ConfigMgr FFI functions
 pub(crate) fn check_config_mgr_return_code(return_code: i64) -> SzResult<()> {
     if return_code == 0 {
+        super::boundary::observe_success();
         return Ok(());
     }
 
     let actual_error_code = unsafe { super::SzConfigMgr_getLastExceptionCode() };
-    Err(SzError::from_code_with_message(
-        actual_error_code,
-        crate::error::SzComponent::ConfigMgr,
-    ))
+    let error =
+        SzError::from_code_with_message(actual_error_code, crate::error::SzComponent::ConfigMgr);
+    super::boundary::observe_error(&error);
+    Err(error)
 }
 
 /// Checks the return code from SzProduct FFI functions
 pub(crate) fn check_product_return_code(return_code: i64) -> SzResult<()> {
     if return_code == 0 {
+        super::boundary::observe_success();
         return Ok(());
     }
 
     let actual_error_code = unsafe { super::SzProduct_getLastExceptionCode() };
-    Err(SzError::from_code_with_message(
-        actual_error_code,
-        crate::error::SzComponent::Product,
-    ))
+    let error =
+        SzError::from_code_with_message(actual_error_code, crate::error::SzComponent::Product);
+    super::boundary::observe_error(&error);
+    Err(error)
 }
 
 /// Checks the return code from SzDiagnostic FFI functions
 pub(crate) fn check_diagnostic_return_code(return_code: i64) -> SzResult<()> {
     if return_code == 0 {
+        super::boundary::observe_success();
         return Ok(());
     }
 
     let actual_error_code = unsafe { super::SzDiagnostic_getLastExceptionCode() };
-    Err(SzError::from_code_with_message(
-        actual_error_code,
-        crate::error::SzComponent::Diagnostic,
-    ))
+    let error =
+        SzError::from_code_with_message(actual_error_code, crate::error::SzComponent::Diagnostic);
+    super::boundary::observe_error(&error);
+    Err(error)
 }
 
 /// Macro for safely calling FFI functions with proper error handling
@@ -194,7 +266,7 @@ pub(crate) fn check_diagnostic_return_code(return_code: i64) -> SzResult<()> {
 macro_rules! ffi_call {
     ($ffi_fn:expr) => {{
         #[allow(clippy::macro_metavars_in_unsafe)]
-        let result = unsafe { $ffi_fn };
+        let result = $crate::profiling::native(|| unsafe { $ffi_fn });
         $crate::ffi::helpers::check_return_code(result)?;
     }};
 }
@@ -307,13 +379,13 @@ macro_rules! process_config_mgr_result {
     }};
 }
 
-/// Process config manager helper result (i64 response like configID)
+/// Process config manager helper result (configID response)
 #[doc(hidden)]
 #[macro_export]
 macro_rules! process_config_mgr_long_result {
     ($result:expr) => {{
         $crate::ffi::helpers::check_config_mgr_return_code($result.returnCode)?;
-        Ok($result.configID)
+        Ok($crate::types::ConfigId($result.configID))
     }};
 }
 
@@ -6,6 +6,15 @@
 //!
 //! Bindings are auto-generated from Senzing C headers using bindgen.
 //! To regenerate: cargo run --example generate_bindings
+//!
+//! With the `runtime-dlopen` feature the library is not linked. build.rs
+//! generates a wrapper for every function in the bindings that resolves the
+//! symbol from the library loaded by [`dynamic`]; the wrappers are defined in
+//! this module and take precedence over the glob-imported declarations.
+//!
+//! With the `stub-ffi` feature nothing is linked or loaded: the generated
+//! wrappers call [`stub::unavailable`], which panics, and [`ensure_loaded`]
+//! fails so SDK calls return an error before reaching them.
 
 // Auto-generated bindings from C headers
 #[allow(
@@ -17,8 +26,42 @@
 )]
 pub(crate) mod bindings_generated;
 
+pub(crate) mod boundary;
+
 #[allow(dead_code)]
 pub(crate) mod helpers;
 
 // Re-export all generated bindings for internal use
 pub(crate) use bindings_generated::*;
+
+#[cfg(all(feature = "runtime-dlopen", not(feature = "stub-ffi")))]
+pub(crate) mod dynamic;
+
+#[cfg(all(feature = "runtime-dlopen", not(feature = "stub-ffi")))]
+include!(concat!(env!("OUT_DIR"), "/dlopen_wrappers.rs"));
+
+#[cfg(feature = "stub-ffi")]
+pub(crate) mod stub;
+
+#[cfg(feature = "stub-ffi")]
+include!(concat!(env!("OUT_DIR"), "/stub_wrappers.rs"));
+
+/// Makes sure the native library is available before the first native call.
+///
+/// Only does work with the `runtime-dlopen` feature; a linked library is
+/// loaded by the platform at process start. With `stub-ffi` there is no
+/// library and this always fails.
+pub(crate) fn ensure_loaded() -> crate::error::SzResult<()> {
+    #[cfg(feature = "stub-ffi")]
+    {
+        Err(stub::error())
+    }
+    #[cfg(all(feature = "runtime-dlopen", not(feature = "stub-ffi")))]
+    {
+        dynamic::library().map(|_| ())
+    }
+    #[cfg(not(any(feature = "runtime-dlopen", feature = "stub-ffi")))]
+    {
+        Ok(())
+    }
+}
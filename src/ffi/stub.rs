@@ -0,0 +1,19 @@
+//! Stub native library (`stub-ffi` feature)
+//!
+//! Lets the crate build, and its documentation and mock-based tests run, on
+//! machines without Senzing installed. No native function is available.
+
+use crate::error::SzError;
+
+const MESSAGE: &str = "Senzing native library is not available: sz-rust-sdk was built with \
+                       the stub-ffi feature";
+
+/// The error [`super::ensure_loaded`] returns.
+pub(crate) fn error() -> SzError {
+    SzError::configuration(MESSAGE)
+}
+
+/// Called by every generated stub in place of the native function.
+pub(crate) fn unavailable(name: &str) -> ! {
+    panic!("{MESSAGE} ({name} called)")
+}
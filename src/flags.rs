@@ -399,3 +399,130 @@ impl SzFlags {
         flags.unwrap_or(default).bits() as i64
     }
 }
+
+bitflags! {
+    /// Kinds of relationship between entities, combinable with `|`.
+    ///
+    /// Used by `SzEngine::get_entity_relationships` to pick which
+    /// `ENTITY_INCLUDE_*_RELATIONS` flags to send, and to classify path hops
+    /// in [`models::FindPathResponse`](crate::models::FindPathResponse).
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct RelationshipKind: u8 {
+        /// Disclosed relationship (`REL_POINTER` / `REL_ANCHOR` records)
+        const DISCLOSED = 1 << 0;
+        /// `POSSIBLY_SAME`
+        const POSSIBLY_SAME = 1 << 1;
+        /// `POSSIBLY_RELATED`
+        const POSSIBLY_RELATED = 1 << 2;
+        /// `NAME_ONLY`
+        const NAME_ONLY = 1 << 3;
+    }
+}
+
+impl RelationshipKind {
+    /// Classifies a relationship by its match level code. Disclosed
+    /// relationships take precedence over the match level; an unknown code
+    /// yields the empty set.
+    pub fn classify(match_level_code: Option<&str>, is_disclosed: bool) -> Self {
+        if is_disclosed {
+            return Self::DISCLOSED;
+        }
+        match match_level_code {
+            Some("DISCLOSED") => Self::DISCLOSED,
+            Some("POSSIBLY_SAME") => Self::POSSIBLY_SAME,
+            Some("POSSIBLY_RELATED") => Self::POSSIBLY_RELATED,
+            Some("NAME_ONLY") => Self::NAME_ONLY,
+            _ => Self::empty(),
+        }
+    }
+
+    /// The `ENTITY_INCLUDE_*_RELATIONS` flags that return these kinds.
+    pub fn entity_flags(self) -> SzFlags {
+        let mut flags = SzFlags::empty();
+        for (kind, flag) in [
+            (Self::DISCLOSED, SzFlags::ENTITY_INCLUDE_DISCLOSED_RELATIONS),
+            (
+                Self::POSSIBLY_SAME,
+                SzFlags::ENTITY_INCLUDE_POSSIBLY_SAME_RELATIONS,
+            ),
+            (
+                Self::POSSIBLY_RELATED,
+                SzFlags::ENTITY_INCLUDE_POSSIBLY_RELATED_RELATIONS,
+            ),
+            (Self::NAME_ONLY, SzFlags::ENTITY_INCLUDE_NAME_ONLY_RELATIONS),
+        ] {
+            if self.contains(kind) {
+                flags |= flag;
+            }
+        }
+        flags
+    }
+}
+
+bitflags! {
+    /// Match levels of search results, combinable with `|`.
+    ///
+    /// Used to filter a [`models::SearchResponse`](crate::models::SearchResponse)
+    /// and to pick the `SEARCH_INCLUDE_*` flags that return these levels.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct MatchLevel: u8 {
+        /// `RESOLVED`: the search attributes would resolve into the entity
+        const RESOLVED = 1 << 0;
+        /// `POSSIBLY_SAME`
+        const POSSIBLY_SAME = 1 << 1;
+        /// `POSSIBLY_RELATED`
+        const POSSIBLY_RELATED = 1 << 2;
+        /// `NAME_ONLY`
+        const NAME_ONLY = 1 << 3;
+    }
+}
+
+impl MatchLevel {
+    /// Classifies a search result by its match level code; an unknown code
+    /// yields the empty set.
+    pub fn classify(match_level_code: Option<&str>) -> Self {
+        match match_level_code {
+            Some("RESOLVED") => Self::RESOLVED,
+            Some("POSSIBLY_SAME") => Self::POSSIBLY_SAME,
+            Some("POSSIBLY_RELATED") => Self::POSSIBLY_RELATED,
+            Some("NAME_ONLY") => Self::NAME_ONLY,
+            _ => Self::empty(),
+        }
+    }
+
+    /// The `SEARCH_INCLUDE_*` flags that return these levels.
+    pub fn search_flags(self) -> SzFlags {
+        let mut flags = SzFlags::empty();
+        for (level, flag) in [
+            (Self::RESOLVED, SzFlags::SEARCH_INCLUDE_RESOLVED),
+            (Self::POSSIBLY_SAME, SzFlags::SEARCH_INCLUDE_POSSIBLY_SAME),
+            (
+                Self::POSSIBLY_RELATED,
+                SzFlags::SEARCH_INCLUDE_POSSIBLY_RELATED,
+            ),
+            (Self::NAME_ONLY, SzFlags::SEARCH_INCLUDE_NAME_ONLY),
+        ] {
+            if self.contains(level) {
+                flags |= flag;
+            }
+        }
+        flags
+    }
+
+    /// Ranking weight of a single level, as in the engine's `MATCH_LEVEL`:
+    /// resolved 1, possibly same 2, possibly related 3, name only 4, other 5.
+    /// Lower is a stronger match.
+    pub fn weight(self) -> u32 {
+        if self == Self::RESOLVED {
+            1
+        } else if self == Self::POSSIBLY_SAME {
+            2
+        } else if self == Self::POSSIBLY_RELATED {
+            3
+        } else if self == Self::NAME_ONLY {
+            4
+        } else {
+            5
+        }
+    }
+}
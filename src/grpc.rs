@@ -0,0 +1,855 @@
+//! A gRPC server for the Senzing engine and product protos.
+//!
+//! [`EngineService`] and [`ProductService`] implement the `szengine.SzEngine`
+//! and `szproduct.SzProduct` services of the Senzing gRPC protos
+//! (`proto/*.proto`) over this SDK, so clients in other languages can use a
+//! Rust process as their resolution service. [`serve`] runs both; the
+//! `sz-grpc-server` binary wraps it.
+//!
+//! Requests follow the Senzing gRPC conventions:
+//!
+//! - `flags` are passed to the engine as given, so clients send the
+//!   operation's default flags themselves.
+//! - Lists are JSON documents: entity IDs as
+//!   `{"ENTITIES":[{"ENTITY_ID":1}]}`, record keys as
+//!   `{"RECORDS":[{"DATA_SOURCE":"CUSTOMERS","RECORD_ID":"1001"}]}` and data
+//!   sources as `{"DATA_SOURCES":["CUSTOMERS"]}`. An empty string means none.
+//! - An empty `search_profile` uses the default profile.
+//!
+//! Engine calls run on tokio's blocking thread pool. Errors become a
+//! `Status` with the code from [`status`] and a JSON message,
+//! `{"category":..,"code":..,"message":..}`, with record values redacted
+//! while [`redact`](crate::redact) is on. The config services of the protos
+//! are not served; manage configurations with the SDK or the Senzing tools.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sz_rust_sdk::prelude::*;
+//!
+//! # async fn run() -> SzResult<()> {
+//! let env = SzEnvironmentCore::get_instance("grpc", "{}", false)?;
+//! sz_rust_sdk::grpc::serve(env, "0.0.0.0:8261".parse().unwrap()).await
+//! # }
+//! ```
+
+use crate::context::SzCallContext;
+use crate::core::SzEnvironmentCore;
+use crate::error::{SzError, SzResult};
+use crate::flags::SzFlags;
+use crate::redact;
+use crate::traits::{SzEngine, SzEnvironment, SzProduct};
+use crate::types::{ConfigId, EntityId, EntityRef, RecordKey};
+use futures_util::Stream;
+use serde::Deserialize;
+use serde_json::json;
+use std::collections::HashSet;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tonic::{Code, Request, Response, Status};
+
+/// Generated messages and server of `proto/szengine.proto`.
+#[allow(missing_docs, clippy::all)]
+pub mod szengine {
+    tonic::include_proto!("szengine");
+}
+
+/// Generated messages and server of `proto/szproduct.proto`.
+#[allow(missing_docs, clippy::all)]
+pub mod szproduct {
+    tonic::include_proto!("szproduct");
+}
+
+use szengine::sz_engine_server::SzEngineServer;
+use szengine::*;
+use szproduct::sz_product_server::SzProductServer;
+use szproduct::*;
+
+/// Export chunks buffered for a streaming export before the engine waits
+/// for the client.
+const EXPORT_STREAM_BUFFER: usize = 64;
+
+/// Serves [`EngineService`] and [`ProductService`] on `addr` until the
+/// server fails.
+///
+/// # Errors
+///
+/// * Any error creating the services
+/// * `SzError::Unknown` - the server failed, e.g. `addr` is in use
+pub async fn serve(environment: Arc<SzEnvironmentCore>, addr: SocketAddr) -> SzResult<()> {
+    let engine = EngineService::new(Arc::clone(&environment))?;
+    let product = ProductService::new(&environment)?;
+    tonic::transport::Server::builder()
+        .add_service(SzEngineServer::new(engine))
+        .add_service(SzProductServer::new(product))
+        .serve(addr)
+        .await
+        .map_err(|e| SzError::unknown(format!("gRPC server failed: {e}")))
+}
+
+/// gRPC status for an error returned by the engine.
+pub fn status(error: &SzError) -> Status {
+    let code = match error {
+        SzError::BadInput(_) | SzError::Json(_) | SzError::StringConversion(_) => {
+            Code::InvalidArgument
+        }
+        SzError::NotFound(_) | SzError::UnknownDataSource(_) => Code::NotFound,
+        SzError::ReplaceConflict(_) | SzError::ExportInvalidated(_) => Code::Aborted,
+        SzError::ResultTooLarge { .. } => Code::ResourceExhausted,
+        SzError::License(_) | SzError::NotPermitted(_) => Code::PermissionDenied,
+        SzError::Timeout(_) => Code::DeadlineExceeded,
+        SzError::Cancelled(_) => Code::Cancelled,
+        SzError::NotInitialized(_) | SzError::EnvironmentDestroyed(_) => Code::FailedPrecondition,
+        SzError::Retryable(_)
+        | SzError::RetryTimeoutExceeded(_)
+        | SzError::DatabaseTransient(_)
+        | SzError::DatabaseConnectionLost(_) => Code::Unavailable,
+        _ => Code::Internal,
+    };
+    let message = json!({
+        "category": error.category(),
+        "code": error.error_code(),
+        "message": redact::message(&error.to_string()),
+    });
+    Status::new(code, message.to_string())
+}
+
+/// The `szengine.SzEngine` service; see the [module documentation](self).
+#[derive(Clone)]
+pub struct EngineService {
+    environment: Arc<SzEnvironmentCore>,
+    engine: Arc<dyn SzEngine>,
+}
+
+impl EngineService {
+    /// Creates the service with an engine from `environment`.
+    ///
+    /// # Errors
+    ///
+    /// Any error from `SzEnvironment::get_engine`.
+    pub fn new(environment: Arc<SzEnvironmentCore>) -> SzResult<Self> {
+        let engine = Arc::from(environment.get_engine()?);
+        Ok(Self {
+            environment,
+            engine,
+        })
+    }
+
+    /// Runs `call` with the engine on the blocking thread pool.
+    async fn call<T, F>(&self, call: F) -> Result<Response<T>, Status>
+    where
+        T: Send + 'static,
+        F: FnOnce(&dyn SzEngine) -> SzResult<T> + Send + 'static,
+    {
+        let engine = Arc::clone(&self.engine);
+        blocking(move || call(&*engine)).await
+    }
+
+    /// Streams an export in chunks of `fetch_next` output, closing the
+    /// export when done or when the client goes away.
+    fn stream_export<T, F>(&self, open: F, wrap: fn(String) -> T) -> ExportStream<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&dyn SzEngine) -> SzResult<i64> + Send + 'static,
+    {
+        let engine = Arc::clone(&self.engine);
+        let (sender, receiver) = mpsc::channel(EXPORT_STREAM_BUFFER);
+        tokio::task::spawn_blocking(move || {
+            let handle = match open(&*engine) {
+                Ok(handle) => handle,
+                Err(e) => {
+                    let _ = sender.blocking_send(Err(status(&e)));
+                    return;
+                }
+            };
+            loop {
+                let item = match engine.fetch_next(handle) {
+                    Ok(chunk) if chunk.is_empty() => break,
+                    Ok(chunk) => Ok(wrap(chunk)),
+                    Err(e) => Err(status(&e)),
+                };
+                let failed = item.is_err();
+                if sender.blocking_send(item).is_err() || failed {
+                    break;
+                }
+            }
+            let _ = engine.close_export_report(handle);
+        });
+        Box::pin(futures_util::stream::unfold(
+            receiver,
+            |mut receiver| async move { receiver.recv().await.map(|item| (item, receiver)) },
+        ))
+    }
+}
+
+type ExportStream<T> = Pin<Box<dyn Stream<Item = Result<T, Status>> + Send>>;
+
+#[tonic::async_trait]
+impl szengine::sz_engine_server::SzEngine for EngineService {
+    type StreamExportCsvEntityReportStream = ExportStream<StreamExportCsvEntityReportResponse>;
+    type StreamExportJsonEntityReportStream = ExportStream<StreamExportJsonEntityReportResponse>;
+
+    async fn add_record(
+        &self,
+        request: Request<AddRecordRequest>,
+    ) -> Result<Response<AddRecordResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let result = engine.add_record(
+                &r.data_source_code,
+                &r.record_id,
+                &r.record_definition,
+                flags(r.flags),
+            )?;
+            Ok(AddRecordResponse { result })
+        })
+        .await
+    }
+
+    async fn close_export_report(
+        &self,
+        request: Request<CloseExportReportRequest>,
+    ) -> Result<Response<CloseExportReportResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            engine.close_export_report(r.export_handle)?;
+            Ok(CloseExportReportResponse {})
+        })
+        .await
+    }
+
+    async fn count_redo_records(
+        &self,
+        _request: Request<CountRedoRecordsRequest>,
+    ) -> Result<Response<CountRedoRecordsResponse>, Status> {
+        self.call(|engine| {
+            let result = engine.count_redo_records()?;
+            Ok(CountRedoRecordsResponse { result })
+        })
+        .await
+    }
+
+    async fn delete_record(
+        &self,
+        request: Request<DeleteRecordRequest>,
+    ) -> Result<Response<DeleteRecordResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let result = engine.delete_record(&r.data_source_code, &r.record_id, flags(r.flags))?;
+            Ok(DeleteRecordResponse { result })
+        })
+        .await
+    }
+
+    async fn export_csv_entity_report(
+        &self,
+        request: Request<ExportCsvEntityReportRequest>,
+    ) -> Result<Response<ExportCsvEntityReportResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let result = engine.export_csv_entity_report(&r.csv_column_list, flags(r.flags))?;
+            Ok(ExportCsvEntityReportResponse { result })
+        })
+        .await
+    }
+
+    async fn export_json_entity_report(
+        &self,
+        request: Request<ExportJsonEntityReportRequest>,
+    ) -> Result<Response<ExportJsonEntityReportResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let result = engine.export_json_entity_report(flags(r.flags))?;
+            Ok(ExportJsonEntityReportResponse { result })
+        })
+        .await
+    }
+
+    async fn fetch_next(
+        &self,
+        request: Request<FetchNextRequest>,
+    ) -> Result<Response<FetchNextResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let result = engine.fetch_next(r.export_handle)?;
+            Ok(FetchNextResponse { result })
+        })
+        .await
+    }
+
+    async fn find_interesting_entities_by_entity_id(
+        &self,
+        request: Request<FindInterestingEntitiesByEntityIdRequest>,
+    ) -> Result<Response<FindInterestingEntitiesByEntityIdResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let result = engine
+                .find_interesting_entities(EntityRef::Id(EntityId(r.entity_id)), flags(r.flags))?;
+            Ok(FindInterestingEntitiesByEntityIdResponse { result })
+        })
+        .await
+    }
+
+    async fn find_interesting_entities_by_record_id(
+        &self,
+        request: Request<FindInterestingEntitiesByRecordIdRequest>,
+    ) -> Result<Response<FindInterestingEntitiesByRecordIdResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let entity_ref = EntityRef::Record {
+                data_source: &r.data_source_code,
+                record_id: &r.record_id,
+            };
+            let result = engine.find_interesting_entities(entity_ref, flags(r.flags))?;
+            Ok(FindInterestingEntitiesByRecordIdResponse { result })
+        })
+        .await
+    }
+
+    async fn find_network_by_entity_id(
+        &self,
+        request: Request<FindNetworkByEntityIdRequest>,
+    ) -> Result<Response<FindNetworkByEntityIdResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let result = engine.find_network_by_entity_id(
+                &entity_ids(&r.entity_ids)?,
+                r.max_degrees,
+                r.build_out_degrees,
+                r.build_out_max_entities,
+                flags(r.flags),
+            )?;
+            Ok(FindNetworkByEntityIdResponse { result })
+        })
+        .await
+    }
+
+    async fn find_network_by_record_id(
+        &self,
+        request: Request<FindNetworkByRecordIdRequest>,
+    ) -> Result<Response<FindNetworkByRecordIdResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let result = engine.find_network_by_record_id(
+                &record_keys(&r.record_keys)?,
+                r.max_degrees,
+                r.build_out_degrees,
+                r.build_out_max_entities,
+                flags(r.flags),
+            )?;
+            Ok(FindNetworkByRecordIdResponse { result })
+        })
+        .await
+    }
+
+    async fn find_path_by_entity_id(
+        &self,
+        request: Request<FindPathByEntityIdRequest>,
+    ) -> Result<Response<FindPathByEntityIdResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let avoid: HashSet<EntityId> = entity_ids(&r.avoid_entity_ids)?.into_iter().collect();
+            let required = data_sources(&r.required_data_sources)?;
+            let result = engine.find_path_by_entity_id(
+                EntityId(r.start_entity_id),
+                EntityId(r.end_entity_id),
+                r.max_degrees,
+                Some(&avoid).filter(|avoid| !avoid.is_empty()),
+                Some(&required).filter(|required| !required.is_empty()),
+                flags(r.flags),
+            )?;
+            Ok(FindPathByEntityIdResponse { result })
+        })
+        .await
+    }
+
+    async fn find_path_by_record_id(
+        &self,
+        request: Request<FindPathByRecordIdRequest>,
+    ) -> Result<Response<FindPathByRecordIdResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let avoid = record_keys(&r.avoid_record_keys)?;
+            let required = data_sources(&r.required_data_sources)?;
+            let result = engine.find_path_by_record_id(
+                &r.start_data_source_code,
+                &r.start_record_id,
+                &r.end_data_source_code,
+                &r.end_record_id,
+                r.max_degrees,
+                Some(&avoid[..]).filter(|avoid| !avoid.is_empty()),
+                Some(&required).filter(|required| !required.is_empty()),
+                flags(r.flags),
+            )?;
+            Ok(FindPathByRecordIdResponse { result })
+        })
+        .await
+    }
+
+    async fn get_active_config_id(
+        &self,
+        _request: Request<GetActiveConfigIdRequest>,
+    ) -> Result<Response<GetActiveConfigIdResponse>, Status> {
+        let environment = Arc::clone(&self.environment);
+        blocking(move || {
+            let result = environment.get_active_config_id()?.0;
+            Ok(GetActiveConfigIdResponse { result })
+        })
+        .await
+    }
+
+    async fn get_entity_by_entity_id(
+        &self,
+        request: Request<GetEntityByEntityIdRequest>,
+    ) -> Result<Response<GetEntityByEntityIdResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let result = engine.get_entity(EntityRef::Id(EntityId(r.entity_id)), flags(r.flags))?;
+            Ok(GetEntityByEntityIdResponse { result })
+        })
+        .await
+    }
+
+    async fn get_entity_by_record_id(
+        &self,
+        request: Request<GetEntityByRecordIdRequest>,
+    ) -> Result<Response<GetEntityByRecordIdResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let entity_ref = EntityRef::Record {
+                data_source: &r.data_source_code,
+                record_id: &r.record_id,
+            };
+            let result = engine.get_entity(entity_ref, flags(r.flags))?;
+            Ok(GetEntityByRecordIdResponse { result })
+        })
+        .await
+    }
+
+    async fn get_record(
+        &self,
+        request: Request<GetRecordRequest>,
+    ) -> Result<Response<GetRecordResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let result = engine.get_record(&r.data_source_code, &r.record_id, flags(r.flags))?;
+            Ok(GetRecordResponse { result })
+        })
+        .await
+    }
+
+    async fn get_record_preview(
+        &self,
+        request: Request<GetRecordPreviewRequest>,
+    ) -> Result<Response<GetRecordPreviewResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let result = engine.get_record_preview(&r.record_definition, flags(r.flags))?;
+            Ok(GetRecordPreviewResponse { result })
+        })
+        .await
+    }
+
+    async fn get_redo_record(
+        &self,
+        _request: Request<GetRedoRecordRequest>,
+    ) -> Result<Response<GetRedoRecordResponse>, Status> {
+        self.call(|engine| {
+            let result = engine.get_redo_record()?;
+            Ok(GetRedoRecordResponse { result })
+        })
+        .await
+    }
+
+    async fn get_stats(
+        &self,
+        _request: Request<GetStatsRequest>,
+    ) -> Result<Response<GetStatsResponse>, Status> {
+        self.call(|engine| {
+            let result = engine.get_stats()?;
+            Ok(GetStatsResponse { result })
+        })
+        .await
+    }
+
+    async fn get_virtual_entity_by_record_id(
+        &self,
+        request: Request<GetVirtualEntityByRecordIdRequest>,
+    ) -> Result<Response<GetVirtualEntityByRecordIdResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let result =
+                engine.get_virtual_entity(&record_keys(&r.record_keys)?, flags(r.flags))?;
+            Ok(GetVirtualEntityByRecordIdResponse { result })
+        })
+        .await
+    }
+
+    async fn how_entity_by_entity_id(
+        &self,
+        request: Request<HowEntityByEntityIdRequest>,
+    ) -> Result<Response<HowEntityByEntityIdResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let result = engine.how_entity(EntityId(r.entity_id), flags(r.flags))?;
+            Ok(HowEntityByEntityIdResponse { result })
+        })
+        .await
+    }
+
+    async fn prime_engine(
+        &self,
+        _request: Request<PrimeEngineRequest>,
+    ) -> Result<Response<PrimeEngineResponse>, Status> {
+        self.call(|engine| {
+            engine.prime_engine()?;
+            Ok(PrimeEngineResponse {})
+        })
+        .await
+    }
+
+    async fn process_redo_record(
+        &self,
+        request: Request<ProcessRedoRecordRequest>,
+    ) -> Result<Response<ProcessRedoRecordResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let result = engine.process_redo_record(&r.redo_record, flags(r.flags))?;
+            Ok(ProcessRedoRecordResponse { result })
+        })
+        .await
+    }
+
+    async fn reevaluate_entity(
+        &self,
+        request: Request<ReevaluateEntityRequest>,
+    ) -> Result<Response<ReevaluateEntityResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let result = engine.reevaluate_entity(EntityId(r.entity_id), flags(r.flags))?;
+            Ok(ReevaluateEntityResponse { result })
+        })
+        .await
+    }
+
+    async fn reevaluate_record(
+        &self,
+        request: Request<ReevaluateRecordRequest>,
+    ) -> Result<Response<ReevaluateRecordResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let result =
+                engine.reevaluate_record(&r.data_source_code, &r.record_id, flags(r.flags))?;
+            Ok(ReevaluateRecordResponse { result })
+        })
+        .await
+    }
+
+    async fn reinitialize(
+        &self,
+        request: Request<ReinitializeRequest>,
+    ) -> Result<Response<ReinitializeResponse>, Status> {
+        let r = request.into_inner();
+        let environment = Arc::clone(&self.environment);
+        blocking(move || {
+            environment.reinitialize(ConfigId(r.config_id))?;
+            Ok(ReinitializeResponse {})
+        })
+        .await
+    }
+
+    async fn search_by_attributes(
+        &self,
+        request: Request<SearchByAttributesRequest>,
+    ) -> Result<Response<SearchByAttributesResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let result = engine.search_by_attributes(
+                &r.attributes,
+                Some(r.search_profile.as_str()).filter(|profile| !profile.is_empty()),
+                flags(r.flags),
+            )?;
+            Ok(SearchByAttributesResponse { result })
+        })
+        .await
+    }
+
+    async fn stream_export_csv_entity_report(
+        &self,
+        request: Request<StreamExportCsvEntityReportRequest>,
+    ) -> Result<Response<Self::StreamExportCsvEntityReportStream>, Status> {
+        let r = request.into_inner();
+        Ok(Response::new(self.stream_export(
+            move |engine| engine.export_csv_entity_report(&r.csv_column_list, flags(r.flags)),
+            |result| StreamExportCsvEntityReportResponse { result },
+        )))
+    }
+
+    async fn stream_export_json_entity_report(
+        &self,
+        request: Request<StreamExportJsonEntityReportRequest>,
+    ) -> Result<Response<Self::StreamExportJsonEntityReportStream>, Status> {
+        let r = request.into_inner();
+        Ok(Response::new(self.stream_export(
+            move |engine| engine.export_json_entity_report(flags(r.flags)),
+            |result| StreamExportJsonEntityReportResponse { result },
+        )))
+    }
+
+    async fn why_entities(
+        &self,
+        request: Request<WhyEntitiesRequest>,
+    ) -> Result<Response<WhyEntitiesResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let result = engine.why_entities(
+                EntityId(r.entity_id_1),
+                EntityId(r.entity_id_2),
+                flags(r.flags),
+            )?;
+            Ok(WhyEntitiesResponse { result })
+        })
+        .await
+    }
+
+    async fn why_record_in_entity(
+        &self,
+        request: Request<WhyRecordInEntityRequest>,
+    ) -> Result<Response<WhyRecordInEntityResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let result =
+                engine.why_record_in_entity(&r.data_source_code, &r.record_id, flags(r.flags))?;
+            Ok(WhyRecordInEntityResponse { result })
+        })
+        .await
+    }
+
+    async fn why_records(
+        &self,
+        request: Request<WhyRecordsRequest>,
+    ) -> Result<Response<WhyRecordsResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let result = engine.why_records(
+                &r.data_source_code_1,
+                &r.record_id_1,
+                &r.data_source_code_2,
+                &r.record_id_2,
+                flags(r.flags),
+            )?;
+            Ok(WhyRecordsResponse { result })
+        })
+        .await
+    }
+
+    async fn why_search(
+        &self,
+        request: Request<WhySearchRequest>,
+    ) -> Result<Response<WhySearchResponse>, Status> {
+        let r = request.into_inner();
+        self.call(move |engine| {
+            let result = engine.why_search(
+                &r.attributes,
+                EntityId(r.entity_id),
+                Some(r.search_profile.as_str()).filter(|profile| !profile.is_empty()),
+                flags(r.flags),
+            )?;
+            Ok(WhySearchResponse { result })
+        })
+        .await
+    }
+}
+
+/// The `szproduct.SzProduct` service.
+#[derive(Clone)]
+pub struct ProductService {
+    product: Arc<dyn SzProduct>,
+}
+
+impl ProductService {
+    /// Creates the service with the product interface of `environment`.
+    ///
+    /// # Errors
+    ///
+    /// Any error from `SzEnvironment::get_product`.
+    pub fn new(environment: &SzEnvironmentCore) -> SzResult<Self> {
+        Ok(Self {
+            product: Arc::from(environment.get_product()?),
+        })
+    }
+}
+
+#[tonic::async_trait]
+impl szproduct::sz_product_server::SzProduct for ProductService {
+    async fn get_license(
+        &self,
+        _request: Request<GetLicenseRequest>,
+    ) -> Result<Response<GetLicenseResponse>, Status> {
+        let product = Arc::clone(&self.product);
+        blocking(move || {
+            let result = product.get_license()?;
+            Ok(GetLicenseResponse { result })
+        })
+        .await
+    }
+
+    async fn get_version(
+        &self,
+        _request: Request<GetVersionRequest>,
+    ) -> Result<Response<GetVersionResponse>, Status> {
+        let product = Arc::clone(&self.product);
+        blocking(move || {
+            let result = product.get_version()?;
+            Ok(GetVersionResponse { result })
+        })
+        .await
+    }
+}
+
+/// Runs `call` on the blocking thread pool, in the handler's
+/// `SzCallContext` and, with the `otel` feature, its span, and wraps its
+/// result.
+async fn blocking<T, F>(call: F) -> Result<Response<T>, Status>
+where
+    T: Send + 'static,
+    F: FnOnce() -> SzResult<T> + Send + 'static,
+{
+    let call = {
+        let context = SzCallContext::current();
+        move || context.scope(call)
+    };
+    #[cfg(feature = "otel")]
+    let call = {
+        let span = tracing::Span::current();
+        move || span.in_scope(call)
+    };
+    match tokio::task::spawn_blocking(call).await {
+        Ok(Ok(value)) => Ok(Response::new(value)),
+        Ok(Err(e)) => Err(status(&e)),
+        Err(e) => Err(status(&SzError::unknown(format!(
+            "Engine call failed: {e}"
+        )))),
+    }
+}
+
+/// Flags exactly as the client sent them.
+fn flags(bits: i64) -> Option<SzFlags> {
+    Some(SzFlags::from_bits_retain(bits as u64))
+}
+
+#[derive(Deserialize)]
+struct EntityList {
+    #[serde(rename = "ENTITIES", default)]
+    entities: Vec<EntityEntry>,
+}
+
+#[derive(Deserialize)]
+struct EntityEntry {
+    #[serde(rename = "ENTITY_ID")]
+    entity_id: i64,
+}
+
+#[derive(Deserialize)]
+struct RecordList {
+    #[serde(rename = "RECORDS", default)]
+    records: Vec<RecordEntry>,
+}
+
+#[derive(Deserialize)]
+struct RecordEntry {
+    #[serde(rename = "DATA_SOURCE")]
+    data_source: String,
+    #[serde(rename = "RECORD_ID")]
+    record_id: String,
+}
+
+#[derive(Deserialize)]
+struct DataSourceList {
+    #[serde(rename = "DATA_SOURCES", default)]
+    data_sources: HashSet<String>,
+}
+
+/// Parses `{"ENTITIES":[{"ENTITY_ID":..}]}`; empty for an empty string.
+fn entity_ids(json: &str) -> SzResult<Vec<EntityId>> {
+    if json.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let list: EntityList = serde_json::from_str(json)?;
+    Ok(list
+        .entities
+        .into_iter()
+        .map(|entry| EntityId(entry.entity_id))
+        .collect())
+}
+
+/// Parses `{"RECORDS":[{"DATA_SOURCE":..,"RECORD_ID":..}]}`; empty for an
+/// empty string.
+fn record_keys(json: &str) -> SzResult<Vec<RecordKey>> {
+    if json.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let list: RecordList = serde_json::from_str(json)?;
+    list.records
+        .into_iter()
+        .map(|entry| RecordKey::new(entry.data_source, entry.record_id))
+        .collect()
+}
+
+/// Parses `{"DATA_SOURCES":[..]}`; empty for an empty string.
+fn data_sources(json: &str) -> SzResult<HashSet<String>> {
+    if json.trim().is_empty() {
+        return Ok(HashSet::new());
+    }
+    let list: DataSourceList = serde_json::from_str(json)?;
+    Ok(list.data_sources)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_parameters() {
+        assert_eq!(
+            entity_ids(r#"{"ENTITIES":[{"ENTITY_ID":1},{"ENTITY_ID":7}]}"#).unwrap(),
+            vec![EntityId(1), EntityId(7)]
+        );
+        assert!(entity_ids("").unwrap().is_empty());
+        assert!(matches!(entity_ids("[1]"), Err(SzError::Json(_))));
+
+        let keys = record_keys(r#"{"RECORDS":[{"DATA_SOURCE":"crm","RECORD_ID":"1"}]}"#).unwrap();
+        assert_eq!(keys, vec![RecordKey::new("CRM", "1").unwrap()]);
+        assert!(record_keys(r#"{"RECORDS":[{"DATA_SOURCE":"CRM","RECORD_ID":""}]}"#).is_err());
+
+        assert_eq!(
+            data_sources(r#"{"DATA_SOURCES":["CRM"]}"#).unwrap(),
+            HashSet::from(["CRM".to_string()])
+        );
+        assert!(data_sources(" ").unwrap().is_empty());
+
+        assert_eq!(flags(0), Some(SzFlags::empty()));
+        assert_eq!(
+            flags(SzFlags::WITH_INFO.bits() as i64),
+            Some(SzFlags::WITH_INFO)
+        );
+    }
+
+    #[test]
+    fn test_error_status() {
+        assert_eq!(
+            status(&SzError::bad_input("x")).code(),
+            Code::InvalidArgument
+        );
+        assert_eq!(status(&SzError::retryable("x")).code(), Code::Unavailable);
+        assert_eq!(status(&SzError::unrecoverable("x")).code(), Code::Internal);
+
+        let status = status(&SzError::not_found("Unknown record: 'CUSTOMERS' '1001'"));
+        assert_eq!(status.code(), Code::NotFound);
+        let message: serde_json::Value = serde_json::from_str(status.message()).unwrap();
+        assert_eq!(message["category"], "bad_input");
+        assert!(!message["message"].as_str().unwrap().contains("CUSTOMERS"));
+    }
+}
@@ -0,0 +1,331 @@
+//! Health checks for liveness and readiness probes.
+//!
+//! [`SzHealth`] runs a fixed set of checks against an environment and
+//! returns a [`HealthReport`] with one [`HealthCheck`] per component. The
+//! report serializes to JSON, so it can be returned directly from a probe
+//! endpoint.
+//!
+//! | Check | Unhealthy when | Degraded when |
+//! |-------|----------------|---------------|
+//! | [`Engine`](HealthComponent::Engine) | the engine cannot be initialized | — |
+//! | [`Search`](HealthComponent::Search) | a trivial search fails | — |
+//! | [`License`](HealthComponent::License) | the license has expired or cannot be read | it expires within the warning window |
+//! | [`Database`](HealthComponent::Database) | the repository performance ping fails | — |
+//! | [`Config`](HealthComponent::Config) | the config IDs cannot be read | the active config is not the default |
+//!
+//! # Example
+//!
+//! ```
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::health::SzHealth;
+//! use sz_rust_sdk::prelude::*;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_health")?;
+//! let report = SzHealth::check(&env);
+//! println!("{}", serde_json::to_string(&report)?);
+//! if !report.is_ready() {
+//!     eprintln!("not ready: {:?}", report.status);
+//! }
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::core::SzEnvironmentCore;
+use crate::error::SzResult;
+use crate::flags::SzFlags;
+use crate::traits::SzEnvironment;
+use serde::Serialize;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+/// Default number of days before license expiry that the license check
+/// reports [`HealthStatus::Degraded`].
+pub const DEFAULT_LICENSE_WARNING_DAYS: i64 = 30;
+
+/// Default duration of the repository performance ping, in seconds.
+pub const DEFAULT_PING_SECONDS: i64 = 1;
+
+/// Outcome of a check, ordered from best to worst.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthStatus {
+    /// Working normally
+    Healthy,
+    /// Working, but needs attention
+    Degraded,
+    /// Not working
+    Unhealthy,
+}
+
+/// What a [`HealthCheck`] verified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthComponent {
+    /// Native engine initialization
+    Engine,
+    /// A trivial search by attributes
+    Search,
+    /// License validity and expiry
+    License,
+    /// Database connectivity, via the diagnostic performance ping
+    Database,
+    /// Active configuration is the current default
+    Config,
+}
+
+/// Result of a single check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HealthCheck {
+    /// Component checked
+    pub component: HealthComponent,
+    /// Outcome
+    pub status: HealthStatus,
+    /// Human-readable detail, such as an error message
+    pub detail: String,
+    /// Time the check took, in milliseconds
+    pub duration_ms: u64,
+}
+
+/// Result of [`SzHealth::run`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HealthReport {
+    /// Worst status of all checks
+    pub status: HealthStatus,
+    /// Individual checks, in the order they ran
+    pub checks: Vec<HealthCheck>,
+}
+
+impl HealthReport {
+    /// Liveness: the native engine is initialized.
+    pub fn is_live(&self) -> bool {
+        self.check(HealthComponent::Engine)
+            .is_some_and(|check| check.status != HealthStatus::Unhealthy)
+    }
+
+    /// Readiness: no check is unhealthy.
+    pub fn is_ready(&self) -> bool {
+        self.status != HealthStatus::Unhealthy
+    }
+
+    /// Returns the check for a component, if it ran.
+    pub fn check(&self, component: HealthComponent) -> Option<&HealthCheck> {
+        self.checks
+            .iter()
+            .find(|check| check.component == component)
+    }
+}
+
+/// Health checker with configurable thresholds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SzHealth {
+    license_warning_days: i64,
+    ping_seconds: i64,
+}
+
+impl Default for SzHealth {
+    fn default() -> Self {
+        Self {
+            license_warning_days: DEFAULT_LICENSE_WARNING_DAYS,
+            ping_seconds: DEFAULT_PING_SECONDS,
+        }
+    }
+}
+
+impl SzHealth {
+    /// Creates a checker with the default thresholds.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs all checks with the default thresholds.
+    pub fn check(env: &SzEnvironmentCore) -> HealthReport {
+        Self::default().run(env)
+    }
+
+    /// Sets how many days before expiry the license is reported degraded.
+    pub fn license_warning_days(mut self, days: i64) -> Self {
+        self.license_warning_days = days;
+        self
+    }
+
+    /// Sets the duration of the repository performance ping in seconds.
+    /// A value below 1 is treated as 1.
+    pub fn ping_seconds(mut self, seconds: i64) -> Self {
+        self.ping_seconds = seconds.max(1);
+        self
+    }
+
+    /// Runs all checks against `env`.
+    ///
+    /// When the engine cannot be initialized the remaining checks are
+    /// skipped, since they all depend on it.
+    pub fn run(&self, env: &SzEnvironmentCore) -> HealthReport {
+        let mut checks = Vec::new();
+
+        let engine = timed(HealthComponent::Engine, &mut checks, || {
+            env.get_engine()
+                .map(|engine| (HealthStatus::Healthy, String::new(), engine))
+        });
+        if let Some(engine) = engine {
+            timed(HealthComponent::Search, &mut checks, || {
+                engine.search_by_attributes(
+                    r#"{"NAME_FULL":"Senzing Health Check"}"#,
+                    None,
+                    Some(SzFlags::SEARCH_BY_ATTRIBUTES_MINIMAL_STRONG),
+                )?;
+                Ok((HealthStatus::Healthy, String::new(), ()))
+            });
+            timed(HealthComponent::License, &mut checks, || {
+                let license = env.get_product()?.get_license()?;
+                Ok((self.license_status(&license)?, license_detail(&license), ()))
+            });
+            timed(HealthComponent::Database, &mut checks, || {
+                let result = env
+                    .get_diagnostic()?
+                    .check_repository_performance(self.ping_seconds)?;
+                Ok((HealthStatus::Healthy, result, ()))
+            });
+            timed(HealthComponent::Config, &mut checks, || {
+                let active = env.get_active_config_id()?;
+                let default = env.get_config_manager()?.get_default_config_id()?;
+                if active == default {
+                    Ok((HealthStatus::Healthy, format!("config {active}"), ()))
+                } else {
+                    Ok((
+                        HealthStatus::Degraded,
+                        format!("active config {active} is not the default {default}"),
+                        (),
+                    ))
+                }
+            });
+        }
+
+        let status = checks
+            .iter()
+            .map(|check| check.status)
+            .max()
+            .unwrap_or(HealthStatus::Healthy);
+        HealthReport { status, checks }
+    }
+
+    fn license_status(&self, license: &str) -> SzResult<HealthStatus> {
+        let license: serde_json::Value = serde_json::from_str(license)?;
+        let Some(expires) = license["expireDate"].as_str().and_then(days_from_date) else {
+            // No expiry date: nothing to warn about
+            return Ok(HealthStatus::Healthy);
+        };
+        let today = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| (d.as_secs() / 86_400) as i64);
+        Ok(expiry_status(expires - today, self.license_warning_days))
+    }
+}
+
+/// Runs one check, records it, and returns its value on success.
+fn timed<T>(
+    component: HealthComponent,
+    checks: &mut Vec<HealthCheck>,
+    check: impl FnOnce() -> SzResult<(HealthStatus, String, T)>,
+) -> Option<T> {
+    let start = Instant::now();
+    let result = check();
+    let duration_ms = u64::try_from(start.elapsed().as_millis()).unwrap_or(u64::MAX);
+    let (status, detail, value) = match result {
+        Ok((status, detail, value)) => (status, detail, Some(value)),
+        Err(e) => (HealthStatus::Unhealthy, e.to_string(), None),
+    };
+    checks.push(HealthCheck {
+        component,
+        status,
+        detail,
+        duration_ms,
+    });
+    value
+}
+
+fn license_detail(license: &str) -> String {
+    let license: serde_json::Value = serde_json::from_str(license).unwrap_or_default();
+    match license["expireDate"].as_str() {
+        Some(date) => format!("expires {date}"),
+        None => String::new(),
+    }
+}
+
+fn expiry_status(days_left: i64, warning_days: i64) -> HealthStatus {
+    if days_left < 0 {
+        HealthStatus::Unhealthy
+    } else if days_left <= warning_days {
+        HealthStatus::Degraded
+    } else {
+        HealthStatus::Healthy
+    }
+}
+
+/// Days since 1970-01-01 for a `YYYY-MM-DD` date (proleptic Gregorian).
+pub(crate) fn days_from_date(date: &str) -> Option<i64> {
+    let mut parts = date.get(..10)?.splitn(3, '-');
+    let year: i64 = parts.next()?.parse().ok()?;
+    let month: i64 = parts.next()?.parse().ok()?;
+    let day: i64 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    // Howard Hinnant's days_from_civil
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    Some(era * 146_097 + doe - 719_468)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days_from_date() {
+        assert_eq!(days_from_date("1970-01-01"), Some(0));
+        assert_eq!(days_from_date("2000-03-01"), Some(11_017));
+        assert_eq!(days_from_date("2026-06-12T00:00:00"), Some(20_616));
+        assert_eq!(days_from_date("2026-13-01"), None);
+        assert_eq!(days_from_date("soon"), None);
+    }
+
+    #[test]
+    fn test_expiry_status() {
+        assert_eq!(expiry_status(-1, 30), HealthStatus::Unhealthy);
+        assert_eq!(expiry_status(0, 30), HealthStatus::Degraded);
+        assert_eq!(expiry_status(30, 30), HealthStatus::Degraded);
+        assert_eq!(expiry_status(31, 30), HealthStatus::Healthy);
+    }
+
+    #[test]
+    fn test_report_status() {
+        let check = |component, status| HealthCheck {
+            component,
+            status,
+            detail: String::new(),
+            duration_ms: 0,
+        };
+        let report = HealthReport {
+            status: HealthStatus::Degraded,
+            checks: vec![
+                check(HealthComponent::Engine, HealthStatus::Healthy),
+                check(HealthComponent::Config, HealthStatus::Degraded),
+            ],
+        };
+        assert!(report.is_live());
+        assert!(report.is_ready());
+        assert_eq!(
+            serde_json::to_value(&report).unwrap()["checks"][1]["status"],
+            "degraded"
+        );
+
+        let report = HealthReport {
+            status: HealthStatus::Unhealthy,
+            checks: vec![check(HealthComponent::Engine, HealthStatus::Unhealthy)],
+        };
+        assert!(!report.is_live());
+        assert!(!report.is_ready());
+    }
+}
@@ -11,78 +11,6 @@
 //! setup when needed.
 
 use crate::prelude::*;
-use std::path::Path;
-
-/// Detected Senzing installation paths for building the engine init JSON.
-///
-/// These correspond to the `PIPELINE` section of the Senzing engine configuration:
-/// <https://www.senzing.com/docs/tutorials/senzing_engine_config/>
-struct SenzingPaths {
-    config_path: String,
-    resource_path: String,
-    support_path: String,
-}
-
-/// Auto-detect Senzing installation paths based on platform.
-///
-/// Checks standard installation locations in priority order:
-/// 1. macOS Homebrew official cask (Apple Silicon): `/opt/homebrew/opt/senzing`
-/// 2. macOS Homebrew official cask (Intel): `/usr/local/opt/senzing`
-/// 3. macOS Homebrew legacy unofficial tap: `.../senzing/runtime`
-/// 4. Linux standard: `/opt/senzing` with `/etc/opt/senzing` for config
-///
-/// This matches the detection logic used by `build.rs` for library linking.
-fn to_forward_slashes(p: &Path) -> String {
-    p.to_string_lossy().replace('\\', "/")
-}
-
-fn detect_senzing_paths() -> SenzingPaths {
-    // Priority 1: SENZING_DIR env var (set by Scoop on Windows, or manual override)
-    if let Ok(senzing_dir) = std::env::var("SENZING_DIR") {
-        let base = Path::new(&senzing_dir);
-        if base.join("resources").exists() {
-            // Senzing config JSON requires forward slashes even on Windows
-            return SenzingPaths {
-                config_path: to_forward_slashes(&base.join("resources/templates")),
-                resource_path: to_forward_slashes(&base.join("resources")),
-                support_path: to_forward_slashes(
-                    &base
-                        .parent()
-                        .map_or_else(|| base.join("../data"), |p| p.join("data")),
-                ),
-            };
-        }
-    }
-
-    // Priority 2: macOS Homebrew — official cask first, then legacy unofficial
-    for homebrew_base in [
-        "/opt/homebrew/opt/senzing",
-        "/usr/local/opt/senzing",
-        "/opt/homebrew/opt/senzing/runtime",
-        "/usr/local/opt/senzing/runtime",
-    ] {
-        if Path::new(&format!("{homebrew_base}/er/resources")).exists() {
-            return SenzingPaths {
-                config_path: format!("{homebrew_base}/er/resources/templates"),
-                resource_path: format!("{homebrew_base}/er/resources"),
-                support_path: format!("{homebrew_base}/data"),
-            };
-        }
-    }
-
-    // Priority 3: Linux standard
-    let config_path = if Path::new("/etc/opt/senzing").exists() {
-        "/etc/opt/senzing".to_string()
-    } else {
-        "/opt/senzing/er/resources/templates".to_string()
-    };
-
-    SenzingPaths {
-        config_path,
-        resource_path: "/opt/senzing/er/resources".to_string(),
-        support_path: "/opt/senzing/data".to_string(),
-    }
-}
 
 /// Enhanced error reporting for examples with backtrace support
 ///
@@ -284,7 +212,7 @@ impl ExampleEnvironment {
             return Ok(config);
         }
 
-        let paths = detect_senzing_paths();
+        let paths = crate::native::detect_pipeline_paths();
         let debug = if verbose { r#","DEBUGLEVEL":"2""# } else { "" };
         let config = format!(
             r#"{{"PIPELINE":{{"CONFIGPATH":"{}","RESOURCEPATH":"{}","SUPPORTPATH":"{}"}},"SQL":{{"CONNECTION":"internal://"{debug}}}}}"#,
@@ -0,0 +1,517 @@
+//! Loading a backfill and a live stream at the same time.
+//!
+//! A migration usually loads a snapshot of the source from files while the
+//! source keeps changing. A change stream (Kafka, CDC) carries those changes.
+//! Waiting for the backfill to finish before consuming the stream makes the
+//! cutover late. Consuming both at once lets an old backfill version of a
+//! record overwrite a newer stream version.
+//!
+//! A [`DualWriteLoader`] takes both and reconciles them per record by the
+//! record's timestamp, an attribute named by
+//! [`with_timestamp_field`](DualWriteLoader::with_timestamp_field)
+//! ([`DEFAULT_TIMESTAMP_FIELD`] by default):
+//!
+//! - A stream write is applied unless the repository already holds a newer
+//!   version of the record, from the stream or the backfill.
+//! - A backfill write is applied unless the stream has already written or
+//!   deleted the record at the same or a later time.
+//! - Ties, and records without a timestamp, go to the stream.
+//!
+//! Writes to the same record are serialized, so the outcome does not depend
+//! on which thread gets there first. When the backfill is done, the
+//! repository holds the newest version of every record and the stream can
+//! be written directly: call [`cutover`](DualWriteLoader::cutover) and
+//! switch the consumer over.
+//!
+//! The loader remembers the timestamp of every record the stream wrote
+//! until cutover. Backfill records are not remembered; a stream write for a
+//! record the stream has not seen yet reads the stored version's timestamp
+//! with `get_record` instead.
+//!
+//! The loader also tracks the latest timestamp loaded per data source, for
+//! scheduling the next incremental extract; see
+//! [`watermarks`](DualWriteLoader::watermarks) and the
+//! [`watermark`](super::watermark) module.
+//!
+//! # Timestamps
+//!
+//! A timestamp is an integer, or a string holding one, compared as given;
+//! or an ISO 8601 date-time such as `2026-03-01T12:30:00Z` or
+//! `2026-03-01T14:30:00.250+02:00`, converted to milliseconds since the
+//! epoch. Use one form per source so that values compare correctly.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use std::fs::File;
+//! use std::io::BufReader;
+//! use sz_rust_sdk::ingest::DualWriteLoader;
+//! use sz_rust_sdk::prelude::*;
+//!
+//! # fn next_message() -> Option<String> { None }
+//! # let env = ExampleEnvironment::initialize("doctest_dual_write")?;
+//! let engine = env.get_engine()?;
+//! let loader = DualWriteLoader::new(&*engine);
+//! std::thread::scope(|scope| {
+//!     let backfill = scope.spawn(|| {
+//!         let file = File::open("customers.jsonl")
+//!             .map_err(|e| SzError::bad_input(e.to_string()))?;
+//!         loader.backfill_from_reader(BufReader::new(file), None)
+//!     });
+//!     while !backfill.is_finished() {
+//!         if let Some(message) = next_message() {
+//!             loader.stream_record(&message)?;
+//!         }
+//!     }
+//!     backfill.join().expect("backfill panicked")?;
+//!     Ok::<(), SzError>(())
+//! })?;
+//! let summary = loader.cutover();
+//! println!("{summary}");
+//! // Write further stream records directly with `add_record`
+//! # Ok::<(), SzError>(())
+//! ```
+
+use super::{IngestRecordKey, WatermarkStore, parse_object};
+use crate::control::ControlHandle;
+use crate::error::{SzError, SzResult};
+use crate::flags::SzFlags;
+use crate::traits::SzEngine;
+use crate::types::JsonString;
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io::BufRead;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+/// Default record attribute holding the record timestamp.
+pub const DEFAULT_TIMESTAMP_FIELD: &str = "RECORD_TIMESTAMP";
+
+/// Number of locks the record keys are spread over.
+const STRIPES: usize = 256;
+
+/// Counts of a [`DualWriteLoader`] run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DualWriteSummary {
+    /// Backfill records written
+    pub backfill_applied: u64,
+    /// Backfill records skipped for a newer stream version
+    pub backfill_superseded: u64,
+    /// Stream records written or deleted
+    pub stream_applied: u64,
+    /// Stream records skipped for a newer stored version
+    pub stream_superseded: u64,
+}
+
+impl fmt::Display for DualWriteSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "backfill: {} applied, {} superseded; stream: {} applied, {} superseded",
+            self.backfill_applied,
+            self.backfill_superseded,
+            self.stream_applied,
+            self.stream_superseded
+        )
+    }
+}
+
+/// The last version the stream wrote for a record.
+#[derive(Debug, Clone, Copy)]
+struct StreamVersion {
+    timestamp: Option<i64>,
+}
+
+/// Loads a backfill and a live stream together; see the
+/// [module documentation](self).
+pub struct DualWriteLoader<'a> {
+    engine: &'a dyn SzEngine,
+    timestamp_field: String,
+    flags: Option<SzFlags>,
+    watermarks: WatermarkStore,
+    stripes: Vec<Mutex<HashMap<(String, String), StreamVersion>>>,
+    backfill_applied: AtomicU64,
+    backfill_superseded: AtomicU64,
+    stream_applied: AtomicU64,
+    stream_superseded: AtomicU64,
+}
+
+impl<'a> DualWriteLoader<'a> {
+    /// Creates a loader writing to `engine`.
+    pub fn new(engine: &'a dyn SzEngine) -> Self {
+        Self {
+            engine,
+            timestamp_field: DEFAULT_TIMESTAMP_FIELD.to_string(),
+            flags: None,
+            watermarks: WatermarkStore::new(),
+            stripes: (0..STRIPES).map(|_| Mutex::new(HashMap::new())).collect(),
+            backfill_applied: AtomicU64::new(0),
+            backfill_superseded: AtomicU64::new(0),
+            stream_applied: AtomicU64::new(0),
+            stream_superseded: AtomicU64::new(0),
+        }
+    }
+
+    /// Reads record timestamps from attribute `field`.
+    pub fn with_timestamp_field(mut self, field: impl Into<String>) -> Self {
+        self.timestamp_field = field.into();
+        self
+    }
+
+    /// Flags for the `add_record` and `delete_record` calls, e.g.
+    /// `SzFlags::WITH_INFO`.
+    pub fn with_flags(mut self, flags: SzFlags) -> Self {
+        self.flags = Some(flags);
+        self
+    }
+
+    /// Tracks [`watermarks`](Self::watermarks) in `store`, e.g. one opened
+    /// from the previous run's checkpoint file, instead of in memory.
+    pub fn with_watermark_store(mut self, store: WatermarkStore) -> Self {
+        self.watermarks = store;
+        self
+    }
+
+    /// Writes a backfill record unless the stream already holds the same or
+    /// a later version. Returns the engine's response, or `None` when
+    /// superseded.
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::BadInput` - `record` lacks `DATA_SOURCE` or `RECORD_ID`,
+    ///   or has an unreadable timestamp
+    /// * Any error from `SzEngine::add_record`
+    pub fn backfill_record(&self, record: &str) -> SzResult<Option<JsonString>> {
+        let (data_source, record_id, timestamp) = self.parse(record)?;
+        let key = (data_source.to_ascii_uppercase(), record_id);
+        let stripe = self.lock(&key);
+        if let Some(stream) = stripe.get(&key)
+            && !backfill_wins(timestamp, stream.timestamp)
+        {
+            self.backfill_superseded.fetch_add(1, Ordering::Relaxed);
+            self.loaded(&data_source, timestamp);
+            return Ok(None);
+        }
+        let info = self
+            .engine
+            .add_record(&data_source, &key.1, record, self.flags)?;
+        drop(stripe);
+        self.backfill_applied.fetch_add(1, Ordering::Relaxed);
+        self.loaded(&data_source, timestamp);
+        Ok(Some(info))
+    }
+
+    /// Writes a stream record unless a later version is already stored.
+    /// Returns the engine's response, or `None` when superseded.
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::BadInput` - `record` lacks `DATA_SOURCE` or `RECORD_ID`,
+    ///   or has an unreadable timestamp
+    /// * Any error from `SzEngine::get_record` other than
+    ///   `SzError::NotFound`, or from `SzEngine::add_record`
+    pub fn stream_record(&self, record: &str) -> SzResult<Option<JsonString>> {
+        let (data_source, record_id, timestamp) = self.parse(record)?;
+        self.stream_write(data_source, record_id, timestamp, |engine, ds, id| {
+            engine.add_record(ds, id, record, self.flags)
+        })
+    }
+
+    /// Deletes a record for the stream unless a version later than
+    /// `timestamp` is already stored, and keeps the backfill from adding
+    /// back older versions. Returns the engine's response, or `None` when
+    /// superseded.
+    ///
+    /// # Errors
+    ///
+    /// Any error from `SzEngine::get_record` other than `SzError::NotFound`,
+    /// or from `SzEngine::delete_record`.
+    pub fn stream_delete(
+        &self,
+        data_source: &str,
+        record_id: &str,
+        timestamp: Option<i64>,
+    ) -> SzResult<Option<JsonString>> {
+        self.stream_write(
+            data_source.to_string(),
+            record_id.to_string(),
+            timestamp,
+            |engine, ds, id| engine.delete_record(ds, id, self.flags),
+        )
+    }
+
+    /// Writes the line-delimited JSON records of `reader` with
+    /// [`backfill_record`](Self::backfill_record), checking `control` before
+    /// each line, and checkpoints the watermarks at the end. Returns the
+    /// counts for this reader; stream counts are 0.
+    ///
+    /// Stops at the first failure; the error names the 1-based line number.
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::BadInput` - A line could not be read or is not a valid
+    ///   record
+    /// * Any error from `SzEngine::add_record`
+    /// * `SzError::BadInput` - The watermarks cannot be checkpointed
+    pub fn backfill_from_reader<R: BufRead>(
+        &self,
+        reader: R,
+        control: Option<&ControlHandle>,
+    ) -> SzResult<DualWriteSummary> {
+        let mut summary = DualWriteSummary::default();
+        for (index, line) in reader.lines().enumerate() {
+            if control.is_some_and(|control| !control.checkpoint()) {
+                break;
+            }
+            let line_number = index + 1;
+            let line = line.map_err(|e| {
+                SzError::bad_input(format!("Failed reading line {line_number}: {e}"))
+            })?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let written = self.backfill_record(line.trim()).map_err(|e| match e {
+                SzError::BadInput(_) => {
+                    SzError::bad_input(format!("Line {line_number}: {}", e.message()))
+                }
+                e => e,
+            })?;
+            match written {
+                Some(_) => summary.backfill_applied += 1,
+                None => summary.backfill_superseded += 1,
+            }
+        }
+        self.checkpoint()?;
+        Ok(summary)
+    }
+
+    /// Latest record timestamp loaded per data source, from both the
+    /// backfill and the stream. Superseded records count as loaded, since a
+    /// newer version of them is stored.
+    pub fn watermarks(&self) -> BTreeMap<String, i64> {
+        self.watermarks.watermarks()
+    }
+
+    /// The store holding the [`watermarks`](Self::watermarks).
+    pub fn watermark_store(&self) -> &WatermarkStore {
+        &self.watermarks
+    }
+
+    /// Writes the watermarks to the store's checkpoint file; see
+    /// [`WatermarkStore::checkpoint`].
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the file cannot be written.
+    pub fn checkpoint(&self) -> SzResult<()> {
+        self.watermarks.checkpoint()
+    }
+
+    /// Counts so far.
+    pub fn summary(&self) -> DualWriteSummary {
+        DualWriteSummary {
+            backfill_applied: self.backfill_applied.load(Ordering::Relaxed),
+            backfill_superseded: self.backfill_superseded.load(Ordering::Relaxed),
+            stream_applied: self.stream_applied.load(Ordering::Relaxed),
+            stream_superseded: self.stream_superseded.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Ends dual writing and returns the final counts. Call once the
+    /// backfill is done; stream records can then be written directly.
+    pub fn cutover(self) -> DualWriteSummary {
+        self.summary()
+    }
+
+    /// Applies a stream write with `write` unless a later version is stored,
+    /// and remembers its timestamp.
+    fn stream_write(
+        &self,
+        data_source: String,
+        record_id: String,
+        timestamp: Option<i64>,
+        write: impl FnOnce(&dyn SzEngine, &str, &str) -> SzResult<JsonString>,
+    ) -> SzResult<Option<JsonString>> {
+        let key = (data_source.to_ascii_uppercase(), record_id);
+        let mut stripe = self.lock(&key);
+        let stored = match stripe.get(&key) {
+            Some(stream) => stream.timestamp,
+            None => self.stored_timestamp(&data_source, &key.1)?,
+        };
+        if !stream_wins(timestamp, stored) {
+            self.stream_superseded.fetch_add(1, Ordering::Relaxed);
+            self.loaded(&data_source, timestamp);
+            return Ok(None);
+        }
+        let info = write(self.engine, &data_source, &key.1)?;
+        stripe.insert(key, StreamVersion { timestamp });
+        drop(stripe);
+        self.stream_applied.fetch_add(1, Ordering::Relaxed);
+        self.loaded(&data_source, timestamp);
+        Ok(Some(info))
+    }
+
+    /// Raises the watermark of `data_source` for a record handled.
+    fn loaded(&self, data_source: &str, timestamp: Option<i64>) {
+        if let Some(timestamp) = timestamp {
+            self.watermarks.observe(data_source, timestamp);
+        }
+    }
+
+    /// Timestamp of the stored version of a record, if it has one.
+    fn stored_timestamp(&self, data_source: &str, record_id: &str) -> SzResult<Option<i64>> {
+        let record = match self.engine.get_record(
+            data_source,
+            record_id,
+            Some(SzFlags::ENTITY_INCLUDE_RECORD_JSON_DATA),
+        ) {
+            Ok(record) => record,
+            Err(SzError::NotFound(_)) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let value: Value = serde_json::from_str(&record)?;
+        match value
+            .get("JSON_DATA")
+            .and_then(|data| data.get(&self.timestamp_field))
+        {
+            Some(timestamp) => parse_timestamp(timestamp).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// The key and timestamp of a record.
+    fn parse(&self, record: &str) -> SzResult<(String, String, Option<i64>)> {
+        let key = IngestRecordKey::extract(record)?;
+        let (Some(data_source), Some(record_id)) = (key.data_source, key.record_id) else {
+            return Err(SzError::bad_input(
+                "record must be a JSON object with DATA_SOURCE and RECORD_ID",
+            ));
+        };
+        let timestamp = match parse_object(record)?.get(&self.timestamp_field) {
+            Some(timestamp) => Some(parse_timestamp(timestamp)?),
+            None => None,
+        };
+        Ok((data_source, record_id, timestamp))
+    }
+
+    fn lock(
+        &self,
+        key: &(String, String),
+    ) -> MutexGuard<'_, HashMap<(String, String), StreamVersion>> {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        match self.stripes[hasher.finish() as usize % STRIPES].lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+/// Whether a backfill version replaces what the stream wrote: only when both
+/// have timestamps and the backfill's is later.
+fn backfill_wins(backfill: Option<i64>, stream: Option<i64>) -> bool {
+    matches!((backfill, stream), (Some(backfill), Some(stream)) if backfill > stream)
+}
+
+/// Whether a stream version replaces the stored one: unless both have
+/// timestamps and the stored one is later.
+fn stream_wins(stream: Option<i64>, stored: Option<i64>) -> bool {
+    !matches!((stream, stored), (Some(stream), Some(stored)) if stored > stream)
+}
+
+/// Reads a record timestamp; see the [module documentation](self).
+pub(super) fn parse_timestamp(value: &Value) -> SzResult<i64> {
+    let parsed = match value {
+        Value::Number(number) => number.as_i64(),
+        Value::String(text) => text.trim().parse().ok().or_else(|| iso_millis(text.trim())),
+        _ => None,
+    };
+    parsed.ok_or_else(|| SzError::bad_input(format!("Invalid record timestamp: {value}")))
+}
+
+/// Milliseconds since the epoch for an ISO 8601 date-time with an optional
+/// fraction and a `Z` or `±HH:MM` offset; no offset means UTC.
+fn iso_millis(text: &str) -> Option<i64> {
+    let days = crate::health::days_from_date(text)?;
+    let time = text.get(10..)?.strip_prefix(['T', ' '])?;
+    let number = |range: std::ops::Range<usize>| time.get(range)?.parse::<i64>().ok();
+    let (hour, minute, second) = (number(0..2)?, number(3..5)?, number(6..8)?);
+    if time.get(2..3)? != ":" || time.get(5..6)? != ":" || hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    let mut rest = &time[8..];
+    let mut millis = 0;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let digits = fraction.len()
+            - fraction
+                .trim_start_matches(|c: char| c.is_ascii_digit())
+                .len();
+        if digits == 0 {
+            return None;
+        }
+        millis = format!("{:0<3}", &fraction[..digits.min(3)]).parse().ok()?;
+        rest = &fraction[digits..];
+    }
+    let offset_minutes = match rest {
+        "" | "Z" | "z" => 0,
+        _ => {
+            let sign = match rest.get(..1)? {
+                "+" => 1,
+                "-" => -1,
+                _ => return None,
+            };
+            let hours: i64 = rest.get(1..3)?.parse().ok()?;
+            let minutes: i64 = rest.get(4..6)?.parse().ok()?;
+            if rest.get(3..4)? != ":" || rest.len() != 6 {
+                return None;
+            }
+            sign * (hours * 60 + minutes)
+        }
+    };
+    let seconds = days * 86_400 + hour * 3_600 + minute * 60 + second - offset_minutes * 60;
+    Some(seconds * 1_000 + millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp(&json!(1700000000)).unwrap(), 1_700_000_000);
+        assert_eq!(parse_timestamp(&json!("42")).unwrap(), 42);
+        assert_eq!(
+            parse_timestamp(&json!("1970-01-02T00:00:01Z")).unwrap(),
+            86_401_000
+        );
+        assert_eq!(
+            parse_timestamp(&json!("2026-03-01T14:30:00.25+02:00")).unwrap(),
+            parse_timestamp(&json!("2026-03-01T12:30:00.250Z")).unwrap()
+        );
+        assert_eq!(
+            parse_timestamp(&json!("2026-03-01 12:30:00")).unwrap(),
+            parse_timestamp(&json!("2026-03-01T12:30:00Z")).unwrap()
+        );
+        assert!(parse_timestamp(&json!("2026-03-01")).is_err());
+        assert!(parse_timestamp(&json!("2026-03-01T12:30:00+2")).is_err());
+        assert!(parse_timestamp(&json!(true)).is_err());
+    }
+
+    #[test]
+    fn test_conflict_rules() {
+        assert!(backfill_wins(Some(2), Some(1)));
+        assert!(!backfill_wins(Some(1), Some(1)));
+        assert!(!backfill_wins(None, Some(1)));
+        assert!(!backfill_wins(Some(2), None));
+
+        assert!(stream_wins(Some(1), Some(1)));
+        assert!(stream_wins(Some(1), None));
+        assert!(stream_wins(None, Some(5)));
+        assert!(!stream_wins(Some(1), Some(2)));
+    }
+}
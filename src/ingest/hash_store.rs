@@ -0,0 +1,224 @@
+//! Stored content hashes for skipping unchanged records on reload.
+//!
+//! `SzEngine::add_record_if_changed` finds unchanged records by reading the
+//! stored record back, which still costs one native `get_record` per
+//! record. A [`RecordHashStore`] remembers the
+//! [`record_content_hash`](super::record_content_hash) of every record added
+//! through it, so `SzEngine::add_record_if_changed_with_hashes` skips an
+//! unchanged record without calling the engine at all. Records the store has
+//! no hash for fall back to the `get_record` comparison.
+//!
+//! Implement the trait over a sidecar table kept next to the source data,
+//! or use [`MemoryRecordHashStore`], which can be checkpointed to a file
+//! between runs of a daily full-file reload.
+//!
+//! The store only sees changes made through it. After deleting or changing
+//! records by other means, [`remove`](RecordHashStore::remove) their hashes
+//! or start from an empty store; a stale hash makes the record look
+//! unchanged.
+
+use crate::error::{SzError, SzResult};
+use crate::types::RecordKey;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+/// Content hashes of loaded records, keyed by record; see the
+/// [module documentation](self).
+pub trait RecordHashStore: Send + Sync {
+    /// The hash stored for `key`, if any.
+    ///
+    /// # Errors
+    ///
+    /// Any error reaching the underlying storage.
+    fn get(&self, key: &RecordKey) -> SzResult<Option<u64>>;
+
+    /// Stores `hash` as the content hash of `key`.
+    ///
+    /// # Errors
+    ///
+    /// Any error reaching the underlying storage.
+    fn set(&self, key: &RecordKey, hash: u64) -> SzResult<()>;
+
+    /// Forgets the hash of `key`, e.g. after the record was deleted.
+    ///
+    /// # Errors
+    ///
+    /// Any error reaching the underlying storage.
+    fn remove(&self, key: &RecordKey) -> SzResult<()>;
+}
+
+/// A [`RecordHashStore`] held in memory, optionally persisted to a
+/// checkpoint file with one JSON line per record.
+#[derive(Debug, Default)]
+pub struct MemoryRecordHashStore {
+    path: Option<PathBuf>,
+    hashes: Mutex<HashMap<RecordKey, u64>>,
+}
+
+/// One line of the checkpoint file.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+struct HashLine {
+    #[serde(flatten)]
+    key: RecordKey,
+    hash: u64,
+}
+
+impl MemoryRecordHashStore {
+    /// Creates a store kept in memory only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Opens the store checkpointed at `path`, starting empty if the file
+    /// does not exist yet.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the file cannot be read or a line is not a
+    /// `{"DATA_SOURCE": ..., "RECORD_ID": ..., "HASH": ...}` object.
+    pub fn open(path: impl AsRef<Path>) -> SzResult<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut hashes = HashMap::new();
+        match File::open(&path) {
+            Ok(file) => {
+                for (index, line) in BufReader::new(file).lines().enumerate() {
+                    let line = line.map_err(|e| io_error(&path, e))?;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    let entry: HashLine = serde_json::from_str(&line).map_err(|e| {
+                        SzError::bad_input(format!(
+                            "Invalid record hash on line {} of {}: {e}",
+                            index + 1,
+                            path.display()
+                        ))
+                    })?;
+                    hashes.insert(entry.key, entry.hash);
+                }
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(io_error(&path, e)),
+        }
+        Ok(Self {
+            path: Some(path),
+            hashes: Mutex::new(hashes),
+        })
+    }
+
+    /// The checkpoint file, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Number of records with a stored hash.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Whether no record has a stored hash.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Writes the hashes to the checkpoint file, replacing it atomically.
+    /// Does nothing for an in-memory store.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the file cannot be written.
+    pub fn checkpoint(&self) -> SzResult<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let mut temp = path.clone().into_os_string();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+        let file = File::create(&temp).map_err(|e| io_error(&temp, e))?;
+        let mut writer = BufWriter::new(file);
+        for (key, &hash) in self.lock().iter() {
+            let line = serde_json::to_string(&HashLine {
+                key: key.clone(),
+                hash,
+            })?;
+            writeln!(writer, "{line}").map_err(|e| io_error(&temp, e))?;
+        }
+        writer
+            .into_inner()
+            .map_err(|e| io_error(&temp, e.into_error()))?
+            .sync_data()
+            .map_err(|e| io_error(&temp, e))?;
+        std::fs::rename(&temp, path).map_err(|e| io_error(path, e))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, HashMap<RecordKey, u64>> {
+        match self.hashes.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+impl RecordHashStore for MemoryRecordHashStore {
+    fn get(&self, key: &RecordKey) -> SzResult<Option<u64>> {
+        Ok(self.lock().get(key).copied())
+    }
+
+    fn set(&self, key: &RecordKey, hash: u64) -> SzResult<()> {
+        self.lock().insert(key.clone(), hash);
+        Ok(())
+    }
+
+    fn remove(&self, key: &RecordKey) -> SzResult<()> {
+        self.lock().remove(key);
+        Ok(())
+    }
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> SzError {
+    SzError::bad_input(format!("Failed accessing {}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_hashes_persist() {
+        let path = std::env::temp_dir().join(format!("sz_record_hashes_{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let store = MemoryRecordHashStore::open(&path).unwrap();
+        assert!(store.is_empty());
+        let key = RecordKey::new("customers", "1001").unwrap();
+        store.set(&key, u64::MAX).unwrap();
+        store
+            .set(&RecordKey::new("CUSTOMERS", "1002").unwrap(), 7)
+            .unwrap();
+        store
+            .remove(&RecordKey::new("CUSTOMERS", "1002").unwrap())
+            .unwrap();
+        store.checkpoint().unwrap();
+
+        let reopened = MemoryRecordHashStore::open(&path).unwrap();
+        assert_eq!(reopened.len(), 1);
+        assert_eq!(
+            reopened
+                .get(&RecordKey::new("CUSTOMERS", "1001").unwrap())
+                .unwrap(),
+            Some(u64::MAX)
+        );
+
+        std::fs::write(&path, "not json\n").unwrap();
+        assert!(
+            MemoryRecordHashStore::open(&path)
+                .unwrap_err()
+                .is_bad_input()
+        );
+        std::fs::remove_file(path).unwrap();
+    }
+}
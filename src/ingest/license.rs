@@ -0,0 +1,192 @@
+//! License record limit checks for bulk loads.
+//!
+//! A Senzing license caps the number of records a repository may hold. The
+//! native library only enforces the cap when the record that exceeds it is
+//! added, so a large load fails part way through with a license error that
+//! does not say how far it got. A [`LicenseGuard`] reads the limit from
+//! `SzProduct::get_license` and the current record count from
+//! `SzEngine::repository_counts` once, before the load, and the loader checks
+//! it before each record. With [`LicenseAction::Halt`] the load stops cleanly
+//! before the limit is passed; with [`LicenseAction::Warn`] it carries on and
+//! only reports it.
+//!
+//! The guard counts every added record as new. Records that replace an
+//! existing record with the same key do not grow the repository, so a load
+//! with many updates can be stopped earlier than necessary.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::ingest::{self, LicenseGuard, LoadOptions};
+//! use sz_rust_sdk::prelude::*;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_license_guard")?;
+//! let engine = env.get_engine()?;
+//! let guard = LicenseGuard::read(&*env.get_product()?, &*engine)?;
+//! let mut options = LoadOptions::new();
+//! if let Some(guard) = &guard {
+//!     options = options.with_license_guard(guard);
+//! }
+//! # let data: &[u8] = b"";
+//! let summary = ingest::load_from_reader_with_options(&*engine, data, options)?;
+//! if summary.license_limit_reached {
+//!     eprintln!("stopped after {} records: license limit reached", summary.records_loaded);
+//! }
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::error::SzResult;
+use crate::traits::{SzEngine, SzProduct};
+use crate::types::COUNT_CACHE_MAX_AGE;
+
+/// Default share of the record limit, in percent, at which a load reports
+/// [`LoadSummary::license_warning`](super::LoadSummary::license_warning).
+pub const DEFAULT_WARN_AT_PERCENT: u8 = 90;
+
+/// What the loader does when the next record would pass the license record
+/// limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LicenseAction {
+    /// Stop before adding the record, with
+    /// [`LoadSummary::stopped`](super::LoadSummary::stopped) set.
+    #[default]
+    Halt,
+    /// Keep adding records and leave it to the engine to reject them.
+    Warn,
+}
+
+/// The license record limit and the records already in the repository.
+///
+/// See the [module documentation](self).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LicenseGuard {
+    /// Records the license allows in the repository
+    pub record_limit: u64,
+    /// Records in the repository when the load starts
+    pub existing_records: u64,
+    /// Share of `record_limit`, in percent, at which the load reports a
+    /// warning
+    pub warn_at_percent: u8,
+    /// Behavior when the next record would pass `record_limit`
+    pub action: LicenseAction,
+}
+
+/// Where a load stands against a [`LicenseGuard`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum LicenseCheck {
+    /// Below the warning threshold
+    Clear,
+    /// At or above the warning threshold, within the limit
+    Approaching,
+    /// The next record would pass the limit
+    Exceeded,
+}
+
+impl LicenseGuard {
+    /// Creates a guard that halts at `record_limit` and warns at
+    /// [`DEFAULT_WARN_AT_PERCENT`].
+    pub fn new(record_limit: u64, existing_records: u64) -> Self {
+        Self {
+            record_limit,
+            existing_records,
+            warn_at_percent: DEFAULT_WARN_AT_PERCENT,
+            action: LicenseAction::default(),
+        }
+    }
+
+    /// Reads the record limit from the license and the record count from
+    /// the repository.
+    ///
+    /// Returns `None` if the license has no record limit. Record counts up to
+    /// [`COUNT_CACHE_MAX_AGE`] old are reused; otherwise counting walks a
+    /// full export.
+    ///
+    /// # Errors
+    ///
+    /// * Any error from `SzProduct::get_license` or
+    ///   `SzEngine::repository_counts`
+    /// * `SzError::Json` - The license is not JSON
+    pub fn read(product: &dyn SzProduct, engine: &dyn SzEngine) -> SzResult<Option<Self>> {
+        let Some(record_limit) = record_limit(&product.get_license()?)? else {
+            return Ok(None);
+        };
+        let counts = engine.repository_counts(COUNT_CACHE_MAX_AGE)?;
+        let existing_records = u64::try_from(counts.records).unwrap_or_default();
+        Ok(Some(Self::new(record_limit, existing_records)))
+    }
+
+    /// Sets what the loader does at the limit.
+    pub fn with_action(mut self, action: LicenseAction) -> Self {
+        self.action = action;
+        self
+    }
+
+    /// Sets the share of the limit, in percent, at which the load reports a
+    /// warning.
+    pub fn warn_at_percent(mut self, percent: u8) -> Self {
+        self.warn_at_percent = percent;
+        self
+    }
+
+    /// Records that can be added before the limit is reached.
+    pub fn remaining(&self) -> u64 {
+        self.record_limit.saturating_sub(self.existing_records)
+    }
+
+    /// Checks the next record of a load that has added `records_loaded`
+    /// records so far.
+    pub(crate) fn check(&self, records_loaded: u64) -> LicenseCheck {
+        let total = self
+            .existing_records
+            .saturating_add(records_loaded)
+            .saturating_add(1);
+        let warn_at = u128::from(self.record_limit) * u128::from(self.warn_at_percent) / 100;
+        if total > self.record_limit {
+            LicenseCheck::Exceeded
+        } else if u128::from(total) >= warn_at {
+            LicenseCheck::Approaching
+        } else {
+            LicenseCheck::Clear
+        }
+    }
+}
+
+/// Reads `recordLimit` from license JSON; a missing or zero limit is `None`.
+fn record_limit(license: &str) -> SzResult<Option<u64>> {
+    let license: serde_json::Value = serde_json::from_str(license)?;
+    Ok(license["recordLimit"].as_u64().filter(|limit| *limit > 0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_limit_from_license() {
+        let license = r#"{"customer":"ACME","licenseType":"EVAL","expireDate":"2027-01-01","recordLimit":500000}"#;
+        assert_eq!(record_limit(license).unwrap(), Some(500_000));
+        assert_eq!(record_limit(r#"{"recordLimit":0}"#).unwrap(), None);
+        assert_eq!(record_limit(r#"{"customer":"ACME"}"#).unwrap(), None);
+        assert!(record_limit("not json").is_err());
+    }
+
+    #[test]
+    fn test_license_guard_check() {
+        let guard = LicenseGuard::new(100, 80);
+        assert_eq!(guard.remaining(), 20);
+        assert_eq!(guard.check(0), LicenseCheck::Clear);
+        assert_eq!(guard.check(8), LicenseCheck::Clear);
+        assert_eq!(guard.check(9), LicenseCheck::Approaching);
+        assert_eq!(guard.check(19), LicenseCheck::Approaching);
+        assert_eq!(guard.check(20), LicenseCheck::Exceeded);
+
+        let full = LicenseGuard::new(100, 150).warn_at_percent(0);
+        assert_eq!(full.remaining(), 0);
+        assert_eq!(full.check(0), LicenseCheck::Exceeded);
+        assert_eq!(
+            LicenseGuard::new(100, 0).warn_at_percent(0).check(0),
+            LicenseCheck::Approaching
+        );
+    }
+}
@@ -0,0 +1,367 @@
+//! Declarative mapping of source fields to Senzing attributes.
+//!
+//! Sources rarely use Senzing attribute names. A [`Mapping`] lists, for each
+//! attribute of the output record, where its value comes from:
+//!
+//! | Kind | Spec | Output |
+//! |------|------|--------|
+//! | Rename | `from: cust_id` | the source field's value |
+//! | Concatenation | `concat: [first, last]` | the non-empty values joined by `separator` (default a space) |
+//! | Constant | `constant: CUSTOMERS` | the given text |
+//!
+//! Any entry can carry a `when` condition on a source field: `equals` a
+//! value, or just being present and non-empty. When several entries set the
+//! same attribute, the first one that produces a value wins, so conditional
+//! entries followed by a fallback read like an if/else chain.
+//!
+//! Source fields are looked up by name; a dotted name such as
+//! `address.city` reaches into nested objects. Numbers and booleans are
+//! converted to text. Attributes whose source is missing, null or blank are
+//! left out of the record.
+//!
+//! Mappings are loaded from JSON, or from YAML with the `yaml` feature, and
+//! applied to every record of a load with
+//! [`LoadOptions::with_mapping`](super::LoadOptions::with_mapping).
+//!
+//! # Example
+//!
+//! ```
+//! use sz_rust_sdk::ingest::Mapping;
+//!
+//! let mapping = Mapping::from_json_str(
+//!     r#"[
+//!         {"target": "DATA_SOURCE", "constant": "CUSTOMERS"},
+//!         {"target": "RECORD_ID", "from": "id"},
+//!         {"target": "NAME_ORG", "from": "name", "when": {"field": "kind", "equals": "company"}},
+//!         {"target": "NAME_FULL", "concat": ["first", "last"]},
+//!         {"target": "ADDR_CITY", "from": "address.city"}
+//!     ]"#,
+//! )?;
+//! let record = mapping.map_record(
+//!     r#"{"id": 42, "kind": "person", "first": "Ann", "last": "Lee", "address": {"city": "Oslo"}}"#,
+//! )?;
+//! assert_eq!(
+//!     record,
+//!     r#"{"ADDR_CITY":"Oslo","DATA_SOURCE":"CUSTOMERS","NAME_FULL":"Ann Lee","RECORD_ID":"42"}"#
+//! );
+//! # Ok::<(), sz_rust_sdk::error::SzError>(())
+//! ```
+//!
+//! The same mapping in YAML:
+//!
+//! ```yaml
+//! - target: DATA_SOURCE
+//!   constant: CUSTOMERS
+//! - target: RECORD_ID
+//!   from: id
+//! - target: NAME_ORG
+//!   from: name
+//!   when: { field: kind, equals: company }
+//! - target: NAME_FULL
+//!   concat: [first, last]
+//! - target: ADDR_CITY
+//!   from: address.city
+//! ```
+
+use super::parse_object;
+use crate::error::{SzError, SzResult};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Where a mapped attribute's value comes from.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldSource {
+    /// The value of one source field
+    From(String),
+    /// The non-empty values of several source fields, joined
+    Concat(Vec<String>),
+    /// A fixed value
+    Constant(String),
+}
+
+/// Condition on a source field that decides whether an entry applies.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Condition {
+    /// Source field to test
+    pub field: String,
+    /// Value the field must have; without it the field only has to be
+    /// present and non-empty
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub equals: Option<String>,
+}
+
+impl Condition {
+    /// Applies when `field` has the value `value`.
+    pub fn equals(field: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            equals: Some(value.into()),
+        }
+    }
+
+    /// Applies when `field` is present and non-empty.
+    pub fn present(field: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            equals: None,
+        }
+    }
+
+    fn holds(&self, source: &Map<String, Value>) -> bool {
+        match (field_text(source, &self.field), &self.equals) {
+            (Some(value), Some(expected)) => value == *expected,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+}
+
+/// One attribute of the output record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FieldMapping {
+    /// Senzing attribute to set
+    pub target: String,
+    /// Where the value comes from
+    #[serde(flatten)]
+    pub source: FieldSource,
+    /// Separator for [`FieldSource::Concat`]; defaults to a space
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub separator: Option<String>,
+    /// Condition the entry applies under; always applies without one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<Condition>,
+}
+
+impl FieldMapping {
+    /// Sets `target` from the source field `field`.
+    pub fn from(target: impl Into<String>, field: impl Into<String>) -> Self {
+        Self::new(target, FieldSource::From(field.into()))
+    }
+
+    /// Sets `target` from the non-empty values of `fields`, joined with
+    /// spaces.
+    pub fn concat<I, S>(target: impl Into<String>, fields: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self::new(
+            target,
+            FieldSource::Concat(fields.into_iter().map(Into::into).collect()),
+        )
+    }
+
+    /// Sets `target` to `value`.
+    pub fn constant(target: impl Into<String>, value: impl Into<String>) -> Self {
+        Self::new(target, FieldSource::Constant(value.into()))
+    }
+
+    fn new(target: impl Into<String>, source: FieldSource) -> Self {
+        Self {
+            target: target.into(),
+            source,
+            separator: None,
+            when: None,
+        }
+    }
+
+    /// Sets the separator for a concatenation.
+    pub fn with_separator(mut self, separator: impl Into<String>) -> Self {
+        self.separator = Some(separator.into());
+        self
+    }
+
+    /// Applies the entry only when `condition` holds.
+    pub fn when(mut self, condition: Condition) -> Self {
+        self.when = Some(condition);
+        self
+    }
+
+    fn value(&self, source: &Map<String, Value>) -> Option<String> {
+        if let Some(condition) = &self.when
+            && !condition.holds(source)
+        {
+            return None;
+        }
+        let value = match &self.source {
+            FieldSource::From(field) => field_text(source, field)?,
+            FieldSource::Concat(fields) => fields
+                .iter()
+                .filter_map(|field| field_text(source, field))
+                .collect::<Vec<_>>()
+                .join(self.separator.as_deref().unwrap_or(" ")),
+            FieldSource::Constant(value) => value.clone(),
+        };
+        (!value.is_empty()).then_some(value)
+    }
+}
+
+/// Converts source records to Senzing records.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Mapping {
+    fields: Vec<FieldMapping>,
+}
+
+impl Mapping {
+    /// Creates an empty mapping.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an entry.
+    pub fn with_field(mut self, field: FieldMapping) -> Self {
+        self.fields.push(field);
+        self
+    }
+
+    /// The entries, in the order they are applied.
+    pub fn fields(&self) -> &[FieldMapping] {
+        &self.fields
+    }
+
+    /// Parses a mapping from a JSON list of entries.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the mapping is malformed, empty, or has an
+    /// entry without a target.
+    pub fn from_json_str(spec: &str) -> SzResult<Self> {
+        serde_json::from_str::<Self>(spec)
+            .map_err(|e| SzError::bad_input(format!("Invalid mapping: {e}")))?
+            .validated()
+    }
+
+    /// Parses a mapping from a YAML list of entries.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`Mapping::from_json_str`].
+    #[cfg(feature = "yaml")]
+    pub fn from_yaml_str(spec: &str) -> SzResult<Self> {
+        serde_yaml_ng::from_str::<Self>(spec)
+            .map_err(|e| SzError::bad_input(format!("Invalid mapping: {e}")))?
+            .validated()
+    }
+
+    fn validated(self) -> SzResult<Self> {
+        if self.fields.is_empty() {
+            return Err(SzError::bad_input("Mapping has no fields"));
+        }
+        if self
+            .fields
+            .iter()
+            .any(|field| field.target.trim().is_empty())
+        {
+            return Err(SzError::bad_input("Mapping field has an empty target"));
+        }
+        Ok(self)
+    }
+
+    /// Builds the output attributes for a parsed source record.
+    pub fn apply(&self, source: &Map<String, Value>) -> Map<String, Value> {
+        let mut record = Map::new();
+        for field in &self.fields {
+            if !record.contains_key(&field.target)
+                && let Some(value) = field.value(source)
+            {
+                record.insert(field.target.clone(), Value::String(value));
+            }
+        }
+        record
+    }
+
+    /// Converts a JSON source record to a Senzing record.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the record is not a JSON object.
+    pub fn map_record(&self, record: &str) -> SzResult<String> {
+        let source = parse_object(record)?;
+        Ok(Value::Object(self.apply(&source)).to_string())
+    }
+}
+
+/// The text of a source field, following dots into nested objects.
+fn field_text(source: &Map<String, Value>, field: &str) -> Option<String> {
+    let value = source.get(field).or_else(|| {
+        let mut parts = field.split('.');
+        let first = source.get(parts.next()?)?;
+        parts.try_fold(first, |value, part| value.as_object()?.get(part))
+    })?;
+    let text = match value {
+        Value::String(text) => text.trim().to_string(),
+        Value::Number(number) => number.to_string(),
+        Value::Bool(flag) => flag.to_string(),
+        _ => return None,
+    };
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mapping_entries() {
+        let mapping = Mapping::new()
+            .with_field(FieldMapping::from("RECORD_ID", "id"))
+            .with_field(
+                FieldMapping::from("NAME_ORG", "name").when(Condition::equals("kind", "company")),
+            )
+            .with_field(FieldMapping::from("NAME_FULL", "name"))
+            .with_field(FieldMapping::concat("ADDR_LINE1", ["street", "unit"]).with_separator(", "))
+            .with_field(
+                FieldMapping::constant("TRUSTED_ID_TYPE", "VIP").when(Condition::present("vip")),
+            );
+
+        let company = parse_object(
+            r#"{"id":"C1","kind":"company","name":"Acme","street":"1 Main St","unit":" "}"#,
+        )
+        .unwrap();
+        let record = mapping.apply(&company);
+        assert_eq!(record["NAME_ORG"], "Acme");
+        assert_eq!(record["NAME_FULL"], "Acme");
+        assert_eq!(record["ADDR_LINE1"], "1 Main St");
+        assert!(!record.contains_key("TRUSTED_ID_TYPE"));
+
+        let person =
+            parse_object(r#"{"id":7,"name":"Ann Lee","street":"2 Elm St","unit":"4B","vip":true}"#)
+                .unwrap();
+        let record = mapping.apply(&person);
+        assert_eq!(record["RECORD_ID"], "7");
+        assert!(!record.contains_key("NAME_ORG"));
+        assert_eq!(record["NAME_FULL"], "Ann Lee");
+        assert_eq!(record["ADDR_LINE1"], "2 Elm St, 4B");
+        assert_eq!(record["TRUSTED_ID_TYPE"], "VIP");
+    }
+
+    #[test]
+    fn test_mapping_parse() {
+        let json = r#"[{"target":"NAME_FULL","concat":["a","b"],"separator":"-"}]"#;
+        let mapping = Mapping::from_json_str(json).unwrap();
+        assert_eq!(
+            mapping.fields(),
+            [FieldMapping::concat("NAME_FULL", ["a", "b"]).with_separator("-")]
+        );
+        assert_eq!(serde_json::to_string(&mapping).unwrap(), json);
+
+        assert!(Mapping::from_json_str("[]").is_err());
+        assert!(Mapping::from_json_str(r#"[{"target":"","from":"a"}]"#).is_err());
+        assert!(Mapping::from_json_str(r#"[{"target":"A","copy":"a"}]"#).is_err());
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn test_mapping_from_yaml() {
+        let mapping = Mapping::from_yaml_str(
+            "- target: DATA_SOURCE\n  constant: CUSTOMERS\n- target: RECORD_ID\n  from: id\n  when: { field: id }\n",
+        )
+        .unwrap();
+        assert_eq!(
+            mapping.map_record(r#"{"id":"9"}"#).unwrap(),
+            r#"{"DATA_SOURCE":"CUSTOMERS","RECORD_ID":"9"}"#
+        );
+    }
+}
@@ -0,0 +1,1196 @@
+//! Bulk record loading from line-delimited JSON and CSV.
+//!
+//! [`load_from_reader`] reads one record per line from any `BufRead` source
+//! and adds each record to the engine. The data source and record identifier
+//! are taken from the record's own `DATA_SOURCE` and `RECORD_ID` fields, as in
+//! the Senzing file loaders.
+//!
+//! Reading is streaming, so sources larger than memory are fine: a local file,
+//! a decompressor, or a download stream. With the `object-store` feature,
+//! `load_from_object_store` streams records straight from an `s3://`,
+//! `gs://` or `az://` URL.
+//!
+//! [`load_from_reader_with_control`] takes a [`ControlHandle`] so the load can
+//! be paused, resumed or stopped from another thread.
+//!
+//! [`load_from_reader_deduplicated`] skips records that repeat an earlier
+//! record's name, date of birth, email and phone, as detected by a
+//! [`DuplicateFilter`], so dirty sources do not send exact duplicates to the
+//! engine.
+//!
+//! [`load_from_reader_with_options`] combines these with converting each
+//! source record through a [`Mapping`] and cleaning it with a
+//! [`Normalizer`] before it is checked and added; see [`LoadOptions`].
+//! [`load_csv_with_options`] does the same for CSV with a header row, where
+//! each row becomes a record keyed by the column names.
+//!
+//! [`LoadOptions::with_license_guard`] checks each record against the
+//! license record limit, so a load stops before the limit rather than
+//! failing on it; see [`LicenseGuard`].
+//!
+//! [`DualWriteLoader`] loads a backfill from files while consuming a live
+//! stream of changes, keeping the newest version of each record by its
+//! timestamp so the stream can take over at cutover. It keeps
+//! per-data-source high-watermarks of the loaded timestamps in a
+//! [`WatermarkStore`] for scheduling incremental extracts. The other loads
+//! track them with [`LoadOptions::with_watermarks`].
+//!
+//! [`RecordHashStore`] keeps the content hash of each loaded record so
+//! `SzEngine::add_record_if_changed_with_hashes` can skip unchanged records
+//! on a full reload without asking the engine.
+//!
+//! Loaders that pass the key to `SzEngine::add_record` themselves can use
+//! [`check_record_key`] or [`fill_record_key`] to make sure the record's own
+//! `DATA_SOURCE` and `RECORD_ID` agree with it.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::ingest;
+//! use sz_rust_sdk::prelude::*;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_ingest")?;
+//! let engine = env.get_engine()?;
+//! let data = "{\"DATA_SOURCE\":\"TEST\",\"RECORD_ID\":\"1\",\"NAME_FULL\":\"Ann Lee\"}\n";
+//! let summary = ingest::load_from_reader(&*engine, data.as_bytes())?;
+//! assert_eq!(summary.records_loaded, 1);
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::control::{ControlHandle, SzCancellationToken};
+use crate::error::{SzError, SzResult};
+use crate::normalize::{self, Normalizer};
+use crate::provenance::LoadMetadata;
+use crate::traits::SzEngine;
+use license::LicenseCheck;
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::BufRead;
+
+pub mod dual_write;
+pub mod hash_store;
+pub mod license;
+pub mod mapping;
+pub mod watermark;
+
+pub use dual_write::{DualWriteLoader, DualWriteSummary};
+pub use hash_store::{MemoryRecordHashStore, RecordHashStore};
+pub use license::{LicenseAction, LicenseGuard};
+pub use mapping::{Condition, FieldMapping, FieldSource, Mapping};
+pub use watermark::WatermarkStore;
+
+/// Outcome of a [`load_from_reader`] run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LoadSummary {
+    /// Records added to the engine
+    pub records_loaded: u64,
+    /// Blank lines skipped
+    pub blank_lines: u64,
+    /// Records skipped as duplicates by a [`DuplicateFilter`]
+    pub duplicates_skipped: u64,
+    /// Whether the load was stopped through its [`ControlHandle`] before the
+    /// end of the input. The next line to load is
+    /// `records_loaded + blank_lines + duplicates_skipped + 1`.
+    pub stopped: bool,
+    /// Whether the load reached the warning threshold of its
+    /// [`LicenseGuard`]
+    pub license_warning: bool,
+    /// Whether a record would have passed the record limit of the load's
+    /// [`LicenseGuard`]. With [`LicenseAction::Halt`] the load stopped before
+    /// that record and `stopped` is set as well.
+    pub license_limit_reached: bool,
+    watermarks: BTreeMap<String, i64>,
+}
+
+impl LoadSummary {
+    /// Latest record timestamp loaded per data source by this load, when it
+    /// tracked watermarks with [`LoadOptions::with_watermarks`]. Empty
+    /// otherwise.
+    pub fn watermarks(&self) -> &BTreeMap<String, i64> {
+        &self.watermarks
+    }
+}
+
+/// The `DATA_SOURCE` and `RECORD_ID` embedded in a record document.
+///
+/// A numeric `RECORD_ID` is read as its decimal string. Unlike the engine's
+/// [`RecordKey`](crate::types::RecordKey), either field may be missing, and
+/// the data source code is kept as written.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IngestRecordKey {
+    /// The record's `DATA_SOURCE`, if present
+    pub data_source: Option<String>,
+    /// The record's `RECORD_ID`, if present
+    pub record_id: Option<String>,
+}
+
+impl IngestRecordKey {
+    /// Extracts the key fields from a record document.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the record is not a JSON object, or a key field
+    /// is neither a string nor a number.
+    pub fn extract(record: &str) -> SzResult<Self> {
+        let document = parse_object(record)?;
+        Ok(Self {
+            data_source: key_field(&document, "DATA_SOURCE")?,
+            record_id: key_field(&document, "RECORD_ID")?,
+        })
+    }
+}
+
+/// Checks that the `DATA_SOURCE` and `RECORD_ID` embedded in `record`, where
+/// present, match the values passed to `SzEngine::add_record`.
+///
+/// The engine stores the record under the parameters, so a record whose own
+/// fields disagree is silently filed under a different key than its
+/// producer intended. Data source codes compare case-insensitively, as the
+/// engine does; record IDs compare exactly.
+///
+/// # Errors
+///
+/// `SzError::BadInput` if the record cannot be read or a field mismatches.
+///
+/// # Example
+///
+/// ```
+/// use sz_rust_sdk::ingest;
+///
+/// let record = r#"{"DATA_SOURCE":"TEST","RECORD_ID":"1","NAME_FULL":"Ann Lee"}"#;
+/// assert!(ingest::check_record_key("TEST", "1", record).is_ok());
+/// assert!(ingest::check_record_key("TEST", "2", record).is_err());
+/// ```
+pub fn check_record_key(data_source_code: &str, record_id: &str, record: &str) -> SzResult<()> {
+    let key = IngestRecordKey::extract(record)?;
+    check_key(&key, data_source_code, record_id)
+}
+
+/// Like [`check_record_key`], but also writes the parameters into the record
+/// when it lacks `DATA_SOURCE` or `RECORD_ID`.
+///
+/// Returns the record unchanged when both fields are already present.
+///
+/// # Errors
+///
+/// Same as [`check_record_key`].
+///
+/// # Example
+///
+/// ```
+/// use sz_rust_sdk::ingest::{self, IngestRecordKey};
+///
+/// let filled = ingest::fill_record_key("TEST", "1", r#"{"NAME_FULL":"Ann Lee"}"#)?;
+/// let key = IngestRecordKey::extract(&filled)?;
+/// assert_eq!(key.data_source.as_deref(), Some("TEST"));
+/// assert_eq!(key.record_id.as_deref(), Some("1"));
+/// # Ok::<(), sz_rust_sdk::error::SzError>(())
+/// ```
+pub fn fill_record_key<'a>(
+    data_source_code: &str,
+    record_id: &str,
+    record: &'a str,
+) -> SzResult<Cow<'a, str>> {
+    let mut document = parse_object(record)?;
+    let key = IngestRecordKey {
+        data_source: key_field(&document, "DATA_SOURCE")?,
+        record_id: key_field(&document, "RECORD_ID")?,
+    };
+    check_key(&key, data_source_code, record_id)?;
+    if key.data_source.is_some() && key.record_id.is_some() {
+        return Ok(Cow::Borrowed(record));
+    }
+    if key.data_source.is_none() {
+        document.insert("DATA_SOURCE".to_string(), data_source_code.into());
+    }
+    if key.record_id.is_none() {
+        document.insert("RECORD_ID".to_string(), record_id.into());
+    }
+    Ok(Cow::Owned(Value::Object(document).to_string()))
+}
+
+/// Returns a hash of a record's content, as compared by
+/// `SzEngine::add_record_if_changed`.
+///
+/// The hash ignores object key order, whitespace, and the top-level
+/// `DATA_SOURCE` and `RECORD_ID` fields, which the engine takes from the
+/// `add_record` parameters. It is 64-bit FNV-1a over a canonical
+/// serialization and does not change between releases, so it can be kept in
+/// a sidecar table to skip unchanged records without asking the engine; see
+/// [`RecordHashStore`].
+///
+/// # Errors
+///
+/// `SzError::BadInput` if the record is not a JSON object.
+///
+/// # Example
+///
+/// ```
+/// use sz_rust_sdk::ingest::record_content_hash;
+///
+/// let a = record_content_hash(r#"{"NAME_FULL":"Ann Lee","RECORD_ID":"1"}"#)?;
+/// let b = record_content_hash(r#"{ "NAME_FULL": "Ann Lee" }"#)?;
+/// assert_eq!(a, b);
+/// # Ok::<(), sz_rust_sdk::error::SzError>(())
+/// ```
+pub fn record_content_hash(record: &str) -> SzResult<u64> {
+    let mut document = parse_object(record)?;
+    document.remove("DATA_SOURCE");
+    document.remove("RECORD_ID");
+    let mut canonical = String::with_capacity(record.len());
+    write_canonical(&Value::Object(document), &mut canonical);
+    Ok(fnv1a_64(canonical.as_bytes()))
+}
+
+/// Serializes `value` with object keys sorted and no whitespace.
+fn write_canonical(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_unstable_by_key(|(key, _)| *key);
+            out.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical(value, out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical(item, out);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+/// Name attributes [`DuplicateFilter`] compares, as a set of name tokens.
+const NAME_ATTRIBUTES: [&str; 5] = [
+    "NAME_FULL",
+    "NAME_ORG",
+    "NAME_FIRST",
+    "NAME_MIDDLE",
+    "NAME_LAST",
+];
+
+/// Counts kept by a [`DuplicateFilter`] across every record it checked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DedupStats {
+    /// Records checked
+    pub checked: u64,
+    /// Records found to duplicate an earlier one
+    pub skipped: u64,
+    /// Records without any compared feature, which are never duplicates
+    pub without_features: u64,
+}
+
+/// Detects records that repeat an earlier record's identifying features.
+///
+/// Each record is reduced to a 64-bit hash of its data source and its
+/// normalized features: name tokens (uppercased, punctuation removed, in
+/// any order and across `NAME_FULL`, `NAME_FIRST`, `NAME_LAST`, ...), the
+/// digits of `DATE_OF_BIRTH` and `PHONE_NUMBER`, and the lowercased
+/// `EMAIL_ADDRESS`. Attributes with a usage prefix, such as
+/// `HOME_PHONE_NUMBER`, and attributes in nested lists count as well. A
+/// record is a duplicate when an earlier one had the same hash; other
+/// attributes, including `RECORD_ID`, are not compared.
+///
+/// The filter keeps 8 bytes per distinct record for as long as it lives.
+///
+/// # Example
+///
+/// ```
+/// use sz_rust_sdk::ingest::DuplicateFilter;
+///
+/// let mut filter = DuplicateFilter::new();
+/// let first = r#"{"DATA_SOURCE":"CRM","RECORD_ID":"1","NAME_FULL":"Ann Lee","PHONE_NUMBER":"555-0100"}"#;
+/// let again = r#"{"DATA_SOURCE":"CRM","RECORD_ID":"2","NAME_LAST":"LEE","NAME_FIRST":"ann","PHONE_NUMBER":"(555) 0100"}"#;
+/// assert!(!filter.is_duplicate(first)?);
+/// assert!(filter.is_duplicate(again)?);
+/// assert_eq!(filter.stats().skipped, 1);
+/// # Ok::<(), sz_rust_sdk::error::SzError>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct DuplicateFilter {
+    seen: HashSet<u64>,
+    stats: DedupStats,
+}
+
+impl DuplicateFilter {
+    /// Creates a filter that has seen no records.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the feature hash of a record, or `None` if it has none of
+    /// the compared features.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the record is not a JSON object.
+    pub fn feature_hash(record: &str) -> SzResult<Option<u64>> {
+        Ok(feature_hash(&parse_object(record)?))
+    }
+
+    /// Returns whether `record` duplicates a record checked before, and
+    /// remembers it otherwise.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the record is not a JSON object.
+    pub fn is_duplicate(&mut self, record: &str) -> SzResult<bool> {
+        let hash = Self::feature_hash(record)?;
+        self.stats.checked += 1;
+        let Some(hash) = hash else {
+            self.stats.without_features += 1;
+            return Ok(false);
+        };
+        if self.seen.insert(hash) {
+            return Ok(false);
+        }
+        self.stats.skipped += 1;
+        Ok(true)
+    }
+
+    /// Counts of the records checked so far.
+    pub fn stats(&self) -> DedupStats {
+        self.stats
+    }
+
+    /// Number of distinct feature hashes seen.
+    pub fn len(&self) -> usize {
+        self.seen.len()
+    }
+
+    /// Returns whether no record with features has been seen.
+    pub fn is_empty(&self) -> bool {
+        self.seen.is_empty()
+    }
+}
+
+/// Normalized features of a record, as compared by [`DuplicateFilter`].
+#[derive(Default)]
+struct Features {
+    name_tokens: BTreeSet<String>,
+    dates_of_birth: BTreeSet<String>,
+    emails: BTreeSet<String>,
+    phones: BTreeSet<String>,
+}
+
+impl Features {
+    fn collect(&mut self, document: &Map<String, Value>) {
+        for (key, value) in document {
+            match value {
+                Value::String(text) => self.add(key, text),
+                Value::Array(items) => {
+                    for item in items {
+                        if let Value::Object(nested) = item {
+                            self.collect(nested);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn add(&mut self, attribute: &str, text: &str) {
+        let is = |name: &str| normalize::attribute_matches(attribute, name);
+        if NAME_ATTRIBUTES.iter().any(|name| is(name)) {
+            self.name_tokens.extend(
+                text.split(|c: char| !c.is_alphanumeric())
+                    .filter(|token| !token.is_empty())
+                    .map(str::to_uppercase),
+            );
+        } else if is("DATE_OF_BIRTH") {
+            insert_nonempty(&mut self.dates_of_birth, digits(text));
+        } else if is("EMAIL_ADDRESS") {
+            insert_nonempty(&mut self.emails, text.trim().to_lowercase());
+        } else if is("PHONE_NUMBER") {
+            insert_nonempty(&mut self.phones, digits(text));
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.name_tokens.is_empty()
+            && self.dates_of_birth.is_empty()
+            && self.emails.is_empty()
+            && self.phones.is_empty()
+    }
+}
+
+fn digits(text: &str) -> String {
+    text.chars().filter(char::is_ascii_digit).collect()
+}
+
+fn insert_nonempty(set: &mut BTreeSet<String>, value: String) {
+    if !value.is_empty() {
+        set.insert(value);
+    }
+}
+
+/// Hashes the data source and features of a parsed record.
+fn feature_hash(document: &Map<String, Value>) -> Option<u64> {
+    let mut features = Features::default();
+    features.collect(document);
+    if features.is_empty() {
+        return None;
+    }
+    let data_source = key_field(document, "DATA_SOURCE")
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+        .to_uppercase();
+    let mut canonical = data_source;
+    for group in [
+        &features.name_tokens,
+        &features.dates_of_birth,
+        &features.emails,
+        &features.phones,
+    ] {
+        canonical.push('\u{1e}');
+        for (i, value) in group.iter().enumerate() {
+            if i > 0 {
+                canonical.push('\u{1f}');
+            }
+            canonical.push_str(value);
+        }
+    }
+    Some(fnv1a_64(canonical.as_bytes()))
+}
+
+pub(crate) fn fnv1a_64(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+    bytes.iter().fold(OFFSET_BASIS, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
+}
+
+fn check_key(key: &IngestRecordKey, data_source_code: &str, record_id: &str) -> SzResult<()> {
+    if let Some(embedded) = &key.data_source
+        && !embedded.eq_ignore_ascii_case(data_source_code)
+    {
+        return Err(SzError::bad_input(format!(
+            "record DATA_SOURCE {embedded:?} does not match data source code {data_source_code:?}"
+        )));
+    }
+    if let Some(embedded) = &key.record_id
+        && embedded != record_id
+    {
+        return Err(SzError::bad_input(format!(
+            "record RECORD_ID {embedded:?} does not match record ID {record_id:?}"
+        )));
+    }
+    Ok(())
+}
+
+pub(crate) fn parse_object(record: &str) -> SzResult<Map<String, Value>> {
+    match serde_json::from_str(record) {
+        Ok(Value::Object(document)) => Ok(document),
+        Ok(_) => Err(SzError::bad_input("record must be a JSON object")),
+        Err(e) => Err(SzError::bad_input(format!("record is not valid JSON: {e}"))),
+    }
+}
+
+pub(crate) fn key_field(document: &Map<String, Value>, name: &str) -> SzResult<Option<String>> {
+    match document.get(name) {
+        None | Some(Value::Null) => Ok(None),
+        Some(Value::String(value)) => Ok(Some(value.clone())),
+        Some(Value::Number(value)) => Ok(Some(value.to_string())),
+        Some(_) => Err(SzError::bad_input(format!(
+            "record {name} must be a string or number"
+        ))),
+    }
+}
+
+/// Optional steps of a load run by [`load_from_reader_with_options`].
+#[derive(Debug, Default)]
+pub struct LoadOptions<'a> {
+    control: Option<&'a ControlHandle>,
+    cancellation: Option<&'a SzCancellationToken>,
+    mapping: Option<&'a Mapping>,
+    normalizer: Option<&'a Normalizer>,
+    duplicates: Option<&'a mut DuplicateFilter>,
+    license: Option<&'a LicenseGuard>,
+    watermarks: Option<&'a WatermarkStore>,
+    timestamp_field: Option<&'a str>,
+    pub(crate) metadata: Option<&'a LoadMetadata>,
+}
+
+impl<'a> LoadOptions<'a> {
+    /// Creates options for a plain load.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Checks `control` before each line, as in
+    /// [`load_from_reader_with_control`].
+    pub fn with_control(mut self, control: &'a ControlHandle) -> Self {
+        self.control = Some(control);
+        self
+    }
+
+    /// Stops the load before the next line once `token` is cancelled, with
+    /// [`LoadSummary::stopped`] set.
+    pub fn with_cancellation(mut self, token: &'a SzCancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Converts each source record with `mapping` before anything else.
+    pub fn with_mapping(mut self, mapping: &'a Mapping) -> Self {
+        self.mapping = Some(mapping);
+        self
+    }
+
+    /// Cleans each record with `normalizer` before it is checked and added.
+    pub fn with_normalizer(mut self, normalizer: &'a Normalizer) -> Self {
+        self.normalizer = Some(normalizer);
+        self
+    }
+
+    /// Skips records that `filter` finds to be duplicates, as in
+    /// [`load_from_reader_deduplicated`].
+    pub fn with_duplicate_filter(mut self, filter: &'a mut DuplicateFilter) -> Self {
+        self.duplicates = Some(filter);
+        self
+    }
+
+    /// Checks each record against the license record limit in `guard`
+    /// before it is added.
+    pub fn with_license_guard(mut self, guard: &'a LicenseGuard) -> Self {
+        self.license = Some(guard);
+        self
+    }
+
+    /// Raises the watermark in `store` for each record added, by the
+    /// timestamp in its [`with_timestamp_field`](Self::with_timestamp_field)
+    /// attribute, and checkpoints the store when the load ends without an
+    /// error. Records without the attribute are loaded but not tracked; see
+    /// the [`watermark`] module.
+    pub fn with_watermarks(mut self, store: &'a WatermarkStore) -> Self {
+        self.watermarks = Some(store);
+        self
+    }
+
+    /// Names the timestamp attribute read for
+    /// [`with_watermarks`](Self::with_watermarks), instead of
+    /// [`DEFAULT_TIMESTAMP_FIELD`](dual_write::DEFAULT_TIMESTAMP_FIELD).
+    pub fn with_timestamp_field(mut self, field: &'a str) -> Self {
+        self.timestamp_field = Some(field);
+        self
+    }
+
+    /// The timestamp of `record` when watermarks are tracked and it has one.
+    fn timestamp(&self, record: &str) -> SzResult<Option<i64>> {
+        if self.watermarks.is_none() {
+            return Ok(None);
+        }
+        let field = self
+            .timestamp_field
+            .unwrap_or(dual_write::DEFAULT_TIMESTAMP_FIELD);
+        parse_object(record)?
+            .get(field)
+            .map(dual_write::parse_timestamp)
+            .transpose()
+    }
+
+    /// Raises the watermarks of `summary` and the store for a record added.
+    fn observe(&self, data_source: &str, timestamp: Option<i64>, summary: &mut LoadSummary) {
+        let (Some(store), Some(timestamp)) = (self.watermarks, timestamp) else {
+            return;
+        };
+        store.observe(data_source, timestamp);
+        let watermark = summary
+            .watermarks
+            .entry(data_source.to_ascii_uppercase())
+            .or_insert(timestamp);
+        *watermark = (*watermark).max(timestamp);
+    }
+
+    /// Whether the load should stop before the next line, waiting while the
+    /// control handle is paused.
+    fn stop_requested(&self) -> bool {
+        if let Some(control) = self.control
+            && !control.checkpoint()
+        {
+            return true;
+        }
+        self.cancellation
+            .is_some_and(SzCancellationToken::is_cancelled)
+    }
+}
+
+/// Loads line-delimited JSON records from `reader` into `engine`.
+///
+/// Stops at the first failure. The returned error names the 1-based line
+/// number so the load can be resumed or the source fixed.
+///
+/// # Errors
+///
+/// * `SzError::BadInput` - A line could not be read, is not JSON, or lacks
+///   `DATA_SOURCE` / `RECORD_ID`
+/// * Any error from `SzEngine::add_record`
+pub fn load_from_reader<R: BufRead>(engine: &dyn SzEngine, reader: R) -> SzResult<LoadSummary> {
+    load_from_reader_with_options(engine, reader, LoadOptions::new())
+}
+
+/// Loads line-delimited JSON records like [`load_from_reader`], checking
+/// `control` before each line.
+///
+/// While the handle is paused the load waits; after a stop request it
+/// returns with [`LoadSummary::stopped`] set. The handle is left in the
+/// stopped state when this returns, whatever the outcome.
+///
+/// # Errors
+///
+/// Same as [`load_from_reader`].
+pub fn load_from_reader_with_control<R: BufRead>(
+    engine: &dyn SzEngine,
+    reader: R,
+    control: &ControlHandle,
+) -> SzResult<LoadSummary> {
+    load_from_reader_with_options(engine, reader, LoadOptions::new().with_control(control))
+}
+
+/// Loads line-delimited JSON records like [`load_from_reader`], skipping
+/// records that `filter` finds to be duplicates.
+///
+/// Skipped records are counted in [`LoadSummary::duplicates_skipped`] and
+/// in the filter's [`DedupStats`]. The filter can be reused across loads to
+/// find duplicates between sources of the same data source.
+///
+/// # Errors
+///
+/// Same as [`load_from_reader`].
+pub fn load_from_reader_deduplicated<R: BufRead>(
+    engine: &dyn SzEngine,
+    reader: R,
+    filter: &mut DuplicateFilter,
+) -> SzResult<LoadSummary> {
+    load_from_reader_with_options(
+        engine,
+        reader,
+        LoadOptions::new().with_duplicate_filter(filter),
+    )
+}
+
+/// Loads line-delimited JSON records like [`load_from_reader`] with the
+/// steps set in `options`.
+///
+/// Each record is mapped and normalized first, so duplicates are detected
+/// and records are added in their converted, cleaned form. A control handle given in `options` is
+/// left in the stopped state when this returns.
+///
+/// # Errors
+///
+/// Same as [`load_from_reader`].
+pub fn load_from_reader_with_options<R: BufRead>(
+    engine: &dyn SzEngine,
+    reader: R,
+    options: LoadOptions<'_>,
+) -> SzResult<LoadSummary> {
+    let control = options.control;
+    let watermarks = options.watermarks;
+    let result = load_lines(engine, reader, options);
+    if let Some(control) = control {
+        control.finished();
+    }
+    let summary = result?;
+    if let Some(store) = watermarks {
+        store.checkpoint()?;
+    }
+    Ok(summary)
+}
+
+/// Maps a failure reading line `line_number` of the source. Object store
+/// download failures keep their own kind, so transient ones are retryable.
+fn read_error(line_number: usize, e: std::io::Error) -> SzError {
+    #[cfg(feature = "object-store")]
+    if let Some(error) = crate::cloud_storage::io_storage_error(&e) {
+        return error;
+    }
+    SzError::bad_input(format!("Failed reading line {line_number}: {e}"))
+}
+
+/// Loads line-delimited JSON records from cloud object storage into
+/// `engine`.
+///
+/// `url` names the object: `s3://bucket/key`, `gs://bucket/key`,
+/// `az://container/key` (or the providers' `https://` forms), or
+/// `file:///path` for a local file. Credentials and regions are read from the
+/// provider's usual environment variables (`AWS_*`, `GOOGLE_*`, `AZURE_*`).
+/// The object is streamed as it is loaded, on a tokio runtime owned by the
+/// call; do not call this from inside an async task.
+///
+/// # Errors
+///
+/// * `SzError::BadInput` - `url` is not an `s3://`, `gs://`, `az://` or
+///   `file://` URL, or as in [`load_from_reader`]
+/// * `SzError::Configuration` - The store cannot be configured, e.g. for
+///   missing credentials
+/// * `SzError::NotFound` - The object does not exist
+/// * `SzError::Retryable` - A request failed after the client's own retries,
+///   e.g. on a network error or timeout
+/// * `SzError::Unknown` - The object store rejected a request
+/// * Any error from `SzEngine::add_record`
+///
+/// # Example
+///
+/// ```no_run
+/// # use sz_rust_sdk::helpers::ExampleEnvironment;
+/// use sz_rust_sdk::ingest;
+/// use sz_rust_sdk::prelude::*;
+///
+/// # let env = ExampleEnvironment::initialize("doctest_load_from_object_store")?;
+/// let engine = env.get_engine()?;
+/// let summary = ingest::load_from_object_store(&*engine, "gs://landing/customers.jsonl")?;
+/// println!("loaded {} records", summary.records_loaded);
+/// # Ok::<(), SzError>(())
+/// ```
+#[cfg(feature = "object-store")]
+pub fn load_from_object_store(engine: &dyn SzEngine, url: &str) -> SzResult<LoadSummary> {
+    load_from_object_store_with_options(engine, url, LoadOptions::new())
+}
+
+/// Loads line-delimited JSON records from cloud object storage like
+/// [`load_from_object_store`], with the steps set in `options`.
+///
+/// # Errors
+///
+/// Same as [`load_from_object_store`].
+#[cfg(feature = "object-store")]
+pub fn load_from_object_store_with_options(
+    engine: &dyn SzEngine,
+    url: &str,
+    options: LoadOptions<'_>,
+) -> SzResult<LoadSummary> {
+    let location = crate::cloud_storage::ObjectLocation::parse(url)?;
+    let reader = std::io::BufReader::new(location.download()?);
+    load_from_reader_with_options(engine, reader, options)
+}
+
+/// Loads CSV records from `reader` into `engine` with the steps set in
+/// `options`.
+///
+/// The first row names the columns; each later row becomes a record with
+/// one string attribute per non-empty column, which is then loaded as in
+/// [`load_from_reader_with_options`]. Without a mapping the column names
+/// must be Senzing attributes, including `DATA_SOURCE` and `RECORD_ID`.
+/// Fields may be quoted with `"`, contain commas and line breaks, and
+/// escape quotes by doubling them. Errors name the line the row starts on.
+///
+/// # Errors
+///
+/// * `SzError::BadInput` - The input has no header row, a row has more
+///   fields than the header or an unterminated quote, or a record lacks
+///   `DATA_SOURCE` / `RECORD_ID`
+/// * Any error from `SzEngine::add_record`
+pub fn load_csv_with_options<R: BufRead>(
+    engine: &dyn SzEngine,
+    reader: R,
+    options: LoadOptions<'_>,
+) -> SzResult<LoadSummary> {
+    let control = options.control;
+    let watermarks = options.watermarks;
+    let result = load_csv_rows(engine, reader, options);
+    if let Some(control) = control {
+        control.finished();
+    }
+    let summary = result?;
+    if let Some(store) = watermarks {
+        store.checkpoint()?;
+    }
+    Ok(summary)
+}
+
+/// Loads CSV rows after the header, applying each step set in `options`.
+fn load_csv_rows<R: BufRead>(
+    engine: &dyn SzEngine,
+    mut reader: R,
+    mut options: LoadOptions<'_>,
+) -> SzResult<LoadSummary> {
+    let mut line_number = 0;
+    let Some((_, header)) = read_csv_row(&mut reader, &mut line_number)? else {
+        return Err(SzError::bad_input("CSV input has no header row"));
+    };
+    let mut summary = LoadSummary::default();
+    loop {
+        if options.stop_requested() {
+            summary.stopped = true;
+            break;
+        }
+        let Some((row_line, fields)) = read_csv_row(&mut reader, &mut line_number)? else {
+            break;
+        };
+        if fields.iter().all(|field| field.trim().is_empty()) {
+            summary.blank_lines += 1;
+            continue;
+        }
+        if fields.len() > header.len() {
+            return Err(SzError::bad_input(format!(
+                "Line {row_line}: row has {} fields but the header has {}",
+                fields.len(),
+                header.len()
+            )));
+        }
+        let row: Map<String, Value> = header
+            .iter()
+            .zip(fields)
+            .filter(|(_, field)| !field.is_empty())
+            .map(|(column, field)| (column.clone(), Value::String(field)))
+            .collect();
+        let record = Value::Object(row).to_string();
+        load_record(engine, &record, row_line, &mut options, &mut summary)?;
+        if summary.stopped {
+            break;
+        }
+    }
+    Ok(summary)
+}
+
+/// Reads one CSV row, which may span lines inside quotes. Returns the line
+/// the row starts on and its fields, or `None` at the end of the input.
+fn read_csv_row<R: BufRead>(
+    reader: &mut R,
+    line_number: &mut usize,
+) -> SzResult<Option<(usize, Vec<String>)>> {
+    let start = *line_number + 1;
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = reader
+            .read_line(&mut line)
+            .map_err(|e| read_error(*line_number + 1, e))?;
+        if read == 0 {
+            if quoted {
+                return Err(SzError::bad_input(format!(
+                    "Line {start}: unterminated quoted field"
+                )));
+            }
+            return Ok(None);
+        }
+        *line_number += 1;
+        let mut chars = line.strip_suffix('\n').unwrap_or(&line).chars().peekable();
+        if !quoted && chars.peek().is_none() {
+            return Ok(Some((start, Vec::new())));
+        }
+        while let Some(c) = chars.next() {
+            match (quoted, c) {
+                (true, '"') if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                (true, '"') => quoted = false,
+                (true, c) => field.push(c),
+                (false, '"') if field.is_empty() => quoted = true,
+                (false, ',') => fields.push(std::mem::take(&mut field)),
+                (false, '\r') if chars.peek().is_none() => {}
+                (false, c) => field.push(c),
+            }
+        }
+        if quoted {
+            field.push('\n');
+        } else {
+            fields.push(field);
+            return Ok(Some((start, fields)));
+        }
+    }
+}
+
+/// Loads lines, applying each step set in `options`.
+fn load_lines<R: BufRead>(
+    engine: &dyn SzEngine,
+    reader: R,
+    mut options: LoadOptions<'_>,
+) -> SzResult<LoadSummary> {
+    let mut summary = LoadSummary::default();
+    for (index, line) in reader.lines().enumerate() {
+        if options.stop_requested() {
+            summary.stopped = true;
+            break;
+        }
+        let line_number = index + 1;
+        let line = line.map_err(|e| read_error(line_number, e))?;
+        let record = line.trim();
+        if record.is_empty() {
+            summary.blank_lines += 1;
+            continue;
+        }
+        load_record(engine, record, line_number, &mut options, &mut summary)?;
+        if summary.stopped {
+            break;
+        }
+    }
+    Ok(summary)
+}
+
+/// Maps, normalizes, checks and adds one record, counting it in `summary`.
+/// Sets `summary.stopped` instead if the license guard halts the load.
+fn load_record(
+    engine: &dyn SzEngine,
+    record: &str,
+    line_number: usize,
+    options: &mut LoadOptions<'_>,
+    summary: &mut LoadSummary,
+) -> SzResult<()> {
+    let line_error =
+        |e: SzError| SzError::bad_input(format!("Line {line_number}: {}", e.message()));
+    let mut record = Cow::Borrowed(record);
+    if let Some(mapping) = options.mapping {
+        record = Cow::Owned(mapping.map_record(&record).map_err(line_error)?);
+    }
+    if let Some(normalizer) = options.normalizer {
+        record = Cow::Owned(normalizer.normalize_record(&record).map_err(line_error)?);
+    }
+
+    let (data_source, record_id) = parse_record_key(&record)
+        .map_err(|e| SzError::bad_input(format!("Line {line_number}: {e}")))?;
+    if let Some(filter) = options.duplicates.as_deref_mut()
+        && filter.is_duplicate(&record).map_err(line_error)?
+    {
+        summary.duplicates_skipped += 1;
+        return Ok(());
+    }
+    if let Some(metadata) = options.metadata {
+        record = Cow::Owned(metadata.apply(&record).map_err(line_error)?);
+    }
+    if let Some(guard) = options.license {
+        match guard.check(summary.records_loaded) {
+            LicenseCheck::Clear => {}
+            LicenseCheck::Approaching => summary.license_warning = true,
+            LicenseCheck::Exceeded => {
+                summary.license_warning = true;
+                summary.license_limit_reached = true;
+                if guard.action == LicenseAction::Halt {
+                    summary.stopped = true;
+                    return Ok(());
+                }
+            }
+        }
+    }
+    let timestamp = options.timestamp(&record).map_err(line_error)?;
+    engine.add_record(&data_source, &record_id, &record, None)?;
+    summary.records_loaded += 1;
+    options.observe(&data_source, timestamp, summary);
+    Ok(())
+}
+
+/// Extracts the `DATA_SOURCE` and `RECORD_ID` a loadable record must carry.
+fn parse_record_key(record: &str) -> Result<(String, String), String> {
+    const REQUIRED: &str = "record must be a JSON object with DATA_SOURCE and RECORD_ID";
+    match IngestRecordKey::extract(record) {
+        Ok(IngestRecordKey {
+            data_source: Some(data_source),
+            record_id: Some(record_id),
+        }) => Ok((data_source, record_id)),
+        Ok(_) => Err(REQUIRED.to_string()),
+        Err(e) => Err(format!("{REQUIRED}: {}", e.message())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_record_key() {
+        let (data_source, record_id) =
+            parse_record_key(r#"{"DATA_SOURCE":"TEST","RECORD_ID":"42","NAME_FULL":"A B"}"#)
+                .unwrap();
+        assert_eq!(data_source, "TEST");
+        assert_eq!(record_id, "42");
+    }
+
+    #[test]
+    fn test_parse_record_key_missing_fields() {
+        assert!(parse_record_key(r#"{"RECORD_ID":"42"}"#).is_err());
+        assert!(parse_record_key(r#"{"DATA_SOURCE":"TEST"}"#).is_err());
+        assert!(parse_record_key("not json").is_err());
+    }
+
+    #[test]
+    fn test_record_key_numeric_and_missing() {
+        let key = IngestRecordKey::extract(r#"{"RECORD_ID":7,"DATA_SOURCE":null}"#).unwrap();
+        assert_eq!(key.record_id.as_deref(), Some("7"));
+        assert_eq!(key.data_source, None);
+        assert!(IngestRecordKey::extract(r#"{"RECORD_ID":["7"]}"#).is_err());
+        assert!(IngestRecordKey::extract("[1]").is_err());
+    }
+
+    #[test]
+    fn test_check_record_key_mismatch() {
+        let record = r#"{"DATA_SOURCE":"test","RECORD_ID":"1"}"#;
+        assert!(check_record_key("TEST", "1", record).is_ok());
+        let err = check_record_key("OTHER", "1", record).unwrap_err();
+        assert!(err.message().contains("DATA_SOURCE"));
+        let err = check_record_key("TEST", "01", record).unwrap_err();
+        assert!(err.message().contains("RECORD_ID"));
+        assert!(check_record_key("TEST", "1", "{}").is_ok());
+    }
+
+    #[test]
+    fn test_fill_record_key() {
+        let complete = r#"{"DATA_SOURCE":"TEST","RECORD_ID":"1"}"#;
+        assert!(matches!(
+            fill_record_key("TEST", "1", complete).unwrap(),
+            Cow::Borrowed(_)
+        ));
+
+        let filled =
+            fill_record_key("TEST", "1", r#"{"RECORD_ID":"1","NAME_FULL":"A B"}"#).unwrap();
+        let key = IngestRecordKey::extract(&filled).unwrap();
+        assert_eq!(key.data_source.as_deref(), Some("TEST"));
+        assert_eq!(key.record_id.as_deref(), Some("1"));
+        assert!(filled.contains("NAME_FULL"));
+
+        assert!(fill_record_key("TEST", "2", r#"{"RECORD_ID":"1"}"#).is_err());
+    }
+
+    #[test]
+    fn test_duplicate_filter() {
+        let mut filter = DuplicateFilter::new();
+        let record = |id: &str, features: &str| {
+            format!(r#"{{"DATA_SOURCE":"CRM","RECORD_ID":"{id}",{features}}}"#)
+        };
+        assert!(
+            !filter
+                .is_duplicate(&record(
+                    "1",
+                    r#""NAME_FULL":"Lee, Ann","DATE_OF_BIRTH":"1980-01-02""#
+                ))
+                .unwrap()
+        );
+        // Same features in another shape
+        assert!(
+            filter
+                .is_duplicate(&record(
+                    "2",
+                    r#""NAMES":[{"PRIMARY_NAME_FIRST":"ANN","PRIMARY_NAME_LAST":"LEE"}],"DATE_OF_BIRTH":"1980/01/02""#
+                ))
+                .unwrap()
+        );
+        assert!(
+            filter
+                .is_duplicate(&record(
+                    "3",
+                    r#""NAME_FULL":"Ann Lee","DATE_OF_BIRTH":"1980-01-02""#
+                ))
+                .unwrap()
+        );
+        // An extra feature makes it a different record
+        assert!(
+            !filter
+                .is_duplicate(&record(
+                    "4",
+                    r#""NAME_FULL":"Ann Lee","DATE_OF_BIRTH":"1980-01-02","EMAIL_ADDRESS":"ann@x.com""#
+                ))
+                .unwrap()
+        );
+        // So does another data source
+        assert!(
+            !filter
+                .is_duplicate(r#"{"DATA_SOURCE":"WATCHLIST","NAME_FULL":"Ann Lee","DATE_OF_BIRTH":"1980-01-02"}"#)
+                .unwrap()
+        );
+        assert!(
+            !filter
+                .is_duplicate(&record("5", r#""ADDR_CITY":"Oslo""#))
+                .unwrap()
+        );
+        assert!(
+            !filter
+                .is_duplicate(&record("6", r#""ADDR_CITY":"Oslo""#))
+                .unwrap()
+        );
+        assert!(filter.is_duplicate("[]").is_err());
+
+        let stats = filter.stats();
+        assert_eq!(stats.checked, 7);
+        assert_eq!(stats.without_features, 2);
+        assert_eq!(stats.skipped, 2);
+        assert_eq!(DuplicateFilter::feature_hash("{}").unwrap(), None);
+    }
+
+    #[test]
+    fn test_record_content_hash() {
+        let base = record_content_hash(r#"{"A":"1","B":[{"X":1,"Y":2}]}"#).unwrap();
+        assert_eq!(
+            record_content_hash(r#"{"DATA_SOURCE":"TEST","B":[{"Y":2,"X":1}],"A":"1"}"#).unwrap(),
+            base
+        );
+        assert_ne!(
+            record_content_hash(r#"{"A":"2","B":[{"X":1,"Y":2}]}"#).unwrap(),
+            base
+        );
+        // Array order is content
+        assert_ne!(
+            record_content_hash(r#"{"A":"1","B":[{"Y":2},{"X":1}]}"#).unwrap(),
+            base
+        );
+        assert!(record_content_hash("[]").is_err());
+        // Fixed value, so stored hashes stay valid across releases
+        assert_eq!(record_content_hash("{}").unwrap(), fnv1a_64(b"{}"));
+        assert_eq!(fnv1a_64(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a_64(b"a"), 0xaf63_dc4c_8601_ec8c);
+    }
+
+    #[test]
+    fn test_read_csv_row() {
+        let mut reader = "a,\"b, \"\"c\"\"\",\r\n\n\"multi\nline\",x\n\"open".as_bytes();
+        let mut line = 0;
+        assert_eq!(
+            read_csv_row(&mut reader, &mut line).unwrap(),
+            Some((1, vec!["a".into(), "b, \"c\"".into(), String::new()]))
+        );
+        assert_eq!(
+            read_csv_row(&mut reader, &mut line).unwrap(),
+            Some((2, vec![]))
+        );
+        assert_eq!(
+            read_csv_row(&mut reader, &mut line).unwrap(),
+            Some((3, vec!["multi\nline".into(), "x".into()]))
+        );
+        assert!(read_csv_row(&mut reader, &mut line).is_err());
+        assert_eq!(read_csv_row(&mut reader, &mut line).unwrap(), None);
+    }
+
+    #[test]
+    fn test_load_watermarks() {
+        let record = |timestamp: &str| format!(r#"{{"DATA_SOURCE":"TEST","TS":{timestamp}}}"#);
+        assert_eq!(LoadOptions::new().timestamp(&record("5")).unwrap(), None);
+
+        let store = WatermarkStore::new();
+        let options = LoadOptions::new()
+            .with_watermarks(&store)
+            .with_timestamp_field("TS");
+        let mut summary = LoadSummary::default();
+        for timestamp in ["20", "\"10\""] {
+            let timestamp = options.timestamp(&record(timestamp)).unwrap();
+            options.observe("test", timestamp, &mut summary);
+        }
+        options.observe("test", None, &mut summary);
+        assert_eq!(summary.watermarks(), &BTreeMap::from([("TEST".into(), 20)]));
+        assert_eq!(store.get("TEST"), Some(20));
+        assert_eq!(
+            options.timestamp(r#"{"DATA_SOURCE":"TEST"}"#).unwrap(),
+            None
+        );
+        assert!(options.timestamp(&record("[1]")).is_err());
+    }
+}
@@ -0,0 +1,220 @@
+//! Per-data-source high-watermarks of loaded source timestamps.
+//!
+//! Incremental extracts ask the source for everything changed since the
+//! last load. A [`WatermarkStore`] keeps, for each data source, the latest
+//! record timestamp that has been loaded, so the next extract can start
+//! there. [`checkpoint`](WatermarkStore::checkpoint) writes the watermarks to
+//! a JSON file, `{"CUSTOMERS": 1772366400000, ...}`, which
+//! [`open`](WatermarkStore::open) reads back on the next run.
+//!
+//! Watermarks only move forward. Checkpoint after the records up to the
+//! watermark are durable in the repository. For a stream, that is also when
+//! its consumer offsets are committed. A restarted extract then re-sends at
+//! most the records since the last checkpoint, and reloading those is
+//! harmless.
+//!
+//! With the `encryption` feature, `WatermarkStore::open_encrypted` keeps
+//! the checkpoint file sealed with a `PayloadCipher`, as one sealed line.
+//!
+//! [`DualWriteLoader`](super::DualWriteLoader) raises the watermark for
+//! every record it writes or finds superseded; see
+//! [`DualWriteLoader::watermarks`](super::DualWriteLoader::watermarks).
+//! The bulk loads of the [`ingest`](super) module raise it for every record
+//! they add when given a store with
+//! [`LoadOptions::with_watermarks`](super::LoadOptions::with_watermarks), and
+//! report the watermarks of the load in
+//! [`LoadSummary::watermarks`](super::LoadSummary::watermarks).
+
+use crate::encryption::PayloadCipher;
+use crate::error::{SzError, SzResult};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+
+/// Latest loaded source timestamp per data source, optionally persisted to
+/// a checkpoint file; see the [module documentation](self).
+#[derive(Debug, Default)]
+pub struct WatermarkStore {
+    path: Option<PathBuf>,
+    cipher: Option<PayloadCipher>,
+    watermarks: Mutex<BTreeMap<String, i64>>,
+}
+
+impl WatermarkStore {
+    /// Creates a store kept in memory only.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Opens the store checkpointed at `path`, starting empty if the file
+    /// does not exist yet.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the file cannot be read or is not a JSON
+    /// object of integers.
+    pub fn open(path: impl AsRef<Path>) -> SzResult<Self> {
+        Self::open_with(path.as_ref(), None)
+    }
+
+    /// Opens the store checkpointed at `path` like [`open`](Self::open),
+    /// with the checkpoint file sealed by `cipher`.
+    ///
+    /// # Errors
+    ///
+    /// As for [`open`](Self::open); also `SzError::BadInput` if the file is
+    /// not sealed with `cipher`.
+    #[cfg(feature = "encryption")]
+    pub fn open_encrypted(path: impl AsRef<Path>, cipher: PayloadCipher) -> SzResult<Self> {
+        Self::open_with(path.as_ref(), Some(cipher))
+    }
+
+    fn open_with(path: &Path, cipher: Option<PayloadCipher>) -> SzResult<Self> {
+        let path = path.to_path_buf();
+        let invalid = |e: String| {
+            SzError::bad_input(format!("Invalid watermarks in {}: {e}", path.display()))
+        };
+        let watermarks = match std::fs::read_to_string(&path) {
+            Ok(mut text) => {
+                if let Some(cipher) = &cipher {
+                    text = cipher
+                        .open_line(&text)
+                        .map_err(|e| invalid(e.message().to_string()))?;
+                }
+                serde_json::from_str(&text).map_err(|e| invalid(e.to_string()))?
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(io_error(&path, e)),
+        };
+        Ok(Self {
+            path: Some(path),
+            cipher,
+            watermarks: Mutex::new(watermarks),
+        })
+    }
+
+    /// The checkpoint file, if any.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Raises the watermark of `data_source` to `timestamp` and returns
+    /// whether it moved. Data source codes are uppercased.
+    pub fn observe(&self, data_source: &str, timestamp: i64) -> bool {
+        let mut watermarks = self.lock();
+        let key = data_source.to_ascii_uppercase();
+        if watermarks
+            .get(&key)
+            .is_some_and(|&watermark| watermark >= timestamp)
+        {
+            return false;
+        }
+        watermarks.insert(key, timestamp);
+        true
+    }
+
+    /// The watermark of `data_source`, if any record of it was loaded.
+    pub fn get(&self, data_source: &str) -> Option<i64> {
+        self.lock().get(&data_source.to_ascii_uppercase()).copied()
+    }
+
+    /// All watermarks, by data source.
+    pub fn watermarks(&self) -> BTreeMap<String, i64> {
+        self.lock().clone()
+    }
+
+    /// Writes the watermarks to the checkpoint file, replacing it
+    /// atomically. Does nothing for an in-memory store.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the file cannot be written.
+    pub fn checkpoint(&self) -> SzResult<()> {
+        let Some(path) = &self.path else {
+            return Ok(());
+        };
+        let json = match &self.cipher {
+            Some(cipher) => cipher
+                .seal_line(&serde_json::to_string(&*self.lock())?)?
+                .into_bytes(),
+            None => serde_json::to_vec_pretty(&*self.lock())?,
+        };
+        let mut temp = path.clone().into_os_string();
+        temp.push(".tmp");
+        let temp = PathBuf::from(temp);
+        let mut file = File::create(&temp).map_err(|e| io_error(&temp, e))?;
+        file.write_all(&json)
+            .and_then(|()| file.sync_data())
+            .map_err(|e| io_error(&temp, e))?;
+        std::fs::rename(&temp, path).map_err(|e| io_error(path, e))
+    }
+
+    fn lock(&self) -> MutexGuard<'_, BTreeMap<String, i64>> {
+        match self.watermarks.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> SzError {
+    SzError::bad_input(format!("Failed accessing {}: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watermarks_move_forward_and_persist() {
+        let path = std::env::temp_dir().join(format!("sz_watermarks_{}.json", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let store = WatermarkStore::open(&path).unwrap();
+        assert_eq!(store.get("customers"), None);
+        assert!(store.observe("customers", 20));
+        assert!(!store.observe("CUSTOMERS", 10));
+        assert!(!store.observe("CUSTOMERS", 20));
+        assert!(store.observe("WATCHLIST", 5));
+        assert_eq!(store.get("customers"), Some(20));
+        store.checkpoint().unwrap();
+
+        let reopened = WatermarkStore::open(&path).unwrap();
+        assert_eq!(
+            reopened.watermarks(),
+            BTreeMap::from([("CUSTOMERS".to_string(), 20), ("WATCHLIST".to_string(), 5)])
+        );
+        std::fs::write(&path, "[1]").unwrap();
+        assert!(WatermarkStore::open(&path).is_err());
+        let _ = std::fs::remove_file(&path);
+
+        assert!(WatermarkStore::new().checkpoint().is_ok());
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encrypted_checkpoint_round_trip() {
+        let path = std::env::temp_dir().join(format!(
+            "sz_watermarks_encrypted_{}.json",
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        let cipher = PayloadCipher::new(&[4u8; 32]).unwrap();
+
+        let store = WatermarkStore::open_encrypted(&path, cipher.clone()).unwrap();
+        store.observe("CUSTOMERS", 20);
+        store.checkpoint().unwrap();
+        let stored = std::fs::read_to_string(&path).unwrap();
+        assert!(stored.starts_with("szenc1:"));
+        assert!(!stored.contains("CUSTOMERS"));
+
+        let reopened = WatermarkStore::open_encrypted(&path, cipher).unwrap();
+        assert_eq!(reopened.get("CUSTOMERS"), Some(20));
+        assert!(WatermarkStore::open(&path).is_err());
+        let other = PayloadCipher::new(&[5u8; 32]).unwrap();
+        assert!(WatermarkStore::open_encrypted(&path, other).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+}
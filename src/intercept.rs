@@ -0,0 +1,477 @@
+//! Interceptors around engine calls.
+//!
+//! An [`SzInterceptor`] added with `SzEnvironmentCore::add_interceptor` sees
+//! every `SzEngine` call made by the environment's engines: [`before`] with
+//! the operation name and a summary of its arguments, and [`after`] with the
+//! duration and result code. That is enough for custom logging, metrics or
+//! authorization without wrapping the engine:
+//!
+//! - `before` returning an error rejects the call; the engine is not called
+//!   and the caller gets the error.
+//! - `before` returning [`Intercept::Respond`] answers the call with the given
+//!   JSON instead of calling the engine, e.g. from a cache filled in `after`.
+//!   Only calls that return JSON (`JsonString` or `SzBytes`) can be answered
+//!   this way; for others the response is ignored and the engine is called.
+//!
+//! Interceptors run on the calling thread in the order they were added, and
+//! their `after` hooks in reverse order. Every interceptor's `after` is called
+//! for every call, including calls rejected or answered by another
+//! interceptor.
+//!
+//! The argument summary holds identifiers, flags and limits, but never record
+//! definitions, search attributes or redo records, which carry personal
+//! data. Engine calls made while another one is intercepted on the same
+//! thread, such as `get_entity` reading through `get_entity_bytes` or engine
+//! calls from inside a hook, are not intercepted again. A panic in a hook is
+//! caught at the native boundary and returned as `SzError::Unhandled`.
+//!
+//! [`before`]: SzInterceptor::before
+//! [`after`]: SzInterceptor::after
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::intercept::{CallOutcome, EngineCall, Intercept, SzInterceptor};
+//! use sz_rust_sdk::prelude::*;
+//!
+//! struct SlowCallLogger;
+//!
+//! impl SzInterceptor for SlowCallLogger {
+//!     fn after(&self, call: &EngineCall<'_>, outcome: &CallOutcome<'_>) {
+//!         if outcome.duration.as_millis() > 100 {
+//!             eprintln!("slow {} {:?}: {:?}", call.operation, call.parameters, outcome.duration);
+//!         }
+//!     }
+//! }
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_intercept")?;
+//! env.add_interceptor(Box::new(SlowCallLogger));
+//! let engine = env.get_engine()?;
+//! engine.get_stats()?;
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::audit::SDK_ERROR_CODE;
+use crate::bytes::SzBytes;
+use crate::core::settings::EngineSettings;
+use crate::error::{SzError, SzResult};
+use crate::types::{EntityRef, JsonString, RepositoryCounts};
+use serde_json::{Map, Value, json};
+use std::cell::Cell;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+thread_local! {
+    /// Whether an intercepted call is running on this thread
+    static ACTIVE: Cell<bool> = const { Cell::new(false) };
+    /// Number of engine calls running on this thread, so that only the
+    /// outermost one records its failure
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Where the calling thread is in a nesting of engine calls, carried to a
+/// worker thread that makes part of the call so the worker's engine calls
+/// count as nested too.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Nesting {
+    active: bool,
+    depth: usize,
+}
+
+impl Nesting {
+    /// The nesting of the current thread.
+    pub(crate) fn current() -> Self {
+        Self {
+            active: ACTIVE.get(),
+            depth: DEPTH.get(),
+        }
+    }
+
+    /// Runs `f` in this nesting, restoring the thread's own afterwards.
+    pub(crate) fn scope<R>(self, f: impl FnOnce() -> R) -> R {
+        let _restore = Restore(Self {
+            active: ACTIVE.replace(self.active),
+            depth: DEPTH.replace(self.depth),
+        });
+        f()
+    }
+}
+
+/// Puts a thread's nesting back, also on panic.
+struct Restore(Nesting);
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        ACTIVE.set(self.0.active);
+        DEPTH.set(self.0.depth);
+    }
+}
+
+/// An engine call about to be made.
+#[derive(Debug, Clone, Copy)]
+pub struct EngineCall<'a> {
+    /// `SzEngine` method name, e.g. `get_entity`
+    pub operation: &'static str,
+    /// Identifying arguments of the call, e.g. entity ID and flags
+    pub parameters: &'a Map<String, Value>,
+}
+
+/// How an engine call ended.
+#[derive(Debug, Clone, Copy)]
+pub struct CallOutcome<'a> {
+    /// Time from the first `before` hook to the end of the call
+    pub duration: Duration,
+    /// 0 on success, the native error code on failure, or
+    /// `audit::SDK_ERROR_CODE` for failures without one
+    pub result_code: i64,
+    /// The error, on failure
+    pub error: Option<&'a SzError>,
+    /// The JSON response, for successful calls that return JSON
+    pub response: Option<&'a str>,
+}
+
+/// What to do with a call after [`SzInterceptor::before`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Intercept {
+    /// Continue with the next interceptor and then the engine
+    Proceed,
+    /// Answer the call with this JSON instead of calling the engine
+    Respond(JsonString),
+}
+
+/// Hooks called around engine calls.
+pub trait SzInterceptor: Send + Sync {
+    /// Called before the engine call.
+    ///
+    /// # Errors
+    ///
+    /// Any error rejects the call and is returned to the caller.
+    fn before(&self, call: &EngineCall<'_>) -> SzResult<Intercept> {
+        let _ = call;
+        Ok(Intercept::Proceed)
+    }
+
+    /// Called after the engine call, or after the call was rejected or
+    /// answered by an interceptor.
+    fn after(&self, call: &EngineCall<'_>, outcome: &CallOutcome<'_>) {
+        let _ = (call, outcome);
+    }
+}
+
+/// The interceptors of an environment, in the order they were added.
+#[derive(Default)]
+pub(crate) struct Interceptors(pub(crate) Vec<Arc<dyn SzInterceptor>>);
+
+impl std::fmt::Debug for Interceptors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Interceptors({})", self.0.len())
+    }
+}
+
+/// Return types of engine calls, as seen by interceptors.
+pub(crate) trait Response: Sized {
+    /// The response as JSON, if it is JSON.
+    fn json(&self) -> Option<&str> {
+        None
+    }
+
+    /// The response built from JSON an interceptor answered with, if this
+    /// type can be.
+    fn from_json(json: JsonString) -> Option<Self> {
+        let _ = json;
+        None
+    }
+}
+
+impl Response for JsonString {
+    fn json(&self) -> Option<&str> {
+        Some(self)
+    }
+
+    fn from_json(json: JsonString) -> Option<Self> {
+        Some(json)
+    }
+}
+
+impl Response for SzBytes {
+    fn json(&self) -> Option<&str> {
+        std::str::from_utf8(self.as_bytes()).ok()
+    }
+
+    fn from_json(json: JsonString) -> Option<Self> {
+        Some(SzBytes::from(json))
+    }
+}
+
+impl Response for () {}
+impl Response for i64 {}
+impl Response for RepositoryCounts {}
+
+/// Evaluates the argument summary of a call, only when there are
+/// interceptors to see it and the call is not nested in an intercepted one.
+pub(crate) fn parameters(
+    settings: &EngineSettings,
+    parameters: impl FnOnce() -> Value,
+) -> Option<Map<String, Value>> {
+    if !settings.has_interceptors() || ACTIVE.get() {
+        return None;
+    }
+    match parameters() {
+        Value::Object(map) => Some(map),
+        _ => Some(Map::new()),
+    }
+}
+
+/// Runs `call` through the environment's interceptors. `parameters` is the
+/// summary from [`parameters`]; `None` means there are no interceptors.
+///
+/// Failed calls are kept in the environment's error log. A failure is kept
+/// once, for the outermost call, when calls nest such as `get_entity`
+/// reading through `get_entity_bytes`.
+pub(crate) fn run<T: Response>(
+    settings: &EngineSettings,
+    operation: &'static str,
+    parameters: Option<Map<String, Value>>,
+    call: impl FnOnce() -> SzResult<T>,
+) -> SzResult<T> {
+    let outer = Nesting::current();
+    let result = Nesting {
+        depth: outer.depth + 1,
+        ..outer
+    }
+    .scope(|| intercepted(settings, operation, parameters, call));
+    if let Err(e) = &result
+        && outer.depth == 0
+    {
+        settings.record_error(operation, e);
+    }
+    result
+}
+
+fn intercepted<T: Response>(
+    settings: &EngineSettings,
+    operation: &'static str,
+    parameters: Option<Map<String, Value>>,
+    call: impl FnOnce() -> SzResult<T>,
+) -> SzResult<T> {
+    // Closing an export releases native resources, so it is never refused
+    if operation != "close_export_report" {
+        settings.check_cancelled(operation)?;
+    }
+    let Some(parameters) = parameters else {
+        return call();
+    };
+    struct Active;
+    impl Drop for Active {
+        fn drop(&mut self) {
+            ACTIVE.set(false);
+        }
+    }
+    ACTIVE.set(true);
+    let _active = Active;
+
+    let interceptors: Vec<Arc<dyn SzInterceptor>> = settings.interceptors();
+    let engine_call = EngineCall {
+        operation,
+        parameters: &parameters,
+    };
+    let start = Instant::now();
+
+    let mut answered = None;
+    let mut rejected = None;
+    for interceptor in &interceptors {
+        match interceptor.before(&engine_call) {
+            Ok(Intercept::Proceed) => {}
+            Ok(Intercept::Respond(json)) => {
+                if let Some(response) = T::from_json(json) {
+                    answered = Some(response);
+                    break;
+                }
+            }
+            Err(e) => {
+                rejected = Some(e);
+                break;
+            }
+        }
+    }
+    let result = match (rejected, answered) {
+        (Some(e), _) => Err(e),
+        (None, Some(response)) => Ok(response),
+        (None, None) => call(),
+    };
+
+    let outcome = CallOutcome {
+        duration: start.elapsed(),
+        result_code: match &result {
+            Ok(_) => 0,
+            Err(e) => e.error_code().unwrap_or(SDK_ERROR_CODE),
+        },
+        error: result.as_ref().err(),
+        response: result.as_ref().ok().and_then(Response::json),
+    };
+    for interceptor in interceptors.iter().rev() {
+        interceptor.after(&engine_call, &outcome);
+    }
+    result
+}
+
+/// Summary of an [`EntityRef`] argument.
+pub(crate) fn entity_ref(entity_ref: &EntityRef<'_>) -> Value {
+    match entity_ref {
+        EntityRef::Id(entity_id) => json!({ "entity_id": entity_id }),
+        EntityRef::Record {
+            data_source,
+            record_id,
+        } => json!({ "data_source": data_source, "record_id": record_id }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder {
+        seen: Mutex<Vec<String>>,
+        name: &'static str,
+        before: Option<fn() -> SzResult<Intercept>>,
+    }
+
+    impl SzInterceptor for Recorder {
+        fn before(&self, call: &EngineCall<'_>) -> SzResult<Intercept> {
+            self.seen
+                .lock()
+                .unwrap()
+                .push(format!("{} before {}", self.name, call.operation));
+            self.before
+                .map_or(Ok(Intercept::Proceed), |before| before())
+        }
+
+        fn after(&self, call: &EngineCall<'_>, outcome: &CallOutcome<'_>) {
+            self.seen.lock().unwrap().push(format!(
+                "{} after {} {} {:?}",
+                self.name, call.operation, outcome.result_code, outcome.response
+            ));
+        }
+    }
+
+    fn settings_with(interceptors: Vec<Arc<Recorder>>) -> EngineSettings {
+        let settings = EngineSettings::default();
+        for interceptor in interceptors {
+            settings.add_interceptor(interceptor);
+        }
+        settings
+    }
+
+    fn call(settings: &EngineSettings) -> SzResult<JsonString> {
+        let parameters = parameters(settings, || json!({ "entity_id": 1 }));
+        run(settings, "get_entity", parameters, || Ok("{}".to_string()))
+    }
+
+    #[test]
+    fn test_no_interceptors_skips_parameters() {
+        let settings = EngineSettings::default();
+        assert!(parameters(&settings, || unreachable!()).is_none());
+        assert_eq!(call(&settings).unwrap(), "{}");
+    }
+
+    #[test]
+    fn test_hook_order_and_outcome() {
+        let outer = Arc::new(Recorder {
+            name: "outer",
+            ..Default::default()
+        });
+        let inner = Arc::new(Recorder {
+            name: "inner",
+            ..Default::default()
+        });
+        let settings = settings_with(vec![outer.clone(), inner.clone()]);
+        assert_eq!(call(&settings).unwrap(), "{}");
+        assert_eq!(
+            *outer.seen.lock().unwrap(),
+            [
+                "outer before get_entity",
+                "outer after get_entity 0 Some(\"{}\")"
+            ]
+        );
+        assert_eq!(inner.seen.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_nested_calls_are_not_intercepted() {
+        let recorder = Arc::new(Recorder {
+            name: "outer",
+            ..Default::default()
+        });
+        let settings = settings_with(vec![recorder.clone()]);
+        let parameters = parameters(&settings, || json!({}));
+        run(&settings, "get_entity", parameters, || call(&settings)).unwrap();
+        assert_eq!(recorder.seen.lock().unwrap().len(), 2);
+        assert!(!ACTIVE.get());
+    }
+
+    #[test]
+    fn test_nested_failure_is_recorded_once() {
+        // get_entity reading through get_entity_bytes, which hands the
+        // native call to a worker thread as it does with a timeout
+        let settings = Arc::new(EngineSettings::default());
+        let failing = || -> SzResult<JsonString> { Err(SzError::not_found("no entity")) };
+        let result = run(&settings, "get_entity", None, || {
+            run(&settings, "get_entity_bytes", None, || {
+                let nesting = Nesting::current();
+                let worker = Arc::clone(&settings);
+                std::thread::spawn(move || {
+                    nesting.scope(|| run(&worker, "get_entity_bytes", None, failing))
+                })
+                .join()
+                .unwrap()
+            })
+        });
+        assert!(result.is_err());
+        let errors = settings.errors_matching(&crate::types::ErrorFilter::new());
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].operation, "get_entity");
+        assert_eq!(DEPTH.get(), 0);
+
+        assert!(run(&settings, "get_record", None, failing).is_err());
+        assert_eq!(
+            settings
+                .errors_matching(&crate::types::ErrorFilter::new())
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_reject_and_respond() {
+        let denying = Arc::new(Recorder {
+            name: "auth",
+            before: Some(|| Err(SzError::bad_input("denied"))),
+            ..Default::default()
+        });
+        let later = Arc::new(Recorder {
+            name: "later",
+            ..Default::default()
+        });
+        let settings = settings_with(vec![denying, later.clone()]);
+        assert!(call(&settings).is_err());
+        assert_eq!(
+            *later.seen.lock().unwrap(),
+            [format!("later after get_entity {SDK_ERROR_CODE} None")]
+        );
+
+        let cache = Arc::new(Recorder {
+            name: "cache",
+            before: Some(|| Ok(Intercept::Respond(r#"{"cached":true}"#.to_string()))),
+            ..Default::default()
+        });
+        let settings = settings_with(vec![cache]);
+        assert_eq!(call(&settings).unwrap(), r#"{"cached":true}"#);
+        let counted = run(&settings, "count_redo_records", Some(Map::new()), || {
+            Ok(3i64)
+        });
+        assert_eq!(counted.unwrap(), 3);
+    }
+}
@@ -0,0 +1,1106 @@
+//! Engine calls in a supervised child process.
+//!
+//! A crash in the native library (a segfault or abort) takes down the whole
+//! process. [`IsolatedEngine`] runs the engine in a child process instead and
+//! forwards every [`SzEngine`] call to it. If the child dies, the call in
+//! flight fails with [`SzError::Unrecoverable`], a new child is started, and
+//! the host process keeps running.
+//!
+//! The child is the host executable itself, started with an environment
+//! variable that makes [`run_worker_if_requested`] serve engine calls
+//! instead of running the program. Call it first thing in `main`; use
+//! [`IsolatedEngine::with_program`] to run a dedicated worker binary instead.
+//!
+//! # Protocol
+//!
+//! Requests and responses are single-line JSON documents on the child's
+//! standard input and output:
+//!
+//! ```text
+//! {"id":1,"method":"get_record","args":["TEST","1001",null]}
+//! {"id":1,"ok":"{\"DATA_SOURCE\":\"TEST\",...}"}
+//! {"id":2,"err":{"category":"bad_input","message":"...","code":33}}
+//! ```
+//!
+//! Before serving requests the child reports how its start-up went with a
+//! response for id 0: `{"id":0,"ok":null}`, or the error from creating the
+//! environment. A child that cannot start, e.g. for bad settings or a
+//! license error, fails the call with that error instead of being
+//! restarted.
+//!
+//! Output lines from the native library that are not responses are copied
+//! to standard error.
+//!
+//! # Limitations
+//!
+//! Calls are sent to the child one at a time; create several engines for
+//! parallel work. Export handles belong to the child and are invalid after a
+//! restart. A call that was in flight when the child died is not retried,
+//! since it may already have been applied.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use sz_rust_sdk::isolated::{self, IsolatedEngine};
+//! use sz_rust_sdk::prelude::*;
+//!
+//! fn main() -> SzResult<()> {
+//!     isolated::run_worker_if_requested();
+//!
+//!     let settings = std::env::var("SENZING_ENGINE_CONFIGURATION_JSON").unwrap_or_default();
+//!     let engine = IsolatedEngine::new("isolated-example", &settings, false);
+//!     match engine.get_record("CUSTOMERS", "1001", None) {
+//!         Err(e) if e.is_unrecoverable() => eprintln!("engine restarted: {e}"),
+//!         result => println!("{}", result?),
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+use crate::core::SzEnvironmentCore;
+use crate::error::{ErrorContext, SzComponent, SzError, SzResult};
+use crate::flags::SzFlags;
+use crate::traits::{SzEngine, SzEngineLoad, SzEngineRead, SzEnvironment};
+use crate::types::*;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader, Write};
+use std::path::PathBuf;
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+/// Environment variable that marks a process as an isolated engine worker.
+pub const WORKER_ENV: &str = "SZ_ISOLATED_WORKER";
+const MODULE_ENV: &str = "SZ_ISOLATED_MODULE";
+const SETTINGS_ENV: &str = "SZ_ISOLATED_SETTINGS";
+const VERBOSE_ENV: &str = "SZ_ISOLATED_VERBOSE";
+
+/// Request ID of the start-up report the worker sends before serving.
+const HANDSHAKE_ID: u64 = 0;
+
+/// How long dropping an engine waits for the child to exit before killing it.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Request {
+    id: u64,
+    method: String,
+    args: Vec<Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct Response {
+    id: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ok: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    err: Option<WireError>,
+}
+
+/// An [`SzError`] as sent over the pipe.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct WireError {
+    category: String,
+    message: String,
+    #[serde(default)]
+    code: Option<i64>,
+    #[serde(default)]
+    suggested_flags: Option<u64>,
+}
+
+impl From<&SzError> for WireError {
+    fn from(e: &SzError) -> Self {
+        Self {
+            category: e.category().to_string(),
+            message: e.message().to_string(),
+            code: e.error_code(),
+            suggested_flags: e.suggested_flags().map(|flags| flags.bits()),
+        }
+    }
+}
+
+impl From<WireError> for SzError {
+    fn from(wire: WireError) -> Self {
+        if let Some(code) = wire.code {
+            let ctx = ErrorContext::with_code(wire.message, code, SzComponent::Engine);
+            return crate::error_mappings_generated::map_error_code(code, ctx);
+        }
+        let message = wire.message;
+        match wire.category.as_str() {
+            "bad_input" => SzError::bad_input(message),
+            "configuration" => SzError::configuration(message),
+            "database" => SzError::database(message),
+            "database_connection" => SzError::database_connection_lost(message),
+            "database_transient" => SzError::database_transient(message),
+            "license" => SzError::license(message),
+            "not_initialized" => SzError::not_initialized(message),
+            "retryable" => SzError::retryable(message),
+            "unrecoverable" => SzError::unrecoverable(message),
+            "replace_conflict" => SzError::replace_conflict(message),
+            "environment_destroyed" => SzError::environment_destroyed(message),
+            "result_too_large" => SzError::result_too_large(
+                message,
+                SzFlags::from_bits_retain(wire.suggested_flags.unwrap_or_default()),
+            ),
+            "timeout" => SzError::timeout(message),
+            "not_permitted" => SzError::not_permitted(message),
+            "export_invalidated" => SzError::export_invalidated(message),
+            "cancelled" => SzError::cancelled(message),
+            _ => SzError::unknown(message),
+        }
+    }
+}
+
+/// An entity reference as sent over the pipe.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+struct WireEntityRef {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    entity_id: Option<EntityId>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    data_source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    record_id: Option<String>,
+}
+
+impl From<&EntityRef<'_>> for WireEntityRef {
+    fn from(entity_ref: &EntityRef<'_>) -> Self {
+        match entity_ref {
+            EntityRef::Id(id) => Self {
+                entity_id: Some(*id),
+                data_source: None,
+                record_id: None,
+            },
+            EntityRef::Record {
+                data_source,
+                record_id,
+            } => Self {
+                entity_id: None,
+                data_source: Some(data_source.to_string()),
+                record_id: Some(record_id.to_string()),
+            },
+        }
+    }
+}
+
+fn flags_arg(flags: Option<SzFlags>) -> Value {
+    json!(flags.map(|flags| flags.bits()))
+}
+
+fn entity_ref_arg(entity_ref: &EntityRef<'_>) -> Value {
+    json!(WireEntityRef::from(entity_ref))
+}
+
+/// A running worker process and its pipes.
+struct Worker {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl Worker {
+    /// Sends one request and waits for its response. An `Err` means the
+    /// pipe broke, which is treated as the child having died.
+    fn call(&mut self, request: &Request) -> Result<Response, String> {
+        let mut line = serde_json::to_string(request).map_err(|e| e.to_string())?;
+        line.push('\n');
+        self.stdin
+            .write_all(line.as_bytes())
+            .and_then(|()| self.stdin.flush())
+            .map_err(|e| format!("writing request failed: {e}"))?;
+
+        self.read_response(request.id)
+    }
+
+    /// Waits for the response to request `id`.
+    fn read_response(&mut self, id: u64) -> Result<Response, String> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match self.stdout.read_line(&mut line) {
+                Ok(0) => return Err("worker closed its output".to_string()),
+                Ok(_) => {}
+                Err(e) => return Err(format!("reading response failed: {e}")),
+            }
+            match serde_json::from_str::<Response>(&line) {
+                Ok(response) if response.id == id => return Ok(response),
+                // Output from the native library, or a stale response
+                _ => eprint!("{line}"),
+            }
+        }
+    }
+
+    /// Kills the child and returns how it ended.
+    fn kill(mut self) -> String {
+        let _ = self.child.kill();
+        match self.child.wait() {
+            Ok(status) => status.to_string(),
+            Err(e) => format!("unknown status ({e})"),
+        }
+    }
+}
+
+/// An [`SzEngine`] whose calls run in a supervised child process.
+///
+/// See the [module documentation](self).
+pub struct IsolatedEngine {
+    program: Option<PathBuf>,
+    module_name: String,
+    ini_params: String,
+    verbose_logging: bool,
+    worker: Mutex<Option<Worker>>,
+    next_id: AtomicU64,
+    restarts: AtomicU64,
+}
+
+impl IsolatedEngine {
+    /// Creates an engine that runs the current executable as its worker.
+    ///
+    /// The worker is started on the first call, or by [`start`](Self::start).
+    pub fn new(module_name: &str, ini_params: &str, verbose_logging: bool) -> Self {
+        Self {
+            program: None,
+            module_name: module_name.to_string(),
+            ini_params: ini_params.to_string(),
+            verbose_logging,
+            worker: Mutex::new(None),
+            next_id: AtomicU64::new(1),
+            restarts: AtomicU64::new(0),
+        }
+    }
+
+    /// Runs `program` as the worker instead of the current executable. The
+    /// program must call [`run_worker_if_requested`] on startup.
+    pub fn with_program(mut self, program: impl Into<PathBuf>) -> Self {
+        self.program = Some(program.into());
+        self
+    }
+
+    /// Starts the worker now if it is not running.
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::Unrecoverable` - The worker process could not be started,
+    ///   or exited without reporting its start-up
+    /// * Any error from creating the environment in the worker, e.g.
+    ///   `SzError::Configuration` or `SzError::License`
+    pub fn start(&self) -> SzResult<()> {
+        let mut worker = self.lock();
+        if worker.is_none() {
+            *worker = Some(self.spawn()?);
+        }
+        Ok(())
+    }
+
+    /// Number of times the worker was restarted after dying.
+    pub fn restarts(&self) -> u64 {
+        self.restarts.load(Ordering::Relaxed)
+    }
+
+    /// Process ID of the running worker, if any.
+    pub fn worker_pid(&self) -> Option<u32> {
+        self.lock().as_ref().map(|worker| worker.child.id())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Option<Worker>> {
+        match self.worker.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    fn spawn(&self) -> SzResult<Worker> {
+        let program = match &self.program {
+            Some(program) => program.clone(),
+            None => std::env::current_exe().map_err(|e| {
+                SzError::unrecoverable(format!("Cannot locate the current executable: {e}"))
+            })?,
+        };
+        let mut child = Command::new(&program)
+            .env(WORKER_ENV, "1")
+            .env(MODULE_ENV, &self.module_name)
+            .env(SETTINGS_ENV, &self.ini_params)
+            .env(VERBOSE_ENV, if self.verbose_logging { "1" } else { "0" })
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| {
+                SzError::unrecoverable(format!(
+                    "Failed to start engine worker {}: {e}",
+                    program.display()
+                ))
+            })?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            let _ = child.kill();
+            return Err(SzError::unrecoverable("Engine worker has no pipes"));
+        };
+        let mut worker = Worker {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout),
+        };
+        match worker.read_response(HANDSHAKE_ID) {
+            Ok(Response { err: None, .. }) => Ok(worker),
+            Ok(Response { err: Some(err), .. }) => {
+                worker.kill();
+                Err(err.into())
+            }
+            Err(reason) => {
+                let status = worker.kill();
+                Err(SzError::unrecoverable(format!(
+                    "Engine worker failed to start ({reason}, exit {status})"
+                )))
+            }
+        }
+    }
+
+    /// Sends a call to the worker, restarting it if it died.
+    fn call(&self, method: &str, args: Vec<Value>) -> SzResult<Value> {
+        let request = Request {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            method: method.to_string(),
+            args,
+        };
+        let mut slot = self.lock();
+        let worker = match slot.as_mut() {
+            Some(worker) => worker,
+            None => slot.insert(self.spawn()?),
+        };
+        match worker.call(&request) {
+            Ok(Response { err: Some(err), .. }) => Err(err.into()),
+            Ok(Response { ok, .. }) => Ok(ok.unwrap_or(Value::Null)),
+            Err(reason) => {
+                let status = slot.take().map(Worker::kill).unwrap_or_default();
+                self.restarts.fetch_add(1, Ordering::Relaxed);
+                // Restart now so the next call finds a worker; if that fails
+                // the next call tries again.
+                *slot = self.spawn().ok();
+                Err(SzError::unrecoverable(format!(
+                    "Engine worker died during {method} ({reason}, exit {status}); worker restarted"
+                )))
+            }
+        }
+    }
+
+    fn call_string(&self, method: &str, args: Vec<Value>) -> SzResult<JsonString> {
+        match self.call(method, args)? {
+            Value::String(s) => Ok(s),
+            Value::Null => Ok(String::new()),
+            other => Ok(other.to_string()),
+        }
+    }
+
+    fn call_i64(&self, method: &str, args: Vec<Value>) -> SzResult<i64> {
+        self.call(method, args)?.as_i64().ok_or_else(|| {
+            SzError::unknown(format!("Engine worker returned a non-integer for {method}"))
+        })
+    }
+}
+
+impl Drop for IsolatedEngine {
+    fn drop(&mut self) {
+        let Some(worker) = self.lock().take() else {
+            return;
+        };
+        let Worker {
+            mut child, stdin, ..
+        } = worker;
+        // Closing stdin asks the worker to shut down cleanly
+        drop(stdin);
+        let deadline = Instant::now() + SHUTDOWN_GRACE;
+        while Instant::now() < deadline {
+            match child.try_wait() {
+                Ok(Some(_)) | Err(_) => return,
+                Ok(None) => std::thread::sleep(Duration::from_millis(20)),
+            }
+        }
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+}
+
+impl SzEngine for IsolatedEngine {
+    fn prime_engine(&self) -> SzResult<()> {
+        self.call("prime_engine", vec![]).map(|_| ())
+    }
+}
+
+impl SzEngineRead for IsolatedEngine {
+    fn get_stats(&self) -> SzResult<JsonString> {
+        self.call_string("get_stats", vec![])
+    }
+
+    fn get_record_preview(
+        &self,
+        record_definition: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.call_string(
+            "get_record_preview",
+            vec![json!(record_definition), flags_arg(flags)],
+        )
+    }
+
+    fn search_by_attributes(
+        &self,
+        attributes: &str,
+        search_profile: Option<&str>,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.call_string(
+            "search_by_attributes",
+            vec![json!(attributes), json!(search_profile), flags_arg(flags)],
+        )
+    }
+
+    fn why_search(
+        &self,
+        attributes: &str,
+        entity_id: EntityId,
+        search_profile: Option<&str>,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.call_string(
+            "why_search",
+            vec![
+                json!(attributes),
+                json!(entity_id),
+                json!(search_profile),
+                flags_arg(flags),
+            ],
+        )
+    }
+
+    fn get_entity(&self, entity_ref: EntityRef, flags: Option<SzFlags>) -> SzResult<JsonString> {
+        self.call_string(
+            "get_entity",
+            vec![entity_ref_arg(&entity_ref), flags_arg(flags)],
+        )
+    }
+
+    fn get_record(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.call_string(
+            "get_record",
+            vec![json!(data_source_code), json!(record_id), flags_arg(flags)],
+        )
+    }
+
+    fn find_interesting_entities(
+        &self,
+        entity_ref: EntityRef,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.call_string(
+            "find_interesting_entities",
+            vec![entity_ref_arg(&entity_ref), flags_arg(flags)],
+        )
+    }
+
+    fn find_path_by_entity_id(
+        &self,
+        start_entity_id: EntityId,
+        end_entity_id: EntityId,
+        max_degrees: i64,
+        avoid_entity_ids: Option<&HashSet<EntityId>>,
+        required_data_sources: Option<&HashSet<String>>,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.call_string(
+            "find_path_by_entity_id",
+            vec![
+                json!(start_entity_id),
+                json!(end_entity_id),
+                json!(max_degrees),
+                json!(avoid_entity_ids),
+                json!(required_data_sources),
+                flags_arg(flags),
+            ],
+        )
+    }
+
+    fn find_path_by_record_id(
+        &self,
+        start_data_source_code: &str,
+        start_record_id: &str,
+        end_data_source_code: &str,
+        end_record_id: &str,
+        max_degrees: i64,
+        avoid_record_keys: Option<&[RecordKey]>,
+        required_data_sources: Option<&HashSet<String>>,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.call_string(
+            "find_path_by_record_id",
+            vec![
+                json!(start_data_source_code),
+                json!(start_record_id),
+                json!(end_data_source_code),
+                json!(end_record_id),
+                json!(max_degrees),
+                json!(avoid_record_keys),
+                json!(required_data_sources),
+                flags_arg(flags),
+            ],
+        )
+    }
+
+    fn find_network_by_entity_id(
+        &self,
+        entity_list: &[EntityId],
+        max_degrees: i64,
+        build_out_degrees: i64,
+        max_entities: i64,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.call_string(
+            "find_network_by_entity_id",
+            vec![
+                json!(entity_list),
+                json!(max_degrees),
+                json!(build_out_degrees),
+                json!(max_entities),
+                flags_arg(flags),
+            ],
+        )
+    }
+
+    fn find_network_by_record_id(
+        &self,
+        record_keys: &[RecordKey],
+        max_degrees: i64,
+        build_out_degrees: i64,
+        max_entities: i64,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.call_string(
+            "find_network_by_record_id",
+            vec![
+                json!(record_keys),
+                json!(max_degrees),
+                json!(build_out_degrees),
+                json!(max_entities),
+                flags_arg(flags),
+            ],
+        )
+    }
+
+    fn why_entities(
+        &self,
+        entity_id1: EntityId,
+        entity_id2: EntityId,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.call_string(
+            "why_entities",
+            vec![json!(entity_id1), json!(entity_id2), flags_arg(flags)],
+        )
+    }
+
+    fn why_records(
+        &self,
+        data_source_code1: &str,
+        record_id1: &str,
+        data_source_code2: &str,
+        record_id2: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.call_string(
+            "why_records",
+            vec![
+                json!(data_source_code1),
+                json!(record_id1),
+                json!(data_source_code2),
+                json!(record_id2),
+                flags_arg(flags),
+            ],
+        )
+    }
+
+    fn why_record_in_entity(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.call_string(
+            "why_record_in_entity",
+            vec![json!(data_source_code), json!(record_id), flags_arg(flags)],
+        )
+    }
+
+    fn how_entity(&self, entity_id: EntityId, flags: Option<SzFlags>) -> SzResult<JsonString> {
+        self.call_string("how_entity", vec![json!(entity_id), flags_arg(flags)])
+    }
+
+    fn get_virtual_entity(
+        &self,
+        record_keys: &[RecordKey],
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.call_string(
+            "get_virtual_entity",
+            vec![json!(record_keys), flags_arg(flags)],
+        )
+    }
+
+    fn count_redo_records(&self) -> SzResult<i64> {
+        self.call_i64("count_redo_records", vec![])
+    }
+
+    fn export_json_entity_report(&self, flags: Option<SzFlags>) -> SzResult<ExportHandle> {
+        self.call_i64("export_json_entity_report", vec![flags_arg(flags)])
+    }
+
+    fn export_csv_entity_report(
+        &self,
+        csv_column_list: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<ExportHandle> {
+        self.call_i64(
+            "export_csv_entity_report",
+            vec![json!(csv_column_list), flags_arg(flags)],
+        )
+    }
+
+    fn fetch_next(&self, export_handle: ExportHandle) -> SzResult<JsonString> {
+        self.call_string("fetch_next", vec![json!(export_handle)])
+    }
+
+    fn close_export_report(&self, export_handle: ExportHandle) -> SzResult<()> {
+        self.call("close_export_report", vec![json!(export_handle)])
+            .map(|_| ())
+    }
+}
+
+impl SzEngineLoad for IsolatedEngine {
+    fn add_record(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        record_definition: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.call_string(
+            "add_record",
+            vec![
+                json!(data_source_code),
+                json!(record_id),
+                json!(record_definition),
+                flags_arg(flags),
+            ],
+        )
+    }
+
+    fn delete_record(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.call_string(
+            "delete_record",
+            vec![json!(data_source_code), json!(record_id), flags_arg(flags)],
+        )
+    }
+
+    fn reevaluate_record(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.call_string(
+            "reevaluate_record",
+            vec![json!(data_source_code), json!(record_id), flags_arg(flags)],
+        )
+    }
+
+    fn reevaluate_entity(
+        &self,
+        entity_id: EntityId,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.call_string(
+            "reevaluate_entity",
+            vec![json!(entity_id), flags_arg(flags)],
+        )
+    }
+
+    fn process_redo_record(
+        &self,
+        redo_record: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.call_string(
+            "process_redo_record",
+            vec![json!(redo_record), flags_arg(flags)],
+        )
+    }
+
+    fn get_redo_record(&self) -> SzResult<JsonString> {
+        self.call_string("get_redo_record", vec![])
+    }
+}
+
+// ============================================================================
+// Worker side
+// ============================================================================
+
+/// Serves engine calls and exits if this process was started as an
+/// isolated engine worker; otherwise returns immediately.
+///
+/// Call this at the start of `main`, before any other work.
+pub fn run_worker_if_requested() {
+    if std::env::var_os(WORKER_ENV).is_none() {
+        return;
+    }
+    let code = match serve() {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("isolated engine worker failed: {e}");
+            1
+        }
+    };
+    std::process::exit(code);
+}
+
+fn serve() -> SzResult<()> {
+    let module_name = std::env::var(MODULE_ENV).unwrap_or_else(|_| "SzIsolatedWorker".into());
+    let settings = std::env::var(SETTINGS_ENV).unwrap_or_else(|_| "{}".into());
+    let verbose = std::env::var(VERBOSE_ENV).is_ok_and(|v| v == "1");
+    let mut stdout = std::io::stdout().lock();
+    let started = SzEnvironmentCore::get_instance(&module_name, &settings, verbose)
+        .and_then(|env| Ok((env.get_engine()?, env)));
+    let (engine, env) = match started {
+        Ok(started) => {
+            write_response(&mut stdout, &respond(HANDSHAKE_ID, Ok(Value::Null)))?;
+            started
+        }
+        Err(e) => {
+            // The host reports the error; exiting afterwards is expected
+            let report = Response {
+                id: HANDSHAKE_ID,
+                ok: None,
+                err: Some(WireError::from(&e)),
+            };
+            let _ = write_response(&mut stdout, &report);
+            return Err(e);
+        }
+    };
+
+    let stdin = std::io::stdin().lock();
+    for line in stdin.lines() {
+        let line = line.map_err(|e| SzError::unrecoverable(format!("Reading request: {e}")))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => respond(request.id, dispatch(engine.as_ref(), &request)),
+            Err(e) => respond(0, Err(SzError::bad_input(format!("Invalid request: {e}")))),
+        };
+        write_response(&mut stdout, &response)?;
+    }
+
+    drop(engine);
+    env.destroy()
+}
+
+fn write_response(stdout: &mut impl Write, response: &Response) -> SzResult<()> {
+    let mut out = serde_json::to_string(response)?;
+    out.push('\n');
+    stdout
+        .write_all(out.as_bytes())
+        .and_then(|()| stdout.flush())
+        .map_err(|e| SzError::unrecoverable(format!("Writing response: {e}")))
+}
+
+fn respond(id: u64, result: SzResult<Value>) -> Response {
+    match result {
+        Ok(value) => Response {
+            id,
+            ok: Some(value),
+            err: None,
+        },
+        Err(e) => Response {
+            id,
+            ok: None,
+            err: Some(WireError::from(&e)),
+        },
+    }
+}
+
+/// An owned [`EntityRef`] decoded from a request.
+#[derive(Debug, PartialEq, Eq)]
+enum OwnedEntityRef {
+    Id(EntityId),
+    Record(String, String),
+}
+
+impl OwnedEntityRef {
+    fn as_entity_ref(&self) -> EntityRef<'_> {
+        match self {
+            Self::Id(id) => EntityRef::Id(*id),
+            Self::Record(data_source, record_id) => EntityRef::from_record(data_source, record_id),
+        }
+    }
+}
+
+/// Positional arguments of a request, decoded in order.
+struct Args(std::vec::IntoIter<Value>);
+
+impl Args {
+    fn next<T: DeserializeOwned>(&mut self) -> SzResult<T> {
+        Ok(serde_json::from_value(
+            self.0.next().unwrap_or(Value::Null),
+        )?)
+    }
+
+    fn flags(&mut self) -> SzResult<Option<SzFlags>> {
+        Ok(self.next::<Option<u64>>()?.map(SzFlags::from_bits_retain))
+    }
+
+    fn entity_ref(&mut self) -> SzResult<OwnedEntityRef> {
+        let wire: WireEntityRef = self.next()?;
+        match (wire.entity_id, wire.data_source, wire.record_id) {
+            (Some(id), _, _) => Ok(OwnedEntityRef::Id(id)),
+            (None, Some(data_source), Some(record_id)) => {
+                Ok(OwnedEntityRef::Record(data_source, record_id))
+            }
+            _ => Err(SzError::bad_input("Invalid entity reference")),
+        }
+    }
+}
+
+fn dispatch(engine: &dyn SzEngine, request: &Request) -> SzResult<Value> {
+    let mut a = Args(request.args.clone().into_iter());
+    let text = |result: SzResult<String>| result.map(Value::String);
+    match request.method.as_str() {
+        "prime_engine" => engine.prime_engine().map(|()| Value::Null),
+        "get_stats" => text(engine.get_stats()),
+        "add_record" => text(engine.add_record(
+            &a.next::<String>()?,
+            &a.next::<String>()?,
+            &a.next::<String>()?,
+            a.flags()?,
+        )),
+        "get_record_preview" => text(engine.get_record_preview(&a.next::<String>()?, a.flags()?)),
+        "delete_record" => {
+            text(engine.delete_record(&a.next::<String>()?, &a.next::<String>()?, a.flags()?))
+        }
+        "reevaluate_record" => {
+            text(engine.reevaluate_record(&a.next::<String>()?, &a.next::<String>()?, a.flags()?))
+        }
+        "reevaluate_entity" => text(engine.reevaluate_entity(a.next()?, a.flags()?)),
+        "search_by_attributes" => text(engine.search_by_attributes(
+            &a.next::<String>()?,
+            a.next::<Option<String>>()?.as_deref(),
+            a.flags()?,
+        )),
+        "why_search" => text(engine.why_search(
+            &a.next::<String>()?,
+            a.next()?,
+            a.next::<Option<String>>()?.as_deref(),
+            a.flags()?,
+        )),
+        "get_entity" | "find_interesting_entities" => {
+            let owned = a.entity_ref()?;
+            let entity_ref = owned.as_entity_ref();
+            let flags = a.flags()?;
+            if request.method == "get_entity" {
+                text(engine.get_entity(entity_ref, flags))
+            } else {
+                text(engine.find_interesting_entities(entity_ref, flags))
+            }
+        }
+        "get_record" => {
+            text(engine.get_record(&a.next::<String>()?, &a.next::<String>()?, a.flags()?))
+        }
+        "find_path_by_entity_id" => text(engine.find_path_by_entity_id(
+            a.next()?,
+            a.next()?,
+            a.next()?,
+            a.next::<Option<HashSet<EntityId>>>()?.as_ref(),
+            a.next::<Option<HashSet<String>>>()?.as_ref(),
+            a.flags()?,
+        )),
+        "find_path_by_record_id" => {
+            let (start_ds, start_id): (String, String) = (a.next()?, a.next()?);
+            let (end_ds, end_id): (String, String) = (a.next()?, a.next()?);
+            let max_degrees = a.next()?;
+            let avoid: Option<Vec<RecordKey>> = a.next()?;
+            text(engine.find_path_by_record_id(
+                &start_ds,
+                &start_id,
+                &end_ds,
+                &end_id,
+                max_degrees,
+                avoid.as_deref(),
+                a.next::<Option<HashSet<String>>>()?.as_ref(),
+                a.flags()?,
+            ))
+        }
+        "find_network_by_entity_id" => text(engine.find_network_by_entity_id(
+            &a.next::<Vec<EntityId>>()?,
+            a.next()?,
+            a.next()?,
+            a.next()?,
+            a.flags()?,
+        )),
+        "find_network_by_record_id" => text(engine.find_network_by_record_id(
+            &a.next::<Vec<RecordKey>>()?,
+            a.next()?,
+            a.next()?,
+            a.next()?,
+            a.flags()?,
+        )),
+        "why_entities" => text(engine.why_entities(a.next()?, a.next()?, a.flags()?)),
+        "why_records" => text(engine.why_records(
+            &a.next::<String>()?,
+            &a.next::<String>()?,
+            &a.next::<String>()?,
+            &a.next::<String>()?,
+            a.flags()?,
+        )),
+        "why_record_in_entity" => text(engine.why_record_in_entity(
+            &a.next::<String>()?,
+            &a.next::<String>()?,
+            a.flags()?,
+        )),
+        "how_entity" => text(engine.how_entity(a.next()?, a.flags()?)),
+        "get_virtual_entity" => {
+            text(engine.get_virtual_entity(&a.next::<Vec<RecordKey>>()?, a.flags()?))
+        }
+        "process_redo_record" => text(engine.process_redo_record(&a.next::<String>()?, a.flags()?)),
+        "get_redo_record" => text(engine.get_redo_record()),
+        "count_redo_records" => engine.count_redo_records().map(Value::from),
+        "export_json_entity_report" => engine
+            .export_json_entity_report(a.flags()?)
+            .map(Value::from),
+        "export_csv_entity_report" => engine
+            .export_csv_entity_report(&a.next::<String>()?, a.flags()?)
+            .map(Value::from),
+        "fetch_next" => text(engine.fetch_next(a.next()?)),
+        "close_export_report" => engine.close_export_report(a.next()?).map(|()| Value::Null),
+        other => Err(SzError::bad_input(format!(
+            "Unknown isolated engine method: {other}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wire_error_round_trip() {
+        let original = SzError::result_too_large("too big", SzFlags::ENTITY_INCLUDE_RECORD_DATA);
+        let restored = SzError::from(WireError::from(&original));
+        assert_eq!(restored.category(), "result_too_large");
+        assert_eq!(restored.message(), "too big");
+        assert_eq!(
+            restored.suggested_flags(),
+            Some(SzFlags::ENTITY_INCLUDE_RECORD_DATA)
+        );
+
+        let restored = SzError::from(WireError::from(&SzError::not_permitted("no")));
+        assert!(matches!(restored, SzError::NotPermitted(_)));
+    }
+
+    #[test]
+    fn test_wire_error_with_code_uses_native_mapping() {
+        let wire = WireError {
+            category: "unknown".to_string(),
+            message: "license expired".to_string(),
+            code: Some(999),
+            suggested_flags: None,
+        };
+        let restored = SzError::from(wire);
+        assert!(matches!(restored, SzError::License(_)));
+        assert_eq!(restored.error_code(), Some(999));
+    }
+
+    #[test]
+    fn test_args_decoding() {
+        let request: Request = serde_json::from_str(
+            r#"{"id":3,"method":"get_entity","args":[{"DATA_SOURCE":"TEST","RECORD_ID":"1"},null]}"#,
+        )
+        .unwrap();
+        let mut args = Args(request.args.into_iter());
+        assert_eq!(
+            args.entity_ref().unwrap(),
+            OwnedEntityRef::Record("TEST".to_string(), "1".to_string())
+        );
+        assert_eq!(args.flags().unwrap(), None);
+
+        let mut args = Args(
+            vec![
+                entity_ref_arg(&EntityRef::Id(EntityId(7))),
+                flags_arg(Some(SzFlags::empty())),
+            ]
+            .into_iter(),
+        );
+        assert_eq!(args.entity_ref().unwrap(), OwnedEntityRef::Id(EntityId(7)));
+        assert_eq!(args.flags().unwrap(), Some(SzFlags::empty()));
+    }
+
+    /// Writes an executable shell script standing in for a worker.
+    #[cfg(unix)]
+    fn fake_worker(name: &str, body: &str) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("sz_isolated_{name}_{}", std::process::id()));
+        std::fs::write(&path, format!("#!/bin/sh\n{body}\n")).unwrap();
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        path
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_dead_worker_is_restarted() {
+        // A worker that starts and then exits behaves like one that crashed
+        let program = fake_worker("crashing", r#"echo '{"id":0,"ok":null}'"#);
+        let engine = IsolatedEngine::new("test", "{}", false).with_program(&program);
+        let err = engine
+            .get_stats()
+            .expect_err("dead worker should fail the call");
+        assert!(err.is_unrecoverable());
+        assert!(err.message().contains("get_stats"));
+        assert_eq!(engine.restarts(), 1);
+
+        assert!(engine.count_redo_records().is_err());
+        assert_eq!(engine.restarts(), 2);
+        std::fs::remove_file(program).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_failed_start_is_reported() {
+        let program = fake_worker(
+            "unlicensed",
+            r#"echo '{"id":0,"err":{"category":"license","message":"no license"}}'; exit 1"#,
+        );
+        let engine = IsolatedEngine::new("test", "{}", false).with_program(&program);
+        let err = engine.get_stats().expect_err("start-up error");
+        assert!(err.is_license());
+        assert_eq!(err.message(), "no license");
+        assert!(engine.start().is_err());
+        assert_eq!(engine.restarts(), 0);
+        assert_eq!(engine.worker_pid(), None);
+        std::fs::remove_file(program).unwrap();
+
+        // A worker that exits without reporting is not restarted either
+        let engine = IsolatedEngine::new("test", "{}", false).with_program("/bin/true");
+        assert!(engine.get_stats().unwrap_err().is_unrecoverable());
+        assert_eq!(engine.restarts(), 0);
+    }
+}
@@ -47,13 +47,86 @@
 //! # Ok::<(), Box<dyn std::error::Error>>(())
 //! ```
 
+pub mod analysis;
+pub mod analysis_cache;
+pub mod audit;
+pub mod bytes;
+#[cfg(feature = "capi")]
+pub mod capi;
+#[cfg(feature = "object-store")]
+mod cloud_storage;
+pub mod compat;
+pub mod config_tools;
+pub mod context;
+pub mod control;
 pub mod core;
+#[cfg(feature = "encryption")]
+pub mod encryption;
+/// Without the `encryption` feature, an uninhabited stand-in for
+/// `encryption::PayloadCipher`, so persisted stores can hold an optional
+/// cipher either way.
+#[cfg(not(feature = "encryption"))]
+mod encryption {
+    use crate::error::SzResult;
+
+    #[derive(Debug, Clone)]
+    pub(crate) enum PayloadCipher {}
+
+    impl PayloadCipher {
+        pub(crate) fn seal_line(&self, _line: &str) -> SzResult<String> {
+            match *self {}
+        }
+
+        pub(crate) fn open_line(&self, _line: &str) -> SzResult<String> {
+            match *self {}
+        }
+    }
+}
 pub mod error;
 mod error_mappings_generated; // Internal - generated error mappings used by error module
+pub mod eval;
+pub mod events;
+pub mod export;
 mod ffi; // Internal FFI module - not part of public API
 pub mod flags;
+#[cfg(feature = "sz-grpc-server")]
+pub mod grpc;
+pub mod health;
+pub mod ingest;
+pub mod intercept;
+#[cfg(feature = "isolated")]
+pub mod isolated;
+#[cfg(feature = "live-events")]
+pub mod live;
+pub mod maintenance;
+pub mod models;
+pub mod native;
+pub mod normalize;
+#[cfg(feature = "otel")]
+pub mod otel;
+pub mod outbox;
+#[cfg(feature = "pipeline")]
+pub mod pipeline;
+pub mod policy;
+pub mod profiling;
+pub mod provenance;
+#[cfg(feature = "python")]
+pub mod python;
+pub mod redact;
+pub mod redo;
+pub mod reevaluate;
+pub mod report;
+pub mod scoring;
+pub mod stewardship;
+#[cfg(feature = "support-bundle")]
+pub mod support;
+pub mod sync;
+pub mod testing;
+pub mod tools;
 pub mod traits;
 pub mod types;
+#[cfg(feature = "axum")]
+pub mod web;
 
 // Internal helpers module - only for examples and tests, not part of public API
 // Hidden from documentation but accessible for internal use
@@ -61,6 +134,7 @@ pub mod types;
 pub mod helpers;
 
 // Public API from core module
+pub use bytes::SzBytes;
 pub use core::SenzingGuard;
 pub use core::SzEnvironmentCore;
 pub use error::*;
@@ -77,6 +151,7 @@ pub use types::*;
 /// - Error types and result aliases
 /// - Flag types for controlling operations
 pub mod prelude {
+    pub use crate::bytes::SzBytes;
     pub use crate::core::SenzingGuard;
     pub use crate::core::SzEnvironmentCore;
     pub use crate::error::*;
@@ -0,0 +1,223 @@
+//! Live entity change events over Server-Sent Events and WebSocket.
+//!
+//! Demo UIs and internal dashboards often want to watch resolutions as they
+//! happen. An [`EventBroadcaster`] fans the [`EntityEvent`]s of an
+//! [`EntityChangeStream`] out to any number of subscribers, and
+//! [`EventBroadcaster::router`] serves them with axum:
+//!
+//! - `GET /events`: a Server-Sent Events stream. Each event has the event
+//!   name (e.g. `entity_merged`) as its SSE event type and the event as JSON
+//!   data.
+//! - `GET /ws`: a WebSocket that sends each event as a JSON text message.
+//!
+//! Publishing never blocks the loader. Each subscriber has a buffer of
+//! recent events; a subscriber that falls further behind skips the oldest
+//! and is sent a `lagged` event, `{"event":"lagged","missed":N}`, in their
+//! place. Events published while nobody is subscribed are dropped.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::events::EntityChangeStream;
+//! use sz_rust_sdk::live::EventBroadcaster;
+//! use sz_rust_sdk::prelude::*;
+//!
+//! # async fn serve(app: axum::Router) {}
+//! # let env = ExampleEnvironment::initialize("doctest_live_events")?;
+//! let broadcaster = EventBroadcaster::new();
+//! let app = broadcaster.router();
+//! // Serve `app` on the application's tokio runtime, e.g. with `axum::serve`
+//! # let _ = serve(app);
+//!
+//! let engine = env.get_engine()?;
+//! let mut changes = EntityChangeStream::new(&*engine);
+//! let info = engine.add_record(
+//!     "TEST",
+//!     "1001",
+//!     r#"{"NAME_FULL": "Ann Lee"}"#,
+//!     Some(SzFlags::WITH_INFO),
+//! )?;
+//! broadcaster.process(&mut changes, &info)?;
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::error::SzResult;
+use crate::events::{EntityChangeStream, EntityEvent};
+use axum::Router;
+use axum::extract::State;
+use axum::extract::ws::{Message, WebSocket, WebSocketUpgrade};
+use axum::response::Response;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::routing::get;
+use futures_util::Stream;
+use std::convert::Infallible;
+use tokio::sync::broadcast::{self, Receiver, Sender, error::RecvError};
+
+/// Default number of events buffered for each subscriber.
+pub const DEFAULT_CAPACITY: usize = 1024;
+
+/// One serialized event as sent to subscribers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LiveMessage {
+    /// Event name, e.g. `entity_merged`, or `lagged`
+    pub name: &'static str,
+    /// The event as JSON
+    pub json: String,
+}
+
+/// Fans entity change events out to SSE and WebSocket subscribers; see the
+/// [module documentation](self).
+///
+/// Clones share the same subscribers.
+#[derive(Debug, Clone)]
+pub struct EventBroadcaster {
+    sender: Sender<LiveMessage>,
+}
+
+impl Default for EventBroadcaster {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBroadcaster {
+    /// Creates a broadcaster buffering [`DEFAULT_CAPACITY`] events per
+    /// subscriber.
+    pub fn new() -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY)
+    }
+
+    /// Creates a broadcaster buffering `capacity` events per subscriber; at
+    /// least 1.
+    pub fn with_capacity(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Sends an event to every current subscriber and returns how many
+    /// there are.
+    pub fn publish(&self, event: &EntityEvent) -> usize {
+        let message = LiveMessage {
+            name: event.name(),
+            json: serde_json::to_string(event).unwrap_or_default(),
+        };
+        self.sender.send(message).unwrap_or(0)
+    }
+
+    /// Infers the events of one `WITH_INFO` response with `changes`,
+    /// publishes them and returns them.
+    ///
+    /// # Errors
+    ///
+    /// Any error from [`EntityChangeStream::process`].
+    pub fn process(
+        &self,
+        changes: &mut EntityChangeStream<'_>,
+        info: &str,
+    ) -> SzResult<Vec<EntityEvent>> {
+        let events = changes.process(info)?;
+        for event in &events {
+            self.publish(event);
+        }
+        Ok(events)
+    }
+
+    /// Number of current subscribers.
+    pub fn subscribers(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Subscribes to the events published from now on.
+    pub fn subscribe(&self) -> Receiver<LiveMessage> {
+        self.sender.subscribe()
+    }
+
+    /// An axum router serving `GET /events` (SSE) and `GET /ws`
+    /// (WebSocket); nest or merge it into the application's router.
+    pub fn router(&self) -> Router {
+        Router::new()
+            .route("/events", get(sse))
+            .route("/ws", get(websocket))
+            .with_state(self.clone())
+    }
+}
+
+async fn sse(
+    State(broadcaster): State<EventBroadcaster>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = futures_util::stream::unfold(broadcaster.subscribe(), |mut receiver| async {
+        let message = next_message(&mut receiver).await?;
+        let event = Event::default().event(message.name).data(message.json);
+        Some((Ok(event), receiver))
+    });
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+async fn websocket(
+    State(broadcaster): State<EventBroadcaster>,
+    upgrade: WebSocketUpgrade,
+) -> Response {
+    let receiver = broadcaster.subscribe();
+    upgrade.on_upgrade(|socket| forward(socket, receiver))
+}
+
+async fn forward(mut socket: WebSocket, mut receiver: Receiver<LiveMessage>) {
+    while let Some(message) = next_message(&mut receiver).await {
+        if socket
+            .send(Message::Text(message.json.into()))
+            .await
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+/// The next message for a subscriber, with a `lagged` message in place of
+/// skipped events, or `None` once the broadcaster is gone.
+async fn next_message(receiver: &mut Receiver<LiveMessage>) -> Option<LiveMessage> {
+    match receiver.recv().await {
+        Ok(message) => Some(message),
+        Err(RecvError::Lagged(missed)) => Some(LiveMessage {
+            name: "lagged",
+            json: format!(r#"{{"event":"lagged","missed":{missed}}}"#),
+        }),
+        Err(RecvError::Closed) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::EntityId;
+
+    #[test]
+    fn test_subscribers_receive_events_and_lag_notices() {
+        let broadcaster = EventBroadcaster::with_capacity(2);
+        let removed = EntityEvent::EntityRemoved {
+            entity_id: EntityId(3),
+        };
+        assert_eq!(broadcaster.publish(&removed), 0);
+
+        let mut receiver = broadcaster.subscribe();
+        assert_eq!(broadcaster.publish(&removed), 1);
+        let message = tokio_test::block_on(next_message(&mut receiver)).unwrap();
+        assert_eq!(message.name, "entity_removed");
+        assert_eq!(message.json, r#"{"event":"entity_removed","entity_id":3}"#);
+
+        for entity_id in 0..3 {
+            broadcaster.publish(&EntityEvent::EntityUpdated {
+                entity_id: EntityId(entity_id),
+            });
+        }
+        let message = tokio_test::block_on(next_message(&mut receiver)).unwrap();
+        assert_eq!(message.json, r#"{"event":"lagged","missed":1}"#);
+        let message = tokio_test::block_on(next_message(&mut receiver)).unwrap();
+        assert_eq!(message.json, r#"{"event":"entity_updated","entity_id":1}"#);
+
+        drop(broadcaster);
+        tokio_test::block_on(next_message(&mut receiver)).unwrap();
+        assert!(tokio_test::block_on(next_message(&mut receiver)).is_none());
+    }
+}
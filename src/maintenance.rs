@@ -0,0 +1,725 @@
+//! Periodic engine maintenance.
+//!
+//! [`SzMaintenanceScheduler`] runs recurring engine housekeeping on a single
+//! background thread: priming the engine and sampling `get_stats`. Each task
+//! has its own [`Schedule`]: a fixed interval with an optional initial
+//! delay, or a cron expression such as `"0 3 * * *"` (see [`CronSchedule`]).
+//! Stats samples and task failures are delivered to callbacks, so they can be
+//! forwarded to whatever metrics or logging system the application uses, and
+//! [`MaintenanceHandle::stats`] counts the runs and failures of each task.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::maintenance::SzMaintenanceScheduler;
+//! use sz_rust_sdk::prelude::*;
+//! use std::time::Duration;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_maintenance")?;
+//! let handle = SzMaintenanceScheduler::new(env.clone())
+//!     .prime_engine_every(Duration::from_secs(3600))
+//!     .collect_stats_every(Duration::from_secs(60), |stats| {
+//!         println!("engine stats: {stats}");
+//!     })
+//!     .on_error(|task, err| eprintln!("{task:?} failed: {err}"))
+//!     .start()?;
+//!
+//! // ... application runs ...
+//! for stats in handle.stats() {
+//!     println!("{:?}: {} runs, {} failed", stats.task, stats.runs, stats.failures);
+//! }
+//! handle.stop();
+//! # Ok::<(), SzError>(())
+//! ```
+//!
+//! Priming the engine nightly at 03:00 UTC instead:
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::maintenance::{MaintenanceTask, Schedule, SzMaintenanceScheduler};
+//! # use sz_rust_sdk::prelude::*;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_maintenance_cron")?;
+//! let handle = SzMaintenanceScheduler::new(env.clone())
+//!     .schedule(MaintenanceTask::PrimeEngine, Schedule::cron("0 3 * * *")?)
+//!     .start()?;
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::core::SzEnvironmentCore;
+use crate::error::{SzError, SzResult};
+use crate::traits::{SzEngine, SzEnvironment};
+use crate::types::JsonString;
+use std::str::FromStr;
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A recurring maintenance task.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MaintenanceTask {
+    /// Call `SzEngine::prime_engine`
+    PrimeEngine,
+    /// Call `SzEngine::get_stats` and deliver the result to the stats callback
+    CollectStats,
+}
+
+/// When a task runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Schedule {
+    /// After `initial_delay`, then every `interval`
+    Every {
+        /// Time between runs
+        interval: Duration,
+        /// Delay before the first run
+        initial_delay: Duration,
+    },
+    /// At the times a cron expression matches
+    Cron(CronSchedule),
+}
+
+impl Schedule {
+    /// Runs immediately, then every `interval`.
+    pub fn every(interval: Duration) -> Self {
+        Self::Every {
+            interval,
+            initial_delay: Duration::ZERO,
+        }
+    }
+
+    /// Runs at the times `expression` matches; see [`CronSchedule`].
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::BadInput` - The expression is not valid
+    pub fn cron(expression: &str) -> SzResult<Self> {
+        expression.parse().map(Self::Cron)
+    }
+
+    /// Sets the delay before the first run of an interval schedule. Has no
+    /// effect on a cron schedule.
+    pub fn starting_after(mut self, delay: Duration) -> Self {
+        if let Self::Every { initial_delay, .. } = &mut self {
+            *initial_delay = delay;
+        }
+        self
+    }
+
+    /// The first run time at or after `now`.
+    fn first(&self, now: SystemTime) -> Option<SystemTime> {
+        match self {
+            Self::Every { initial_delay, .. } => Some(now + *initial_delay),
+            Self::Cron(cron) => cron.next_after(now - Duration::from_secs(1)),
+        }
+    }
+
+    /// The run time after a run that was due at `previous` and ended at
+    /// `now`. Missed runs are skipped instead of run back to back.
+    fn next(&self, previous: SystemTime, now: SystemTime) -> Option<SystemTime> {
+        match self {
+            Self::Every { interval, .. } => Some(advance(previous, *interval, now)),
+            Self::Cron(cron) => cron.next_after(now),
+        }
+    }
+}
+
+/// A cron expression: five fields for minute, hour, day of month, month and
+/// day of week, matched in UTC.
+///
+/// Each field is `*`, a value, a range `a-b`, a step `*/n` or `a-b/n`, or a
+/// comma-separated list of these. Months and days of the week also take
+/// three-letter English names (`JAN`, `MON`); Sunday is 0 or 7. As in
+/// classic cron, when both day fields are restricted a day matching either
+/// one runs. `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly` are
+/// shorthands.
+///
+/// ```
+/// use sz_rust_sdk::maintenance::CronSchedule;
+/// use std::time::{Duration, UNIX_EPOCH};
+///
+/// let weekdays: CronSchedule = "30 2 * * MON-FRI".parse()?;
+/// // Thursday 1970-01-01 00:00 UTC
+/// let next = weekdays.next_after(UNIX_EPOCH).unwrap();
+/// assert_eq!(next, UNIX_EPOCH + Duration::from_secs(2 * 3600 + 30 * 60));
+/// # Ok::<(), sz_rust_sdk::error::SzError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    expression: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    /// Whether the day of month and day of week fields are not `*`
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+const MONTH_NAMES: [&str; 12] = [
+    "JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// How far ahead a next run is looked for, enough for every leap day
+const SEARCH_DAYS: i64 = 366 * 8 + 2;
+
+impl CronSchedule {
+    /// The expression this schedule was parsed from.
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+
+    /// The first matching minute strictly after `time`, or `None` when the
+    /// expression does not match within the next eight years (e.g. February
+    /// 30th).
+    pub fn next_after(&self, time: SystemTime) -> Option<SystemTime> {
+        let seconds = time.duration_since(UNIX_EPOCH).ok()?.as_secs();
+        let start = i64::try_from(seconds / 60 + 1).ok()?;
+        let (start_day, start_minute) = (start.div_euclid(1440), start.rem_euclid(1440));
+        for day in start_day..start_day + SEARCH_DAYS {
+            if !self.matches_day(day) {
+                continue;
+            }
+            let first = if day == start_day { start_minute } else { 0 };
+            if let Some(minute) = (first..1440)
+                .find(|minute| bit(self.hours, minute / 60) && bit(self.minutes, minute % 60))
+            {
+                let at = u64::try_from((day * 1440 + minute) * 60).ok()?;
+                return Some(UNIX_EPOCH + Duration::from_secs(at));
+            }
+        }
+        None
+    }
+
+    fn matches_day(&self, day: i64) -> bool {
+        let (_, month, day_of_month) = civil_from_days(day);
+        if !bit(self.months, month) {
+            return false;
+        }
+        // 1970-01-01 was a Thursday
+        let weekday = (day + 4).rem_euclid(7);
+        let by_day = bit(self.days, day_of_month);
+        let by_weekday = bit(self.weekdays, weekday);
+        if self.days_restricted && self.weekdays_restricted {
+            by_day || by_weekday
+        } else {
+            by_day && by_weekday
+        }
+    }
+}
+
+impl FromStr for CronSchedule {
+    type Err = SzError;
+
+    fn from_str(expression: &str) -> SzResult<Self> {
+        let expanded = match expression.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            other => other,
+        };
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(SzError::bad_input(format!(
+                "Cron expression '{expression}' must have 5 fields: \
+                 minute hour day-of-month month day-of-week"
+            )));
+        };
+        let invalid =
+            |e: String| SzError::bad_input(format!("Cron expression '{expression}': {e}"));
+        let mut weekday_bits = cron_field(weekdays, 0, 7, &WEEKDAY_NAMES).map_err(invalid)?;
+        // Sunday is both 0 and 7
+        if bit(weekday_bits, 7) {
+            weekday_bits |= 1;
+        }
+        Ok(Self {
+            expression: expression.trim().to_string(),
+            minutes: cron_field(minutes, 0, 59, &[]).map_err(invalid)?,
+            hours: cron_field(hours, 0, 23, &[]).map_err(invalid)?,
+            days: cron_field(days, 1, 31, &[]).map_err(invalid)?,
+            months: cron_field(months, 1, 12, &MONTH_NAMES).map_err(invalid)?,
+            weekdays: weekday_bits,
+            days_restricted: !days.starts_with('*'),
+            weekdays_restricted: !weekdays.starts_with('*'),
+        })
+    }
+}
+
+impl std::fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.expression)
+    }
+}
+
+/// Parses one cron field into a bit set of the values it matches. `names`
+/// name the values from `min` on.
+fn cron_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64, String> {
+    let value = |token: &str| -> Result<u32, String> {
+        let value = match names
+            .iter()
+            .position(|name| name.eq_ignore_ascii_case(token))
+        {
+            Some(index) => min + index as u32,
+            None => token
+                .parse()
+                .map_err(|_| format!("'{token}' is not a number"))?,
+        };
+        if !(min..=max).contains(&value) {
+            return Err(format!("{value} is outside {min}-{max}"));
+        }
+        Ok(value)
+    };
+    let mut bits = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| format!("'{step}' is not a step"))?;
+                if step == 0 {
+                    return Err("step must be greater than zero".to_string());
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+        let (first, last) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((first, last)) => (value(first)?, value(last)?),
+                // `n/step` runs from n to the end of the range
+                None if step > 1 => (value(range)?, max),
+                None => {
+                    let value = value(range)?;
+                    (value, value)
+                }
+            },
+        };
+        if first > last {
+            return Err(format!("range {first}-{last} is empty"));
+        }
+        for value in (first..=last).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Ok(bits)
+}
+
+fn bit(bits: u64, value: i64) -> bool {
+    (0..64).contains(&value) && bits & (1 << value) != 0
+}
+
+/// Year, month (1-12) and day (1-31) of a day counted from 1970-01-01.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    // Howard Hinnant's algorithm, with eras of 400 years starting in March
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Run and failure counts of a scheduled task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MaintenanceStats {
+    /// The task
+    pub task: MaintenanceTask,
+    /// Completed runs, successful or not
+    pub runs: u64,
+    /// Runs that failed
+    pub failures: u64,
+    /// When the last run started
+    pub last_run: Option<SystemTime>,
+    /// Message of the last failure
+    pub last_error: Option<String>,
+    /// When the task runs next; `None` when it never runs again
+    pub next_run: Option<SystemTime>,
+}
+
+type StatsCallback = Box<dyn FnMut(JsonString) + Send>;
+type ErrorCallback = Box<dyn FnMut(MaintenanceTask, SzError) + Send>;
+
+/// Builder and runner for periodic engine maintenance.
+pub struct SzMaintenanceScheduler {
+    env: Arc<SzEnvironmentCore>,
+    jobs: Vec<(MaintenanceTask, Schedule)>,
+    on_stats: Option<StatsCallback>,
+    on_error: Option<ErrorCallback>,
+}
+
+impl SzMaintenanceScheduler {
+    /// Creates a scheduler with no tasks.
+    pub fn new(env: Arc<SzEnvironmentCore>) -> Self {
+        Self {
+            env,
+            jobs: Vec::new(),
+            on_stats: None,
+            on_error: None,
+        }
+    }
+
+    /// Schedules a task. Replaces any earlier schedule for the same task.
+    pub fn schedule(mut self, task: MaintenanceTask, schedule: Schedule) -> Self {
+        self.jobs.retain(|(existing, _)| *existing != task);
+        self.jobs.push((task, schedule));
+        self
+    }
+
+    /// Primes the engine immediately and then every `interval`.
+    pub fn prime_engine_every(self, interval: Duration) -> Self {
+        self.schedule(MaintenanceTask::PrimeEngine, Schedule::every(interval))
+    }
+
+    /// Samples `get_stats` immediately and then every `interval`, passing each
+    /// sample to `callback`.
+    pub fn collect_stats_every<F>(mut self, interval: Duration, callback: F) -> Self
+    where
+        F: FnMut(JsonString) + Send + 'static,
+    {
+        self.on_stats = Some(Box::new(callback));
+        self.schedule(MaintenanceTask::CollectStats, Schedule::every(interval))
+    }
+
+    /// Sets the callback for task failures. Without one, failures are ignored
+    /// and the task is retried at its next scheduled time.
+    pub fn on_error<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(MaintenanceTask, SzError) + Send + 'static,
+    {
+        self.on_error = Some(Box::new(callback));
+        self
+    }
+
+    /// Starts the maintenance thread.
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::BadInput` - No tasks are scheduled, an interval is zero,
+    ///   or a cron expression never matches
+    /// * Any error from obtaining an engine from the environment
+    pub fn start(self) -> SzResult<MaintenanceHandle> {
+        if self.jobs.is_empty() {
+            return Err(SzError::bad_input("No maintenance tasks are scheduled"));
+        }
+        let now = SystemTime::now();
+        let mut due = Vec::with_capacity(self.jobs.len());
+        for (task, schedule) in &self.jobs {
+            if let Schedule::Every { interval, .. } = schedule
+                && interval.is_zero()
+            {
+                return Err(SzError::bad_input(format!(
+                    "Maintenance interval for {task:?} must be greater than zero"
+                )));
+            }
+            let Some(first) = schedule.first(now) else {
+                return Err(SzError::bad_input(format!(
+                    "Maintenance schedule for {task:?} never runs"
+                )));
+            };
+            due.push(Some(first));
+        }
+        let stats = Arc::new(Mutex::new(
+            self.jobs
+                .iter()
+                .zip(&due)
+                .map(|((task, _), next_run)| MaintenanceStats {
+                    task: *task,
+                    runs: 0,
+                    failures: 0,
+                    last_run: None,
+                    last_error: None,
+                    next_run: *next_run,
+                })
+                .collect::<Vec<_>>(),
+        ));
+
+        let engine = self.env.get_engine()?;
+        let (stop, stopped) = mpsc::channel::<()>();
+        let worker_stats = Arc::clone(&stats);
+        let worker = std::thread::Builder::new()
+            .name("sz-maintenance".to_string())
+            .spawn(move || {
+                let Self {
+                    jobs,
+                    mut on_stats,
+                    mut on_error,
+                    ..
+                } = self;
+
+                // Stop requested or handle dropped
+                while let Some((index, at)) = next_due(&due) {
+                    let wait = at
+                        .duration_since(SystemTime::now())
+                        .unwrap_or(Duration::ZERO);
+                    if !matches!(stopped.recv_timeout(wait), Err(RecvTimeoutError::Timeout)) {
+                        return;
+                    }
+
+                    let (task, schedule) = &jobs[index];
+                    let started = SystemTime::now();
+                    let result = run_task(engine.as_ref(), *task, &mut on_stats);
+                    due[index] = schedule.next(at, SystemTime::now());
+                    {
+                        let mut stats = lock(&worker_stats);
+                        let stats = &mut stats[index];
+                        stats.runs += 1;
+                        stats.last_run = Some(started);
+                        stats.next_run = due[index];
+                        if let Err(e) = &result {
+                            stats.failures += 1;
+                            stats.last_error = Some(e.to_string());
+                        }
+                    }
+                    if let Err(e) = result
+                        && let Some(on_error) = on_error.as_mut()
+                    {
+                        on_error(*task, e);
+                    }
+                }
+                // No task runs again; wait to be stopped
+                let _ = stopped.recv();
+            })
+            .map_err(|e| SzError::unknown(format!("Failed to start maintenance thread: {e}")))?;
+
+        Ok(MaintenanceHandle {
+            stop: Some(stop),
+            worker: Some(worker),
+            stats,
+        })
+    }
+}
+
+/// Controls a running [`SzMaintenanceScheduler`].
+///
+/// Dropping the handle stops the scheduler, waiting for a task in progress
+/// to finish.
+pub struct MaintenanceHandle {
+    stop: Option<Sender<()>>,
+    worker: Option<JoinHandle<()>>,
+    stats: Arc<Mutex<Vec<MaintenanceStats>>>,
+}
+
+impl MaintenanceHandle {
+    /// Run and failure counts of each scheduled task, in the order the tasks
+    /// were scheduled.
+    pub fn stats(&self) -> Vec<MaintenanceStats> {
+        lock(&self.stats).clone()
+    }
+
+    /// Stops the scheduler and waits for the maintenance thread to exit.
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        if let Some(stop) = self.stop.take() {
+            let _ = stop.send(());
+        }
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+impl Drop for MaintenanceHandle {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+fn run_task(
+    engine: &dyn SzEngine,
+    task: MaintenanceTask,
+    on_stats: &mut Option<StatsCallback>,
+) -> SzResult<()> {
+    match task {
+        MaintenanceTask::PrimeEngine => engine.prime_engine(),
+        MaintenanceTask::CollectStats => {
+            let stats = engine.get_stats()?;
+            if let Some(on_stats) = on_stats.as_mut() {
+                on_stats(stats);
+            }
+            Ok(())
+        }
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    match mutex.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+/// Returns the index and time of the earliest due job, or `None` when no
+/// job runs again.
+fn next_due(due: &[Option<SystemTime>]) -> Option<(usize, SystemTime)> {
+    due.iter()
+        .enumerate()
+        .filter_map(|(index, at)| at.map(|at| (index, at)))
+        .min_by_key(|(_, at)| *at)
+}
+
+/// Computes the next run time after a run that was due at `previous`.
+///
+/// Keeps a fixed cadence, but skips missed runs instead of running them back
+/// to back when a task overran its interval.
+fn advance(previous: SystemTime, interval: Duration, now: SystemTime) -> SystemTime {
+    let mut next = previous + interval;
+    if next <= now {
+        let behind = now.duration_since(next).unwrap_or(Duration::ZERO);
+        let skipped = behind.as_nanos() / interval.as_nanos() + 1;
+        next += interval * u32::try_from(skipped).unwrap_or(u32::MAX);
+    }
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_next_due_picks_earliest() {
+        let now = SystemTime::now();
+        let due = [
+            Some(now + Duration::from_secs(5)),
+            None,
+            Some(now + Duration::from_secs(1)),
+            Some(now + Duration::from_secs(3)),
+        ];
+        assert_eq!(next_due(&due), Some((2, now + Duration::from_secs(1))));
+        assert_eq!(next_due(&[None]), None);
+    }
+
+    #[test]
+    fn test_advance_keeps_cadence() {
+        let start = SystemTime::now();
+        let interval = Duration::from_secs(10);
+        let next = advance(start, interval, start + Duration::from_secs(2));
+        assert_eq!(next, start + interval);
+    }
+
+    #[test]
+    fn test_advance_skips_missed_runs() {
+        let start = SystemTime::now();
+        let interval = Duration::from_secs(10);
+        let next = advance(start, interval, start + Duration::from_secs(35));
+        assert_eq!(next, start + Duration::from_secs(40));
+    }
+
+    #[test]
+    fn test_schedule_builder() {
+        let schedule =
+            Schedule::every(Duration::from_secs(60)).starting_after(Duration::from_secs(5));
+        assert_eq!(
+            schedule,
+            Schedule::Every {
+                interval: Duration::from_secs(60),
+                initial_delay: Duration::from_secs(5),
+            }
+        );
+        let cron = Schedule::cron("@daily").unwrap();
+        assert_eq!(cron.clone().starting_after(Duration::from_secs(5)), cron);
+    }
+
+    /// Seconds since the epoch of a UTC time
+    fn utc(year: i64, month: i64, day: i64, hour: u64, minute: u64) -> SystemTime {
+        let days = (0..)
+            .find(|days| civil_from_days(*days) == (year, month, day))
+            .unwrap();
+        UNIX_EPOCH + Duration::from_secs(days as u64 * 86_400 + hour * 3600 + minute * 60)
+    }
+
+    #[test]
+    fn test_civil_from_days() {
+        assert_eq!(civil_from_days(0), (1970, 1, 1));
+        assert_eq!(civil_from_days(59), (1970, 3, 1));
+        assert_eq!(civil_from_days(11_016), (2000, 2, 29));
+        assert_eq!(civil_from_days(20_513), (2026, 3, 1));
+    }
+
+    #[test]
+    fn test_cron_next_after() {
+        let cron = |expression: &str| expression.parse::<CronSchedule>().unwrap();
+        // Thursday
+        let now = utc(2026, 1, 1, 10, 7);
+
+        assert_eq!(
+            cron("*/15 * * * *").next_after(now),
+            Some(utc(2026, 1, 1, 10, 15))
+        );
+        assert_eq!(
+            cron("0 3 * * *").next_after(now),
+            Some(utc(2026, 1, 2, 3, 0))
+        );
+        assert_eq!(
+            cron("7 10 * * *").next_after(now),
+            Some(utc(2026, 1, 2, 10, 7))
+        );
+        assert_eq!(
+            cron("0 0 1 * *").next_after(now),
+            Some(utc(2026, 2, 1, 0, 0))
+        );
+        assert_eq!(
+            cron("30 2 * * MON-FRI").next_after(now),
+            Some(utc(2026, 1, 2, 2, 30))
+        );
+        assert_eq!(
+            cron("0 12 * * sat,7").next_after(now),
+            Some(utc(2026, 1, 3, 12, 0))
+        );
+        assert_eq!(
+            cron("0 0 29 FEB *").next_after(now),
+            Some(utc(2028, 2, 29, 0, 0))
+        );
+        assert_eq!(cron("0 0 30 2 *").next_after(now), None);
+        // Either day field matches when both are restricted
+        assert_eq!(
+            cron("0 0 15 * MON").next_after(now),
+            Some(utc(2026, 1, 5, 0, 0))
+        );
+        assert_eq!(
+            cron("5/20 1-3/2 * * *").next_after(now),
+            Some(utc(2026, 1, 2, 1, 5))
+        );
+        assert_eq!(
+            cron("@hourly").next_after(now),
+            Some(utc(2026, 1, 1, 11, 0))
+        );
+        assert_eq!(cron("@hourly").expression(), "@hourly");
+    }
+
+    #[test]
+    fn test_cron_rejects_invalid_expressions() {
+        for expression in [
+            "* * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "* * * 13 *",
+            "* * * * 8",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "a * * * *",
+            "* * * FOO *",
+        ] {
+            let err = expression.parse::<CronSchedule>().unwrap_err();
+            assert!(matches!(err, SzError::BadInput(_)), "{expression}");
+        }
+    }
+}
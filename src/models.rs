@@ -0,0 +1,1840 @@
+//! Typed models for engine JSON responses.
+//!
+//! The engine methods return raw JSON strings so that callers can choose how to
+//! parse them. The types in this module cover the commonly used parts of those
+//! documents; fields the engine only emits for particular flags are optional or
+//! default to empty, and unknown fields are ignored.
+//!
+//! # Example
+//!
+//! ```
+//! use sz_rust_sdk::models::ExportedEntity;
+//!
+//! let line = r#"{"RESOLVED_ENTITY":{"ENTITY_ID":1,"ENTITY_NAME":"John Smith",
+//!     "RECORDS":[{"DATA_SOURCE":"TEST","RECORD_ID":"1001"}]},"RELATED_ENTITIES":[]}"#;
+//! let entity: ExportedEntity = serde_json::from_str(line)?;
+//! assert_eq!(entity.resolved_entity.entity_id, 1);
+//! assert_eq!(entity.resolved_entity.records[0].record_id, "1001");
+//! # Ok::<(), serde_json::Error>(())
+//! ```
+
+use crate::flags::{MatchLevel, RelationshipKind};
+use crate::redact::{self, Sensitive};
+use crate::types::EntityId;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// One entity from a JSON entity export (one line of `fetch_next` output).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct ExportedEntity {
+    /// The resolved entity itself
+    pub resolved_entity: ResolvedEntity,
+    /// Entities related to this one (present when relation flags are requested)
+    #[serde(default)]
+    pub related_entities: Vec<RelatedEntity>,
+}
+
+/// A resolved entity and the records that make it up.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct ResolvedEntity {
+    /// Engine-assigned entity identifier
+    pub entity_id: EntityId,
+    /// Best name for the entity, when entity name flags are requested
+    #[serde(default)]
+    pub entity_name: Option<String>,
+    /// Records resolved into this entity
+    #[serde(default)]
+    pub records: Vec<EntityRecord>,
+}
+
+impl fmt::Debug for ResolvedEntity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResolvedEntity")
+            .field("entity_id", &self.entity_id)
+            .field("entity_name", &redact::option(&self.entity_name))
+            .field("records", &self.records)
+            .finish()
+    }
+}
+
+/// A record belonging to a resolved entity.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct EntityRecord {
+    /// Data source code of the record
+    pub data_source: String,
+    /// Record identifier within the data source
+    pub record_id: String,
+    /// Match key that brought the record into the entity
+    #[serde(default)]
+    pub match_key: Option<String>,
+    /// Resolution rule code (ERRULE_CODE) that matched the record
+    #[serde(default, rename = "ERRULE_CODE")]
+    pub errule_code: Option<String>,
+}
+
+/// An entity related to a resolved entity.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct RelatedEntity {
+    /// Identifier of the related entity
+    pub entity_id: EntityId,
+    /// Best name for the related entity, when requested
+    #[serde(default)]
+    pub entity_name: Option<String>,
+    /// Match level of the relationship (e.g. 2 = possible match)
+    #[serde(default)]
+    pub match_level: Option<i64>,
+    /// Match level code (e.g. "POSSIBLY_SAME")
+    #[serde(default)]
+    pub match_level_code: Option<String>,
+    /// Match key describing why the entities are related
+    #[serde(default)]
+    pub match_key: Option<String>,
+    /// Whether the relationship was disclosed rather than derived
+    #[serde(default)]
+    pub is_disclosed: Option<i64>,
+    /// Whether the relationship is ambiguous
+    #[serde(default)]
+    pub is_ambiguous: Option<i64>,
+}
+
+impl fmt::Debug for RelatedEntity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RelatedEntity")
+            .field("entity_id", &self.entity_id)
+            .field("entity_name", &redact::option(&self.entity_name))
+            .field("match_level", &self.match_level)
+            .field("match_level_code", &self.match_level_code)
+            .field("match_key", &self.match_key)
+            .field("is_disclosed", &self.is_disclosed)
+            .field("is_ambiguous", &self.is_ambiguous)
+            .finish()
+    }
+}
+
+impl RelatedEntity {
+    /// The kind of relationship; empty for an unknown match level.
+    pub fn relationship(&self) -> RelationshipKind {
+        RelationshipKind::classify(
+            self.match_level_code.as_deref(),
+            self.is_disclosed.unwrap_or(0) != 0,
+        )
+    }
+}
+
+/// A `search_by_attributes` response.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct SearchResponse {
+    /// Matching entities, best match first
+    #[serde(default)]
+    pub resolved_entities: Vec<SearchResult>,
+}
+
+/// One entity matched by `search_by_attributes`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct SearchResult {
+    /// How the entity matched the search attributes
+    #[serde(default)]
+    pub match_info: SearchMatchInfo,
+    /// The matched entity
+    pub entity: SearchEntity,
+}
+
+/// Match summary of a [`SearchResult`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct SearchMatchInfo {
+    /// Match level code (e.g. "RESOLVED", "POSSIBLY_SAME")
+    #[serde(default)]
+    pub match_level_code: Option<String>,
+    /// Match key describing which features matched
+    #[serde(default)]
+    pub match_key: Option<String>,
+    /// Resolution rule code (ERRULE_CODE) that matched
+    #[serde(default, rename = "ERRULE_CODE")]
+    pub errule_code: Option<String>,
+    /// Feature comparisons, keyed by feature type (present when
+    /// `SEARCH_INCLUDE_FEATURE_SCORES` is requested)
+    #[serde(default)]
+    pub feature_scores: std::collections::BTreeMap<String, Vec<FeatureScore>>,
+}
+
+impl SearchResult {
+    /// The match level of the result; empty for an unknown level.
+    pub fn match_level(&self) -> MatchLevel {
+        MatchLevel::classify(self.match_info.match_level_code.as_deref())
+    }
+
+    /// Highest score of the given feature type (e.g. `NAME`), when feature
+    /// scores were requested and the feature was compared.
+    pub fn best_score(&self, feature_type: &str) -> Option<i64> {
+        self.match_info
+            .feature_scores
+            .get(feature_type)?
+            .iter()
+            .filter_map(|score| score.score)
+            .max()
+    }
+}
+
+impl SearchResponse {
+    /// Results at any of `levels`, in engine order.
+    ///
+    /// ```
+    /// use sz_rust_sdk::flags::MatchLevel;
+    /// use sz_rust_sdk::models::SearchResponse;
+    ///
+    /// let response: SearchResponse = serde_json::from_str(
+    ///     r#"{"RESOLVED_ENTITIES":[
+    ///         {"MATCH_INFO":{"MATCH_LEVEL_CODE":"POSSIBLY_RELATED"},"ENTITY":{"RESOLVED_ENTITY":{"ENTITY_ID":1}}},
+    ///         {"MATCH_INFO":{"MATCH_LEVEL_CODE":"RESOLVED"},"ENTITY":{"RESOLVED_ENTITY":{"ENTITY_ID":2}}}]}"#,
+    /// )?;
+    /// let confident = response.filter_by_match_level(MatchLevel::RESOLVED | MatchLevel::POSSIBLY_SAME);
+    /// assert_eq!(confident.len(), 1);
+    /// assert_eq!(confident[0].entity.resolved_entity.entity_id, 2);
+    /// # Ok::<(), serde_json::Error>(())
+    /// ```
+    pub fn filter_by_match_level(&self, levels: MatchLevel) -> Vec<&SearchResult> {
+        self.resolved_entities
+            .iter()
+            .filter(|result| levels.intersects(result.match_level()))
+            .collect()
+    }
+
+    /// Results whose best score for `feature_type` is at least `min_score`,
+    /// in engine order. Needs `SEARCH_INCLUDE_FEATURE_SCORES`; results
+    /// without a score for the feature type are left out.
+    pub fn filter_by_score(&self, feature_type: &str, min_score: i64) -> Vec<&SearchResult> {
+        self.resolved_entities
+            .iter()
+            .filter(|result| {
+                result
+                    .best_score(feature_type)
+                    .is_some_and(|score| score >= min_score)
+            })
+            .collect()
+    }
+
+    /// Results ordered by match level, strongest first (see
+    /// [`MatchLevel::weight`]), keeping engine order within a level.
+    pub fn ranked(&self) -> Vec<&SearchResult> {
+        let mut results: Vec<&SearchResult> = self.resolved_entities.iter().collect();
+        results.sort_by_key(|result| result.match_level().weight());
+        results
+    }
+}
+
+/// The entity of a [`SearchResult`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct SearchEntity {
+    /// The resolved entity
+    pub resolved_entity: ResolvedEntity,
+}
+
+/// Why an entity matched (or did not match) a set of search attributes, as
+/// returned by `SzEngine::why_search`.
+///
+/// # Example
+///
+/// ```
+/// use sz_rust_sdk::models::SearchExplanation;
+///
+/// let json = r#"{"WHY_RESULTS":[{"ENTITY_ID":7,"MATCH_INFO":{
+///     "WHY_KEY":"+NAME","WHY_ERRULE_CODE":"SNAME",
+///     "FEATURE_SCORES":{"NAME":[{"INBOUND_FEAT_DESC":"JOHN SMITH",
+///         "CANDIDATE_FEAT_DESC":"JOHN SMITH","SCORE":100,"SCORE_BUCKET":"SAME"}]},
+///     "CANDIDATE_KEYS":{"NAME_KEY":[{"FEAT_ID":3,"FEAT_DESC":"JN|SM0"}]}}}]}"#;
+/// let explanation = SearchExplanation::from_json(json)?;
+/// assert_eq!(explanation.entity_id, 7);
+/// assert_eq!(explanation.feature_scores["NAME"][0].score, Some(100));
+/// assert_eq!(explanation.candidate_keys["NAME_KEY"][0].feat_desc, "JN|SM0");
+/// # Ok::<(), sz_rust_sdk::SzError>(())
+/// ```
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchExplanation {
+    /// Entity the explanation is for
+    pub entity_id: EntityId,
+    /// Match key of the features that matched (e.g. "+NAME+DOB")
+    pub why_key: Option<String>,
+    /// Resolution rule code that applied
+    pub why_errule_code: Option<String>,
+    /// Feature comparisons, keyed by feature type
+    pub feature_scores: std::collections::BTreeMap<String, Vec<FeatureScore>>,
+    /// Candidate keys that found the entity, keyed by key type
+    pub candidate_keys: std::collections::BTreeMap<String, Vec<CandidateKey>>,
+    /// Disclosed relationships involved in the match, as returned by the
+    /// engine (the shape depends on the relationship types)
+    pub disclosed_relations: Option<serde_json::Value>,
+}
+
+impl fmt::Debug for SearchExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SearchExplanation")
+            .field("entity_id", &self.entity_id)
+            .field("why_key", &self.why_key)
+            .field("why_errule_code", &self.why_errule_code)
+            .field("feature_scores", &self.feature_scores)
+            .field("candidate_keys", &self.candidate_keys)
+            .field(
+                "disclosed_relations",
+                &redact::option(&self.disclosed_relations),
+            )
+            .finish()
+    }
+}
+
+/// Comparison of one search feature with one entity feature.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE", default)]
+pub struct FeatureScore {
+    /// Feature ID of the search attribute
+    pub inbound_feat_id: Option<i64>,
+    /// Description of the search attribute
+    pub inbound_feat_desc: Option<String>,
+    /// Feature ID of the entity feature
+    pub candidate_feat_id: Option<i64>,
+    /// Description of the entity feature
+    pub candidate_feat_desc: Option<String>,
+    /// Comparison score, 0 to 100
+    pub score: Option<i64>,
+    /// Score bucket (e.g. "SAME", "CLOSE", "NO_CHANCE")
+    pub score_bucket: Option<String>,
+    /// Feature behavior used for scoring
+    pub score_behavior: Option<String>,
+}
+
+impl fmt::Debug for FeatureScore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FeatureScore")
+            .field("inbound_feat_id", &self.inbound_feat_id)
+            .field(
+                "inbound_feat_desc",
+                &redact::option(&self.inbound_feat_desc),
+            )
+            .field("candidate_feat_id", &self.candidate_feat_id)
+            .field(
+                "candidate_feat_desc",
+                &redact::option(&self.candidate_feat_desc),
+            )
+            .field("score", &self.score)
+            .field("score_bucket", &self.score_bucket)
+            .field("score_behavior", &self.score_behavior)
+            .finish()
+    }
+}
+
+/// A candidate key (bucket) through which the entity was found.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE", default)]
+pub struct CandidateKey {
+    /// Feature ID of the key
+    pub feat_id: Option<i64>,
+    /// Key value
+    pub feat_desc: String,
+}
+
+impl fmt::Debug for CandidateKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CandidateKey")
+            .field("feat_id", &self.feat_id)
+            .field("feat_desc", &Sensitive(&self.feat_desc))
+            .finish()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+struct WhySearchResponse {
+    #[serde(default)]
+    why_results: Vec<WhySearchResult>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+struct WhySearchResult {
+    entity_id: EntityId,
+    #[serde(default)]
+    match_info: WhyMatchInfo,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE", default)]
+struct WhyMatchInfo {
+    why_key: Option<String>,
+    #[serde(rename = "WHY_ERRULE_CODE")]
+    why_errule_code: Option<String>,
+    match_level_code: Option<String>,
+    feature_scores: std::collections::BTreeMap<String, Vec<FeatureScore>>,
+    candidate_keys: std::collections::BTreeMap<String, Vec<CandidateKey>>,
+    disclosed_relations: Option<serde_json::Value>,
+}
+
+impl SearchExplanation {
+    /// Parses the JSON returned by `SzEngine::why_search`.
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::Json` - The document is not valid why-search JSON
+    /// * `SzError::BadInput` - The document has no `WHY_RESULTS` entry
+    pub fn from_json(json: &str) -> crate::error::SzResult<Self> {
+        let response: WhySearchResponse = serde_json::from_str(json)?;
+        let Some(result) = response.why_results.into_iter().next() else {
+            return Err(crate::error::SzError::bad_input(
+                "why_search response has no WHY_RESULTS entry",
+            ));
+        };
+        let info = result.match_info;
+        Ok(Self {
+            entity_id: result.entity_id,
+            why_key: info.why_key.filter(|key| !key.is_empty()),
+            why_errule_code: info.why_errule_code.filter(|code| !code.is_empty()),
+            feature_scores: info.feature_scores,
+            candidate_keys: info.candidate_keys,
+            disclosed_relations: info.disclosed_relations,
+        })
+    }
+}
+
+/// A search result together with the explanation of its match.
+///
+/// Returned by `SzEngine::search_by_attributes_explained`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExplainedSearchResult {
+    /// The search result
+    pub result: SearchResult,
+    /// Why the entity matched
+    pub explanation: SearchExplanation,
+}
+
+/// Why two records resolved together (or did not), as returned by
+/// `SzEngine::why_records`.
+///
+/// # Example
+///
+/// ```
+/// use sz_rust_sdk::models::RecordsExplanation;
+///
+/// let json = r#"{"WHY_RESULTS":[{"ENTITY_ID":7,"ENTITY_ID_2":7,"MATCH_INFO":{
+///     "WHY_KEY":"+NAME+PHONE","WHY_ERRULE_CODE":"CNAME_CFF","MATCH_LEVEL_CODE":"RESOLVED",
+///     "FEATURE_SCORES":{"NAME":[{"SCORE":100,"SCORE_BUCKET":"SAME"}]}}}]}"#;
+/// let explanation = RecordsExplanation::from_json(json)?;
+/// assert!(explanation.same_entity());
+/// assert_eq!(explanation.why_key.as_deref(), Some("+NAME+PHONE"));
+/// # Ok::<(), sz_rust_sdk::SzError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RecordsExplanation {
+    /// Entity of the first record
+    pub entity_id: EntityId,
+    /// Entity of the second record
+    pub entity_id_2: EntityId,
+    /// Match key of the features that matched (e.g. "+NAME+DOB")
+    pub why_key: Option<String>,
+    /// Resolution rule code that applied
+    pub why_errule_code: Option<String>,
+    /// Match level code (e.g. "RESOLVED", "POSSIBLY_SAME")
+    pub match_level_code: Option<String>,
+    /// Feature comparisons, keyed by feature type
+    pub feature_scores: std::collections::BTreeMap<String, Vec<FeatureScore>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+struct WhyRecordsResponse {
+    #[serde(default)]
+    why_results: Vec<WhyRecordsResult>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+struct WhyRecordsResult {
+    entity_id: EntityId,
+    #[serde(rename = "ENTITY_ID_2")]
+    entity_id_2: EntityId,
+    #[serde(default)]
+    match_info: WhyMatchInfo,
+}
+
+impl RecordsExplanation {
+    /// Parses the JSON returned by `SzEngine::why_records`.
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::Json` - The document is not valid why-records JSON
+    /// * `SzError::BadInput` - The document has no `WHY_RESULTS` entry
+    pub fn from_json(json: &str) -> crate::error::SzResult<Self> {
+        let response: WhyRecordsResponse = serde_json::from_str(json)?;
+        let Some(result) = response.why_results.into_iter().next() else {
+            return Err(crate::error::SzError::bad_input(
+                "why_records response has no WHY_RESULTS entry",
+            ));
+        };
+        let info = result.match_info;
+        Ok(Self {
+            entity_id: result.entity_id,
+            entity_id_2: result.entity_id_2,
+            why_key: info.why_key.filter(|key| !key.is_empty()),
+            why_errule_code: info.why_errule_code.filter(|code| !code.is_empty()),
+            match_level_code: info.match_level_code.filter(|code| !code.is_empty()),
+            feature_scores: info.feature_scores,
+        })
+    }
+
+    /// Returns whether both records are in the same entity.
+    pub fn same_entity(&self) -> bool {
+        self.entity_id == self.entity_id_2
+    }
+}
+
+/// Engine statistics as returned by `SzEngine::get_stats`.
+///
+/// Only the workload counters are modelled; the per-feature breakdowns and
+/// thread/resource sections vary between engine versions and are ignored.
+///
+/// # Example
+///
+/// ```
+/// use sz_rust_sdk::models::{EngineStats, WorkloadCounters};
+/// use std::time::Duration;
+///
+/// let earlier = EngineStats::from_json(r#"{"workload":{"loadedRecords":100}}"#)?;
+/// let later = EngineStats::from_json(r#"{"workload":{"loadedRecords":350}}"#)?;
+/// let delta = later.delta(&earlier);
+/// assert_eq!(delta.loaded_records, 250);
+/// assert_eq!(WorkloadCounters::rate(delta.loaded_records, Duration::from_secs(10)), 25.0);
+/// # Ok::<(), sz_rust_sdk::SzError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EngineStats {
+    /// Workload section of the statistics document
+    #[serde(default)]
+    pub workload: WorkloadStats,
+}
+
+/// The `workload` section of the engine statistics.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkloadStats {
+    /// Engine API version that produced the statistics
+    #[serde(default)]
+    pub api_version: Option<String>,
+    /// Counter values
+    #[serde(flatten)]
+    pub counters: WorkloadCounters,
+}
+
+/// Workload counters from the engine statistics.
+///
+/// Also used for the difference between two samples, see
+/// [`EngineStats::delta`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WorkloadCounters {
+    /// Records loaded by this process
+    pub loaded_records: u64,
+    /// Records added
+    pub added_records: u64,
+    /// Records deleted
+    pub deleted_records: u64,
+    /// Entity reevaluations
+    pub reevaluations: u64,
+    /// Entities repaired
+    pub repaired_entities: u64,
+    /// Time spent in engine calls, in milliseconds
+    pub duration: u64,
+    /// Retried operations
+    pub retries: u64,
+    /// Candidate entities considered for resolution
+    pub candidates: u64,
+    /// Ambiguity tests performed
+    pub actual_ambiguous_test: u64,
+    /// Ambiguity tests answered from cache
+    pub cached_ambiguous_test: u64,
+    /// Unresolve tests performed
+    pub unresolve_test: u64,
+    /// Unresolve operations aborted
+    pub aborted_unresolve: u64,
+    /// Re-resolutions skipped
+    pub reresolve_skipped: u64,
+    /// Observed features filtered out
+    pub filtered_obs_feat: u64,
+    /// Library feature cache hits
+    pub lib_feat_cache_hit: u64,
+    /// Library feature cache misses
+    pub lib_feat_cache_miss: u64,
+    /// Resolution feature statistic cache hits
+    pub res_feat_stat_cache_hit: u64,
+    /// Resolution feature statistic cache misses
+    pub res_feat_stat_cache_miss: u64,
+}
+
+impl EngineStats {
+    /// Parses the JSON returned by `SzEngine::get_stats`.
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::Json` - The document is not valid statistics JSON
+    pub fn from_json(json: &str) -> crate::error::SzResult<Self> {
+        serde_json::from_str(json).map_err(crate::error::SzError::from)
+    }
+
+    /// Returns the counter increase from `earlier` to this sample.
+    ///
+    /// A counter that went down (the engine was restarted between samples)
+    /// is reported as its current value.
+    pub fn delta(&self, earlier: &EngineStats) -> WorkloadCounters {
+        self.workload.counters.delta(&earlier.workload.counters)
+    }
+}
+
+impl WorkloadCounters {
+    /// Returns the increase of each counter from `earlier` to `self`.
+    ///
+    /// A counter that went down is reported as its current value.
+    pub fn delta(&self, earlier: &WorkloadCounters) -> WorkloadCounters {
+        fn diff(now: u64, before: u64) -> u64 {
+            if now >= before { now - before } else { now }
+        }
+        WorkloadCounters {
+            loaded_records: diff(self.loaded_records, earlier.loaded_records),
+            added_records: diff(self.added_records, earlier.added_records),
+            deleted_records: diff(self.deleted_records, earlier.deleted_records),
+            reevaluations: diff(self.reevaluations, earlier.reevaluations),
+            repaired_entities: diff(self.repaired_entities, earlier.repaired_entities),
+            duration: diff(self.duration, earlier.duration),
+            retries: diff(self.retries, earlier.retries),
+            candidates: diff(self.candidates, earlier.candidates),
+            actual_ambiguous_test: diff(self.actual_ambiguous_test, earlier.actual_ambiguous_test),
+            cached_ambiguous_test: diff(self.cached_ambiguous_test, earlier.cached_ambiguous_test),
+            unresolve_test: diff(self.unresolve_test, earlier.unresolve_test),
+            aborted_unresolve: diff(self.aborted_unresolve, earlier.aborted_unresolve),
+            reresolve_skipped: diff(self.reresolve_skipped, earlier.reresolve_skipped),
+            filtered_obs_feat: diff(self.filtered_obs_feat, earlier.filtered_obs_feat),
+            lib_feat_cache_hit: diff(self.lib_feat_cache_hit, earlier.lib_feat_cache_hit),
+            lib_feat_cache_miss: diff(self.lib_feat_cache_miss, earlier.lib_feat_cache_miss),
+            res_feat_stat_cache_hit: diff(
+                self.res_feat_stat_cache_hit,
+                earlier.res_feat_stat_cache_hit,
+            ),
+            res_feat_stat_cache_miss: diff(
+                self.res_feat_stat_cache_miss,
+                earlier.res_feat_stat_cache_miss,
+            ),
+        }
+    }
+
+    /// Returns `count` per second over `elapsed`, or 0 for a zero interval.
+    pub fn rate(count: u64, elapsed: std::time::Duration) -> f64 {
+        let seconds = elapsed.as_secs_f64();
+        if seconds > 0.0 {
+            count as f64 / seconds
+        } else {
+            0.0
+        }
+    }
+
+    /// Library feature cache hit ratio in `0.0..=1.0`, or `None` without lookups.
+    pub fn lib_feat_cache_hit_ratio(&self) -> Option<f64> {
+        hit_ratio(self.lib_feat_cache_hit, self.lib_feat_cache_miss)
+    }
+
+    /// Resolution feature statistic cache hit ratio, or `None` without lookups.
+    pub fn res_feat_stat_cache_hit_ratio(&self) -> Option<f64> {
+        hit_ratio(self.res_feat_stat_cache_hit, self.res_feat_stat_cache_miss)
+    }
+}
+
+fn hit_ratio(hits: u64, misses: u64) -> Option<f64> {
+    let total = hits + misses;
+    (total > 0).then(|| hits as f64 / total as f64)
+}
+
+/// One entry of the configuration registry.
+///
+/// # Example
+///
+/// ```
+/// use sz_rust_sdk::models::ConfigInfo;
+///
+/// let registry = r#"{"CONFIGS":[{"CONFIG_ID":4019066234,
+///     "CONFIG_COMMENT":"Initial","SYS_CREATE_DT":"2024-10-01 12:00:00.000"}]}"#;
+/// let configs = ConfigInfo::from_registry_json(registry)?;
+/// assert_eq!(configs[0].config_id, 4019066234);
+/// assert_eq!(configs[0].comment, "Initial");
+/// # Ok::<(), sz_rust_sdk::SzError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigInfo {
+    /// Configuration ID
+    #[serde(rename = "CONFIG_ID")]
+    pub config_id: crate::types::ConfigId,
+    /// Comment given when the configuration was registered
+    #[serde(rename = "CONFIG_COMMENT", default)]
+    pub comment: String,
+    /// Registration timestamp as reported by the repository
+    #[serde(rename = "SYS_CREATE_DT", default)]
+    pub created: String,
+}
+
+#[derive(Deserialize)]
+struct ConfigRegistry {
+    #[serde(rename = "CONFIGS", default)]
+    configs: Vec<ConfigInfo>,
+}
+
+impl ConfigInfo {
+    /// Parses the JSON returned by `SzConfigManager::get_config_registry`.
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::Json` - The document is not valid registry JSON
+    pub fn from_registry_json(json: &str) -> crate::error::SzResult<Vec<Self>> {
+        let registry: ConfigRegistry = serde_json::from_str(json)?;
+        Ok(registry.configs)
+    }
+
+    /// Loads this configuration for inspection.
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::NotFound` - The configuration no longer exists
+    pub fn load(
+        &self,
+        config_mgr: &dyn crate::traits::SzConfigManager,
+    ) -> crate::error::SzResult<Box<dyn crate::traits::SzConfig>> {
+        config_mgr.create_config_from_id(self.config_id)
+    }
+}
+
+/// A redo record as returned by `SzEngine::get_redo_record`.
+///
+/// Redo records are produced by the engine and their fields depend on why the
+/// record was queued. Current engines emit a flat document with a `REASON`
+/// and the affected record; older engines emitted a `UMF_PROC` message with
+/// named parameters. Both forms are accepted. Keep [`json`](Self::json) to
+/// pass the record back to `SzEngine::process_redo_record` unchanged.
+///
+/// # Example
+///
+/// ```
+/// use sz_rust_sdk::models::{RedoReason, RedoRecord};
+///
+/// let json = r#"{"REASON":"LIB_FEAT_ID[12] of FTYPE_ID[3] went generic for CANDIDATES",
+///     "DATA_SOURCE":"TEST","RECORD_ID":"1001","REEVAL_ITERATION":1,
+///     "ENTITY_CORRUPTION_TRANSIENT":true,"DSRC_ACTION":"X"}"#;
+/// let redo = RedoRecord::from_json(json)?;
+/// assert_eq!(
+///     redo.reason,
+///     RedoReason::FeatureWentGeneric { lib_feat_id: Some(12), ftype_id: Some(3) }
+/// );
+/// assert_eq!(redo.record_key(), Some(("TEST", "1001")));
+/// assert!(redo.requires_reevaluation());
+/// # Ok::<(), sz_rust_sdk::SzError>(())
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct RedoRecord {
+    /// Why the record was queued
+    pub reason: RedoReason,
+    /// Data source of the affected record
+    pub data_source: Option<String>,
+    /// ID of the affected record
+    pub record_id: Option<String>,
+    /// Affected entity, when the redo targets an entity rather than a record
+    pub entity_id: Option<EntityId>,
+    /// How many times the record has been reevaluated
+    pub reeval_iteration: Option<i64>,
+    /// Whether the engine flagged a transient entity corruption
+    pub entity_corruption_transient: bool,
+    /// Data source action code (e.g. "X" for reevaluate)
+    pub dsrc_action: Option<String>,
+    /// The record as returned by the engine
+    pub json: String,
+}
+
+impl fmt::Debug for RedoRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RedoRecord")
+            .field("reason", &self.reason)
+            .field("data_source", &self.data_source)
+            .field("record_id", &self.record_id)
+            .field("entity_id", &self.entity_id)
+            .field("reeval_iteration", &self.reeval_iteration)
+            .field(
+                "entity_corruption_transient",
+                &self.entity_corruption_transient,
+            )
+            .field("dsrc_action", &self.dsrc_action)
+            .field("json", &Sensitive(&self.json))
+            .finish()
+    }
+}
+
+/// Why a redo record was queued.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RedoReason {
+    /// A feature became generic, so the entities sharing it need to be
+    /// re-resolved
+    FeatureWentGeneric {
+        /// Library feature ID, from `LIB_FEAT_ID[..]`
+        lib_feat_id: Option<crate::types::FeatureId>,
+        /// Feature type ID, from `FTYPE_ID[..]`
+        ftype_id: Option<i64>,
+    },
+    /// A delete was deferred and still has to be completed
+    DeferredDelete,
+    /// Older `UMF_PROC` message, with the procedure name (e.g. "VALIDATE")
+    Umf(String),
+    /// Any other reason, as reported by the engine
+    Other(String),
+}
+
+impl RedoReason {
+    fn classify(reason: &str) -> Self {
+        let lower = reason.to_ascii_lowercase();
+        if lower.contains("went generic") {
+            Self::FeatureWentGeneric {
+                lib_feat_id: bracketed(reason, "LIB_FEAT_ID["),
+                ftype_id: bracketed(reason, "FTYPE_ID["),
+            }
+        } else if lower.contains("deferred delete") {
+            Self::DeferredDelete
+        } else {
+            Self::Other(reason.to_string())
+        }
+    }
+}
+
+/// Parses the integer following `prefix` up to the closing bracket.
+fn bracketed(text: &str, prefix: &str) -> Option<i64> {
+    let start = text.find(prefix)? + prefix.len();
+    let end = start + text[start..].find(']')?;
+    text[start..end].trim().parse().ok()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+struct RawRedoRecord {
+    #[serde(default)]
+    reason: Option<String>,
+    #[serde(default)]
+    data_source: Option<String>,
+    #[serde(default)]
+    record_id: Option<serde_json::Value>,
+    #[serde(default)]
+    entity_id: Option<serde_json::Value>,
+    #[serde(default)]
+    reeval_iteration: Option<i64>,
+    #[serde(default)]
+    entity_corruption_transient: Option<serde_json::Value>,
+    #[serde(default)]
+    dsrc_action: Option<String>,
+    #[serde(default)]
+    umf_proc: Option<UmfProc>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+struct UmfProc {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    params: Vec<UmfParamEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+struct UmfParamEntry {
+    param: UmfParam,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+struct UmfParam {
+    name: String,
+    #[serde(default)]
+    value: serde_json::Value,
+}
+
+/// Reads a value the engine emits either as a string or as a number.
+fn string_value(value: &serde_json::Value) -> Option<String> {
+    match value {
+        serde_json::Value::String(s) if !s.is_empty() => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+impl RedoRecord {
+    /// Parses the JSON returned by `SzEngine::get_redo_record`.
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::Json` - The document is not valid JSON
+    /// * `SzError::BadInput` - The document is empty, which means the redo
+    ///   queue was empty
+    pub fn from_json(json: &str) -> crate::error::SzResult<Self> {
+        if json.trim().is_empty() {
+            return Err(crate::error::SzError::bad_input("redo record is empty"));
+        }
+        let raw: RawRedoRecord = serde_json::from_str(json)?;
+
+        let mut data_source = raw.data_source.filter(|ds| !ds.is_empty());
+        let mut record_id = raw.record_id.as_ref().and_then(string_value);
+        let mut entity_id = raw
+            .entity_id
+            .as_ref()
+            .and_then(string_value)
+            .and_then(|id| id.parse().ok())
+            .and_then(EntityId::from_engine);
+        let reason = match raw.umf_proc {
+            Some(umf) => {
+                for entry in &umf.params {
+                    let value = string_value(&entry.param.value);
+                    match entry.param.name.as_str() {
+                        "DATA_SOURCE" if data_source.is_none() => data_source = value,
+                        "RECORD_ID" if record_id.is_none() => record_id = value,
+                        "ENTITY_ID" if entity_id.is_none() => {
+                            entity_id = value
+                                .and_then(|id| id.parse().ok())
+                                .and_then(EntityId::from_engine)
+                        }
+                        _ => {}
+                    }
+                }
+                RedoReason::Umf(umf.name)
+            }
+            None => RedoReason::classify(raw.reason.as_deref().unwrap_or_default()),
+        };
+        let entity_corruption_transient = match raw.entity_corruption_transient {
+            Some(serde_json::Value::Bool(b)) => b,
+            Some(serde_json::Value::Number(n)) => n.as_i64().is_some_and(|n| n != 0),
+            Some(serde_json::Value::String(s)) => matches!(s.as_str(), "true" | "Y" | "1"),
+            _ => false,
+        };
+
+        Ok(Self {
+            reason,
+            data_source,
+            record_id,
+            entity_id,
+            reeval_iteration: raw.reeval_iteration,
+            entity_corruption_transient,
+            dsrc_action: raw.dsrc_action.filter(|action| !action.is_empty()),
+            json: json.to_string(),
+        })
+    }
+
+    /// Returns the affected record as `(data_source, record_id)`, if the
+    /// redo names one.
+    pub fn record_key(&self) -> Option<(&str, &str)> {
+        Some((self.data_source.as_deref()?, self.record_id.as_deref()?))
+    }
+
+    /// Returns the affected record or entity as an [`EntityRef`](crate::types::EntityRef).
+    pub fn entity_ref(&self) -> Option<crate::types::EntityRef<'_>> {
+        match (self.record_key(), self.entity_id) {
+            (Some((data_source, record_id)), _) => Some(crate::types::EntityRef::Record {
+                data_source,
+                record_id,
+            }),
+            (None, Some(id)) => Some(crate::types::EntityRef::Id(id)),
+            (None, None) => None,
+        }
+    }
+
+    /// Whether processing this redo re-resolves the affected record or
+    /// entity, so its entity may change.
+    ///
+    /// Deferred deletes only finish removing a record and are the exception,
+    /// unless the engine also asked for a reevaluation (`DSRC_ACTION` "X").
+    pub fn requires_reevaluation(&self) -> bool {
+        match self.reason {
+            RedoReason::DeferredDelete => self.dsrc_action.as_deref() == Some("X"),
+            _ => true,
+        }
+    }
+}
+
+/// A `WITH_INFO` response from a record operation (`add_record`,
+/// `delete_record`, `process_redo_record`, ...).
+///
+/// The document only lists which entities were touched. [`summarize`](Self::summarize)
+/// looks those entities up to say what happened to them.
+///
+/// # Example
+///
+/// ```
+/// use sz_rust_sdk::models::WithInfoResult;
+///
+/// let info = WithInfoResult::from_json(
+///     r#"{"DATA_SOURCE":"TEST","RECORD_ID":"1","AFFECTED_ENTITIES":[{"ENTITY_ID":7}]}"#,
+/// )?;
+/// assert_eq!(info.affected_entity_ids(), vec![7]);
+/// assert!(WithInfoResult::from_json("")?.affected_entities.is_empty());
+/// # Ok::<(), sz_rust_sdk::SzError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct WithInfoResult {
+    /// Data source of the record the operation targeted
+    #[serde(default)]
+    pub data_source: Option<String>,
+    /// ID of the record the operation targeted
+    #[serde(default)]
+    pub record_id: Option<String>,
+    /// Entities whose membership or features changed
+    #[serde(default)]
+    pub affected_entities: Vec<AffectedEntity>,
+}
+
+/// An entity listed in `AFFECTED_ENTITIES`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct AffectedEntity {
+    /// Identifier of the affected entity
+    pub entity_id: EntityId,
+}
+
+/// What happened to one entity in a [`ResolutionSummary`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ResolutionOutcome {
+    /// The record is the only record of its entity, which was created for it
+    Created {
+        /// The record's entity
+        entity_id: EntityId,
+    },
+    /// The record resolved into an entity that has other records
+    Merged {
+        /// The record's entity
+        entity_id: EntityId,
+    },
+    /// The record no longer exists, e.g. after `delete_record`
+    RecordRemoved,
+    /// An affected entity no longer exists: its records moved into another
+    /// entity, or it lost its last record
+    EntityRemoved {
+        /// The removed entity
+        entity_id: EntityId,
+    },
+    /// An affected entity other than the record's still exists but changed,
+    /// typically because records split off it or it was split off
+    SplitTriggered {
+        /// The changed entity
+        entity_id: EntityId,
+    },
+}
+
+impl std::fmt::Display for ResolutionOutcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Created { entity_id } => write!(f, "record created entity {entity_id}"),
+            Self::Merged { entity_id } => write!(f, "record merged into entity {entity_id}"),
+            Self::RecordRemoved => write!(f, "record removed"),
+            Self::EntityRemoved { entity_id } => write!(f, "entity {entity_id} removed"),
+            Self::SplitTriggered { entity_id } => write!(f, "entity {entity_id} split"),
+        }
+    }
+}
+
+/// Outcome of a record operation, from [`WithInfoResult::summarize`].
+///
+/// Displays as a readable sentence, e.g. "TEST:1001: record merged into
+/// entity 123, entity 456 removed".
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ResolutionSummary {
+    /// Data source of the record the operation targeted
+    pub data_source: Option<String>,
+    /// ID of the record the operation targeted
+    pub record_id: Option<String>,
+    /// The record's outcome first, then one per other affected entity
+    pub outcomes: Vec<ResolutionOutcome>,
+}
+
+impl std::fmt::Display for ResolutionSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let (Some(data_source), Some(record_id)) = (&self.data_source, &self.record_id) {
+            write!(f, "{data_source}:{record_id}: ")?;
+        }
+        if self.outcomes.is_empty() {
+            return write!(f, "no entities changed");
+        }
+        for (i, outcome) in self.outcomes.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{outcome}")?;
+        }
+        Ok(())
+    }
+}
+
+impl WithInfoResult {
+    /// Parses a `WITH_INFO` response. An empty response (`SZ_NO_INFO`)
+    /// parses as a result with no affected entities.
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::Json` - The document is not valid JSON
+    pub fn from_json(json: &str) -> crate::error::SzResult<Self> {
+        if json.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Returns the IDs in `AFFECTED_ENTITIES`, in order.
+    pub fn affected_entity_ids(&self) -> Vec<EntityId> {
+        self.affected_entities.iter().map(|e| e.entity_id).collect()
+    }
+
+    /// Looks up the record and the affected entities to describe what the
+    /// operation did.
+    ///
+    /// The lookups show the repository as it is now, so call this right
+    /// after the operation; later changes to the same entities are reflected
+    /// in the summary.
+    ///
+    /// # Errors
+    ///
+    /// Any error from `SzEngine::get_entity` other than `SzError::NotFound`.
+    pub fn summarize(
+        &self,
+        engine: &dyn crate::traits::SzEngine,
+    ) -> crate::error::SzResult<ResolutionSummary> {
+        use crate::error::SzError;
+        use crate::flags::SzFlags;
+        use crate::types::EntityRef;
+
+        let mut outcomes = Vec::new();
+        let mut record_entity = None;
+        if let (Some(data_source), Some(record_id)) = (&self.data_source, &self.record_id) {
+            let entity_ref = EntityRef::Record {
+                data_source,
+                record_id,
+            };
+            match engine.get_entity(entity_ref, Some(SzFlags::ENTITY_INCLUDE_RECORD_DATA)) {
+                Ok(json) => {
+                    let entity: ExportedEntity = serde_json::from_str(&json)?;
+                    let entity_id = entity.resolved_entity.entity_id;
+                    record_entity = Some(entity_id);
+                    outcomes.push(if entity.resolved_entity.records.len() > 1 {
+                        ResolutionOutcome::Merged { entity_id }
+                    } else {
+                        ResolutionOutcome::Created { entity_id }
+                    });
+                }
+                Err(SzError::NotFound(_)) => outcomes.push(ResolutionOutcome::RecordRemoved),
+                Err(e) => return Err(e),
+            }
+        }
+
+        for entity_id in self.affected_entity_ids() {
+            if Some(entity_id) == record_entity {
+                continue;
+            }
+            match engine.get_entity(EntityRef::Id(entity_id), Some(SzFlags::empty())) {
+                Ok(_) => outcomes.push(ResolutionOutcome::SplitTriggered { entity_id }),
+                Err(SzError::NotFound(_)) => {
+                    outcomes.push(ResolutionOutcome::EntityRemoved { entity_id })
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(ResolutionSummary {
+            data_source: self.data_source.clone(),
+            record_id: self.record_id.clone(),
+            outcomes,
+        })
+    }
+}
+
+/// A `find_path_by_entity_id` / `find_path_by_record_id` response.
+///
+/// Each path is resolved into hops with the match key of the relationship
+/// they follow. Hop details come from `ENTITY_PATH_LINKS` (returned with
+/// `SzFlags::FIND_PATH_INCLUDE_MATCHING_INFO`, part of the default flags) or,
+/// failing that, from the `RELATED_ENTITIES` of the path's entities.
+///
+/// # Example
+///
+/// ```
+/// use sz_rust_sdk::models::FindPathResponse;
+/// use sz_rust_sdk::RelationshipKind;
+///
+/// let response = FindPathResponse::from_json(r#"{
+///     "ENTITY_PATHS":[{"START_ENTITY_ID":1,"END_ENTITY_ID":3,"ENTITIES":[1,2,3]}],
+///     "ENTITY_PATH_LINKS":[
+///         {"MIN_ENTITY_ID":1,"MAX_ENTITY_ID":2,"MATCH_LEVEL_CODE":"POSSIBLY_RELATED","MATCH_KEY":"+PHONE"},
+///         {"MIN_ENTITY_ID":2,"MAX_ENTITY_ID":3,"MATCH_LEVEL_CODE":"POSSIBLY_SAME","MATCH_KEY":"+NAME+DOB"}]}"#)?;
+/// let path = &response.paths[0];
+/// assert_eq!(path.degrees(), 2);
+/// assert_eq!(path.hops[0].match_key.as_deref(), Some("+PHONE"));
+/// assert!(path.only_through(RelationshipKind::POSSIBLY_RELATED | RelationshipKind::POSSIBLY_SAME));
+/// # Ok::<(), sz_rust_sdk::SzError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FindPathResponse {
+    /// Paths found, in the order the engine returned them
+    pub paths: Vec<EntityPath>,
+    /// Details of the entities on the paths, when entity flags are requested
+    pub entities: Vec<ExportedEntity>,
+}
+
+/// One path between two entities.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntityPath {
+    /// Entity the path starts at
+    pub start_entity_id: EntityId,
+    /// Entity the path ends at
+    pub end_entity_id: EntityId,
+    /// Entities on the path, start and end included; empty when no path was
+    /// found
+    pub entity_ids: Vec<EntityId>,
+    /// One hop per consecutive pair of `entity_ids`
+    pub hops: Vec<PathHop>,
+}
+
+/// A relationship between two consecutive entities on a path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathHop {
+    /// Entity the hop leaves
+    pub from: EntityId,
+    /// Entity the hop reaches
+    pub to: EntityId,
+    /// Match key of the relationship (e.g. "+PHONE-DOB")
+    pub match_key: Option<String>,
+    /// Match level code (e.g. "POSSIBLY_RELATED")
+    pub match_level_code: Option<String>,
+    /// Resolution rule that related the entities
+    pub errule_code: Option<String>,
+    /// Whether the relationship was disclosed rather than derived
+    pub is_disclosed: bool,
+    /// Whether the relationship is ambiguous
+    pub is_ambiguous: bool,
+}
+
+impl PathHop {
+    /// The kind of relationship the hop follows; empty for an unknown match
+    /// level.
+    pub fn relationship(&self) -> RelationshipKind {
+        RelationshipKind::classify(self.match_level_code.as_deref(), self.is_disclosed)
+    }
+}
+
+/// Ranking weight: lower is stronger evidence.
+fn relationship_weight(kind: RelationshipKind) -> u32 {
+    if kind == RelationshipKind::DISCLOSED {
+        1
+    } else if kind == RelationshipKind::POSSIBLY_SAME {
+        2
+    } else if kind == RelationshipKind::POSSIBLY_RELATED {
+        3
+    } else if kind == RelationshipKind::NAME_ONLY {
+        4
+    } else {
+        5
+    }
+}
+
+impl EntityPath {
+    /// Whether a path was found.
+    pub fn is_found(&self) -> bool {
+        !self.entity_ids.is_empty()
+    }
+
+    /// Number of hops on the path.
+    pub fn degrees(&self) -> usize {
+        self.hops.len()
+    }
+
+    /// Sum of the hop weights: disclosed 1, possibly same 2, possibly
+    /// related 3, name only 4, other 5. Lower is a stronger path.
+    pub fn cost(&self) -> u32 {
+        self.hops
+            .iter()
+            .map(|hop| relationship_weight(hop.relationship()))
+            .sum()
+    }
+
+    /// Whether every hop follows one of `kinds`.
+    pub fn only_through(&self, kinds: RelationshipKind) -> bool {
+        self.hops.iter().all(|hop| {
+            let kind = hop.relationship();
+            !kind.is_empty() && kinds.contains(kind)
+        })
+    }
+
+    /// The hops as `(from, to, match_key)` edges, for loading into a graph
+    /// library.
+    pub fn edges(&self) -> impl Iterator<Item = (EntityId, EntityId, Option<&str>)> {
+        self.hops
+            .iter()
+            .map(|hop| (hop.from, hop.to, hop.match_key.as_deref()))
+    }
+}
+
+impl FindPathResponse {
+    /// Parses a find-path response.
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::Json` - The document is not valid JSON
+    pub fn from_json(json: &str) -> crate::error::SzResult<Self> {
+        let raw: RawFindPath = serde_json::from_str(json)?;
+        let paths = raw
+            .entity_paths
+            .into_iter()
+            .map(|path| {
+                let hops = path
+                    .entities
+                    .windows(2)
+                    .map(|pair| {
+                        hop_between(pair[0], pair[1], &raw.entity_path_links, &raw.entities)
+                    })
+                    .collect();
+                EntityPath {
+                    start_entity_id: path.start_entity_id,
+                    end_entity_id: path.end_entity_id,
+                    entity_ids: path.entities,
+                    hops,
+                }
+            })
+            .collect();
+        Ok(Self {
+            paths,
+            entities: raw.entities,
+        })
+    }
+
+    /// Found paths, ranked strongest first: fewest hops, then lowest
+    /// [`cost`](EntityPath::cost).
+    pub fn ranked(&self) -> Vec<&EntityPath> {
+        let mut paths: Vec<_> = self.paths.iter().filter(|p| p.is_found()).collect();
+        paths.sort_by_key(|path| (path.degrees(), path.cost()));
+        paths
+    }
+
+    /// Found paths whose every hop follows one of `kinds`.
+    pub fn paths_through(&self, kinds: RelationshipKind) -> Vec<&EntityPath> {
+        self.paths
+            .iter()
+            .filter(|path| path.is_found() && path.only_through(kinds))
+            .collect()
+    }
+}
+
+fn hop_between(
+    from: EntityId,
+    to: EntityId,
+    links: &[RawPathLink],
+    entities: &[ExportedEntity],
+) -> PathHop {
+    let (min, max) = (from.min(to), from.max(to));
+    if let Some(link) = links
+        .iter()
+        .find(|link| link.min_entity_id == min && link.max_entity_id == max)
+    {
+        return PathHop {
+            from,
+            to,
+            match_key: link.match_key.clone(),
+            match_level_code: link.match_level_code.clone(),
+            errule_code: link.errule_code.clone(),
+            is_disclosed: link.is_disclosed.unwrap_or(0) != 0,
+            is_ambiguous: link.is_ambiguous.unwrap_or(0) != 0,
+        };
+    }
+    let related = entities
+        .iter()
+        .find(|entity| entity.resolved_entity.entity_id == from)
+        .and_then(|entity| entity.related_entities.iter().find(|r| r.entity_id == to));
+    PathHop {
+        from,
+        to,
+        match_key: related.and_then(|r| r.match_key.clone()),
+        match_level_code: related.and_then(|r| r.match_level_code.clone()),
+        errule_code: None,
+        is_disclosed: related.and_then(|r| r.is_disclosed).unwrap_or(0) != 0,
+        is_ambiguous: related.and_then(|r| r.is_ambiguous).unwrap_or(0) != 0,
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+struct RawFindPath {
+    #[serde(default)]
+    entity_paths: Vec<RawEntityPath>,
+    #[serde(default)]
+    entity_path_links: Vec<RawPathLink>,
+    #[serde(default)]
+    entities: Vec<ExportedEntity>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+struct RawEntityPath {
+    start_entity_id: EntityId,
+    end_entity_id: EntityId,
+    #[serde(default)]
+    entities: Vec<EntityId>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+struct RawPathLink {
+    min_entity_id: EntityId,
+    max_entity_id: EntityId,
+    #[serde(default)]
+    match_key: Option<String>,
+    #[serde(default)]
+    match_level_code: Option<String>,
+    #[serde(default, rename = "ERRULE_CODE")]
+    errule_code: Option<String>,
+    #[serde(default)]
+    is_disclosed: Option<i64>,
+    #[serde(default)]
+    is_ambiguous: Option<i64>,
+}
+
+pub mod borrowed {
+    //! Borrowed versions of the entity models.
+    //!
+    //! String fields are `Cow<'a, str>` borrowed from the response buffer where
+    //! possible, which avoids an allocation per field when processing large
+    //! volumes of responses. Parse them with
+    //! [`SzBytes::parse_in_place`](crate::SzBytes::parse_in_place), and call
+    //! `into_owned` to keep a value beyond the buffer's lifetime.
+
+    use crate::redact;
+    use crate::types::EntityId;
+    use serde::Deserialize;
+    use std::borrow::Cow;
+    use std::fmt;
+
+    /// Borrowed form of [`super::ExportedEntity`].
+    #[derive(Debug, Clone, PartialEq, Deserialize)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    pub struct ExportedEntity<'a> {
+        /// The resolved entity itself
+        #[serde(borrow)]
+        pub resolved_entity: ResolvedEntity<'a>,
+        /// Entities related to this one
+        #[serde(default, borrow)]
+        pub related_entities: Vec<RelatedEntity<'a>>,
+    }
+
+    /// Borrowed form of [`super::ResolvedEntity`].
+    #[derive(Clone, PartialEq, Deserialize)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    pub struct ResolvedEntity<'a> {
+        /// Engine-assigned entity identifier
+        pub entity_id: EntityId,
+        /// Best name for the entity
+        #[serde(default, borrow)]
+        pub entity_name: Option<Cow<'a, str>>,
+        /// Records resolved into this entity
+        #[serde(default, borrow)]
+        pub records: Vec<EntityRecord<'a>>,
+    }
+
+    impl fmt::Debug for ResolvedEntity<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("ResolvedEntity")
+                .field("entity_id", &self.entity_id)
+                .field("entity_name", &redact::option(&self.entity_name))
+                .field("records", &self.records)
+                .finish()
+        }
+    }
+
+    /// Borrowed form of [`super::EntityRecord`].
+    #[derive(Debug, Clone, PartialEq, Deserialize)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    pub struct EntityRecord<'a> {
+        /// Data source code of the record
+        #[serde(borrow)]
+        pub data_source: Cow<'a, str>,
+        /// Record identifier within the data source
+        #[serde(borrow)]
+        pub record_id: Cow<'a, str>,
+        /// Match key that brought the record into the entity
+        #[serde(default, borrow)]
+        pub match_key: Option<Cow<'a, str>>,
+        /// Resolution rule code that matched the record
+        #[serde(default, borrow, rename = "ERRULE_CODE")]
+        pub errule_code: Option<Cow<'a, str>>,
+    }
+
+    /// Borrowed form of [`super::RelatedEntity`].
+    #[derive(Clone, PartialEq, Deserialize)]
+    #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+    pub struct RelatedEntity<'a> {
+        /// Identifier of the related entity
+        pub entity_id: EntityId,
+        /// Best name for the related entity
+        #[serde(default, borrow)]
+        pub entity_name: Option<Cow<'a, str>>,
+        /// Match level of the relationship
+        #[serde(default)]
+        pub match_level: Option<i64>,
+        /// Match level code
+        #[serde(default, borrow)]
+        pub match_level_code: Option<Cow<'a, str>>,
+        /// Match key describing why the entities are related
+        #[serde(default, borrow)]
+        pub match_key: Option<Cow<'a, str>>,
+        /// Whether the relationship was disclosed
+        #[serde(default)]
+        pub is_disclosed: Option<i64>,
+        /// Whether the relationship is ambiguous
+        #[serde(default)]
+        pub is_ambiguous: Option<i64>,
+    }
+
+    impl fmt::Debug for RelatedEntity<'_> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("RelatedEntity")
+                .field("entity_id", &self.entity_id)
+                .field("entity_name", &redact::option(&self.entity_name))
+                .field("match_level", &self.match_level)
+                .field("match_level_code", &self.match_level_code)
+                .field("match_key", &self.match_key)
+                .field("is_disclosed", &self.is_disclosed)
+                .field("is_ambiguous", &self.is_ambiguous)
+                .finish()
+        }
+    }
+
+    fn owned(value: Option<Cow<'_, str>>) -> Option<String> {
+        value.map(Cow::into_owned)
+    }
+
+    impl ExportedEntity<'_> {
+        /// Converts into the owned model.
+        pub fn into_owned(self) -> super::ExportedEntity {
+            super::ExportedEntity {
+                resolved_entity: self.resolved_entity.into_owned(),
+                related_entities: self
+                    .related_entities
+                    .into_iter()
+                    .map(RelatedEntity::into_owned)
+                    .collect(),
+            }
+        }
+    }
+
+    impl ResolvedEntity<'_> {
+        /// Converts into the owned model.
+        pub fn into_owned(self) -> super::ResolvedEntity {
+            super::ResolvedEntity {
+                entity_id: self.entity_id,
+                entity_name: owned(self.entity_name),
+                records: self
+                    .records
+                    .into_iter()
+                    .map(EntityRecord::into_owned)
+                    .collect(),
+            }
+        }
+    }
+
+    impl EntityRecord<'_> {
+        /// Converts into the owned model.
+        pub fn into_owned(self) -> super::EntityRecord {
+            super::EntityRecord {
+                data_source: self.data_source.into_owned(),
+                record_id: self.record_id.into_owned(),
+                match_key: owned(self.match_key),
+                errule_code: owned(self.errule_code),
+            }
+        }
+    }
+
+    impl RelatedEntity<'_> {
+        /// Converts into the owned model.
+        pub fn into_owned(self) -> super::RelatedEntity {
+            super::RelatedEntity {
+                entity_id: self.entity_id,
+                entity_name: owned(self.entity_name),
+                match_level: self.match_level,
+                match_level_code: owned(self.match_level_code),
+                match_key: owned(self.match_key),
+                is_disclosed: self.is_disclosed,
+                is_ambiguous: self.is_ambiguous,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exported_entity_full() {
+        let line = r#"{
+            "RESOLVED_ENTITY": {
+                "ENTITY_ID": 7,
+                "ENTITY_NAME": "Robert Smith",
+                "FEATURES": {},
+                "RECORDS": [
+                    {"DATA_SOURCE": "CUSTOMERS", "RECORD_ID": "1001",
+                     "MATCH_KEY": "", "ERRULE_CODE": ""},
+                    {"DATA_SOURCE": "CUSTOMERS", "RECORD_ID": "1002",
+                     "MATCH_KEY": "+NAME+ADDRESS", "ERRULE_CODE": "CNAME_CFF"}
+                ]
+            },
+            "RELATED_ENTITIES": [
+                {"ENTITY_ID": 9, "MATCH_LEVEL": 3, "MATCH_LEVEL_CODE": "POSSIBLY_RELATED",
+                 "MATCH_KEY": "+ADDRESS", "IS_DISCLOSED": 0, "IS_AMBIGUOUS": 0}
+            ]
+        }"#;
+
+        let entity: ExportedEntity = serde_json::from_str(line).unwrap();
+        assert_eq!(entity.resolved_entity.entity_id, 7);
+        assert_eq!(
+            entity.resolved_entity.entity_name.as_deref(),
+            Some("Robert Smith")
+        );
+        assert_eq!(entity.resolved_entity.records.len(), 2);
+        assert_eq!(
+            entity.resolved_entity.records[1].errule_code.as_deref(),
+            Some("CNAME_CFF")
+        );
+        assert_eq!(entity.related_entities[0].entity_id, 9);
+        assert_eq!(entity.related_entities[0].match_level, Some(3));
+    }
+
+    #[test]
+    fn test_borrowed_into_owned() {
+        let line = r#"{"RESOLVED_ENTITY":{"ENTITY_ID":3,"ENTITY_NAME":"Ann",
+            "RECORDS":[{"DATA_SOURCE":"TEST","RECORD_ID":"1"}]}}"#;
+        let borrowed: borrowed::ExportedEntity<'_> = serde_json::from_str(line).unwrap();
+        let owned: ExportedEntity = serde_json::from_str(line).unwrap();
+        assert_eq!(borrowed.into_owned(), owned);
+    }
+
+    #[test]
+    fn test_engine_stats_delta() {
+        let earlier = EngineStats::from_json(
+            r#"{"workload":{"apiVersion":"4.0.0","loadedRecords":10,"libFeatCacheHit":8,
+                "libFeatCacheMiss":2,"scoredPairs":[{"NAME":1}]}}"#,
+        )
+        .unwrap();
+        assert_eq!(earlier.workload.api_version.as_deref(), Some("4.0.0"));
+        assert_eq!(
+            earlier.workload.counters.lib_feat_cache_hit_ratio(),
+            Some(0.8)
+        );
+
+        let later = EngineStats::from_json(
+            r#"{"workload":{"loadedRecords":40,"libFeatCacheHit":5,"libFeatCacheMiss":5}}"#,
+        )
+        .unwrap();
+        let delta = later.delta(&earlier);
+        assert_eq!(delta.loaded_records, 30);
+        // Counters that went down were reset; the current value is the delta.
+        assert_eq!(delta.lib_feat_cache_hit, 5);
+        assert_eq!(delta.lib_feat_cache_miss, 3);
+        assert_eq!(
+            WorkloadCounters::rate(delta.loaded_records, std::time::Duration::from_secs(3)),
+            10.0
+        );
+        assert_eq!(delta.res_feat_stat_cache_hit_ratio(), None);
+    }
+
+    #[test]
+    fn test_redo_record_reasons() {
+        let redo = RedoRecord::from_json(
+            r#"{"REASON":"deferred delete","DATA_SOURCE":"TEST","RECORD_ID":7,
+                "ENTITY_CORRUPTION_TRANSIENT":0}"#,
+        )
+        .unwrap();
+        assert_eq!(redo.reason, RedoReason::DeferredDelete);
+        assert_eq!(redo.record_key(), Some(("TEST", "7")));
+        assert!(!redo.entity_corruption_transient);
+        assert!(!redo.requires_reevaluation());
+
+        let redo = RedoRecord::from_json(r#"{"REASON":"Something new","ENTITY_ID":"42"}"#).unwrap();
+        assert_eq!(redo.reason, RedoReason::Other("Something new".to_string()));
+        assert_eq!(redo.record_key(), None);
+        assert_eq!(
+            redo.entity_ref(),
+            Some(crate::types::EntityRef::Id(EntityId(42)))
+        );
+        assert!(redo.requires_reevaluation());
+
+        let redo =
+            RedoRecord::from_json(r#"{"REASON":"LIB_FEAT_ID[x] of FTYPE_ID[9] went generic"}"#)
+                .unwrap();
+        assert_eq!(
+            redo.reason,
+            RedoReason::FeatureWentGeneric {
+                lib_feat_id: None,
+                ftype_id: Some(9)
+            }
+        );
+    }
+
+    #[test]
+    fn test_redo_record_umf() {
+        let json = r#"{"UMF_PROC":{"NAME":"VALIDATE","PARAMS":[
+            {"PARAM":{"NAME":"DATA_SOURCE","VALUE":"TEST"}},
+            {"PARAM":{"NAME":"RECORD_ID","VALUE":"1001"}},
+            {"PARAM":{"NAME":"ENTITY_ID","VALUE":"5"}}]}}"#;
+        let redo = RedoRecord::from_json(json).unwrap();
+        assert_eq!(redo.reason, RedoReason::Umf("VALIDATE".to_string()));
+        assert_eq!(redo.record_key(), Some(("TEST", "1001")));
+        assert_eq!(redo.entity_id, Some(EntityId(5)));
+        assert_eq!(redo.json, json);
+
+        assert!(RedoRecord::from_json("").is_err());
+        assert!(RedoRecord::from_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_exported_entity_minimal() {
+        let entity: ExportedEntity =
+            serde_json::from_str(r#"{"RESOLVED_ENTITY":{"ENTITY_ID":1}}"#).unwrap();
+        assert_eq!(entity.resolved_entity.entity_id, 1);
+        assert!(entity.resolved_entity.records.is_empty());
+        assert!(entity.related_entities.is_empty());
+    }
+
+    #[test]
+    fn test_resolution_summary_display() {
+        let summary = ResolutionSummary {
+            data_source: Some("TEST".to_string()),
+            record_id: Some("1001".to_string()),
+            outcomes: vec![
+                ResolutionOutcome::Merged {
+                    entity_id: EntityId(123),
+                },
+                ResolutionOutcome::EntityRemoved {
+                    entity_id: EntityId(456),
+                },
+                ResolutionOutcome::SplitTriggered {
+                    entity_id: EntityId(789),
+                },
+            ],
+        };
+        assert_eq!(
+            summary.to_string(),
+            "TEST:1001: record merged into entity 123, entity 456 removed, entity 789 split"
+        );
+        assert_eq!(
+            ResolutionSummary::default().to_string(),
+            "no entities changed"
+        );
+    }
+
+    #[test]
+    fn test_with_info_result_parse() {
+        let info = WithInfoResult::from_json(
+            r#"{"DATA_SOURCE":"TEST","RECORD_ID":"1","AFFECTED_ENTITIES":[{"ENTITY_ID":3},{"ENTITY_ID":4}],"INTERESTING_ENTITIES":{"ENTITIES":[]}}"#,
+        )
+        .unwrap();
+        assert_eq!(info.data_source.as_deref(), Some("TEST"));
+        assert_eq!(info.affected_entity_ids(), vec![3, 4]);
+        assert_eq!(
+            WithInfoResult::from_json("  ").unwrap(),
+            WithInfoResult::default()
+        );
+        assert!(WithInfoResult::from_json("{").is_err());
+    }
+
+    const TWO_PATHS: &str = r#"{
+        "ENTITY_PATHS":[
+            {"START_ENTITY_ID":1,"END_ENTITY_ID":4,"ENTITIES":[1,2,3,4]},
+            {"START_ENTITY_ID":1,"END_ENTITY_ID":4,"ENTITIES":[1,5,4]},
+            {"START_ENTITY_ID":1,"END_ENTITY_ID":9,"ENTITIES":[]}],
+        "ENTITY_PATH_LINKS":[
+            {"MIN_ENTITY_ID":1,"MAX_ENTITY_ID":2,"MATCH_LEVEL_CODE":"POSSIBLY_SAME","MATCH_KEY":"+NAME+DOB"},
+            {"MIN_ENTITY_ID":2,"MAX_ENTITY_ID":3,"MATCH_LEVEL_CODE":"POSSIBLY_SAME","MATCH_KEY":"+NAME+DOB"},
+            {"MIN_ENTITY_ID":3,"MAX_ENTITY_ID":4,"MATCH_LEVEL_CODE":"POSSIBLY_SAME","MATCH_KEY":"+NAME+DOB"},
+            {"MIN_ENTITY_ID":1,"MAX_ENTITY_ID":5,"MATCH_LEVEL_CODE":"POSSIBLY_RELATED","MATCH_KEY":"+PHONE"},
+            {"MIN_ENTITY_ID":4,"MAX_ENTITY_ID":5,"MATCH_LEVEL_CODE":"DISCLOSED","MATCH_KEY":"+REL_POINTER(EMPLOYER:)","IS_DISCLOSED":1}]}"#;
+
+    #[test]
+    fn test_find_path_hops_and_ranking() {
+        let response = FindPathResponse::from_json(TWO_PATHS).unwrap();
+        assert_eq!(response.paths.len(), 3);
+        assert!(!response.paths[2].is_found());
+
+        let long = &response.paths[0];
+        assert_eq!(long.degrees(), 3);
+        assert_eq!(long.cost(), 6);
+        let short = &response.paths[1];
+        // Links are keyed by min/max, so reversed hops still resolve
+        assert_eq!(short.hops[1].from, 5);
+        assert_eq!(short.hops[1].to, 4);
+        assert_eq!(short.hops[1].relationship(), RelationshipKind::DISCLOSED);
+        assert_eq!(short.cost(), 4);
+
+        let ranked = response.ranked();
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].entity_ids, vec![1, 5, 4]);
+
+        let strong = response.paths_through(RelationshipKind::POSSIBLY_SAME);
+        assert_eq!(strong.len(), 1);
+        assert_eq!(strong[0].entity_ids, vec![1, 2, 3, 4]);
+
+        let edges: Vec<_> = short.edges().collect();
+        assert_eq!(edges[0], (EntityId(1), EntityId(5), Some("+PHONE")));
+    }
+
+    #[test]
+    fn test_search_response_filters() {
+        let response: SearchResponse = serde_json::from_str(
+            r#"{"RESOLVED_ENTITIES":[
+                {"MATCH_INFO":{"MATCH_LEVEL_CODE":"NAME_ONLY","FEATURE_SCORES":{
+                    "NAME":[{"SCORE":70},{"SCORE":92}]}},
+                 "ENTITY":{"RESOLVED_ENTITY":{"ENTITY_ID":1}}},
+                {"MATCH_INFO":{"MATCH_LEVEL_CODE":"POSSIBLY_SAME","FEATURE_SCORES":{
+                    "NAME":[{"SCORE":85}],"DOB":[{"SCORE":100}]}},
+                 "ENTITY":{"RESOLVED_ENTITY":{"ENTITY_ID":2}}},
+                {"MATCH_INFO":{"MATCH_LEVEL_CODE":"RESOLVED"},
+                 "ENTITY":{"RESOLVED_ENTITY":{"ENTITY_ID":3}}}]}"#,
+        )
+        .unwrap();
+        let ids = |results: Vec<&SearchResult>| -> Vec<EntityId> {
+            results
+                .iter()
+                .map(|r| r.entity.resolved_entity.entity_id)
+                .collect()
+        };
+
+        assert_eq!(response.resolved_entities[0].best_score("NAME"), Some(92));
+        assert_eq!(response.resolved_entities[2].best_score("NAME"), None);
+        assert_eq!(
+            ids(response.filter_by_match_level(MatchLevel::RESOLVED | MatchLevel::POSSIBLY_SAME)),
+            vec![2, 3]
+        );
+        assert_eq!(ids(response.filter_by_score("NAME", 90)), vec![1]);
+        assert_eq!(ids(response.filter_by_score("DOB", 90)), vec![2]);
+        assert_eq!(ids(response.ranked()), vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn test_find_path_hops_from_related_entities() {
+        let response = FindPathResponse::from_json(
+            r#"{"ENTITY_PATHS":[{"START_ENTITY_ID":1,"END_ENTITY_ID":2,"ENTITIES":[1,2]}],
+            "ENTITIES":[{"RESOLVED_ENTITY":{"ENTITY_ID":1},"RELATED_ENTITIES":[
+                {"ENTITY_ID":2,"MATCH_LEVEL_CODE":"NAME_ONLY","MATCH_KEY":"+NAME"}]}]}"#,
+        )
+        .unwrap();
+        let hop = &response.paths[0].hops[0];
+        assert_eq!(hop.match_key.as_deref(), Some("+NAME"));
+        assert_eq!(hop.relationship(), RelationshipKind::NAME_ONLY);
+        assert_eq!(response.entities.len(), 1);
+    }
+}
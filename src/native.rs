@@ -0,0 +1,519 @@
+//! Native library discovery and installation checks.
+//!
+//! [`probe`] inspects the local Senzing installation without loading the
+//! native library: where it is installed, which version it is, and whether
+//! the `PIPELINE` paths an environment would be initialized with exist. It is
+//! meant to run before `SzEnvironmentCore` is created, so misconfiguration is
+//! reported as a list of findings instead of an opaque `Sz_init` failure.
+//!
+//! # Example
+//!
+//! ```
+//! use sz_rust_sdk::native;
+//!
+//! let report = native::probe();
+//! match &report.version {
+//!     Some(version) => println!("Senzing {} (build {})", version.version, version.build_number),
+//!     None => println!("Senzing version unknown"),
+//! }
+//! for problem in &report.problems {
+//!     eprintln!("{problem}");
+//! }
+//! ```
+
+use crate::error::SzResult;
+use serde::Deserialize;
+use std::ffi::OsString;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// Homebrew installation roots, official cask first, then the legacy tap.
+const HOMEBREW_ROOTS: [&str; 4] = [
+    "/opt/homebrew/opt/senzing",
+    "/usr/local/opt/senzing",
+    "/opt/homebrew/opt/senzing/runtime",
+    "/usr/local/opt/senzing/runtime",
+];
+
+/// Linux (and other Unix) installation root.
+const UNIX_ROOT: &str = "/opt/senzing";
+
+/// Linux configuration directory, used as `CONFIGPATH` when it exists.
+const UNIX_CONFIG_DIR: &str = "/etc/opt/senzing";
+
+/// Used when `ProgramFiles` is not set.
+const WINDOWS_PROGRAM_FILES: &str = "C:\\Program Files";
+
+/// The `PIPELINE` paths of the Senzing engine configuration.
+///
+/// See <https://www.senzing.com/docs/tutorials/senzing_engine_config/>.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipelinePaths {
+    /// `CONFIGPATH`
+    pub config_path: String,
+    /// `RESOURCEPATH`
+    pub resource_path: String,
+    /// `SUPPORTPATH`
+    pub support_path: String,
+}
+
+impl PipelinePaths {
+    /// Reads the `PIPELINE` section of engine settings JSON.
+    ///
+    /// Missing entries are returned as empty strings.
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::Json` - `settings` is not valid JSON
+    pub fn from_settings(settings: &str) -> SzResult<Self> {
+        let settings: serde_json::Value = serde_json::from_str(settings)?;
+        let path = |key: &str| {
+            settings["PIPELINE"][key]
+                .as_str()
+                .unwrap_or_default()
+                .to_string()
+        };
+        Ok(Self {
+            config_path: path("CONFIGPATH"),
+            resource_path: path("RESOURCEPATH"),
+            support_path: path("SUPPORTPATH"),
+        })
+    }
+}
+
+/// Version information of a Senzing installation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct NativeVersion {
+    /// Product version, e.g. `4.0.0`
+    #[serde(rename = "VERSION", default)]
+    pub version: String,
+    /// API version
+    #[serde(rename = "API_VERSION", default)]
+    pub api_version: String,
+    /// Build number
+    #[serde(rename = "BUILD_NUMBER", default)]
+    pub build_number: String,
+    /// Platform the build targets
+    #[serde(rename = "PLATFORM", default)]
+    pub platform: String,
+}
+
+/// A problem found by [`probe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ProbeProblem {
+    /// The native library was not found in any searched directory
+    LibraryNotFound {
+        /// Directories searched
+        searched: Vec<PathBuf>,
+    },
+    /// The installation has no readable version file
+    VersionUnknown {
+        /// Installation directory
+        install_dir: PathBuf,
+    },
+    /// `CONFIGPATH` does not exist
+    MissingConfigPath(PathBuf),
+    /// `RESOURCEPATH` does not exist or does not contain `templates`
+    InvalidResourcePath(PathBuf),
+    /// `SUPPORTPATH` does not exist
+    MissingSupportPath(PathBuf),
+}
+
+impl fmt::Display for ProbeProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LibraryNotFound { searched } => {
+                let searched: Vec<String> =
+                    searched.iter().map(|d| d.display().to_string()).collect();
+                write!(
+                    f,
+                    "Senzing library not found (searched {}); install Senzing or set SENZING_LIB_PATH",
+                    searched.join(", ")
+                )
+            }
+            Self::VersionUnknown { install_dir } => write!(
+                f,
+                "no szBuildVersion.json in {}; cannot determine the Senzing version",
+                install_dir.display()
+            ),
+            Self::MissingConfigPath(path) => {
+                write!(f, "CONFIGPATH {} does not exist", path.display())
+            }
+            Self::InvalidResourcePath(path) => write!(
+                f,
+                "RESOURCEPATH {} is not a Senzing resources directory (no templates)",
+                path.display()
+            ),
+            Self::MissingSupportPath(path) => write!(
+                f,
+                "SUPPORTPATH {} does not exist; install the Senzing data package",
+                path.display()
+            ),
+        }
+    }
+}
+
+/// Result of [`probe`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProbeReport {
+    /// Installation (`er`) directory, when one was found
+    pub install_dir: Option<PathBuf>,
+    /// Native library file, when found
+    pub library_path: Option<PathBuf>,
+    /// Version of the installation, when known
+    pub version: Option<NativeVersion>,
+    /// The `PIPELINE` paths that were checked
+    pub paths: PipelinePaths,
+    /// Problems found; empty when the installation looks usable
+    pub problems: Vec<ProbeProblem>,
+}
+
+impl ProbeReport {
+    /// Returns `true` when no problems were found.
+    pub fn is_ok(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Probes the installation using auto-detected `PIPELINE` paths.
+///
+/// Uses the same search order as the build script and
+/// [`detect_pipeline_paths`]. Does not load the native library.
+pub fn probe() -> ProbeReport {
+    probe_paths(detect_pipeline_paths())
+}
+
+/// Probes the installation and the `PIPELINE` paths of the given engine
+/// settings, as passed to `SzEnvironmentCore::get_instance`.
+///
+/// # Errors
+///
+/// * `SzError::Json` - `settings` is not valid JSON
+pub fn probe_settings(settings: &str) -> SzResult<ProbeReport> {
+    Ok(probe_paths(PipelinePaths::from_settings(settings)?))
+}
+
+fn probe_paths(paths: PipelinePaths) -> ProbeReport {
+    let mut problems = Vec::new();
+
+    let searched = library_search_dirs();
+    let file_name = library_file_name();
+    let library_path = searched
+        .iter()
+        .map(|dir| dir.join(&file_name))
+        .find(|path| path.is_file());
+    if library_path.is_none() {
+        problems.push(ProbeProblem::LibraryNotFound { searched });
+    }
+
+    let install_dir = library_path
+        .as_deref()
+        .and_then(Path::parent)
+        .and_then(Path::parent)
+        .map(Path::to_path_buf)
+        .or_else(detect_install_dir);
+    let version = install_dir.as_deref().and_then(read_version);
+    if let Some(dir) = &install_dir
+        && version.is_none()
+    {
+        problems.push(ProbeProblem::VersionUnknown {
+            install_dir: dir.clone(),
+        });
+    }
+
+    problems.extend(check_paths(&paths));
+
+    ProbeReport {
+        install_dir,
+        library_path,
+        version,
+        paths,
+        problems,
+    }
+}
+
+/// Checks that the `PIPELINE` paths exist and look like a Senzing install.
+fn check_paths(paths: &PipelinePaths) -> Vec<ProbeProblem> {
+    let mut problems = Vec::new();
+    let config_path = Path::new(&paths.config_path);
+    if !config_path.is_dir() {
+        problems.push(ProbeProblem::MissingConfigPath(config_path.to_path_buf()));
+    }
+    let resource_path = Path::new(&paths.resource_path);
+    if !resource_path.join("templates").is_dir() {
+        problems.push(ProbeProblem::InvalidResourcePath(
+            resource_path.to_path_buf(),
+        ));
+    }
+    let support_path = Path::new(&paths.support_path);
+    if !support_path.is_dir() {
+        problems.push(ProbeProblem::MissingSupportPath(support_path.to_path_buf()));
+    }
+    problems
+}
+
+fn read_version(install_dir: &Path) -> Option<NativeVersion> {
+    let text = std::fs::read_to_string(install_dir.join("szBuildVersion.json")).ok()?;
+    serde_json::from_str(&text).ok()
+}
+
+fn library_file_name() -> String {
+    format!(
+        "{}Sz{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    )
+}
+
+/// Installation roots for the current platform, in priority order. Each root
+/// holds the `er` installation directory and the `data` support directory.
+///
+/// | Platform | Roots |
+/// |----------|-------|
+/// | macOS | Homebrew cask (`/opt/homebrew/opt/senzing`, `/usr/local/opt/senzing`), then the legacy tap (`.../senzing/runtime`) |
+/// | Windows | `%ProgramFiles%\Senzing`, `C:\Senzing`, then Scoop (`%USERPROFILE%\scoop\apps\senzingsdk\current`) |
+/// | Linux and other Unix | `/opt/senzing` |
+///
+/// `SENZING_DIR`, when set, takes precedence over all of them.
+pub fn install_roots() -> Vec<PathBuf> {
+    platform_install_roots(std::env::consts::OS, |name| std::env::var_os(name))
+}
+
+fn platform_install_roots(os: &str, var: impl Fn(&str) -> Option<OsString>) -> Vec<PathBuf> {
+    match os {
+        "macos" => HOMEBREW_ROOTS.iter().map(PathBuf::from).collect(),
+        "windows" => {
+            let program_files = var("ProgramFiles")
+                .map_or_else(|| PathBuf::from(WINDOWS_PROGRAM_FILES), PathBuf::from);
+            let mut roots = vec![program_files.join("Senzing"), PathBuf::from("C:\\Senzing")];
+            if let Some(home) = var("USERPROFILE") {
+                roots.push(PathBuf::from(home).join("scoop\\apps\\senzingsdk\\current"));
+            }
+            roots
+        }
+        _ => vec![PathBuf::from(UNIX_ROOT)],
+    }
+}
+
+/// Subdirectories of an `er` directory that hold the native library. Windows
+/// installs keep `Sz.dll` next to its dependencies in `bin`, with the import
+/// library in `lib`.
+fn library_subdirs(os: &str) -> &'static [&'static str] {
+    if os == "windows" {
+        &["lib", "bin"]
+    } else {
+        &["lib"]
+    }
+}
+
+/// Directories searched for the native library, in priority order:
+/// `SENZING_LIB_PATH` (which may list several directories, separated as in
+/// `PATH`), `SENZING_DIR`, then the [`install_roots`] of the platform. The
+/// same order the build script uses for linking.
+pub fn library_search_dirs() -> Vec<PathBuf> {
+    platform_library_search_dirs(std::env::consts::OS, |name| std::env::var_os(name))
+}
+
+fn platform_library_search_dirs(os: &str, var: impl Fn(&str) -> Option<OsString>) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    if let Some(paths) = var("SENZING_LIB_PATH") {
+        dirs.extend(std::env::split_paths(&paths).filter(|dir| !dir.as_os_str().is_empty()));
+    }
+    let er_dirs = var("SENZING_DIR").map(PathBuf::from).into_iter().chain(
+        platform_install_roots(os, &var)
+            .into_iter()
+            .map(|root| root.join("er")),
+    );
+    for er_dir in er_dirs {
+        for subdir in library_subdirs(os) {
+            dirs.push(er_dir.join(subdir));
+        }
+    }
+    dirs
+}
+
+/// Finds the installation (`er`) directory from its resources.
+fn detect_install_dir() -> Option<PathBuf> {
+    std::env::var_os("SENZING_DIR")
+        .map(PathBuf::from)
+        .into_iter()
+        .chain(install_roots().into_iter().map(|root| root.join("er")))
+        .find(|dir| dir.join("resources").is_dir())
+}
+
+/// Senzing config JSON requires forward slashes even on Windows.
+fn to_forward_slashes(p: &Path) -> String {
+    p.to_string_lossy().replace('\\', "/")
+}
+
+/// `PIPELINE` paths of an installation root laid out as `er` plus `data`.
+fn root_pipeline_paths(root: &Path) -> PipelinePaths {
+    let resources = root.join("er").join("resources");
+    PipelinePaths {
+        config_path: to_forward_slashes(&resources.join("templates")),
+        resource_path: to_forward_slashes(&resources),
+        support_path: to_forward_slashes(&root.join("data")),
+    }
+}
+
+/// Auto-detects the `PIPELINE` paths based on platform.
+///
+/// Checks standard installation locations in priority order:
+/// 1. `SENZING_DIR` (set by Scoop on Windows, or a manual override)
+/// 2. The [`install_roots`] of the platform, first one installed
+///
+/// Without an installation, the first platform root is assumed. On Linux
+/// `/etc/opt/senzing` is used for `CONFIGPATH` when it exists. Paths always
+/// use forward slashes, as the engine configuration requires.
+pub fn detect_pipeline_paths() -> PipelinePaths {
+    if let Ok(senzing_dir) = std::env::var("SENZING_DIR") {
+        let base = Path::new(&senzing_dir);
+        if base.join("resources").exists() {
+            return PipelinePaths {
+                config_path: to_forward_slashes(&base.join("resources").join("templates")),
+                resource_path: to_forward_slashes(&base.join("resources")),
+                support_path: to_forward_slashes(
+                    &base
+                        .parent()
+                        .map_or_else(|| base.join("..").join("data"), |p| p.join("data")),
+                ),
+            };
+        }
+    }
+
+    let roots = install_roots();
+    let root = roots
+        .iter()
+        .find(|root| root.join("er").join("resources").exists())
+        .or(roots.first())
+        .map_or_else(|| PathBuf::from(UNIX_ROOT), PathBuf::clone);
+    let mut paths = root_pipeline_paths(&root);
+    if root == Path::new(UNIX_ROOT) && Path::new(UNIX_CONFIG_DIR).exists() {
+        paths.config_path = UNIX_CONFIG_DIR.to_string();
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipeline_paths_from_settings() {
+        let paths = PipelinePaths::from_settings(
+            r#"{"PIPELINE":{"CONFIGPATH":"/c","RESOURCEPATH":"/r","SUPPORTPATH":"/s"},
+                "SQL":{"CONNECTION":"internal://"}}"#,
+        )
+        .unwrap();
+        assert_eq!(paths.config_path, "/c");
+        assert_eq!(paths.resource_path, "/r");
+        assert_eq!(paths.support_path, "/s");
+        assert_eq!(PipelinePaths::from_settings("{}").unwrap().support_path, "");
+        assert!(PipelinePaths::from_settings("not json").is_err());
+    }
+
+    #[test]
+    fn test_check_paths_reports_each_problem() {
+        let missing = std::env::temp_dir().join("sz-probe-does-not-exist");
+        let missing = missing.to_string_lossy().to_string();
+        let problems = check_paths(&PipelinePaths {
+            config_path: missing.clone(),
+            resource_path: missing.clone(),
+            support_path: missing.clone(),
+        });
+        assert_eq!(
+            problems,
+            vec![
+                ProbeProblem::MissingConfigPath(PathBuf::from(&missing)),
+                ProbeProblem::InvalidResourcePath(PathBuf::from(&missing)),
+                ProbeProblem::MissingSupportPath(PathBuf::from(&missing)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resource_path_needs_templates() {
+        let dir = std::env::temp_dir().join(format!("sz-probe-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("templates")).unwrap();
+        let path = dir.to_string_lossy().to_string();
+        let problems = check_paths(&PipelinePaths {
+            config_path: path.clone(),
+            resource_path: path.clone(),
+            support_path: path,
+        });
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(problems.is_empty(), "{problems:?}");
+    }
+
+    #[test]
+    fn test_read_version() {
+        let dir = std::env::temp_dir().join(format!("sz-probe-version-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("szBuildVersion.json"),
+            r#"{"PLATFORM":"Linux","VERSION":"4.0.0","API_VERSION":"4.0.0","BUILD_NUMBER":"2025_01_01__00_00"}"#,
+        )
+        .unwrap();
+        let version = read_version(&dir);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let version = version.expect("version file should parse");
+        assert_eq!(version.version, "4.0.0");
+        assert_eq!(version.build_number, "2025_01_01__00_00");
+        assert!(read_version(Path::new("/nonexistent")).is_none());
+    }
+
+    #[test]
+    fn test_platform_install_roots() {
+        let no_vars = |_: &str| None;
+        assert_eq!(
+            platform_install_roots("linux", no_vars),
+            vec![PathBuf::from("/opt/senzing")]
+        );
+        assert_eq!(
+            platform_install_roots("macos", no_vars).len(),
+            HOMEBREW_ROOTS.len()
+        );
+
+        let windows = platform_install_roots("windows", |name| match name {
+            "ProgramFiles" => Some(OsString::from("D:\\Apps")),
+            "USERPROFILE" => Some(OsString::from("C:\\Users\\ann")),
+            _ => None,
+        });
+        assert_eq!(windows.len(), 3);
+        assert!(windows[0].starts_with("D:\\Apps"));
+        assert!(windows[2].starts_with("C:\\Users\\ann"));
+        assert_eq!(platform_install_roots("windows", no_vars).len(), 2);
+    }
+
+    #[test]
+    fn test_library_search_dirs_order() {
+        let lib_path = std::env::join_paths(["/first", "/second"]).unwrap();
+        let dirs = platform_library_search_dirs("linux", |name| match name {
+            "SENZING_LIB_PATH" => Some(lib_path.clone()),
+            "SENZING_DIR" => Some(OsString::from("/custom/er")),
+            _ => None,
+        });
+        assert_eq!(
+            dirs,
+            vec![
+                PathBuf::from("/first"),
+                PathBuf::from("/second"),
+                PathBuf::from("/custom/er/lib"),
+                PathBuf::from("/opt/senzing/er/lib"),
+            ]
+        );
+
+        let windows = platform_library_search_dirs("windows", |_| None);
+        assert!(windows.iter().any(|dir| dir.ends_with("bin")));
+        assert!(windows.iter().all(|dir| !dir.starts_with("/opt/senzing")));
+    }
+
+    #[test]
+    fn test_root_pipeline_paths_use_forward_slashes() {
+        let paths = root_pipeline_paths(Path::new("C:\\Senzing"));
+        assert!(!paths.resource_path.contains('\\'));
+        assert!(paths.config_path.ends_with("er/resources/templates"));
+        assert!(paths.support_path.ends_with("/data"));
+    }
+}
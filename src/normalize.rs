@@ -0,0 +1,294 @@
+//! Pre-cleaning of record attributes before loading.
+//!
+//! The engine standardizes most features itself, but sources often carry
+//! stray whitespace, decomposed Unicode, and phone numbers and dates in
+//! local formats. A [`Normalizer`] cleans attribute values with [`Rule`]s
+//! configured per attribute:
+//!
+//! - [`Rule::Trim`] removes surrounding whitespace and collapses inner runs.
+//! - [`Rule::Nfc`] composes Unicode to NFC, so `"e\u{301}"` becomes `"é"`.
+//! - [`Rule::PhoneE164`] formats phone numbers as E.164 (`+15550100`).
+//! - [`Rule::IsoDate`] coerces dates to ISO-8601 (`1980-01-02`).
+//!
+//! An attribute name matches the attribute itself and its usage-prefixed
+//! forms, so rules for `PHONE_NUMBER` also clean `HOME_PHONE_NUMBER`.
+//! Values a rule cannot interpret are left as they are for the engine to
+//! judge. `DATA_SOURCE` and `RECORD_ID` are never changed.
+//!
+//! Loads apply a normalizer to every record with
+//! `ingest::LoadOptions::with_normalizer`.
+//!
+//! # Example
+//!
+//! ```
+//! use sz_rust_sdk::normalize::{DateOrder, Normalizer, Rule};
+//!
+//! let normalizer = Normalizer::standard().with_rule("DATE_OF_BIRTH", Rule::IsoDate(DateOrder::DayFirst));
+//! let record = normalizer.normalize_record(
+//!     r#"{"RECORD_ID":" 7 ","NAME_FULL":"  Ann   Lee ","HOME_PHONE_NUMBER":"(555) 010-0199","DATE_OF_BIRTH":"02.01.1980"}"#,
+//! )?;
+//! assert_eq!(
+//!     record,
+//!     r#"{"DATE_OF_BIRTH":"1980-01-02","HOME_PHONE_NUMBER":"+15550100199","NAME_FULL":"Ann Lee","RECORD_ID":" 7 "}"#
+//! );
+//! # Ok::<(), sz_rust_sdk::error::SzError>(())
+//! ```
+
+use crate::error::SzResult;
+use crate::ingest::parse_object;
+use serde_json::{Map, Value};
+use unicode_normalization::UnicodeNormalization;
+
+/// Attributes no rule changes, because they identify the record.
+const KEY_ATTRIBUTES: [&str; 2] = ["DATA_SOURCE", "RECORD_ID"];
+
+/// Order of day and month in dates that do not start with the year.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DateOrder {
+    /// `MM/DD/YYYY`
+    #[default]
+    MonthFirst,
+    /// `DD/MM/YYYY`
+    DayFirst,
+}
+
+/// A cleaning step applied to an attribute value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rule {
+    /// Remove leading and trailing whitespace and collapse inner whitespace
+    /// to single spaces
+    Trim,
+    /// Compose Unicode to normalization form C
+    Nfc,
+    /// Format as E.164: `+`, country code and number. Numbers written
+    /// without a country code (no leading `+` or `00`) get
+    /// `default_country_code`. Numbers with letters, such as extensions,
+    /// or outside 7 to 15 digits are left as they are.
+    PhoneE164 {
+        /// Country calling code for national numbers, e.g. `"1"`
+        default_country_code: String,
+    },
+    /// Coerce `YYYY-MM-DD`, `YYYY/MM/DD`, `YYYYMMDD` and day/month/year in
+    /// the given order, with any separators, to `YYYY-MM-DD`. `YYYY-MM` and
+    /// `MM/YYYY` become `YYYY-MM`. Years must have four digits; dates with
+    /// month names are left as they are.
+    IsoDate(DateOrder),
+}
+
+impl Rule {
+    /// Applies the rule to `value`.
+    pub fn apply(&self, value: &str) -> String {
+        match self {
+            Rule::Trim => value.split_whitespace().collect::<Vec<_>>().join(" "),
+            Rule::Nfc => value.nfc().collect(),
+            Rule::PhoneE164 {
+                default_country_code,
+            } => phone_e164(value, default_country_code).unwrap_or_else(|| value.to_string()),
+            Rule::IsoDate(order) => iso_date(value, *order).unwrap_or_else(|| value.to_string()),
+        }
+    }
+}
+
+/// Cleans record attributes with rules configured per attribute.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Normalizer {
+    all: Vec<Rule>,
+    attributes: Vec<(String, Rule)>,
+}
+
+impl Normalizer {
+    /// Creates a normalizer without rules.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a normalizer with the usual rules: [`Rule::Trim`] and
+    /// [`Rule::Nfc`] for every attribute, E.164 with country code 1 for
+    /// `PHONE_NUMBER`, and month-first ISO dates for `DATE_OF_BIRTH` and
+    /// `DATE_OF_DEATH`.
+    pub fn standard() -> Self {
+        let phone = Rule::PhoneE164 {
+            default_country_code: "1".to_string(),
+        };
+        Self::new()
+            .with_rule_for_all(Rule::Trim)
+            .with_rule_for_all(Rule::Nfc)
+            .with_rule("PHONE_NUMBER", phone)
+            .with_rule("DATE_OF_BIRTH", Rule::IsoDate(DateOrder::MonthFirst))
+            .with_rule("DATE_OF_DEATH", Rule::IsoDate(DateOrder::MonthFirst))
+    }
+
+    /// Adds a rule applied to every attribute, before the
+    /// attribute-specific rules.
+    pub fn with_rule_for_all(mut self, rule: Rule) -> Self {
+        self.all.push(rule);
+        self
+    }
+
+    /// Adds a rule for `attribute` and its usage-prefixed forms. A rule of
+    /// the same kind added earlier for the same attribute is replaced.
+    pub fn with_rule(mut self, attribute: impl Into<String>, rule: Rule) -> Self {
+        let attribute = attribute.into();
+        self.attributes.retain(|(name, existing)| {
+            *name != attribute || std::mem::discriminant(existing) != std::mem::discriminant(&rule)
+        });
+        self.attributes.push((attribute, rule));
+        self
+    }
+
+    /// Returns `value` cleaned with the rules for `attribute`.
+    pub fn normalize_value(&self, attribute: &str, value: &str) -> String {
+        if KEY_ATTRIBUTES.contains(&attribute) {
+            return value.to_string();
+        }
+        let specific = self
+            .attributes
+            .iter()
+            .filter(|(name, _)| attribute_matches(attribute, name))
+            .map(|(_, rule)| rule);
+        self.all
+            .iter()
+            .chain(specific)
+            .fold(value.to_string(), |value, rule| rule.apply(&value))
+    }
+
+    /// Cleans every string attribute of a parsed record, including
+    /// attributes in nested lists.
+    pub fn normalize_document(&self, document: &mut Map<String, Value>) {
+        for (attribute, value) in document.iter_mut() {
+            match value {
+                Value::String(text) => *text = self.normalize_value(attribute, text),
+                Value::Array(items) => {
+                    for item in items {
+                        if let Value::Object(nested) = item {
+                            self.normalize_document(nested);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Returns `record` with every string attribute cleaned.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the record is not a JSON object.
+    pub fn normalize_record(&self, record: &str) -> SzResult<String> {
+        let mut document = parse_object(record)?;
+        self.normalize_document(&mut document);
+        Ok(Value::Object(document).to_string())
+    }
+}
+
+/// Whether `attribute` is `name` or `name` with a usage prefix, such as
+/// `HOME_PHONE_NUMBER` for `PHONE_NUMBER`.
+pub(crate) fn attribute_matches(attribute: &str, name: &str) -> bool {
+    attribute
+        .strip_suffix(name)
+        .is_some_and(|prefix| prefix.is_empty() || prefix.ends_with('_'))
+}
+
+fn phone_e164(value: &str, default_country_code: &str) -> Option<String> {
+    let value = value.trim();
+    if value.chars().any(char::is_alphabetic) {
+        return None;
+    }
+    let digits: String = value.chars().filter(char::is_ascii_digit).collect();
+    let number = if value.starts_with('+') {
+        digits
+    } else if let Some(international) = digits.strip_prefix("00") {
+        international.to_string()
+    } else {
+        let national = digits.strip_prefix('0').unwrap_or(&digits);
+        format!("{default_country_code}{national}")
+    };
+    (7..=15)
+        .contains(&number.len())
+        .then(|| format!("+{number}"))
+}
+
+fn iso_date(value: &str, order: DateOrder) -> Option<String> {
+    if value.chars().any(char::is_alphabetic) {
+        return None;
+    }
+    let parts: Vec<&str> = value
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .collect();
+    let (year, month, day) = match parts.as_slice() {
+        [compact] if compact.len() == 8 => (&compact[..4], &compact[4..6], Some(&compact[6..])),
+        [year, month] if year.len() == 4 => (*year, *month, None),
+        [month, year] if year.len() == 4 => (*year, *month, None),
+        [year, month, day] if year.len() == 4 => (*year, *month, Some(*day)),
+        [first, second, year] if year.len() == 4 => match order {
+            DateOrder::MonthFirst => (*year, *first, Some(*second)),
+            DateOrder::DayFirst => (*year, *second, Some(*first)),
+        },
+        _ => return None,
+    };
+    let month: u32 = month.parse().ok().filter(|m| (1..=12).contains(m))?;
+    match day {
+        Some(day) => {
+            let day: u32 = day.parse().ok().filter(|d| (1..=31).contains(d))?;
+            Some(format!("{year}-{month:02}-{day:02}"))
+        }
+        None => Some(format!("{year}-{month:02}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rules() {
+        assert_eq!(Rule::Trim.apply("  Ann \t Lee\n"), "Ann Lee");
+        assert_eq!(Rule::Nfc.apply("Jose\u{301}"), "José");
+
+        let phone = Rule::PhoneE164 {
+            default_country_code: "44".to_string(),
+        };
+        assert_eq!(phone.apply("020 7946 0958"), "+442079460958");
+        assert_eq!(phone.apply("+1 (555) 010-0199"), "+15550100199");
+        assert_eq!(phone.apply("0033 1 23 45 67 89"), "+33123456789");
+        assert_eq!(phone.apply("555-0100 ext 12"), "555-0100 ext 12");
+        assert_eq!(phone.apply("12"), "12");
+
+        let month_first = Rule::IsoDate(DateOrder::MonthFirst);
+        let day_first = Rule::IsoDate(DateOrder::DayFirst);
+        assert_eq!(month_first.apply("1980/1/2"), "1980-01-02");
+        assert_eq!(month_first.apply("19800102"), "1980-01-02");
+        assert_eq!(month_first.apply("01/02/1980"), "1980-01-02");
+        assert_eq!(day_first.apply("02.01.1980"), "1980-01-02");
+        assert_eq!(month_first.apply("3/1980"), "1980-03");
+        assert_eq!(month_first.apply("13/02/1980"), "13/02/1980");
+        assert_eq!(month_first.apply("Jan 2 1980"), "Jan 2 1980");
+    }
+
+    #[test]
+    fn test_normalizer_attributes() {
+        assert!(attribute_matches("HOME_PHONE_NUMBER", "PHONE_NUMBER"));
+        assert!(attribute_matches("PHONE_NUMBER", "PHONE_NUMBER"));
+        assert!(!attribute_matches("XPHONE_NUMBER", "PHONE_NUMBER"));
+
+        let normalizer = Normalizer::standard().with_rule(
+            "PHONE_NUMBER",
+            Rule::PhoneE164 {
+                default_country_code: "49".to_string(),
+            },
+        );
+        assert_eq!(
+            normalizer.normalize_value("WORK_PHONE_NUMBER", " 030 1234567 "),
+            "+49301234567"
+        );
+        assert_eq!(normalizer.normalize_value("RECORD_ID", " 1 "), " 1 ");
+
+        let record = normalizer
+            .normalize_record(r#"{"NAMES":[{"NAME_FULL":" Ann  Lee"}],"AGE":41}"#)
+            .unwrap();
+        assert_eq!(record, r#"{"AGE":41,"NAMES":[{"NAME_FULL":"Ann Lee"}]}"#);
+        assert!(normalizer.normalize_record("[]").is_err());
+    }
+}
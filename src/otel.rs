@@ -0,0 +1,206 @@
+//! Distributed trace context for loads.
+//!
+//! With the `otel` feature, one record can be followed as a single
+//! distributed trace: from its source message, through the loader, to the
+//! info messages its load produces. Trace context travels as message
+//! headers in [`TraceHeaders`]. They are W3C `traceparent` and
+//! `tracestate`, or whatever format the propagator installed with
+//! `opentelemetry::global::set_text_map_propagator` uses.
+//!
+//! - Consumers of Kafka messages or HTTP requests read the headers with
+//!   [`TraceHeaders::from_pairs`] and add the record inside
+//!   [`record_span`], a child of the producer's span.
+//! - [`Pipeline::run`](crate::pipeline::Pipeline::run) continues the
+//!   caller's trace. Each record is added in a [`record_span`] under the span
+//!   that was current when the run started, on whichever loader thread takes
+//!   it.
+//! - [`Outbox::append`](crate::outbox::Outbox::append) stores the headers of
+//!   the current span in [`OutboxMessage::headers`]. An `InfoSink` sends
+//!   them as the broker message's headers, so consumers of the info messages
+//!   continue the same trace.
+//! - `web::SzState::blocking` and the `grpc` services run engine calls in
+//!   the handler's span.
+//!
+//! Spans are `tracing` spans. Install a `tracing-opentelemetry` layer to
+//! export them; without one, the current span has no context and no headers
+//! are produced.
+//!
+//! [`OutboxMessage::headers`]: crate::outbox::OutboxMessage::headers
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::otel::{TraceHeaders, record_span};
+//! use sz_rust_sdk::outbox::Outbox;
+//! use sz_rust_sdk::prelude::*;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_otel")?;
+//! let engine = env.get_engine()?;
+//! let outbox = Outbox::open("info.outbox")?;
+//!
+//! // Headers and payload of a consumed Kafka message
+//! let kafka_headers = [(
+//!     "traceparent",
+//!     b"00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".as_slice(),
+//! )];
+//! let record = r#"{"NAME_FULL": "Ann Lee"}"#;
+//!
+//! let headers = TraceHeaders::from_pairs(kafka_headers);
+//! record_span(Some(&headers), "TEST", "1001").in_scope(|| {
+//!     let info = engine.add_record("TEST", "1001", record, Some(SzFlags::WITH_INFO))?;
+//!     outbox.append(&info)
+//! })?;
+//! # Ok::<(), SzError>(())
+//! ```
+
+use opentelemetry::Context;
+use opentelemetry::propagation::{Extractor, Injector};
+use std::collections::BTreeMap;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// Name of the span [`record_span`] creates.
+pub const RECORD_SPAN: &str = "sz_record";
+
+/// Trace context carried as message headers, with lowercase names.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TraceHeaders(BTreeMap<String, String>);
+
+impl TraceHeaders {
+    /// Collects headers from name and value pairs, such as Kafka record
+    /// headers or HTTP request headers. Names are lowercased; values that are
+    /// not UTF-8 are skipped.
+    pub fn from_pairs<K, V>(pairs: impl IntoIterator<Item = (K, V)>) -> Self
+    where
+        K: AsRef<str>,
+        V: AsRef<[u8]>,
+    {
+        Self(
+            pairs
+                .into_iter()
+                .filter_map(|(name, value)| {
+                    let value = std::str::from_utf8(value.as_ref()).ok()?;
+                    Some((name.as_ref().to_ascii_lowercase(), value.to_string()))
+                })
+                .collect(),
+        )
+    }
+
+    /// The headers of `context`, written by the global propagator.
+    pub fn from_context(context: &Context) -> Self {
+        let mut headers = Self::default();
+        opentelemetry::global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(context, &mut headers)
+        });
+        headers
+    }
+
+    /// The headers of the current `tracing` span.
+    pub fn current() -> Self {
+        Self::from_context(&tracing::Span::current().context())
+    }
+
+    /// The context the headers carry, read by the global propagator.
+    pub fn context(&self) -> Context {
+        opentelemetry::global::get_text_map_propagator(|propagator| propagator.extract(self))
+    }
+
+    /// The value of header `name`, compared case-insensitively.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0.get(&name.to_ascii_lowercase()).map(String::as_str)
+    }
+
+    /// Whether there are no headers.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The headers, ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Returns the headers as a map.
+    pub fn into_map(self) -> BTreeMap<String, String> {
+        self.0
+    }
+}
+
+impl From<BTreeMap<String, String>> for TraceHeaders {
+    fn from(headers: BTreeMap<String, String>) -> Self {
+        Self::from_pairs(headers)
+    }
+}
+
+impl Extractor for TraceHeaders {
+    fn get(&self, key: &str) -> Option<&str> {
+        TraceHeaders::get(self, key)
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(String::as_str).collect()
+    }
+}
+
+impl Injector for TraceHeaders {
+    fn set(&mut self, key: &str, value: String) {
+        self.0.insert(key.to_ascii_lowercase(), value);
+    }
+}
+
+/// A [`RECORD_SPAN`] span for loading one record.
+///
+/// Its parent is the context in `headers` when they carry one, and the
+/// current span otherwise. Enter it around the engine calls for the record
+/// and the hand-off of their info to the sinks.
+pub fn record_span(
+    headers: Option<&TraceHeaders>,
+    data_source: &str,
+    record_id: &str,
+) -> tracing::Span {
+    let span = tracing::info_span!(RECORD_SPAN, data_source, record_id);
+    if let Some(headers) = headers.filter(|headers| !headers.is_empty()) {
+        // Fails only without an OpenTelemetry layer, when there is no trace
+        // to join anyway
+        let _ = span.set_parent(headers.context());
+    }
+    span
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trace_headers() {
+        let headers = TraceHeaders::from_pairs([
+            (
+                "TraceParent",
+                b"00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".as_slice(),
+            ),
+            ("tracestate", b"vendor=1".as_slice()),
+            ("binary", [0xff, 0xfe].as_slice()),
+        ]);
+        assert_eq!(
+            headers.get("TRACEPARENT"),
+            Some("00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+        );
+        assert_eq!(headers.get("binary"), None);
+        assert_eq!(Extractor::keys(&headers), vec!["traceparent", "tracestate"]);
+        assert_eq!(TraceHeaders::from(headers.clone().into_map()), headers);
+
+        let mut injected = TraceHeaders::default();
+        Injector::set(&mut injected, "TraceState", "vendor=2".to_string());
+        assert_eq!(
+            injected.iter().collect::<Vec<_>>(),
+            vec![("tracestate", "vendor=2")]
+        );
+
+        // No propagator or layer is installed, so there is no context to
+        // write and a span is created regardless
+        assert!(TraceHeaders::current().is_empty());
+        let _span = record_span(Some(&headers), "TEST", "1");
+    }
+}
@@ -0,0 +1,600 @@
+//! Durable outbox for `WITH_INFO` payloads.
+//!
+//! Publishing each `WITH_INFO` response straight to a message broker ties the
+//! loader's throughput to the broker's availability. With an [`Outbox`], the
+//! loader appends the response to a local file, which is synced before
+//! `append` returns, and an [`OutboxRelay`] delivers the stored messages to an
+//! [`InfoSink`] in order, retrying with backoff while the sink is failing.
+//!
+//! Delivery is at least once: a message delivered just before a crash, but
+//! not yet acknowledged in the store, is delivered again after a restart.
+//! Every message carries a [`dedup_key`](OutboxMessage::dedup_key) that stays
+//! the same across redeliveries, so the receiving side can drop duplicates.
+//!
+//! The store is the outbox file plus a `.ack` file next to it holding the
+//! sequence number of the last delivered message. Delivered messages are
+//! removed from the file once everything has been delivered and the file
+//! has grown past [`COMPACT_THRESHOLD`]. Undelivered messages are also kept
+//! in memory.
+//!
+//! With the `encryption` feature, `Outbox::open_encrypted` seals every
+//! stored message with a `PayloadCipher`, so the `WITH_INFO` payloads are not
+//! kept in plaintext. The `.ack` file only holds a sequence number and is
+//! not encrypted.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use std::sync::Arc;
+//! use std::sync::atomic::AtomicBool;
+//! use sz_rust_sdk::outbox::{InfoSink, Outbox, OutboxMessage, OutboxRelay};
+//! use sz_rust_sdk::prelude::*;
+//!
+//! struct Broker;
+//!
+//! impl InfoSink for Broker {
+//!     fn deliver(&self, message: &OutboxMessage) -> SzResult<()> {
+//!         println!("{} {}", message.dedup_key, message.payload);
+//!         Ok(())
+//!     }
+//! }
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_outbox")?;
+//! let engine = env.get_engine()?;
+//! let outbox = Arc::new(Outbox::open("info.outbox")?);
+//! let relay = OutboxRelay::new(outbox.clone(), Arc::new(Broker));
+//! let stop = AtomicBool::new(false);
+//! std::thread::scope(|scope| {
+//!     scope.spawn(|| relay.run(&stop));
+//!     let info = engine.add_record(
+//!         "TEST",
+//!         "1001",
+//!         r#"{"NAME_FULL": "Ann Lee"}"#,
+//!         Some(SzFlags::WITH_INFO),
+//!     )?;
+//!     outbox.append(&info)?;
+//!     stop.store(true, std::sync::atomic::Ordering::Relaxed);
+//!     Ok::<(), SzError>(())
+//! })?;
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::encryption::PayloadCipher;
+use crate::error::{SzError, SzResult};
+use crate::types::SZ_NO_INFO;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+/// Size in bytes above which a fully delivered outbox file is truncated.
+pub const COMPACT_THRESHOLD: u64 = 1 << 20;
+
+/// Default number of delivery attempts per message in
+/// [`OutboxRelay::relay_pending`].
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 5;
+
+/// Default wait before the first retry; doubled after each failed attempt.
+pub const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Default cap on the wait between retries.
+pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Default wait of [`OutboxRelay::run`] when the outbox is empty.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A stored `WITH_INFO` payload.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OutboxMessage {
+    /// Position in the outbox, starting at 1
+    pub sequence: u64,
+    /// Key that identifies the message across redeliveries
+    pub dedup_key: String,
+    /// The `WITH_INFO` response as returned by the engine
+    pub payload: String,
+    /// Trace context of the load that produced the message, to send as the
+    /// broker message's headers. Only filled with the `otel` feature; see
+    /// [`otel`](crate::otel).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub headers: BTreeMap<String, String>,
+}
+
+/// Destination of outbox messages, e.g. a message broker.
+pub trait InfoSink: Send + Sync {
+    /// Delivers one message. An error leaves the message in the outbox to be
+    /// retried.
+    fn deliver(&self, message: &OutboxMessage) -> SzResult<()>;
+}
+
+/// Durable, append-only store of `WITH_INFO` payloads awaiting delivery.
+pub struct Outbox {
+    path: PathBuf,
+    ack_path: PathBuf,
+    cipher: Option<PayloadCipher>,
+    state: Mutex<State>,
+}
+
+struct State {
+    file: File,
+    pending: VecDeque<OutboxMessage>,
+    next_sequence: u64,
+}
+
+impl Outbox {
+    /// Opens the outbox at `path`, creating it if needed, and loads the
+    /// messages not yet delivered.
+    ///
+    /// A partially written last line, left by a crash during `append`, is
+    /// discarded.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the files cannot be read or written, or a
+    /// complete line is not an outbox message.
+    pub fn open(path: impl AsRef<Path>) -> SzResult<Self> {
+        Self::open_with(path.as_ref(), None)
+    }
+
+    /// Opens the outbox at `path` like [`open`](Self::open), with every
+    /// message sealed by `cipher` on disk.
+    ///
+    /// # Errors
+    ///
+    /// As for [`open`](Self::open); also `SzError::BadInput` if a stored
+    /// message is not sealed with `cipher`.
+    #[cfg(feature = "encryption")]
+    pub fn open_encrypted(path: impl AsRef<Path>, cipher: PayloadCipher) -> SzResult<Self> {
+        Self::open_with(path.as_ref(), Some(cipher))
+    }
+
+    fn open_with(path: &Path, cipher: Option<PayloadCipher>) -> SzResult<Self> {
+        let path = path.to_path_buf();
+        let mut ack_path = path.clone().into_os_string();
+        ack_path.push(".ack");
+        let ack_path = PathBuf::from(ack_path);
+
+        let delivered = match std::fs::read_to_string(&ack_path) {
+            Ok(text) => text
+                .trim()
+                .parse::<u64>()
+                .map_err(|e| SzError::bad_input(format!("Invalid {}: {e}", ack_path.display())))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+            Err(e) => return Err(io_error(&ack_path, e)),
+        };
+
+        let file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&path)
+            .map_err(|e| io_error(&path, e))?;
+        let (messages, valid_len) = read_messages(&file, &path, cipher.as_ref())?;
+        let file_len = file.metadata().map_err(|e| io_error(&path, e))?.len();
+        if valid_len < file_len {
+            file.set_len(valid_len).map_err(|e| io_error(&path, e))?;
+        }
+
+        let last_sequence = messages.last().map_or(0, |m| m.sequence);
+        let pending = messages
+            .into_iter()
+            .filter(|message| message.sequence > delivered)
+            .collect();
+        Ok(Self {
+            path,
+            ack_path,
+            cipher,
+            state: Mutex::new(State {
+                file,
+                pending,
+                next_sequence: last_sequence.max(delivered) + 1,
+            }),
+        })
+    }
+
+    /// Stores `payload` and returns its sequence number, or `None` for an
+    /// empty payload (`SZ_NO_INFO`), which is not stored. The message is on
+    /// disk when this returns.
+    ///
+    /// With the `otel` feature, the message keeps the trace context of the
+    /// current span in [`OutboxMessage::headers`].
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the outbox file cannot be written.
+    pub fn append(&self, payload: &str) -> SzResult<Option<u64>> {
+        if payload == SZ_NO_INFO {
+            return Ok(None);
+        }
+        let mut state = self.lock();
+        let sequence = state.next_sequence;
+        let message = OutboxMessage {
+            sequence,
+            dedup_key: format!(
+                "{sequence}-{:016x}",
+                crate::ingest::fnv1a_64(payload.as_bytes())
+            ),
+            payload: payload.to_string(),
+            headers: trace_headers(),
+        };
+        let mut line = serde_json::to_string(&message)?;
+        if let Some(cipher) = &self.cipher {
+            line = cipher.seal_line(&line)?;
+        }
+        writeln!(state.file, "{line}")
+            .and_then(|()| state.file.sync_data())
+            .map_err(|e| io_error(&self.path, e))?;
+        state.next_sequence += 1;
+        state.pending.push_back(message);
+        Ok(Some(sequence))
+    }
+
+    /// Number of messages not yet delivered.
+    pub fn pending(&self) -> usize {
+        self.lock().pending.len()
+    }
+
+    /// Returns the oldest undelivered message.
+    fn front(&self) -> Option<OutboxMessage> {
+        self.lock().pending.front().cloned()
+    }
+
+    /// Records that the message with `sequence` was delivered.
+    fn acknowledge(&self, sequence: u64) -> SzResult<()> {
+        let mut state = self.lock();
+        if state.pending.front().map(|m| m.sequence) == Some(sequence) {
+            state.pending.pop_front();
+        }
+        write_atomically(&self.ack_path, &sequence.to_string())?;
+        if state.pending.is_empty() {
+            let len = state
+                .file
+                .metadata()
+                .map_err(|e| io_error(&self.path, e))?
+                .len();
+            if len > COMPACT_THRESHOLD {
+                state.file.set_len(0).map_err(|e| io_error(&self.path, e))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        match self.state.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+/// Outcome of a relay run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RelaySummary {
+    /// Messages delivered and acknowledged
+    pub delivered: u64,
+    /// Failed delivery attempts that were retried
+    pub retries: u64,
+}
+
+/// Delivers outbox messages to a sink, in order, with retries.
+pub struct OutboxRelay {
+    outbox: Arc<Outbox>,
+    sink: Arc<dyn InfoSink>,
+    max_attempts: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    poll_interval: Duration,
+}
+
+impl OutboxRelay {
+    /// Creates a relay with the default retry settings.
+    pub fn new(outbox: Arc<Outbox>, sink: Arc<dyn InfoSink>) -> Self {
+        Self {
+            outbox,
+            sink,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            initial_backoff: DEFAULT_INITIAL_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+        }
+    }
+
+    /// Sets the number of delivery attempts per message in
+    /// [`relay_pending`](Self::relay_pending). Values below 1 are treated
+    /// as 1.
+    pub fn with_max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// Sets the wait before the first retry and the cap it doubles up to.
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max.max(initial);
+        self
+    }
+
+    /// Sets how long [`run`](Self::run) waits when the outbox is empty.
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Delivers every pending message, retrying each up to the maximum
+    /// number of attempts.
+    ///
+    /// # Errors
+    ///
+    /// The sink's last error when a message could not be delivered; it and
+    /// the messages after it stay in the outbox. Also `SzError::BadInput` if
+    /// the acknowledgement cannot be written.
+    pub fn relay_pending(&self) -> SzResult<RelaySummary> {
+        let mut summary = RelaySummary::default();
+        while let Some(message) = self.outbox.front() {
+            let mut backoff = self.initial_backoff;
+            let mut attempt = 1;
+            loop {
+                match self.sink.deliver(&message) {
+                    Ok(()) => break,
+                    Err(e) if attempt >= self.max_attempts => return Err(e),
+                    Err(_) => {
+                        summary.retries += 1;
+                        attempt += 1;
+                        std::thread::sleep(backoff);
+                        backoff = (backoff * 2).min(self.max_backoff);
+                    }
+                }
+            }
+            self.outbox.acknowledge(message.sequence)?;
+            summary.delivered += 1;
+        }
+        Ok(summary)
+    }
+
+    /// Delivers messages as they are appended until `stop` is set, retrying
+    /// failed deliveries with backoff for as long as it takes. Messages
+    /// still pending when stopped are delivered by the next run.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if an acknowledgement cannot be written.
+    pub fn run(&self, stop: &AtomicBool) -> SzResult<RelaySummary> {
+        let mut summary = RelaySummary::default();
+        let mut backoff = self.initial_backoff;
+        while !stop.load(Ordering::Relaxed) {
+            let Some(message) = self.outbox.front() else {
+                std::thread::sleep(self.poll_interval);
+                continue;
+            };
+            match self.sink.deliver(&message) {
+                Ok(()) => {
+                    self.outbox.acknowledge(message.sequence)?;
+                    summary.delivered += 1;
+                    backoff = self.initial_backoff;
+                }
+                Err(_) => {
+                    summary.retries += 1;
+                    std::thread::sleep(backoff);
+                    backoff = (backoff * 2).min(self.max_backoff);
+                }
+            }
+        }
+        Ok(summary)
+    }
+}
+
+/// Reads the messages of an outbox file. Returns them with the length of the
+/// file up to the end of the last complete line.
+fn read_messages(
+    file: &File,
+    path: &Path,
+    cipher: Option<&PayloadCipher>,
+) -> SzResult<(Vec<OutboxMessage>, u64)> {
+    let mut reader = BufReader::new(file);
+    let mut messages = Vec::new();
+    let mut valid_len = 0;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = reader.read_line(&mut line).map_err(|e| io_error(path, e))?;
+        if read == 0 || !line.ends_with('\n') {
+            break;
+        }
+        if !line.trim().is_empty() {
+            if let Some(cipher) = cipher {
+                line = cipher.open_line(&line).map_err(|e| {
+                    SzError::bad_input(format!(
+                        "Invalid outbox message in {}: {}",
+                        path.display(),
+                        e.message()
+                    ))
+                })?;
+            }
+            let message: OutboxMessage = serde_json::from_str(&line).map_err(|e| {
+                SzError::bad_input(format!("Invalid outbox message in {}: {e}", path.display()))
+            })?;
+            messages.push(message);
+        }
+        valid_len += read as u64;
+    }
+    Ok((messages, valid_len))
+}
+
+/// Replaces the file at `path` with `contents` so readers never see a
+/// partial write.
+fn write_atomically(path: &Path, contents: &str) -> SzResult<()> {
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".tmp");
+    let temp = PathBuf::from(temp);
+    let mut file = File::create(&temp).map_err(|e| io_error(&temp, e))?;
+    file.write_all(contents.as_bytes())
+        .and_then(|()| file.sync_data())
+        .map_err(|e| io_error(&temp, e))?;
+    std::fs::rename(&temp, path).map_err(|e| io_error(path, e))
+}
+
+/// Headers carrying the trace context of the current span.
+fn trace_headers() -> BTreeMap<String, String> {
+    #[cfg(feature = "otel")]
+    {
+        crate::otel::TraceHeaders::current().into_map()
+    }
+    #[cfg(not(feature = "otel"))]
+    {
+        BTreeMap::new()
+    }
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> SzError {
+    SzError::bad_input(format!("Outbox I/O on {} failed: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("sz_outbox_{name}_{}", std::process::id()))
+    }
+
+    fn remove(path: &Path) {
+        let _ = std::fs::remove_file(path);
+        let mut ack = path.as_os_str().to_owned();
+        ack.push(".ack");
+        let _ = std::fs::remove_file(ack);
+    }
+
+    #[derive(Default)]
+    struct FlakySink {
+        failures_left: AtomicUsize,
+        delivered: Mutex<Vec<OutboxMessage>>,
+    }
+
+    impl InfoSink for FlakySink {
+        fn deliver(&self, message: &OutboxMessage) -> SzResult<()> {
+            if self
+                .failures_left
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| n.checked_sub(1))
+                .is_ok()
+            {
+                return Err(SzError::unknown("broker unavailable"));
+            }
+            self.delivered.lock().unwrap().push(message.clone());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_append_survives_reopen() {
+        let path = temp_path("reopen");
+        remove(&path);
+        let outbox = Outbox::open(&path).unwrap();
+        assert_eq!(outbox.append(SZ_NO_INFO).unwrap(), None);
+        assert_eq!(outbox.append(r#"{"A":1}"#).unwrap(), Some(1));
+        assert_eq!(outbox.append(r#"{"A":2}"#).unwrap(), Some(2));
+        drop(outbox);
+
+        // A torn final line is discarded
+        let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+        file.write_all(br#"{"sequence":3,"ded"#).unwrap();
+        drop(file);
+
+        let outbox = Outbox::open(&path).unwrap();
+        assert_eq!(outbox.pending(), 2);
+        assert_eq!(outbox.append(r#"{"A":3}"#).unwrap(), Some(3));
+        remove(&path);
+    }
+
+    #[test]
+    fn test_relay_retries_and_acknowledges() {
+        let path = temp_path("relay");
+        remove(&path);
+        let outbox = Arc::new(Outbox::open(&path).unwrap());
+        outbox.append(r#"{"A":1}"#).unwrap();
+        outbox.append(r#"{"A":2}"#).unwrap();
+
+        let sink = Arc::new(FlakySink {
+            failures_left: AtomicUsize::new(2),
+            ..Default::default()
+        });
+        let relay = OutboxRelay::new(outbox.clone(), sink.clone())
+            .with_backoff(Duration::ZERO, Duration::ZERO)
+            .with_max_attempts(3);
+        let summary = relay.relay_pending().unwrap();
+        assert_eq!(
+            summary,
+            RelaySummary {
+                delivered: 2,
+                retries: 2
+            }
+        );
+        assert_eq!(outbox.pending(), 0);
+
+        let delivered = sink.delivered.lock().unwrap().clone();
+        assert_eq!(delivered[0].sequence, 1);
+        assert_ne!(delivered[0].dedup_key, delivered[1].dedup_key);
+        drop(relay);
+        drop(outbox);
+
+        // Acknowledged messages are not delivered again, and sequence
+        // numbers continue
+        let outbox = Outbox::open(&path).unwrap();
+        assert_eq!(outbox.pending(), 0);
+        assert_eq!(outbox.append(r#"{"A":3}"#).unwrap(), Some(3));
+        remove(&path);
+    }
+
+    #[test]
+    fn test_relay_gives_up_after_max_attempts() {
+        let path = temp_path("give_up");
+        remove(&path);
+        let outbox = Arc::new(Outbox::open(&path).unwrap());
+        outbox.append(r#"{"A":1}"#).unwrap();
+        let sink = Arc::new(FlakySink {
+            failures_left: AtomicUsize::new(usize::MAX),
+            ..Default::default()
+        });
+        let relay = OutboxRelay::new(outbox.clone(), sink)
+            .with_backoff(Duration::ZERO, Duration::ZERO)
+            .with_max_attempts(2);
+        assert!(relay.relay_pending().is_err());
+        assert_eq!(outbox.pending(), 1);
+        remove(&path);
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encrypted_outbox_round_trip() {
+        let path = temp_path("encrypted");
+        remove(&path);
+        let cipher = PayloadCipher::new(&[5u8; 32]).unwrap();
+        let outbox = Outbox::open_encrypted(&path, cipher.clone()).unwrap();
+        outbox.append(r#"{"NAME_FULL":"Ann Lee"}"#).unwrap();
+        outbox.append(r#"{"A":2}"#).unwrap();
+        drop(outbox);
+
+        let stored = std::fs::read_to_string(&path).unwrap();
+        assert!(!stored.contains("Ann Lee"));
+        assert!(stored.lines().all(|line| line.starts_with("szenc1:")));
+
+        let outbox = Outbox::open_encrypted(&path, cipher).unwrap();
+        assert_eq!(outbox.pending(), 2);
+        assert_eq!(
+            outbox.front().unwrap().payload,
+            r#"{"NAME_FULL":"Ann Lee"}"#
+        );
+        assert_eq!(outbox.append(r#"{"A":3}"#).unwrap(), Some(3));
+        drop(outbox);
+
+        // Another key, or no key, cannot read the messages
+        let other = PayloadCipher::new(&[6u8; 32]).unwrap();
+        assert!(Outbox::open_encrypted(&path, other).is_err());
+        assert!(Outbox::open(&path).is_err());
+        remove(&path);
+    }
+}
@@ -0,0 +1,943 @@
+//! Declarative ingestion pipelines.
+//!
+//! A [`PipelineSpec`] describes a standard load in TOML or YAML: the JSON
+//! lines files to read, data source mappings, the number of loader threads,
+//! where to write the `WITH_INFO` responses, and what to do with redo records
+//! afterwards. [`Pipeline::run`] carries it out:
+//!
+//! 1. Registers `data_sources` in the default configuration when listed
+//!    (see [`SzBootstrap::ensure_default_config`]).
+//! 2. Reads the sources in order and adds their records on `workers`
+//!    threads. Records that fail are counted and reported; the load goes on.
+//! 3. Drains the redo queue unless the redo policy is `skip`.
+//!
+//! Requires the `pipeline` feature.
+//!
+//! # Spec
+//!
+//! ```toml
+//! data_sources = ["CUSTOMERS", "WATCHLIST"]
+//! workers = 8
+//! batch_size = 64
+//! info_sink = { file = "info.jsonl" }   # or "stdout"
+//!
+//! [data_source_map]
+//! CUST = "CUSTOMERS"
+//!
+//! [[sources]]
+//! path = "customers.jsonl"
+//!
+//! [[sources]]
+//! path = "watchlist.jsonl"
+//! data_source = "WATCHLIST"   # overrides DATA_SOURCE in every record
+//!
+//! [redo]
+//! policy = "drain"   # or "skip"
+//! workers = 4
+//! poll_interval_ms = 1000
+//! ```
+//!
+//! Relative source and sink paths in a spec file are resolved against the
+//! file's directory.
+//!
+//! # Runtime tuning
+//!
+//! A long-running pipeline can be retuned without a restart, either through
+//! [`ControlHandle::tune`] on the handle given to [`Pipeline::with_control`],
+//! or by editing the spec file given to [`Pipeline::watch_spec`]. The
+//! [`Tuning`] fields apply to the phase that is running:
+//!
+//! | Phase | `workers` | `batch_size` | `poll_interval` |
+//! |-------|-----------|--------------|-----------------|
+//! | Load | loader threads, raised or lowered | records per batch | — |
+//! | Redo | redo threads, up to `redo.workers` | — | redo lag checks |
+//!
+//! Changes take effect with the next batch or poll. When the redo phase
+//! starts, tuning from the load phase is cleared.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::pipeline::{Pipeline, PipelineSpec};
+//! use sz_rust_sdk::prelude::*;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_pipeline")?;
+//! let spec = PipelineSpec::from_path("load.toml")?;
+//! let report = Pipeline::new(env.clone(), spec).run()?;
+//! println!(
+//!     "{} loaded, {} failed",
+//!     report.records_loaded(),
+//!     report.records_failed()
+//! );
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::config_tools::SzBootstrap;
+use crate::control::{ControlHandle, Tuning};
+use crate::core::SzEnvironmentCore;
+use crate::error::{SzError, SzResult};
+use crate::flags::SzFlags;
+use crate::ingest::{key_field, parse_object};
+use crate::redo::{DEFAULT_POLL_INTERVAL, RedoSummary, SzRedoProcessor};
+use crate::traits::SzEnvironment;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Default number of loader threads.
+pub const DEFAULT_WORKERS: usize = 4;
+
+/// Default number of redo threads.
+pub const DEFAULT_REDO_WORKERS: usize = 4;
+
+/// Number of record failures kept in a [`PipelineReport`]; later failures
+/// are only counted.
+pub const MAX_REPORTED_FAILURES: usize = 100;
+
+/// Default number of records handed to a loader thread at a time.
+pub const DEFAULT_BATCH_SIZE: usize = 64;
+
+/// Batches queued between the reader and the loader threads.
+const QUEUE_DEPTH: usize = 16;
+
+/// How often a spec file watched with [`Pipeline::watch_spec`] is checked
+/// for changes.
+pub const SPEC_WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Wait of a loader thread above the tuned worker count before it checks
+/// the count again.
+const IDLE_WORKER_WAIT: Duration = Duration::from_millis(100);
+
+/// A declarative ingestion pipeline.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PipelineSpec {
+    /// Files to load, in order
+    pub sources: Vec<SourceSpec>,
+    /// Data sources the default configuration must register before loading
+    #[serde(default)]
+    pub data_sources: Vec<String>,
+    /// Renames record `DATA_SOURCE` values, compared case-insensitively
+    #[serde(default)]
+    pub data_source_map: BTreeMap<String, String>,
+    /// Loader threads
+    #[serde(default = "default_workers")]
+    pub workers: usize,
+    /// Records handed to a loader thread at a time
+    #[serde(default = "default_batch_size")]
+    pub batch_size: usize,
+    /// Where to write the `WITH_INFO` response of each record; no info is
+    /// requested without a sink
+    #[serde(default)]
+    pub info_sink: Option<InfoSinkSpec>,
+    /// Redo handling after the load
+    #[serde(default)]
+    pub redo: RedoSpec,
+}
+
+/// One JSON lines file of a [`PipelineSpec`].
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SourceSpec {
+    /// File to read
+    pub path: PathBuf,
+    /// Data source for every record in the file, replacing the record's own
+    /// `DATA_SOURCE`; not subject to `data_source_map`
+    #[serde(default)]
+    pub data_source: Option<String>,
+}
+
+/// Destination of `WITH_INFO` responses, one per line.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InfoSinkSpec {
+    /// Standard output
+    Stdout,
+    /// A file, created or truncated
+    File(PathBuf),
+}
+
+/// What a pipeline does with redo records after loading.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RedoPolicy {
+    /// Leave them queued, e.g. for a separate redo service
+    Skip,
+    /// Process them until the queue is empty
+    #[default]
+    Drain,
+}
+
+/// Redo settings of a [`PipelineSpec`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RedoSpec {
+    /// Whether to process redo records
+    #[serde(default)]
+    pub policy: RedoPolicy,
+    /// Redo threads
+    #[serde(default = "default_redo_workers")]
+    pub workers: usize,
+    /// Interval between redo lag checks, in milliseconds
+    #[serde(default = "default_redo_poll_interval_ms")]
+    pub poll_interval_ms: u64,
+}
+
+impl RedoSpec {
+    /// Interval between redo lag checks.
+    pub fn poll_interval(&self) -> Duration {
+        Duration::from_millis(self.poll_interval_ms)
+    }
+}
+
+impl Default for RedoSpec {
+    fn default() -> Self {
+        Self {
+            policy: RedoPolicy::default(),
+            workers: DEFAULT_REDO_WORKERS,
+            poll_interval_ms: default_redo_poll_interval_ms(),
+        }
+    }
+}
+
+fn default_workers() -> usize {
+    DEFAULT_WORKERS
+}
+
+fn default_batch_size() -> usize {
+    DEFAULT_BATCH_SIZE
+}
+
+fn default_redo_workers() -> usize {
+    DEFAULT_REDO_WORKERS
+}
+
+fn default_redo_poll_interval_ms() -> u64 {
+    DEFAULT_POLL_INTERVAL.as_millis() as u64
+}
+
+impl PipelineSpec {
+    /// Parses a TOML spec.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the spec is malformed or has no sources.
+    pub fn from_toml_str(spec: &str) -> SzResult<Self> {
+        toml::from_str::<Self>(spec)
+            .map_err(|e| SzError::bad_input(format!("Invalid pipeline spec: {e}")))?
+            .validated()
+    }
+
+    /// Parses a YAML spec.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the spec is malformed or has no sources.
+    pub fn from_yaml_str(spec: &str) -> SzResult<Self> {
+        serde_yaml_ng::from_str::<Self>(spec)
+            .map_err(|e| SzError::bad_input(format!("Invalid pipeline spec: {e}")))?
+            .validated()
+    }
+
+    /// Reads a spec file, as YAML for a `.yaml` or `.yml` extension and as
+    /// TOML otherwise. Relative paths in the spec are resolved against the
+    /// file's directory.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the file cannot be read, is malformed, or has
+    /// no sources.
+    pub fn from_path(path: impl AsRef<Path>) -> SzResult<Self> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| SzError::bad_input(format!("Failed reading {}: {e}", path.display())))?;
+        let yaml = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("yaml") || ext.eq_ignore_ascii_case("yml"));
+        let spec = if yaml {
+            Self::from_yaml_str(&text)?
+        } else {
+            Self::from_toml_str(&text)?
+        };
+        Ok(match path.parent() {
+            Some(base) => spec.relative_to(base),
+            None => spec,
+        })
+    }
+
+    /// Resolves relative source and sink paths against `base`.
+    pub fn relative_to(mut self, base: &Path) -> Self {
+        for source in &mut self.sources {
+            source.path = base.join(&source.path);
+        }
+        if let Some(InfoSinkSpec::File(path)) = &mut self.info_sink {
+            *path = base.join(&*path);
+        }
+        self
+    }
+
+    fn validated(mut self) -> SzResult<Self> {
+        if self.sources.is_empty() {
+            return Err(SzError::bad_input("Pipeline spec has no sources"));
+        }
+        self.workers = self.workers.max(1);
+        self.batch_size = self.batch_size.max(1);
+        self.redo.workers = self.redo.workers.max(1);
+        Ok(self)
+    }
+
+    /// The settings of this spec that the load phase can change at runtime.
+    pub fn load_tuning(&self) -> Tuning {
+        Tuning::default()
+            .with_workers(self.workers)
+            .with_batch_size(self.batch_size)
+    }
+
+    /// The settings of this spec that the redo phase can change at runtime.
+    pub fn redo_tuning(&self) -> Tuning {
+        Tuning::default()
+            .with_workers(self.redo.workers)
+            .with_poll_interval(self.redo.poll_interval())
+    }
+
+    /// Returns the data source and record ID of a source line, and the
+    /// record to add with the spec's mappings applied.
+    fn prepare_record(
+        &self,
+        source: &SourceSpec,
+        line: &str,
+    ) -> SzResult<(String, String, String)> {
+        let mut document = parse_object(line)?;
+        let Some(record_id) = key_field(&document, "RECORD_ID")? else {
+            return Err(SzError::bad_input("record has no RECORD_ID"));
+        };
+        let embedded = key_field(&document, "DATA_SOURCE")?;
+        let data_source = match (&source.data_source, &embedded) {
+            (Some(forced), _) => forced.clone(),
+            (None, Some(embedded)) => self
+                .data_source_map
+                .iter()
+                .find(|(from, _)| from.eq_ignore_ascii_case(embedded))
+                .map_or_else(|| embedded.clone(), |(_, to)| to.clone()),
+            (None, None) => return Err(SzError::bad_input("record has no DATA_SOURCE")),
+        };
+        if embedded.as_ref() == Some(&data_source) {
+            return Ok((data_source, record_id, line.to_string()));
+        }
+        document.insert(
+            "DATA_SOURCE".to_string(),
+            Value::String(data_source.clone()),
+        );
+        let record = Value::Object(document).to_string();
+        Ok((data_source, record_id, record))
+    }
+}
+
+/// A record that could not be loaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordFailure {
+    /// Source file
+    pub source: PathBuf,
+    /// 1-based line number
+    pub line: usize,
+    /// Error message
+    pub message: String,
+}
+
+/// Load outcome of one source.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceReport {
+    /// Source file
+    pub path: PathBuf,
+    /// Records added
+    pub records_loaded: u64,
+    /// Records that failed
+    pub records_failed: u64,
+    /// Blank lines skipped
+    pub blank_lines: u64,
+}
+
+/// Outcome of [`Pipeline::run`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PipelineReport {
+    /// Per source, in spec order; sources not reached are absent
+    pub sources: Vec<SourceReport>,
+    /// The first [`MAX_REPORTED_FAILURES`] record failures
+    pub failures: Vec<RecordFailure>,
+    /// Redo processing, unless skipped or the run was stopped first
+    pub redo: Option<RedoSummary>,
+    /// Whether the run was stopped through its [`ControlHandle`]
+    pub stopped: bool,
+    /// Wall time of the run
+    pub elapsed: Duration,
+}
+
+impl PipelineReport {
+    /// Records added across all sources.
+    pub fn records_loaded(&self) -> u64 {
+        self.sources.iter().map(|s| s.records_loaded).sum()
+    }
+
+    /// Records that failed across all sources.
+    pub fn records_failed(&self) -> u64 {
+        self.sources.iter().map(|s| s.records_failed).sum()
+    }
+}
+
+/// A source line on its way to a loader thread, in a batch.
+struct Job {
+    source: usize,
+    line: usize,
+    text: String,
+}
+
+/// Counters shared by the loader threads.
+struct LoadState {
+    loaded: Vec<AtomicU64>,
+    failed: Vec<AtomicU64>,
+    failures: Mutex<Vec<RecordFailure>>,
+    fatal: Mutex<Option<SzError>>,
+    /// Set once the reader has queued its last batch
+    closed: AtomicBool,
+}
+
+impl LoadState {
+    fn fail(&self, source: &SourceSpec, index: usize, line: usize, error: &SzError) {
+        self.failed[index].fetch_add(1, Ordering::Relaxed);
+        let mut failures = match self.failures.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if failures.len() < MAX_REPORTED_FAILURES {
+            failures.push(RecordFailure {
+                source: source.path.clone(),
+                line,
+                message: error.to_string(),
+            });
+        }
+    }
+
+    fn fatal(&self, error: SzError) {
+        let mut fatal = match self.fatal.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        fatal.get_or_insert(error);
+    }
+
+    fn has_failed(&self) -> bool {
+        match self.fatal.lock() {
+            Ok(guard) => guard.is_some(),
+            Err(poisoned) => poisoned.into_inner().is_some(),
+        }
+    }
+}
+
+/// Runs a [`PipelineSpec`] against an environment.
+pub struct Pipeline {
+    env: Arc<SzEnvironmentCore>,
+    spec: PipelineSpec,
+    control: ControlHandle,
+    watch: Option<PathBuf>,
+}
+
+impl Pipeline {
+    /// Creates a pipeline for `spec`.
+    pub fn new(env: Arc<SzEnvironmentCore>, spec: PipelineSpec) -> Self {
+        Self {
+            env,
+            spec,
+            control: ControlHandle::new(),
+            watch: None,
+        }
+    }
+
+    /// Sets the handle used to pause, resume, stop and tune the run.
+    ///
+    /// The reader checks the handle before each line and the redo phase
+    /// before each redo record. The handle is left in the stopped state when
+    /// the run returns. See the [module documentation](self) for the tuning
+    /// each phase honors.
+    pub fn with_control(mut self, control: ControlHandle) -> Self {
+        self.control = control;
+        self
+    }
+
+    /// Watches the spec file at `path` while the pipeline runs and applies
+    /// its `workers`, `batch_size` and `redo` settings through the control
+    /// handle when the file changes. Other changes in the file are ignored,
+    /// as is a file that fails to parse.
+    pub fn watch_spec(mut self, path: impl Into<PathBuf>) -> Self {
+        self.watch = Some(path.into());
+        self
+    }
+
+    /// Returns the spec.
+    pub fn spec(&self) -> &PipelineSpec {
+        &self.spec
+    }
+
+    /// Runs the pipeline.
+    ///
+    /// With the `otel` feature, each record is added in an
+    /// [`otel::record_span`](crate::otel::record_span) under the span current
+    /// when `run` is called.
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::BadInput` - A source or the info sink cannot be opened or
+    ///   written
+    /// * Any error from the configuration update, obtaining engines, or the
+    ///   redo phase. Failures of individual records are reported instead.
+    pub fn run(&self) -> SzResult<PipelineReport> {
+        let redo_phase = AtomicBool::new(false);
+        let finished = AtomicBool::new(false);
+        let result = std::thread::scope(|scope| {
+            let watcher = self.watch.as_deref().map(|path| {
+                let (redo_phase, finished) = (&redo_phase, &finished);
+                scope.spawn(move || self.watch_spec_file(path, redo_phase, finished))
+            });
+            let result = self.run_phases(&redo_phase);
+            finished.store(true, Ordering::SeqCst);
+            if let Some(watcher) = watcher {
+                watcher.thread().unpark();
+            }
+            result
+        });
+        self.control.finished();
+        result
+    }
+
+    fn run_phases(&self, redo_phase: &AtomicBool) -> SzResult<PipelineReport> {
+        let started = Instant::now();
+        if !self.spec.data_sources.is_empty() {
+            let data_sources: Vec<&str> =
+                self.spec.data_sources.iter().map(String::as_str).collect();
+            SzBootstrap::ensure_default_config(&self.env, &data_sources)?;
+        }
+
+        let (sources, failures, stopped) = self.load()?;
+        let redo = if stopped || self.spec.redo.policy == RedoPolicy::Skip {
+            None
+        } else {
+            redo_phase.store(true, Ordering::SeqCst);
+            self.control.set_tuning(self.redo_tuning());
+            let summary = SzRedoProcessor::new(self.env.clone())
+                .with_workers(1, self.spec.redo.workers)
+                .poll_interval(self.spec.redo.poll_interval())
+                .with_control(self.control.clone())
+                .drain_until(1)?;
+            Some(summary)
+        };
+
+        Ok(PipelineReport {
+            stopped: stopped || redo.is_some_and(|summary| summary.stopped),
+            sources,
+            failures,
+            redo,
+            elapsed: started.elapsed(),
+        })
+    }
+
+    /// Tuning for the redo phase: the watched spec file's redo settings, or
+    /// none so that the processor uses the spec it was started with.
+    fn redo_tuning(&self) -> Tuning {
+        self.watch
+            .as_deref()
+            .and_then(|path| PipelineSpec::from_path(path).ok())
+            .map_or_else(Tuning::default, |spec| spec.redo_tuning())
+    }
+
+    /// Polls the modification time of the spec file until `finished` is set,
+    /// applying the tuning of the current phase after each change.
+    fn watch_spec_file(&self, path: &Path, redo_phase: &AtomicBool, finished: &AtomicBool) {
+        let modified = || std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        let mut last = modified();
+        while !finished.load(Ordering::SeqCst) {
+            std::thread::park_timeout(SPEC_WATCH_INTERVAL);
+            let current = modified();
+            if current == last {
+                continue;
+            }
+            last = current;
+            // A half-written or broken file is skipped; the next save is
+            // picked up again.
+            if let Ok(spec) = PipelineSpec::from_path(path) {
+                self.control.tune(if redo_phase.load(Ordering::SeqCst) {
+                    spec.redo_tuning()
+                } else {
+                    spec.load_tuning()
+                });
+            }
+        }
+    }
+
+    /// Number of loader threads, after the control handle's tuning.
+    fn loader_workers(&self) -> usize {
+        self.control
+            .tuning()
+            .workers
+            .unwrap_or(self.spec.workers)
+            .max(1)
+    }
+
+    /// Records per batch, after the control handle's tuning.
+    fn batch_size(&self) -> usize {
+        self.control
+            .tuning()
+            .batch_size
+            .unwrap_or(self.spec.batch_size)
+            .max(1)
+    }
+
+    /// Loads all sources; returns the per-source reports, the kept
+    /// failures, and whether the load was stopped.
+    ///
+    /// Loader threads are started as the worker count grows. Threads beyond
+    /// a lowered count idle until it rises again or the load ends.
+    fn load(&self) -> SzResult<(Vec<SourceReport>, Vec<RecordFailure>, bool)> {
+        let sink = open_sink(self.spec.info_sink.as_ref())?;
+        let flags = sink.as_ref().map(|_| SzFlags::WITH_INFO);
+        let state = LoadState {
+            loaded: self
+                .spec
+                .sources
+                .iter()
+                .map(|_| AtomicU64::new(0))
+                .collect(),
+            failed: self
+                .spec
+                .sources
+                .iter()
+                .map(|_| AtomicU64::new(0))
+                .collect(),
+            failures: Mutex::new(Vec::new()),
+            fatal: Mutex::new(None),
+            closed: AtomicBool::new(false),
+        };
+        let mut reports: Vec<SourceReport> = Vec::new();
+        let mut stopped = false;
+
+        let (sender, receiver) = mpsc::sync_channel::<Vec<Job>>(QUEUE_DEPTH);
+        let receiver = Mutex::new(receiver);
+        // Loader threads continue the caller's trace
+        #[cfg(feature = "otel")]
+        let parent = tracing::Span::current();
+        std::thread::scope(|scope| {
+            let mut spawned = 0;
+            let mut spawn_workers = || {
+                while spawned < self.loader_workers() {
+                    let index = spawned;
+                    let (receiver, state, sink) = (&receiver, &state, sink.as_ref());
+                    #[cfg(feature = "otel")]
+                    let parent = parent.clone();
+                    scope.spawn(move || {
+                        #[cfg(feature = "otel")]
+                        let _parent = parent.entered();
+                        self.load_worker(index, receiver, state, sink, flags)
+                    });
+                    spawned += 1;
+                }
+            };
+            let mut batch = Vec::new();
+
+            'sources: for (index, source) in self.spec.sources.iter().enumerate() {
+                let reader = match File::open(&source.path) {
+                    Ok(file) => BufReader::new(file),
+                    Err(e) => {
+                        state.fatal(SzError::bad_input(format!(
+                            "Failed opening {}: {e}",
+                            source.path.display()
+                        )));
+                        break;
+                    }
+                };
+                reports.push(SourceReport {
+                    path: source.path.clone(),
+                    ..SourceReport::default()
+                });
+                for (line_index, text) in reader.lines().enumerate() {
+                    if state.has_failed() {
+                        break 'sources;
+                    }
+                    if !self.control.checkpoint() {
+                        stopped = true;
+                        break 'sources;
+                    }
+                    let line = line_index + 1;
+                    let text = match text {
+                        Ok(text) => text,
+                        Err(e) => {
+                            state.fatal(SzError::bad_input(format!(
+                                "Failed reading {} line {line}: {e}",
+                                source.path.display()
+                            )));
+                            break 'sources;
+                        }
+                    };
+                    if text.trim().is_empty() {
+                        if let Some(report) = reports.last_mut() {
+                            report.blank_lines += 1;
+                        }
+                        continue;
+                    }
+                    batch.push(Job {
+                        source: index,
+                        line,
+                        text,
+                    });
+                    if batch.len() >= self.batch_size() {
+                        spawn_workers();
+                        if sender.send(std::mem::take(&mut batch)).is_err() {
+                            break 'sources;
+                        }
+                    }
+                }
+            }
+            if !batch.is_empty() && !stopped && !state.has_failed() {
+                spawn_workers();
+                let _ = sender.send(batch);
+            }
+            drop(sender);
+            state.closed.store(true, Ordering::SeqCst);
+        });
+
+        if let Some(error) = state.fatal.into_inner().unwrap_or_else(|p| p.into_inner()) {
+            return Err(error);
+        }
+        if let Some(sink) = &sink {
+            lock_sink(sink)
+                .flush()
+                .map_err(|e| SzError::bad_input(format!("Failed writing info sink: {e}")))?;
+        }
+        for (index, report) in reports.iter_mut().enumerate() {
+            report.records_loaded = state.loaded[index].load(Ordering::Relaxed);
+            report.records_failed = state.failed[index].load(Ordering::Relaxed);
+        }
+        let failures = state
+            .failures
+            .into_inner()
+            .unwrap_or_else(|p| p.into_inner());
+        Ok((reports, failures, stopped))
+    }
+
+    /// Adds batches of records from `receiver` until it closes. After a
+    /// fatal error the remaining batches are discarded, so the reader never
+    /// blocks on a full queue.
+    fn load_worker(
+        &self,
+        index: usize,
+        receiver: &Mutex<mpsc::Receiver<Vec<Job>>>,
+        state: &LoadState,
+        sink: Option<&Mutex<Box<dyn Write + Send>>>,
+        flags: Option<SzFlags>,
+    ) {
+        let engine = match self.env.get_engine() {
+            Ok(engine) => Some(engine),
+            Err(e) => {
+                state.fatal(e);
+                None
+            }
+        };
+        loop {
+            if index >= self.loader_workers() {
+                if state.closed.load(Ordering::SeqCst) {
+                    return;
+                }
+                std::thread::sleep(IDLE_WORKER_WAIT);
+                continue;
+            }
+            let batch = {
+                let receiver = match receiver.lock() {
+                    Ok(guard) => guard,
+                    Err(poisoned) => poisoned.into_inner(),
+                };
+                match receiver.recv() {
+                    Ok(batch) => batch,
+                    Err(_) => return,
+                }
+            };
+            let Some(engine) = engine.as_ref().filter(|_| !state.has_failed()) else {
+                continue;
+            };
+            for job in batch {
+                let source = &self.spec.sources[job.source];
+                let added = self.spec.prepare_record(source, &job.text).and_then(
+                    |(data_source, record_id, record)| {
+                        #[cfg(feature = "otel")]
+                        let _span =
+                            crate::otel::record_span(None, &data_source, &record_id).entered();
+                        engine.add_record(&data_source, &record_id, &record, flags)
+                    },
+                );
+                match added {
+                    Ok(info) => {
+                        state.loaded[job.source].fetch_add(1, Ordering::Relaxed);
+                        if let Some(sink) = sink
+                            && !info.is_empty()
+                            && let Err(e) = writeln!(lock_sink(sink), "{info}")
+                        {
+                            state.fatal(SzError::bad_input(format!(
+                                "Failed writing info sink: {e}"
+                            )));
+                        }
+                    }
+                    Err(e) => state.fail(source, job.source, job.line, &e),
+                }
+            }
+        }
+    }
+}
+
+fn open_sink(spec: Option<&InfoSinkSpec>) -> SzResult<Option<Mutex<Box<dyn Write + Send>>>> {
+    let writer: Box<dyn Write + Send> = match spec {
+        None => return Ok(None),
+        Some(InfoSinkSpec::Stdout) => Box::new(std::io::stdout()),
+        Some(InfoSinkSpec::File(path)) => {
+            let file = File::create(path).map_err(|e| {
+                SzError::bad_input(format!("Failed creating {}: {e}", path.display()))
+            })?;
+            Box::new(BufWriter::new(file))
+        }
+    };
+    Ok(Some(Mutex::new(writer)))
+}
+
+fn lock_sink(
+    sink: &Mutex<Box<dyn Write + Send>>,
+) -> std::sync::MutexGuard<'_, Box<dyn Write + Send>> {
+    match sink.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOML_SPEC: &str = r#"
+        data_sources = ["CUSTOMERS"]
+        workers = 0
+        info_sink = { file = "info.jsonl" }
+
+        [data_source_map]
+        cust = "CUSTOMERS"
+
+        [[sources]]
+        path = "customers.jsonl"
+
+        [[sources]]
+        path = "/data/watchlist.jsonl"
+        data_source = "WATCHLIST"
+
+        [redo]
+        policy = "skip"
+    "#;
+
+    #[test]
+    fn test_parse_toml_spec() {
+        let spec = PipelineSpec::from_toml_str(TOML_SPEC)
+            .unwrap()
+            .relative_to(Path::new("/etc/loads"));
+        assert_eq!(spec.workers, 1);
+        assert_eq!(
+            spec.sources[0].path,
+            Path::new("/etc/loads/customers.jsonl")
+        );
+        assert_eq!(spec.sources[1].path, Path::new("/data/watchlist.jsonl"));
+        assert_eq!(spec.sources[1].data_source.as_deref(), Some("WATCHLIST"));
+        assert_eq!(
+            spec.info_sink,
+            Some(InfoSinkSpec::File(PathBuf::from("/etc/loads/info.jsonl")))
+        );
+        assert_eq!(spec.redo.policy, RedoPolicy::Skip);
+        assert_eq!(spec.redo.workers, DEFAULT_REDO_WORKERS);
+    }
+
+    #[test]
+    fn test_parse_yaml_spec() {
+        let yaml = "
+sources:
+  - path: customers.jsonl
+info_sink: stdout
+redo:
+  workers: 2
+";
+        let spec = PipelineSpec::from_yaml_str(yaml).unwrap();
+        assert_eq!(spec.workers, DEFAULT_WORKERS);
+        assert_eq!(spec.info_sink, Some(InfoSinkSpec::Stdout));
+        assert_eq!(spec.redo.policy, RedoPolicy::Drain);
+        assert_eq!(spec.redo.workers, 2);
+    }
+
+    #[test]
+    fn test_spec_tuning() {
+        let spec = PipelineSpec::from_toml_str(
+            "workers = 3\nbatch_size = 10\n[[sources]]\npath = \"a.jsonl\"\n[redo]\nworkers = 2\npoll_interval_ms = 250",
+        )
+        .unwrap();
+        assert_eq!(
+            spec.load_tuning(),
+            Tuning::default().with_workers(3).with_batch_size(10)
+        );
+        assert_eq!(
+            spec.redo_tuning(),
+            Tuning::default()
+                .with_workers(2)
+                .with_poll_interval(Duration::from_millis(250))
+        );
+        let defaults = PipelineSpec::from_toml_str("[[sources]]\npath = \"a.jsonl\"").unwrap();
+        assert_eq!(defaults.batch_size, DEFAULT_BATCH_SIZE);
+        assert_eq!(defaults.redo.poll_interval(), DEFAULT_POLL_INTERVAL);
+    }
+
+    #[test]
+    fn test_invalid_specs() {
+        assert!(PipelineSpec::from_toml_str("sources = []").is_err());
+        assert!(PipelineSpec::from_toml_str("workers = 2").is_err());
+        let unknown = "[[sources]]\npath = \"a.jsonl\"\nthreads = 2";
+        assert!(PipelineSpec::from_toml_str(unknown).is_err());
+    }
+
+    #[test]
+    fn test_prepare_record_mappings() {
+        let spec = PipelineSpec::from_toml_str(TOML_SPEC).unwrap();
+        let (data_source, record_id, record) = spec
+            .prepare_record(&spec.sources[0], r#"{"DATA_SOURCE":"CUST","RECORD_ID":7}"#)
+            .unwrap();
+        assert_eq!(
+            (data_source.as_str(), record_id.as_str()),
+            ("CUSTOMERS", "7")
+        );
+        assert!(record.contains(r#""DATA_SOURCE":"CUSTOMERS""#));
+
+        let unchanged = r#"{"DATA_SOURCE":"OTHER","RECORD_ID":"1"}"#;
+        let (data_source, _, record) = spec.prepare_record(&spec.sources[0], unchanged).unwrap();
+        assert_eq!(data_source, "OTHER");
+        assert_eq!(record, unchanged);
+
+        let (data_source, _, record) = spec
+            .prepare_record(&spec.sources[1], r#"{"RECORD_ID":"2"}"#)
+            .unwrap();
+        assert_eq!(data_source, "WATCHLIST");
+        assert!(record.contains(r#""DATA_SOURCE":"WATCHLIST""#));
+
+        assert!(
+            spec.prepare_record(&spec.sources[0], r#"{"RECORD_ID":"3"}"#)
+                .is_err()
+        );
+        assert!(
+            spec.prepare_record(&spec.sources[1], r#"{"NAME_FULL":"No Id"}"#)
+                .is_err()
+        );
+    }
+}
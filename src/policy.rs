@@ -0,0 +1,236 @@
+//! Per-data-source defaults for record mutations.
+//!
+//! Data sources often need different handling: a watchlist wants `WITH_INFO`
+//! on every change, a trial feed should not touch the repository yet, and
+//! records from a hand-built source deserve stricter checks. An [`SzPolicy`]
+//! set with `SzEnvironmentCore::set_policy` holds those defaults for one data
+//! source, and every engine created by the environment consults it in
+//! `add_record` and `delete_record`, and for dry-run also in the reevaluate
+//! calls:
+//!
+//! | Setting | Effect |
+//! |---------|--------|
+//! | [`with_add_flags`](SzPolicy::with_add_flags), [`with_delete_flags`](SzPolicy::with_delete_flags) | Flags used when the caller passes `None` |
+//! | [`with_dry_run`](SzPolicy::with_dry_run) | The calls run in dry-run mode, as with `SzEnvironmentCore::set_dry_run`; so do `reevaluate_record` for the data source and `reevaluate_entity` for entities with a record from it |
+//! | [`with_validation`](SzPolicy::with_validation) | Records are checked before they are added; see [`RecordValidation`] |
+//! | [`with_info_outbox`](SzPolicy::with_info_outbox) | The calls add `WITH_INFO` and append the response to the outbox |
+//!
+//! Data source codes are matched case-insensitively. Data sources without a
+//! policy behave as before.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use std::sync::Arc;
+//! use sz_rust_sdk::outbox::Outbox;
+//! use sz_rust_sdk::policy::{RecordValidation, SzPolicy};
+//! use sz_rust_sdk::prelude::*;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_policy")?;
+//! let outbox = Arc::new(Outbox::open("watchlist.outbox")?);
+//! env.set_policy(
+//!     "WATCHLIST",
+//!     Some(
+//!         SzPolicy::new()
+//!             .with_validation(RecordValidation::Preview)
+//!             .with_info_outbox(outbox.clone()),
+//!     ),
+//! );
+//! let engine = env.get_engine()?;
+//! engine.add_record("WATCHLIST", "W1", r#"{"NAME_FULL": "Ann Lee"}"#, None)?;
+//! assert_eq!(outbox.pending(), 1);
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::error::SzResult;
+use crate::flags::SzFlags;
+use crate::outbox::Outbox;
+use crate::traits::SzEngine;
+use std::fmt;
+use std::sync::Arc;
+
+/// Checks applied to a record before it is added.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum RecordValidation {
+    /// No checks beyond the engine's own
+    #[default]
+    Off,
+    /// The record's own `DATA_SOURCE` and `RECORD_ID`, when present, must
+    /// match the call's (see `ingest::check_record_key`)
+    Keys,
+    /// [`Keys`](Self::Keys), and the record must pass
+    /// `SzEngine::get_record_preview`
+    Preview,
+}
+
+/// Defaults for the records of one data source.
+#[derive(Clone, Default)]
+pub struct SzPolicy {
+    add_flags: Option<SzFlags>,
+    delete_flags: Option<SzFlags>,
+    dry_run: bool,
+    validation: RecordValidation,
+    info_outbox: Option<Arc<Outbox>>,
+}
+
+impl fmt::Debug for SzPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SzPolicy")
+            .field("add_flags", &self.add_flags)
+            .field("delete_flags", &self.delete_flags)
+            .field("dry_run", &self.dry_run)
+            .field("validation", &self.validation)
+            .field("info_outbox", &self.info_outbox.as_ref().map(|_| "Outbox"))
+            .finish()
+    }
+}
+
+impl SzPolicy {
+    /// Creates a policy that changes nothing.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the flags `add_record` uses when called with `None`.
+    pub fn with_add_flags(mut self, flags: SzFlags) -> Self {
+        self.add_flags = Some(flags);
+        self
+    }
+
+    /// Sets the flags `delete_record` uses when called with `None`.
+    pub fn with_delete_flags(mut self, flags: SzFlags) -> Self {
+        self.delete_flags = Some(flags);
+        self
+    }
+
+    /// Runs adds, deletes and reevaluations in dry-run mode.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Sets the checks applied before a record is added.
+    pub fn with_validation(mut self, validation: RecordValidation) -> Self {
+        self.validation = validation;
+        self
+    }
+
+    /// Requests `WITH_INFO` on adds and deletes and appends each response
+    /// to `outbox`. The call fails if the append fails, although the change
+    /// has been made.
+    pub fn with_info_outbox(mut self, outbox: Arc<Outbox>) -> Self {
+        self.info_outbox = Some(outbox);
+        self
+    }
+
+    /// Flags for `add_record` when called with `None`
+    pub fn add_flags(&self) -> Option<SzFlags> {
+        self.add_flags
+    }
+
+    /// Flags for `delete_record` when called with `None`
+    pub fn delete_flags(&self) -> Option<SzFlags> {
+        self.delete_flags
+    }
+
+    /// Whether adds and deletes run in dry-run mode
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Checks applied before a record is added
+    pub fn validation(&self) -> RecordValidation {
+        self.validation
+    }
+
+    /// Outbox receiving the `WITH_INFO` responses
+    pub fn info_outbox(&self) -> Option<&Arc<Outbox>> {
+        self.info_outbox.as_ref()
+    }
+
+    /// The flags an `add_record` call made with `flags` runs with.
+    pub(crate) fn flags_for_add(&self, flags: Option<SzFlags>) -> Option<SzFlags> {
+        self.effective_flags(flags, self.add_flags, SzFlags::ADD_RECORD_DEFAULT_FLAGS)
+    }
+
+    /// The flags a `delete_record` call made with `flags` runs with.
+    pub(crate) fn flags_for_delete(&self, flags: Option<SzFlags>) -> Option<SzFlags> {
+        self.effective_flags(
+            flags,
+            self.delete_flags,
+            SzFlags::DELETE_RECORD_DEFAULT_FLAGS,
+        )
+    }
+
+    /// The caller's flags, else `default`, plus `WITH_INFO` when responses
+    /// go to an outbox.
+    fn effective_flags(
+        &self,
+        flags: Option<SzFlags>,
+        default: Option<SzFlags>,
+        base: SzFlags,
+    ) -> Option<SzFlags> {
+        let flags = flags.or(default);
+        match self.info_outbox {
+            Some(_) => Some(flags.unwrap_or(base) | SzFlags::WITH_INFO),
+            None => flags,
+        }
+    }
+
+    /// Applies the validation level to a record about to be added.
+    pub(crate) fn validate(
+        &self,
+        engine: &dyn SzEngine,
+        data_source_code: &str,
+        record_id: &str,
+        record_definition: &str,
+    ) -> SzResult<()> {
+        if self.validation == RecordValidation::Off {
+            return Ok(());
+        }
+        crate::ingest::check_record_key(data_source_code, record_id, record_definition)?;
+        if self.validation == RecordValidation::Preview {
+            engine.get_record_preview(record_definition, Some(SzFlags::empty()))?;
+        }
+        Ok(())
+    }
+
+    /// Appends a successful response to the outbox, if one is set.
+    pub(crate) fn route_info(&self, info: &str) -> SzResult<()> {
+        if let Some(outbox) = &self.info_outbox {
+            outbox.append(info)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_flags_and_routing() {
+        let policy = SzPolicy::new().with_add_flags(SzFlags::WITH_INFO);
+        assert_eq!(policy.flags_for_add(None), Some(SzFlags::WITH_INFO));
+        assert_eq!(
+            policy.flags_for_add(Some(SzFlags::empty())),
+            Some(SzFlags::empty())
+        );
+        assert_eq!(policy.flags_for_delete(None), None);
+
+        let path = std::env::temp_dir().join(format!("sz_policy_{}", std::process::id()));
+        let outbox = Arc::new(Outbox::open(&path).unwrap());
+        let routed = SzPolicy::new().with_info_outbox(outbox.clone());
+        assert_eq!(
+            routed.flags_for_delete(Some(SzFlags::empty())),
+            Some(SzFlags::WITH_INFO)
+        );
+        routed.route_info(r#"{"AFFECTED_ENTITIES":[]}"#).unwrap();
+        routed.route_info(crate::types::SZ_NO_INFO).unwrap();
+        assert_eq!(outbox.pending(), 1);
+        assert!(format!("{routed:?}").contains("info_outbox: Some(\"Outbox\")"));
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(path.with_extension("ack"));
+    }
+}
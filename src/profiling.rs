@@ -0,0 +1,244 @@
+//! Per-call timings for engine calls.
+//!
+//! With the `profiling` feature, every `SzEngine` call on the core engine
+//! records how long it took and how much of that time was spent inside the
+//! native library. The difference is the SDK's own work: argument and
+//! response conversion, buffer handling and, for timed calls, nothing of the
+//! caller's wait. The most recent [`RING_CAPACITY`] calls are kept and
+//! returned by `SzDiagnostic::recent_call_timings`.
+//!
+//! With the `profiling-tracing` feature, each call also opens a `sz_call`
+//! span with a child `native` span around every native call. Install a
+//! [`tracing-flame`](https://docs.rs/tracing-flame) layer to turn them into a
+//! flamegraph (see the `profile_calls` example).
+//!
+//! Without the feature nothing is recorded and the recording hooks compile
+//! to nothing.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::prelude::*;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_profiling")?;
+//! let engine = env.get_engine()?;
+//! engine.search_by_attributes(r#"{"NAME_FULL": "Ann Lee"}"#, None, None)?;
+//!
+//! for timing in env.get_diagnostic()?.recent_call_timings() {
+//!     println!(
+//!         "{}: {:?} total, {:?} native, {:?} SDK",
+//!         timing.operation,
+//!         timing.total,
+//!         timing.native,
+//!         timing.sdk()
+//!     );
+//! }
+//! # Ok::<(), SzError>(())
+//! ```
+
+use std::time::Duration;
+
+/// Number of call timings kept.
+pub const RING_CAPACITY: usize = 1024;
+
+/// Timing of one engine call.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallTiming {
+    /// `SzEngine` method name, e.g. `add_record`
+    pub operation: &'static str,
+    /// Wall time of the whole call
+    pub total: Duration,
+    /// Time spent inside the native library
+    pub native: Duration,
+}
+
+impl CallTiming {
+    /// Time spent in the SDK itself.
+    pub fn sdk(&self) -> Duration {
+        self.total.saturating_sub(self.native)
+    }
+}
+
+/// Returns the most recent call timings, oldest first. Always empty without
+/// the `profiling` feature.
+pub fn recent_call_timings() -> Vec<CallTiming> {
+    #[cfg(feature = "profiling")]
+    {
+        enabled::recent()
+    }
+    #[cfg(not(feature = "profiling"))]
+    {
+        Vec::new()
+    }
+}
+
+/// Discards the recorded call timings.
+pub fn clear_call_timings() {
+    #[cfg(feature = "profiling")]
+    enabled::clear();
+}
+
+/// Times one engine call until dropped. Nested calls on the same thread
+/// (an engine method calling another) count toward the outermost call only.
+#[must_use]
+pub(crate) struct CallScope {
+    #[cfg(feature = "profiling")]
+    inner: Option<enabled::Scope>,
+}
+
+/// Starts timing the engine call `operation`.
+#[inline(always)]
+pub(crate) fn call(operation: &'static str) -> CallScope {
+    #[cfg(feature = "profiling")]
+    {
+        CallScope {
+            inner: enabled::Scope::start(operation),
+        }
+    }
+    #[cfg(not(feature = "profiling"))]
+    {
+        let _ = operation;
+        CallScope {}
+    }
+}
+
+/// Runs a native call, adding its duration to the current call's native time.
+#[inline(always)]
+pub(crate) fn native<T>(call: impl FnOnce() -> T) -> T {
+    #[cfg(feature = "profiling")]
+    {
+        enabled::native(call)
+    }
+    #[cfg(not(feature = "profiling"))]
+    {
+        call()
+    }
+}
+
+/// Stops the current call on this thread from being recorded, because its
+/// work moved to another thread that records it instead.
+#[inline(always)]
+pub(crate) fn hand_off() {
+    #[cfg(feature = "profiling")]
+    enabled::hand_off();
+}
+
+#[cfg(feature = "profiling")]
+mod enabled {
+    use super::{CallTiming, RING_CAPACITY};
+    use std::cell::Cell;
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+    use std::time::{Duration, Instant};
+
+    static TIMINGS: Mutex<VecDeque<CallTiming>> = Mutex::new(VecDeque::new());
+
+    thread_local! {
+        static DEPTH: Cell<u32> = const { Cell::new(0) };
+        static NATIVE: Cell<Duration> = const { Cell::new(Duration::ZERO) };
+        static HANDED_OFF: Cell<bool> = const { Cell::new(false) };
+    }
+
+    fn timings() -> std::sync::MutexGuard<'static, VecDeque<CallTiming>> {
+        match TIMINGS.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+
+    pub(super) fn recent() -> Vec<CallTiming> {
+        timings().iter().copied().collect()
+    }
+
+    pub(super) fn clear() {
+        timings().clear();
+    }
+
+    pub(super) struct Scope {
+        operation: &'static str,
+        start: Instant,
+        #[cfg(feature = "profiling-tracing")]
+        _span: tracing::span::EnteredSpan,
+    }
+
+    impl Scope {
+        /// Returns `None` for a nested call.
+        pub(super) fn start(operation: &'static str) -> Option<Self> {
+            let depth = DEPTH.get();
+            DEPTH.set(depth + 1);
+            if depth > 0 {
+                return None;
+            }
+            NATIVE.set(Duration::ZERO);
+            HANDED_OFF.set(false);
+            Some(Self {
+                operation,
+                start: Instant::now(),
+                #[cfg(feature = "profiling-tracing")]
+                _span: tracing::trace_span!("sz_call", operation).entered(),
+            })
+        }
+    }
+
+    impl Drop for super::CallScope {
+        fn drop(&mut self) {
+            DEPTH.set(DEPTH.get().saturating_sub(1));
+            let Some(scope) = self.inner.take() else {
+                return;
+            };
+            if HANDED_OFF.get() {
+                return;
+            }
+            let timing = CallTiming {
+                operation: scope.operation,
+                total: scope.start.elapsed(),
+                native: NATIVE.get(),
+            };
+            let mut timings = timings();
+            if timings.len() == RING_CAPACITY {
+                timings.pop_front();
+            }
+            timings.push_back(timing);
+        }
+    }
+
+    pub(super) fn native<T>(call: impl FnOnce() -> T) -> T {
+        #[cfg(feature = "profiling-tracing")]
+        let _span = tracing::trace_span!("native").entered();
+        let start = Instant::now();
+        let result = call();
+        NATIVE.set(NATIVE.get() + start.elapsed());
+        result
+    }
+
+    pub(super) fn hand_off() {
+        HANDED_OFF.set(true);
+    }
+}
+
+#[cfg(all(test, feature = "profiling"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_calls_record_once() {
+        {
+            let _outer = call("test_outer");
+            let _inner = call("test_inner");
+            native(|| std::thread::sleep(Duration::from_millis(2)));
+        }
+        {
+            let _handed_off = call("test_handed_off");
+            hand_off();
+        }
+        let timings: Vec<_> = recent_call_timings()
+            .into_iter()
+            .filter(|t| t.operation.starts_with("test_"))
+            .collect();
+        assert_eq!(timings.len(), 1);
+        assert_eq!(timings[0].operation, "test_outer");
+        assert!(timings[0].native >= Duration::from_millis(2));
+        assert!(timings[0].total >= timings[0].native);
+    }
+}
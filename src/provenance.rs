@@ -0,0 +1,275 @@
+//! Load provenance for records.
+//!
+//! The engine keeps any attribute it does not recognize as payload in the
+//! record's `JSON_DATA`, so provenance can travel with the record itself.
+//! [`LoadMetadata`] writes a load ID, source file and batch ID into records
+//! as the `LOAD_ID`, `LOAD_SOURCE_FILE` and `LOAD_BATCH_ID` attributes, and
+//! reads them back from `get_record` output for audits. A [`LoadSession`]
+//! tags every record added through it.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::prelude::*;
+//! use sz_rust_sdk::provenance::{LoadMetadata, LoadSession};
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_provenance")?;
+//! let engine = env.get_engine()?;
+//! let session = LoadSession::new(
+//!     &*engine,
+//!     LoadMetadata::generate().with_source_file("customers.jsonl"),
+//! );
+//! session.add_record("TEST", "1001", r#"{"NAME_FULL": "Ann Lee"}"#, None)?;
+//!
+//! let record = engine.get_record("TEST", "1001", None)?;
+//! let metadata = LoadMetadata::from_record(&record)?.expect("record was tagged");
+//! assert_eq!(metadata.load_id, session.metadata().load_id);
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::control::ControlHandle;
+use crate::error::{SzError, SzResult};
+use crate::flags::SzFlags;
+use crate::ingest::{self, LoadOptions, LoadSummary};
+use crate::traits::SzEngine;
+use crate::types::JsonString;
+use serde_json::Value;
+use std::io::BufRead;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Record attribute holding the load ID.
+pub const LOAD_ID_ATTR: &str = "LOAD_ID";
+/// Record attribute holding the source file.
+pub const LOAD_SOURCE_FILE_ATTR: &str = "LOAD_SOURCE_FILE";
+/// Record attribute holding the batch ID.
+pub const LOAD_BATCH_ID_ATTR: &str = "LOAD_BATCH_ID";
+
+/// Where a record came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoadMetadata {
+    /// Identifies the load run
+    pub load_id: String,
+    /// File or object the record was read from
+    pub source_file: Option<String>,
+    /// Batch within the load
+    pub batch_id: Option<String>,
+}
+
+impl LoadMetadata {
+    /// Creates metadata with the given load ID.
+    pub fn new(load_id: impl Into<String>) -> Self {
+        Self {
+            load_id: load_id.into(),
+            source_file: None,
+            batch_id: None,
+        }
+    }
+
+    /// Creates metadata with a load ID unique to this process and moment,
+    /// e.g. `load-1760600000123-4242-0`.
+    pub fn generate() -> Self {
+        static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        Self::new(format!(
+            "load-{millis}-{}-{}",
+            std::process::id(),
+            SEQUENCE.fetch_add(1, Ordering::Relaxed)
+        ))
+    }
+
+    /// Sets the source file.
+    pub fn with_source_file(mut self, source_file: impl Into<String>) -> Self {
+        self.source_file = Some(source_file.into());
+        self
+    }
+
+    /// Sets the batch ID.
+    pub fn with_batch_id(mut self, batch_id: impl Into<String>) -> Self {
+        self.batch_id = Some(batch_id.into());
+        self
+    }
+
+    /// Returns `record` with the metadata attributes added.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the record is not a JSON object or already
+    /// carries a metadata attribute with a different value.
+    pub fn apply(&self, record: &str) -> SzResult<String> {
+        let mut document = ingest::parse_object(record)?;
+        let fields = [
+            (LOAD_ID_ATTR, Some(&self.load_id)),
+            (LOAD_SOURCE_FILE_ATTR, self.source_file.as_ref()),
+            (LOAD_BATCH_ID_ATTR, self.batch_id.as_ref()),
+        ];
+        for (name, value) in fields {
+            let Some(value) = value else { continue };
+            match document.get(name) {
+                Some(Value::String(existing)) if existing == value => {}
+                Some(existing) => {
+                    return Err(SzError::bad_input(format!(
+                        "record {name} {existing} conflicts with load metadata {value:?}"
+                    )));
+                }
+                None => {
+                    document.insert(name.to_string(), Value::String(value.clone()));
+                }
+            }
+        }
+        Ok(Value::Object(document).to_string())
+    }
+
+    /// Reads the metadata back from a record document or a `get_record`
+    /// response (whose `JSON_DATA` holds the record). Returns `None` when the
+    /// record has no `LOAD_ID`.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::Json` if the document is not valid JSON.
+    pub fn from_record(json: &str) -> SzResult<Option<Self>> {
+        let document: Value = serde_json::from_str(json)?;
+        let record = document.get("JSON_DATA").unwrap_or(&document);
+        let field = |name: &str| record[name].as_str().map(str::to_string);
+        Ok(field(LOAD_ID_ATTR).map(|load_id| Self {
+            load_id,
+            source_file: field(LOAD_SOURCE_FILE_ATTR),
+            batch_id: field(LOAD_BATCH_ID_ATTR),
+        }))
+    }
+}
+
+/// Adds records tagged with one [`LoadMetadata`].
+///
+/// The session can be shared between loader threads.
+pub struct LoadSession<'a> {
+    engine: &'a dyn SzEngine,
+    metadata: LoadMetadata,
+    added: AtomicU64,
+}
+
+impl<'a> LoadSession<'a> {
+    /// Creates a session adding records to `engine`.
+    pub fn new(engine: &'a dyn SzEngine, metadata: LoadMetadata) -> Self {
+        Self {
+            engine,
+            metadata,
+            added: AtomicU64::new(0),
+        }
+    }
+
+    /// The metadata written into each record.
+    pub fn metadata(&self) -> &LoadMetadata {
+        &self.metadata
+    }
+
+    /// Number of records added through this session. Records from a
+    /// `load_from_reader` call that failed part-way are not counted.
+    pub fn records_added(&self) -> u64 {
+        self.added.load(Ordering::Relaxed)
+    }
+
+    /// Tags `record_definition` and adds it, like `SzEngine::add_record`.
+    ///
+    /// # Errors
+    ///
+    /// Any error from [`LoadMetadata::apply`] or `SzEngine::add_record`.
+    pub fn add_record(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        record_definition: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        let tagged = self.metadata.apply(record_definition)?;
+        let info = self
+            .engine
+            .add_record(data_source_code, record_id, &tagged, flags)?;
+        self.added.fetch_add(1, Ordering::Relaxed);
+        Ok(info)
+    }
+
+    /// Loads line-delimited JSON like [`ingest::load_from_reader`], tagging
+    /// each record.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`ingest::load_from_reader`], plus a `SzError::BadInput`
+    /// naming the line when a record's metadata attributes conflict.
+    pub fn load_from_reader<R: BufRead>(&self, reader: R) -> SzResult<LoadSummary> {
+        self.load_from_reader_with_control(reader, &ControlHandle::new())
+    }
+
+    /// Loads line-delimited JSON like
+    /// [`ingest::load_from_reader_with_control`], tagging each record.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`load_from_reader`](Self::load_from_reader).
+    pub fn load_from_reader_with_control<R: BufRead>(
+        &self,
+        reader: R,
+        control: &ControlHandle,
+    ) -> SzResult<LoadSummary> {
+        let mut options = LoadOptions::new().with_control(control);
+        options.metadata = Some(&self.metadata);
+        let result = ingest::load_from_reader_with_options(self.engine, reader, options);
+        if let Ok(summary) = &result {
+            self.added
+                .fetch_add(summary.records_loaded, Ordering::Relaxed);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_and_read_back() {
+        let metadata = LoadMetadata::new("load-1")
+            .with_source_file("people.jsonl")
+            .with_batch_id("7");
+        let tagged = metadata.apply(r#"{"NAME_FULL":"Ann Lee"}"#).unwrap();
+        assert_eq!(
+            LoadMetadata::from_record(&tagged).unwrap(),
+            Some(metadata.clone())
+        );
+
+        // From a get_record response
+        let response = format!(r#"{{"DATA_SOURCE":"TEST","RECORD_ID":"1","JSON_DATA":{tagged}}}"#);
+        assert_eq!(
+            LoadMetadata::from_record(&response).unwrap(),
+            Some(metadata)
+        );
+        assert_eq!(
+            LoadMetadata::from_record(r#"{"NAME_FULL":"A"}"#).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_apply_conflicts() {
+        let metadata = LoadMetadata::new("load-1");
+        assert!(metadata.apply(r#"{"LOAD_ID":"load-1"}"#).is_ok());
+        assert!(matches!(
+            metadata.apply(r#"{"LOAD_ID":"load-2"}"#),
+            Err(SzError::BadInput(_))
+        ));
+        // Unset fields are not checked
+        assert!(metadata.apply(r#"{"LOAD_BATCH_ID":"3"}"#).is_ok());
+    }
+
+    #[test]
+    fn test_generate_is_unique() {
+        assert_ne!(
+            LoadMetadata::generate().load_id,
+            LoadMetadata::generate().load_id
+        );
+    }
+}
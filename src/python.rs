@@ -0,0 +1,237 @@
+//! Python bindings for the high-level subsystems.
+//!
+//! With the `python` feature the crate builds as the Python extension module
+//! `sz_rust_sdk`; `pyproject.toml` builds and installs it with maturin
+//! (`maturin develop --release`). Python orchestrates, while loading,
+//! redo processing and exports run in Rust with the GIL released:
+//!
+//! | Python | Rust |
+//! |--------|------|
+//! | `SzRepository(module_name, settings, verbose_logging=False)` | `SzEnvironmentCore::get_instance` and an engine |
+//! | `add_record`, `get_entity_by_record_id`, `search_by_attributes` | the `SzEngine` methods of the same name |
+//! | `load_file(path)` | `ingest::load_from_reader` |
+//! | `drain_redo(count_below, max_workers=4)` | `redo::SzRedoProcessor::drain_until` |
+//! | `export_entities(flags=None)` | `export::SzExporter::export_entities_stream`, as an iterator of JSON strings |
+//! | `export_records_csv(path, flags=None)` | `report::write_records_csv` |
+//!
+//! Flags are `SzFlags` bits. `None`, the default, uses the method's default
+//! flags; 0 is an explicitly empty set (`SZ_NO_FLAGS`), as in the C API.
+//! Failures raise `sz_rust_sdk.SzError`, whose message starts with the error
+//! category, e.g. `NotFound: ...`.
+//!
+//! ```python
+//! import sz_rust_sdk
+//!
+//! repo = sz_rust_sdk.SzRepository("loader", settings)
+//! loaded = repo.load_file("customers.jsonl")
+//! repo.drain_redo(0, max_workers=8)
+//! for entity in repo.export_entities():
+//!     ...
+//! repo.destroy()
+//! ```
+
+use crate::core::SzEnvironmentCore;
+use crate::error::SzError as RustSzError;
+use crate::export::{ExportStream, SzExporter};
+use crate::flags::SzFlags;
+use crate::redo::SzRedoProcessor;
+use crate::traits::{SzEngine, SzEnvironment};
+use crate::types::EntityRef;
+use crate::{ingest, report};
+use pyo3::create_exception;
+use pyo3::exceptions::PyException;
+use pyo3::prelude::*;
+use std::io::{BufReader, BufWriter};
+use std::sync::{Arc, Mutex};
+
+create_exception!(sz_rust_sdk, SzError, PyException, "A Senzing SDK error.");
+
+/// Converts an SDK error into a Python `SzError`.
+fn to_py(e: RustSzError) -> PyErr {
+    SzError::new_err(format!("{}: {e}", e.category()))
+}
+
+fn flags_arg(flags: Option<u64>) -> Option<SzFlags> {
+    flags.map(SzFlags::from_bits_retain)
+}
+
+/// An initialized environment and its engine.
+#[pyclass(module = "sz_rust_sdk")]
+struct SzRepository {
+    env: Arc<SzEnvironmentCore>,
+    engine: Box<dyn SzEngine>,
+}
+
+impl SzRepository {
+    fn io_error(path: &str, e: std::io::Error) -> PyErr {
+        to_py(RustSzError::bad_input(format!(
+            "Failed opening {path}: {e}"
+        )))
+    }
+}
+
+#[pymethods]
+impl SzRepository {
+    #[new]
+    #[pyo3(signature = (module_name, settings, verbose_logging = false))]
+    fn new(module_name: &str, settings: &str, verbose_logging: bool) -> PyResult<Self> {
+        let env = SzEnvironmentCore::get_instance(module_name, settings, verbose_logging)
+            .map_err(to_py)?;
+        let engine = env.get_engine().map_err(to_py)?;
+        Ok(Self { env, engine })
+    }
+
+    #[pyo3(signature = (data_source_code, record_id, record_definition, flags = None))]
+    fn add_record(
+        &self,
+        py: Python<'_>,
+        data_source_code: &str,
+        record_id: &str,
+        record_definition: &str,
+        flags: Option<u64>,
+    ) -> PyResult<String> {
+        py.detach(|| {
+            self.engine.add_record(
+                data_source_code,
+                record_id,
+                record_definition,
+                flags_arg(flags),
+            )
+        })
+        .map_err(to_py)
+    }
+
+    #[pyo3(signature = (data_source_code, record_id, flags = None))]
+    fn get_entity_by_record_id(
+        &self,
+        py: Python<'_>,
+        data_source_code: &str,
+        record_id: &str,
+        flags: Option<u64>,
+    ) -> PyResult<String> {
+        py.detach(|| {
+            self.engine.get_entity(
+                EntityRef::Record {
+                    data_source: data_source_code,
+                    record_id,
+                },
+                flags_arg(flags),
+            )
+        })
+        .map_err(to_py)
+    }
+
+    #[pyo3(signature = (attributes, flags = None))]
+    fn search_by_attributes(
+        &self,
+        py: Python<'_>,
+        attributes: &str,
+        flags: Option<u64>,
+    ) -> PyResult<String> {
+        py.detach(|| {
+            self.engine
+                .search_by_attributes(attributes, None, flags_arg(flags))
+        })
+        .map_err(to_py)
+    }
+
+    /// Loads a JSON-lines file with the bulk loader; returns the number of
+    /// records loaded.
+    fn load_file(&self, py: Python<'_>, path: &str) -> PyResult<u64> {
+        let file = std::fs::File::open(path).map_err(|e| Self::io_error(path, e))?;
+        py.detach(|| ingest::load_from_reader(self.engine.as_ref(), BufReader::new(file)))
+            .map(|summary| summary.records_loaded)
+            .map_err(to_py)
+    }
+
+    /// Processes redo records until fewer than `count_below` remain; returns
+    /// `(processed, failed, remaining)`.
+    #[pyo3(signature = (count_below, max_workers = crate::redo::DEFAULT_MAX_WORKERS))]
+    fn drain_redo(
+        &self,
+        py: Python<'_>,
+        count_below: i64,
+        max_workers: usize,
+    ) -> PyResult<(u64, u64, i64)> {
+        let processor = SzRedoProcessor::new(self.env.clone()).with_workers(1, max_workers);
+        py.detach(|| processor.drain_until(count_below))
+            .map(|summary| (summary.processed, summary.failed, summary.remaining))
+            .map_err(to_py)
+    }
+
+    /// Starts an export; iterating the result yields one entity JSON
+    /// document per entity.
+    #[pyo3(signature = (flags = None))]
+    fn export_entities(&self, flags: Option<u64>) -> PyResult<EntityIterator> {
+        let stream = SzExporter::new(self.env.clone())
+            .export_entities_stream(flags_arg(flags))
+            .map_err(to_py)?;
+        Ok(EntityIterator {
+            stream: Mutex::new(stream),
+        })
+    }
+
+    /// Writes one CSV row per exported record to `path`; returns the number
+    /// of rows written.
+    #[pyo3(signature = (path, flags = None))]
+    fn export_records_csv(
+        &self,
+        py: Python<'_>,
+        path: &str,
+        flags: Option<u64>,
+    ) -> PyResult<usize> {
+        let file = std::fs::File::create(path).map_err(|e| Self::io_error(path, e))?;
+        let env = self.env.clone();
+        py.detach(|| {
+            let stream = SzExporter::new(env).export_entities_stream(flags_arg(flags))?;
+            report::write_records_csv(stream, BufWriter::new(file))
+        })
+        .map_err(to_py)
+    }
+
+    /// Destroys the environment. The repository cannot be used afterwards.
+    fn destroy(&self) -> PyResult<()> {
+        self.env.clone().destroy().map_err(to_py)
+    }
+}
+
+/// Iterator over the entities of a running export, as JSON strings.
+#[pyclass(module = "sz_rust_sdk")]
+struct EntityIterator {
+    stream: Mutex<ExportStream>,
+}
+
+#[pymethods]
+impl EntityIterator {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&self, py: Python<'_>) -> PyResult<Option<String>> {
+        let next = py.detach(|| {
+            let mut stream = match self.stream.lock() {
+                Ok(guard) => guard,
+                Err(poisoned) => poisoned.into_inner(),
+            };
+            stream.next()
+        });
+        match next {
+            Some(entity) => {
+                let entity = entity.map_err(to_py)?;
+                serde_json::to_string(&entity)
+                    .map(Some)
+                    .map_err(|e| to_py(e.into()))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// The `sz_rust_sdk` Python module.
+#[pymodule]
+fn sz_rust_sdk(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<SzRepository>()?;
+    m.add_class::<EntityIterator>()?;
+    m.add("SzError", m.py().get_type::<SzError>())?;
+    Ok(())
+}
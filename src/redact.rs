@@ -0,0 +1,204 @@
+//! Redaction of personal data in debug output.
+//!
+//! Response models carry names, addresses and identifiers from the records
+//! they describe. Their `Debug` output replaces those values with
+//! `<redacted>`, so a stray `dbg!` or an error log that formats a response
+//! does not leak them. Structure, IDs, codes and scores are kept:
+//!
+//! | Type | Redacted fields |
+//! |------|-----------------|
+//! | `ResolvedEntity`, `RelatedEntity` (owned and borrowed) | `entity_name` |
+//! | `FeatureScore` | `inbound_feat_desc`, `candidate_feat_desc` |
+//! | `CandidateKey` | `feat_desc` |
+//! | `SearchExplanation` | `disclosed_relations` |
+//! | `RedoRecord` | `json` |
+//! | `RecordOp` | `record_definition` |
+//! | `analysis::FeatureDiff` | `only_in_a`, `only_in_b` |
+//! | `report::RecordRow` | `entity_name` |
+//!
+//! Redaction is on by default and applies process-wide; turn it off with
+//! [`set_enabled`] when debugging against test data. Wrap your own values in
+//! [`Sensitive`] to give them the same treatment, and pass engine error
+//! messages through [`message`] before sharing them.
+//!
+//! # Example
+//!
+//! ```
+//! use sz_rust_sdk::models::ResolvedEntity;
+//! use sz_rust_sdk::types::EntityId;
+//!
+//! let entity = ResolvedEntity {
+//!     entity_id: EntityId(1),
+//!     entity_name: Some("John Smith".to_string()),
+//!     records: Vec::new(),
+//! };
+//! let debug = format!("{entity:?}");
+//! assert!(debug.contains("entity_id: EntityId(1)"));
+//! assert!(!debug.contains("John Smith"));
+//! ```
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Placeholder written in place of a redacted value.
+pub const REDACTED: &str = "<redacted>";
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+/// Turns redaction on or off for the whole process.
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// Returns whether redaction is on.
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// A value that formats as [`REDACTED`] while redaction is on.
+///
+/// Both `Debug` and `Display` are redacted; with redaction off they format
+/// the wrapped value.
+#[derive(Clone, Copy)]
+pub struct Sensitive<'a, T: ?Sized>(pub &'a T);
+
+impl<T: fmt::Debug + ?Sized> fmt::Debug for Sensitive<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if is_enabled() {
+            f.write_str(REDACTED)
+        } else {
+            fmt::Debug::fmt(self.0, f)
+        }
+    }
+}
+
+impl<T: fmt::Display + ?Sized> fmt::Display for Sensitive<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if is_enabled() {
+            f.write_str(REDACTED)
+        } else {
+            fmt::Display::fmt(self.0, f)
+        }
+    }
+}
+
+/// Redacts the record values in an engine error message while redaction is
+/// on.
+///
+/// Engine messages quote the values they reject and sometimes include the
+/// record JSON. Quoted text and `{...}` blocks become [`REDACTED`]; error
+/// codes and the wording around them are kept.
+///
+/// # Example
+///
+/// ```
+/// use sz_rust_sdk::redact;
+///
+/// assert_eq!(
+///     redact::message(r#"0023E|Conflicting DATA_SOURCE values 'CUSTOMERS' and 'WATCHLIST'"#),
+///     "0023E|Conflicting DATA_SOURCE values <redacted> and <redacted>"
+/// );
+/// ```
+pub fn message(message: &str) -> String {
+    if is_enabled() {
+        redact_message(message)
+    } else {
+        message.to_string()
+    }
+}
+
+fn redact_message(message: &str) -> String {
+    let chars: Vec<char> = message.chars().collect();
+    let mut redacted = String::with_capacity(message.len());
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let opens_quote =
+            matches!(c, '\'' | '"' | '`') && (i == 0 || !chars[i - 1].is_alphanumeric());
+        let end = if opens_quote {
+            // An apostrophe inside a word does not close the quote
+            (i + 1..chars.len()).find(|&j| {
+                chars[j] == c && chars.get(j + 1).is_none_or(|next| !next.is_alphanumeric())
+            })
+        } else if c == '{' {
+            let mut depth = 0usize;
+            let close = (i..chars.len()).find(|&j| {
+                match chars[j] {
+                    '{' => depth += 1,
+                    '}' => depth -= 1,
+                    _ => {}
+                }
+                depth == 0
+            });
+            // A truncated JSON block runs to the end of the message
+            Some(close.unwrap_or(chars.len() - 1))
+        } else {
+            None
+        };
+        match end {
+            Some(end) => {
+                redacted.push_str(REDACTED);
+                i = end + 1;
+            }
+            None => {
+                redacted.push(c);
+                i += 1;
+            }
+        }
+    }
+    redacted
+}
+
+/// Redacts the contents of an optional value, keeping whether it is set.
+pub(crate) fn option<T: fmt::Debug>(value: &Option<T>) -> Option<Sensitive<'_, T>> {
+    value.as_ref().map(Sensitive)
+}
+
+/// Redacts each element of a list, keeping its length.
+pub(crate) fn each<T: fmt::Debug>(values: &[T]) -> Vec<Sensitive<'_, T>> {
+    values.iter().map(Sensitive).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sensitive_formatting() {
+        let name = Some("Jane Doe".to_string());
+        let descriptions = vec!["123-45-6789".to_string(), "Main St".to_string()];
+
+        assert!(is_enabled());
+        assert_eq!(format!("{:?}", option(&name)), "Some(<redacted>)");
+        assert_eq!(format!("{:?}", option::<String>(&None)), "None");
+        assert_eq!(
+            format!("{:?}", each(&descriptions)),
+            "[<redacted>, <redacted>]"
+        );
+        assert_eq!(Sensitive("Jane Doe").to_string(), REDACTED);
+
+        set_enabled(false);
+        let unredacted = format!("{:?}", option(&name));
+        let displayed = Sensitive("Jane Doe").to_string();
+        set_enabled(true);
+        assert_eq!(unredacted, r#"Some("Jane Doe")"#);
+        assert_eq!(displayed, "Jane Doe");
+    }
+
+    #[test]
+    fn test_redact_message() {
+        assert_eq!(
+            redact_message(r#"0002E|Invalid value "Jane Doe" for 'NAME_FULL'"#),
+            "0002E|Invalid value <redacted> for <redacted>"
+        );
+        assert_eq!(
+            redact_message(r#"0007E|Record doesn't parse: {"A":{"B":"x"}} at 3"#),
+            "0007E|Record doesn't parse: <redacted> at 3"
+        );
+        assert_eq!(
+            redact_message(r#"bad JSON {"NAME_FULL":"Jane"#),
+            "bad JSON <redacted>"
+        );
+        assert_eq!(redact_message("unclosed 'quote"), "unclosed 'quote");
+    }
+}
@@ -0,0 +1,440 @@
+//! Redo processing with backpressure.
+//!
+//! The engine queues redo records while it loads data; they have to be
+//! processed with `process_redo_record` for entities to reach their final
+//! resolution. [`SzRedoProcessor`] does this on a pool of worker threads and
+//! sizes the pool from the redo lag reported by `count_redo_records`:
+//!
+//! * [`drain_until`](SzRedoProcessor::drain_until) catches up with all
+//!   workers until the lag falls below a given count, then returns.
+//! * [`run_continuous`](SzRedoProcessor::run_continuous) runs until stopped.
+//!   While the lag is at or below the target lag only the minimum number of
+//!   workers run, leaving engine capacity for foreground API calls; above it
+//!   workers are added in proportion, up to the maximum at twice the target.
+//!
+//! Either mode can be paused, resumed and stopped through a
+//! [`ControlHandle`] given to [`with_control`](SzRedoProcessor::with_control).
+//! Its [`Tuning`] can lower the number of active workers and change the poll
+//! interval while the processor runs. An [`SzCancellationToken`] given to
+//! [`with_cancellation`](SzRedoProcessor::with_cancellation) stops a run the
+//! same way, for services that share one token across their shutdown.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::prelude::*;
+//! use sz_rust_sdk::redo::SzRedoProcessor;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_redo")?;
+//! let processor = SzRedoProcessor::new(env.clone())
+//!     .with_workers(1, 8)
+//!     .target_lag(500);
+//! let summary = processor.drain_until(100)?;
+//! println!("{} processed, {} still queued", summary.processed, summary.remaining);
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::control::{ControlHandle, Deadline, SzCancellationToken, Tuning};
+use crate::core::SzEnvironmentCore;
+use crate::error::{SzError, SzResult};
+use crate::flags::SzFlags;
+use crate::traits::{SzEngine, SzEnvironment};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::Thread;
+use std::time::Duration;
+
+/// Default minimum number of worker threads in continuous mode.
+pub const DEFAULT_MIN_WORKERS: usize = 1;
+
+/// Default maximum number of worker threads.
+pub const DEFAULT_MAX_WORKERS: usize = 4;
+
+/// Default redo lag that continuous mode handles with the minimum workers.
+pub const DEFAULT_TARGET_LAG: i64 = 1_000;
+
+/// Default interval between lag checks, and the idle wait of a worker that
+/// found the queue empty.
+pub const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Outcome of a redo processing run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RedoSummary {
+    /// Redo records processed successfully
+    pub processed: u64,
+    /// Redo records whose processing failed
+    pub failed: u64,
+    /// Redo records still queued when the run ended
+    pub remaining: i64,
+    /// Whether the run was stopped through its [`ControlHandle`] or
+    /// cancellation token
+    pub stopped: bool,
+}
+
+type FailureCallback = Box<dyn FnMut(&str, &SzError) + Send>;
+
+/// Processes redo records on a worker pool sized from the redo lag.
+pub struct SzRedoProcessor {
+    env: Arc<SzEnvironmentCore>,
+    min_workers: usize,
+    max_workers: usize,
+    target_lag: i64,
+    poll_interval: Duration,
+    flags: Option<SzFlags>,
+    on_failure: Option<Mutex<FailureCallback>>,
+    control: ControlHandle,
+    cancellation: Option<SzCancellationToken>,
+}
+
+impl SzRedoProcessor {
+    /// Creates a processor with the default settings.
+    pub fn new(env: Arc<SzEnvironmentCore>) -> Self {
+        Self {
+            env,
+            min_workers: DEFAULT_MIN_WORKERS,
+            max_workers: DEFAULT_MAX_WORKERS,
+            target_lag: DEFAULT_TARGET_LAG,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            flags: None,
+            on_failure: None,
+            control: ControlHandle::new(),
+            cancellation: None,
+        }
+    }
+
+    /// Sets the worker pool bounds.
+    ///
+    /// `max` below 1 is treated as 1 and `min` is capped at `max`. A `min`
+    /// of 0 lets continuous mode pause entirely while the lag is at or below
+    /// the target.
+    pub fn with_workers(mut self, min: usize, max: usize) -> Self {
+        self.max_workers = max.max(1);
+        self.min_workers = min.min(self.max_workers);
+        self
+    }
+
+    /// Sets the redo lag that continuous mode handles with the minimum
+    /// number of workers. Negative values are treated as 0.
+    pub fn target_lag(mut self, count: i64) -> Self {
+        self.target_lag = count.max(0);
+        self
+    }
+
+    /// Sets how often the lag is checked.
+    pub fn poll_interval(mut self, interval: Duration) -> Self {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Sets the flags passed to each `process_redo_record` call.
+    pub fn with_flags(mut self, flags: SzFlags) -> Self {
+        self.flags = Some(flags);
+        self
+    }
+
+    /// Sets a callback invoked with the redo record JSON and the error when
+    /// processing a record fails.
+    ///
+    /// Called from the worker threads, one call at a time.
+    pub fn on_failure<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(&str, &SzError) + Send + 'static,
+    {
+        self.on_failure = Some(Mutex::new(Box::new(callback)));
+        self
+    }
+
+    /// Sets the handle used to pause, resume and stop runs.
+    ///
+    /// Workers check the handle before fetching each redo record. The handle
+    /// is left in the stopped state when a run returns, so use a new handle
+    /// for each run.
+    ///
+    /// [`Tuning::workers`] caps the number of active workers at or below the
+    /// maximum set with [`with_workers`](Self::with_workers), and
+    /// [`Tuning::poll_interval`] replaces the poll interval; both take effect
+    /// within one poll interval.
+    pub fn with_control(mut self, control: ControlHandle) -> Self {
+        if let Some(token) = &self.cancellation {
+            control.set_cancellation(token.clone());
+        }
+        self.control = control;
+        self
+    }
+
+    /// Stops runs once `token` is cancelled, as a stop through the control
+    /// handle would.
+    ///
+    /// Workers notice the token before fetching the next redo record, and a
+    /// paused run within 50 ms.
+    pub fn with_cancellation(mut self, token: SzCancellationToken) -> Self {
+        self.control.set_cancellation(token.clone());
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Processes redo records with all workers until fewer than
+    /// `count_below` remain queued, or the queue is empty.
+    ///
+    /// A `count_below` of 1 or less drains the queue completely. The lag is
+    /// checked once per poll interval, so a few more records than necessary
+    /// may be processed.
+    ///
+    /// # Errors
+    ///
+    /// * Any error from obtaining an engine, reading the redo count, or
+    ///   fetching a redo record. Failures of individual records are counted
+    ///   in the summary instead.
+    pub fn drain_until(&self, count_below: i64) -> SzResult<RedoSummary> {
+        let engine = self.env.get_engine()?;
+        let count_below = count_below.max(1);
+        let run = Run::new(std::thread::current());
+        if engine.count_redo_records()? < count_below {
+            return self.finish(run, engine.as_ref());
+        }
+
+        run.active.store(self.max_workers, Ordering::SeqCst);
+        std::thread::scope(|scope| {
+            for index in 0..self.max_workers {
+                let (run, engine) = (&run, &engine);
+                scope.spawn(move || {
+                    while !run.done.load(Ordering::SeqCst) {
+                        if index >= self.worker_cap() {
+                            std::thread::park_timeout(self.current_poll_interval());
+                            continue;
+                        }
+                        match self.process_next(engine.as_ref(), run) {
+                            Some(true) => {}
+                            Some(false) | None => break,
+                        }
+                    }
+                    run.worker_exited();
+                });
+            }
+            while run.active.load(Ordering::SeqCst) > 0 && !run.done.load(Ordering::SeqCst) {
+                std::thread::park_timeout(self.current_poll_interval());
+                if self.control.is_stop_requested() {
+                    break;
+                }
+                match engine.count_redo_records() {
+                    Ok(count) if count < count_below => run.done.store(true, Ordering::SeqCst),
+                    Ok(_) => {}
+                    Err(e) => run.fatal(e),
+                }
+            }
+            self.release_workers(&run);
+        });
+        self.finish(run, engine.as_ref())
+    }
+
+    /// Processes redo records until `stop` is set or a stop is requested
+    /// through the control handle, adjusting the number of active workers to
+    /// the redo lag once per poll interval.
+    ///
+    /// Workers that find the queue empty wait one poll interval before
+    /// trying again. Records being processed when `stop` is set are
+    /// finished before this returns.
+    ///
+    /// # Errors
+    ///
+    /// * Any error from obtaining an engine, reading the redo count, or
+    ///   fetching a redo record
+    pub fn run_continuous(&self, stop: &AtomicBool) -> SzResult<RedoSummary> {
+        let engine = self.env.get_engine()?;
+        let run = Run::new(std::thread::current());
+        let desired = AtomicUsize::new(self.min_workers);
+
+        run.active.store(self.max_workers, Ordering::SeqCst);
+        std::thread::scope(|scope| {
+            for index in 0..self.max_workers {
+                let (run, desired, engine) = (&run, &desired, &engine);
+                scope.spawn(move || {
+                    while !run.done.load(Ordering::SeqCst) {
+                        if index >= desired.load(Ordering::SeqCst) {
+                            std::thread::park_timeout(self.current_poll_interval());
+                            continue;
+                        }
+                        match self.process_next(engine.as_ref(), run) {
+                            Some(true) => {}
+                            Some(false) => std::thread::park_timeout(self.current_poll_interval()),
+                            None => break,
+                        }
+                    }
+                    run.worker_exited();
+                });
+            }
+            while !stop.load(Ordering::SeqCst)
+                && !self.control.is_stop_requested()
+                && !run.done.load(Ordering::SeqCst)
+            {
+                match engine.count_redo_records() {
+                    Ok(lag) => desired.store(
+                        workers_for_lag(lag, self.target_lag, self.min_workers, self.max_workers)
+                            .min(self.worker_cap()),
+                        Ordering::SeqCst,
+                    ),
+                    Err(e) => run.fatal(e),
+                }
+                std::thread::park_timeout(self.current_poll_interval());
+            }
+            self.release_workers(&run);
+        });
+        self.finish(run, engine.as_ref())
+    }
+
+    /// Maximum number of active workers, after the control handle's tuning.
+    fn worker_cap(&self) -> usize {
+        match self.control.tuning() {
+            Tuning {
+                workers: Some(workers),
+                ..
+            } => workers.clamp(1, self.max_workers),
+            _ => self.max_workers,
+        }
+    }
+
+    /// Poll interval, after the control handle's tuning.
+    fn current_poll_interval(&self) -> Duration {
+        self.control
+            .tuning()
+            .poll_interval
+            .unwrap_or(self.poll_interval)
+    }
+
+    /// Fetches and processes one redo record.
+    ///
+    /// Returns `Some(true)` when a record was handled, `Some(false)` when the
+    /// queue was empty, and `None` after a fatal error or a stop request.
+    fn process_next(&self, engine: &dyn SzEngine, run: &Run) -> Option<bool> {
+        if !self.control.checkpoint() {
+            return None;
+        }
+        let redo = match engine.get_redo_record() {
+            Ok(redo) if redo.is_empty() => return Some(false),
+            Ok(redo) => redo,
+            Err(e) => {
+                run.fatal(e);
+                return None;
+            }
+        };
+        match engine.process_redo_record(&redo, self.flags) {
+            Ok(_) => {
+                run.processed.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                run.failed.fetch_add(1, Ordering::Relaxed);
+                if let Some(on_failure) = &self.on_failure {
+                    let mut on_failure = match on_failure.lock() {
+                        Ok(guard) => guard,
+                        Err(poisoned) => poisoned.into_inner(),
+                    };
+                    on_failure(&redo, &e);
+                }
+            }
+        }
+        Some(true)
+    }
+
+    /// Ends the run: records whether the handle asked for it, then moves the
+    /// handle to stopping so that paused workers wake up and exit.
+    fn release_workers(&self, run: &Run) {
+        if self.control.is_stop_requested() {
+            run.stopped.store(true, Ordering::SeqCst);
+        }
+        run.done.store(true, Ordering::SeqCst);
+        self.control.stop(Deadline::immediate());
+    }
+
+    fn finish(&self, run: Run, engine: &dyn SzEngine) -> SzResult<RedoSummary> {
+        let result = run.finish(engine);
+        self.control.finished();
+        result
+    }
+}
+
+/// Shared bookkeeping for a running processor.
+struct Run {
+    done: AtomicBool,
+    stopped: AtomicBool,
+    active: AtomicUsize,
+    processed: AtomicU64,
+    failed: AtomicU64,
+    error: Mutex<Option<SzError>>,
+    controller: Thread,
+}
+
+impl Run {
+    fn new(controller: Thread) -> Self {
+        Self {
+            done: AtomicBool::new(false),
+            stopped: AtomicBool::new(false),
+            active: AtomicUsize::new(0),
+            processed: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            error: Mutex::new(None),
+            controller,
+        }
+    }
+
+    /// Records the first fatal error and stops the run.
+    fn fatal(&self, e: SzError) {
+        let mut error = match self.error.lock() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        error.get_or_insert(e);
+        self.done.store(true, Ordering::SeqCst);
+        self.controller.unpark();
+    }
+
+    fn worker_exited(&self) {
+        self.active.fetch_sub(1, Ordering::SeqCst);
+        self.controller.unpark();
+    }
+
+    fn finish(self, engine: &dyn SzEngine) -> SzResult<RedoSummary> {
+        let error = match self.error.into_inner() {
+            Ok(error) => error,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if let Some(e) = error {
+            return Err(e);
+        }
+        Ok(RedoSummary {
+            processed: self.processed.into_inner(),
+            failed: self.failed.into_inner(),
+            remaining: engine.count_redo_records()?,
+            stopped: self.stopped.into_inner(),
+        })
+    }
+}
+
+/// Number of workers for a redo lag: `min` up to the target, `max` from
+/// twice the target, and linear in between.
+fn workers_for_lag(lag: i64, target: i64, min: usize, max: usize) -> usize {
+    if lag <= target {
+        return min;
+    }
+    if target == 0 || lag >= target.saturating_mul(2) {
+        return max;
+    }
+    let extra = (max - min) as i64 * (lag - target) / target;
+    min + extra as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_workers_for_lag() {
+        assert_eq!(workers_for_lag(0, 1_000, 1, 9), 1);
+        assert_eq!(workers_for_lag(1_000, 1_000, 1, 9), 1);
+        assert_eq!(workers_for_lag(1_500, 1_000, 1, 9), 5);
+        assert_eq!(workers_for_lag(2_000, 1_000, 1, 9), 9);
+        assert_eq!(workers_for_lag(50_000, 1_000, 1, 9), 9);
+        assert_eq!(workers_for_lag(1, 0, 0, 4), 4);
+        assert_eq!(workers_for_lag(0, 0, 0, 4), 0);
+    }
+}
@@ -0,0 +1,300 @@
+//! Bulk reevaluation.
+//!
+//! After configuration changes that affect resolution, existing entities need
+//! to be reevaluated. [`ReevaluationCampaign`] drives `reevaluate_entity` or
+//! `reevaluate_record` over a list of targets with a fixed number of worker
+//! threads, reports progress after every target, and can resume an
+//! interrupted run from a checkpoint.
+//!
+//! # Resuming
+//!
+//! Targets are processed in a fixed order. [`CampaignProgress::checkpoint`]
+//! is the number of leading targets that are finished; persist it from the
+//! progress callback and pass it to [`ReevaluationCampaign::resume_from`] to
+//! skip them on the next run. Targets after the checkpoint may be reevaluated
+//! twice after a restart, which is harmless.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::prelude::*;
+//! use sz_rust_sdk::reevaluate::ReevaluationCampaign;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_reevaluate")?;
+//! let summary = ReevaluationCampaign::for_data_source(env.clone(), "CUSTOMERS")?
+//!     .with_concurrency(8)
+//!     .on_progress(|p| println!("{}/{} (checkpoint {})", p.completed, p.total, p.checkpoint))
+//!     .run()?;
+//! println!("{} reevaluated, {} failed", summary.reevaluated, summary.failures.len());
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::core::SzEnvironmentCore;
+use crate::error::{SzError, SzResult};
+use crate::flags::SzFlags;
+use crate::traits::{SzEngine, SzEnvironment};
+use crate::types::EntityId;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Default number of worker threads.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Something to reevaluate.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ReevaluationTarget {
+    /// Reevaluate an entity with `reevaluate_entity`
+    Entity(EntityId),
+    /// Reevaluate a record with `reevaluate_record`
+    Record {
+        /// Data source code
+        data_source: String,
+        /// Record identifier
+        record_id: String,
+    },
+}
+
+/// Progress of a running campaign.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CampaignProgress {
+    /// Number of targets in the campaign, including skipped ones
+    pub total: usize,
+    /// Targets finished in this run, successfully or not
+    pub completed: usize,
+    /// Targets that failed in this run
+    pub failed: usize,
+    /// Number of leading targets that are finished; resume from here
+    pub checkpoint: usize,
+}
+
+/// Outcome of [`ReevaluationCampaign::run`].
+#[derive(Debug)]
+pub struct CampaignSummary {
+    /// Targets reevaluated successfully in this run
+    pub reevaluated: usize,
+    /// Targets that failed, with their errors
+    pub failures: Vec<(ReevaluationTarget, SzError)>,
+    /// Final checkpoint (equal to the target count when the run completed)
+    pub checkpoint: usize,
+}
+
+type ProgressCallback = Box<dyn FnMut(&CampaignProgress) + Send>;
+
+/// Reevaluates a list of entities or records with bounded concurrency.
+pub struct ReevaluationCampaign {
+    env: Arc<SzEnvironmentCore>,
+    targets: Vec<ReevaluationTarget>,
+    concurrency: usize,
+    start_at: usize,
+    flags: Option<SzFlags>,
+    on_progress: Option<ProgressCallback>,
+}
+
+impl ReevaluationCampaign {
+    /// Creates a campaign over the given targets, processed in the given order.
+    pub fn new(env: Arc<SzEnvironmentCore>, targets: Vec<ReevaluationTarget>) -> Self {
+        Self {
+            env,
+            targets,
+            concurrency: DEFAULT_CONCURRENCY,
+            start_at: 0,
+            flags: None,
+            on_progress: None,
+        }
+    }
+
+    /// Creates a campaign that reevaluates the given entities.
+    pub fn for_entities(
+        env: Arc<SzEnvironmentCore>,
+        entity_ids: impl IntoIterator<Item = EntityId>,
+    ) -> Self {
+        let targets = entity_ids
+            .into_iter()
+            .map(ReevaluationTarget::Entity)
+            .collect();
+        Self::new(env, targets)
+    }
+
+    /// Creates a campaign that reevaluates every record of a data source.
+    ///
+    /// The records are found with an entity export and sorted by record ID,
+    /// so the order (and therefore any checkpoint) is stable between runs as
+    /// long as the data source is not modified.
+    ///
+    /// # Errors
+    ///
+    /// * Any error from starting or reading the export
+    pub fn for_data_source(env: Arc<SzEnvironmentCore>, data_source_code: &str) -> SzResult<Self> {
+        let engine = env.get_engine()?;
+        let record_ids = crate::export::data_source_record_ids(engine.as_ref(), data_source_code)?;
+        drop(engine);
+
+        let targets = record_ids
+            .into_iter()
+            .map(|record_id| ReevaluationTarget::Record {
+                data_source: data_source_code.to_string(),
+                record_id,
+            })
+            .collect();
+        Ok(Self::new(env, targets))
+    }
+
+    /// Sets the number of worker threads. A value of 0 is treated as 1.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Skips the first `checkpoint` targets, as reported by an earlier run.
+    pub fn resume_from(mut self, checkpoint: usize) -> Self {
+        self.start_at = checkpoint;
+        self
+    }
+
+    /// Sets the flags passed to each reevaluate call.
+    pub fn with_flags(mut self, flags: SzFlags) -> Self {
+        self.flags = Some(flags);
+        self
+    }
+
+    /// Sets a callback invoked after each target finishes.
+    ///
+    /// Called from the worker threads, one call at a time.
+    pub fn on_progress<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(&CampaignProgress) + Send + 'static,
+    {
+        self.on_progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Returns the campaign's targets in processing order.
+    pub fn targets(&self) -> &[ReevaluationTarget] {
+        &self.targets
+    }
+
+    /// Runs the campaign to completion.
+    ///
+    /// Failures of individual targets are collected in the summary rather
+    /// than stopping the run.
+    ///
+    /// # Errors
+    ///
+    /// * Any error from obtaining an engine from the environment
+    pub fn run(self) -> SzResult<CampaignSummary> {
+        let engine = self.env.get_engine()?;
+        let total = self.targets.len();
+        let start_at = self.start_at.min(total);
+
+        let state = Mutex::new(RunState {
+            finished: vec![false; total - start_at],
+            progress: CampaignProgress {
+                total,
+                checkpoint: start_at,
+                ..CampaignProgress::default()
+            },
+            failures: Vec::new(),
+            on_progress: self.on_progress,
+        });
+        let next = AtomicUsize::new(start_at);
+
+        std::thread::scope(|scope| {
+            for _ in 0..self.concurrency.min(total - start_at) {
+                scope.spawn(|| {
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(target) = self.targets.get(index) else {
+                            break;
+                        };
+                        let result = reevaluate(engine.as_ref(), target, self.flags);
+
+                        let mut state = match state.lock() {
+                            Ok(guard) => guard,
+                            Err(poisoned) => poisoned.into_inner(),
+                        };
+                        state.finish(index - start_at, start_at, target, result);
+                    }
+                });
+            }
+        });
+
+        let state = match state.into_inner() {
+            Ok(state) => state,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        Ok(CampaignSummary {
+            reevaluated: state.progress.completed - state.progress.failed,
+            failures: state.failures,
+            checkpoint: state.progress.checkpoint,
+        })
+    }
+}
+
+/// Shared bookkeeping for a running campaign.
+struct RunState {
+    /// Finished flags for the targets after the starting checkpoint
+    finished: Vec<bool>,
+    progress: CampaignProgress,
+    failures: Vec<(ReevaluationTarget, SzError)>,
+    on_progress: Option<ProgressCallback>,
+}
+
+impl RunState {
+    fn finish(
+        &mut self,
+        offset: usize,
+        start_at: usize,
+        target: &ReevaluationTarget,
+        result: SzResult<()>,
+    ) {
+        self.finished[offset] = true;
+        self.progress.completed += 1;
+        if let Err(e) = result {
+            self.progress.failed += 1;
+            self.failures.push((target.clone(), e));
+        }
+        self.progress.checkpoint =
+            start_at + advance_checkpoint(&self.finished, self.progress.checkpoint - start_at);
+        if let Some(on_progress) = self.on_progress.as_mut() {
+            on_progress(&self.progress);
+        }
+    }
+}
+
+fn reevaluate(
+    engine: &dyn SzEngine,
+    target: &ReevaluationTarget,
+    flags: Option<SzFlags>,
+) -> SzResult<()> {
+    match target {
+        ReevaluationTarget::Entity(entity_id) => engine.reevaluate_entity(*entity_id, flags),
+        ReevaluationTarget::Record {
+            data_source,
+            record_id,
+        } => engine.reevaluate_record(data_source, record_id, flags),
+    }
+    .map(|_| ())
+}
+
+/// Moves the checkpoint past every finished target that directly follows it.
+fn advance_checkpoint(finished: &[bool], checkpoint: usize) -> usize {
+    checkpoint
+        + finished[checkpoint..]
+            .iter()
+            .take_while(|&&done| done)
+            .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advance_checkpoint() {
+        assert_eq!(advance_checkpoint(&[false, true, true], 0), 0);
+        assert_eq!(advance_checkpoint(&[true, true, false, true], 0), 2);
+        assert_eq!(advance_checkpoint(&[true, true, true, true], 2), 4);
+        assert_eq!(advance_checkpoint(&[], 0), 0);
+    }
+}
@@ -0,0 +1,627 @@
+//! Conversion of entity exports to interchange formats.
+//!
+//! Analytics tools read tables, not nested entity documents. The converters
+//! here take the entities of an export, such as an `export::ExportStream`,
+//! and write them in formats data lakes load directly:
+//!
+//! - [`write_records_csv`] writes one CSV row per record, with the columns
+//!   in [`RECORD_COLUMNS`].
+//! - [`write_normalized_ndjson`] writes one JSON document per entity with
+//!   lowercase keys, see [`normalize_keys`].
+//! - `record_batches` and `write_records_parquet` (feature `arrow`) turn the
+//!   same rows into Arrow record batches and a Parquet file.
+//!   `SzExporter::export_entities_arrow` streams the batches straight from an
+//!   export, for DataFusion or Polars to consume without a file in between.
+//! - `ToDataFrame` (feature `polars`) turns a `models::SearchResponse` or a
+//!   slice of exported entities into a Polars data frame for notebook-style
+//!   analysis.
+//!
+//! Every converter stops at the first error from the export and returns it.
+//!
+//! # Record table
+//!
+//! The CSV, Arrow and Parquet outputs share one table with a row per record:
+//!
+//! | Column | Arrow type | Nullable | Content |
+//! |--------|------------|----------|---------|
+//! | `entity_id` | `Int64` | no | Entity the record resolved into |
+//! | `entity_name` | `Utf8` | yes | Best name of the entity, with entity name flags |
+//! | `data_source` | `Utf8` | no | Data source code of the record |
+//! | `record_id` | `Utf8` | no | Record ID within the data source |
+//! | `match_key` | `Utf8` | yes | Match key that brought the record into the entity |
+//! | `errule_code` | `Utf8` | yes | Resolution rule that matched the record |
+//!
+//! Arrow types come from `arrow-array` and `arrow-schema` 54.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::export::SzExporter;
+//! use sz_rust_sdk::prelude::*;
+//! use sz_rust_sdk::report;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_report")?;
+//! let entities = SzExporter::new(env.clone()).export_entities_stream(None)?;
+//! let file = std::fs::File::create("/tmp/records.csv")
+//!     .map_err(|e| SzError::bad_input(e.to_string()))?;
+//! let rows = report::write_records_csv(entities, std::io::BufWriter::new(file))?;
+//! println!("wrote {rows} records");
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::error::{SzError, SzResult};
+use crate::models::ExportedEntity;
+use crate::redact;
+use crate::types::EntityId;
+use serde_json::Value;
+use std::fmt;
+use std::io::Write;
+
+/// Columns of the per-record table, in order.
+pub const RECORD_COLUMNS: [&str; 6] = [
+    "entity_id",
+    "entity_name",
+    "data_source",
+    "record_id",
+    "match_key",
+    "errule_code",
+];
+
+/// One record of an exported entity, flattened into a table row.
+#[derive(Clone, PartialEq, Eq)]
+pub struct RecordRow {
+    /// Entity the record resolved into
+    pub entity_id: EntityId,
+    /// Best name of the entity, when exported with entity name flags
+    pub entity_name: Option<String>,
+    /// Data source code of the record
+    pub data_source: String,
+    /// Record identifier within the data source
+    pub record_id: String,
+    /// Match key that brought the record into the entity
+    pub match_key: Option<String>,
+    /// Resolution rule code that matched the record
+    pub errule_code: Option<String>,
+}
+
+impl RecordRow {
+    /// Returns a row for each record of `entity`.
+    pub fn from_entity(entity: &ExportedEntity) -> Vec<Self> {
+        let resolved = &entity.resolved_entity;
+        resolved
+            .records
+            .iter()
+            .map(|record| Self {
+                entity_id: resolved.entity_id,
+                entity_name: resolved.entity_name.clone(),
+                data_source: record.data_source.clone(),
+                record_id: record.record_id.clone(),
+                match_key: record.match_key.clone(),
+                errule_code: record.errule_code.clone(),
+            })
+            .collect()
+    }
+}
+
+impl fmt::Debug for RecordRow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RecordRow")
+            .field("entity_id", &self.entity_id)
+            .field("entity_name", &redact::option(&self.entity_name))
+            .field("data_source", &self.data_source)
+            .field("record_id", &self.record_id)
+            .field("match_key", &self.match_key)
+            .field("errule_code", &self.errule_code)
+            .finish()
+    }
+}
+
+/// Writes a header and one CSV row per record of `entities`.
+///
+/// Fields are quoted only when they contain a comma, quote or line break;
+/// missing values are empty. Returns the number of rows written, not
+/// counting the header.
+///
+/// # Errors
+///
+/// The first error from `entities`, or `SzError::BadInput` if writing fails.
+pub fn write_records_csv<W: Write>(
+    entities: impl IntoIterator<Item = SzResult<ExportedEntity>>,
+    mut writer: W,
+) -> SzResult<usize> {
+    write_line(&mut writer, &RECORD_COLUMNS.join(","))?;
+    let mut rows = 0;
+    for entity in entities {
+        for row in RecordRow::from_entity(&entity?) {
+            let entity_id = row.entity_id.to_string();
+            let fields = [
+                entity_id.as_str(),
+                row.entity_name.as_deref().unwrap_or_default(),
+                &row.data_source,
+                &row.record_id,
+                row.match_key.as_deref().unwrap_or_default(),
+                row.errule_code.as_deref().unwrap_or_default(),
+            ];
+            let line: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+            write_line(&mut writer, &line.join(","))?;
+            rows += 1;
+        }
+    }
+    flush(&mut writer)?;
+    Ok(rows)
+}
+
+/// Writes each entity of `entities` as one line of JSON with keys normalized
+/// by [`normalize_keys`].
+///
+/// Returns the number of entities written.
+///
+/// # Errors
+///
+/// The first error from `entities`, or `SzError::BadInput` if writing fails.
+pub fn write_normalized_ndjson<W: Write>(
+    entities: impl IntoIterator<Item = SzResult<ExportedEntity>>,
+    mut writer: W,
+) -> SzResult<usize> {
+    let mut written = 0;
+    for entity in entities {
+        let mut document = serde_json::to_value(entity?)?;
+        normalize_keys(&mut document);
+        write_line(&mut writer, &document.to_string())?;
+        written += 1;
+    }
+    flush(&mut writer)?;
+    Ok(written)
+}
+
+/// Lowercases every object key in `value`, at any depth.
+///
+/// The engine's `RESOLVED_ENTITY`/`ENTITY_ID` style keys become
+/// `resolved_entity`/`entity_id`, the snake case most analytics schemas use.
+/// Keys come out sorted.
+///
+/// # Examples
+///
+/// ```
+/// use sz_rust_sdk::report::normalize_keys;
+///
+/// let mut value = serde_json::json!({"RESOLVED_ENTITY": {"ENTITY_ID": 1, "RECORDS": [{"DATA_SOURCE": "TEST"}]}});
+/// normalize_keys(&mut value);
+/// assert_eq!(value["resolved_entity"]["records"][0]["data_source"], "TEST");
+/// ```
+pub fn normalize_keys(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            let entries = std::mem::take(map);
+            for (key, mut nested) in entries {
+                normalize_keys(&mut nested);
+                map.insert(key.to_lowercase(), nested);
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(normalize_keys),
+        _ => {}
+    }
+}
+
+/// Quotes a CSV field if it contains a separator, quote or line break.
+pub(crate) fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+pub(crate) fn write_line<W: Write>(writer: &mut W, line: &str) -> SzResult<()> {
+    writer
+        .write_all(line.as_bytes())
+        .and_then(|()| writer.write_all(b"\n"))
+        .map_err(|e| SzError::bad_input(format!("Failed writing report: {e}")))
+}
+
+pub(crate) fn flush<W: Write>(writer: &mut W) -> SzResult<()> {
+    writer
+        .flush()
+        .map_err(|e| SzError::bad_input(format!("Failed to flush report: {e}")))
+}
+
+#[cfg(feature = "arrow")]
+pub use self::arrow::{
+    RECORD_BATCH_ROWS, record_batch, record_batches, record_schema, write_records_parquet,
+};
+
+#[cfg(feature = "arrow")]
+mod arrow {
+    use super::{RECORD_COLUMNS, RecordRow};
+    use crate::error::{SzError, SzResult};
+    use crate::models::ExportedEntity;
+    use arrow_array::{ArrayRef, Int64Array, RecordBatch, StringArray};
+    use arrow_schema::{DataType, Field, Schema, SchemaRef};
+    use parquet::arrow::ArrowWriter;
+    use std::io::Write;
+    use std::sync::Arc;
+
+    /// Rows after which [`record_batches`] cuts a batch.
+    pub const RECORD_BATCH_ROWS: usize = 8192;
+
+    /// Arrow schema of the record table described in the
+    /// [module documentation](super).
+    pub fn record_schema() -> SchemaRef {
+        let [
+            entity_id,
+            entity_name,
+            data_source,
+            record_id,
+            match_key,
+            errule_code,
+        ] = RECORD_COLUMNS;
+        Arc::new(Schema::new(vec![
+            Field::new(entity_id, DataType::Int64, false),
+            Field::new(entity_name, DataType::Utf8, true),
+            Field::new(data_source, DataType::Utf8, false),
+            Field::new(record_id, DataType::Utf8, false),
+            Field::new(match_key, DataType::Utf8, true),
+            Field::new(errule_code, DataType::Utf8, true),
+        ]))
+    }
+
+    /// Builds a record batch with [`record_schema`] from `rows`.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::Unknown` if Arrow rejects the columns.
+    pub fn record_batch(rows: &[RecordRow]) -> SzResult<RecordBatch> {
+        let columns: Vec<ArrayRef> = vec![
+            Arc::new(Int64Array::from_iter_values(
+                rows.iter().map(|row| row.entity_id.get()),
+            )),
+            Arc::new(StringArray::from_iter(
+                rows.iter().map(|row| row.entity_name.as_deref()),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|row| &row.data_source),
+            )),
+            Arc::new(StringArray::from_iter_values(
+                rows.iter().map(|row| &row.record_id),
+            )),
+            Arc::new(StringArray::from_iter(
+                rows.iter().map(|row| row.match_key.as_deref()),
+            )),
+            Arc::new(StringArray::from_iter(
+                rows.iter().map(|row| row.errule_code.as_deref()),
+            )),
+        ];
+        RecordBatch::try_new(record_schema(), columns)
+            .map_err(|e| SzError::unknown(format!("Failed building record batch: {e}")))
+    }
+
+    /// Groups the records of `entities` into record batches with
+    /// [`record_schema`].
+    ///
+    /// A batch holds whole entities and is cut once it reaches
+    /// [`RECORD_BATCH_ROWS`] rows, so an entity with many records can make
+    /// it longer. An error from `entities` is yielded after the batch of the
+    /// entities before it, and ends the iterator.
+    pub fn record_batches(
+        entities: impl IntoIterator<Item = SzResult<ExportedEntity>>,
+    ) -> impl Iterator<Item = SzResult<RecordBatch>> {
+        let mut entities = entities.into_iter();
+        let mut failed: Option<SzError> = None;
+        let mut done = false;
+        std::iter::from_fn(move || {
+            if let Some(e) = failed.take() {
+                done = true;
+                return Some(Err(e));
+            }
+            if done {
+                return None;
+            }
+            let mut rows = Vec::new();
+            while rows.len() < RECORD_BATCH_ROWS {
+                match entities.next() {
+                    Some(Ok(entity)) => rows.extend(RecordRow::from_entity(&entity)),
+                    Some(Err(e)) => {
+                        failed = Some(e);
+                        break;
+                    }
+                    None => {
+                        done = true;
+                        break;
+                    }
+                }
+            }
+            if rows.is_empty() {
+                done = true;
+                return failed.take().map(Err);
+            }
+            Some(record_batch(&rows))
+        })
+    }
+
+    /// Writes one row per record of `entities` to a Parquet file, one row
+    /// group per batch of [`record_batches`].
+    ///
+    /// Returns the number of rows written.
+    ///
+    /// # Errors
+    ///
+    /// The first error from `entities`, or `SzError::BadInput` if writing
+    /// fails.
+    pub fn write_records_parquet<W: Write + Send>(
+        entities: impl IntoIterator<Item = SzResult<ExportedEntity>>,
+        writer: W,
+    ) -> SzResult<usize> {
+        let parquet_error = |e: parquet::errors::ParquetError| {
+            SzError::bad_input(format!("Failed writing Parquet report: {e}"))
+        };
+        let mut parquet =
+            ArrowWriter::try_new(writer, record_schema(), None).map_err(parquet_error)?;
+        let mut written = 0;
+        for batch in record_batches(entities) {
+            let batch = batch?;
+            parquet.write(&batch).map_err(parquet_error)?;
+            written += batch.num_rows();
+        }
+        parquet.close().map_err(parquet_error)?;
+        Ok(written)
+    }
+}
+
+#[cfg(feature = "polars")]
+pub use self::frames::ToDataFrame;
+
+#[cfg(feature = "polars")]
+mod frames {
+    use super::{RECORD_COLUMNS, RecordRow};
+    use crate::error::{SzError, SzResult};
+    use crate::models::{ExportedEntity, SearchResponse};
+    use polars::prelude::{Column, DataFrame};
+    use std::collections::BTreeSet;
+
+    /// Conversion of results to a Polars data frame, for notebook-style
+    /// analysis of match quality.
+    pub trait ToDataFrame {
+        /// Builds a data frame from the results.
+        ///
+        /// # Errors
+        ///
+        /// `SzError::Unknown` if Polars rejects the columns.
+        fn to_dataframe(&self) -> SzResult<DataFrame>;
+    }
+
+    /// One row per result, best match first: `rank` (from 1), `entity_id`,
+    /// `entity_name`, `match_level_code`, `match_key`, `errule_code` and
+    /// `record_count`, followed by a `<feature>_score` column with the best
+    /// score of each feature type compared in any result, e.g. `name_score`.
+    /// Score columns need `SEARCH_INCLUDE_FEATURE_SCORES`.
+    impl ToDataFrame for SearchResponse {
+        fn to_dataframe(&self) -> SzResult<DataFrame> {
+            let results = &self.resolved_entities;
+            let feature_types: BTreeSet<&String> = results
+                .iter()
+                .flat_map(|result| result.match_info.feature_scores.keys())
+                .collect();
+            let mut columns = vec![
+                Column::new(
+                    "rank".into(),
+                    (1..=results.len() as u32).collect::<Vec<_>>(),
+                ),
+                Column::new(
+                    "entity_id".into(),
+                    results
+                        .iter()
+                        .map(|result| result.entity.resolved_entity.entity_id.get())
+                        .collect::<Vec<_>>(),
+                ),
+                Column::new(
+                    "entity_name".into(),
+                    results
+                        .iter()
+                        .map(|result| result.entity.resolved_entity.entity_name.as_deref())
+                        .collect::<Vec<_>>(),
+                ),
+                Column::new(
+                    "match_level_code".into(),
+                    results
+                        .iter()
+                        .map(|result| result.match_info.match_level_code.as_deref())
+                        .collect::<Vec<_>>(),
+                ),
+                Column::new(
+                    "match_key".into(),
+                    results
+                        .iter()
+                        .map(|result| result.match_info.match_key.as_deref())
+                        .collect::<Vec<_>>(),
+                ),
+                Column::new(
+                    "errule_code".into(),
+                    results
+                        .iter()
+                        .map(|result| result.match_info.errule_code.as_deref())
+                        .collect::<Vec<_>>(),
+                ),
+                Column::new(
+                    "record_count".into(),
+                    results
+                        .iter()
+                        .map(|result| result.entity.resolved_entity.records.len() as u32)
+                        .collect::<Vec<_>>(),
+                ),
+            ];
+            for feature_type in feature_types {
+                columns.push(Column::new(
+                    format!("{}_score", feature_type.to_lowercase()).into(),
+                    results
+                        .iter()
+                        .map(|result| result.best_score(feature_type))
+                        .collect::<Vec<_>>(),
+                ));
+            }
+            dataframe(columns)
+        }
+    }
+
+    /// The record table described in the [module documentation](super), one
+    /// row per record of every entity.
+    impl ToDataFrame for [ExportedEntity] {
+        fn to_dataframe(&self) -> SzResult<DataFrame> {
+            let rows: Vec<RecordRow> = self.iter().flat_map(RecordRow::from_entity).collect();
+            let [
+                entity_id,
+                entity_name,
+                data_source,
+                record_id,
+                match_key,
+                errule_code,
+            ] = RECORD_COLUMNS;
+            dataframe(vec![
+                Column::new(
+                    entity_id.into(),
+                    rows.iter()
+                        .map(|row| row.entity_id.get())
+                        .collect::<Vec<_>>(),
+                ),
+                Column::new(
+                    entity_name.into(),
+                    rows.iter()
+                        .map(|row| row.entity_name.as_deref())
+                        .collect::<Vec<_>>(),
+                ),
+                Column::new(
+                    data_source.into(),
+                    rows.iter()
+                        .map(|row| row.data_source.as_str())
+                        .collect::<Vec<_>>(),
+                ),
+                Column::new(
+                    record_id.into(),
+                    rows.iter()
+                        .map(|row| row.record_id.as_str())
+                        .collect::<Vec<_>>(),
+                ),
+                Column::new(
+                    match_key.into(),
+                    rows.iter()
+                        .map(|row| row.match_key.as_deref())
+                        .collect::<Vec<_>>(),
+                ),
+                Column::new(
+                    errule_code.into(),
+                    rows.iter()
+                        .map(|row| row.errule_code.as_deref())
+                        .collect::<Vec<_>>(),
+                ),
+            ])
+        }
+    }
+
+    fn dataframe(columns: Vec<Column>) -> SzResult<DataFrame> {
+        DataFrame::new(columns)
+            .map_err(|e| SzError::unknown(format!("Failed building data frame: {e}")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entities() -> Vec<SzResult<ExportedEntity>> {
+        let json = r#"{"RESOLVED_ENTITY": {"ENTITY_ID": 7, "ENTITY_NAME": "Smith, \"Jo\"",
+            "RECORDS": [{"DATA_SOURCE": "CUSTOMERS", "RECORD_ID": "1", "MATCH_KEY": "", "ERRULE_CODE": ""},
+                        {"DATA_SOURCE": "WATCHLIST", "RECORD_ID": "9", "MATCH_KEY": "+NAME+DOB", "ERRULE_CODE": "CNAME_CFF"}]}}"#;
+        vec![Ok(serde_json::from_str(json).unwrap())]
+    }
+
+    #[test]
+    fn test_write_records_csv() {
+        let mut out = Vec::new();
+        assert_eq!(write_records_csv(entities(), &mut out).unwrap(), 2);
+        let csv = String::from_utf8(out).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines,
+            [
+                "entity_id,entity_name,data_source,record_id,match_key,errule_code",
+                r#"7,"Smith, ""Jo""",CUSTOMERS,1,,"#,
+                r#"7,"Smith, ""Jo""",WATCHLIST,9,+NAME+DOB,CNAME_CFF"#,
+            ]
+        );
+
+        let failing = vec![Err(SzError::unknown("export failed"))];
+        assert!(write_records_csv(failing, Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_write_normalized_ndjson() {
+        let mut out = Vec::new();
+        assert_eq!(write_normalized_ndjson(entities(), &mut out).unwrap(), 1);
+        let line: Value = serde_json::from_slice(&out).unwrap();
+        assert_eq!(line["resolved_entity"]["entity_id"], 7);
+        assert_eq!(
+            line["resolved_entity"]["records"][1]["errule_code"],
+            "CNAME_CFF"
+        );
+        assert!(line.get("RESOLVED_ENTITY").is_none());
+    }
+
+    #[cfg(feature = "polars")]
+    #[test]
+    fn test_to_dataframe() {
+        let exported: Vec<ExportedEntity> = entities().into_iter().map(Result::unwrap).collect();
+        let frame = exported.to_dataframe().unwrap();
+        assert_eq!(frame.shape(), (2, RECORD_COLUMNS.len()));
+        assert_eq!(frame.get_column_names_str(), RECORD_COLUMNS);
+
+        let search: crate::models::SearchResponse = serde_json::from_str(
+            r#"{"RESOLVED_ENTITIES": [
+                {"MATCH_INFO": {"MATCH_LEVEL_CODE": "RESOLVED",
+                    "FEATURE_SCORES": {"NAME": [{"SCORE": 90}, {"SCORE": 100}]}},
+                 "ENTITY": {"RESOLVED_ENTITY": {"ENTITY_ID": 1}}},
+                {"MATCH_INFO": {"MATCH_LEVEL_CODE": "POSSIBLY_SAME",
+                    "FEATURE_SCORES": {"DOB": [{"SCORE": 85}]}},
+                 "ENTITY": {"RESOLVED_ENTITY": {"ENTITY_ID": 2}}}]}"#,
+        )
+        .unwrap();
+        let frame = search.to_dataframe().unwrap();
+        assert_eq!(frame.height(), 2);
+        let name_score = frame.column("name_score").unwrap().i64().unwrap();
+        assert_eq!(name_score.get(0), Some(100));
+        assert_eq!(name_score.get(1), None);
+        assert!(frame.column("dob_score").is_ok());
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn test_write_records_parquet() {
+        use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+        let path = std::env::temp_dir().join(format!("sz_report_{}.parquet", std::process::id()));
+        let file = std::fs::File::create(&path).unwrap();
+        assert_eq!(write_records_parquet(entities(), file).unwrap(), 2);
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(std::fs::File::open(&path).unwrap())
+            .unwrap()
+            .build()
+            .unwrap();
+        let batches: Vec<_> = reader.collect::<Result<_, _>>().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0].num_rows(), 2);
+        assert_eq!(batches[0].schema(), record_schema());
+    }
+
+    #[cfg(feature = "arrow")]
+    #[test]
+    fn test_record_batches_yield_rows_before_error() {
+        let mut input = entities();
+        input.push(Err(SzError::unknown("export failed")));
+        input.extend(entities());
+        let batches: Vec<_> = record_batches(input).collect();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].as_ref().unwrap().num_rows(), 2);
+        assert!(batches[1].is_err());
+        assert_eq!(record_batches(Vec::new()).count(), 0);
+    }
+}
@@ -0,0 +1,407 @@
+//! Named score profiles for accept/review/reject decisions.
+//!
+//! Search and why responses carry feature scores (with
+//! `SEARCH_INCLUDE_FEATURE_SCORES`), but each service tends to pick its own
+//! cut-offs. A [`ScoreProfile`] holds those cut-offs as [`Thresholds`] per
+//! feature type, with an optional default for the others, and sorts
+//! comparisons into a [`Decision`]:
+//!
+//! - A feature's best score at or above `accept` is [`Decision::Accept`].
+//! - At or above `review` it is [`Decision::Review`].
+//! - Below `review` it is [`Decision::Reject`].
+//!
+//! The overall decision is the weakest decision among the scored features.
+//! Features without thresholds are ignored. When no scored feature has
+//! thresholds, the result goes to review.
+//!
+//! [`ScoreProfiles`] names profiles so services can share them.
+//! [`ScoreProfiles::builtin`] has `strict` and `lenient`. Profiles can also be
+//! loaded from JSON, or from TOML with the `toml` feature:
+//!
+//! ```toml
+//! [watchlist]
+//! default = { accept = 95, review = 85 }
+//!
+//! [watchlist.features]
+//! NAME = { accept = 98, review = 90 }
+//! DOB = { accept = 100, review = 80 }
+//! ```
+//!
+//! # Example
+//!
+//! ```
+//! use sz_rust_sdk::models::SearchExplanation;
+//! use sz_rust_sdk::scoring::{Decision, ScoreProfiles};
+//!
+//! let explanation = SearchExplanation::from_json(
+//!     r#"{"WHY_RESULTS":[{"ENTITY_ID":7,"MATCH_INFO":{"FEATURE_SCORES":{
+//!         "NAME":[{"SCORE":92}],"DOB":[{"SCORE":100}]}}}]}"#,
+//! )?;
+//! let profiles = ScoreProfiles::builtin();
+//! let strict = profiles.decide("strict", &explanation.feature_scores)?;
+//! assert_eq!(strict.decision, Decision::Review);
+//! assert_eq!(strict.features["DOB"], Decision::Accept);
+//! let lenient = profiles.decide("lenient", &explanation.feature_scores)?;
+//! assert_eq!(lenient.decision, Decision::Accept);
+//! # Ok::<(), sz_rust_sdk::error::SzError>(())
+//! ```
+
+use crate::error::{SzError, SzResult};
+use crate::models::{FeatureScore, SearchResponse, SearchResult};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Outcome of comparing scores with a profile, weakest first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Decision {
+    /// The scores are too low to be the same
+    Reject,
+    /// A person should look at the match
+    Review,
+    /// The scores are high enough to act on
+    Accept,
+}
+
+/// Score cut-offs for one feature type, from 0 to 100.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Thresholds {
+    /// Lowest score that is accepted
+    pub accept: i64,
+    /// Lowest score that goes to review
+    pub review: i64,
+}
+
+impl Thresholds {
+    /// Creates thresholds; `review` should not be above `accept`.
+    pub fn new(accept: i64, review: i64) -> Self {
+        Self { accept, review }
+    }
+
+    /// The decision for `score`.
+    pub fn decide(&self, score: i64) -> Decision {
+        if score >= self.accept {
+            Decision::Accept
+        } else if score >= self.review {
+            Decision::Review
+        } else {
+            Decision::Reject
+        }
+    }
+
+    fn validate(&self, profile: &str, feature: &str) -> SzResult<()> {
+        if (0..=self.accept).contains(&self.review) && self.accept <= 100 {
+            return Ok(());
+        }
+        Err(SzError::bad_input(format!(
+            "Score profile '{profile}' has invalid thresholds for {feature}: \
+             need 0 <= review ({}) <= accept ({}) <= 100",
+            self.review, self.accept
+        )))
+    }
+}
+
+/// Thresholds per feature type, with an optional default.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScoreProfile {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default: Option<Thresholds>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    features: BTreeMap<String, Thresholds>,
+}
+
+/// The decision for a set of feature scores, with the decision of each
+/// feature type that had thresholds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoreDecision {
+    /// Weakest decision among the features, or review if none was decided
+    pub decision: Decision,
+    /// Decision per feature type, from its best score
+    pub features: BTreeMap<String, Decision>,
+}
+
+/// Search results sorted by [`ScoreProfile::partition`], each in engine
+/// order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ScoreBuckets<'a> {
+    /// Results decided [`Decision::Accept`]
+    pub accept: Vec<&'a SearchResult>,
+    /// Results decided [`Decision::Review`]
+    pub review: Vec<&'a SearchResult>,
+    /// Results decided [`Decision::Reject`]
+    pub reject: Vec<&'a SearchResult>,
+}
+
+impl ScoreProfile {
+    /// Creates a profile without thresholds.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the thresholds of feature types without their own.
+    pub fn with_default(mut self, thresholds: Thresholds) -> Self {
+        self.default = Some(thresholds);
+        self
+    }
+
+    /// Sets the thresholds of `feature_type`, e.g. `NAME`.
+    pub fn with_feature(mut self, feature_type: impl Into<String>, thresholds: Thresholds) -> Self {
+        self.features.insert(feature_type.into(), thresholds);
+        self
+    }
+
+    /// The thresholds that apply to `feature_type`.
+    pub fn thresholds(&self, feature_type: &str) -> Option<&Thresholds> {
+        self.features.get(feature_type).or(self.default.as_ref())
+    }
+
+    /// Decides on feature scores keyed by feature type, as found in
+    /// `SearchMatchInfo`, `SearchExplanation` and `RecordsExplanation`.
+    pub fn decide(&self, feature_scores: &BTreeMap<String, Vec<FeatureScore>>) -> ScoreDecision {
+        let features: BTreeMap<String, Decision> = feature_scores
+            .iter()
+            .filter_map(|(feature_type, scores)| {
+                let thresholds = self.thresholds(feature_type)?;
+                let best = scores.iter().filter_map(|score| score.score).max()?;
+                Some((feature_type.clone(), thresholds.decide(best)))
+            })
+            .collect();
+        let decision = features.values().copied().min().unwrap_or(Decision::Review);
+        ScoreDecision { decision, features }
+    }
+
+    /// Sorts search results by their decision. Needs
+    /// `SEARCH_INCLUDE_FEATURE_SCORES`; results without scores go to review.
+    pub fn partition<'a>(&self, response: &'a SearchResponse) -> ScoreBuckets<'a> {
+        let mut buckets = ScoreBuckets::default();
+        for result in &response.resolved_entities {
+            let bucket = match self.decide(&result.match_info.feature_scores).decision {
+                Decision::Accept => &mut buckets.accept,
+                Decision::Review => &mut buckets.review,
+                Decision::Reject => &mut buckets.reject,
+            };
+            bucket.push(result);
+        }
+        buckets
+    }
+
+    fn validate(&self, name: &str) -> SzResult<()> {
+        if let Some(default) = &self.default {
+            default.validate(name, "the default")?;
+        }
+        for (feature_type, thresholds) in &self.features {
+            thresholds.validate(name, feature_type)?;
+        }
+        Ok(())
+    }
+}
+
+/// Score profiles by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ScoreProfiles {
+    profiles: BTreeMap<String, ScoreProfile>,
+}
+
+impl ScoreProfiles {
+    /// Creates an empty set of profiles.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The built-in profiles:
+    ///
+    /// | Profile | Accept | Review |
+    /// |---------|--------|--------|
+    /// | `strict` | 95 | 80 |
+    /// | `lenient` | 85 | 60 |
+    pub fn builtin() -> Self {
+        Self::new()
+            .with_profile(
+                "strict",
+                ScoreProfile::new().with_default(Thresholds::new(95, 80)),
+            )
+            .with_profile(
+                "lenient",
+                ScoreProfile::new().with_default(Thresholds::new(85, 60)),
+            )
+    }
+
+    /// Adds a profile, replacing one with the same name.
+    pub fn with_profile(mut self, name: impl Into<String>, profile: ScoreProfile) -> Self {
+        self.profiles.insert(name.into(), profile);
+        self
+    }
+
+    /// Adds every profile of `other`, replacing those with the same names,
+    /// e.g. to override the built-in profiles with loaded ones.
+    pub fn merge(mut self, other: Self) -> Self {
+        self.profiles.extend(other.profiles);
+        self
+    }
+
+    /// Parses profiles from a JSON object keyed by profile name.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the profiles are malformed or a threshold is
+    /// outside 0 to 100 or has `review` above `accept`.
+    pub fn from_json_str(spec: &str) -> SzResult<Self> {
+        serde_json::from_str::<Self>(spec)
+            .map_err(|e| SzError::bad_input(format!("Invalid score profiles: {e}")))?
+            .validated()
+    }
+
+    /// Parses profiles from TOML, one table per profile.
+    ///
+    /// # Errors
+    ///
+    /// Same as [`ScoreProfiles::from_json_str`].
+    #[cfg(feature = "toml")]
+    pub fn from_toml_str(spec: &str) -> SzResult<Self> {
+        toml::from_str::<Self>(spec)
+            .map_err(|e| SzError::bad_input(format!("Invalid score profiles: {e}")))?
+            .validated()
+    }
+
+    /// The profile called `name`.
+    pub fn profile(&self, name: &str) -> Option<&ScoreProfile> {
+        self.profiles.get(name)
+    }
+
+    /// Profile names, sorted.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.profiles.keys().map(String::as_str)
+    }
+
+    /// Decides on feature scores with the profile called `name`.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if there is no such profile.
+    pub fn decide(
+        &self,
+        name: &str,
+        feature_scores: &BTreeMap<String, Vec<FeatureScore>>,
+    ) -> SzResult<ScoreDecision> {
+        self.profile(name)
+            .map(|profile| profile.decide(feature_scores))
+            .ok_or_else(|| SzError::bad_input(format!("Unknown score profile '{name}'")))
+    }
+
+    fn validated(self) -> SzResult<Self> {
+        for (name, profile) in &self.profiles {
+            profile.validate(name)?;
+        }
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scores(pairs: &[(&str, &[i64])]) -> BTreeMap<String, Vec<FeatureScore>> {
+        pairs
+            .iter()
+            .map(|(feature_type, values)| {
+                let scores = values
+                    .iter()
+                    .map(|&score| FeatureScore {
+                        score: Some(score),
+                        ..FeatureScore::default()
+                    })
+                    .collect();
+                (feature_type.to_string(), scores)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_profile_decisions() {
+        let profile = ScoreProfile::new()
+            .with_default(Thresholds::new(90, 70))
+            .with_feature("DOB", Thresholds::new(100, 85));
+
+        let decided = profile.decide(&scores(&[("NAME", &[60, 95]), ("DOB", &[90])]));
+        assert_eq!(decided.features["NAME"], Decision::Accept);
+        assert_eq!(decided.features["DOB"], Decision::Review);
+        assert_eq!(decided.decision, Decision::Review);
+
+        let rejected = profile.decide(&scores(&[("NAME", &[95]), ("ADDRESS", &[40])]));
+        assert_eq!(rejected.decision, Decision::Reject);
+
+        let unscored = ScoreProfile::new().with_feature("DOB", Thresholds::new(100, 85));
+        let decided = unscored.decide(&scores(&[("NAME", &[100]), ("DOB", &[])]));
+        assert!(decided.features.is_empty());
+        assert_eq!(decided.decision, Decision::Review);
+    }
+
+    #[test]
+    fn test_partition_search_results() {
+        let response: SearchResponse = serde_json::from_str(
+            r#"{"RESOLVED_ENTITIES":[
+                {"MATCH_INFO":{"FEATURE_SCORES":{"NAME":[{"SCORE":100}]}},"ENTITY":{"RESOLVED_ENTITY":{"ENTITY_ID":1}}},
+                {"MATCH_INFO":{"FEATURE_SCORES":{"NAME":[{"SCORE":50}]}},"ENTITY":{"RESOLVED_ENTITY":{"ENTITY_ID":2}}},
+                {"ENTITY":{"RESOLVED_ENTITY":{"ENTITY_ID":3}}}]}"#,
+        )
+        .unwrap();
+        let buckets = ScoreProfiles::builtin()
+            .profile("strict")
+            .unwrap()
+            .partition(&response);
+        let ids = |results: &[&SearchResult]| {
+            results
+                .iter()
+                .map(|result| result.entity.resolved_entity.entity_id.0)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&buckets.accept), [1]);
+        assert_eq!(ids(&buckets.reject), [2]);
+        assert_eq!(ids(&buckets.review), [3]);
+    }
+
+    #[test]
+    fn test_load_profiles() {
+        let loaded = ScoreProfiles::from_json_str(
+            r#"{"strict":{"default":{"accept":99,"review":90}},
+                "watchlist":{"features":{"NAME":{"accept":90,"review":75}}}}"#,
+        )
+        .unwrap();
+        let profiles = ScoreProfiles::builtin().merge(loaded);
+        assert_eq!(
+            profiles.names().collect::<Vec<_>>(),
+            ["lenient", "strict", "watchlist"]
+        );
+        assert_eq!(
+            profiles.profile("strict").unwrap().thresholds("NAME"),
+            Some(&Thresholds::new(99, 90))
+        );
+        assert!(profiles.decide("missing", &BTreeMap::new()).is_err());
+
+        assert!(
+            ScoreProfiles::from_json_str(r#"{"x":{"default":{"accept":80,"review":90}}}"#).is_err()
+        );
+        assert!(
+            ScoreProfiles::from_json_str(r#"{"x":{"default":{"accept":101,"review":90}}}"#)
+                .is_err()
+        );
+        assert!(ScoreProfiles::from_json_str(r#"{"x":{"minimum":1}}"#).is_err());
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn test_load_toml_profiles() {
+        let profiles = ScoreProfiles::from_toml_str(
+            "[watchlist]\ndefault = { accept = 95, review = 85 }\n\n\
+             [watchlist.features]\nDOB = { accept = 100, review = 80 }\n",
+        )
+        .unwrap();
+        let watchlist = profiles.profile("watchlist").unwrap();
+        assert_eq!(watchlist.thresholds("NAME"), Some(&Thresholds::new(95, 85)));
+        assert_eq!(watchlist.thresholds("DOB"), Some(&Thresholds::new(100, 80)));
+        assert!(ScoreProfiles::from_toml_str("[x]\ndefault = 1").is_err());
+    }
+}
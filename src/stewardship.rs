@@ -0,0 +1,264 @@
+//! Record edits behind the stewardship methods of `SzEngine`.
+//!
+//! The engine takes resolution overrides as record attributes rather than as
+//! separate calls. `SzEngine::force_merge`, `force_unmerge` and
+//! `force_relate` read the stored records, add the attributes below and add
+//! the records again, so a data steward never writes the JSON by hand:
+//!
+//! | Action | Attributes |
+//! |--------|------------|
+//! | Force merge | The same `TRUSTED_ID_NUMBER` of type [`STEWARD_DOMAIN`] on both records |
+//! | Force unmerge | A `TRUSTED_ID_NUMBER` of type [`STEWARD_DOMAIN`] on the record and a different one on every other record of its entity |
+//! | Force relate | `REL_ANCHOR_DOMAIN` / `REL_ANCHOR_KEY` on one record and a `REL_POINTER_DOMAIN` / `REL_POINTER_KEY` / `REL_POINTER_ROLE` entry in the other's `REL_POINTERS` list |
+//!
+//! Records that disagree on a trusted ID of the same type never resolve
+//! together, and records that share one always do. Each record carries at
+//! most one steward trusted ID, so a later action replaces an earlier one on
+//! the same record. [`clear_overrides`] removes everything added here.
+//!
+//! # Example
+//!
+//! ```
+//! use sz_rust_sdk::stewardship;
+//!
+//! let mut record = serde_json::from_str(r#"{"NAME_FULL": "Ann Lee"}"#)?;
+//! stewardship::set_trusted_id(&mut record, "CUSTOMERS:1001");
+//! stewardship::add_rel_pointer(&mut record, "CUSTOMERS:2002", "SPOUSE");
+//! assert_eq!(record["TRUSTED_ID_TYPE"], stewardship::STEWARD_DOMAIN);
+//! assert_eq!(record["REL_POINTERS"][0]["REL_POINTER_ROLE"], "SPOUSE");
+//!
+//! stewardship::clear_overrides(&mut record);
+//! assert_eq!(serde_json::Value::Object(record), serde_json::json!({"NAME_FULL": "Ann Lee"}));
+//! # Ok::<(), serde_json::Error>(())
+//! ```
+
+use crate::error::{SzError, SzResult};
+use crate::flags::SzFlags;
+use crate::traits::SzEngineRead;
+use crate::types::{EntityRef, RecordKey};
+use serde_json::{Map, Value, json};
+
+/// Trusted ID type and relationship domain of the overrides added here.
+pub const STEWARD_DOMAIN: &str = "STEWARD";
+
+const TRUSTED_ID_TYPE: &str = "TRUSTED_ID_TYPE";
+const TRUSTED_ID_NUMBER: &str = "TRUSTED_ID_NUMBER";
+const REL_ANCHOR_DOMAIN: &str = "REL_ANCHOR_DOMAIN";
+const REL_ANCHOR_KEY: &str = "REL_ANCHOR_KEY";
+const REL_POINTERS: &str = "REL_POINTERS";
+
+/// The steward trusted ID of a record, if it has one.
+pub fn trusted_id(record: &Map<String, Value>) -> Option<&str> {
+    (record.get(TRUSTED_ID_TYPE)?.as_str()? == STEWARD_DOMAIN)
+        .then(|| record.get(TRUSTED_ID_NUMBER)?.as_str())
+        .flatten()
+}
+
+/// Sets the record's steward trusted ID to `number`. Returns whether the
+/// record changed.
+///
+/// A trusted ID of another type at the top level is left alone and the
+/// steward one goes into a `TRUSTED_IDS` list instead.
+pub fn set_trusted_id(record: &mut Map<String, Value>, number: &str) -> bool {
+    if trusted_id(record) == Some(number) || listed_trusted_id(record) == Some(number) {
+        return false;
+    }
+    let top_level_free = record
+        .get(TRUSTED_ID_TYPE)
+        .is_none_or(|id_type| id_type.as_str() == Some(STEWARD_DOMAIN));
+    if top_level_free {
+        remove_listed_trusted_id(record);
+        record.insert(TRUSTED_ID_TYPE.to_string(), json!(STEWARD_DOMAIN));
+        record.insert(TRUSTED_ID_NUMBER.to_string(), json!(number));
+    } else {
+        remove_listed_trusted_id(record);
+        list(record, "TRUSTED_IDS").push(json!({
+            TRUSTED_ID_TYPE: STEWARD_DOMAIN,
+            TRUSTED_ID_NUMBER: number,
+        }));
+    }
+    true
+}
+
+/// Sets the record's relationship anchor in [`STEWARD_DOMAIN`], keeping an
+/// existing one. Returns the anchor key and whether the record changed.
+pub fn set_rel_anchor(record: &mut Map<String, Value>, key: &str) -> (String, bool) {
+    if record.get(REL_ANCHOR_DOMAIN).and_then(Value::as_str) == Some(STEWARD_DOMAIN)
+        && let Some(existing) = record.get(REL_ANCHOR_KEY).and_then(Value::as_str)
+    {
+        return (existing.to_string(), false);
+    }
+    record.insert(REL_ANCHOR_DOMAIN.to_string(), json!(STEWARD_DOMAIN));
+    record.insert(REL_ANCHOR_KEY.to_string(), json!(key));
+    (key.to_string(), true)
+}
+
+/// Adds a pointer to the anchor `key` in [`STEWARD_DOMAIN`] with `role`.
+/// Returns whether the record changed.
+pub fn add_rel_pointer(record: &mut Map<String, Value>, key: &str, role: &str) -> bool {
+    let pointer = json!({
+        "REL_POINTER_DOMAIN": STEWARD_DOMAIN,
+        "REL_POINTER_KEY": key,
+        "REL_POINTER_ROLE": role,
+    });
+    let pointers = list(record, REL_POINTERS);
+    if pointers.contains(&pointer) {
+        return false;
+    }
+    pointers.push(pointer);
+    true
+}
+
+/// Removes every override added by this module. Returns whether the record
+/// changed.
+pub fn clear_overrides(record: &mut Map<String, Value>) -> bool {
+    let mut changed = remove_listed_trusted_id(record);
+    if trusted_id(record).is_some() {
+        record.remove(TRUSTED_ID_TYPE);
+        record.remove(TRUSTED_ID_NUMBER);
+        changed = true;
+    }
+    if record.get(REL_ANCHOR_DOMAIN).and_then(Value::as_str) == Some(STEWARD_DOMAIN) {
+        record.remove(REL_ANCHOR_DOMAIN);
+        record.remove(REL_ANCHOR_KEY);
+        changed = true;
+    }
+    if let Some(Value::Array(pointers)) = record.get_mut(REL_POINTERS) {
+        let before = pointers.len();
+        pointers.retain(|pointer| pointer["REL_POINTER_DOMAIN"] != STEWARD_DOMAIN);
+        changed |= pointers.len() != before;
+        if pointers.is_empty() {
+            record.remove(REL_POINTERS);
+        }
+    }
+    changed
+}
+
+fn listed_trusted_id(record: &Map<String, Value>) -> Option<&str> {
+    record
+        .get("TRUSTED_IDS")?
+        .as_array()?
+        .iter()
+        .filter_map(Value::as_object)
+        .find_map(trusted_id)
+}
+
+fn remove_listed_trusted_id(record: &mut Map<String, Value>) -> bool {
+    let Some(Value::Array(ids)) = record.get_mut("TRUSTED_IDS") else {
+        return false;
+    };
+    let before = ids.len();
+    ids.retain(|id| id[TRUSTED_ID_TYPE] != STEWARD_DOMAIN);
+    let changed = ids.len() != before;
+    if ids.is_empty() {
+        record.remove("TRUSTED_IDS");
+    }
+    changed
+}
+
+/// The list attribute `name`, created when missing or not a list.
+fn list<'a>(record: &'a mut Map<String, Value>, name: &str) -> &'a mut Vec<Value> {
+    let value = record
+        .entry(name)
+        .and_modify(|value| {
+            if !value.is_array() {
+                *value = Value::Array(Vec::new());
+            }
+        })
+        .or_insert_with(|| Value::Array(Vec::new()));
+    match value {
+        Value::Array(items) => items,
+        _ => unreachable!("list attribute was just made an array"),
+    }
+}
+
+/// Reads the stored `JSON_DATA` of a record.
+pub(crate) fn stored_record(
+    engine: &(impl SzEngineRead + ?Sized),
+    key: &RecordKey,
+) -> SzResult<Map<String, Value>> {
+    let record: Value = serde_json::from_str(&engine.get_record(
+        key.data_source.as_str(),
+        &key.record_id,
+        Some(SzFlags::ENTITY_INCLUDE_RECORD_JSON_DATA),
+    )?)?;
+    match record.get("JSON_DATA") {
+        Some(Value::Object(data)) => Ok(data.clone()),
+        _ => Err(SzError::bad_input(format!(
+            "Record {key} has no stored JSON_DATA"
+        ))),
+    }
+}
+
+/// The keys of the other records in a record's entity, sorted.
+pub(crate) fn entity_mates(
+    engine: &(impl SzEngineRead + ?Sized),
+    key: &RecordKey,
+) -> SzResult<Vec<RecordKey>> {
+    let entity: crate::models::ExportedEntity = serde_json::from_str(&engine.get_entity(
+        EntityRef::from(key),
+        Some(SzFlags::ENTITY_INCLUDE_RECORD_DATA),
+    )?)?;
+    let mut mates = entity
+        .resolved_entity
+        .records
+        .into_iter()
+        .map(|record| RecordKey::new(record.data_source, record.record_id))
+        .collect::<SzResult<Vec<_>>>()?;
+    mates.retain(|mate| mate != key);
+    mates.sort();
+    Ok(mates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(json: &str) -> Map<String, Value> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_trusted_id() {
+        let mut plain = record(r#"{"NAME_FULL":"A"}"#);
+        assert!(set_trusted_id(&mut plain, "X:1"));
+        assert!(!set_trusted_id(&mut plain, "X:1"));
+        assert!(set_trusted_id(&mut plain, "X:2"));
+        assert_eq!(trusted_id(&plain), Some("X:2"));
+
+        let mut other = record(r#"{"TRUSTED_ID_TYPE":"SSN","TRUSTED_ID_NUMBER":"9"}"#);
+        assert!(set_trusted_id(&mut other, "X:1"));
+        assert_eq!(other["TRUSTED_ID_NUMBER"], "9");
+        assert_eq!(listed_trusted_id(&other), Some("X:1"));
+        assert!(set_trusted_id(&mut other, "X:3"));
+        assert_eq!(other["TRUSTED_IDS"].as_array().unwrap().len(), 1);
+
+        assert!(clear_overrides(&mut other));
+        assert_eq!(
+            other,
+            record(r#"{"TRUSTED_ID_TYPE":"SSN","TRUSTED_ID_NUMBER":"9"}"#)
+        );
+        assert!(!clear_overrides(&mut other));
+    }
+
+    #[test]
+    fn test_relationship_attributes() {
+        let mut anchor = record(r#"{"REL_POINTERS":"bad"}"#);
+        assert_eq!(
+            set_rel_anchor(&mut anchor, "A:1"),
+            ("A:1".to_string(), true)
+        );
+        assert_eq!(
+            set_rel_anchor(&mut anchor, "A:2"),
+            ("A:1".to_string(), false)
+        );
+
+        assert!(add_rel_pointer(&mut anchor, "B:1", "EMPLOYER"));
+        assert!(!add_rel_pointer(&mut anchor, "B:1", "EMPLOYER"));
+        assert!(add_rel_pointer(&mut anchor, "B:1", "OWNER"));
+        assert_eq!(anchor[REL_POINTERS].as_array().unwrap().len(), 2);
+
+        assert!(clear_overrides(&mut anchor));
+        assert!(anchor.is_empty());
+    }
+}
@@ -0,0 +1,240 @@
+//! Support bundles for Senzing support tickets.
+//!
+//! [`SzDiagnostics::collect_support_bundle`] writes a zip archive with what
+//! Senzing support asks for when a ticket is opened:
+//!
+//! | Entry | Contents |
+//! |-------|----------|
+//! | `version.json` | `SzProduct::get_version` |
+//! | `license.json` | `SzProduct::get_license` |
+//! | `config.json` | Export of the active configuration |
+//! | `repository_info.json` | `SzDiagnostic::get_repository_info` |
+//! | `stats.json` | `SzEngine::get_stats` |
+//! | `errors.json` | `SzEnvironmentCore::recent_errors`, messages passed through [`redact::message`] |
+//! | `manifest.json` | The [`BundleManifest`] |
+//!
+//! Collection is best effort: an entry that cannot be read is left out and
+//! its error is recorded in the manifest, so a bundle can still be produced
+//! from an environment that is failing. The engine settings are not
+//! included, since they can hold database credentials.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::prelude::*;
+//! use sz_rust_sdk::support::SzDiagnostics;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_support_bundle")?;
+//! let manifest = SzDiagnostics::new(env.clone()).collect_support_bundle("/tmp/senzing-support.zip")?;
+//! for entry in manifest.entries.iter().filter(|entry| entry.error.is_some()) {
+//!     eprintln!("{} is missing from the bundle", entry.name);
+//! }
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::core::SzEnvironmentCore;
+use crate::error::{SzError, SzResult};
+use crate::redact;
+use crate::traits::SzEnvironment;
+use serde::Serialize;
+use serde_json::json;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use zip::write::SimpleFileOptions;
+
+/// Name of the manifest entry, written last.
+pub const MANIFEST_ENTRY: &str = "manifest.json";
+
+/// One entry of a support bundle.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BundleEntry {
+    /// File name inside the archive
+    pub name: &'static str,
+    /// Why the entry could not be collected, redacted; `None` if it is in
+    /// the archive
+    pub error: Option<String>,
+}
+
+/// What a support bundle contains, written into it as `manifest.json`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BundleManifest {
+    /// Version of the SDK crate that wrote the bundle
+    pub sdk_version: &'static str,
+    /// When the bundle was written, in seconds since the Unix epoch
+    pub created_at: u64,
+    /// Whether error messages were redacted, see [`redact::is_enabled`]
+    pub redacted: bool,
+    /// Entries in the order they were collected, excluding the manifest
+    pub entries: Vec<BundleEntry>,
+}
+
+impl BundleManifest {
+    /// Whether every entry was collected.
+    pub fn is_complete(&self) -> bool {
+        self.entries.iter().all(|entry| entry.error.is_none())
+    }
+}
+
+/// Collects diagnostics from an environment for Senzing support.
+#[derive(Clone)]
+pub struct SzDiagnostics {
+    env: Arc<SzEnvironmentCore>,
+}
+
+impl SzDiagnostics {
+    /// Creates a collector for the given environment.
+    pub fn new(env: Arc<SzEnvironmentCore>) -> Self {
+        Self { env }
+    }
+
+    /// Writes a support bundle to `path` and returns its manifest.
+    ///
+    /// An existing file at `path` is replaced.
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::BadInput` - The archive could not be written
+    pub fn collect_support_bundle(&self, path: impl AsRef<Path>) -> SzResult<BundleManifest> {
+        let env = &self.env;
+        let entries = vec![
+            (
+                "version.json",
+                env.get_product().and_then(|p| p.get_version()),
+            ),
+            (
+                "license.json",
+                env.get_product().and_then(|p| p.get_license()),
+            ),
+            ("config.json", self.active_config()),
+            (
+                "repository_info.json",
+                env.get_diagnostic()
+                    .and_then(|diagnostic| diagnostic.get_repository_info()),
+            ),
+            ("stats.json", env.get_engine().and_then(|e| e.get_stats())),
+            ("errors.json", Ok(self.error_history())),
+        ];
+        write_bundle(path.as_ref(), entries)
+    }
+
+    fn active_config(&self) -> SzResult<String> {
+        let config_id = self.env.get_active_config_id()?;
+        self.env
+            .get_config_manager()?
+            .create_config_from_id(config_id)?
+            .export()
+    }
+
+    fn error_history(&self) -> String {
+        let errors: Vec<_> = self
+            .env
+            .recent_errors()
+            .iter()
+            .map(|error| {
+                json!({
+                    "operation": error.operation,
+                    "category": error.category,
+                    "code": error.code,
+                    "component": error.component.map(|component| format!("{component:?}")),
+                    "message": redact::message(&error.message),
+                    "occurred_at": epoch_secs(error.occurred_at),
+                })
+            })
+            .collect();
+        serde_json::Value::Array(errors).to_string()
+    }
+}
+
+/// Writes the collected entries and the manifest to a new archive at `path`.
+fn write_bundle(
+    path: &Path,
+    entries: Vec<(&'static str, SzResult<String>)>,
+) -> SzResult<BundleManifest> {
+    let failed = |e: &dyn std::fmt::Display| {
+        SzError::bad_input(format!(
+            "Failed writing support bundle {}: {e}",
+            path.display()
+        ))
+    };
+    let file = std::fs::File::create(path).map_err(|e| failed(&e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    let mut manifest = BundleManifest {
+        sdk_version: env!("CARGO_PKG_VERSION"),
+        created_at: epoch_secs(SystemTime::now()),
+        redacted: redact::is_enabled(),
+        entries: Vec::with_capacity(entries.len()),
+    };
+    for (name, contents) in entries {
+        let error = match contents {
+            Ok(contents) => {
+                zip.start_file(name, options).map_err(|e| failed(&e))?;
+                zip.write_all(contents.as_bytes()).map_err(|e| failed(&e))?;
+                None
+            }
+            Err(e) => Some(redact::message(&e.to_string())),
+        };
+        manifest.entries.push(BundleEntry { name, error });
+    }
+
+    zip.start_file(MANIFEST_ENTRY, options)
+        .map_err(|e| failed(&e))?;
+    zip.write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())
+        .map_err(|e| failed(&e))?;
+    zip.finish().map_err(|e| failed(&e))?;
+    Ok(manifest)
+}
+
+fn epoch_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_write_bundle_records_failed_entries() {
+        let path = std::env::temp_dir().join(format!("sz_support_{}.zip", std::process::id()));
+        let manifest = write_bundle(
+            &path,
+            vec![
+                ("version.json", Ok(r#"{"VERSION":"4.3.1"}"#.to_string())),
+                (
+                    "license.json",
+                    Err(SzError::license("0001E|license 'ACME' expired")),
+                ),
+            ],
+        )
+        .unwrap();
+        assert!(!manifest.is_complete());
+        assert_eq!(manifest.entries[0].error, None);
+
+        let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+        let mut names: Vec<_> = archive.file_names().collect();
+        names.sort();
+        assert_eq!(names, ["manifest.json", "version.json"]);
+        let mut version = String::new();
+        archive
+            .by_name("version.json")
+            .unwrap()
+            .read_to_string(&mut version)
+            .unwrap();
+        assert_eq!(version, r#"{"VERSION":"4.3.1"}"#);
+        let mut written = String::new();
+        archive
+            .by_name(MANIFEST_ENTRY)
+            .unwrap()
+            .read_to_string(&mut written)
+            .unwrap();
+        let written: serde_json::Value = serde_json::from_str(&written).unwrap();
+        assert_eq!(written["entries"][1]["name"], "license.json");
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,247 @@
+//! Entities as documents of an Elasticsearch index.
+//!
+//! Each entity is indexed with its entity ID as the document `_id` and the
+//! entity, in the shape of an export line, as the source. Changes are
+//! buffered and sent as `_bulk` requests through an [`HttpTransport`] over
+//! the application's HTTP client, every [`DEFAULT_BULK_SIZE`] actions and on
+//! each flush.
+
+use super::EntityProjection;
+use crate::error::{SzError, SzResult};
+use crate::models::ExportedEntity;
+use crate::types::EntityId;
+use serde_json::json;
+
+/// Default number of index and delete actions per `_bulk` request.
+pub const DEFAULT_BULK_SIZE: usize = 500;
+
+/// Sends requests for an [`ElasticsearchProjection`].
+///
+/// Implement this over the application's HTTP client, adding the cluster
+/// address and credentials.
+pub trait HttpTransport {
+    /// Sends a `POST` to `path` with `body` and returns the response body.
+    /// Responses other than 2xx should be returned as errors.
+    fn post(&mut self, path: &str, content_type: &str, body: &str) -> SzResult<String>;
+}
+
+/// Writes entities to an Elasticsearch index; see the
+/// [module documentation](self).
+#[derive(Debug)]
+pub struct ElasticsearchProjection<T> {
+    transport: T,
+    index: String,
+    bulk_size: usize,
+    pending: String,
+    pending_actions: usize,
+}
+
+impl<T: HttpTransport> ElasticsearchProjection<T> {
+    /// Creates a projection writing to `index`.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if `index` is not a valid index name.
+    pub fn new(transport: T, index: &str) -> SzResult<Self> {
+        if !valid_index_name(index) {
+            return Err(SzError::bad_input(format!(
+                "Invalid Elasticsearch index name: {index}"
+            )));
+        }
+        Ok(Self {
+            transport,
+            index: index.to_string(),
+            bulk_size: DEFAULT_BULK_SIZE,
+            pending: String::new(),
+            pending_actions: 0,
+        })
+    }
+
+    /// Sets the number of actions per `_bulk` request; at least 1.
+    pub fn with_bulk_size(mut self, bulk_size: usize) -> Self {
+        self.bulk_size = bulk_size.max(1);
+        self
+    }
+
+    /// The index written to.
+    pub fn index(&self) -> &str {
+        &self.index
+    }
+
+    /// The transport sending the requests.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Returns the transport. Buffered actions are dropped; call
+    /// [`flush`](EntityProjection::flush) first.
+    pub fn into_transport(self) -> T {
+        self.transport
+    }
+
+    /// Buffers a bulk `action` on the document of `entity_id`, with the
+    /// document source for index actions.
+    pub(crate) fn push(
+        &mut self,
+        action: &str,
+        entity_id: EntityId,
+        source: Option<String>,
+    ) -> SzResult<()> {
+        let header = json!({ action: { "_index": self.index, "_id": entity_id.to_string() } });
+        self.pending.push_str(&header.to_string());
+        self.pending.push('\n');
+        if let Some(source) = source {
+            self.pending.push_str(&source);
+            self.pending.push('\n');
+        }
+        self.pending_actions += 1;
+        if self.pending_actions >= self.bulk_size {
+            self.send()?;
+        }
+        Ok(())
+    }
+
+    fn send(&mut self) -> SzResult<()> {
+        if self.pending_actions == 0 {
+            return Ok(());
+        }
+        let response = self
+            .transport
+            .post("/_bulk", "application/x-ndjson", &self.pending)?;
+        self.pending.clear();
+        self.pending_actions = 0;
+        bulk_error(&response).map_or(Ok(()), Err)
+    }
+}
+
+impl<T: HttpTransport> EntityProjection for ElasticsearchProjection<T> {
+    fn upsert(&mut self, entity: &ExportedEntity) -> SzResult<()> {
+        let source = serde_json::to_string(entity)?;
+        self.push("index", entity.resolved_entity.entity_id, Some(source))
+    }
+
+    fn remove(&mut self, entity_id: EntityId) -> SzResult<()> {
+        self.push("delete", entity_id, None)
+    }
+
+    fn flush(&mut self) -> SzResult<()> {
+        self.send()
+    }
+}
+
+/// Whether `index` is a valid Elasticsearch or OpenSearch index name.
+pub(crate) fn valid_index_name(index: &str) -> bool {
+    !index.is_empty()
+        && !index.starts_with(['-', '_', '+'])
+        && index != "."
+        && index != ".."
+        && index
+            .chars()
+            .all(|c| !c.is_ascii_uppercase() && !c.is_whitespace() && !r#"\/*?"<>|,#:"#.contains(c))
+}
+
+/// The first failed item of a `_bulk` response, if any. Deleting a missing
+/// document is not a failure.
+fn bulk_error(response: &str) -> Option<SzError> {
+    let response: serde_json::Value = match serde_json::from_str(response) {
+        Ok(response) => response,
+        Err(e) => return Some(e.into()),
+    };
+    if response["errors"].as_bool() != Some(true) {
+        return None;
+    }
+    let items = response["items"].as_array()?;
+    items.iter().find_map(|item| {
+        let (action, result) = item.as_object()?.iter().next()?;
+        let error = result.get("error")?;
+        Some(SzError::unknown(format!(
+            "Elasticsearch {action} of document {} failed: {}",
+            result["_id"].as_str().unwrap_or("?"),
+            error["reason"].as_str().unwrap_or("unknown reason")
+        )))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ResolvedEntity;
+
+    #[derive(Default)]
+    struct Recorder {
+        bodies: Vec<String>,
+        response: String,
+    }
+
+    impl HttpTransport for Recorder {
+        fn post(&mut self, path: &str, content_type: &str, body: &str) -> SzResult<String> {
+            assert_eq!((path, content_type), ("/_bulk", "application/x-ndjson"));
+            self.bodies.push(body.to_string());
+            Ok(self.response.clone())
+        }
+    }
+
+    fn entity(entity_id: i64) -> ExportedEntity {
+        ExportedEntity {
+            resolved_entity: ResolvedEntity {
+                entity_id: EntityId(entity_id),
+                entity_name: None,
+                records: Vec::new(),
+            },
+            related_entities: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_elasticsearch_bulk_requests() {
+        assert!(ElasticsearchProjection::new(Recorder::default(), "Entities").is_err());
+        assert!(ElasticsearchProjection::new(Recorder::default(), "_entities").is_err());
+
+        let transport = Recorder {
+            response: r#"{"took":3,"errors":false,"items":[]}"#.to_string(),
+            ..Recorder::default()
+        };
+        let mut projection = ElasticsearchProjection::new(transport, "entities")
+            .unwrap()
+            .with_bulk_size(2);
+        projection.upsert(&entity(1)).unwrap();
+        projection.remove(EntityId(2)).unwrap();
+        projection.upsert(&entity(3)).unwrap();
+        assert_eq!(projection.transport().bodies.len(), 1);
+        projection.flush().unwrap();
+        projection.flush().unwrap();
+
+        let bodies = projection.into_transport().bodies;
+        assert_eq!(bodies.len(), 2);
+        let lines: Vec<serde_json::Value> = bodies[0]
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            json!({"index": {"_index": "entities", "_id": "1"}})
+        );
+        assert_eq!(lines[1]["RESOLVED_ENTITY"]["ENTITY_ID"], 1);
+        assert_eq!(
+            lines[2],
+            json!({"delete": {"_index": "entities", "_id": "2"}})
+        );
+    }
+
+    #[test]
+    fn test_bulk_error_reports_first_failed_item() {
+        assert!(bulk_error(r#"{"errors":false,"items":[]}"#).is_none());
+        let response = r#"{"errors":true,"items":[
+            {"delete":{"_id":"2","status":404,"result":"not_found"}},
+            {"index":{"_id":"1","status":400,"error":{"type":"mapper_parsing_exception","reason":"failed to parse"}}}
+        ]}"#;
+        let error = bulk_error(response).unwrap();
+        assert!(
+            error
+                .to_string()
+                .contains("Elasticsearch index of document 1 failed: failed to parse")
+        );
+        assert!(bulk_error("<html>").is_some());
+    }
+}
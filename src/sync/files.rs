@@ -0,0 +1,96 @@
+//! Entities as JSON files in a directory.
+
+use super::EntityProjection;
+use crate::error::{SzError, SzResult};
+use crate::models::ExportedEntity;
+use crate::types::EntityId;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Writes each entity to `<entity id>.json` in a directory, in the shape of
+/// an export line.
+///
+/// Each file is replaced atomically, so readers never see a partial entity.
+#[derive(Debug, Clone)]
+pub struct JsonFileProjection {
+    dir: PathBuf,
+}
+
+impl JsonFileProjection {
+    /// Creates a projection writing to `dir`, creating the directory if
+    /// needed.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the directory cannot be created.
+    pub fn new(dir: impl Into<PathBuf>) -> SzResult<Self> {
+        let dir = dir.into();
+        std::fs::create_dir_all(&dir).map_err(|e| io_error(&dir, e))?;
+        Ok(Self { dir })
+    }
+
+    /// The directory the files are written to.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Path of the file holding `entity_id`.
+    pub fn path(&self, entity_id: EntityId) -> PathBuf {
+        self.dir.join(format!("{entity_id}.json"))
+    }
+}
+
+impl EntityProjection for JsonFileProjection {
+    fn upsert(&mut self, entity: &ExportedEntity) -> SzResult<()> {
+        let path = self.path(entity.resolved_entity.entity_id);
+        let temp = path.with_extension("json.tmp");
+        let json = serde_json::to_vec(entity)?;
+        let mut file = File::create(&temp).map_err(|e| io_error(&temp, e))?;
+        file.write_all(&json)
+            .and_then(|()| file.sync_data())
+            .map_err(|e| io_error(&temp, e))?;
+        std::fs::rename(&temp, &path).map_err(|e| io_error(&path, e))
+    }
+
+    fn remove(&mut self, entity_id: EntityId) -> SzResult<()> {
+        let path = self.path(entity_id);
+        match std::fs::remove_file(&path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(io_error(&path, e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn io_error(path: &Path, e: std::io::Error) -> SzError {
+    SzError::bad_input(format!("Entity file I/O on {} failed: {e}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ResolvedEntity;
+
+    #[test]
+    fn test_json_files_upsert_and_remove() {
+        let dir = std::env::temp_dir().join(format!("sz_sync_files_{}", std::process::id()));
+        let mut projection = JsonFileProjection::new(&dir).unwrap();
+        let entity = ExportedEntity {
+            resolved_entity: ResolvedEntity {
+                entity_id: EntityId(7),
+                entity_name: Some("Ann Lee".to_string()),
+                records: Vec::new(),
+            },
+            related_entities: Vec::new(),
+        };
+        projection.upsert(&entity).unwrap();
+        let written: ExportedEntity =
+            serde_json::from_slice(&std::fs::read(projection.path(EntityId(7))).unwrap()).unwrap();
+        assert_eq!(written, entity);
+
+        projection.remove(EntityId(7)).unwrap();
+        assert!(!projection.path(EntityId(7)).exists());
+        projection.remove(EntityId(7)).unwrap();
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
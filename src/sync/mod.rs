@@ -0,0 +1,291 @@
+//! Keeping a denormalized copy of entities in an external store.
+//!
+//! Search and reporting systems usually want one row or document per entity,
+//! holding its name, records and relationships, rather than the records the
+//! engine was given. An [`EntitySync`] keeps such a copy up to date through an
+//! [`EntityProjection`]:
+//!
+//! 1. [`backfill`](EntitySync::backfill) exports every entity and writes it
+//!    to the projection.
+//! 2. [`apply`](EntitySync::apply) takes each later `WITH_INFO` response,
+//!    turns it into [`EntityEvent`]s with an [`EntityChangeStream`], reads the
+//!    entities that changed and writes or removes them. Entities merged away
+//!    are removed without a lookup.
+//!
+//! Responses must be applied in the order the operations ran, as for
+//! [`EntityChangeStream`]. Backfill teaches the stream every record in the
+//! repository, so merges and splits of existing entities are recognized;
+//! the memory this takes grows with the record count.
+//!
+//! Ready-made projections:
+//!
+//! - [`JsonFileProjection`]: one JSON file per entity in a directory.
+//! - [`PostgresProjection`]: one row per entity in a PostgreSQL table.
+//! - [`ElasticsearchProjection`]: one document per entity in an
+//!   Elasticsearch index.
+//! - [`Neo4jProjection`]: one node per entity and one edge per relationship
+//!   in Neo4j, for link analysis.
+//!
+//! With the `search-ahead` feature, [`search_ahead::SearchAheadIndex`]
+//! keeps searchable entity summaries in Elasticsearch or OpenSearch for
+//! fuzzy candidate retrieval confirmed by the engine.
+//!
+//! The SDK does not bundle a database or HTTP client: the PostgreSQL,
+//! Elasticsearch and Neo4j projections build the statements, request bodies
+//! and queries and hand them to a [`SqlExecutor`], [`HttpTransport`] or
+//! [`CypherExecutor`] implemented over the application's own client.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::prelude::*;
+//! use sz_rust_sdk::sync::{EntitySync, JsonFileProjection};
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_entity_sync")?;
+//! let engine = env.get_engine()?;
+//! let mut sync = EntitySync::new(&*engine, JsonFileProjection::new("entities")?);
+//! let summary = sync.backfill()?;
+//! println!("backfilled {} entities", summary.upserted);
+//!
+//! let info = engine.add_record(
+//!     "TEST",
+//!     "1001",
+//!     r#"{"NAME_FULL": "Ann Lee"}"#,
+//!     Some(SzFlags::WITH_INFO),
+//! )?;
+//! sync.apply(&info)?;
+//! # Ok::<(), SzError>(())
+//! ```
+
+pub mod elasticsearch;
+pub mod files;
+pub mod neo4j;
+pub mod postgres;
+#[cfg(feature = "search-ahead")]
+pub mod search_ahead;
+
+pub use elasticsearch::{ElasticsearchProjection, HttpTransport};
+pub use files::JsonFileProjection;
+pub use neo4j::{CypherExecutor, Neo4jProjection};
+pub use postgres::{PostgresProjection, SqlExecutor, SqlValue};
+
+use crate::error::{SzError, SzResult};
+use crate::events::{EntityChangeStream, EntityEvent};
+use crate::export;
+use crate::flags::SzFlags;
+use crate::models::ExportedEntity;
+use crate::traits::SzEngine;
+use crate::types::{EntityId, EntityRef};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Flags used to read each entity written to a projection: its name, its
+/// records with their match keys, and its relationships.
+pub const SYNC_ENTITY_FLAGS: SzFlags = SzFlags::from_bits_truncate(
+    SzFlags::ENTITY_INCLUDE_ENTITY_NAME.bits()
+        | SzFlags::ENTITY_INCLUDE_RECORD_DATA.bits()
+        | SzFlags::ENTITY_INCLUDE_RECORD_MATCHING_INFO.bits()
+        | SzFlags::ENTITY_INCLUDE_ALL_RELATIONS.bits()
+        | SzFlags::ENTITY_INCLUDE_RELATED_ENTITY_NAME.bits()
+        | SzFlags::ENTITY_INCLUDE_RELATED_MATCHING_INFO.bits(),
+);
+
+/// A store holding one entry per entity, kept up to date by [`EntitySync`].
+///
+/// Writes may be buffered until [`flush`](Self::flush), which `EntitySync`
+/// calls at the end of each backfill and each applied response.
+pub trait EntityProjection {
+    /// Inserts the entity, or replaces the entry with the same entity ID.
+    fn upsert(&mut self, entity: &ExportedEntity) -> SzResult<()>;
+
+    /// Removes the entry of an entity that no longer exists. Removing an
+    /// entity that has no entry is not an error.
+    fn remove(&mut self, entity_id: EntityId) -> SzResult<()>;
+
+    /// Writes out any buffered changes.
+    fn flush(&mut self) -> SzResult<()> {
+        Ok(())
+    }
+}
+
+/// Entries written by one [`EntitySync`] call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SyncSummary {
+    /// Entities inserted or replaced
+    pub upserted: u64,
+    /// Entities removed
+    pub removed: u64,
+}
+
+impl fmt::Display for SyncSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} upserted, {} removed", self.upserted, self.removed)
+    }
+}
+
+/// Keeps an [`EntityProjection`] in step with the repository; see the
+/// [module documentation](self).
+pub struct EntitySync<'a, P> {
+    engine: &'a dyn SzEngine,
+    changes: EntityChangeStream<'a>,
+    projection: P,
+}
+
+impl<'a, P: EntityProjection> EntitySync<'a, P> {
+    /// Creates a sync that writes to `projection`.
+    pub fn new(engine: &'a dyn SzEngine, projection: P) -> Self {
+        Self {
+            engine,
+            changes: EntityChangeStream::new(engine),
+            projection,
+        }
+    }
+
+    /// Writes every entity in the repository to the projection.
+    ///
+    /// Entries of entities that no longer exist are not removed; start from
+    /// an empty projection.
+    ///
+    /// # Errors
+    ///
+    /// Any error from the export or the projection.
+    pub fn backfill(&mut self) -> SzResult<SyncSummary> {
+        let mut summary = SyncSummary::default();
+        let flags = SzFlags::EXPORT_INCLUDE_ALL_ENTITIES | SYNC_ENTITY_FLAGS;
+        export::for_each_entity(self.engine, flags, |entity| {
+            self.changes.observe(&entity);
+            self.projection.upsert(&entity)?;
+            summary.upserted += 1;
+            Ok(())
+        })?;
+        self.projection.flush()?;
+        Ok(summary)
+    }
+
+    /// Applies the changes of one `WITH_INFO` response to the projection.
+    ///
+    /// # Errors
+    ///
+    /// * Any error from [`EntityChangeStream::process`]
+    /// * Any error from `SzEngine::get_entity` other than
+    ///   `SzError::NotFound`
+    /// * Any error from the projection
+    pub fn apply(&mut self, info: &str) -> SzResult<SyncSummary> {
+        let events = self.changes.process(info)?;
+        let (refresh, gone) = plan(&events);
+        let mut summary = SyncSummary::default();
+        for entity_id in refresh {
+            let entity = match self
+                .engine
+                .get_entity(EntityRef::Id(entity_id), Some(SYNC_ENTITY_FLAGS))
+            {
+                Ok(json) => Some(serde_json::from_str::<ExportedEntity>(&json)?),
+                Err(SzError::NotFound(_)) => None,
+                Err(e) => return Err(e),
+            };
+            match entity {
+                Some(entity) => {
+                    self.projection.upsert(&entity)?;
+                    summary.upserted += 1;
+                }
+                None => {
+                    self.projection.remove(entity_id)?;
+                    summary.removed += 1;
+                }
+            }
+        }
+        for entity_id in gone {
+            self.projection.remove(entity_id)?;
+            summary.removed += 1;
+        }
+        self.projection.flush()?;
+        Ok(summary)
+    }
+
+    /// The projection being written.
+    pub fn projection(&self) -> &P {
+        &self.projection
+    }
+
+    /// Ends the sync and returns the projection.
+    pub fn into_projection(self) -> P {
+        self.projection
+    }
+}
+
+/// Splits the entities named by `events` into those to read again and
+/// those known to be gone.
+fn plan(events: &[EntityEvent]) -> (BTreeSet<EntityId>, BTreeSet<EntityId>) {
+    let mut refresh = BTreeSet::new();
+    let mut gone = BTreeSet::new();
+    for event in events {
+        match event {
+            EntityEvent::EntityMerged { survivor, absorbed } => {
+                refresh.insert(*survivor);
+                gone.extend(absorbed);
+            }
+            EntityEvent::EntitySplit {
+                origin,
+                new_entities,
+            } => {
+                refresh.insert(*origin);
+                refresh.extend(new_entities);
+            }
+            EntityEvent::EntityUpdated { entity_id } => {
+                refresh.insert(*entity_id);
+            }
+            EntityEvent::EntityRemoved { entity_id } => {
+                gone.insert(*entity_id);
+            }
+        }
+    }
+    gone.retain(|entity_id| !refresh.contains(entity_id));
+    (refresh, gone)
+}
+
+/// Distinct data source codes of an entity's records, sorted.
+pub(crate) fn data_sources(entity: &ExportedEntity) -> Vec<String> {
+    entity
+        .resolved_entity
+        .records
+        .iter()
+        .map(|record| record.data_source.clone())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plan_reads_survivors_and_drops_absorbed() {
+        let events = [
+            EntityEvent::EntityMerged {
+                survivor: EntityId(1),
+                absorbed: vec![EntityId(2), EntityId(3)],
+            },
+            EntityEvent::EntitySplit {
+                origin: EntityId(4),
+                new_entities: vec![EntityId(5)],
+            },
+            EntityEvent::EntityRemoved {
+                entity_id: EntityId(6),
+            },
+            EntityEvent::EntityUpdated {
+                entity_id: EntityId(7),
+            },
+        ];
+        let (refresh, gone) = plan(&events);
+        assert_eq!(
+            refresh.into_iter().collect::<Vec<_>>(),
+            [EntityId(1), EntityId(4), EntityId(5), EntityId(7)]
+        );
+        assert_eq!(
+            gone.into_iter().collect::<Vec<_>>(),
+            [EntityId(2), EntityId(3), EntityId(6)]
+        );
+    }
+}
@@ -0,0 +1,293 @@
+//! Entities and their relationships as a Neo4j graph.
+//!
+//! Each entity becomes a node, labeled `Entity` by default, with these
+//! properties:
+//!
+//! | Property | Contents |
+//! |----------|----------|
+//! | `entity_id` | Entity ID, unique |
+//! | `name` | Best name, if any |
+//! | `record_count` | Records in the entity |
+//! | `data_sources` | Distinct data sources of the records, sorted |
+//! | `records` | Record keys as `DATA_SOURCE:RECORD_ID` |
+//!
+//! Each relationship becomes one `RELATED_TO` edge, from the lower entity ID
+//! to the higher, with `match_key`, `match_level`, `match_level_code`,
+//! `is_disclosed` and `is_ambiguous` properties. Upserting an entity
+//! replaces all its edges, so edges to entities it is no longer related to
+//! go away.
+//!
+//! Queries use `$name` parameters and are run by a [`CypherExecutor`] over
+//! the application's bolt driver. Run
+//! [`constraint_cypher`](Neo4jProjection::constraint_cypher) once first, so
+//! the node lookups by entity ID are indexed.
+
+use super::{EntityProjection, data_sources};
+use crate::error::{SzError, SzResult};
+use crate::models::ExportedEntity;
+use crate::types::EntityId;
+use serde_json::{Value, json};
+
+/// Default label of entity nodes.
+pub const DEFAULT_ENTITY_LABEL: &str = "Entity";
+
+/// Default type of relationship edges.
+pub const DEFAULT_RELATIONSHIP_TYPE: &str = "RELATED_TO";
+
+/// Runs Cypher queries for a [`Neo4jProjection`].
+///
+/// Implement this over the application's bolt driver, passing `params` (a
+/// JSON object) as the query parameters. Running the queries of one
+/// `EntitySync` call in a transaction, committed in
+/// [`flush`](Self::flush), keeps the graph consistent if a call fails.
+pub trait CypherExecutor {
+    /// Runs one query.
+    fn run(&mut self, query: &str, params: &Value) -> SzResult<()>;
+
+    /// Called after each backfill and each applied response.
+    fn flush(&mut self) -> SzResult<()> {
+        Ok(())
+    }
+}
+
+/// Writes entities and relationships to Neo4j; see the
+/// [module documentation](self).
+#[derive(Debug)]
+pub struct Neo4jProjection<E> {
+    executor: E,
+    entity_label: String,
+    relationship_type: String,
+}
+
+impl<E: CypherExecutor> Neo4jProjection<E> {
+    /// Creates a projection with the default node label and edge type.
+    pub fn new(executor: E) -> Self {
+        Self {
+            executor,
+            entity_label: DEFAULT_ENTITY_LABEL.to_string(),
+            relationship_type: DEFAULT_RELATIONSHIP_TYPE.to_string(),
+        }
+    }
+
+    /// Sets the label of entity nodes.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if `label` is not a plain Cypher identifier.
+    pub fn with_entity_label(mut self, label: &str) -> SzResult<Self> {
+        self.entity_label = identifier(label)?;
+        Ok(self)
+    }
+
+    /// Sets the type of relationship edges.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if `relationship_type` is not a plain Cypher
+    /// identifier.
+    pub fn with_relationship_type(mut self, relationship_type: &str) -> SzResult<Self> {
+        self.relationship_type = identifier(relationship_type)?;
+        Ok(self)
+    }
+
+    /// `CREATE CONSTRAINT` query making `entity_id` unique on entity nodes.
+    pub fn constraint_cypher(&self) -> String {
+        format!(
+            "CREATE CONSTRAINT {label}_entity_id IF NOT EXISTS \
+             FOR (e:{label}) REQUIRE e.entity_id IS UNIQUE",
+            label = self.entity_label
+        )
+    }
+
+    /// Runs [`constraint_cypher`](Self::constraint_cypher).
+    ///
+    /// # Errors
+    ///
+    /// Any error from the executor.
+    pub fn create_constraint(&mut self) -> SzResult<()> {
+        let query = self.constraint_cypher();
+        self.executor.run(&query, &json!({}))
+    }
+
+    /// The executor running the queries.
+    pub fn executor(&self) -> &E {
+        &self.executor
+    }
+
+    /// Returns the executor.
+    pub fn into_executor(self) -> E {
+        self.executor
+    }
+}
+
+impl<E: CypherExecutor> EntityProjection for Neo4jProjection<E> {
+    fn upsert(&mut self, entity: &ExportedEntity) -> SzResult<()> {
+        let (label, edge) = (&self.entity_label, &self.relationship_type);
+        let resolved = &entity.resolved_entity;
+        let entity_id = resolved.entity_id.0;
+        let records: Vec<String> = resolved
+            .records
+            .iter()
+            .map(|record| format!("{}:{}", record.data_source, record.record_id))
+            .collect();
+        self.executor.run(
+            &format!(
+                "MERGE (e:{label} {{entity_id: $entity_id}}) \
+                 SET e.name = $name, e.record_count = $record_count, \
+                 e.data_sources = $data_sources, e.records = $records"
+            ),
+            &json!({
+                "entity_id": entity_id,
+                "name": resolved.entity_name,
+                "record_count": resolved.records.len(),
+                "data_sources": data_sources(entity),
+                "records": records,
+            }),
+        )?;
+        self.executor.run(
+            &format!("MATCH (:{label} {{entity_id: $entity_id}})-[old:{edge}]-() DELETE old"),
+            &json!({ "entity_id": entity_id }),
+        )?;
+        if entity.related_entities.is_empty() {
+            return Ok(());
+        }
+        let relationships: Vec<Value> = entity
+            .related_entities
+            .iter()
+            .map(|related| {
+                let other = related.entity_id.0;
+                json!({
+                    "from": entity_id.min(other),
+                    "to": entity_id.max(other),
+                    "match_key": related.match_key,
+                    "match_level": related.match_level,
+                    "match_level_code": related.match_level_code,
+                    "is_disclosed": related.is_disclosed.map(|flag| flag != 0),
+                    "is_ambiguous": related.is_ambiguous.map(|flag| flag != 0),
+                })
+            })
+            .collect();
+        self.executor.run(
+            &format!(
+                "UNWIND $relationships AS rel \
+                 MERGE (a:{label} {{entity_id: rel.from}}) \
+                 MERGE (b:{label} {{entity_id: rel.to}}) \
+                 MERGE (a)-[r:{edge}]->(b) \
+                 SET r.match_key = rel.match_key, r.match_level = rel.match_level, \
+                 r.match_level_code = rel.match_level_code, \
+                 r.is_disclosed = rel.is_disclosed, r.is_ambiguous = rel.is_ambiguous"
+            ),
+            &json!({ "relationships": relationships }),
+        )
+    }
+
+    fn remove(&mut self, entity_id: EntityId) -> SzResult<()> {
+        self.executor.run(
+            &format!(
+                "MATCH (e:{} {{entity_id: $entity_id}}) DETACH DELETE e",
+                self.entity_label
+            ),
+            &json!({ "entity_id": entity_id.0 }),
+        )
+    }
+
+    fn flush(&mut self) -> SzResult<()> {
+        self.executor.flush()
+    }
+}
+
+/// Checks that `name` can be used as a label or edge type without quoting.
+fn identifier(name: &str) -> SzResult<String> {
+    let valid = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !valid {
+        return Err(SzError::bad_input(format!(
+            "Invalid Neo4j label or relationship type: {name}"
+        )));
+    }
+    Ok(name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{EntityRecord, RelatedEntity, ResolvedEntity};
+
+    #[derive(Default)]
+    struct Recorder(Vec<(String, Value)>);
+
+    impl CypherExecutor for Recorder {
+        fn run(&mut self, query: &str, params: &Value) -> SzResult<()> {
+            self.0.push((query.to_string(), params.clone()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_neo4j_queries() {
+        assert!(
+            Neo4jProjection::new(Recorder::default())
+                .with_entity_label("Entity) DETACH DELETE (x")
+                .is_err()
+        );
+        let mut projection = Neo4jProjection::new(Recorder::default())
+            .with_entity_label("Person")
+            .unwrap();
+        assert_eq!(
+            projection.constraint_cypher(),
+            "CREATE CONSTRAINT Person_entity_id IF NOT EXISTS \
+             FOR (e:Person) REQUIRE e.entity_id IS UNIQUE"
+        );
+
+        let entity = ExportedEntity {
+            resolved_entity: ResolvedEntity {
+                entity_id: EntityId(20),
+                entity_name: Some("Ann Lee".to_string()),
+                records: vec![EntityRecord {
+                    data_source: "CRM".to_string(),
+                    record_id: "1".to_string(),
+                    match_key: None,
+                    errule_code: None,
+                }],
+            },
+            related_entities: vec![RelatedEntity {
+                entity_id: EntityId(4),
+                entity_name: None,
+                match_level: Some(2),
+                match_level_code: Some("POSSIBLY_SAME".to_string()),
+                match_key: Some("+NAME+PHONE".to_string()),
+                is_disclosed: Some(0),
+                is_ambiguous: Some(0),
+            }],
+        };
+        projection.upsert(&entity).unwrap();
+        projection.remove(EntityId(5)).unwrap();
+
+        let queries = projection.into_executor().0;
+        assert_eq!(queries.len(), 4);
+        assert!(
+            queries[0]
+                .0
+                .starts_with("MERGE (e:Person {entity_id: $entity_id})")
+        );
+        assert_eq!(queries[0].1["records"], json!(["CRM:1"]));
+        assert!(queries[1].0.contains("-[old:RELATED_TO]-() DELETE old"));
+        assert_eq!(
+            queries[2].1["relationships"][0],
+            json!({
+                "from": 4, "to": 20, "match_key": "+NAME+PHONE", "match_level": 2,
+                "match_level_code": "POSSIBLY_SAME", "is_disclosed": false, "is_ambiguous": false
+            })
+        );
+        assert_eq!(
+            queries[3],
+            (
+                "MATCH (e:Person {entity_id: $entity_id}) DETACH DELETE e".to_string(),
+                json!({"entity_id": 5})
+            )
+        );
+    }
+}
@@ -0,0 +1,222 @@
+//! Entities as rows of a PostgreSQL table.
+//!
+//! The table has one row per entity:
+//!
+//! | Column | Type | Contents |
+//! |--------|------|----------|
+//! | `entity_id` | `BIGINT PRIMARY KEY` | Entity ID |
+//! | `entity_name` | `TEXT` | Best name, if any |
+//! | `record_count` | `INTEGER` | Records in the entity |
+//! | `data_sources` | `TEXT[]` | Distinct data sources of the records, sorted |
+//! | `document` | `JSONB` | The entity in the shape of an export line |
+//! | `updated_at` | `TIMESTAMPTZ` | When the row was last written |
+//!
+//! [`PostgresProjection::create_table_sql`] gives the DDL. Statements use
+//! `$1`-style placeholders and are run by a [`SqlExecutor`] over the
+//! application's PostgreSQL client.
+
+use super::{EntityProjection, data_sources};
+use crate::error::{SzError, SzResult};
+use crate::models::ExportedEntity;
+use crate::types::EntityId;
+
+/// A parameter of a statement run by a [`SqlExecutor`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SqlValue {
+    /// `BIGINT`
+    BigInt(i64),
+    /// `INTEGER`
+    Integer(i32),
+    /// `TEXT`, possibly `NULL`
+    Text(Option<String>),
+    /// `TEXT[]`
+    TextArray(Vec<String>),
+    /// `JSONB`, as JSON text
+    Jsonb(String),
+}
+
+/// Runs statements for a [`PostgresProjection`].
+///
+/// Implement this over the application's client, binding each [`SqlValue`]
+/// to the placeholder of the same position. Wrapping the statements of one
+/// `EntitySync` call in a transaction, committed in
+/// [`flush`](Self::flush), keeps the table consistent if a call fails.
+pub trait SqlExecutor {
+    /// Runs one statement and returns the number of rows it changed.
+    fn execute(&mut self, statement: &str, params: &[SqlValue]) -> SzResult<u64>;
+
+    /// Called after each backfill and each applied response.
+    fn flush(&mut self) -> SzResult<()> {
+        Ok(())
+    }
+}
+
+/// Writes entities to a PostgreSQL table; see the
+/// [module documentation](self).
+#[derive(Debug)]
+pub struct PostgresProjection<E> {
+    executor: E,
+    table: String,
+}
+
+impl<E: SqlExecutor> PostgresProjection<E> {
+    /// Creates a projection writing to `table`, which may be qualified with
+    /// a schema.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if `table` is not a plain SQL identifier.
+    pub fn new(executor: E, table: &str) -> SzResult<Self> {
+        let valid = table.split('.').all(|part| {
+            part.chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && part.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        });
+        if !valid {
+            return Err(SzError::bad_input(format!(
+                "Invalid PostgreSQL table name: {table}"
+            )));
+        }
+        Ok(Self {
+            executor,
+            table: table.to_string(),
+        })
+    }
+
+    /// `CREATE TABLE IF NOT EXISTS` statement for the table.
+    pub fn create_table_sql(&self) -> String {
+        format!(
+            "CREATE TABLE IF NOT EXISTS {} (\
+             entity_id BIGINT PRIMARY KEY, \
+             entity_name TEXT, \
+             record_count INTEGER NOT NULL, \
+             data_sources TEXT[] NOT NULL, \
+             document JSONB NOT NULL, \
+             updated_at TIMESTAMPTZ NOT NULL DEFAULT now())",
+            self.table
+        )
+    }
+
+    /// Runs [`create_table_sql`](Self::create_table_sql).
+    ///
+    /// # Errors
+    ///
+    /// Any error from the executor.
+    pub fn create_table(&mut self) -> SzResult<()> {
+        let statement = self.create_table_sql();
+        self.executor.execute(&statement, &[]).map(|_| ())
+    }
+
+    /// The executor running the statements.
+    pub fn executor(&self) -> &E {
+        &self.executor
+    }
+
+    /// Returns the executor.
+    pub fn into_executor(self) -> E {
+        self.executor
+    }
+}
+
+impl<E: SqlExecutor> EntityProjection for PostgresProjection<E> {
+    fn upsert(&mut self, entity: &ExportedEntity) -> SzResult<()> {
+        let resolved = &entity.resolved_entity;
+        let statement = format!(
+            "INSERT INTO {} (entity_id, entity_name, record_count, data_sources, document, updated_at) \
+             VALUES ($1, $2, $3, $4, $5, now()) \
+             ON CONFLICT (entity_id) DO UPDATE SET \
+             entity_name = EXCLUDED.entity_name, \
+             record_count = EXCLUDED.record_count, \
+             data_sources = EXCLUDED.data_sources, \
+             document = EXCLUDED.document, \
+             updated_at = EXCLUDED.updated_at",
+            self.table
+        );
+        let params = [
+            SqlValue::BigInt(resolved.entity_id.0),
+            SqlValue::Text(resolved.entity_name.clone()),
+            SqlValue::Integer(i32::try_from(resolved.records.len()).unwrap_or(i32::MAX)),
+            SqlValue::TextArray(data_sources(entity)),
+            SqlValue::Jsonb(serde_json::to_string(entity)?),
+        ];
+        self.executor.execute(&statement, &params).map(|_| ())
+    }
+
+    fn remove(&mut self, entity_id: EntityId) -> SzResult<()> {
+        let statement = format!("DELETE FROM {} WHERE entity_id = $1", self.table);
+        self.executor
+            .execute(&statement, &[SqlValue::BigInt(entity_id.0)])
+            .map(|_| ())
+    }
+
+    fn flush(&mut self) -> SzResult<()> {
+        self.executor.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{EntityRecord, ResolvedEntity};
+
+    #[derive(Default)]
+    struct Recorder(Vec<(String, Vec<SqlValue>)>);
+
+    impl SqlExecutor for Recorder {
+        fn execute(&mut self, statement: &str, params: &[SqlValue]) -> SzResult<u64> {
+            self.0.push((statement.to_string(), params.to_vec()));
+            Ok(1)
+        }
+    }
+
+    #[test]
+    fn test_postgres_statements() {
+        assert!(PostgresProjection::new(Recorder::default(), "entities; DROP TABLE x").is_err());
+        assert!(PostgresProjection::new(Recorder::default(), "1entities").is_err());
+        let mut projection = PostgresProjection::new(Recorder::default(), "mdm.entities").unwrap();
+        assert!(
+            projection
+                .create_table_sql()
+                .starts_with("CREATE TABLE IF NOT EXISTS mdm.entities (entity_id BIGINT")
+        );
+
+        let record = |data_source: &str, record_id: &str| EntityRecord {
+            data_source: data_source.to_string(),
+            record_id: record_id.to_string(),
+            match_key: None,
+            errule_code: None,
+        };
+        let entity = ExportedEntity {
+            resolved_entity: ResolvedEntity {
+                entity_id: EntityId(12),
+                entity_name: Some("Ann Lee".to_string()),
+                records: vec![record("WEB", "1"), record("CRM", "2"), record("WEB", "3")],
+            },
+            related_entities: Vec::new(),
+        };
+        projection.upsert(&entity).unwrap();
+        projection.remove(EntityId(13)).unwrap();
+
+        let statements = projection.into_executor().0;
+        assert!(statements[0].0.starts_with("INSERT INTO mdm.entities"));
+        assert!(
+            statements[0]
+                .0
+                .contains("ON CONFLICT (entity_id) DO UPDATE")
+        );
+        assert_eq!(statements[0].1[0], SqlValue::BigInt(12));
+        assert_eq!(statements[0].1[2], SqlValue::Integer(3));
+        assert_eq!(
+            statements[0].1[3],
+            SqlValue::TextArray(vec!["CRM".to_string(), "WEB".to_string()])
+        );
+        assert_eq!(
+            statements[1],
+            (
+                "DELETE FROM mdm.entities WHERE entity_id = $1".to_string(),
+                vec![SqlValue::BigInt(13)]
+            )
+        );
+    }
+}
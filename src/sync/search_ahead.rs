@@ -0,0 +1,388 @@
+//! Search-ahead index of entity summaries in Elasticsearch or OpenSearch.
+//!
+//! Type-ahead and fuzzy lookups over millions of entities are a job for a
+//! text index, while deciding whether a candidate really matches is a job for
+//! the engine. A [`SearchAheadIndex`] is an [`EntityProjection`] that keeps
+//! one small [`EntitySummary`] document per entity (entity ID, names and
+//! identifiers) in an index, and [`search_fuzzy_then_resolve`] combines the
+//! two: it asks the index for candidates with a fuzzy name query and exact
+//! identifier terms, then confirms each candidate with
+//! `SzEngine::explain_search`.
+//!
+//! Create the index with [`index_mapping`] before the first backfill, so
+//! names are analyzed text and identifiers are exact keywords. The same
+//! requests work against Elasticsearch and OpenSearch.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::prelude::*;
+//! use sz_rust_sdk::sync::search_ahead::{
+//!     DEFAULT_MAX_CANDIDATES, SearchAheadIndex, search_fuzzy_then_resolve,
+//! };
+//! use sz_rust_sdk::sync::{EntitySync, HttpTransport};
+//!
+//! struct OpenSearch;
+//!
+//! impl HttpTransport for OpenSearch {
+//!     fn post(&mut self, path: &str, content_type: &str, body: &str) -> SzResult<String> {
+//!         // Send with the application's HTTP client
+//!         # let _ = (path, content_type, body);
+//!         Ok(r#"{"errors":false,"items":[],"hits":{"hits":[]}}"#.to_string())
+//!     }
+//! }
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_search_ahead")?;
+//! let engine = env.get_engine()?;
+//! let index = SearchAheadIndex::new(&*engine, OpenSearch, "entities")?;
+//! EntitySync::new(&*engine, index).backfill()?;
+//!
+//! let matches = search_fuzzy_then_resolve(
+//!     &*engine,
+//!     &mut OpenSearch,
+//!     "entities",
+//!     r#"{"NAME_FULL": "Jon Smyth", "PHONE_NUMBER": "555-0100"}"#,
+//!     DEFAULT_MAX_CANDIDATES,
+//! )?;
+//! for found in &matches {
+//!     println!("{} {:?}", found.entity_id, found.explanation.why_key);
+//! }
+//! # Ok::<(), SzError>(())
+//! ```
+
+use super::elasticsearch::valid_index_name;
+use super::{ElasticsearchProjection, EntityProjection, HttpTransport};
+use crate::error::{SzError, SzResult};
+use crate::flags::SzFlags;
+use crate::models::{ExportedEntity, SearchExplanation};
+use crate::redact::Sensitive;
+use crate::traits::SzEngine;
+use crate::types::{EntityId, EntityRef};
+use serde::{Deserialize, Serialize};
+use serde_json::{Value, json};
+use std::collections::BTreeSet;
+use std::fmt;
+
+/// Flags used to read the entity behind each summary.
+pub const SEARCH_AHEAD_FLAGS: SzFlags = SzFlags::from_bits_truncate(
+    SzFlags::ENTITY_INCLUDE_ENTITY_NAME.bits() | SzFlags::ENTITY_INCLUDE_ALL_FEATURES.bits(),
+);
+
+/// Feature types whose values go into [`EntitySummary::identifiers`].
+pub const IDENTIFIER_FEATURES: &[&str] = &[
+    "SSN",
+    "PASSPORT",
+    "DRLIC",
+    "NATIONAL_ID",
+    "TAX_ID",
+    "OTHER_ID",
+    "ACCT_NUM",
+    "EMAIL",
+    "PHONE",
+];
+
+/// Default number of index hits [`search_fuzzy_then_resolve`] confirms.
+pub const DEFAULT_MAX_CANDIDATES: usize = 20;
+
+/// The searchable summary of one entity, as indexed.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EntitySummary {
+    /// Entity ID
+    pub entity_id: EntityId,
+    /// Best name, if any
+    pub entity_name: Option<String>,
+    /// Distinct `NAME` feature values, sorted
+    pub names: Vec<String>,
+    /// Distinct values of the [`IDENTIFIER_FEATURES`], sorted
+    pub identifiers: Vec<String>,
+}
+
+impl fmt::Debug for EntitySummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EntitySummary")
+            .field("entity_id", &self.entity_id)
+            .field("entity_name", &Sensitive(&self.entity_name))
+            .field("names", &Sensitive(&self.names))
+            .field("identifiers", &Sensitive(&self.identifiers))
+            .finish()
+    }
+}
+
+impl EntitySummary {
+    /// Builds the summary from `SzEngine::get_entity` output read with
+    /// [`SEARCH_AHEAD_FLAGS`].
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::Json` - The document is not valid JSON
+    /// * `SzError::BadInput` - The document has no entity ID
+    pub fn from_entity_json(json: &str) -> SzResult<Self> {
+        let document: Value = serde_json::from_str(json)?;
+        let entity = &document["RESOLVED_ENTITY"];
+        let Some(entity_id) = entity["ENTITY_ID"].as_i64() else {
+            return Err(SzError::bad_input("Entity document has no ENTITY_ID"));
+        };
+        let mut names = BTreeSet::new();
+        let mut identifiers = BTreeSet::new();
+        for (feature_type, values) in entity["FEATURES"].as_object().into_iter().flatten() {
+            let target = if feature_type == "NAME" {
+                &mut names
+            } else if IDENTIFIER_FEATURES.contains(&feature_type.as_str()) {
+                &mut identifiers
+            } else {
+                continue;
+            };
+            target.extend(
+                values
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|value| value["FEAT_DESC"].as_str())
+                    .map(str::to_string),
+            );
+        }
+        Ok(Self {
+            entity_id: EntityId(entity_id),
+            entity_name: entity["ENTITY_NAME"].as_str().map(str::to_string),
+            names: names.into_iter().collect(),
+            identifiers: identifiers.into_iter().collect(),
+        })
+    }
+}
+
+/// Index settings and mappings for [`EntitySummary`] documents, for the
+/// body of the create-index request.
+pub fn index_mapping() -> Value {
+    json!({
+        "mappings": {
+            "properties": {
+                "entity_id": {"type": "long"},
+                "entity_name": {"type": "text"},
+                "names": {"type": "text"},
+                "identifiers": {"type": "keyword"}
+            }
+        }
+    })
+}
+
+/// Keeps [`EntitySummary`] documents in an index; see the
+/// [module documentation](self).
+///
+/// Each upsert reads the entity again with [`SEARCH_AHEAD_FLAGS`], since
+/// the entity [`EntitySync`](super::EntitySync) passes in has no features.
+pub struct SearchAheadIndex<'a, T> {
+    engine: &'a dyn SzEngine,
+    bulk: ElasticsearchProjection<T>,
+}
+
+impl<'a, T: HttpTransport> SearchAheadIndex<'a, T> {
+    /// Creates an index projection writing to `index` through `transport`.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if `index` is not a valid index name.
+    pub fn new(engine: &'a dyn SzEngine, transport: T, index: &str) -> SzResult<Self> {
+        Ok(Self {
+            engine,
+            bulk: ElasticsearchProjection::new(transport, index)?,
+        })
+    }
+
+    /// Sets the number of actions per `_bulk` request; at least 1.
+    pub fn with_bulk_size(mut self, bulk_size: usize) -> Self {
+        self.bulk = self.bulk.with_bulk_size(bulk_size);
+        self
+    }
+
+    /// Returns the transport. Buffered actions are dropped; call
+    /// [`flush`](EntityProjection::flush) first.
+    pub fn into_transport(self) -> T {
+        self.bulk.into_transport()
+    }
+}
+
+impl<T: HttpTransport> EntityProjection for SearchAheadIndex<'_, T> {
+    fn upsert(&mut self, entity: &ExportedEntity) -> SzResult<()> {
+        let entity_id = entity.resolved_entity.entity_id;
+        let json = match self
+            .engine
+            .get_entity(EntityRef::Id(entity_id), Some(SEARCH_AHEAD_FLAGS))
+        {
+            Ok(json) => json,
+            Err(SzError::NotFound(_)) => return self.remove(entity_id),
+            Err(e) => return Err(e),
+        };
+        let summary = EntitySummary::from_entity_json(&json)?;
+        self.bulk
+            .push("index", entity_id, Some(serde_json::to_string(&summary)?))
+    }
+
+    fn remove(&mut self, entity_id: EntityId) -> SzResult<()> {
+        self.bulk.push("delete", entity_id, None)
+    }
+
+    fn flush(&mut self) -> SzResult<()> {
+        self.bulk.flush()
+    }
+}
+
+/// An index candidate the engine confirmed, from
+/// [`search_fuzzy_then_resolve`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConfirmedMatch {
+    /// The entity
+    pub entity_id: EntityId,
+    /// Relevance score the index gave the candidate
+    pub index_score: Option<f64>,
+    /// The engine's explanation of the match
+    pub explanation: SearchExplanation,
+}
+
+/// Finds entities matching `attributes` by asking the search-ahead `index`
+/// for up to `max_candidates` candidates and confirming each with
+/// `SzEngine::explain_search`.
+///
+/// `NAME_*` attributes become a fuzzy query on the names; other attribute
+/// values are looked up as exact identifiers. A candidate is confirmed when
+/// a resolution rule applies to it. Confirmed matches are returned in index
+/// order; candidates the engine no longer has are skipped.
+///
+/// # Errors
+///
+/// * `SzError::BadInput` - `attributes` is not a JSON object with string
+///   values, or `index` is not a valid index name
+/// * Any error from the transport or the engine
+pub fn search_fuzzy_then_resolve<T: HttpTransport>(
+    engine: &dyn SzEngine,
+    transport: &mut T,
+    index: &str,
+    attributes: &str,
+    max_candidates: usize,
+) -> SzResult<Vec<ConfirmedMatch>> {
+    if !valid_index_name(index) {
+        return Err(SzError::bad_input(format!(
+            "Invalid Elasticsearch index name: {index}"
+        )));
+    }
+    let query = candidate_query(attributes, max_candidates)?;
+    let response = transport.post(
+        &format!("/{index}/_search"),
+        "application/json",
+        &query.to_string(),
+    )?;
+    let mut confirmed = Vec::new();
+    for (entity_id, index_score) in candidates(&response)? {
+        let explanation = match engine.explain_search(attributes, entity_id) {
+            Ok(explanation) => explanation,
+            Err(SzError::NotFound(_)) => continue,
+            Err(e) => return Err(e),
+        };
+        if explanation.why_errule_code.is_some() {
+            confirmed.push(ConfirmedMatch {
+                entity_id,
+                index_score,
+                explanation,
+            });
+        }
+    }
+    Ok(confirmed)
+}
+
+/// Builds the `_search` body for `attributes`.
+fn candidate_query(attributes: &str, max_candidates: usize) -> SzResult<Value> {
+    let attributes: Value = serde_json::from_str(attributes)?;
+    let Some(attributes) = attributes.as_object() else {
+        return Err(SzError::bad_input(
+            "Search attributes must be a JSON object",
+        ));
+    };
+    let mut names = Vec::new();
+    let mut identifiers = Vec::new();
+    for (attribute, value) in attributes {
+        let Some(value) = value.as_str().filter(|value| !value.trim().is_empty()) else {
+            continue;
+        };
+        if attribute.starts_with("NAME_") {
+            names.push(value);
+        } else {
+            identifiers.push(value);
+        }
+    }
+    let mut should = Vec::new();
+    if !names.is_empty() {
+        should.push(json!({
+            "match": {"names": {"query": names.join(" "), "fuzziness": "AUTO"}}
+        }));
+    }
+    if !identifiers.is_empty() {
+        should.push(json!({"terms": {"identifiers": identifiers}}));
+    }
+    if should.is_empty() {
+        return Err(SzError::bad_input(
+            "Search attributes have no values to look up",
+        ));
+    }
+    Ok(json!({
+        "size": max_candidates,
+        "_source": false,
+        "query": {"bool": {"should": should, "minimum_should_match": 1}}
+    }))
+}
+
+/// Entity IDs and scores of the hits of a `_search` response, in order.
+fn candidates(response: &str) -> SzResult<Vec<(EntityId, Option<f64>)>> {
+    let response: Value = serde_json::from_str(response)?;
+    Ok(response["hits"]["hits"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|hit| {
+            let entity_id = hit["_id"].as_str()?.parse().ok()?;
+            Some((EntityId(entity_id), hit["_score"].as_f64()))
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entity_summary_from_entity_json() {
+        let json = r#"{"RESOLVED_ENTITY":{"ENTITY_ID":42,"ENTITY_NAME":"Robert Smith",
+            "FEATURES":{
+                "NAME":[{"FEAT_DESC":"Robert Smith"},{"FEAT_DESC":"Bob Smith"}],
+                "PHONE":[{"FEAT_DESC":"555-1212"}],
+                "SSN":[{"FEAT_DESC":"123-45-6789"}],
+                "ADDRESS":[{"FEAT_DESC":"1 Main St"}]}}}"#;
+        let summary = EntitySummary::from_entity_json(json).unwrap();
+        assert_eq!(summary.entity_id, EntityId(42));
+        assert_eq!(summary.entity_name.as_deref(), Some("Robert Smith"));
+        assert_eq!(summary.names, ["Bob Smith", "Robert Smith"]);
+        assert_eq!(summary.identifiers, ["123-45-6789", "555-1212"]);
+        assert!(EntitySummary::from_entity_json(r#"{"RESOLVED_ENTITY":{}}"#).is_err());
+    }
+
+    #[test]
+    fn test_candidate_query_and_hits() {
+        let query = candidate_query(
+            r#"{"NAME_FIRST": "Jon", "NAME_LAST": "Smyth", "PHONE_NUMBER": "555-1212", "DATE_OF_BIRTH": ""}"#,
+            5,
+        )
+        .unwrap();
+        assert_eq!(query["size"], 5);
+        let should = query["query"]["bool"]["should"].as_array().unwrap();
+        assert_eq!(should[0]["match"]["names"]["query"], "Jon Smyth");
+        assert_eq!(should[1], json!({"terms": {"identifiers": ["555-1212"]}}));
+        assert!(candidate_query(r#"{"NAME_FULL": " "}"#, 5).is_err());
+        assert!(candidate_query("[]", 5).is_err());
+
+        let response = r#"{"hits":{"hits":[
+            {"_id":"7","_score":3.5},{"_id":"not-an-id","_score":1.0},{"_id":"9"}]}}"#;
+        assert_eq!(
+            candidates(response).unwrap(),
+            [(EntityId(7), Some(3.5)), (EntityId(9), None)]
+        );
+    }
+}
@@ -0,0 +1,659 @@
+//! Deterministic synthetic records.
+//!
+//! [`DatasetSpec`] describes how many person and organization records to
+//! generate and how much they should overlap. The same spec and seed always
+//! produce the same records, so tests can assert on the resolved entity
+//! count and benchmarks compare like with like.
+//!
+//! Three rates control the overlap:
+//!
+//! - the duplicate rate is the chance that a record describes an entity
+//!   generated earlier, with its name, address and phone written differently;
+//! - the shared address and shared phone rates are the chance that a new
+//!   entity reuses another entity's address or phone, as households and
+//!   offices do, without being the same entity.
+//!
+//! Each [`SyntheticRecord`] carries the key of the entity it describes, so
+//! the expected resolution is known.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::prelude::*;
+//! use sz_rust_sdk::testing::datasets::DatasetSpec;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_datasets")?;
+//! let engine = env.get_engine()?;
+//! let dataset = DatasetSpec::new(42)
+//!     .with_people(500)
+//!     .with_organizations(100)
+//!     .with_duplicate_rate(0.2)
+//!     .generate()?;
+//! for record in dataset.records() {
+//!     engine.add_record(&record.data_source, &record.record_id, &record.definition, None)?;
+//! }
+//! println!("{} records for {} entities", dataset.len(), dataset.entity_count());
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::error::{SzError, SzResult};
+use serde_json::json;
+
+const FIRST_NAMES: &[(&str, &str)] = &[
+    ("Robert", "Bob"),
+    ("William", "Bill"),
+    ("Elizabeth", "Liz"),
+    ("Margaret", "Peggy"),
+    ("James", "Jim"),
+    ("Katherine", "Kate"),
+    ("Richard", "Rick"),
+    ("Jennifer", "Jen"),
+    ("Michael", "Mike"),
+    ("Patricia", "Pat"),
+    ("Thomas", "Tom"),
+    ("Susan", "Sue"),
+    ("Daniel", "Dan"),
+    ("Rebecca", "Becky"),
+    ("Joseph", "Joe"),
+    ("Victoria", "Vicky"),
+    ("Anthony", "Tony"),
+    ("Christine", "Chris"),
+    ("Edward", "Ed"),
+    ("Abigail", "Abby"),
+];
+
+const LAST_NAMES: &[&str] = &[
+    "Smith",
+    "Johnson",
+    "Garcia",
+    "Nguyen",
+    "Okafor",
+    "Kowalski",
+    "Haddad",
+    "Larsen",
+    "Moreau",
+    "Tanaka",
+    "Petrov",
+    "Silva",
+    "O'Brien",
+    "Schmidt",
+    "Patel",
+    "Rossi",
+    "Andersson",
+    "Kim",
+    "Mendoza",
+    "Fischer",
+    "Dubois",
+    "Walker",
+    "Novak",
+    "Costa",
+    "Ibrahim",
+];
+
+const ORG_STEMS: &[&str] = &[
+    "Acme",
+    "Summit",
+    "Bluewater",
+    "Northwind",
+    "Ironbridge",
+    "Cedar",
+    "Pinnacle",
+    "Redwood",
+    "Harbor",
+    "Silverline",
+    "Keystone",
+    "Evergreen",
+    "Granite",
+    "Lighthouse",
+    "Meridian",
+];
+
+const ORG_KINDS: &[&str] = &[
+    "Logistics",
+    "Holdings",
+    "Consulting",
+    "Manufacturing",
+    "Foods",
+    "Analytics",
+    "Builders",
+    "Medical",
+];
+
+const ORG_SUFFIXES: &[(&str, &str)] = &[
+    ("Incorporated", "Inc"),
+    ("Corporation", "Corp"),
+    ("Limited", "Ltd"),
+    ("Company", "Co"),
+];
+
+const STREET_NAMES: &[&str] = &[
+    "Main",
+    "Oak",
+    "Maple",
+    "Cedar",
+    "Elm",
+    "Washington",
+    "Lake",
+    "Hill",
+    "Park",
+    "Pine",
+    "Walnut",
+    "River",
+    "Church",
+    "Mill",
+    "Spring",
+];
+
+const STREET_TYPES: &[(&str, &str)] = &[
+    ("Street", "St"),
+    ("Avenue", "Ave"),
+    ("Road", "Rd"),
+    ("Boulevard", "Blvd"),
+    ("Drive", "Dr"),
+    ("Lane", "Ln"),
+];
+
+const CITIES: &[(&str, &str)] = &[
+    ("Springfield", "IL"),
+    ("Riverton", "WY"),
+    ("Fairview", "TX"),
+    ("Madison", "WI"),
+    ("Georgetown", "KY"),
+    ("Salem", "OR"),
+    ("Franklin", "TN"),
+    ("Clinton", "IA"),
+];
+
+/// Kind of entity a synthetic record describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RecordKind {
+    /// A person, with `NAME_FULL`, `DATE_OF_BIRTH` and `EMAIL_ADDRESS`
+    Person,
+    /// An organization, with `NAME_ORG`
+    Organization,
+}
+
+/// One generated record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntheticRecord {
+    /// Data source code
+    pub data_source: String,
+    /// Record ID, `P000001` for people and `O000001` for organizations
+    pub record_id: String,
+    /// Kind of entity described
+    pub kind: RecordKind,
+    /// Ground-truth key of the entity described; records sharing a key are
+    /// duplicates and should resolve together
+    pub entity_key: usize,
+    /// Record JSON, including `DATA_SOURCE` and `RECORD_ID`
+    pub definition: String,
+}
+
+/// Generated records with their ground truth.
+#[derive(Debug, Clone, Default)]
+pub struct Dataset {
+    records: Vec<SyntheticRecord>,
+    entity_count: usize,
+}
+
+impl Dataset {
+    /// The records, in generation order.
+    pub fn records(&self) -> &[SyntheticRecord] {
+        &self.records
+    }
+
+    /// Number of records.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    /// Whether no records were generated.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Number of distinct entities the records describe.
+    pub fn entity_count(&self) -> usize {
+        self.entity_count
+    }
+
+    /// Number of records that duplicate an earlier entity.
+    pub fn duplicate_count(&self) -> usize {
+        self.records.len() - self.entity_count
+    }
+
+    /// The records as line-delimited JSON, ready for
+    /// [`ingest::load_from_reader`](crate::ingest::load_from_reader).
+    pub fn to_jsonl(&self) -> String {
+        let mut out = String::new();
+        for record in &self.records {
+            out.push_str(&record.definition);
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Describes a synthetic dataset.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DatasetSpec {
+    seed: u64,
+    data_source: String,
+    people: usize,
+    organizations: usize,
+    duplicate_rate: f64,
+    shared_address_rate: f64,
+    shared_phone_rate: f64,
+}
+
+impl DatasetSpec {
+    /// Creates a spec for 100 people and no organizations in the `TEST` data
+    /// source, with 10% duplicates and 5% shared addresses and phones.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            data_source: "TEST".to_string(),
+            people: 100,
+            organizations: 0,
+            duplicate_rate: 0.1,
+            shared_address_rate: 0.05,
+            shared_phone_rate: 0.05,
+        }
+    }
+
+    /// Sets the data source code.
+    pub fn with_data_source(mut self, data_source: impl Into<String>) -> Self {
+        self.data_source = data_source.into();
+        self
+    }
+
+    /// Sets the number of person records, duplicates included.
+    pub fn with_people(mut self, count: usize) -> Self {
+        self.people = count;
+        self
+    }
+
+    /// Sets the number of organization records, duplicates included.
+    pub fn with_organizations(mut self, count: usize) -> Self {
+        self.organizations = count;
+        self
+    }
+
+    /// Sets the chance that a record duplicates an earlier entity.
+    pub fn with_duplicate_rate(mut self, rate: f64) -> Self {
+        self.duplicate_rate = rate;
+        self
+    }
+
+    /// Sets the chance that a new entity reuses another entity's address.
+    pub fn with_shared_address_rate(mut self, rate: f64) -> Self {
+        self.shared_address_rate = rate;
+        self
+    }
+
+    /// Sets the chance that a new entity reuses another entity's phone.
+    pub fn with_shared_phone_rate(mut self, rate: f64) -> Self {
+        self.shared_phone_rate = rate;
+        self
+    }
+
+    /// Generates the records: people first, then organizations.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if a rate is outside `0.0..=1.0` or the data
+    /// source code is empty.
+    pub fn generate(&self) -> SzResult<Dataset> {
+        for (name, rate) in [
+            ("duplicate rate", self.duplicate_rate),
+            ("shared address rate", self.shared_address_rate),
+            ("shared phone rate", self.shared_phone_rate),
+        ] {
+            if !(0.0..=1.0).contains(&rate) {
+                return Err(SzError::bad_input(format!(
+                    "{name} {rate} is not between 0 and 1"
+                )));
+            }
+        }
+        if self.data_source.is_empty() {
+            return Err(SzError::bad_input("data source code is empty"));
+        }
+
+        let mut generator = Generator {
+            spec: self,
+            rng: Rng::new(self.seed),
+            dataset: Dataset::default(),
+            addresses: Vec::new(),
+            phones: Vec::new(),
+        };
+        generator.generate(RecordKind::Person, self.people);
+        generator.generate(RecordKind::Organization, self.organizations);
+        Ok(generator.dataset)
+    }
+}
+
+/// An entity's canonical values, varied when writing duplicates.
+struct Identity {
+    name: Name,
+    address: Address,
+    phone: u32,
+    extra: String,
+}
+
+enum Name {
+    Person {
+        first: usize,
+        middle: char,
+        last: usize,
+    },
+    Organization {
+        stem: usize,
+        kind: usize,
+        suffix: usize,
+    },
+}
+
+#[derive(Clone, Copy)]
+struct Address {
+    number: u32,
+    street: usize,
+    street_type: usize,
+    city: usize,
+    postal: u32,
+}
+
+struct Generator<'a> {
+    spec: &'a DatasetSpec,
+    rng: Rng,
+    dataset: Dataset,
+    addresses: Vec<Address>,
+    phones: Vec<u32>,
+}
+
+impl Generator<'_> {
+    fn generate(&mut self, kind: RecordKind, count: usize) {
+        let prefix = match kind {
+            RecordKind::Person => 'P',
+            RecordKind::Organization => 'O',
+        };
+        let mut identities: Vec<(usize, Identity)> = Vec::new();
+        for n in 1..=count {
+            let (index, variant) =
+                if !identities.is_empty() && self.rng.chance(self.spec.duplicate_rate) {
+                    (self.rng.below(identities.len()), true)
+                } else {
+                    let identity = self.identity(kind);
+                    identities.push((self.dataset.entity_count, identity));
+                    self.dataset.entity_count += 1;
+                    (identities.len() - 1, false)
+                };
+            let record_id = format!("{prefix}{n:06}");
+            let (entity_key, identity) = &identities[index];
+            let definition = definition(
+                &self.spec.data_source,
+                kind,
+                &record_id,
+                identity,
+                variant.then_some(&mut self.rng),
+            );
+            self.dataset.records.push(SyntheticRecord {
+                data_source: self.spec.data_source.clone(),
+                record_id,
+                kind,
+                entity_key: *entity_key,
+                definition,
+            });
+        }
+    }
+
+    fn identity(&mut self, kind: RecordKind) -> Identity {
+        let rng = &mut self.rng;
+        let name = match kind {
+            RecordKind::Person => Name::Person {
+                first: rng.below(FIRST_NAMES.len()),
+                middle: char::from(b'A' + rng.below(26) as u8),
+                last: rng.below(LAST_NAMES.len()),
+            },
+            RecordKind::Organization => Name::Organization {
+                stem: rng.below(ORG_STEMS.len()),
+                kind: rng.below(ORG_KINDS.len()),
+                suffix: rng.below(ORG_SUFFIXES.len()),
+            },
+        };
+        let address = if !self.addresses.is_empty() && rng.chance(self.spec.shared_address_rate) {
+            self.addresses[rng.below(self.addresses.len())]
+        } else {
+            Address {
+                number: 1 + rng.below(9999) as u32,
+                street: rng.below(STREET_NAMES.len()),
+                street_type: rng.below(STREET_TYPES.len()),
+                city: rng.below(CITIES.len()),
+                postal: 10000 + rng.below(89999) as u32,
+            }
+        };
+        let phone = if !self.phones.is_empty() && rng.chance(self.spec.shared_phone_rate) {
+            self.phones[rng.below(self.phones.len())]
+        } else {
+            2_000_000 + rng.below(7_999_999) as u32
+        };
+        self.addresses.push(address);
+        self.phones.push(phone);
+        // Unique per entity, so entities sharing a name stay distinct
+        let serial = self.dataset.entity_count;
+        let extra = match kind {
+            RecordKind::Person => format!(
+                "{}-{:02}-{:02}",
+                1940 + serial % 60,
+                1 + rng.below(12),
+                1 + rng.below(28)
+            ),
+            RecordKind::Organization => format!("{:02}-{:07}", 10 + serial % 90, serial),
+        };
+        Identity {
+            name,
+            address,
+            phone,
+            extra,
+        }
+    }
+}
+
+/// Writes an entity as a record. Duplicates pass `variant`, which randomly
+/// switches the name, address and phone to their alternate spellings.
+fn definition(
+    data_source: &str,
+    kind: RecordKind,
+    record_id: &str,
+    identity: &Identity,
+    mut variant: Option<&mut Rng>,
+) -> String {
+    let mut vary = || variant.as_mut().is_some_and(|rng| rng.chance(0.5));
+    let name = match identity.name {
+        Name::Person {
+            first,
+            middle,
+            last,
+        } => {
+            let (formal, nickname) = FIRST_NAMES[first];
+            if vary() {
+                format!("{nickname} {}", LAST_NAMES[last])
+            } else {
+                format!("{formal} {middle} {}", LAST_NAMES[last])
+            }
+        }
+        Name::Organization { stem, kind, suffix } => {
+            let (long, short) = ORG_SUFFIXES[suffix];
+            let suffix = if vary() { short } else { long };
+            format!("{} {} {suffix}", ORG_STEMS[stem], ORG_KINDS[kind])
+        }
+    };
+    let address = &identity.address;
+    let (long, short) = STREET_TYPES[address.street_type];
+    let street_type = if vary() { short } else { long };
+    let (city, state) = CITIES[address.city];
+    let address = format!(
+        "{} {} {street_type}, {city}, {state} {}",
+        address.number, STREET_NAMES[address.street], address.postal
+    );
+    let phone = if vary() {
+        format!(
+            "(555) {}-{:04}",
+            identity.phone / 10_000,
+            identity.phone % 10_000
+        )
+    } else {
+        format!(
+            "555-{}-{:04}",
+            identity.phone / 10_000,
+            identity.phone % 10_000
+        )
+    };
+
+    let mut record = json!({
+        "DATA_SOURCE": data_source,
+        "RECORD_ID": record_id,
+        "ADDR_FULL": address,
+        "PHONE_NUMBER": phone,
+    });
+    match kind {
+        RecordKind::Person => {
+            record["RECORD_TYPE"] = "PERSON".into();
+            record["NAME_FULL"] = name.clone().into();
+            record["DATE_OF_BIRTH"] = identity.extra.clone().into();
+            let local: String = name
+                .split_whitespace()
+                .map(|part| part.to_lowercase())
+                .collect::<Vec<_>>()
+                .join(".")
+                .replace('\'', "");
+            record["EMAIL_ADDRESS"] = format!("{local}@example.com").into();
+        }
+        RecordKind::Organization => {
+            record["RECORD_TYPE"] = "ORGANIZATION".into();
+            record["NAME_ORG"] = name.into();
+            record["TAX_ID_NUMBER"] = identity.extra.clone().into();
+        }
+    }
+    record.to_string()
+}
+
+/// SplitMix64, so datasets don't depend on a random number crate's stream
+/// staying stable across versions.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    fn below(&mut self, bound: usize) -> usize {
+        (self.next_u64() % bound as u64) as usize
+    }
+
+    fn chance(&mut self, probability: f64) -> bool {
+        // 53 random bits give a uniform value in [0, 1)
+        ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < probability
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_is_deterministic() {
+        let spec = DatasetSpec::new(7).with_people(50).with_organizations(20);
+        let first = spec.generate().unwrap();
+        let second = spec.generate().unwrap();
+        assert_eq!(first.records(), second.records());
+        assert_ne!(
+            first.records(),
+            DatasetSpec::new(8)
+                .with_people(50)
+                .with_organizations(20)
+                .generate()
+                .unwrap()
+                .records()
+        );
+    }
+
+    #[test]
+    fn test_counts_and_ground_truth() {
+        let dataset = DatasetSpec::new(1)
+            .with_people(1000)
+            .with_organizations(200)
+            .with_duplicate_rate(0.25)
+            .generate()
+            .unwrap();
+        assert_eq!(dataset.len(), 1200);
+        let people = dataset
+            .records()
+            .iter()
+            .filter(|r| r.kind == RecordKind::Person)
+            .count();
+        assert_eq!(people, 1000);
+
+        let duplicates = dataset.duplicate_count() as f64 / dataset.len() as f64;
+        assert!((0.2..0.3).contains(&duplicates), "{duplicates}");
+
+        let mut keys: Vec<_> = dataset.records().iter().map(|r| r.entity_key).collect();
+        keys.sort_unstable();
+        keys.dedup();
+        assert_eq!(keys.len(), dataset.entity_count());
+
+        let ids: std::collections::HashSet<_> =
+            dataset.records().iter().map(|r| &r.record_id).collect();
+        assert_eq!(ids.len(), dataset.len());
+    }
+
+    #[test]
+    fn test_no_overlap() {
+        let dataset = DatasetSpec::new(3)
+            .with_people(200)
+            .with_duplicate_rate(0.0)
+            .with_shared_address_rate(0.0)
+            .with_shared_phone_rate(0.0)
+            .generate()
+            .unwrap();
+        assert_eq!(dataset.entity_count(), 200);
+        assert_eq!(dataset.duplicate_count(), 0);
+    }
+
+    #[test]
+    fn test_records_are_loadable_json() {
+        let dataset = DatasetSpec::new(5)
+            .with_data_source("CUSTOMERS")
+            .with_people(5)
+            .with_organizations(5)
+            .generate()
+            .unwrap();
+        for record in dataset.records() {
+            let json: serde_json::Value = serde_json::from_str(&record.definition).unwrap();
+            assert_eq!(json["DATA_SOURCE"], "CUSTOMERS");
+            assert_eq!(json["RECORD_ID"], record.record_id.as_str());
+            let name = match record.kind {
+                RecordKind::Person => "NAME_FULL",
+                RecordKind::Organization => "NAME_ORG",
+            };
+            assert!(json[name].is_string());
+        }
+        assert_eq!(dataset.to_jsonl().lines().count(), 10);
+    }
+
+    #[test]
+    fn test_invalid_rate() {
+        assert!(matches!(
+            DatasetSpec::new(0).with_duplicate_rate(1.5).generate(),
+            Err(SzError::BadInput(_))
+        ));
+    }
+}
@@ -0,0 +1,13 @@
+//! Support for tests, examples and benchmarks.
+//!
+//! - [`datasets`] generates synthetic records with known ground truth.
+//! - [`replay`] records searches and replays them against a new
+//!   configuration or engine version, reporting changed match results.
+//! - [`snapshot`] compares normalized engine responses against golden files.
+
+pub mod datasets;
+pub mod replay;
+pub mod snapshot;
+
+pub use crate::{assert_entity_snapshot, assert_json_snapshot};
+pub use replay::{ReplayReport, SearchReplay};
@@ -0,0 +1,455 @@
+//! Recording and replaying searches to validate configuration upgrades.
+//!
+//! A [`SearchReplay`] runs `search_by_attributes` and keeps each request
+//! with its response. Saved to a file, the searches can be replayed later
+//! against an engine with a new configuration or a new Senzing version, and
+//! [`SearchReplay::replay`] reports which match results changed.
+//!
+//! Entity IDs are not stable across loads, so matched entities are compared
+//! by their record keys; searches are run with [`REPLAY_FLAGS`] so the
+//! responses list them. An entity that gained or lost records therefore
+//! shows as one entity removed and another added. Entities that keep their
+//! records are reported as changed when their match level code or match
+//! key differs.
+//!
+//! The file holds one JSON object per line with `attributes`,
+//! `search_profile`, `flags` and `response`.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::prelude::*;
+//! use sz_rust_sdk::testing::SearchReplay;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_search_replay")?;
+//! let engine = env.get_engine()?;
+//! let mut replay = SearchReplay::new();
+//! replay.record(&*engine, r#"{"NAME_FULL": "Ann Lee", "DATE_OF_BIRTH": "1980-01-01"}"#, None)?;
+//! replay.save("searches.jsonl")?;
+//!
+//! // ... after installing the new configuration ...
+//! let report = SearchReplay::load("searches.jsonl")?.replay(&*engine)?;
+//! if !report.is_unchanged() {
+//!     println!("{report}");
+//! }
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::error::{SzError, SzResult};
+use crate::flags::SzFlags;
+use crate::models::SearchResponse;
+use crate::redact;
+use crate::traits::SzEngine;
+use crate::types::{EntityId, JsonString, RecordKey};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+/// Flags [`SearchReplay::record`] searches with: the default search flags
+/// plus the records of each matched entity.
+pub const REPLAY_FLAGS: SzFlags = SzFlags::from_bits_truncate(
+    SzFlags::SEARCH_BY_ATTRIBUTES_DEFAULT_FLAGS.bits() | SzFlags::ENTITY_INCLUDE_RECORD_DATA.bits(),
+);
+
+/// One recorded search.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SearchCase {
+    /// Search attributes as passed to `search_by_attributes`
+    pub attributes: String,
+    /// Search profile, if one was given
+    #[serde(default)]
+    pub search_profile: Option<String>,
+    /// Bits of the flags the search ran with
+    pub flags: u64,
+    /// The recorded response
+    pub response: Value,
+}
+
+/// An entity matched by a search, as compared by [`SearchReplay::replay`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchMatch {
+    /// Entity ID at the time of the search
+    pub entity_id: EntityId,
+    /// Records of the entity; empty if the response did not list them
+    pub records: BTreeSet<RecordKey>,
+    /// Match level code, e.g. "RESOLVED" or "POSSIBLY_SAME"
+    pub match_level_code: Option<String>,
+    /// Match key describing which features matched
+    pub match_key: Option<String>,
+}
+
+/// How the results of one search changed on replay.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchDiff {
+    /// Index of the search in the replay, from 0
+    pub case: usize,
+    /// Search attributes
+    pub attributes: String,
+    /// Entities only the recorded response matched
+    pub removed: Vec<SearchMatch>,
+    /// Entities only the replayed response matched
+    pub added: Vec<SearchMatch>,
+    /// Entities both matched with a different match level code or match
+    /// key, as recorded and as replayed
+    pub changed: Vec<(SearchMatch, SearchMatch)>,
+}
+
+impl SearchDiff {
+    /// Returns whether the search matched the same entities the same way.
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.added.is_empty() && self.changed.is_empty()
+    }
+}
+
+/// Outcome of [`SearchReplay::replay`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplayReport {
+    /// Searches replayed
+    pub searches: usize,
+    /// Searches whose results changed, in replay order
+    pub diffs: Vec<SearchDiff>,
+}
+
+impl ReplayReport {
+    /// Returns whether every search matched as recorded.
+    pub fn is_unchanged(&self) -> bool {
+        self.diffs.is_empty()
+    }
+}
+
+/// Recorded searches; see the [module documentation](self).
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SearchReplay {
+    cases: Vec<SearchCase>,
+}
+
+impl SearchReplay {
+    /// Creates a replay with no searches.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs a search with [`REPLAY_FLAGS`], records it, and returns the
+    /// response.
+    ///
+    /// # Errors
+    ///
+    /// * Any error from `SzEngine::search_by_attributes`
+    /// * `SzError::Json` - The response is not JSON
+    pub fn record(
+        &mut self,
+        engine: &dyn SzEngine,
+        attributes: &str,
+        search_profile: Option<&str>,
+    ) -> SzResult<JsonString> {
+        let response =
+            engine.search_by_attributes(attributes, search_profile, Some(REPLAY_FLAGS))?;
+        self.cases.push(SearchCase {
+            attributes: attributes.to_string(),
+            search_profile: search_profile.map(str::to_string),
+            flags: REPLAY_FLAGS.bits(),
+            response: serde_json::from_str(&response)?,
+        });
+        Ok(response)
+    }
+
+    /// Adds a search recorded elsewhere.
+    pub fn push(&mut self, case: SearchCase) {
+        self.cases.push(case);
+    }
+
+    /// The recorded searches, in order.
+    pub fn cases(&self) -> &[SearchCase] {
+        &self.cases
+    }
+
+    /// Number of recorded searches.
+    pub fn len(&self) -> usize {
+        self.cases.len()
+    }
+
+    /// Returns whether no search was recorded.
+    pub fn is_empty(&self) -> bool {
+        self.cases.is_empty()
+    }
+
+    /// Reads searches written by [`save`](Self::save).
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the file cannot be read or a line is not a
+    /// recorded search.
+    pub fn load(path: impl AsRef<Path>) -> SzResult<Self> {
+        let path = path.as_ref();
+        let file = std::fs::File::open(path)
+            .map_err(|e| SzError::bad_input(format!("Failed reading {}: {e}", path.display())))?;
+        let mut cases = Vec::new();
+        for (index, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|e| {
+                SzError::bad_input(format!("Failed reading {}: {e}", path.display()))
+            })?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let case = serde_json::from_str(&line).map_err(|e| {
+                SzError::bad_input(format!("{} line {}: {e}", path.display(), index + 1))
+            })?;
+            cases.push(case);
+        }
+        Ok(Self { cases })
+    }
+
+    /// Writes the searches to `path`, one per line, replacing the file.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the file cannot be written.
+    pub fn save(&self, path: impl AsRef<Path>) -> SzResult<()> {
+        let path = path.as_ref();
+        let failed = |e: &dyn fmt::Display| {
+            SzError::bad_input(format!("Failed writing {}: {e}", path.display()))
+        };
+        let file = std::fs::File::create(path).map_err(|e| failed(&e))?;
+        let mut writer = BufWriter::new(file);
+        for case in &self.cases {
+            serde_json::to_writer(&mut writer, case).map_err(|e| failed(&e))?;
+            writer.write_all(b"\n").map_err(|e| failed(&e))?;
+        }
+        writer.flush().map_err(|e| failed(&e))
+    }
+
+    /// Runs every search again with its recorded flags and compares the
+    /// results with the recorded ones.
+    ///
+    /// # Errors
+    ///
+    /// * Any error from `SzEngine::search_by_attributes`
+    /// * `SzError::Json` - A response is not a search response
+    pub fn replay(&self, engine: &dyn SzEngine) -> SzResult<ReplayReport> {
+        let mut report = ReplayReport::default();
+        for (case, recorded) in self.cases.iter().enumerate() {
+            let response = engine.search_by_attributes(
+                &recorded.attributes,
+                recorded.search_profile.as_deref(),
+                Some(SzFlags::from_bits_truncate(recorded.flags)),
+            )?;
+            let before = search_matches(&recorded.response.to_string())?;
+            let after = search_matches(&response)?;
+            let diff = diff_matches(case, &recorded.attributes, before, after);
+            if !diff.is_empty() {
+                report.diffs.push(diff);
+            }
+            report.searches += 1;
+        }
+        Ok(report)
+    }
+}
+
+/// Reads the matched entities of a `search_by_attributes` response.
+///
+/// # Errors
+///
+/// `SzError::Json` if `response` is not a search response.
+pub fn search_matches(response: &str) -> SzResult<Vec<SearchMatch>> {
+    let response: SearchResponse = serde_json::from_str(response)?;
+    Ok(response
+        .resolved_entities
+        .into_iter()
+        .map(|result| {
+            let entity = result.entity.resolved_entity;
+            SearchMatch {
+                entity_id: entity.entity_id,
+                records: entity
+                    .records
+                    .iter()
+                    .filter_map(|record| {
+                        RecordKey::new(&record.data_source, record.record_id.as_str()).ok()
+                    })
+                    .collect(),
+                match_level_code: result.match_info.match_level_code,
+                match_key: result.match_info.match_key,
+            }
+        })
+        .collect())
+}
+
+/// What identifies a matched entity across loads: its records, or its
+/// entity ID when the response did not list them.
+fn identity(found: &SearchMatch) -> String {
+    if found.records.is_empty() {
+        return format!("entity {}", found.entity_id);
+    }
+    let records: Vec<String> = found.records.iter().map(RecordKey::to_string).collect();
+    records.join(", ")
+}
+
+fn diff_matches(
+    case: usize,
+    attributes: &str,
+    before: Vec<SearchMatch>,
+    after: Vec<SearchMatch>,
+) -> SearchDiff {
+    let mut after: BTreeMap<String, SearchMatch> = after
+        .into_iter()
+        .map(|found| (identity(&found), found))
+        .collect();
+    let mut diff = SearchDiff {
+        case,
+        attributes: attributes.to_string(),
+        removed: Vec::new(),
+        added: Vec::new(),
+        changed: Vec::new(),
+    };
+    for recorded in before {
+        match after.remove(&identity(&recorded)) {
+            None => diff.removed.push(recorded),
+            Some(replayed)
+                if replayed.match_level_code != recorded.match_level_code
+                    || replayed.match_key != recorded.match_key =>
+            {
+                diff.changed.push((recorded, replayed));
+            }
+            Some(_) => {}
+        }
+    }
+    diff.added = after.into_values().collect();
+    diff
+}
+
+impl fmt::Display for SearchMatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {}",
+            self.match_level_code.as_deref().unwrap_or("?"),
+            self.match_key.as_deref().unwrap_or("")
+        )
+    }
+}
+
+impl fmt::Display for ReplayReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} searches replayed, {} changed",
+            self.searches,
+            self.diffs.len()
+        )?;
+        for diff in &self.diffs {
+            writeln!(
+                f,
+                "search {} {}:",
+                diff.case,
+                redact::message(&diff.attributes)
+            )?;
+            for found in &diff.removed {
+                writeln!(
+                    f,
+                    "  - {}: {}",
+                    identity(found),
+                    found.to_string().trim_end()
+                )?;
+            }
+            for found in &diff.added {
+                writeln!(
+                    f,
+                    "  + {}: {}",
+                    identity(found),
+                    found.to_string().trim_end()
+                )?;
+            }
+            for (recorded, replayed) in &diff.changed {
+                writeln!(
+                    f,
+                    "  ~ {}: {} -> {}",
+                    identity(recorded),
+                    recorded.to_string().trim_end(),
+                    replayed.to_string().trim_end()
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(results: &[(i64, &str, &str, &str)]) -> String {
+        let entities: Vec<Value> = results
+            .iter()
+            .map(|(entity_id, record_id, level, key)| {
+                serde_json::json!({
+                    "MATCH_INFO": {"MATCH_LEVEL_CODE": level, "MATCH_KEY": key},
+                    "ENTITY": {"RESOLVED_ENTITY": {
+                        "ENTITY_ID": entity_id,
+                        "RECORDS": [{"DATA_SOURCE": "TEST", "RECORD_ID": record_id}],
+                    }},
+                })
+            })
+            .collect();
+        serde_json::json!({ "RESOLVED_ENTITIES": entities }).to_string()
+    }
+
+    #[test]
+    fn test_diff_matches_by_record_keys() {
+        let before = search_matches(&response(&[
+            (1, "1001", "RESOLVED", "+NAME+DOB"),
+            (2, "1002", "POSSIBLY_SAME", "+NAME"),
+            (3, "1003", "POSSIBLY_RELATED", "+NAME"),
+        ]))
+        .unwrap();
+        let after = search_matches(&response(&[
+            (7, "1001", "RESOLVED", "+NAME+DOB"),
+            (8, "1002", "RESOLVED", "+NAME+PHONE"),
+            (9, "1004", "NAME_ONLY", "+NAME"),
+        ]))
+        .unwrap();
+        assert_eq!(before[0].records.len(), 1);
+
+        let diff = diff_matches(0, r#"{"NAME_FULL":"Ann Lee"}"#, before, after);
+        assert!(!diff.is_empty());
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].entity_id, EntityId(3));
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].entity_id, EntityId(9));
+        assert_eq!(diff.changed.len(), 1);
+        assert_eq!(
+            diff.changed[0].1.match_level_code.as_deref(),
+            Some("RESOLVED")
+        );
+
+        let report = ReplayReport {
+            searches: 2,
+            diffs: vec![diff],
+        };
+        let text = report.to_string();
+        assert!(text.starts_with("2 searches replayed, 1 changed\n"));
+        assert!(text.contains("  - TEST:1003: POSSIBLY_RELATED +NAME\n"));
+        assert!(text.contains("  ~ TEST:1002: POSSIBLY_SAME +NAME -> RESOLVED +NAME+PHONE\n"));
+    }
+
+    #[test]
+    fn test_save_and_load_round_trip() {
+        let path = std::env::temp_dir().join(format!("sz_replay_{}.jsonl", std::process::id()));
+        let mut replay = SearchReplay::new();
+        replay.push(SearchCase {
+            attributes: r#"{"NAME_FULL":"Ann Lee"}"#.to_string(),
+            search_profile: Some("SEARCH".to_string()),
+            flags: REPLAY_FLAGS.bits(),
+            response: serde_json::from_str(&response(&[(1, "1001", "RESOLVED", "+NAME")])).unwrap(),
+        });
+        replay.save(&path).unwrap();
+        assert_eq!(SearchReplay::load(&path).unwrap(), replay);
+
+        std::fs::write(&path, "{\"attributes\":1}\n").unwrap();
+        let err = SearchReplay::load(&path).unwrap_err();
+        assert!(err.message().contains("line 1"));
+        let _ = std::fs::remove_file(&path);
+    }
+}
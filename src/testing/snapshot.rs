@@ -0,0 +1,308 @@
+//! Golden-file snapshots of engine responses.
+//!
+//! Entity IDs, internal IDs and timestamps change from one load to the next
+//! even when resolution does not. [`normalize_response`] replaces them with
+//! placeholders numbered in order of appearance and sorts object keys, so a
+//! response can be compared against a snapshot file checked into the repo.
+//!
+//! [`assert_entity_snapshot!`](crate::assert_entity_snapshot) and
+//! [`assert_json_snapshot!`](crate::assert_json_snapshot) store snapshots in
+//! `tests/snapshots/<test file>__<test function>.snap` under the calling
+//! crate. A missing snapshot is written and the assertion passes; a
+//! mismatch panics with both versions. Set `SZ_UPDATE_SNAPSHOTS=1` to
+//! overwrite mismatched snapshots instead of failing.
+//!
+//! # Example
+//!
+//! ```no_run
+//! # use sz_rust_sdk::helpers::ExampleEnvironment;
+//! use sz_rust_sdk::prelude::*;
+//! use sz_rust_sdk::testing::assert_entity_snapshot;
+//!
+//! # let env = ExampleEnvironment::initialize("doctest_entity_snapshot")?;
+//! let engine = env.get_engine()?;
+//! engine.add_record("TEST", "1", r#"{"NAME_FULL": "Ann Lee"}"#, None)?;
+//! let entity_id = engine.get_entity(EntityRef::from_record("TEST", "1"), None)?;
+//! # let entity_id = EntityId(serde_json::from_str::<serde_json::Value>(&entity_id)?
+//! #     ["RESOLVED_ENTITY"]["ENTITY_ID"].as_i64().unwrap());
+//! assert_entity_snapshot!(engine, entity_id, Some(SzFlags::ENTITY_DEFAULT_FLAGS));
+//! # Ok::<(), SzError>(())
+//! ```
+
+use crate::error::SzResult;
+use crate::types::ResponseFormat;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Environment variable that makes mismatched snapshots be overwritten.
+pub const UPDATE_ENV_VAR: &str = "SZ_UPDATE_SNAPSHOTS";
+
+/// Keys holding entity IDs, replaced with `[entity N]`.
+pub const ENTITY_ID_KEYS: &[&str] = &["ENTITY_ID", "START_ENTITY_ID", "END_ENTITY_ID"];
+
+/// Keys holding other repository-assigned IDs, replaced with `[<key> N]`.
+pub const INTERNAL_ID_KEYS: &[&str] = &["INTERNAL_ID", "LIB_FEAT_ID", "RES_ENT_ID"];
+
+/// Normalizes an engine response for comparison against a snapshot.
+///
+/// Values of [`ENTITY_ID_KEYS`] and [`INTERNAL_ID_KEYS`] become placeholders
+/// numbered in order of first appearance, so two entities keep distinct
+/// placeholders; values of keys ending in `_DT` become `[timestamp]`. The
+/// result is pretty-printed with sorted keys.
+///
+/// # Errors
+///
+/// `SzError::Json` if `json` is not valid JSON.
+pub fn normalize_response(json: &str) -> SzResult<String> {
+    // Parsing the key-sorted form makes the numbering independent of the
+    // native key order
+    let mut document: Value =
+        serde_json::from_str(&ResponseFormat::Compact.apply(json.to_string())?)?;
+    let mut placeholders = Placeholders::default();
+    normalize_value(&mut document, None, &mut placeholders);
+    Ok(serde_json::to_string_pretty(&document)?)
+}
+
+#[derive(Default)]
+struct Placeholders {
+    assigned: HashMap<(&'static str, String), usize>,
+    next: HashMap<&'static str, usize>,
+}
+
+impl Placeholders {
+    fn replace(&mut self, kind: &'static str, value: &Value) -> Value {
+        let key = (kind, value.to_string());
+        let number = match self.assigned.get(&key) {
+            Some(number) => *number,
+            None => {
+                let next = self.next.entry(kind).or_insert(0);
+                *next += 1;
+                self.assigned.insert(key, *next);
+                *next
+            }
+        };
+        Value::String(format!("[{kind} {number}]"))
+    }
+}
+
+fn normalize_value(value: &mut Value, key: Option<&str>, placeholders: &mut Placeholders) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                normalize_value(value, Some(key), placeholders);
+            }
+        }
+        Value::Array(values) => {
+            for value in values {
+                normalize_value(value, key, placeholders);
+            }
+        }
+        Value::Null => {}
+        scalar => {
+            let Some(key) = key else { return };
+            if ENTITY_ID_KEYS.contains(&key) {
+                *scalar = placeholders.replace("entity", scalar);
+            } else if let Some(kind) = INTERNAL_ID_KEYS.iter().find(|k| **k == key) {
+                *scalar = placeholders.replace(kind, scalar);
+            } else if key.ends_with("_DT") {
+                *scalar = Value::String("[timestamp]".to_string());
+            }
+        }
+    }
+}
+
+/// Returns the file for the next snapshot taken in `function` (a path from
+/// `std::any::type_name`), defined in `source_file` of the crate at
+/// `manifest_dir`. The second and later snapshots of a function get `-2`,
+/// `-3`, ... suffixes. Used by the snapshot macros.
+#[doc(hidden)]
+pub fn snapshot_path(manifest_dir: &str, source_file: &str, function: &str) -> PathBuf {
+    static TAKEN: Mutex<Option<HashMap<String, usize>>> = Mutex::new(None);
+
+    let function = function.trim_end_matches("::__snapshot_fn");
+    let function = function.rsplit("::").next().unwrap_or(function);
+    let file = Path::new(source_file)
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("snapshot");
+    let base = format!("{file}__{function}");
+
+    let mut taken = match TAKEN.lock() {
+        Ok(guard) => guard,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    let count = taken
+        .get_or_insert_with(HashMap::new)
+        .entry(base.clone())
+        .or_insert(0);
+    *count += 1;
+    let name = if *count == 1 {
+        base
+    } else {
+        format!("{base}-{count}")
+    };
+    Path::new(manifest_dir)
+        .join("tests")
+        .join("snapshots")
+        .join(format!("{name}.snap"))
+}
+
+/// Compares `actual` against the snapshot at `path`.
+///
+/// Writes the snapshot if it does not exist yet, or if it differs and
+/// [`UPDATE_ENV_VAR`] is set.
+///
+/// # Panics
+///
+/// If the snapshot differs, or cannot be read or written.
+pub fn assert_snapshot(path: &Path, actual: &str) {
+    let actual = format!("{}\n", actual.trim_end());
+    let update = std::env::var_os(UPDATE_ENV_VAR).is_some_and(|v| v != "0");
+    match std::fs::read_to_string(path) {
+        Ok(expected) if expected == actual => {}
+        Ok(expected) if !update => panic!(
+            "snapshot {} does not match (set {UPDATE_ENV_VAR}=1 to update)\n\
+             {}\n--- expected\n{expected}--- actual\n{actual}",
+            path.display(),
+            first_difference(&expected, &actual),
+        ),
+        Ok(_) => write_snapshot(path, &actual),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => write_snapshot(path, &actual),
+        Err(e) => panic!("cannot read snapshot {}: {e}", path.display()),
+    }
+}
+
+fn write_snapshot(path: &Path, contents: &str) {
+    if let Some(dir) = path.parent()
+        && let Err(e) = std::fs::create_dir_all(dir)
+    {
+        panic!("cannot create snapshot directory {}: {e}", dir.display());
+    }
+    if let Err(e) = std::fs::write(path, contents) {
+        panic!("cannot write snapshot {}: {e}", path.display());
+    }
+    eprintln!("wrote snapshot {}", path.display());
+}
+
+fn first_difference(expected: &str, actual: &str) -> String {
+    let mut expected_lines = expected.lines();
+    let mut actual_lines = actual.lines();
+    for line in 1.. {
+        match (expected_lines.next(), actual_lines.next()) {
+            (Some(e), Some(a)) if e == a => {}
+            (None, None) => break,
+            (e, a) => {
+                return format!(
+                    "first difference at line {line}:\n-{}\n+{}",
+                    e.unwrap_or("<end>"),
+                    a.unwrap_or("<end>")
+                );
+            }
+        }
+    }
+    String::new()
+}
+
+/// Asserts that a JSON response, normalized with
+/// [`normalize_response`](crate::testing::snapshot::normalize_response),
+/// matches its snapshot file.
+///
+/// # Panics
+///
+/// If `json` is not valid JSON or the snapshot differs.
+#[macro_export]
+macro_rules! assert_json_snapshot {
+    ($json:expr $(,)?) => {{
+        fn __snapshot_fn() {}
+        let path = $crate::testing::snapshot::snapshot_path(
+            env!("CARGO_MANIFEST_DIR"),
+            file!(),
+            ::std::any::type_name_of_val(&__snapshot_fn),
+        );
+        let normalized = $crate::testing::snapshot::normalize_response(&$json)
+            .unwrap_or_else(|e| panic!("cannot normalize response: {e}"));
+        $crate::testing::snapshot::assert_snapshot(&path, &normalized);
+    }};
+}
+
+/// Asserts that `get_entity` for an entity ID, normalized with
+/// [`normalize_response`](crate::testing::snapshot::normalize_response),
+/// matches its snapshot file.
+///
+/// Takes anything that dereferences to an engine, such as the
+/// `Box<dyn SzEngine>` from `get_engine` or the `Box<dyn SzEngineRead>` from
+/// `get_engine_read`.
+///
+/// # Panics
+///
+/// If `get_entity` fails or the snapshot differs.
+#[macro_export]
+macro_rules! assert_entity_snapshot {
+    ($engine:expr, $entity_id:expr, $flags:expr $(,)?) => {{
+        let entity_id: $crate::types::EntityId = $entity_id;
+        let response = $crate::traits::SzEngineRead::get_entity(
+            &*$engine,
+            $crate::types::EntityRef::Id(entity_id),
+            $flags,
+        )
+        .unwrap_or_else(|e| panic!("get_entity({entity_id}) failed: {e}"));
+        $crate::assert_json_snapshot!(response);
+    }};
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_response() {
+        let json = r#"{
+            "RESOLVED_ENTITY": {"ENTITY_ID": 57, "LAST_SEEN_DT": "2026-01-01T00:00:00Z",
+                "RECORDS": [{"INTERNAL_ID": 9, "RECORD_ID": "1"}, {"INTERNAL_ID": 12, "RECORD_ID": "2"}]},
+            "RELATED_ENTITIES": [{"ENTITY_ID": 80, "MATCH_KEY": "+NAME"}, {"ENTITY_ID": 57}]
+        }"#;
+        let normalized: Value = serde_json::from_str(&normalize_response(json).unwrap()).unwrap();
+        let resolved = &normalized["RESOLVED_ENTITY"];
+        // RELATED_ENTITIES sorts before RESOLVED_ENTITY
+        assert_eq!(resolved["ENTITY_ID"], "[entity 2]");
+        assert_eq!(resolved["LAST_SEEN_DT"], "[timestamp]");
+        assert_eq!(resolved["RECORDS"][1]["INTERNAL_ID"], "[INTERNAL_ID 2]");
+        assert_eq!(resolved["RECORDS"][1]["RECORD_ID"], "2");
+        assert_eq!(normalized["RELATED_ENTITIES"][0]["ENTITY_ID"], "[entity 1]");
+        assert_eq!(normalized["RELATED_ENTITIES"][1]["ENTITY_ID"], "[entity 2]");
+
+        // Different IDs for the same shape normalize identically
+        let renumbered = json.replace("57", "3").replace("80", "4");
+        assert_eq!(
+            normalize_response(json).unwrap(),
+            normalize_response(&renumbered).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_snapshot_path_numbers_repeats() {
+        let function = "my_tests::test_snapshot_path_numbers_repeats::__snapshot_fn";
+        let first = snapshot_path("/repo", "tests/sz_entity_test.rs", function);
+        let second = snapshot_path("/repo", "tests/sz_entity_test.rs", function);
+        assert_eq!(
+            first,
+            Path::new(
+                "/repo/tests/snapshots/sz_entity_test__test_snapshot_path_numbers_repeats.snap"
+            )
+        );
+        assert!(second.ends_with("sz_entity_test__test_snapshot_path_numbers_repeats-2.snap"));
+    }
+
+    #[test]
+    fn test_assert_snapshot_writes_then_compares() {
+        let dir = std::env::temp_dir().join(format!("sz-snapshot-test-{}", std::process::id()));
+        let path = dir.join("case.snap");
+        assert_snapshot(&path, "{\n  \"A\": 1\n}");
+        assert_snapshot(&path, "{\n  \"A\": 1\n}");
+        let mismatch = std::panic::catch_unwind(|| assert_snapshot(&path, "{}"));
+        let _ = std::fs::remove_dir_all(&dir);
+        assert!(mismatch.is_err());
+    }
+}
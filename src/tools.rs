@@ -0,0 +1,304 @@
+//! Data maintenance tools.
+//!
+//! Multi-step repository operations that are otherwise orchestrated by hand
+//! from the engine primitives.
+
+use crate::config_tools::SzBootstrap;
+use crate::core::SzEnvironmentCore;
+use crate::error::{SzError, SzResult};
+use crate::flags::SzFlags;
+use crate::ingest::{self, LoadSummary};
+use crate::redo::{RedoSummary, SzRedoProcessor};
+use crate::traits::{SzEngine, SzEnvironment};
+use crate::types::{ConfigId, PurgeConfirmation, RepositoryCounts};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Default number of redo workers used by [`reload_repository`].
+pub const DEFAULT_RELOAD_REDO_WORKERS: usize = 4;
+
+/// Outcome of [`migrate_data_source`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MigrationSummary {
+    /// Records re-added under the new data source and deleted from the old one
+    pub records_migrated: u64,
+    /// Redo records processed after the migration
+    pub redo_records_processed: u64,
+}
+
+/// Moves every record of data source `from` to data source `to`.
+///
+/// Each record's stored JSON is re-added under `to` with the same record ID
+/// and its `DATA_SOURCE` field rewritten, then the original is deleted.
+/// Afterwards the redo queue is drained, so resolution reflects the move when
+/// this returns. `to` must already be registered in the active configuration.
+///
+/// Records are moved one at a time and the new copy is added before the
+/// original is deleted, so an interrupted migration never loses a record and
+/// can simply be run again: records already moved are no longer in `from`.
+///
+/// Redo processing is not limited to redo records caused by the migration;
+/// any pending redo work is processed as well.
+///
+/// # Example
+///
+/// ```no_run
+/// # use sz_rust_sdk::helpers::ExampleEnvironment;
+/// use sz_rust_sdk::prelude::*;
+/// use sz_rust_sdk::tools;
+///
+/// # let env = ExampleEnvironment::initialize("doctest_migrate_data_source")?;
+/// let engine = env.get_engine()?;
+/// let summary = tools::migrate_data_source(&*engine, "CRM_OLD", "CRM")?;
+/// println!("Moved {} records", summary.records_migrated);
+/// # Ok::<(), SzError>(())
+/// ```
+///
+/// # Errors
+///
+/// * `SzError::BadInput` - `from` and `to` are the same, or a stored record
+///   is not a JSON object
+/// * `SzError::UnknownDataSource` - `from` or `to` is not registered
+/// * Any error from the export, record, or redo calls
+pub fn migrate_data_source(
+    engine: &dyn SzEngine,
+    from: &str,
+    to: &str,
+) -> SzResult<MigrationSummary> {
+    if from == to {
+        return Err(SzError::bad_input(format!(
+            "Cannot migrate data source {from} to itself"
+        )));
+    }
+
+    let mut summary = MigrationSummary::default();
+    for record_id in crate::export::data_source_record_ids(engine, from)? {
+        let record = engine.get_record(
+            from,
+            &record_id,
+            Some(SzFlags::ENTITY_INCLUDE_RECORD_JSON_DATA),
+        )?;
+        let definition = rewrite_data_source(&record, to)?;
+        engine.add_record(to, &record_id, &definition, None)?;
+        engine.delete_record(from, &record_id, None)?;
+        summary.records_migrated += 1;
+    }
+
+    loop {
+        let redo = engine.get_redo_record()?;
+        if redo.is_empty() {
+            break;
+        }
+        engine.process_redo_record(&redo, None)?;
+        summary.redo_records_processed += 1;
+    }
+
+    Ok(summary)
+}
+
+/// A named stream of line-delimited JSON records for [`reload_repository`].
+pub struct ReloadSource {
+    name: String,
+    reader: Box<dyn BufRead>,
+}
+
+impl ReloadSource {
+    /// Wraps any buffered reader; `name` identifies the source in the report.
+    pub fn new(name: impl Into<String>, reader: impl BufRead + 'static) -> Self {
+        Self {
+            name: name.into(),
+            reader: Box::new(reader),
+        }
+    }
+
+    /// Opens a JSON lines file, named by its path.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the file cannot be opened.
+    pub fn open(path: impl AsRef<Path>) -> SzResult<Self> {
+        let path = path.as_ref();
+        let file = File::open(path)
+            .map_err(|e| SzError::bad_input(format!("Failed opening {}: {e}", path.display())))?;
+        Ok(Self::new(path.display().to_string(), BufReader::new(file)))
+    }
+
+    /// Name of the source.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Settings for [`reload_repository`].
+pub struct ReloadOptions {
+    confirmation: PurgeConfirmation,
+    data_sources: Vec<String>,
+    redo_workers: usize,
+}
+
+impl ReloadOptions {
+    /// Creates options with no extra data sources and the default number of
+    /// redo workers. The reload purges the repository, so it takes the same
+    /// confirmation as `SzDiagnostic::purge_repository`.
+    pub fn new(confirmation: PurgeConfirmation) -> Self {
+        Self {
+            confirmation,
+            data_sources: Vec::new(),
+            redo_workers: DEFAULT_RELOAD_REDO_WORKERS,
+        }
+    }
+
+    /// Sets the data sources the default configuration must register before
+    /// loading.
+    pub fn with_data_sources<I, S>(mut self, data_sources: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.data_sources = data_sources.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Sets the number of threads that drain the redo queue. Values below 1
+    /// are treated as 1.
+    pub fn with_redo_workers(mut self, workers: usize) -> Self {
+        self.redo_workers = workers.max(1);
+        self
+    }
+}
+
+/// Outcome of [`reload_repository`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReloadReport {
+    /// Default configuration the records were loaded under
+    pub config_id: ConfigId,
+    /// Load outcome per source, in the order given
+    pub sources: Vec<(String, LoadSummary)>,
+    /// Redo processing after the loads
+    pub redo: RedoSummary,
+    /// Repository totals after redo processing
+    pub counts: RepositoryCounts,
+    /// Wall time of the whole reload
+    pub elapsed: Duration,
+}
+
+impl ReloadReport {
+    /// Records loaded across all sources.
+    pub fn records_loaded(&self) -> u64 {
+        self.sources
+            .iter()
+            .map(|(_, summary)| summary.records_loaded)
+            .sum()
+    }
+}
+
+/// Rebuilds a repository from scratch: purges it, makes sure the default
+/// configuration registers `options`' data sources, loads every source in
+/// order, processes redo records until the queue is empty, and counts the
+/// result.
+///
+/// Meant for development and staging repositories. The purge is subject to
+/// `SzEnvironmentCore::set_purge_enabled`, so an environment with purging
+/// disabled fails before anything is changed.
+///
+/// # Example
+///
+/// ```no_run
+/// # use sz_rust_sdk::helpers::ExampleEnvironment;
+/// use sz_rust_sdk::prelude::*;
+/// use sz_rust_sdk::tools::{self, ReloadOptions, ReloadSource};
+///
+/// # let env = ExampleEnvironment::initialize("doctest_reload_repository")?;
+/// let options = ReloadOptions::new(PurgeConfirmation::I_UNDERSTAND_THIS_DELETES_ALL_DATA)
+///     .with_data_sources(["CUSTOMERS", "WATCHLIST"]);
+/// let sources = [
+///     ReloadSource::open("customers.jsonl")?,
+///     ReloadSource::open("watchlist.jsonl")?,
+/// ];
+/// let report = tools::reload_repository(&env, sources, options)?;
+/// println!(
+///     "{} records loaded into {} entities in {:?}",
+///     report.records_loaded(),
+///     report.counts.entities,
+///     report.elapsed
+/// );
+/// # Ok::<(), SzError>(())
+/// ```
+///
+/// # Errors
+///
+/// * `SzError::BadInput` - Purging is disabled, or a source line cannot be
+///   loaded (the message names the line)
+/// * Any error from the purge, the configuration update, the loads or the
+///   redo processing. The steps before the failure are not undone.
+pub fn reload_repository(
+    env: &Arc<SzEnvironmentCore>,
+    sources: impl IntoIterator<Item = ReloadSource>,
+    options: ReloadOptions,
+) -> SzResult<ReloadReport> {
+    let started = Instant::now();
+    env.get_diagnostic()?
+        .purge_repository(options.confirmation)?;
+
+    let data_sources: Vec<&str> = options.data_sources.iter().map(String::as_str).collect();
+    let config_id = SzBootstrap::ensure_default_config(env, &data_sources)?;
+
+    let engine = env.get_engine()?;
+    let mut loaded = Vec::new();
+    for source in sources {
+        let summary = ingest::load_from_reader(engine.as_ref(), source.reader)?;
+        loaded.push((source.name, summary));
+    }
+
+    let redo = SzRedoProcessor::new(env.clone())
+        .with_workers(1, options.redo_workers)
+        .drain_until(1)?;
+    let counts = engine.repository_counts(Duration::ZERO)?;
+
+    Ok(ReloadReport {
+        config_id,
+        sources: loaded,
+        redo,
+        counts,
+        elapsed: started.elapsed(),
+    })
+}
+
+/// Takes `JSON_DATA` from a `get_record` response and points it at `to`.
+fn rewrite_data_source(record: &str, to: &str) -> SzResult<String> {
+    let record: serde_json::Value = serde_json::from_str(record)?;
+    let Some(serde_json::Value::Object(mut data)) = record.get("JSON_DATA").cloned() else {
+        return Err(SzError::bad_input(
+            "Record response does not contain a JSON_DATA object",
+        ));
+    };
+    data.insert(
+        "DATA_SOURCE".to_string(),
+        serde_json::Value::String(to.to_string()),
+    );
+    Ok(serde_json::Value::Object(data).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_data_source() {
+        let record = r#"{"DATA_SOURCE":"OLD","RECORD_ID":"1",
+            "JSON_DATA":{"DATA_SOURCE":"OLD","RECORD_ID":"1","NAME_FULL":"A B"}}"#;
+        let rewritten: serde_json::Value =
+            serde_json::from_str(&rewrite_data_source(record, "NEW").unwrap()).unwrap();
+        assert_eq!(rewritten["DATA_SOURCE"], "NEW");
+        assert_eq!(rewritten["RECORD_ID"], "1");
+        assert_eq!(rewritten["NAME_FULL"], "A B");
+    }
+
+    #[test]
+    fn test_rewrite_data_source_without_json_data() {
+        assert!(rewrite_data_source(r#"{"RECORD_ID":"1"}"#, "NEW").is_err());
+    }
+}
@@ -3,7 +3,12 @@
 //! This module contains the main trait definitions that mirror the C# SDK interfaces.
 //! These traits define the contract for interacting with the Senzing engine.
 
-use crate::{error::SzResult, flags::SzFlags, types::*};
+use crate::{
+    bytes::SzBytes,
+    error::{SzError, SzResult},
+    flags::{RelationshipKind, SzFlags},
+    types::*,
+};
 use std::collections::HashSet;
 
 /// Main entry point and factory for Senzing SDK components.
@@ -146,6 +151,103 @@ pub trait SzEnvironment: Send + Sync {
     /// * `SzError::EnvironmentDestroyed` - Environment was destroyed
     fn get_engine(&self) -> SzResult<Box<dyn SzEngine>>;
 
+    /// Gets an engine restricted to the methods allowed by `scope`.
+    ///
+    /// Lets components that share one environment hold only the engine
+    /// capabilities they need: a query service can take a `ReadOnly` engine,
+    /// a loader a `Loader` engine. Methods outside the scope fail with
+    /// `SzError::NotPermitted` without reaching the native library. An `Admin`
+    /// engine is the same as [`get_engine`](Self::get_engine).
+    ///
+    /// Use this when the scope is only known at run time, such as from
+    /// configuration. Otherwise prefer the compile-time restricted
+    /// [`get_engine_read`](Self::get_engine_read) and
+    /// [`get_engine_load`](Self::get_engine_load), whose engines do not have
+    /// the methods outside their scope at all.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_get_engine_scoped")?;
+    /// let reader = env.get_engine_scoped(EngineScope::ReadOnly)?;
+    /// let result = reader.add_record("TEST", "SCOPE_1", r#"{"NAME_FULL": "Jo Doe"}"#, None);
+    /// assert!(matches!(result, Err(SzError::NotPermitted(_))));
+    /// # Ok::<(), SzError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::EnvironmentDestroyed` - Environment was destroyed
+    fn get_engine_scoped(&self, scope: EngineScope) -> SzResult<Box<dyn SzEngine>> {
+        let engine = self.get_engine()?;
+        if scope == EngineScope::Admin {
+            return Ok(engine);
+        }
+        Ok(Box::new(crate::core::scoped_engine::ScopedEngine::new(
+            engine, scope,
+        )))
+    }
+
+    /// Gets an engine with only the read methods of [`SzEngineRead`].
+    ///
+    /// The compile-time counterpart of a `ReadOnly`
+    /// [`get_engine_scoped`](Self::get_engine_scoped) engine: writes are not
+    /// rejected at run time, they do not compile.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_get_engine_read")?;
+    /// let reader = env.get_engine_read()?;
+    /// let entity = reader.get_entity(EntityRef::from_record("TEST", "1001"), None);
+    /// # Ok::<(), SzError>(())
+    /// ```
+    ///
+    /// ```compile_fail
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_get_engine_read_write")?;
+    /// let reader = env.get_engine_read()?;
+    /// reader.add_record("TEST", "1001", r#"{"NAME_FULL": "Jo Doe"}"#, None)?;
+    /// # Ok::<(), SzError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::EnvironmentDestroyed` - Environment was destroyed
+    fn get_engine_read(&self) -> SzResult<Box<dyn SzEngineRead>> {
+        Ok(self.get_engine()?)
+    }
+
+    /// Gets an engine with the read and write methods of [`SzEngineLoad`],
+    /// without the administrative methods of [`SzEngine`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_get_engine_load")?;
+    /// let loader = env.get_engine_load()?;
+    /// loader.add_record("TEST", "1001", r#"{"NAME_FULL": "Jo Doe"}"#, None)?;
+    /// # Ok::<(), SzError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::EnvironmentDestroyed` - Environment was destroyed
+    fn get_engine_load(&self) -> SzResult<Box<dyn SzEngineLoad>> {
+        Ok(self.get_engine()?)
+    }
+
     /// Gets the configuration manager interface.
     ///
     /// # Returns
@@ -262,6 +364,12 @@ pub trait SzEnvironment: Send + Sync {
 /// performing searches, and conducting various types of analysis. This is the
 /// primary interface for entity resolution operations.
 ///
+/// Its methods are grouped by capability: the reads are declared on
+/// [`SzEngineRead`], the writes on [`SzEngineLoad`], and `SzEngine` adds
+/// the administrative [`prime_engine`](Self::prime_engine). Bring the
+/// supertraits into scope (they are in the prelude) to call their methods on
+/// a concrete engine type.
+///
 /// # Obtaining an Instance
 ///
 /// ```
@@ -272,7 +380,7 @@ pub trait SzEnvironment: Send + Sync {
 /// let engine = env.get_engine()?;
 /// # Ok::<(), SzError>(())
 /// ```
-pub trait SzEngine: Send + Sync {
+pub trait SzEngine: SzEngineLoad {
     /// Primes the engine for optimal performance.
     ///
     /// Loads internal caches and prepares the engine for high-throughput operations.
@@ -295,7 +403,28 @@ pub trait SzEngine: Send + Sync {
     ///
     /// Returns `SzError::NotInitialized` if the environment is not initialized.
     fn prime_engine(&self) -> SzResult<()>;
+}
 
+/// Entity resolution engine operations that only read.
+///
+/// The searches, entity and record retrieval, analysis, counts and exports
+/// of [`SzEngine`]. A component that must not change the repository can
+/// take a `dyn SzEngineRead` from
+/// [`SzEnvironment::get_engine_read`]; the compiler then rejects any write
+/// it attempts.
+///
+/// # Example
+///
+/// ```
+/// # use sz_rust_sdk::helpers::ExampleEnvironment;
+/// # let env = ExampleEnvironment::initialize("doctest_sz_engine_read")?;
+/// use sz_rust_sdk::prelude::*;
+///
+/// let reader: Box<dyn SzEngineRead> = env.get_engine_read()?;
+/// let stats = reader.get_stats()?;
+/// # Ok::<(), SzError>(())
+/// ```
+pub trait SzEngineRead: Send + Sync {
     /// Gets engine performance statistics.
     ///
     /// Returns a JSON object containing internal performance metrics useful for
@@ -320,68 +449,6 @@ pub trait SzEngine: Send + Sync {
     /// ```
     fn get_stats(&self) -> SzResult<JsonString>;
 
-    /// Adds a record for entity resolution.
-    ///
-    /// Inserts or updates a record in the entity repository. The record will be
-    /// matched and potentially merged with existing entities based on configured rules.
-    ///
-    /// # Arguments
-    ///
-    /// * `data_source_code` - The data source identifier (must be registered)
-    /// * `record_id` - Unique identifier for the record within the data source
-    /// * `record_definition` - JSON object containing the record attributes
-    /// * `flags` - Optional flags controlling what information is returned
-    ///
-    /// # Returns
-    ///
-    /// JSON string with information about affected entities (when flags request it).
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
-    /// use sz_rust_sdk::prelude::*;
-    ///
-    /// # let env = ExampleEnvironment::initialize("doctest_add_record")?;
-    /// let engine = env.get_engine()?;
-    ///
-    /// let record = r#"{"NAME_FULL": "John Smith", "ADDR_FULL": "123 Main St"}"#;
-    /// let result = engine.add_record("TEST", "ADD_1001", record, None)?;
-    /// # Ok::<(), SzError>(())
-    /// ```
-    ///
-    /// With flags to get entity info back:
-    ///
-    /// ```
-    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
-    /// use sz_rust_sdk::prelude::*;
-    ///
-    /// # let env = ExampleEnvironment::initialize("doctest_add_record_flags")?;
-    /// let engine = env.get_engine()?;
-    ///
-    /// let record = r#"{"NAME_FULL": "Jane Doe", "EMAIL": "jane@example.com"}"#;
-    /// let result = engine.add_record(
-    ///     "TEST",
-    ///     "ADD_1002",
-    ///     record,
-    ///     Some(SzFlags::WITH_INFO),
-    /// )?;
-    /// // result contains affected entity info when WITH_INFO is set
-    /// # Ok::<(), SzError>(())
-    /// ```
-    ///
-    /// # Errors
-    ///
-    /// * `SzError::UnknownDataSource` - Data source is not registered
-    /// * `SzError::BadInput` - Invalid JSON or missing required fields
-    fn add_record(
-        &self,
-        data_source_code: &str,
-        record_id: &str,
-        record_definition: &str,
-        flags: Option<SzFlags>,
-    ) -> SzResult<JsonString>;
-
     /// Gets a preview of how a record would be processed without persisting it.
     ///
     /// Useful for testing record mappings and seeing how features would be extracted
@@ -415,132 +482,6 @@ pub trait SzEngine: Send + Sync {
         flags: Option<SzFlags>,
     ) -> SzResult<JsonString>;
 
-    /// Deletes a record from the entity repository.
-    ///
-    /// Removes the record and re-resolves any affected entities. If the record
-    /// was the only record in an entity, the entity is also removed.
-    ///
-    /// # Arguments
-    ///
-    /// * `data_source_code` - The data source identifier
-    /// * `record_id` - The record identifier to delete
-    /// * `flags` - Optional flags controlling what information is returned
-    ///
-    /// # Returns
-    ///
-    /// JSON string with information about affected entities.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
-    /// use sz_rust_sdk::prelude::*;
-    ///
-    /// # let env = ExampleEnvironment::initialize("doctest_delete_record")?;
-    /// let engine = env.get_engine()?;
-    ///
-    /// // First add a record, then delete it
-    /// # engine.add_record("TEST", "DEL_1001",
-    /// #     r#"{"NAME_FULL": "Delete Me"}"#, None)?;
-    /// let result = engine.delete_record("TEST", "DEL_1001", None)?;
-    /// # Ok::<(), SzError>(())
-    /// ```
-    ///
-    /// # Errors
-    ///
-    /// * `SzError::UnknownDataSource` - Data source is not registered
-    /// * `SzError::NotFound` - Record does not exist
-    fn delete_record(
-        &self,
-        data_source_code: &str,
-        record_id: &str,
-        flags: Option<SzFlags>,
-    ) -> SzResult<JsonString>;
-
-    /// Reevaluates a specific record against current rules.
-    ///
-    /// Forces re-resolution of a record using the current configuration. Useful
-    /// after configuration changes to update entity assignments.
-    ///
-    /// # Arguments
-    ///
-    /// * `data_source_code` - The data source identifier
-    /// * `record_id` - The record identifier to reevaluate
-    /// * `flags` - Optional flags controlling what information is returned
-    ///
-    /// # Returns
-    ///
-    /// JSON string with reevaluation results.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
-    /// use sz_rust_sdk::prelude::*;
-    ///
-    /// # let env = ExampleEnvironment::initialize("doctest_reevaluate_record")?;
-    /// let engine = env.get_engine()?;
-    /// # engine.add_record("TEST", "REEV_REC_1001",
-    /// #     r#"{"NAME_FULL": "John Smith"}"#, None)?;
-    ///
-    /// let result = engine.reevaluate_record("TEST", "REEV_REC_1001", None)?;
-    /// # Ok::<(), SzError>(())
-    /// ```
-    ///
-    /// # Errors
-    ///
-    /// * `SzError::NotFound` - Record does not exist
-    fn reevaluate_record(
-        &self,
-        data_source_code: &str,
-        record_id: &str,
-        flags: Option<SzFlags>,
-    ) -> SzResult<JsonString>;
-
-    /// Reevaluates all records for a specific entity.
-    ///
-    /// Forces re-resolution of all records in an entity. The entity may split
-    /// into multiple entities or merge with others based on current rules.
-    ///
-    /// # Arguments
-    ///
-    /// * `entity_id` - The entity identifier to reevaluate
-    /// * `flags` - Optional flags controlling what information is returned
-    ///
-    /// # Returns
-    ///
-    /// JSON string with reevaluation results.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
-    /// use sz_rust_sdk::prelude::*;
-    ///
-    /// # let env = ExampleEnvironment::initialize("doctest_reevaluate_entity")?;
-    /// let engine = env.get_engine()?;
-    /// # engine.add_record("TEST", "REEV_ENT_1001",
-    /// #     r#"{"NAME_FULL": "John Smith"}"#, None)?;
-    /// # let result = engine.get_entity(
-    /// #     EntityRef::Record { data_source: "TEST", record_id: "REEV_ENT_1001" },
-    /// #     None,
-    /// # )?;
-    /// # let entity_json: serde_json::Value = serde_json::from_str(&result).unwrap();
-    /// # let entity_id = entity_json["RESOLVED_ENTITY"]["ENTITY_ID"].as_i64().unwrap();
-    ///
-    /// let result = engine.reevaluate_entity(entity_id, None)?;
-    /// # Ok::<(), SzError>(())
-    /// ```
-    ///
-    /// # Errors
-    ///
-    /// * `SzError::NotFound` - Entity does not exist
-    fn reevaluate_entity(
-        &self,
-        entity_id: EntityId,
-        flags: Option<SzFlags>,
-    ) -> SzResult<JsonString>;
-
     /// Searches for entities by attributes.
     ///
     /// Finds entities that match the provided attributes. Returns scored results
@@ -580,6 +521,21 @@ pub trait SzEngine: Send + Sync {
         flags: Option<SzFlags>,
     ) -> SzResult<JsonString>;
 
+    /// Searches by attributes, returning the native response buffer.
+    ///
+    /// Same as [`search_by_attributes`](Self::search_by_attributes) but
+    /// returns an [`SzBytes`] that owns the engine's buffer instead of copying
+    /// it into a `String`. Useful when parsing many large responses.
+    fn search_by_attributes_bytes(
+        &self,
+        attributes: &str,
+        search_profile: Option<&str>,
+        flags: Option<SzFlags>,
+    ) -> SzResult<SzBytes> {
+        self.search_by_attributes(attributes, search_profile, flags)
+            .map(SzBytes::from)
+    }
+
     /// Analyzes why a search result was returned for an entity.
     ///
     /// Provides detailed explanation of why a particular entity matched the
@@ -611,7 +567,7 @@ pub trait SzEngine: Send + Sync {
     /// #     None,
     /// # )?;
     /// # let entity_json: serde_json::Value = serde_json::from_str(&result).unwrap();
-    /// # let entity_id = entity_json["RESOLVED_ENTITY"]["ENTITY_ID"].as_i64().unwrap();
+    /// # let entity_id = EntityId(entity_json["RESOLVED_ENTITY"]["ENTITY_ID"].as_i64().unwrap());
     ///
     /// let attrs = r#"{"NAME_FULL": "John Smith"}"#;
     /// let result = engine.why_search(attrs, entity_id, None, None)?;
@@ -625,6 +581,88 @@ pub trait SzEngine: Send + Sync {
         flags: Option<SzFlags>,
     ) -> SzResult<JsonString>;
 
+    /// Explains why an entity matches a set of search attributes.
+    ///
+    /// Runs [`why_search`](Self::why_search) with the default flags and
+    /// parses the response into a
+    /// [`SearchExplanation`](crate::models::SearchExplanation): feature
+    /// scores, the candidate keys that found the entity, and any disclosed
+    /// relationships involved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_explain_search")?;
+    /// let engine = env.get_engine()?;
+    /// # engine.add_record("TEST", "EXPL_1001", r#"{"NAME_FULL": "John Smith"}"#, None)?;
+    /// # let entity: serde_json::Value = serde_json::from_str(
+    /// #     &engine.get_entity(EntityRef::from_record("TEST", "EXPL_1001"), None)?)?;
+    /// # let entity_id = EntityId(entity["RESOLVED_ENTITY"]["ENTITY_ID"].as_i64().unwrap());
+    /// let explanation = engine.explain_search(r#"{"NAME_FULL": "John Smith"}"#, entity_id)?;
+    /// for (feature, scores) in &explanation.feature_scores {
+    ///     for score in scores {
+    ///         println!("{feature}: {:?}", score.score);
+    ///     }
+    /// }
+    /// # Ok::<(), SzError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::NotFound` - Entity does not exist
+    /// * `SzError::Json` - The response could not be parsed
+    fn explain_search(
+        &self,
+        attributes: &str,
+        entity_id: EntityId,
+    ) -> SzResult<crate::models::SearchExplanation> {
+        let response = self.why_search(attributes, entity_id, None, None)?;
+        crate::models::SearchExplanation::from_json(&response)
+    }
+
+    /// Searches by attributes and explains each result.
+    ///
+    /// Runs [`search_by_attributes`](Self::search_by_attributes), then
+    /// [`why_search`](Self::why_search) with the same search profile for
+    /// every matched entity, returning the results in search order. This makes one extra engine
+    /// call per result, so it is meant for investigation rather than
+    /// high-volume searching.
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::BadInput` - Invalid search attributes
+    /// * `SzError::Json` - A response could not be parsed
+    /// * Any error from the search or why-search calls
+    fn search_by_attributes_explained(
+        &self,
+        attributes: &str,
+        search_profile: Option<&str>,
+        flags: Option<SzFlags>,
+    ) -> SzResult<Vec<crate::models::ExplainedSearchResult>> {
+        let response: crate::models::SearchResponse =
+            serde_json::from_str(&self.search_by_attributes(attributes, search_profile, flags)?)?;
+        response
+            .resolved_entities
+            .into_iter()
+            .map(|result| {
+                let entity_id = result.entity.resolved_entity.entity_id;
+                let explanation = crate::models::SearchExplanation::from_json(&self.why_search(
+                    attributes,
+                    entity_id,
+                    search_profile,
+                    None,
+                )?)?;
+                Ok(crate::models::ExplainedSearchResult {
+                    result,
+                    explanation,
+                })
+            })
+            .collect()
+    }
+
     /// Gets entity information by entity ID or record key.
     ///
     /// Retrieves complete entity data including all constituent records and
@@ -643,6 +681,7 @@ pub trait SzEngine: Send + Sync {
     /// # Errors
     ///
     /// * `SzError::NotFound` - Entity or record does not exist
+    /// * `SzError::ResultTooLarge` - Response exceeds the environment's result size limit
     ///
     /// # Examples
     ///
@@ -665,6 +704,105 @@ pub trait SzEngine: Send + Sync {
     /// ```
     fn get_entity(&self, entity_ref: EntityRef, flags: Option<SzFlags>) -> SzResult<JsonString>;
 
+    /// Gets entity information, returning the native response buffer.
+    ///
+    /// Same as [`get_entity`](Self::get_entity), including the result size
+    /// limit, but returns an [`SzBytes`] that owns the engine's buffer instead
+    /// of copying it into a `String`.
+    fn get_entity_bytes(&self, entity_ref: EntityRef, flags: Option<SzFlags>) -> SzResult<SzBytes> {
+        self.get_entity(entity_ref, flags).map(SzBytes::from)
+    }
+
+    /// Gets an entity's relationships of the given kinds.
+    ///
+    /// Sends the matching `ENTITY_INCLUDE_*_RELATIONS` flags together with
+    /// the related entity names and matching info, and returns the parsed
+    /// `RELATED_ENTITIES` list. An empty `kinds` returns no relationships
+    /// without calling the engine.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_get_entity_relationships")?;
+    /// let engine = env.get_engine()?;
+    /// # engine.add_record("TEST", "REL_1001",
+    /// #     r#"{"NAME_FULL": "John Smith"}"#, None)?;
+    /// let related = engine.get_entity_relationships(
+    ///     EntityRef::from_record("TEST", "REL_1001"),
+    ///     RelationshipKind::DISCLOSED | RelationshipKind::POSSIBLY_SAME,
+    /// )?;
+    /// for entity in &related {
+    ///     println!("{}: {:?}", entity.entity_id, entity.match_key);
+    /// }
+    /// # Ok::<(), SzError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::NotFound` - Entity or record does not exist
+    fn get_entity_relationships(
+        &self,
+        entity_ref: EntityRef,
+        kinds: RelationshipKind,
+    ) -> SzResult<Vec<crate::models::RelatedEntity>> {
+        if kinds.is_empty() {
+            return Ok(Vec::new());
+        }
+        let flags = kinds.entity_flags()
+            | SzFlags::ENTITY_INCLUDE_RELATED_ENTITY_NAME
+            | SzFlags::ENTITY_INCLUDE_RELATED_MATCHING_INFO;
+        let entity: crate::models::ExportedEntity =
+            serde_json::from_str(&self.get_entity(entity_ref, Some(flags))?)?;
+        Ok(entity
+            .related_entities
+            .into_iter()
+            .filter(|related| kinds.intersects(related.relationship()))
+            .collect())
+    }
+
+    /// Gets the keys of the records in an entity.
+    ///
+    /// Compare the keys taken at two points in time with
+    /// [`analysis::membership_change`](crate::analysis::membership_change) to
+    /// see which records joined or left the entity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_get_entity_record_keys")?;
+    /// let engine = env.get_engine()?;
+    /// # engine.add_record("TEST", "KEYS_1001",
+    /// #     r#"{"NAME_FULL": "John Smith"}"#, None)?;
+    /// # let entity_id = EntityId(serde_json::from_str::<serde_json::Value>(&engine.get_entity(
+    /// #     EntityRef::from_record("TEST", "KEYS_1001"), None)?)?
+    /// #     ["RESOLVED_ENTITY"]["ENTITY_ID"].as_i64().unwrap());
+    /// let keys = engine.get_entity_record_keys(entity_id)?;
+    /// assert!(keys.contains(&RecordKey::new("TEST", "KEYS_1001")?));
+    /// # Ok::<(), SzError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::NotFound` - Entity does not exist
+    fn get_entity_record_keys(&self, entity_id: EntityId) -> SzResult<HashSet<RecordKey>> {
+        let entity: crate::models::ExportedEntity = serde_json::from_str(&self.get_entity(
+            EntityRef::Id(entity_id),
+            Some(SzFlags::ENTITY_INCLUDE_RECORD_DATA),
+        )?)?;
+        entity
+            .resolved_entity
+            .records
+            .into_iter()
+            .map(|record| RecordKey::new(record.data_source, record.record_id))
+            .collect()
+    }
+
     /// Gets record information.
     ///
     /// Retrieves the original record data as stored in the repository.
@@ -704,6 +842,43 @@ pub trait SzEngine: Send + Sync {
         flags: Option<SzFlags>,
     ) -> SzResult<JsonString>;
 
+    /// Gets record information for a [`RecordKey`].
+    ///
+    /// Same as [`get_record`](Self::get_record) with the key's data source
+    /// and record ID, which cannot be swapped by mistake.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_get_record_by_key")?;
+    /// let engine = env.get_engine()?;
+    /// let key = RecordKey::new("TEST", "REC_1002")?;
+    /// engine.add_record_by_key(&key, r#"{"NAME_FULL": "John Smith"}"#, None)?;
+    ///
+    /// let record = engine.get_record_by_key(&key, None)?;
+    /// # Ok::<(), SzError>(())
+    /// ```
+    fn get_record_by_key(&self, key: &RecordKey, flags: Option<SzFlags>) -> SzResult<JsonString> {
+        self.get_record(key.data_source.as_str(), &key.record_id, flags)
+    }
+
+    /// Gets record information, returning the native response buffer.
+    ///
+    /// Same as [`get_record`](Self::get_record) but returns an [`SzBytes`]
+    /// that owns the engine's buffer instead of copying it into a `String`.
+    fn get_record_bytes(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<SzBytes> {
+        self.get_record(data_source_code, record_id, flags)
+            .map(SzBytes::from)
+    }
+
     /// Finds interesting entities related to a given entity or record.
     ///
     /// Identifies entities with notable relationships to the specified entity,
@@ -778,13 +953,13 @@ pub trait SzEngine: Send + Sync {
     /// #     None,
     /// # )?;
     /// # let j1: serde_json::Value = serde_json::from_str(&r1).unwrap();
-    /// # let entity_id1 = j1["RESOLVED_ENTITY"]["ENTITY_ID"].as_i64().unwrap();
+    /// # let entity_id1 = EntityId(j1["RESOLVED_ENTITY"]["ENTITY_ID"].as_i64().unwrap());
     /// # let r2 = engine.get_entity(
     /// #     EntityRef::Record { data_source: "TEST", record_id: "PATH_1002" },
     /// #     None,
     /// # )?;
     /// # let j2: serde_json::Value = serde_json::from_str(&r2).unwrap();
-    /// # let entity_id2 = j2["RESOLVED_ENTITY"]["ENTITY_ID"].as_i64().unwrap();
+    /// # let entity_id2 = EntityId(j2["RESOLVED_ENTITY"]["ENTITY_ID"].as_i64().unwrap());
     ///
     /// let path = engine.find_path_by_entity_id(entity_id1, entity_id2, 3, None, None, None)?;
     /// # Ok::<(), SzError>(())
@@ -801,7 +976,7 @@ pub trait SzEngine: Send + Sync {
 
     /// Finds the shortest path between two entities by record key.
     ///
-    /// Identical to [`find_path_by_entity_id`](SzEngine::find_path_by_entity_id) but entities
+    /// Identical to [`find_path_by_entity_id`](SzEngineRead::find_path_by_entity_id) but entities
     /// are identified by their data source code and record ID instead of entity ID.
     ///
     /// # Arguments
@@ -847,7 +1022,7 @@ pub trait SzEngine: Send + Sync {
         end_data_source_code: &str,
         end_record_id: &str,
         max_degrees: i64,
-        avoid_record_keys: Option<&[(&str, &str)]>,
+        avoid_record_keys: Option<&[RecordKey]>,
         required_data_sources: Option<&HashSet<String>>,
         flags: Option<SzFlags>,
     ) -> SzResult<JsonString>;
@@ -884,7 +1059,7 @@ pub trait SzEngine: Send + Sync {
     /// #     None,
     /// # )?;
     /// # let j1: serde_json::Value = serde_json::from_str(&r1).unwrap();
-    /// # let entity_id = j1["RESOLVED_ENTITY"]["ENTITY_ID"].as_i64().unwrap();
+    /// # let entity_id = EntityId(j1["RESOLVED_ENTITY"]["ENTITY_ID"].as_i64().unwrap());
     ///
     /// let network = engine.find_network_by_entity_id(&[entity_id], 3, 1, 100, None)?;
     /// # Ok::<(), SzError>(())
@@ -900,12 +1075,12 @@ pub trait SzEngine: Send + Sync {
 
     /// Finds a network of related entities by record key.
     ///
-    /// Identical to [`find_network_by_entity_id`](SzEngine::find_network_by_entity_id) but entities
+    /// Identical to [`find_network_by_entity_id`](SzEngineRead::find_network_by_entity_id) but entities
     /// are identified by their data source code and record ID instead of entity ID.
     ///
     /// # Arguments
     ///
-    /// * `record_keys` - Seed record keys as (data_source_code, record_id) pairs
+    /// * `record_keys` - Seed record keys
     /// * `max_degrees` - Maximum relationship hops from seed entities
     /// * `build_out_degrees` - Degrees to expand for building connections
     /// * `max_entities` - Maximum entities to include in the network
@@ -927,20 +1102,64 @@ pub trait SzEngine: Send + Sync {
     /// #     r#"{"NAME_FULL": "John Smith"}"#, None)?;
     ///
     /// let network = engine.find_network_by_record_id(
-    ///     &[("TEST", "FNBR_1001")],
+    ///     &[RecordKey::new("TEST", "FNBR_1001")?],
     ///     3, 1, 100, None,
     /// )?;
     /// # Ok::<(), SzError>(())
     /// ```
     fn find_network_by_record_id(
         &self,
-        record_keys: &[(&str, &str)],
+        record_keys: &[RecordKey],
         max_degrees: i64,
         build_out_degrees: i64,
         max_entities: i64,
         flags: Option<SzFlags>,
     ) -> SzResult<JsonString>;
 
+    /// Finds a network of related entities as described by a validated
+    /// [`FindNetworkRequest`].
+    ///
+    /// Calls [`find_network_by_entity_id`](Self::find_network_by_entity_id)
+    /// or [`find_network_by_record_id`](Self::find_network_by_record_id),
+    /// depending on how the request's seeds are given.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_find_network_req")?;
+    /// let engine = env.get_engine()?;
+    /// # engine.add_record("TEST", "FNRQ_1001",
+    /// #     r#"{"NAME_FULL": "John Smith"}"#, None)?;
+    ///
+    /// let request = FindNetworkRequest::builder()
+    ///     .records([RecordKey::new("TEST", "FNRQ_1001")?])
+    ///     .max_degrees(2)
+    ///     .build()?;
+    /// let network = engine.find_network_req(&request)?;
+    /// # Ok::<(), SzError>(())
+    /// ```
+    fn find_network_req(&self, request: &FindNetworkRequest) -> SzResult<JsonString> {
+        match request.seeds() {
+            NetworkSeeds::Entities(entity_ids) => self.find_network_by_entity_id(
+                entity_ids,
+                request.max_degrees(),
+                request.build_out_degrees(),
+                request.max_entities(),
+                request.flags(),
+            ),
+            NetworkSeeds::Records(record_keys) => self.find_network_by_record_id(
+                record_keys,
+                request.max_degrees(),
+                request.build_out_degrees(),
+                request.max_entities(),
+                request.flags(),
+            ),
+        }
+    }
+
     /// Analyzes why two entities are related.
     ///
     /// Provides detailed explanation of the relationship between two entities,
@@ -973,13 +1192,13 @@ pub trait SzEngine: Send + Sync {
     /// #     None,
     /// # )?;
     /// # let j1: serde_json::Value = serde_json::from_str(&r1).unwrap();
-    /// # let entity_id1 = j1["RESOLVED_ENTITY"]["ENTITY_ID"].as_i64().unwrap();
+    /// # let entity_id1 = EntityId(j1["RESOLVED_ENTITY"]["ENTITY_ID"].as_i64().unwrap());
     /// # let r2 = engine.get_entity(
     /// #     EntityRef::Record { data_source: "TEST", record_id: "WHYE_1002" },
     /// #     None,
     /// # )?;
     /// # let j2: serde_json::Value = serde_json::from_str(&r2).unwrap();
-    /// # let entity_id2 = j2["RESOLVED_ENTITY"]["ENTITY_ID"].as_i64().unwrap();
+    /// # let entity_id2 = EntityId(j2["RESOLVED_ENTITY"]["ENTITY_ID"].as_i64().unwrap());
     ///
     /// let result = engine.why_entities(entity_id1, entity_id2, None)?;
     /// # Ok::<(), SzError>(())
@@ -1098,7 +1317,7 @@ pub trait SzEngine: Send + Sync {
     /// #     None,
     /// # )?;
     /// # let entity_json: serde_json::Value = serde_json::from_str(&result).unwrap();
-    /// # let entity_id = entity_json["RESOLVED_ENTITY"]["ENTITY_ID"].as_i64().unwrap();
+    /// # let entity_id = EntityId(entity_json["RESOLVED_ENTITY"]["ENTITY_ID"].as_i64().unwrap());
     ///
     /// let result = engine.how_entity(entity_id, None)?;
     /// # Ok::<(), SzError>(())
@@ -1112,7 +1331,7 @@ pub trait SzEngine: Send + Sync {
     ///
     /// # Arguments
     ///
-    /// * `record_keys` - Pairs of (data_source_code, record_id)
+    /// * `record_keys` - Keys of the records to resolve together
     /// * `flags` - Optional flags controlling result detail
     ///
     /// # Returns
@@ -1133,60 +1352,755 @@ pub trait SzEngine: Send + Sync {
     /// #     r#"{"NAME_FULL": "Jane Doe"}"#, None)?;
     ///
     /// let record_keys = vec![
-    ///     ("TEST".to_string(), "VIRT_1001".to_string()),
-    ///     ("TEST".to_string(), "VIRT_1002".to_string()),
+    ///     RecordKey::new("TEST", "VIRT_1001")?,
+    ///     RecordKey::new("TEST", "VIRT_1002")?,
     /// ];
     /// let result = engine.get_virtual_entity(&record_keys, None)?;
     /// # Ok::<(), SzError>(())
     /// ```
     fn get_virtual_entity(
         &self,
-        record_keys: &[(String, String)],
+        record_keys: &[RecordKey],
         flags: Option<SzFlags>,
     ) -> SzResult<JsonString>;
 
-    /// Processes a redo record for deferred resolution.
+    /// Counts pending redo records.
     ///
-    /// Handles records that were queued for later processing due to conflicts
-    /// or resource constraints.
+    /// # Returns
+    ///
+    /// Number of records waiting in the redo queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_count_redo_records")?;
+    /// let engine = env.get_engine()?;
+    ///
+    /// let count = engine.count_redo_records()?;
+    /// println!("Pending redo records: {}", count);
+    /// # Ok::<(), SzError>(())
+    /// ```
+    fn count_redo_records(&self) -> SzResult<i64>;
+
+    /// Counts the entities, records and relationships in the repository.
+    ///
+    /// The repository info from `SzDiagnostic` does not carry totals, so the
+    /// counts come from walking a complete entity export, which takes time
+    /// proportional to the repository size. The core engine keeps the last
+    /// counts for its environment and returns them while they are younger
+    /// than `max_age`; pass `Duration::ZERO` to force a fresh count.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    /// use std::time::Duration;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_repository_counts")?;
+    /// let engine = env.get_engine()?;
+    ///
+    /// let counts = engine.repository_counts(Duration::from_secs(300))?;
+    /// println!(
+    ///     "{} entities, {} records, {} relationships (as of {:?} ago)",
+    ///     counts.entities,
+    ///     counts.records,
+    ///     counts.relationships,
+    ///     counts.age()
+    /// );
+    /// # Ok::<(), SzError>(())
+    /// ```
+    fn repository_counts(&self, max_age: std::time::Duration) -> SzResult<RepositoryCounts> {
+        let _ = max_age;
+        crate::export::count_repository(self)
+    }
+
+    /// Counts the resolved entities in the repository.
+    ///
+    /// Reuses counts up to [`COUNT_CACHE_MAX_AGE`] old; see
+    /// [`repository_counts`](Self::repository_counts).
+    fn count_entities(&self) -> SzResult<i64> {
+        self.repository_counts(COUNT_CACHE_MAX_AGE)
+            .map(|counts| counts.entities)
+    }
+
+    /// Counts the records in the repository.
+    ///
+    /// Reuses counts up to [`COUNT_CACHE_MAX_AGE`] old; see
+    /// [`repository_counts`](Self::repository_counts).
+    fn count_records(&self) -> SzResult<i64> {
+        self.repository_counts(COUNT_CACHE_MAX_AGE)
+            .map(|counts| counts.records)
+    }
+
+    /// Counts the relationships between entities in the repository.
+    ///
+    /// Reuses counts up to [`COUNT_CACHE_MAX_AGE`] old; see
+    /// [`repository_counts`](Self::repository_counts).
+    fn count_relationships(&self) -> SzResult<i64> {
+        self.repository_counts(COUNT_CACHE_MAX_AGE)
+            .map(|counts| counts.relationships)
+    }
+
+    /// Starts a JSON entity export.
+    ///
+    /// Initiates an export operation returning a handle for fetching results.
+    /// Use `fetch_next` to retrieve data and `close_export_report` when done.
     ///
     /// # Arguments
     ///
-    /// * `redo_record` - The redo record JSON from `get_redo_record`
-    /// * `flags` - Optional flags controlling result detail
+    /// * `flags` - Optional flags controlling what data is exported
     ///
     /// # Returns
     ///
-    /// JSON string with processing results.
+    /// Handle for fetching export data.
     ///
     /// # Examples
     ///
+    /// Export all entities as JSON (full export loop):
+    ///
     /// ```
     /// # use sz_rust_sdk::helpers::ExampleEnvironment;
     /// use sz_rust_sdk::prelude::*;
     ///
-    /// # let env = ExampleEnvironment::initialize("doctest_process_redo_record")?;
+    /// # let env = ExampleEnvironment::initialize("doctest_export")?;
     /// let engine = env.get_engine()?;
     ///
-    /// let redo = engine.get_redo_record()?;
-    /// if !redo.is_empty() {
-    ///     let result = engine.process_redo_record(&redo, None)?;
+    /// let handle = engine.export_json_entity_report(None)?;
+    /// loop {
+    ///     let chunk = engine.fetch_next(handle)?;
+    ///     if chunk.is_empty() {
+    ///         break;
+    ///     }
+    ///     print!("{}", chunk);
     /// }
+    /// engine.close_export_report(handle)?;
+    /// # Ok::<(), SzError>(())
+    /// ```
+    fn export_json_entity_report(&self, flags: Option<SzFlags>) -> SzResult<ExportHandle>;
+
+    /// Starts a CSV entity export.
+    ///
+    /// Initiates a CSV export with specified columns.
+    ///
+    /// # Arguments
+    ///
+    /// * `csv_column_list` - Comma-separated list of columns to include
+    /// * `flags` - Optional flags controlling what data is exported
+    ///
+    /// # Returns
+    ///
+    /// Handle for fetching export data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_export_csv")?;
+    /// let engine = env.get_engine()?;
+    ///
+    /// let handle = engine.export_csv_entity_report(
+    ///     "RESOLVED_ENTITY_ID,RELATED_ENTITY_ID,MATCH_LEVEL_CODE",
+    ///     None,
+    /// )?;
+    /// loop {
+    ///     let chunk = engine.fetch_next(handle)?;
+    ///     if chunk.is_empty() {
+    ///         break;
+    ///     }
+    ///     print!("{}", chunk);
+    /// }
+    /// engine.close_export_report(handle)?;
+    /// # Ok::<(), SzError>(())
+    /// ```
+    fn export_csv_entity_report(
+        &self,
+        csv_column_list: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<ExportHandle>;
+
+    /// Fetches the next batch of export data.
+    ///
+    /// Call repeatedly until empty string is returned to get all export data.
+    ///
+    /// # Arguments
+    ///
+    /// * `export_handle` - Handle from `export_json_entity_report` or `export_csv_entity_report`
+    ///
+    /// # Returns
+    ///
+    /// Next batch of export data, or empty string when complete.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_fetch_next")?;
+    /// let engine = env.get_engine()?;
+    ///
+    /// let handle = engine.export_json_entity_report(None)?;
+    /// let chunk = engine.fetch_next(handle)?;
+    /// // Empty string means no more data
+    /// engine.close_export_report(handle)?;
+    /// # Ok::<(), SzError>(())
+    /// ```
+    fn fetch_next(&self, export_handle: ExportHandle) -> SzResult<JsonString>;
+
+    /// Closes an export operation and releases resources.
+    ///
+    /// Must be called when finished with an export to free the handle.
+    ///
+    /// # Arguments
+    ///
+    /// * `export_handle` - Handle to close
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_close_export_report")?;
+    /// let engine = env.get_engine()?;
+    ///
+    /// let handle = engine.export_json_entity_report(None)?;
+    /// // ... fetch data ...
+    /// engine.close_export_report(handle)?;
+    /// # Ok::<(), SzError>(())
+    /// ```
+    fn close_export_report(&self, export_handle: ExportHandle) -> SzResult<()>;
+}
+
+/// Entity resolution engine operations of a loader.
+///
+/// The reads of [`SzEngineRead`] plus record writes, reevaluation and redo
+/// processing. Everything in [`SzEngine`] except the administrative
+/// [`prime_engine`](SzEngine::prime_engine). A loader can take a
+/// `dyn SzEngineLoad` from [`SzEnvironment::get_engine_load`].
+pub trait SzEngineLoad: SzEngineRead {
+    /// Adds a record for entity resolution.
+    ///
+    /// Inserts or updates a record in the entity repository. The record will be
+    /// matched and potentially merged with existing entities based on configured rules.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_source_code` - The data source identifier (must be registered)
+    /// * `record_id` - Unique identifier for the record within the data source
+    /// * `record_definition` - JSON object containing the record attributes
+    /// * `flags` - Optional flags controlling what information is returned
+    ///
+    /// # Returns
+    ///
+    /// JSON string with information about affected entities (when flags request it).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_add_record")?;
+    /// let engine = env.get_engine()?;
+    ///
+    /// let record = r#"{"NAME_FULL": "John Smith", "ADDR_FULL": "123 Main St"}"#;
+    /// let result = engine.add_record("TEST", "ADD_1001", record, None)?;
+    /// # Ok::<(), SzError>(())
+    /// ```
+    ///
+    /// With flags to get entity info back:
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_add_record_flags")?;
+    /// let engine = env.get_engine()?;
+    ///
+    /// let record = r#"{"NAME_FULL": "Jane Doe", "EMAIL": "jane@example.com"}"#;
+    /// let result = engine.add_record(
+    ///     "TEST",
+    ///     "ADD_1002",
+    ///     record,
+    ///     Some(SzFlags::WITH_INFO),
+    /// )?;
+    /// // result contains affected entity info when WITH_INFO is set
+    /// # Ok::<(), SzError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::UnknownDataSource` - Data source is not registered
+    /// * `SzError::BadInput` - Invalid JSON or missing required fields
+    fn add_record(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        record_definition: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString>;
+
+    /// Adds a record under a [`RecordKey`].
+    ///
+    /// Same as [`add_record`](Self::add_record) with the key's data source
+    /// and record ID, which cannot be swapped by mistake.
+    fn add_record_by_key(
+        &self,
+        key: &RecordKey,
+        record_definition: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.add_record(
+            key.data_source.as_str(),
+            &key.record_id,
+            record_definition,
+            flags,
+        )
+    }
+
+    /// Deletes a record from the entity repository.
+    ///
+    /// Removes the record and re-resolves any affected entities. If the record
+    /// was the only record in an entity, the entity is also removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_source_code` - The data source identifier
+    /// * `record_id` - The record identifier to delete
+    /// * `flags` - Optional flags controlling what information is returned
+    ///
+    /// # Returns
+    ///
+    /// JSON string with information about affected entities.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_delete_record")?;
+    /// let engine = env.get_engine()?;
+    ///
+    /// // First add a record, then delete it
+    /// # engine.add_record("TEST", "DEL_1001",
+    /// #     r#"{"NAME_FULL": "Delete Me"}"#, None)?;
+    /// let result = engine.delete_record("TEST", "DEL_1001", None)?;
+    /// # Ok::<(), SzError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::UnknownDataSource` - Data source is not registered
+    /// * `SzError::NotFound` - Record does not exist
+    fn delete_record(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString>;
+
+    /// Deletes the record with a [`RecordKey`].
+    ///
+    /// Same as [`delete_record`](Self::delete_record) with the key's data
+    /// source and record ID.
+    fn delete_record_by_key(
+        &self,
+        key: &RecordKey,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.delete_record(key.data_source.as_str(), &key.record_id, flags)
+    }
+
+    /// Replaces a record by deleting it and adding the new definition.
+    ///
+    /// Intended for sources that send full-record snapshots: unlike
+    /// [`add_record`](Self::add_record) on an existing key, features missing
+    /// from the new snapshot are guaranteed to be gone. If the add fails, the
+    /// previous version of the record is restored before the error is
+    /// returned, so callers see either the old or the new record.
+    ///
+    /// With `SzFlags::WITH_INFO` the returned document combines both
+    /// operations: `AFFECTED_ENTITIES` lists every entity touched by either
+    /// the delete or the add. Without it, `SZ_NO_INFO` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_replace_record")?;
+    /// let engine = env.get_engine()?;
+    /// # engine.add_record("TEST", "REPL_1001",
+    /// #     r#"{"NAME_FULL": "John Smith", "PHONE_NUMBER": "555-1212"}"#, None)?;
+    /// let info = engine.replace_record(
+    ///     "TEST",
+    ///     "REPL_1001",
+    ///     r#"{"NAME_FULL": "John Smith"}"#,
+    ///     Some(SzFlags::WITH_INFO),
+    /// )?;
+    /// assert!(info.contains("AFFECTED_ENTITIES"));
+    /// # Ok::<(), SzError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::UnknownDataSource` - Data source is not registered
+    /// * `SzError::BadInput` - Invalid JSON or missing required fields
+    fn replace_record(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        record_definition: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        let previous = match self.get_record(
+            data_source_code,
+            record_id,
+            Some(SzFlags::ENTITY_INCLUDE_RECORD_JSON_DATA),
+        ) {
+            Ok(record) => previous_record_json(&record)?,
+            Err(SzError::NotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+
+        let delete_info = self.delete_record(data_source_code, record_id, flags)?;
+        let add_info = match self.add_record(data_source_code, record_id, record_definition, flags)
+        {
+            Ok(info) => info,
+            Err(e) => {
+                if let Some(previous) = previous {
+                    // Best effort: the add error is what the caller needs to see.
+                    let _ = self.add_record(data_source_code, record_id, &previous, None);
+                }
+                return Err(e);
+            }
+        };
+
+        merge_info(&delete_info, &add_info)
+    }
+
+    /// Replaces the record with a [`RecordKey`].
+    ///
+    /// Same as [`replace_record`](Self::replace_record) with the key's data
+    /// source and record ID.
+    fn replace_record_by_key(
+        &self,
+        key: &RecordKey,
+        record_definition: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.replace_record(
+            key.data_source.as_str(),
+            &key.record_id,
+            record_definition,
+            flags,
+        )
+    }
+
+    /// Adds a record unless the stored record already has the same content.
+    ///
+    /// Intended for daily full-file reloads, where most records are
+    /// unchanged: the stored record's `JSON_DATA` is compared with
+    /// `record_definition` by [`ingest::record_content_hash`](crate::ingest::record_content_hash),
+    /// which ignores key order, whitespace, and the `DATA_SOURCE` and
+    /// `RECORD_ID` fields. When they match, the add is skipped. The check
+    /// costs one `get_record`, which is far cheaper than an add.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_add_record_if_changed")?;
+    /// let engine = env.get_engine()?;
+    /// let record = r#"{"NAME_FULL": "John Smith", "PHONE_NUMBER": "555-1212"}"#;
+    /// engine.add_record_if_changed("TEST", "UPSERT_1001", record, None)?;
+    ///
+    /// let reordered = r#"{"PHONE_NUMBER": "555-1212", "NAME_FULL": "John Smith"}"#;
+    /// let outcome = engine.add_record_if_changed("TEST", "UPSERT_1001", reordered, None)?;
+    /// assert_eq!(outcome, UpsertOutcome::Unchanged);
+    /// # Ok::<(), SzError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::BadInput` - `record_definition` is not valid JSON
+    /// * Any error from [`get_record`](SzEngineRead::get_record) other than
+    ///   `SzError::NotFound`, or from [`add_record`](Self::add_record)
+    fn add_record_if_changed(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        record_definition: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<UpsertOutcome> {
+        let new_hash = crate::ingest::record_content_hash(record_definition)?;
+        let existed = match self.get_record(
+            data_source_code,
+            record_id,
+            Some(SzFlags::ENTITY_INCLUDE_RECORD_JSON_DATA),
+        ) {
+            Ok(record) => {
+                if let Some(previous) = previous_record_json(&record)?
+                    && crate::ingest::record_content_hash(&previous)? == new_hash
+                {
+                    return Ok(UpsertOutcome::Unchanged);
+                }
+                true
+            }
+            Err(SzError::NotFound(_)) => false,
+            Err(e) => return Err(e),
+        };
+
+        let info = self.add_record(data_source_code, record_id, record_definition, flags)?;
+        Ok(if existed {
+            UpsertOutcome::Updated(info)
+        } else {
+            UpsertOutcome::Added(info)
+        })
+    }
+
+    /// Adds a record unless `hashes` holds the same content hash for it,
+    /// without asking the engine.
+    ///
+    /// Like [`add_record_if_changed`](Self::add_record_if_changed), but the
+    /// comparison uses the hash stored for the record in `hashes`, e.g. a
+    /// sidecar table, so an unchanged record costs no native call. A record
+    /// with a different stored hash is added and reported as
+    /// [`UpsertOutcome::Updated`]; a record without one falls back to the
+    /// `get_record` comparison of `add_record_if_changed`. After a
+    /// successful add, or an unchanged fallback, the new hash is stored.
+    ///
+    /// The store only sees changes made through it; see
+    /// [`ingest::hash_store`](crate::ingest::hash_store).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::ingest::MemoryRecordHashStore;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_add_record_if_changed_with_hashes")?;
+    /// let engine = env.get_engine()?;
+    /// let hashes = MemoryRecordHashStore::new();
+    /// let record = r#"{"NAME_FULL": "John Smith", "PHONE_NUMBER": "555-1212"}"#;
+    /// engine.add_record_if_changed_with_hashes("TEST", "HASHED_1001", record, None, &hashes)?;
+    ///
+    /// let outcome =
+    ///     engine.add_record_if_changed_with_hashes("TEST", "HASHED_1001", record, None, &hashes)?;
+    /// assert_eq!(outcome, UpsertOutcome::Unchanged);
+    /// # Ok::<(), SzError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::BadInput` - `record_definition` is not valid JSON, or the
+    ///   data source code or record ID is empty
+    /// * Any error from `hashes`, or as for
+    ///   [`add_record_if_changed`](Self::add_record_if_changed)
+    fn add_record_if_changed_with_hashes(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        record_definition: &str,
+        flags: Option<SzFlags>,
+        hashes: &dyn crate::ingest::RecordHashStore,
+    ) -> SzResult<UpsertOutcome> {
+        let key = RecordKey::new(data_source_code, record_id)?;
+        let new_hash = crate::ingest::record_content_hash(record_definition)?;
+        let outcome = match hashes.get(&key)? {
+            Some(hash) if hash == new_hash => return Ok(UpsertOutcome::Unchanged),
+            Some(_) => UpsertOutcome::Updated(self.add_record(
+                data_source_code,
+                record_id,
+                record_definition,
+                flags,
+            )?),
+            None => {
+                self.add_record_if_changed(data_source_code, record_id, record_definition, flags)?
+            }
+        };
+        hashes.set(&key, new_hash)?;
+        Ok(outcome)
+    }
+
+    /// Applies record adds and deletes in order, stopping at the first
+    /// failure.
+    ///
+    /// The engine has no transactions, so a failed batch leaves the
+    /// operations before the failure applied. With
+    /// [`BatchFailureAction::Compensate`] they are then reversed, newest
+    /// first: the stored `JSON_DATA` of each record is captured before it is
+    /// changed, so an added record is deleted or restored and a deleted
+    /// record is added back. Capturing costs one `get_record` per operation.
+    /// Other writers changing the same records during the batch can make
+    /// the reversal restore stale content.
+    ///
+    /// The report says how far the batch got and which reversals failed; it
+    /// is returned whether or not the batch succeeded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_apply_batch")?;
+    /// let engine = env.get_engine()?;
+    /// let report = engine.apply_batch(
+    ///     vec![
+    ///         RecordOp::add("TEST", "BATCH_1", r#"{"NAME_FULL": "Ann Lee"}"#),
+    ///         RecordOp::add("TEST", "BATCH_2", "not json"),
+    ///     ],
+    ///     BatchFailureAction::Compensate,
+    /// );
+    /// assert_eq!(report.failure.as_ref().map(|f| f.index), Some(1));
+    /// assert!(report.is_rolled_back());
+    /// assert!(engine.get_record("TEST", "BATCH_1", None).is_err());
+    /// # Ok::<(), SzError>(())
+    /// ```
+    fn apply_batch(&self, ops: Vec<RecordOp>, on_failure: BatchFailureAction) -> BatchReport {
+        let compensate = on_failure == BatchFailureAction::Compensate;
+        let mut report = BatchReport::default();
+        // Content of each applied operation's record before the operation
+        let mut undo: Vec<(usize, Option<String>)> = Vec::new();
+
+        for (index, op) in ops.iter().enumerate() {
+            let (data_source_code, record_id) = op.key();
+            let result = (|| -> SzResult<Option<String>> {
+                let previous = if compensate {
+                    match self.get_record(
+                        data_source_code,
+                        record_id,
+                        Some(SzFlags::ENTITY_INCLUDE_RECORD_JSON_DATA),
+                    ) {
+                        Ok(record) => previous_record_json(&record)?,
+                        Err(SzError::NotFound(_)) => None,
+                        Err(e) => return Err(e),
+                    }
+                } else {
+                    None
+                };
+                match op {
+                    RecordOp::Add {
+                        record_definition, ..
+                    } => self.add_record(data_source_code, record_id, record_definition, None)?,
+                    RecordOp::Delete { .. } => {
+                        self.delete_record(data_source_code, record_id, None)?
+                    }
+                };
+                Ok(previous)
+            })();
+            match result {
+                Ok(previous) => {
+                    report.applied += 1;
+                    if compensate {
+                        undo.push((index, previous));
+                    }
+                }
+                Err(error) => {
+                    report.failure = Some(BatchFailure { index, error });
+                    break;
+                }
+            }
+        }
+
+        if report.failure.is_some() {
+            for (index, previous) in undo.into_iter().rev() {
+                let (data_source_code, record_id) = ops[index].key();
+                let result = match (&ops[index], previous) {
+                    (_, Some(previous)) => self
+                        .add_record(data_source_code, record_id, &previous, None)
+                        .map(drop),
+                    (RecordOp::Add { .. }, None) => self
+                        .delete_record(data_source_code, record_id, None)
+                        .map(drop),
+                    (RecordOp::Delete { .. }, None) => Ok(()),
+                };
+                match result {
+                    Ok(()) => report.compensated += 1,
+                    Err(error) => report
+                        .compensation_failures
+                        .push(BatchFailure { index, error }),
+                }
+            }
+        }
+        report
+    }
+
+    /// Makes two records resolve together, whatever their features.
+    ///
+    /// Both records get the same steward trusted ID; see the
+    /// [`stewardship`](crate::stewardship) module. Records whose attributes
+    /// change are added again with `flags`, and the `WITH_INFO` responses
+    /// are merged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_force_merge")?;
+    /// let engine = env.get_engine()?;
+    /// # engine.add_record("TEST", "FM_1", r#"{"NAME_FULL": "Ann Lee"}"#, None)?;
+    /// # engine.add_record("TEST", "FM_2", r#"{"NAME_FULL": "Bo Chen"}"#, None)?;
+    /// let a = RecordKey::new("TEST", "FM_1")?;
+    /// let b = RecordKey::new("TEST", "FM_2")?;
+    /// engine.force_merge(&a, &b, None)?;
     /// # Ok::<(), SzError>(())
     /// ```
-    fn process_redo_record(
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::NotFound` - A record does not exist
+    /// * Any error from [`add_record`](Self::add_record)
+    fn force_merge(
         &self,
-        redo_record: &str,
+        a: &RecordKey,
+        b: &RecordKey,
         flags: Option<SzFlags>,
-    ) -> SzResult<JsonString>;
+    ) -> SzResult<JsonString> {
+        let mut first = crate::stewardship::stored_record(self, a)?;
+        let mut second = crate::stewardship::stored_record(self, b)?;
+        let number = crate::stewardship::trusted_id(&first)
+            .or_else(|| crate::stewardship::trusted_id(&second))
+            .map_or_else(|| a.to_string(), str::to_string);
 
-    /// Gets the next pending redo record.
-    ///
-    /// Retrieves one record from the redo queue for processing.
-    ///
-    /// # Returns
+        let mut info = SZ_NO_INFO.to_string();
+        for (key, record) in [(a, &mut first), (b, &mut second)] {
+            if crate::stewardship::set_trusted_id(record, &number) {
+                let added = self.add_record(
+                    key.data_source.as_str(),
+                    &key.record_id,
+                    &serde_json::Value::Object(record.clone()).to_string(),
+                    flags,
+                )?;
+                info = merge_info(&info, &added)?;
+            }
+        }
+        Ok(info)
+    }
+
+    /// Takes a record out of its entity and keeps it out.
     ///
-    /// JSON string with redo record data, or empty string if queue is empty.
+    /// The record gets a steward trusted ID of its own and every other
+    /// record of its entity a shared, different one, replacing earlier
+    /// steward trusted IDs on those records; see the
+    /// [`stewardship`](crate::stewardship) module. Records whose attributes
+    /// change are added again with `flags`, and the `WITH_INFO` responses
+    /// are merged. A record alone in its entity is left as it is.
     ///
     /// # Examples
     ///
@@ -1194,22 +2108,51 @@ pub trait SzEngine: Send + Sync {
     /// # use sz_rust_sdk::helpers::ExampleEnvironment;
     /// use sz_rust_sdk::prelude::*;
     ///
-    /// # let env = ExampleEnvironment::initialize("doctest_get_redo_record")?;
+    /// # let env = ExampleEnvironment::initialize("doctest_force_unmerge")?;
     /// let engine = env.get_engine()?;
-    ///
-    /// let redo = engine.get_redo_record()?;
-    /// if redo.is_empty() {
-    ///     println!("No redo records pending");
-    /// }
+    /// # engine.add_record("TEST", "FU_1", r#"{"NAME_FULL": "Ann Lee", "PHONE_NUMBER": "555-0100"}"#, None)?;
+    /// # engine.add_record("TEST", "FU_2", r#"{"NAME_FULL": "Ann Lee", "PHONE_NUMBER": "555-0100"}"#, None)?;
+    /// engine.force_unmerge(&RecordKey::new("TEST", "FU_2")?, None)?;
     /// # Ok::<(), SzError>(())
     /// ```
-    fn get_redo_record(&self) -> SzResult<JsonString>;
-
-    /// Counts pending redo records.
     ///
-    /// # Returns
+    /// # Errors
     ///
-    /// Number of records waiting in the redo queue.
+    /// * `SzError::NotFound` - The record does not exist
+    /// * Any error from [`add_record`](Self::add_record)
+    fn force_unmerge(&self, record: &RecordKey, flags: Option<SzFlags>) -> SzResult<JsonString> {
+        let mates = crate::stewardship::entity_mates(self, record)?;
+        let Some(first_mate) = mates.first() else {
+            return Ok(SZ_NO_INFO.to_string());
+        };
+        let mate_number = first_mate.to_string();
+        let assignments = std::iter::once((record, record.to_string()))
+            .chain(mates.iter().map(|mate| (mate, mate_number.clone())));
+
+        let mut info = SZ_NO_INFO.to_string();
+        for (key, number) in assignments {
+            let mut stored = crate::stewardship::stored_record(self, key)?;
+            if crate::stewardship::set_trusted_id(&mut stored, &number) {
+                let added = self.add_record(
+                    key.data_source.as_str(),
+                    &key.record_id,
+                    &serde_json::Value::Object(stored).to_string(),
+                    flags,
+                )?;
+                info = merge_info(&info, &added)?;
+            }
+        }
+        Ok(info)
+    }
+
+    /// Declares a relationship from one record to another, with `role`
+    /// naming what `to` is to `from`, e.g. `EMPLOYER`.
+    ///
+    /// `to` becomes a relationship anchor and `from` points to it; see the
+    /// [`stewardship`](crate::stewardship) module. The entities of the two
+    /// records are then related as disclosed, without being merged.
+    /// Records whose attributes change are added again with `flags`, and the
+    /// `WITH_INFO` responses are merged.
     ///
     /// # Examples
     ///
@@ -1217,64 +2160,125 @@ pub trait SzEngine: Send + Sync {
     /// # use sz_rust_sdk::helpers::ExampleEnvironment;
     /// use sz_rust_sdk::prelude::*;
     ///
-    /// # let env = ExampleEnvironment::initialize("doctest_count_redo_records")?;
+    /// # let env = ExampleEnvironment::initialize("doctest_force_relate")?;
     /// let engine = env.get_engine()?;
-    ///
-    /// let count = engine.count_redo_records()?;
-    /// println!("Pending redo records: {}", count);
+    /// # engine.add_record("TEST", "FR_1", r#"{"NAME_FULL": "Ann Lee"}"#, None)?;
+    /// # engine.add_record("TEST", "FR_2", r#"{"NAME_ORG": "Acme Corp"}"#, None)?;
+    /// let employee = RecordKey::new("TEST", "FR_1")?;
+    /// let employer = RecordKey::new("TEST", "FR_2")?;
+    /// engine.force_relate(&employee, &employer, "EMPLOYER", None)?;
     /// # Ok::<(), SzError>(())
     /// ```
-    fn count_redo_records(&self) -> SzResult<i64>;
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::BadInput` - `role` is empty or both keys are the same
+    /// * `SzError::NotFound` - A record does not exist
+    /// * Any error from [`add_record`](Self::add_record)
+    fn force_relate(
+        &self,
+        from: &RecordKey,
+        to: &RecordKey,
+        role: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        if role.trim().is_empty() {
+            return Err(SzError::bad_input("Relationship role must not be empty"));
+        }
+        if from == to {
+            return Err(SzError::bad_input(format!(
+                "Cannot relate record {from} to itself"
+            )));
+        }
+        let mut anchor = crate::stewardship::stored_record(self, to)?;
+        let mut pointer = crate::stewardship::stored_record(self, from)?;
+        let (anchor_key, anchor_changed) =
+            crate::stewardship::set_rel_anchor(&mut anchor, &to.to_string());
+        let pointer_changed = crate::stewardship::add_rel_pointer(&mut pointer, &anchor_key, role);
 
-    /// Starts a JSON entity export.
+        let mut info = SZ_NO_INFO.to_string();
+        for (key, record, changed) in [
+            (to, anchor, anchor_changed),
+            (from, pointer, pointer_changed),
+        ] {
+            if changed {
+                let added = self.add_record(
+                    key.data_source.as_str(),
+                    &key.record_id,
+                    &serde_json::Value::Object(record).to_string(),
+                    flags,
+                )?;
+                info = merge_info(&info, &added)?;
+            }
+        }
+        Ok(info)
+    }
+
+    /// Reevaluates a specific record against current rules.
     ///
-    /// Initiates an export operation returning a handle for fetching results.
-    /// Use `fetch_next` to retrieve data and `close_export_report` when done.
+    /// Forces re-resolution of a record using the current configuration. Useful
+    /// after configuration changes to update entity assignments.
     ///
     /// # Arguments
     ///
-    /// * `flags` - Optional flags controlling what data is exported
+    /// * `data_source_code` - The data source identifier
+    /// * `record_id` - The record identifier to reevaluate
+    /// * `flags` - Optional flags controlling what information is returned
     ///
     /// # Returns
     ///
-    /// Handle for fetching export data.
+    /// JSON string with reevaluation results.
     ///
     /// # Examples
     ///
-    /// Export all entities as JSON (full export loop):
-    ///
     /// ```
     /// # use sz_rust_sdk::helpers::ExampleEnvironment;
     /// use sz_rust_sdk::prelude::*;
     ///
-    /// # let env = ExampleEnvironment::initialize("doctest_export")?;
+    /// # let env = ExampleEnvironment::initialize("doctest_reevaluate_record")?;
     /// let engine = env.get_engine()?;
+    /// # engine.add_record("TEST", "REEV_REC_1001",
+    /// #     r#"{"NAME_FULL": "John Smith"}"#, None)?;
     ///
-    /// let handle = engine.export_json_entity_report(None)?;
-    /// loop {
-    ///     let chunk = engine.fetch_next(handle)?;
-    ///     if chunk.is_empty() {
-    ///         break;
-    ///     }
-    ///     print!("{}", chunk);
-    /// }
-    /// engine.close_export_report(handle)?;
+    /// let result = engine.reevaluate_record("TEST", "REEV_REC_1001", None)?;
     /// # Ok::<(), SzError>(())
     /// ```
-    fn export_json_entity_report(&self, flags: Option<SzFlags>) -> SzResult<ExportHandle>;
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::NotFound` - Record does not exist
+    fn reevaluate_record(
+        &self,
+        data_source_code: &str,
+        record_id: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString>;
 
-    /// Starts a CSV entity export.
+    /// Reevaluates the record with a [`RecordKey`].
     ///
-    /// Initiates a CSV export with specified columns.
+    /// Same as [`reevaluate_record`](Self::reevaluate_record) with the key's
+    /// data source and record ID.
+    fn reevaluate_record_by_key(
+        &self,
+        key: &RecordKey,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString> {
+        self.reevaluate_record(key.data_source.as_str(), &key.record_id, flags)
+    }
+
+    /// Reevaluates all records for a specific entity.
+    ///
+    /// Forces re-resolution of all records in an entity. The entity may split
+    /// into multiple entities or merge with others based on current rules.
     ///
     /// # Arguments
     ///
-    /// * `csv_column_list` - Comma-separated list of columns to include
-    /// * `flags` - Optional flags controlling what data is exported
+    /// * `entity_id` - The entity identifier to reevaluate
+    /// * `flags` - Optional flags controlling what information is returned
     ///
     /// # Returns
     ///
-    /// Handle for fetching export data.
+    /// JSON string with reevaluation results.
     ///
     /// # Examples
     ///
@@ -1282,40 +2286,43 @@ pub trait SzEngine: Send + Sync {
     /// # use sz_rust_sdk::helpers::ExampleEnvironment;
     /// use sz_rust_sdk::prelude::*;
     ///
-    /// # let env = ExampleEnvironment::initialize("doctest_export_csv")?;
+    /// # let env = ExampleEnvironment::initialize("doctest_reevaluate_entity")?;
     /// let engine = env.get_engine()?;
+    /// # engine.add_record("TEST", "REEV_ENT_1001",
+    /// #     r#"{"NAME_FULL": "John Smith"}"#, None)?;
+    /// # let result = engine.get_entity(
+    /// #     EntityRef::Record { data_source: "TEST", record_id: "REEV_ENT_1001" },
+    /// #     None,
+    /// # )?;
+    /// # let entity_json: serde_json::Value = serde_json::from_str(&result).unwrap();
+    /// # let entity_id = EntityId(entity_json["RESOLVED_ENTITY"]["ENTITY_ID"].as_i64().unwrap());
     ///
-    /// let handle = engine.export_csv_entity_report(
-    ///     "RESOLVED_ENTITY_ID,RELATED_ENTITY_ID,MATCH_LEVEL_CODE",
-    ///     None,
-    /// )?;
-    /// loop {
-    ///     let chunk = engine.fetch_next(handle)?;
-    ///     if chunk.is_empty() {
-    ///         break;
-    ///     }
-    ///     print!("{}", chunk);
-    /// }
-    /// engine.close_export_report(handle)?;
+    /// let result = engine.reevaluate_entity(entity_id, None)?;
     /// # Ok::<(), SzError>(())
     /// ```
-    fn export_csv_entity_report(
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::NotFound` - Entity does not exist
+    fn reevaluate_entity(
         &self,
-        csv_column_list: &str,
+        entity_id: EntityId,
         flags: Option<SzFlags>,
-    ) -> SzResult<ExportHandle>;
+    ) -> SzResult<JsonString>;
 
-    /// Fetches the next batch of export data.
+    /// Processes a redo record for deferred resolution.
     ///
-    /// Call repeatedly until empty string is returned to get all export data.
+    /// Handles records that were queued for later processing due to conflicts
+    /// or resource constraints.
     ///
     /// # Arguments
     ///
-    /// * `export_handle` - Handle from `export_json_entity_report` or `export_csv_entity_report`
+    /// * `redo_record` - The redo record JSON from `get_redo_record`
+    /// * `flags` - Optional flags controlling result detail
     ///
     /// # Returns
     ///
-    /// Next batch of export data, or empty string when complete.
+    /// JSON string with processing results.
     ///
     /// # Examples
     ///
@@ -1323,24 +2330,28 @@ pub trait SzEngine: Send + Sync {
     /// # use sz_rust_sdk::helpers::ExampleEnvironment;
     /// use sz_rust_sdk::prelude::*;
     ///
-    /// # let env = ExampleEnvironment::initialize("doctest_fetch_next")?;
+    /// # let env = ExampleEnvironment::initialize("doctest_process_redo_record")?;
     /// let engine = env.get_engine()?;
     ///
-    /// let handle = engine.export_json_entity_report(None)?;
-    /// let chunk = engine.fetch_next(handle)?;
-    /// // Empty string means no more data
-    /// engine.close_export_report(handle)?;
+    /// let redo = engine.get_redo_record()?;
+    /// if !redo.is_empty() {
+    ///     let result = engine.process_redo_record(&redo, None)?;
+    /// }
     /// # Ok::<(), SzError>(())
     /// ```
-    fn fetch_next(&self, export_handle: ExportHandle) -> SzResult<JsonString>;
+    fn process_redo_record(
+        &self,
+        redo_record: &str,
+        flags: Option<SzFlags>,
+    ) -> SzResult<JsonString>;
 
-    /// Closes an export operation and releases resources.
+    /// Gets the next pending redo record.
     ///
-    /// Must be called when finished with an export to free the handle.
+    /// Retrieves one record from the redo queue for processing.
     ///
-    /// # Arguments
+    /// # Returns
     ///
-    /// * `export_handle` - Handle to close
+    /// JSON string with redo record data, or empty string if queue is empty.
     ///
     /// # Examples
     ///
@@ -1348,15 +2359,16 @@ pub trait SzEngine: Send + Sync {
     /// # use sz_rust_sdk::helpers::ExampleEnvironment;
     /// use sz_rust_sdk::prelude::*;
     ///
-    /// # let env = ExampleEnvironment::initialize("doctest_close_export_report")?;
+    /// # let env = ExampleEnvironment::initialize("doctest_get_redo_record")?;
     /// let engine = env.get_engine()?;
     ///
-    /// let handle = engine.export_json_entity_report(None)?;
-    /// // ... fetch data ...
-    /// engine.close_export_report(handle)?;
+    /// let redo = engine.get_redo_record()?;
+    /// if redo.is_empty() {
+    ///     println!("No redo records pending");
+    /// }
     /// # Ok::<(), SzError>(())
     /// ```
-    fn close_export_report(&self, export_handle: ExportHandle) -> SzResult<()>;
+    fn get_redo_record(&self) -> SzResult<JsonString>;
 }
 
 /// Configuration management operations.
@@ -1594,6 +2606,58 @@ pub trait SzConfigManager {
     fn create_config_from_definition(&self, config_definition: &str)
     -> SzResult<Box<dyn SzConfig>>;
 
+    /// Validates a configuration definition without registering it.
+    ///
+    /// Checks the document for malformed JSON, missing sections, duplicate
+    /// data source or feature type codes, and references to undefined
+    /// feature types, then loads it with
+    /// [`create_config_from_definition`](Self::create_config_from_definition)
+    /// as a dry run. Each problem is reported as a separate
+    /// [`ConfigFinding`](crate::config_tools::ConfigFinding) with the JSON
+    /// path it applies to, instead of a single error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_validate_config")?;
+    /// let config_mgr = env.get_config_manager()?;
+    /// let json = config_mgr.create_config()?.export()?;
+    /// let report = config_mgr.validate_config(&json)?;
+    /// assert!(report.is_valid(), "{report}");
+    /// # Ok::<(), SzError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Problems with the document are returned in the report. Errors are
+    /// only returned when the dry run fails for another reason, for example
+    /// `SzError::EnvironmentDestroyed`.
+    fn validate_config(
+        &self,
+        config_definition: &str,
+    ) -> SzResult<crate::config_tools::ConfigValidationReport> {
+        let mut report = crate::config_tools::validation::check_definition(config_definition);
+        if report.has_invalid_json() {
+            return Ok(report);
+        }
+        match self.create_config_from_definition(config_definition) {
+            Ok(_) => {}
+            Err(e) if e.is_bad_input() || e.is_configuration() => {
+                report
+                    .findings
+                    .push(crate::config_tools::ConfigFinding::Rejected {
+                        error_code: e.error_code(),
+                        message: e.message().to_string(),
+                    });
+            }
+            Err(e) => return Err(e),
+        }
+        Ok(report)
+    }
+
     /// Gets the configuration registry.
     ///
     /// Returns information about all registered configuration versions.
@@ -1616,6 +2680,32 @@ pub trait SzConfigManager {
     /// ```
     fn get_config_registry(&self) -> SzResult<JsonString>;
 
+    /// Lists the registered configurations as typed entries.
+    ///
+    /// Parses [`get_config_registry`](Self::get_config_registry) into
+    /// [`ConfigInfo`](crate::models::ConfigInfo) values in registry order.
+    /// Use [`ConfigInfo::load`](crate::models::ConfigInfo::load) to open one
+    /// of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_list_configs")?;
+    /// let config_mgr = env.get_config_manager()?;
+    /// for info in config_mgr.list_configs()? {
+    ///     println!("{} {} {}", info.config_id, info.created, info.comment);
+    ///     let config = info.load(&*config_mgr)?;
+    ///     println!("{}", config.get_data_source_registry()?);
+    /// }
+    /// # Ok::<(), SzError>(())
+    /// ```
+    fn list_configs(&self) -> SzResult<Vec<crate::models::ConfigInfo>> {
+        crate::models::ConfigInfo::from_registry_json(&self.get_config_registry()?)
+    }
+
     /// Gets the currently active default configuration ID.
     ///
     /// # Returns
@@ -1707,6 +2797,57 @@ pub trait SzConfigManager {
         new_default_config_id: ConfigId,
     ) -> SzResult<()>;
 
+    /// Replaces the default configuration ID if it still equals `expected_current`.
+    ///
+    /// Compare-and-set form of
+    /// [`replace_default_config_id`](Self::replace_default_config_id) for
+    /// coordinating configuration updates between processes. Losing the race
+    /// is not an error of the call itself: it returns `Ok(Err(_))` with a
+    /// [`ConfigReplacedError`](crate::error::ConfigReplacedError) holding the
+    /// default that is now in place, so the caller can rebuild its change on
+    /// top of it and try again. Use `?` on the inner result to treat a lost
+    /// race as `SzError::ReplaceConflict` instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_compare_and_set_default_config_id")?;
+    /// let config_mgr = env.get_config_manager()?;
+    /// let mut current = config_mgr.get_default_config_id()?;
+    /// loop {
+    ///     let config = config_mgr.create_config_from_id(current)?;
+    ///     config.register_data_source("CUSTOMERS")?;
+    ///     let new_id = config_mgr.register_config(&config.export()?, Some("Add CUSTOMERS"))?;
+    ///     match config_mgr.compare_and_set_default_config_id(current, new_id)? {
+    ///         Ok(()) => break,
+    ///         Err(replaced) => current = replaced.actual,
+    ///     }
+    /// }
+    /// # Ok::<(), SzError>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `SzError::NotFound` - New configuration ID does not exist
+    /// * Any other error from the configuration manager
+    fn compare_and_set_default_config_id(
+        &self,
+        expected_current: ConfigId,
+        new_default_config_id: ConfigId,
+    ) -> SzResult<Result<(), crate::error::ConfigReplacedError>> {
+        match self.replace_default_config_id(expected_current, new_default_config_id) {
+            Ok(()) => Ok(Ok(())),
+            Err(SzError::ReplaceConflict(_)) => Ok(Err(crate::error::ConfigReplacedError {
+                expected: expected_current,
+                actual: self.get_default_config_id()?,
+            })),
+            Err(e) => Err(e),
+        }
+    }
+
     /// Registers and activates a configuration in one operation.
     ///
     /// Convenience method that combines `register_config` and `set_default_config_id`.
@@ -1875,11 +3016,20 @@ pub trait SzDiagnostic: Send + Sync {
     /// Removes all records and entities while preserving configuration.
     /// Use with caution - this operation is irreversible.
     ///
+    /// # Arguments
+    ///
+    /// * `confirmation` - Must be `PurgeConfirmation::I_UNDERSTAND_THIS_DELETES_ALL_DATA`
+    ///
     /// # Warning
     ///
     /// This permanently deletes all entity resolution data. Configuration
     /// and data source definitions are preserved.
     ///
+    /// # Errors
+    ///
+    /// * `SzError::BadInput` - Purge is disabled on the environment
+    ///   (`SzEnvironmentCore::set_purge_enabled(false)`)
+    ///
     /// # Examples
     ///
     /// ```
@@ -1888,10 +3038,34 @@ pub trait SzDiagnostic: Send + Sync {
     ///
     /// # let env = ExampleEnvironment::initialize("doctest_purge_repository")?;
     /// let diagnostic = env.get_diagnostic()?;
-    /// diagnostic.purge_repository()?;
+    /// diagnostic.purge_repository(PurgeConfirmation::I_UNDERSTAND_THIS_DELETES_ALL_DATA)?;
+    /// # Ok::<(), SzError>(())
+    /// ```
+    fn purge_repository(&self, confirmation: PurgeConfirmation) -> SzResult<()>;
+
+    /// Returns the timings of the most recent engine calls, oldest first.
+    ///
+    /// Each timing splits the call's wall time into time spent inside the
+    /// native library and time spent in the SDK, to tell which side latency
+    /// comes from. Calls are recorded only with the `profiling` feature;
+    /// without it the list is always empty. See [`crate::profiling`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use sz_rust_sdk::helpers::ExampleEnvironment;
+    /// use sz_rust_sdk::prelude::*;
+    ///
+    /// # let env = ExampleEnvironment::initialize("doctest_recent_call_timings")?;
+    /// let diagnostic = env.get_diagnostic()?;
+    /// for timing in diagnostic.recent_call_timings() {
+    ///     println!("{} spent {:?} in the SDK", timing.operation, timing.sdk());
+    /// }
     /// # Ok::<(), SzError>(())
     /// ```
-    fn purge_repository(&self) -> SzResult<()>;
+    fn recent_call_timings(&self) -> Vec<crate::profiling::CallTiming> {
+        crate::profiling::recent_call_timings()
+    }
 }
 
 /// Product version and license information.
@@ -1955,3 +3129,93 @@ pub trait SzProduct: Send + Sync {
     /// ```
     fn get_version(&self) -> SzResult<JsonString>;
 }
+
+/// Extracts the stored `JSON_DATA` of a `get_record` response.
+fn previous_record_json(record: &str) -> SzResult<Option<String>> {
+    let value: serde_json::Value = serde_json::from_str(record)?;
+    Ok(value.get("JSON_DATA").map(|data| data.to_string()))
+}
+
+/// Combines the WITH_INFO documents of two operations on the same record.
+///
+/// Keys from `second` take precedence; `AFFECTED_ENTITIES` is the union of
+/// both lists, in order of first appearance.
+fn merge_info(first: &str, second: &str) -> SzResult<JsonString> {
+    if first.is_empty() {
+        return Ok(second.to_string());
+    }
+    if second.is_empty() {
+        return Ok(first.to_string());
+    }
+
+    let first: serde_json::Value = serde_json::from_str(first)?;
+    let mut merged: serde_json::Value = serde_json::from_str(second)?;
+    let (Some(first), Some(merged_map)) = (first.as_object(), merged.as_object_mut()) else {
+        return Err(SzError::bad_input(
+            "WITH_INFO response is not a JSON object",
+        ));
+    };
+
+    for (key, value) in first {
+        if key != "AFFECTED_ENTITIES" {
+            merged_map
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+    }
+
+    let mut affected: Vec<serde_json::Value> = Vec::new();
+    let lists = [
+        first.get("AFFECTED_ENTITIES"),
+        merged_map.get("AFFECTED_ENTITIES"),
+    ];
+    for entity in lists
+        .into_iter()
+        .flatten()
+        .filter_map(|v| v.as_array())
+        .flatten()
+    {
+        if !affected.contains(entity) {
+            affected.push(entity.clone());
+        }
+    }
+    merged_map.insert(
+        "AFFECTED_ENTITIES".to_string(),
+        serde_json::Value::Array(affected),
+    );
+
+    Ok(merged.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_info_unions_affected_entities() {
+        let delete = r#"{"DATA_SOURCE":"TEST","RECORD_ID":"1","AFFECTED_ENTITIES":[{"ENTITY_ID":1},{"ENTITY_ID":2}]}"#;
+        let add = r#"{"DATA_SOURCE":"TEST","RECORD_ID":"1","AFFECTED_ENTITIES":[{"ENTITY_ID":2},{"ENTITY_ID":3}]}"#;
+        let merged: serde_json::Value =
+            serde_json::from_str(&merge_info(delete, add).unwrap()).unwrap();
+        assert_eq!(merged["RECORD_ID"], "1");
+        assert_eq!(
+            merged["AFFECTED_ENTITIES"],
+            serde_json::json!([{"ENTITY_ID":1},{"ENTITY_ID":2},{"ENTITY_ID":3}])
+        );
+    }
+
+    #[test]
+    fn test_merge_info_without_info() {
+        assert_eq!(merge_info(SZ_NO_INFO, SZ_NO_INFO).unwrap(), SZ_NO_INFO);
+    }
+
+    #[test]
+    fn test_previous_record_json() {
+        let record = r#"{"DATA_SOURCE":"TEST","RECORD_ID":"1","JSON_DATA":{"NAME_FULL":"A B"}}"#;
+        assert_eq!(
+            previous_record_json(record).unwrap().as_deref(),
+            Some(r#"{"NAME_FULL":"A B"}"#)
+        );
+        assert_eq!(previous_record_json("{}").unwrap(), None);
+    }
+}
@@ -1,10 +1,145 @@
 //! Common types and type aliases for the Senzing SDK
 
-/// Entity ID type
-pub type EntityId = i64;
+use serde::{Deserialize, Serialize};
+use std::fmt;
 
-/// Configuration ID type
-pub type ConfigId = i64;
+/// Identifier of a resolved entity.
+///
+/// A distinct type, so entity IDs cannot be passed where a config or
+/// feature ID is expected. Serializes as the bare integer the engine uses.
+/// The engine assigns positive IDs; where its documents use 0 for "no
+/// entity", typed models hold `Option<EntityId>` instead.
+///
+/// # Examples
+///
+/// ```
+/// use sz_rust_sdk::prelude::*;
+///
+/// let entity_id = EntityId(1001);
+/// assert_eq!(entity_id.to_string(), "1001");
+/// assert_eq!(i64::from(entity_id), 1001);
+/// assert_eq!(EntityId::from_engine(0), None);
+/// # let _ = EntityRef::from(entity_id);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct EntityId(pub i64);
+
+impl EntityId {
+    /// The raw ID.
+    pub const fn get(self) -> i64 {
+        self.0
+    }
+
+    /// Reads an ID from an engine document, where 0 or a negative value
+    /// means there is no entity.
+    pub const fn from_engine(id: i64) -> Option<Self> {
+        if id > 0 { Some(Self(id)) } else { None }
+    }
+}
+
+impl From<i64> for EntityId {
+    fn from(id: i64) -> Self {
+        Self(id)
+    }
+}
+
+impl From<EntityId> for i64 {
+    fn from(id: EntityId) -> Self {
+        id.0
+    }
+}
+
+impl PartialEq<i64> for EntityId {
+    fn eq(&self, other: &i64) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<EntityId> for i64 {
+    fn eq(&self, other: &EntityId) -> bool {
+        *self == other.0
+    }
+}
+
+impl fmt::Display for EntityId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl std::str::FromStr for EntityId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+/// Identifier of a registered configuration.
+///
+/// A distinct type, so config IDs cannot be passed where an entity ID is
+/// expected, e.g. to `reinitialize` or `set_default_config_id`. Serializes as
+/// the bare integer the engine uses.
+///
+/// # Examples
+///
+/// ```
+/// use sz_rust_sdk::prelude::*;
+///
+/// let config_id: ConfigId = serde_json::from_str("4019066234")?;
+/// assert_eq!(config_id, ConfigId(4019066234));
+/// assert_eq!(config_id.to_string(), "4019066234");
+/// # Ok::<(), serde_json::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ConfigId(pub i64);
+
+impl ConfigId {
+    /// The raw ID.
+    pub const fn get(self) -> i64 {
+        self.0
+    }
+}
+
+impl From<i64> for ConfigId {
+    fn from(id: i64) -> Self {
+        Self(id)
+    }
+}
+
+impl From<ConfigId> for i64 {
+    fn from(id: ConfigId) -> Self {
+        id.0
+    }
+}
+
+impl PartialEq<i64> for ConfigId {
+    fn eq(&self, other: &i64) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<ConfigId> for i64 {
+    fn eq(&self, other: &ConfigId) -> bool {
+        *self == other.0
+    }
+}
+
+impl fmt::Display for ConfigId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl std::str::FromStr for ConfigId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
 
 /// Feature ID type
 pub type FeatureId = i64;
@@ -12,12 +147,150 @@ pub type FeatureId = i64;
 /// Export handle type
 pub type ExportHandle = i64;
 
-/// Data source code type
-pub type DataSourceCode = String;
-
 /// Record ID type
 pub type RecordId = String;
 
+/// A data source code, such as `CUSTOMERS`.
+///
+/// The engine compares data source codes case-insensitively, so codes are
+/// trimmed and uppercased on construction, and an empty code is rejected.
+///
+/// # Examples
+///
+/// ```
+/// use sz_rust_sdk::prelude::*;
+///
+/// let code = DataSourceCode::new(" customers ")?;
+/// assert_eq!(code.as_str(), "CUSTOMERS");
+/// assert!(DataSourceCode::new("").is_err());
+/// # Ok::<(), SzError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct DataSourceCode(String);
+
+impl DataSourceCode {
+    /// Normalizes and validates a data source code.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the code is empty or only whitespace.
+    pub fn new(code: impl AsRef<str>) -> crate::error::SzResult<Self> {
+        let code = code.as_ref().trim();
+        if code.is_empty() {
+            return Err(crate::error::SzError::bad_input(
+                "Data source code must not be empty",
+            ));
+        }
+        Ok(Self(code.to_uppercase()))
+    }
+
+    /// The normalized code.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl AsRef<str> for DataSourceCode {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for DataSourceCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl TryFrom<&str> for DataSourceCode {
+    type Error = crate::error::SzError;
+
+    fn try_from(code: &str) -> crate::error::SzResult<Self> {
+        Self::new(code)
+    }
+}
+
+impl TryFrom<String> for DataSourceCode {
+    type Error = crate::error::SzError;
+
+    fn try_from(code: String) -> crate::error::SzResult<Self> {
+        Self::new(code)
+    }
+}
+
+impl From<DataSourceCode> for String {
+    fn from(code: DataSourceCode) -> Self {
+        code.0
+    }
+}
+
+/// The key of a record: its data source code and record ID.
+///
+/// Taking keys as one value, rather than as separate `&str` arguments or
+/// tuples, keeps data source codes and record IDs from being swapped.
+/// Serializes as `{"DATA_SOURCE": ..., "RECORD_ID": ...}`, the shape the
+/// engine uses in record lists.
+///
+/// # Examples
+///
+/// ```
+/// use sz_rust_sdk::prelude::*;
+///
+/// let key = RecordKey::new("customers", "1001")?;
+/// assert_eq!(key.data_source.as_str(), "CUSTOMERS");
+/// assert_eq!(key.to_string(), "CUSTOMERS:1001");
+/// assert_eq!(EntityRef::from(&key), EntityRef::from_record("CUSTOMERS", "1001"));
+/// assert!(RecordKey::new("CUSTOMERS", "").is_err());
+/// # Ok::<(), SzError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub struct RecordKey {
+    /// Data source of the record
+    pub data_source: DataSourceCode,
+    /// Record identifier within the data source
+    pub record_id: RecordId,
+}
+
+impl RecordKey {
+    /// Creates a key, normalizing the data source code.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the data source code or record ID is empty.
+    pub fn new(
+        data_source: impl AsRef<str>,
+        record_id: impl Into<RecordId>,
+    ) -> crate::error::SzResult<Self> {
+        let data_source = DataSourceCode::new(data_source)?;
+        let record_id = record_id.into();
+        if record_id.is_empty() {
+            return Err(crate::error::SzError::bad_input(format!(
+                "Record ID for data source {data_source} must not be empty"
+            )));
+        }
+        Ok(Self {
+            data_source,
+            record_id,
+        })
+    }
+}
+
+impl fmt::Display for RecordKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.data_source, self.record_id)
+    }
+}
+
+impl TryFrom<(&str, &str)> for RecordKey {
+    type Error = crate::error::SzError;
+
+    fn try_from((data_source, record_id): (&str, &str)) -> crate::error::SzResult<Self> {
+        Self::new(data_source, record_id)
+    }
+}
+
 /// JSON string type for Senzing data exchange
 pub type JsonString = String;
 
@@ -38,7 +311,7 @@ pub const SZ_NO_INFO: &str = "";
 /// use sz_rust_sdk::prelude::*;
 ///
 /// // Reference by entity ID (automatic conversion)
-/// let entity_id: EntityId = 1001;
+/// let entity_id = EntityId(1001);
 /// let ref1: EntityRef = entity_id.into();
 ///
 /// // Reference by record key
@@ -66,6 +339,15 @@ impl From<EntityId> for EntityRef<'_> {
     }
 }
 
+impl<'a> From<&'a RecordKey> for EntityRef<'a> {
+    fn from(key: &'a RecordKey) -> Self {
+        EntityRef::Record {
+            data_source: key.data_source.as_str(),
+            record_id: &key.record_id,
+        }
+    }
+}
+
 impl<'a> EntityRef<'a> {
     /// Create an EntityRef from a record key
     pub fn from_record(data_source: &'a str, record_id: &'a str) -> Self {
@@ -75,3 +357,1183 @@ impl<'a> EntityRef<'a> {
         }
     }
 }
+
+/// Seeds a network search expands from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkSeeds {
+    /// Seed entities by entity ID
+    Entities(Vec<EntityId>),
+    /// Seed entities by the key of one of their records
+    Records(Vec<RecordKey>),
+}
+
+/// Validated arguments of a network search, run with
+/// `SzEngine::find_network_req`.
+///
+/// `find_network_by_entity_id` and `find_network_by_record_id` take three
+/// positional integers that are easy to swap. A request names them and
+/// checks their ranges once, when it is built. Unset limits default to
+/// [`DEFAULT_MAX_DEGREES`](Self::DEFAULT_MAX_DEGREES),
+/// [`DEFAULT_BUILD_OUT_DEGREES`](Self::DEFAULT_BUILD_OUT_DEGREES) and
+/// [`DEFAULT_MAX_ENTITIES`](Self::DEFAULT_MAX_ENTITIES).
+///
+/// # Examples
+///
+/// ```
+/// use sz_rust_sdk::prelude::*;
+///
+/// let request = FindNetworkRequest::builder()
+///     .entities([EntityId(1), EntityId(2)])
+///     .max_degrees(2)
+///     .max_entities(50)
+///     .build()?;
+/// assert_eq!(request.build_out_degrees(), FindNetworkRequest::DEFAULT_BUILD_OUT_DEGREES);
+///
+/// assert!(FindNetworkRequest::builder().build().is_err());
+/// assert!(FindNetworkRequest::builder().entities([EntityId(1)]).max_entities(0).build().is_err());
+/// # Ok::<(), SzError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FindNetworkRequest {
+    seeds: NetworkSeeds,
+    max_degrees: i64,
+    build_out_degrees: i64,
+    max_entities: i64,
+    flags: Option<crate::flags::SzFlags>,
+}
+
+impl FindNetworkRequest {
+    /// Relationship hops from the seeds when not set.
+    pub const DEFAULT_MAX_DEGREES: i64 = 3;
+    /// Build-out degrees when not set.
+    pub const DEFAULT_BUILD_OUT_DEGREES: i64 = 1;
+    /// Entity limit when not set.
+    pub const DEFAULT_MAX_ENTITIES: i64 = 100;
+
+    /// Starts a request with default limits and no seeds.
+    pub fn builder() -> FindNetworkRequestBuilder {
+        FindNetworkRequestBuilder::default()
+    }
+
+    /// The seeds the network expands from.
+    pub fn seeds(&self) -> &NetworkSeeds {
+        &self.seeds
+    }
+
+    /// Maximum relationship hops between seed entities.
+    pub fn max_degrees(&self) -> i64 {
+        self.max_degrees
+    }
+
+    /// Degrees to expand around the seed entities.
+    pub fn build_out_degrees(&self) -> i64 {
+        self.build_out_degrees
+    }
+
+    /// Maximum entities returned by the build-out.
+    pub fn max_entities(&self) -> i64 {
+        self.max_entities
+    }
+
+    /// Flags controlling result detail.
+    pub fn flags(&self) -> Option<crate::flags::SzFlags> {
+        self.flags
+    }
+}
+
+/// Builder returned by [`FindNetworkRequest::builder`].
+#[derive(Debug, Clone, Default)]
+pub struct FindNetworkRequestBuilder {
+    entities: Vec<EntityId>,
+    records: Vec<RecordKey>,
+    max_degrees: Option<i64>,
+    build_out_degrees: Option<i64>,
+    max_entities: Option<i64>,
+    flags: Option<crate::flags::SzFlags>,
+}
+
+impl FindNetworkRequestBuilder {
+    /// Adds seed entities by entity ID.
+    pub fn entities(mut self, entity_ids: impl IntoIterator<Item = EntityId>) -> Self {
+        self.entities.extend(entity_ids);
+        self
+    }
+
+    /// Adds seed entities by record key.
+    pub fn records(mut self, record_keys: impl IntoIterator<Item = RecordKey>) -> Self {
+        self.records.extend(record_keys);
+        self
+    }
+
+    /// Sets the maximum relationship hops between seed entities; at least 1.
+    pub fn max_degrees(mut self, max_degrees: i64) -> Self {
+        self.max_degrees = Some(max_degrees);
+        self
+    }
+
+    /// Sets the degrees to expand around the seed entities; 0 or more.
+    pub fn build_out_degrees(mut self, build_out_degrees: i64) -> Self {
+        self.build_out_degrees = Some(build_out_degrees);
+        self
+    }
+
+    /// Sets the maximum entities returned by the build-out; at least 1.
+    pub fn max_entities(mut self, max_entities: i64) -> Self {
+        self.max_entities = Some(max_entities);
+        self
+    }
+
+    /// Sets the flags controlling result detail.
+    pub fn flags(mut self, flags: crate::flags::SzFlags) -> Self {
+        self.flags = Some(flags);
+        self
+    }
+
+    /// Validates the arguments and builds the request.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if there are no seeds, seeds are given both as
+    /// entity IDs and as record keys, or a limit is out of range.
+    pub fn build(self) -> crate::error::SzResult<FindNetworkRequest> {
+        use crate::error::SzError;
+
+        let seeds = match (self.entities.is_empty(), self.records.is_empty()) {
+            (true, true) => {
+                return Err(SzError::bad_input(
+                    "Network search needs at least one seed entity or record",
+                ));
+            }
+            (false, false) => {
+                return Err(SzError::bad_input(
+                    "Network seeds must be either entity IDs or record keys, not both",
+                ));
+            }
+            (false, true) => NetworkSeeds::Entities(self.entities),
+            (true, false) => NetworkSeeds::Records(self.records),
+        };
+        let max_degrees = self
+            .max_degrees
+            .unwrap_or(FindNetworkRequest::DEFAULT_MAX_DEGREES);
+        let build_out_degrees = self
+            .build_out_degrees
+            .unwrap_or(FindNetworkRequest::DEFAULT_BUILD_OUT_DEGREES);
+        let max_entities = self
+            .max_entities
+            .unwrap_or(FindNetworkRequest::DEFAULT_MAX_ENTITIES);
+        if max_degrees < 1 {
+            return Err(SzError::bad_input(format!(
+                "max_degrees must be at least 1, got {max_degrees}"
+            )));
+        }
+        if build_out_degrees < 0 {
+            return Err(SzError::bad_input(format!(
+                "build_out_degrees must not be negative, got {build_out_degrees}"
+            )));
+        }
+        if max_entities < 1 {
+            return Err(SzError::bad_input(format!(
+                "max_entities must be at least 1, got {max_entities}"
+            )));
+        }
+        Ok(FindNetworkRequest {
+            seeds,
+            max_degrees,
+            build_out_degrees,
+            max_entities,
+            flags: self.flags,
+        })
+    }
+}
+
+/// What the engine does when a response exceeds the configured size limit.
+///
+/// See [`ResultSizeLimit`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OversizeAction {
+    /// Return [`SzError::ResultTooLarge`](crate::error::SzError::ResultTooLarge)
+    /// with a suggested reduced flag set.
+    #[default]
+    Error,
+    /// Transparently repeat the call with a reduced flag set, and only fail if
+    /// the reduced response is still too large.
+    DowngradeFlags,
+}
+
+/// Caps the size of `get_entity` and `find_network_*` responses.
+///
+/// Very large entities (hundreds of thousands of records) can produce
+/// multi-hundred-megabyte JSON documents. A limit protects services from
+/// copying such responses into memory. The native response is measured before
+/// it is copied into a Rust `String`.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use sz_rust_sdk::helpers::ExampleEnvironment;
+/// use sz_rust_sdk::prelude::*;
+///
+/// # let env = ExampleEnvironment::initialize("doctest_result_size_limit")?;
+/// env.set_result_size_limit(Some(ResultSizeLimit {
+///     max_bytes: 16 * 1024 * 1024,
+///     action: OversizeAction::DowngradeFlags,
+/// }));
+/// # Ok::<(), SzError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResultSizeLimit {
+    /// Largest response, in bytes, returned without intervention
+    pub max_bytes: usize,
+    /// Behavior when a response exceeds `max_bytes`
+    pub action: OversizeAction,
+}
+
+/// How the core engine formats the JSON responses it returns.
+///
+/// The native library's key order and whitespace are not guaranteed, so
+/// responses stored as snapshot files or compared in tests can differ without
+/// any change in content. `Compact` and `Pretty` both sort object keys, so
+/// equal documents always produce identical strings.
+///
+/// Formatting applies to the `JsonString` responses of the `SzEngine`
+/// methods. `get_redo_record`, `fetch_next` and the `*_bytes` methods return
+/// the native response unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use sz_rust_sdk::prelude::*;
+///
+/// let json = r#"{"B": 1, "A": {"D": [true], "C": null}}"#;
+/// assert_eq!(
+///     ResponseFormat::Compact.apply(json.to_string())?,
+///     r#"{"A":{"C":null,"D":[true]},"B":1}"#
+/// );
+/// # Ok::<(), SzError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResponseFormat {
+    /// Return responses exactly as the native library produced them
+    #[default]
+    Native,
+    /// Sorted keys, no whitespace
+    Compact,
+    /// Sorted keys, indented with two spaces
+    Pretty,
+}
+
+impl ResponseFormat {
+    /// Formats `json`. Empty strings, such as the `SZ_NO_INFO` response of
+    /// mutations called without `WITH_INFO`, are returned unchanged.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::Json` if `json` is not valid JSON and the format is not
+    /// `Native`.
+    pub fn apply(self, json: JsonString) -> crate::error::SzResult<JsonString> {
+        if self == Self::Native || json.is_empty() {
+            return Ok(json);
+        }
+        let document = sorted(serde_json::from_str(&json)?);
+        Ok(match self {
+            Self::Pretty => serde_json::to_string_pretty(&document)?,
+            _ => document.to_string(),
+        })
+    }
+}
+
+/// Rebuilds `value` with every object's keys inserted in sorted order, which
+/// holds whether or not `serde_json` keeps insertion order.
+fn sorted(value: serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.into_iter().collect();
+            entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+            serde_json::Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, sorted(value)))
+                    .collect(),
+            )
+        }
+        serde_json::Value::Array(values) => {
+            serde_json::Value::Array(values.into_iter().map(sorted).collect())
+        }
+        other => other,
+    }
+}
+
+/// Sizing for the argument buffer pool shared by an environment's engines.
+///
+/// Engines convert record definitions and search attributes into C strings
+/// on every call. The pool keeps those buffers for reuse instead of
+/// allocating and freeing one per call, which reduces allocator churn during
+/// bulk loads. Buffers larger than `max_buffer_bytes` are not kept, so a few
+/// unusually large records do not pin memory.
+///
+/// Setting `max_buffers` to 0 disables pooling.
+///
+/// # Examples
+///
+/// ```no_run
+/// # use sz_rust_sdk::helpers::ExampleEnvironment;
+/// use sz_rust_sdk::prelude::*;
+///
+/// # let env = ExampleEnvironment::initialize("doctest_buffer_pool_config")?;
+/// env.set_buffer_pool_config(BufferPoolConfig {
+///     max_buffers: 64,
+///     max_buffer_bytes: 64 * 1024,
+/// });
+/// # Ok::<(), SzError>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BufferPoolConfig {
+    /// Maximum number of idle buffers kept for reuse
+    pub max_buffers: usize,
+    /// Largest buffer capacity, in bytes, that is returned to the pool
+    pub max_buffer_bytes: usize,
+}
+
+impl Default for BufferPoolConfig {
+    fn default() -> Self {
+        Self {
+            max_buffers: 32,
+            max_buffer_bytes: 256 * 1024,
+        }
+    }
+}
+
+/// Counters describing how well the argument buffer pool is being reused.
+///
+/// Returned by `SzEnvironmentCore::buffer_pool_stats`. Counters are
+/// cumulative since the environment was created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BufferPoolStats {
+    /// Buffers served from the pool
+    pub hits: u64,
+    /// Buffers newly allocated because the pool was empty
+    pub misses: u64,
+    /// Buffers dropped instead of pooled (pool full or buffer too large)
+    pub discarded: u64,
+    /// Idle buffers currently held by the pool
+    pub pooled_buffers: usize,
+    /// Total capacity, in bytes, of the idle buffers
+    pub pooled_bytes: usize,
+}
+
+/// Counters for response buffers allocated by the native library.
+///
+/// Every native response is freed exactly once, either right after it is
+/// copied into a `String` or when its [`SzBytes`](crate::SzBytes) is dropped.
+/// Returned by `SzEnvironmentCore::native_buffer_stats`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NativeBufferStats {
+    /// Native response buffers received
+    pub taken: u64,
+    /// Native response buffers freed
+    pub freed: u64,
+}
+
+impl NativeBufferStats {
+    /// Buffers received but not freed yet, i.e. `SzBytes` still alive.
+    pub fn outstanding(&self) -> u64 {
+        self.taken.saturating_sub(self.freed)
+    }
+}
+
+/// Counters for native handles, kept in debug builds only.
+///
+/// Export handles are counted from the export call to `close_export_report`
+/// (or to the SDK closing them on reinitialize or destroy), config handles
+/// for the lifetime of each `SzConfig`. Returned by
+/// `SzEnvironmentCore::native_handle_stats`.
+#[cfg(debug_assertions)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NativeHandleStats {
+    /// Export handles opened
+    pub exports_opened: u64,
+    /// Export handles closed
+    pub exports_closed: u64,
+    /// Config handles opened
+    pub configs_opened: u64,
+    /// Config handles closed
+    pub configs_closed: u64,
+}
+
+#[cfg(debug_assertions)]
+impl NativeHandleStats {
+    /// Export handles opened but not closed yet.
+    pub fn open_exports(&self) -> u64 {
+        self.exports_opened.saturating_sub(self.exports_closed)
+    }
+
+    /// Config handles opened but not closed yet.
+    pub fn open_configs(&self) -> u64 {
+        self.configs_opened.saturating_sub(self.configs_closed)
+    }
+}
+
+/// Outcome of `SzEngine::add_record_if_changed`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UpsertOutcome {
+    /// The record did not exist and was added; holds the add response
+    Added(JsonString),
+    /// The record's content changed and it was added again; holds the add
+    /// response
+    Updated(JsonString),
+    /// The stored record has the same content; nothing was sent to the engine
+    Unchanged,
+}
+
+impl UpsertOutcome {
+    /// Whether the record was sent to the engine.
+    pub fn is_changed(&self) -> bool {
+        !matches!(self, Self::Unchanged)
+    }
+
+    /// The add response, if the record was sent to the engine.
+    pub fn info(&self) -> Option<&str> {
+        match self {
+            Self::Added(info) | Self::Updated(info) => Some(info),
+            Self::Unchanged => None,
+        }
+    }
+}
+
+/// One feature of a [`MatchKey`], such as `NAME` or `REL_POINTER(EMPLOYER:)`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MatchKeyFeature {
+    /// Feature type code, e.g. `NAME`
+    pub code: String,
+    /// Text in parentheses after the code, e.g. the role of a disclosed
+    /// relationship
+    pub detail: Option<String>,
+}
+
+impl fmt::Display for MatchKeyFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.detail {
+            Some(detail) => write!(f, "{}({detail})", self.code),
+            None => f.write_str(&self.code),
+        }
+    }
+}
+
+/// A parsed match key, such as `+NAME+DOB-EMAIL`.
+///
+/// Match keys list the features that agreed (`+`) and disagreed (`-`) when
+/// two records or entities were compared. A leading feature without a sign
+/// counts as agreeing. [`describe`](Self::describe) turns the key into text
+/// for people, with feature names from the configuration (see
+/// `config_tools::feature_type_names`).
+///
+/// # Examples
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use sz_rust_sdk::prelude::*;
+///
+/// let key = MatchKey::parse("NAME+DOB-EMAIL")?;
+/// assert_eq!(key.plus_codes().collect::<Vec<_>>(), ["NAME", "DOB"]);
+/// assert_eq!(key.minus_codes().collect::<Vec<_>>(), ["EMAIL"]);
+/// assert_eq!(key.to_string(), "+NAME+DOB-EMAIL");
+///
+/// let names = BTreeMap::from([
+///     ("NAME".to_string(), "Name".to_string()),
+///     ("DOB".to_string(), "Date of birth".to_string()),
+///     ("EMAIL".to_string(), "Email".to_string()),
+/// ]);
+/// assert_eq!(key.describe(&names), "Name, Date of birth match; Email differs");
+///
+/// let disclosed = MatchKey::parse("+REL_POINTER(EMPLOYER:)")?;
+/// assert_eq!(disclosed.plus[0].detail.as_deref(), Some("EMPLOYER:"));
+/// # Ok::<(), SzError>(())
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct MatchKey {
+    /// Features that agreed, in key order
+    pub plus: Vec<MatchKeyFeature>,
+    /// Features that disagreed, in key order
+    pub minus: Vec<MatchKeyFeature>,
+}
+
+impl MatchKey {
+    /// Parses a match key.
+    ///
+    /// # Errors
+    ///
+    /// `SzError::BadInput` if the key is empty, has a sign without a
+    /// feature, or has unbalanced parentheses.
+    pub fn parse(match_key: &str) -> crate::error::SzResult<Self> {
+        let invalid = |reason: &str| {
+            crate::error::SzError::bad_input(format!("Invalid match key '{match_key}': {reason}"))
+        };
+        let text = match_key.trim();
+        if text.is_empty() {
+            return Err(invalid("it is empty"));
+        }
+
+        let mut key = Self::default();
+        let mut agreed = true;
+        let mut current = String::new();
+        let mut depth = 0usize;
+        let mut finish = |agreed: bool, current: &mut String| -> crate::error::SzResult<()> {
+            let feature = std::mem::take(current);
+            let (code, detail) = match feature.split_once('(') {
+                Some((code, rest)) => match rest.strip_suffix(')') {
+                    Some(detail) => (code, Some(detail.to_string())),
+                    None => return Err(invalid("text after a closing parenthesis")),
+                },
+                None => (feature.as_str(), None),
+            };
+            let code = code.trim();
+            if code.is_empty() {
+                return Err(invalid("a sign is not followed by a feature"));
+            }
+            let feature = MatchKeyFeature {
+                code: code.to_string(),
+                detail,
+            };
+            if agreed {
+                key.plus.push(feature);
+            } else {
+                key.minus.push(feature);
+            }
+            Ok(())
+        };
+
+        for (index, c) in text.char_indices() {
+            match c {
+                '(' => depth += 1,
+                ')' => {
+                    depth = depth
+                        .checked_sub(1)
+                        .ok_or_else(|| invalid("unbalanced parentheses"))?;
+                }
+                '+' | '-' if depth == 0 => {
+                    if index > 0 {
+                        finish(agreed, &mut current)?;
+                    }
+                    agreed = c == '+';
+                    continue;
+                }
+                _ => {}
+            }
+            current.push(c);
+        }
+        if depth != 0 {
+            return Err(invalid("unbalanced parentheses"));
+        }
+        finish(agreed, &mut current)?;
+        Ok(key)
+    }
+
+    /// Codes of the features that agreed.
+    pub fn plus_codes(&self) -> impl Iterator<Item = &str> {
+        self.plus.iter().map(|feature| feature.code.as_str())
+    }
+
+    /// Codes of the features that disagreed.
+    pub fn minus_codes(&self) -> impl Iterator<Item = &str> {
+        self.minus.iter().map(|feature| feature.code.as_str())
+    }
+
+    /// Describes the key in words, e.g.
+    /// `Name, Date of birth match; Email differs`.
+    ///
+    /// `names` maps feature type codes to the names to show; codes missing
+    /// from it are shown as they are. Details follow the name in
+    /// parentheses.
+    pub fn describe(&self, names: &std::collections::BTreeMap<String, String>) -> String {
+        let list = |features: &[MatchKeyFeature]| {
+            features
+                .iter()
+                .map(|feature| {
+                    let name = names.get(&feature.code).unwrap_or(&feature.code);
+                    match &feature.detail {
+                        Some(detail) => format!("{name} ({detail})"),
+                        None => name.clone(),
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let mut parts = Vec::new();
+        if !self.plus.is_empty() {
+            let verb = if self.plus.len() == 1 {
+                "matches"
+            } else {
+                "match"
+            };
+            parts.push(format!("{} {verb}", list(&self.plus)));
+        }
+        if !self.minus.is_empty() {
+            let verb = if self.minus.len() == 1 {
+                "differs"
+            } else {
+                "differ"
+            };
+            parts.push(format!("{} {verb}", list(&self.minus)));
+        }
+        parts.join("; ")
+    }
+}
+
+impl fmt::Display for MatchKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for feature in &self.plus {
+            write!(f, "+{feature}")?;
+        }
+        for feature in &self.minus {
+            write!(f, "-{feature}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for MatchKey {
+    type Err = crate::error::SzError;
+
+    fn from_str(match_key: &str) -> crate::error::SzResult<Self> {
+        Self::parse(match_key)
+    }
+}
+
+/// One record mutation of a batch applied with `SzEngine::apply_batch`.
+#[derive(Clone, PartialEq, Eq)]
+pub enum RecordOp {
+    /// Add or replace a record
+    Add {
+        /// Data source code
+        data_source: String,
+        /// Record identifier
+        record_id: String,
+        /// Record JSON
+        record_definition: String,
+    },
+    /// Delete a record
+    Delete {
+        /// Data source code
+        data_source: String,
+        /// Record identifier
+        record_id: String,
+    },
+}
+
+impl RecordOp {
+    /// An add of `record_definition` as `data_source` / `record_id`.
+    pub fn add(
+        data_source: impl Into<String>,
+        record_id: impl Into<String>,
+        record_definition: impl Into<String>,
+    ) -> Self {
+        Self::Add {
+            data_source: data_source.into(),
+            record_id: record_id.into(),
+            record_definition: record_definition.into(),
+        }
+    }
+
+    /// A delete of `data_source` / `record_id`.
+    pub fn delete(data_source: impl Into<String>, record_id: impl Into<String>) -> Self {
+        Self::Delete {
+            data_source: data_source.into(),
+            record_id: record_id.into(),
+        }
+    }
+
+    /// The data source and record identifier the operation applies to.
+    pub fn key(&self) -> (&str, &str) {
+        match self {
+            Self::Add {
+                data_source,
+                record_id,
+                ..
+            }
+            | Self::Delete {
+                data_source,
+                record_id,
+            } => (data_source, record_id),
+        }
+    }
+}
+
+impl fmt::Debug for RecordOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Add {
+                data_source,
+                record_id,
+                record_definition,
+            } => f
+                .debug_struct("Add")
+                .field("data_source", data_source)
+                .field("record_id", record_id)
+                .field(
+                    "record_definition",
+                    &crate::redact::Sensitive(record_definition),
+                )
+                .finish(),
+            Self::Delete {
+                data_source,
+                record_id,
+            } => f
+                .debug_struct("Delete")
+                .field("data_source", data_source)
+                .field("record_id", record_id)
+                .finish(),
+        }
+    }
+}
+
+/// What `SzEngine::apply_batch` does when an operation fails.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum BatchFailureAction {
+    /// Stop, leaving the operations applied so far in place
+    #[default]
+    Stop,
+    /// Stop and reverse the operations applied so far, newest first:
+    /// records added are deleted, or restored to their earlier content if
+    /// they existed, and deleted records are added again from copies taken
+    /// before the delete
+    Compensate,
+}
+
+/// An operation of a batch that failed.
+#[derive(Debug)]
+pub struct BatchFailure {
+    /// Position of the operation in the batch
+    pub index: usize,
+    /// Why it failed
+    pub error: crate::error::SzError,
+}
+
+/// Outcome of `SzEngine::apply_batch`.
+#[derive(Debug, Default)]
+pub struct BatchReport {
+    /// Operations applied, counted from the start of the batch
+    pub applied: usize,
+    /// The operation that stopped the batch, if any
+    pub failure: Option<BatchFailure>,
+    /// Applied operations that were reversed
+    pub compensated: usize,
+    /// Reversals that failed; those operations remain applied
+    pub compensation_failures: Vec<BatchFailure>,
+}
+
+impl BatchReport {
+    /// Whether every operation was applied.
+    pub fn is_complete(&self) -> bool {
+        self.failure.is_none()
+    }
+
+    /// Whether the batch failed and every applied operation was reversed,
+    /// leaving the records as they were before the batch.
+    pub fn is_rolled_back(&self) -> bool {
+        self.failure.is_some()
+            && self.compensated == self.applied
+            && self.compensation_failures.is_empty()
+    }
+}
+
+/// Engine operations that can be given a timeout.
+///
+/// See `SzEnvironmentCore::set_operation_timeout`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TimedOperation {
+    /// `get_entity` and `get_entity_bytes`
+    GetEntity,
+    /// `search_by_attributes` and `search_by_attributes_bytes`
+    SearchByAttributes,
+    /// `find_path_by_entity_id` and `find_path_by_record_id`
+    FindPath,
+    /// `find_network_by_entity_id` and `find_network_by_record_id`
+    FindNetwork,
+    /// `why_entities`
+    WhyEntities,
+    /// `how_entity`
+    HowEntity,
+}
+
+/// Capability level of an engine from `SzEnvironment::get_engine_scoped`.
+///
+/// Scopes are ordered: each one allows everything the previous one does.
+/// Calling a method outside the engine's scope fails with
+/// `SzError::NotPermitted` without reaching the native library.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EngineScope {
+    /// Searches, entity and record retrieval, why/how/path/network analysis,
+    /// record previews, exports, statistics, and redo counts
+    ReadOnly,
+    /// `ReadOnly` plus adding, replacing, deleting, and reevaluating records
+    /// and processing redo records
+    Loader,
+    /// Every engine method, including `prime_engine`
+    Admin,
+}
+
+/// Explicit acknowledgement required by `SzDiagnostic::purge_repository`.
+///
+/// The only value is [`PurgeConfirmation::I_UNDERSTAND_THIS_DELETES_ALL_DATA`],
+/// so every purge call site spells out what it does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PurgeConfirmation(());
+
+impl PurgeConfirmation {
+    /// Confirms that all entity resolution data in the repository is deleted.
+    pub const I_UNDERSTAND_THIS_DELETES_ALL_DATA: Self = Self(());
+}
+
+/// How long `SzEngine::count_entities`, `count_records` and
+/// `count_relationships` reuse a previous count before counting again.
+pub const COUNT_CACHE_MAX_AGE: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Entity, record and relationship totals of the repository.
+///
+/// Returned by `SzEngine::repository_counts`. Counting walks a full entity
+/// export, so the core engine caches the result for the environment;
+/// `counted_at` tells how current it is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RepositoryCounts {
+    /// Resolved entities
+    pub entities: i64,
+    /// Records across all entities
+    pub records: i64,
+    /// Relationships between entities, each counted once
+    pub relationships: i64,
+    /// When the counts were taken
+    pub counted_at: std::time::SystemTime,
+}
+
+impl RepositoryCounts {
+    /// Time since the counts were taken.
+    pub fn age(&self) -> std::time::Duration {
+        self.counted_at.elapsed().unwrap_or_default()
+    }
+}
+
+/// A call that exceeded its timeout, as recorded by the environment.
+///
+/// Returned by `SzEnvironmentCore::timed_out_calls`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimeoutRecord {
+    /// Name of the engine method that timed out
+    pub operation: &'static str,
+    /// Timeout that was exceeded
+    pub timeout: std::time::Duration,
+    /// When the timeout fired
+    pub occurred_at: std::time::SystemTime,
+}
+
+/// A failed engine call, as recorded by the environment.
+///
+/// Returned by `SzEnvironmentCore::recent_errors` and
+/// `SzEnvironmentCore::query_errors`. The message is kept as the engine
+/// returned it and may contain record values; see `redact::message` before
+/// sharing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ErrorRecord {
+    /// Name of the engine method that failed
+    pub operation: &'static str,
+    /// Error category, as in `SzError::category`
+    pub category: &'static str,
+    /// Native error code, if the error came from the native library
+    pub code: Option<i64>,
+    /// Native component that reported the error, if any
+    pub component: Option<crate::error::SzComponent>,
+    /// Error message without the error type prefix
+    pub message: String,
+    /// When the call failed
+    pub occurred_at: std::time::SystemTime,
+}
+
+/// Conditions an [`ErrorRecord`] must meet to be returned by
+/// `SzEnvironmentCore::query_errors`.
+///
+/// An empty filter matches every record.
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, SystemTime};
+/// use sz_rust_sdk::error::SzComponent;
+/// use sz_rust_sdk::types::{ErrorFilter, ErrorRecord};
+///
+/// let filter = ErrorFilter::new()
+///     .category("database_transient")
+///     .since(SystemTime::now() - Duration::from_secs(300));
+/// let record = ErrorRecord {
+///     operation: "add_record",
+///     category: "database_transient",
+///     code: Some(1007),
+///     component: Some(SzComponent::Engine),
+///     message: "database deadlock".to_string(),
+///     occurred_at: SystemTime::now(),
+/// };
+/// assert!(filter.matches(&record));
+/// assert!(!filter.operation("search_by_attributes").matches(&record));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorFilter {
+    operation: Option<String>,
+    category: Option<String>,
+    code: Option<i64>,
+    component: Option<crate::error::SzComponent>,
+    since: Option<std::time::SystemTime>,
+}
+
+impl ErrorFilter {
+    /// Creates a filter that matches every record.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Matches failures of the engine method `operation`, e.g. `"add_record"`.
+    pub fn operation(mut self, operation: impl Into<String>) -> Self {
+        self.operation = Some(operation.into());
+        self
+    }
+
+    /// Matches errors of `category`, as in `SzError::category`.
+    pub fn category(mut self, category: impl Into<String>) -> Self {
+        self.category = Some(category.into());
+        self
+    }
+
+    /// Matches errors with the native error code `code`.
+    pub fn code(mut self, code: i64) -> Self {
+        self.code = Some(code);
+        self
+    }
+
+    /// Matches errors reported by `component`.
+    pub fn component(mut self, component: crate::error::SzComponent) -> Self {
+        self.component = Some(component);
+        self
+    }
+
+    /// Matches errors that occurred at or after `time`.
+    pub fn since(mut self, time: std::time::SystemTime) -> Self {
+        self.since = Some(time);
+        self
+    }
+
+    /// Returns whether `record` meets every condition.
+    pub fn matches(&self, record: &ErrorRecord) -> bool {
+        self.operation
+            .as_deref()
+            .is_none_or(|operation| operation == record.operation)
+            && self
+                .category
+                .as_deref()
+                .is_none_or(|category| category == record.category)
+            && self.code.is_none_or(|code| record.code == Some(code))
+            && self
+                .component
+                .is_none_or(|component| record.component == Some(component))
+            && self.since.is_none_or(|since| record.occurred_at >= since)
+    }
+}
+
+/// An export that has been started and not yet closed.
+///
+/// Returned by `SzEnvironmentCore::open_exports`. Open exports are closed by
+/// the SDK when the environment is reinitialized or destroyed; see
+/// `SzError::ExportInvalidated`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportSession {
+    /// Handle returned by the export call
+    pub handle: ExportHandle,
+    /// Name of the engine method that started the export
+    pub operation: &'static str,
+    /// When the export was started
+    pub started_at: std::time::SystemTime,
+    /// Number of non-empty `fetch_next` results so far
+    pub rows_fetched: u64,
+}
+
+/// Outcome of initializing one native component, part of an [`InitReport`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InitComponent {
+    /// Native component: `"engine"`, `"config_manager"` or `"product"`
+    pub name: &'static str,
+    /// Whether the component was initialized before the report was taken,
+    /// in which case `elapsed_ms` is only the time to confirm it
+    pub already_initialized: bool,
+    /// Time the initialization took, in milliseconds
+    pub elapsed_ms: u64,
+    /// Why initialization failed, if it did
+    pub error: Option<String>,
+}
+
+impl InitComponent {
+    /// Whether the component is initialized.
+    pub fn is_initialized(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// What initializing an environment did, for startup logs and diagnostics
+/// bundles.
+///
+/// Returned by `SzEnvironmentCore::get_instance_with_report`,
+/// `SzEnvironmentCore::new_with_report` and
+/// `SzEnvironmentCore::init_report`. Serializes to JSON; `Display` gives a
+/// one-line summary.
+///
+/// # Example
+///
+/// ```
+/// use sz_rust_sdk::types::{ConfigId, InitComponent, InitReport};
+///
+/// let report = InitReport {
+///     module_name: "loader".to_string(),
+///     components: vec![InitComponent {
+///         name: "engine",
+///         already_initialized: false,
+///         elapsed_ms: 812,
+///         error: None,
+///     }],
+///     active_config_id: Some(ConfigId(4_019_066_234)),
+///     database_connected: true,
+///     database_error: None,
+///     elapsed_ms: 830,
+///     warnings: vec!["verbose logging is on".to_string()],
+/// };
+/// assert!(report.is_ok());
+/// assert_eq!(
+///     report.to_string(),
+///     "loader initialized engine in 830 ms, config 4019066234, database connected, 1 warning"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InitReport {
+    /// Module name the environment was created with
+    pub module_name: String,
+    /// Native components, in the order they were initialized
+    pub components: Vec<InitComponent>,
+    /// Configuration the engine is running, if the engine initialized
+    pub active_config_id: Option<ConfigId>,
+    /// Whether the repository answered a `get_repository_info` call
+    pub database_connected: bool,
+    /// Why the repository could not be reached, if it could not
+    pub database_error: Option<String>,
+    /// Time the whole report took, in milliseconds
+    pub elapsed_ms: u64,
+    /// Settings worth a look before going to production, such as verbose
+    /// logging or an active configuration that is not the default
+    pub warnings: Vec<String>,
+}
+
+impl InitReport {
+    /// Whether every component initialized and the repository is reachable.
+    pub fn is_ok(&self) -> bool {
+        self.database_connected && self.components.iter().all(InitComponent::is_initialized)
+    }
+}
+
+impl std::fmt::Display for InitReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (initialized, failed): (Vec<_>, Vec<_>) = self
+            .components
+            .iter()
+            .partition(|component| component.is_initialized());
+        let names = |components: &[&InitComponent]| {
+            components
+                .iter()
+                .map(|component| component.name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        write!(
+            f,
+            "{} initialized {} in {} ms",
+            self.module_name,
+            names(&initialized),
+            self.elapsed_ms
+        )?;
+        if !failed.is_empty() {
+            write!(f, ", failed {}", names(&failed))?;
+        }
+        if let Some(config_id) = self.active_config_id {
+            write!(f, ", config {config_id}")?;
+        }
+        let database = if self.database_connected {
+            "connected"
+        } else {
+            "unreachable"
+        };
+        write!(f, ", database {database}")?;
+        match self.warnings.len() {
+            0 => Ok(()),
+            1 => write!(f, ", 1 warning"),
+            count => write!(f, ", {count} warnings"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn names() -> BTreeMap<String, String> {
+        BTreeMap::from([
+            ("NAME".to_string(), "Name".to_string()),
+            ("DOB".to_string(), "Date of birth".to_string()),
+            ("REL_POINTER".to_string(), "Relationship".to_string()),
+        ])
+    }
+
+    #[test]
+    fn test_match_key_rejects_empty_keys() {
+        for key in ["", "   ", "+", "-", "+NAME-", "+NAME++DOB", "+(EMPLOYER:)"] {
+            assert!(MatchKey::parse(key).unwrap_err().is_bad_input(), "{key:?}");
+        }
+        assert_eq!(MatchKey::default().describe(&names()), "");
+        assert_eq!(MatchKey::default().to_string(), "");
+    }
+
+    #[test]
+    fn test_match_key_deny_only() {
+        let key = MatchKey::parse(" -DOB-EMAIL ").unwrap();
+        assert!(key.plus.is_empty());
+        assert_eq!(key.minus_codes().collect::<Vec<_>>(), ["DOB", "EMAIL"]);
+        assert_eq!(key.to_string(), "-DOB-EMAIL");
+        assert_eq!(key.describe(&names()), "Date of birth, EMAIL differ");
+
+        let single = MatchKey::parse("-DOB").unwrap();
+        assert_eq!(single.describe(&names()), "Date of birth differs");
+    }
+
+    #[test]
+    fn test_match_key_unknown_features() {
+        let key = MatchKey::parse("ACCT_NUM+NAME").unwrap();
+        assert_eq!(key.plus_codes().collect::<Vec<_>>(), ["ACCT_NUM", "NAME"]);
+        assert_eq!(key.describe(&names()), "ACCT_NUM, Name match");
+        assert_eq!(key.describe(&BTreeMap::new()), "ACCT_NUM, NAME match");
+    }
+
+    #[test]
+    fn test_match_key_parentheses() {
+        let key = MatchKey::parse("+NAME+REL_POINTER(SPOUSE-OF:)-DOB").unwrap();
+        assert_eq!(key.plus[1].code, "REL_POINTER");
+        assert_eq!(key.plus[1].detail.as_deref(), Some("SPOUSE-OF:"));
+        assert_eq!(key.minus_codes().collect::<Vec<_>>(), ["DOB"]);
+        assert_eq!(
+            key.describe(&names()),
+            "Name, Relationship (SPOUSE-OF:) match; Date of birth differs"
+        );
+        assert_eq!(MatchKey::parse(&key.to_string()).unwrap(), key);
+
+        for key in [
+            "+REL_POINTER(EMPLOYER:",
+            "+REL_POINTER)EMPLOYER:(",
+            "+NAME)",
+            "+REL_POINTER(EMPLOYER:)X",
+            "+REL_POINTER((EMPLOYER:)",
+        ] {
+            assert!(MatchKey::parse(key).unwrap_err().is_bad_input(), "{key:?}");
+        }
+    }
+}
@@ -0,0 +1,342 @@
+//! Building REST services on the SDK with axum.
+//!
+//! - [`SzState`]: the environment and one shared engine, cheap to clone, to
+//!   use as (or inside) the router state. [`SzState::blocking`] runs engine
+//!   calls on tokio's blocking pool so they do not stall the async workers.
+//! - Extractors: [`Sz`] takes the [`SzState`] out of any state it can be
+//!   obtained from with `FromRef`, [`Flags`] reads the `flags` query
+//!   parameter and [`RecordJson`] checks that the body is a JSON object.
+//! - `SzError` implements `IntoResponse`, so handlers can return
+//!   `Result<_, SzError>`. The status comes from [`status_code`] and the body
+//!   is `{"error":{"category":..,"code":..,"message":..}}`, with record
+//!   values in the message redacted while [`redact`](crate::redact) is on.
+//!
+//! # Example
+//!
+//! ```no_run
+//! use axum::Router;
+//! use axum::extract::Path;
+//! use axum::routing::{get, put};
+//! use sz_rust_sdk::prelude::*;
+//! use sz_rust_sdk::web::{Flags, RecordJson, Sz, SzState};
+//!
+//! async fn add(
+//!     Sz(sz): Sz,
+//!     Path((data_source, record_id)): Path<(String, String)>,
+//!     Flags(flags): Flags,
+//!     RecordJson(record): RecordJson,
+//! ) -> Result<String, SzError> {
+//!     sz.blocking(move |engine| engine.add_record(&data_source, &record_id, &record, flags))
+//!         .await
+//! }
+//!
+//! async fn entity(
+//!     Sz(sz): Sz,
+//!     Path(entity_id): Path<i64>,
+//!     Flags(flags): Flags,
+//! ) -> Result<String, SzError> {
+//!     sz.blocking(move |engine| engine.get_entity(EntityRef::Id(EntityId(entity_id)), flags))
+//!         .await
+//! }
+//!
+//! # fn main() -> SzResult<()> {
+//! let env = SzEnvironmentCore::get_instance("rest", "{}", false)?;
+//! let app: Router = Router::new()
+//!     .route("/records/{data_source}/{record_id}", put(add))
+//!     .route("/entities/{entity_id}", get(entity))
+//!     .with_state(SzState::new(env)?);
+//! // Serve `app` with `axum::serve`
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::context::SzCallContext;
+use crate::core::SzEnvironmentCore;
+use crate::error::{SzError, SzResult};
+use crate::flags::SzFlags;
+use crate::redact;
+use crate::traits::{SzEngine, SzEnvironment};
+use axum::extract::{FromRef, FromRequest, FromRequestParts, Request};
+use axum::http::request::Parts;
+use axum::http::{StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use serde_json::json;
+use std::sync::Arc;
+
+/// Largest request body [`RecordJson`] accepts, in bytes.
+pub const MAX_RECORD_BYTES: usize = 1 << 20;
+
+/// The environment and a shared engine for request handlers; see the
+/// [module documentation](self).
+///
+/// Clones share the same engine.
+#[derive(Clone)]
+pub struct SzState {
+    environment: Arc<SzEnvironmentCore>,
+    engine: Arc<dyn SzEngine>,
+}
+
+impl SzState {
+    /// Creates the state with an engine from `environment`.
+    ///
+    /// # Errors
+    ///
+    /// Any error from `SzEnvironment::get_engine`.
+    pub fn new(environment: Arc<SzEnvironmentCore>) -> SzResult<Self> {
+        let engine = Arc::from(environment.get_engine()?);
+        Ok(Self {
+            environment,
+            engine,
+        })
+    }
+
+    /// The environment.
+    pub fn environment(&self) -> &Arc<SzEnvironmentCore> {
+        &self.environment
+    }
+
+    /// The shared engine. Engine calls block; prefer
+    /// [`blocking`](Self::blocking) in async handlers.
+    pub fn engine(&self) -> &dyn SzEngine {
+        &*self.engine
+    }
+
+    /// Runs `call` with the engine on tokio's blocking thread pool, in the
+    /// caller's [`SzCallContext`]. With the `otel` feature, `call` also runs
+    /// in the caller's span.
+    ///
+    /// # Errors
+    ///
+    /// * Any error from `call`
+    /// * `SzError::Unknown` - `call` panicked
+    pub async fn blocking<T, F>(&self, call: F) -> SzResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&dyn SzEngine) -> SzResult<T> + Send + 'static,
+    {
+        let engine = Arc::clone(&self.engine);
+        let call = {
+            let context = SzCallContext::current();
+            move |engine: &dyn SzEngine| context.scope(|| call(engine))
+        };
+        #[cfg(feature = "otel")]
+        let call = {
+            let span = tracing::Span::current();
+            move |engine: &dyn SzEngine| span.in_scope(|| call(engine))
+        };
+        tokio::task::spawn_blocking(move || call(&*engine))
+            .await
+            .map_err(|e| SzError::unknown(format!("Engine call failed: {e}")))?
+    }
+}
+
+/// Extracts the [`SzState`] from the router state.
+pub struct Sz(pub SzState);
+
+impl<S> FromRequestParts<S> for Sz
+where
+    SzState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = SzError;
+
+    async fn from_request_parts(_parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self(SzState::from_ref(state)))
+    }
+}
+
+/// The `flags` query parameter, or `None` when absent.
+///
+/// The value is a decimal bit mask or flag names joined with `|`, e.g.
+/// `?flags=ENTITY_INCLUDE_ENTITY_NAME|ENTITY_INCLUDE_RECORD_DATA`. An
+/// invalid value is rejected with `400 Bad Request`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Flags(pub Option<SzFlags>);
+
+impl<S: Send + Sync> FromRequestParts<S> for Flags {
+    type Rejection = SzError;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let Some(value) = parts
+            .uri
+            .query()
+            .into_iter()
+            .flat_map(|query| query.split('&'))
+            .find_map(|pair| pair.strip_prefix("flags="))
+        else {
+            return Ok(Self(None));
+        };
+        parse_flags(&percent_decode(value)).map(|flags| Self(Some(flags)))
+    }
+}
+
+/// A request body that is a JSON object, as text for `SzEngine::add_record`.
+///
+/// Bodies over [`MAX_RECORD_BYTES`] or that are not a JSON object are
+/// rejected with `400 Bad Request`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordJson(pub String);
+
+impl<S: Send + Sync> FromRequest<S> for RecordJson {
+    type Rejection = SzError;
+
+    async fn from_request(request: Request, _state: &S) -> Result<Self, Self::Rejection> {
+        let body = axum::body::to_bytes(request.into_body(), MAX_RECORD_BYTES)
+            .await
+            .map_err(|e| SzError::bad_input(format!("Failed reading request body: {e}")))?;
+        let body = String::from_utf8(body.to_vec())
+            .map_err(|_| SzError::bad_input("Request body is not UTF-8"))?;
+        if !serde_json::from_str::<serde_json::Value>(&body)?.is_object() {
+            return Err(SzError::bad_input("Request body must be a JSON object"));
+        }
+        Ok(Self(body))
+    }
+}
+
+/// HTTP status for an error returned by a handler.
+pub fn status_code(error: &SzError) -> StatusCode {
+    match error {
+        SzError::BadInput(_) | SzError::Json(_) | SzError::StringConversion(_) => {
+            StatusCode::BAD_REQUEST
+        }
+        SzError::NotFound(_) | SzError::UnknownDataSource(_) => StatusCode::NOT_FOUND,
+        SzError::ReplaceConflict(_) | SzError::ExportInvalidated(_) => StatusCode::CONFLICT,
+        SzError::ResultTooLarge { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+        SzError::License(_) | SzError::NotPermitted(_) => StatusCode::FORBIDDEN,
+        SzError::Timeout(_) => StatusCode::GATEWAY_TIMEOUT,
+        SzError::Retryable(_)
+        | SzError::RetryTimeoutExceeded(_)
+        | SzError::DatabaseTransient(_)
+        | SzError::DatabaseConnectionLost(_)
+        | SzError::NotInitialized(_)
+        | SzError::EnvironmentDestroyed(_)
+        | SzError::Cancelled(_) => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+impl IntoResponse for SzError {
+    fn into_response(self) -> Response {
+        let body = json!({
+            "error": {
+                "category": self.category(),
+                "code": self.error_code(),
+                "message": redact::message(&self.to_string()),
+            }
+        });
+        (
+            status_code(&self),
+            [(header::CONTENT_TYPE, "application/json")],
+            body.to_string(),
+        )
+            .into_response()
+    }
+}
+
+/// Parses a decimal bit mask or `|`-joined flag names.
+fn parse_flags(value: &str) -> SzResult<SzFlags> {
+    if let Ok(bits) = value.trim().parse::<u64>() {
+        return Ok(SzFlags::from_bits_truncate(bits));
+    }
+    bitflags::parser::from_str::<SzFlags>(value)
+        .map_err(|e| SzError::bad_input(format!("Invalid flags {value:?}: {e}")))
+}
+
+/// Decodes `%XX` escapes and `+` in a query parameter value.
+fn percent_decode(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let hex = |i: usize| bytes.get(i).and_then(|b| char::from(*b).to_digit(16));
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match (bytes[i], hex(i + 1), hex(i + 2)) {
+            (b'%', Some(high), Some(low)) => {
+                decoded.push((high * 16 + low) as u8);
+                i += 3;
+                continue;
+            }
+            (b'+', ..) => decoded.push(b' '),
+            (byte, ..) => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flags(uri: &str) -> Result<Flags, SzError> {
+        let (mut parts, ()) = Request::builder().uri(uri).body(()).unwrap().into_parts();
+        tokio_test::block_on(Flags::from_request_parts(&mut parts, &()))
+    }
+
+    #[test]
+    fn test_flags_extractor() {
+        assert_eq!(flags("/entities/1").unwrap(), Flags(None));
+        assert_eq!(
+            flags("/entities/1?x=1&flags=4096").unwrap(),
+            Flags(Some(SzFlags::ENTITY_INCLUDE_ENTITY_NAME))
+        );
+        assert_eq!(
+            flags(
+                "/entities/1?flags=ENTITY_INCLUDE_ENTITY_NAME%20%7C%20ENTITY_INCLUDE_RECORD_DATA"
+            )
+            .unwrap(),
+            Flags(Some(
+                SzFlags::ENTITY_INCLUDE_ENTITY_NAME | SzFlags::ENTITY_INCLUDE_RECORD_DATA
+            ))
+        );
+        assert!(matches!(
+            flags("/entities/1?flags=NO_SUCH_FLAG"),
+            Err(SzError::BadInput(_))
+        ));
+    }
+
+    #[test]
+    fn test_record_json_extractor() {
+        let extract = |body: &'static str| {
+            let request = Request::builder()
+                .body(axum::body::Body::from(body))
+                .unwrap();
+            tokio_test::block_on(RecordJson::from_request(request, &()))
+        };
+        assert_eq!(
+            extract(r#"{"NAME_FULL":"Ann Lee"}"#).unwrap().0,
+            r#"{"NAME_FULL":"Ann Lee"}"#
+        );
+        assert!(extract("[1]").is_err());
+        assert!(extract("{").is_err());
+    }
+
+    #[test]
+    fn test_error_responses() {
+        assert_eq!(
+            status_code(&SzError::bad_input("x")),
+            StatusCode::BAD_REQUEST
+        );
+        assert_eq!(status_code(&SzError::not_found("x")), StatusCode::NOT_FOUND);
+        assert_eq!(
+            status_code(&SzError::retryable("x")),
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+        assert_eq!(
+            status_code(&SzError::unrecoverable("x")),
+            StatusCode::INTERNAL_SERVER_ERROR
+        );
+
+        let response = SzError::not_found("Unknown record: 'CUSTOMERS' '1001'").into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let body =
+            tokio_test::block_on(axum::body::to_bytes(response.into_body(), usize::MAX)).unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["category"], "bad_input");
+        assert!(
+            !body["error"]["message"]
+                .as_str()
+                .unwrap()
+                .contains("CUSTOMERS")
+        );
+    }
+}
@@ -26,8 +26,8 @@ fn test_sdk_compilation_and_imports() {
 
     // Verify core types are available
     fn _check_types() {
-        let _entity_id: EntityId = 1;
-        let _config_id: ConfigId = 1;
+        let _entity_id = EntityId(1);
+        let _config_id = ConfigId(1);
         let _json: JsonString = String::new();
         let _flags = SzFlags::EXPORT_DEFAULT_FLAGS;
     }
@@ -120,10 +120,11 @@ fn test_flags_system() {
 fn test_types_and_aliases() {
     // Test that type aliases work correctly
 
-    let entity_id: EntityId = 123;
-    let config_id: ConfigId = 456;
+    let entity_id = EntityId(123);
+    let config_id = ConfigId(456);
     let json_data: JsonString = r#"{"test": "data"}"#.to_string();
-    let ds_code: DataSourceCode = "TEST_DS".to_string();
+    let ds_code = DataSourceCode::new("test_ds").unwrap();
+    assert_eq!(ds_code.as_str(), "TEST_DS");
     let record_id: RecordId = "REC123".to_string();
 
     // Verify they're the expected underlying types
@@ -133,10 +134,10 @@ fn test_types_and_aliases() {
     fn _check_ds_code(_: String) {}
     fn _check_record_id(_: String) {}
 
-    _check_entity_id(entity_id);
-    _check_config_id(config_id);
+    _check_entity_id(entity_id.into());
+    _check_config_id(config_id.into());
     _check_json(json_data);
-    _check_ds_code(ds_code);
+    _check_ds_code(ds_code.into());
     _check_record_id(record_id);
 
     println!("✅ Types and aliases test passed");
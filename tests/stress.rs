@@ -0,0 +1,216 @@
+//! Concurrency stress test for the SDK's `Send`/`Sync` guarantees.
+//!
+//! Shares one engine across many threads that add, search, read and process
+//! redo records concurrently, then checks that no call failed unexpectedly
+//! and that every native response buffer was freed.
+//!
+//! Built only with the `stress-tests` feature and run without the libtest
+//! harness:
+//!
+//! ```text
+//! STRESS_THREADS=16 STRESS_SECONDS=60 cargo test --features stress-tests --test stress
+//! ```
+//!
+//! | Variable | Default | Meaning |
+//! |----------|---------|---------|
+//! | `STRESS_THREADS` | 8 | Worker threads sharing the engine |
+//! | `STRESS_SECONDS` | 10 | How long the workers run |
+//! | `STRESS_RECORDS` | 200 | Distinct record IDs each worker cycles through |
+
+use std::process::ExitCode;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use sz_rust_sdk::helpers::ExampleEnvironment;
+use sz_rust_sdk::prelude::*;
+
+#[derive(Debug, Clone, Copy)]
+struct StressConfig {
+    threads: usize,
+    duration: Duration,
+    records: usize,
+}
+
+impl StressConfig {
+    fn from_env() -> Self {
+        Self {
+            threads: env_or("STRESS_THREADS", 8).max(1),
+            duration: Duration::from_secs(env_or("STRESS_SECONDS", 10)),
+            records: env_or("STRESS_RECORDS", 200).max(1),
+        }
+    }
+}
+
+fn env_or<T: std::str::FromStr>(name: &str, default: T) -> T {
+    std::env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
+
+#[derive(Default)]
+struct Counters {
+    adds: AtomicU64,
+    searches: AtomicU64,
+    reads: AtomicU64,
+    redos: AtomicU64,
+    retried: AtomicU64,
+    failures: Mutex<Vec<String>>,
+}
+
+impl Counters {
+    /// Counts a call result; retryable errors are expected under contention.
+    fn record<T>(&self, op: &AtomicU64, what: &str, result: SzResult<T>) -> Option<T> {
+        match result {
+            Ok(value) => {
+                op.fetch_add(1, Ordering::Relaxed);
+                Some(value)
+            }
+            Err(e) if e.is_retryable() => {
+                self.retried.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+            Err(e) => {
+                let mut failures = match self.failures.lock() {
+                    Ok(guard) => guard,
+                    Err(poisoned) => poisoned.into_inner(),
+                };
+                failures.push(format!("{what}: {e}"));
+                None
+            }
+        }
+    }
+}
+
+fn worker(
+    engine: &dyn SzEngine,
+    worker_id: usize,
+    config: StressConfig,
+    stop: &AtomicBool,
+    counters: &Counters,
+) {
+    let mut i = 0usize;
+    while !stop.load(Ordering::Relaxed) {
+        let record_id = format!("STRESS_{worker_id}_{}", i % config.records);
+        // Shared phone numbers across workers make entities resolve together
+        // and keep the redo queue busy
+        let definition = format!(
+            r#"{{"NAME_FULL":"Stress Person {i}","PHONE_NUMBER":"555-{:04}","ADDR_FULL":"{} Main St"}}"#,
+            i % 97,
+            i % 13
+        );
+        let added = counters.record(
+            &counters.adds,
+            "add_record",
+            engine.add_record("TEST", &record_id, &definition, None),
+        );
+
+        counters.record(
+            &counters.searches,
+            "search_by_attributes_bytes",
+            engine.search_by_attributes_bytes(r#"{"PHONE_NUMBER":"555-0001"}"#, None, None),
+        );
+
+        if added.is_some() {
+            counters.record(
+                &counters.reads,
+                "get_record_bytes",
+                engine.get_record_bytes("TEST", &record_id, None),
+            );
+        }
+
+        if let Some(redo) =
+            counters.record(&counters.redos, "get_redo_record", engine.get_redo_record())
+            && !redo.is_empty()
+        {
+            counters.record(
+                &counters.redos,
+                "process_redo_record",
+                engine.process_redo_record(&redo, None),
+            );
+        }
+        i += 1;
+    }
+}
+
+fn run(config: StressConfig) -> SzResult<Vec<String>> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-stress")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let before = env.native_buffer_stats();
+
+    let counters = Counters::default();
+    let stop = AtomicBool::new(false);
+    let started = Instant::now();
+    let panicked = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..config.threads)
+            .map(|worker_id| {
+                let (engine, stop, counters) = (engine.as_ref(), &stop, &counters);
+                scope.spawn(move || worker(engine, worker_id, config, stop, counters))
+            })
+            .collect();
+        std::thread::sleep(config.duration);
+        stop.store(true, Ordering::Relaxed);
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().err())
+            .count()
+    });
+    let elapsed = started.elapsed();
+
+    let after = env.native_buffer_stats();
+    let mut failures = match counters.failures.into_inner() {
+        Ok(failures) => failures,
+        Err(poisoned) => poisoned.into_inner(),
+    };
+    if panicked > 0 {
+        failures.push(format!("{panicked} worker threads panicked"));
+    }
+    let outstanding = after.outstanding().saturating_sub(before.outstanding());
+    if outstanding > 0 {
+        failures.push(format!(
+            "{outstanding} native response buffers were not freed ({} taken, {} freed)",
+            after.taken - before.taken,
+            after.freed - before.freed
+        ));
+    }
+
+    println!(
+        "stress: {} threads for {:.1}s: {} adds, {} searches, {} reads, {} redo calls, {} retryable errors, {} native buffers",
+        config.threads,
+        elapsed.as_secs_f64(),
+        counters.adds.load(Ordering::Relaxed),
+        counters.searches.load(Ordering::Relaxed),
+        counters.reads.load(Ordering::Relaxed),
+        counters.redos.load(Ordering::Relaxed),
+        counters.retried.load(Ordering::Relaxed),
+        after.taken - before.taken
+    );
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(failures)
+}
+
+fn main() -> ExitCode {
+    let config = StressConfig::from_env();
+    match run(config) {
+        Ok(failures) if failures.is_empty() => {
+            println!("stress: ok");
+            ExitCode::SUCCESS
+        }
+        Ok(failures) => {
+            // Print a sample; under load the same error tends to repeat
+            for failure in failures.iter().take(20) {
+                eprintln!("stress: {failure}");
+            }
+            eprintln!("stress: {} failures", failures.len());
+            ExitCode::FAILURE
+        }
+        Err(e) => {
+            eprintln!("stress: setup failed: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -0,0 +1,91 @@
+//! Audit log integration tests.
+
+use serial_test::serial;
+use std::sync::Arc;
+use sz_rust_sdk::audit::{self, MemoryAuditSink};
+use sz_rust_sdk::helpers::ExampleEnvironment;
+use sz_rust_sdk::policy::{RecordValidation, SzPolicy};
+use sz_rust_sdk::prelude::*;
+
+/// Test that mutations are recorded with their actor and outcome
+#[test]
+#[serial]
+fn test_audit_mutations() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-audit-mutations")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let sink = Arc::new(MemoryAuditSink::new());
+    env.set_audit_sink(Some(sink.clone()));
+
+    audit::with_actor("auditor", || -> SzResult<()> {
+        engine.add_record("TEST", "AUDIT1", r#"{"NAME_FULL": "Audit Person"}"#, None)?;
+        engine.delete_record("TEST", "AUDIT1", None)?;
+        Ok(())
+    })?;
+    assert!(
+        engine
+            .add_record("NO_SUCH_SOURCE", "AUDIT2", "{}", None)
+            .is_err()
+    );
+
+    let events = sink.take();
+    let operations: Vec<_> = events.iter().map(|e| e.operation).collect();
+    assert_eq!(operations, ["add_record", "delete_record", "add_record"]);
+    assert!(events[0].succeeded());
+    assert_eq!(events[0].actor.as_deref(), Some("auditor"));
+    assert_eq!(events[0].parameters["record_id"], "AUDIT1");
+    assert!(!events[2].succeeded());
+    assert_eq!(events[2].actor, None);
+    assert_eq!(env.audit_failures(), 0);
+
+    env.set_audit_sink(None);
+    engine.add_record("TEST", "AUDIT3", r#"{"NAME_FULL": "Quiet Person"}"#, None)?;
+    assert!(sink.events().is_empty());
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that adds rejected by policy validation and redo processing are audited
+#[test]
+#[serial]
+fn test_audit_rejected_adds_and_redo() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-audit-rejected")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let sink = Arc::new(MemoryAuditSink::new());
+    env.set_audit_sink(Some(sink.clone()));
+    env.set_policy(
+        "TEST",
+        Some(SzPolicy::new().with_validation(RecordValidation::Keys)),
+    );
+
+    let rejected = engine.add_record(
+        "TEST",
+        "AUDIT4",
+        r#"{"RECORD_ID": "OTHER", "NAME_FULL": "Mismatched Person"}"#,
+        None,
+    );
+    assert!(rejected.unwrap_err().is_bad_input());
+    assert!(
+        engine
+            .process_redo_record("not a redo record", None)
+            .is_err()
+    );
+
+    let events = sink.take();
+    let operations: Vec<_> = events.iter().map(|e| e.operation).collect();
+    assert_eq!(operations, ["add_record", "process_redo_record"]);
+    assert!(!events[0].succeeded());
+    assert_eq!(events[0].parameters["record_id"], "AUDIT4");
+    assert!(!events[1].succeeded());
+
+    env.set_policy("TEST", None);
+    env.set_audit_sink(None);
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
@@ -0,0 +1,158 @@
+//! Configuration tool integration tests.
+//!
+//! These tests apply configuration templates to real configurations, load
+//! the templates' example records, validate configuration documents, and
+//! bootstrap the default configuration.
+
+use serial_test::serial;
+use sz_rust_sdk::config_tools::{self, ConfigFinding, SzBootstrap, templates};
+use sz_rust_sdk::helpers::ExampleEnvironment;
+use sz_rust_sdk::prelude::*;
+
+/// Test that every built-in template applies to the default configuration
+#[test]
+#[serial]
+fn test_templates_apply_to_default_config() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-config-templates")?;
+    let config_mgr = env.get_config_manager()?;
+    let config = config_mgr.create_config()?;
+
+    for template in templates::all() {
+        let registered = template.apply(&*config)?;
+        assert_eq!(registered.len(), template.data_sources.len());
+        // Applying again registers nothing new
+        assert!(template.apply(&*config)?.is_empty());
+    }
+
+    let data_sources = config_tools::registered_data_sources(&*config)?;
+    for template in templates::all() {
+        for data_source in template.data_sources {
+            assert!(data_sources.contains(*data_source));
+        }
+    }
+
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test loading a template's example record after activating the template
+#[test]
+#[serial]
+fn test_template_example_record_loads() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-config-template-load")?;
+    let config_mgr = env.get_config_manager()?;
+    let config = config_mgr.create_config()?;
+    templates::PEOPLE.apply(&*config)?;
+    let config_id = config_mgr.set_default_config(&config.export()?, Some("People template"))?;
+    env.reinitialize(config_id)?;
+
+    let engine = env.get_engine()?;
+    engine.add_record("PEOPLE", "1", templates::PEOPLE.example_record, None)?;
+    engine.get_entity(EntityRef::from_record("PEOPLE", "1"), None)?;
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test validating valid, malformed, and inconsistent configuration documents
+#[test]
+#[serial]
+fn test_validate_config() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-config-validate")?;
+    let config_mgr = env.get_config_manager()?;
+    let definition = config_mgr.create_config()?.export()?;
+
+    let report = config_mgr.validate_config(&definition)?;
+    assert!(report.is_valid(), "{report}");
+
+    let report = config_mgr.validate_config(&definition[..definition.len() / 2])?;
+    assert!(matches!(
+        report.findings.as_slice(),
+        [ConfigFinding::InvalidJson { .. }]
+    ));
+
+    let mut document: serde_json::Value = serde_json::from_str(&definition)?;
+    let data_sources = document["G2_CONFIG"]["CFG_DSRC"]
+        .as_array_mut()
+        .expect("CFG_DSRC should be an array");
+    let duplicate = data_sources[0].clone();
+    data_sources.push(duplicate);
+    let report = config_mgr.validate_config(&document.to_string())?;
+    assert!(report.findings.iter().any(|finding| matches!(
+        finding,
+        ConfigFinding::DuplicateDataSource { path, .. } if path.starts_with("G2_CONFIG.CFG_DSRC[")
+    )));
+
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that bootstrapping registers data sources once and is idempotent
+#[test]
+#[serial]
+fn test_bootstrap_ensure_default_config() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-config-bootstrap")?;
+    let config_mgr = env.get_config_manager()?;
+    let original = config_mgr.get_default_config_id()?;
+
+    let config_id = SzBootstrap::ensure_default_config(&env, &["BOOT_A", "boot_b", "BOOT_A"])?;
+    assert_ne!(config_id, original);
+    assert_eq!(config_mgr.get_default_config_id()?, config_id);
+    assert_eq!(env.get_active_config_id()?, config_id);
+
+    let config = config_mgr.create_config_from_id(config_id)?;
+    let data_sources = config_tools::registered_data_sources(&*config)?;
+    assert!(data_sources.contains("BOOT_A"));
+    assert!(data_sources.contains("BOOT_B"));
+
+    // Nothing left to do: the default is unchanged
+    assert_eq!(
+        SzBootstrap::ensure_default_config(&env, &["BOOT_B", "TEST"])?,
+        config_id
+    );
+
+    let engine = env.get_engine()?;
+    engine.add_record("BOOT_B", "1", r#"{"NAME_FULL":"Boot Strap"}"#, None)?;
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that the attribute schema of the default configuration covers the
+/// templates' example records
+#[test]
+#[serial]
+fn test_attribute_schema_covers_template_records() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-config-attribute-schema")?;
+    let config = env.get_config_manager()?.create_config()?;
+    let schema = config_tools::attribute_schema(&*config)?;
+    let properties = schema["properties"]
+        .as_object()
+        .expect("schema has properties");
+
+    for template in templates::all() {
+        let record: serde_json::Value = serde_json::from_str(template.example_record)?;
+        for key in record.as_object().into_iter().flatten().map(|(k, _)| k) {
+            assert!(
+                properties.contains_key(key),
+                "{} example uses {key}, which is not in the schema",
+                template.name
+            );
+        }
+    }
+
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
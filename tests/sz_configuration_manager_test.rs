@@ -184,6 +184,70 @@ fn test_replace_default_config_id() -> SzResult<()> {
     Ok(())
 }
 
+/// Test compare-and-set of the default configuration ID
+#[test]
+#[serial]
+fn test_compare_and_set_default_config_id() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-compare-and-set-config-test")?;
+    let config_manager = env.get_config_manager()?;
+    let original = config_manager.get_default_config_id()?;
+    let definition = config_manager.create_config()?.export()?;
+    let first = config_manager.register_config(&definition, Some("CAS first"))?;
+    let second = config_manager.register_config(&definition, Some("CAS second"))?;
+
+    // Expected ID matches: the swap happens
+    assert_eq!(
+        config_manager.compare_and_set_default_config_id(original, first)?,
+        Ok(())
+    );
+    assert_eq!(config_manager.get_default_config_id()?, first);
+
+    // Stale expected ID: reports the default now in place and leaves it alone
+    let replaced = config_manager
+        .compare_and_set_default_config_id(original, second)?
+        .expect_err("stale expected ID should not replace the default");
+    assert_eq!(replaced.expected, original);
+    assert_eq!(replaced.actual, first);
+    assert_eq!(config_manager.get_default_config_id()?, first);
+
+    let err: SzError = replaced.into();
+    assert!(matches!(err, SzError::ReplaceConflict(_)));
+
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test listing the configuration registry as typed entries
+#[test]
+#[serial]
+fn test_list_configs() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-list-configs-test")?;
+    let config_manager = env.get_config_manager()?;
+    let config = config_manager.create_config()?;
+    config.register_data_source("LISTED")?;
+    let config_id = config_manager.register_config(&config.export()?, Some("Listed config"))?;
+
+    let configs = config_manager.list_configs()?;
+    let default_id = config_manager.get_default_config_id()?;
+    assert!(configs.iter().any(|info| info.config_id == default_id));
+    let info = configs
+        .iter()
+        .find(|info| info.config_id == config_id)
+        .expect("registered config should be listed");
+    assert_eq!(info.comment, "Listed config");
+    assert!(!info.created.is_empty());
+
+    let loaded = info.load(&*config_manager)?;
+    assert!(loaded.get_data_source_registry()?.contains("LISTED"));
+
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
 /// Test configuration lifecycle management
 /// Tests singleton constraints throughout configuration lifecycle
 #[test]
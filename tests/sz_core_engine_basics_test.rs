@@ -6,6 +6,7 @@
 
 use serial_test::serial;
 use sz_rust_sdk::helpers::ExampleEnvironment;
+use sz_rust_sdk::models::EngineStats;
 use sz_rust_sdk::prelude::*;
 
 /// Test basic engine initialization and version retrieval
@@ -38,7 +39,7 @@ fn test_engine_with_registered_data_sources() -> SzResult<()> {
     let env = ExampleEnvironment::initialize("sz-rust-sdk-data-source-test")?;
     let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
     // Test get entity by ID that doesn't exist - should return not found
-    let result = engine.get_entity(99999.into(), Some(SzFlags::ENTITY_DEFAULT_FLAGS));
+    let result = engine.get_entity(EntityId(99999).into(), Some(SzFlags::ENTITY_DEFAULT_FLAGS));
     assert!(result.is_err());
     // Expected not found error or other acceptable error for non-existent entities
 
@@ -104,6 +105,33 @@ fn test_engine_statistics() -> SzResult<()> {
     Ok(())
 }
 
+/// Test parsing engine statistics into the typed model
+#[test]
+#[serial]
+fn test_engine_statistics_typed() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-stats-typed-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+
+    let earlier = EngineStats::from_json(&engine.get_stats()?)?;
+    engine.add_record("TEST", "STATS_1001", r#"{"NAME_FULL":"Stan Tistic"}"#, None)?;
+    engine.add_record(
+        "TEST",
+        "STATS_1002",
+        r#"{"NAME_FULL":"Stella Tistic"}"#,
+        None,
+    )?;
+    let later = EngineStats::from_json(&engine.get_stats()?)?;
+
+    let delta = later.delta(&earlier);
+    assert!(delta.added_records >= 2);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
 /// Test engine with different flag combinations
 /// Mirrors C# flag usage tests
 #[test]
@@ -6,6 +6,7 @@
 
 use serial_test::serial;
 use sz_rust_sdk::helpers::ExampleEnvironment;
+use sz_rust_sdk::models::FindPathResponse;
 use sz_rust_sdk::prelude::*;
 
 /// Test find path between non-existent entities
@@ -300,7 +301,10 @@ fn test_find_network_by_record_id_not_found() -> SzResult<()> {
     let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
 
     let result = engine.find_network_by_record_id(
-        &[("TEST", "NONEXISTENT_NET_1"), ("TEST", "NONEXISTENT_NET_2")],
+        &[
+            RecordKey::new("TEST", "NONEXISTENT_NET_1")?,
+            RecordKey::new("TEST", "NONEXISTENT_NET_2")?,
+        ],
         3,
         1,
         100,
@@ -328,7 +332,8 @@ fn test_find_network_by_record_id_valid() -> SzResult<()> {
         None,
     )?;
 
-    let result = engine.find_network_by_record_id(&[("TEST", "FNBR_1001")], 3, 1, 100, None);
+    let result =
+        engine.find_network_by_record_id(&[RecordKey::new("TEST", "FNBR_1001")?], 3, 1, 100, None);
     // Should succeed with a single known record
     assert!(result.is_ok());
     let json = result.unwrap();
@@ -350,7 +355,7 @@ fn test_find_network_by_record_id_with_flags() -> SzResult<()> {
     engine.add_record("TEST", "FNBRF_1001", r#"{"NAME_FULL": "Diana Blue"}"#, None)?;
 
     let result = engine.find_network_by_record_id(
-        &[("TEST", "FNBRF_1001")],
+        &[RecordKey::new("TEST", "FNBRF_1001")?],
         3,
         1,
         100,
@@ -362,6 +367,37 @@ fn test_find_network_by_record_id_with_flags() -> SzResult<()> {
     Ok(())
 }
 
+/// Test find_network_req with a built request seeded by record key
+#[test]
+#[serial]
+fn test_find_network_req() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-find-network-req")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+
+    engine.add_record("TEST", "FNRQ_1001", r#"{"NAME_FULL": "Edgar Gray"}"#, None)?;
+
+    let request = FindNetworkRequest::builder()
+        .records([RecordKey::new("TEST", "FNRQ_1001")?])
+        .max_degrees(2)
+        .build_out_degrees(0)
+        .max_entities(10)
+        .flags(SzFlags::FIND_NETWORK_DEFAULT_FLAGS)
+        .build()?;
+    let json = engine.find_network_req(&request)?;
+    assert!(json.contains("FNRQ_1001"));
+
+    let invalid = FindNetworkRequest::builder()
+        .records([RecordKey::new("TEST", "FNRQ_1001")?])
+        .build_out_degrees(-1)
+        .build();
+    assert!(matches!(invalid, Err(SzError::BadInput(_))));
+
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
 /// Test find_path_by_entity_id with non-existent entities
 /// Should return an error for entities that don't exist
 #[test]
@@ -372,7 +408,8 @@ fn test_find_path_by_entity_id_not_found() -> SzResult<()> {
     let env = ExampleEnvironment::initialize("test-find-path-by-entity-id-not-found")?;
     let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
 
-    let result = engine.find_path_by_entity_id(999999, 999998, 3, None, None, None);
+    let result =
+        engine.find_path_by_entity_id(EntityId(999999), EntityId(999998), 3, None, None, None);
     assert!(result.is_err());
 
     ExampleEnvironment::cleanup(env)?;
@@ -389,9 +426,62 @@ fn test_find_network_by_entity_id_not_found() -> SzResult<()> {
     let env = ExampleEnvironment::initialize("test-find-network-by-entity-id-not-found")?;
     let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
 
-    let result = engine.find_network_by_entity_id(&[999999, 999998], 3, 1, 100, None);
+    let result =
+        engine.find_network_by_entity_id(&[EntityId(999999), EntityId(999998)], 3, 1, 100, None);
     assert!(result.is_err());
 
     ExampleEnvironment::cleanup(env)?;
     Ok(())
 }
+
+/// Test parsing a real find_path response into typed paths
+#[test]
+#[serial]
+fn test_find_path_typed_response() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-find-path-typed")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+
+    engine.add_record(
+        "TEST",
+        "FPT_1",
+        r#"{"NAME_FULL": "Avery Quill", "PHONE_NUMBER": "555-0301"}"#,
+        None,
+    )?;
+    engine.add_record(
+        "TEST",
+        "FPT_2",
+        r#"{"NAME_FULL": "Morgan Vance", "PHONE_NUMBER": "555-0301", "EMAIL_ADDRESS": "mv@example.com"}"#,
+        None,
+    )?;
+    engine.add_record(
+        "TEST",
+        "FPT_3",
+        r#"{"NAME_FULL": "Rowan Tell", "EMAIL_ADDRESS": "mv@example.com"}"#,
+        None,
+    )?;
+
+    let json = engine.find_path_by_record_id(
+        "TEST",
+        "FPT_1",
+        "TEST",
+        "FPT_3",
+        3,
+        None,
+        None,
+        Some(SzFlags::FIND_PATH_DEFAULT_FLAGS),
+    )?;
+    let response = FindPathResponse::from_json(&json)?;
+    assert_eq!(response.paths.len(), 1);
+    let path = &response.paths[0];
+    if path.is_found() {
+        assert_eq!(path.degrees(), path.entity_ids.len() - 1);
+        assert!(path.hops.iter().all(|hop| hop.match_key.is_some()));
+        assert_eq!(response.ranked().len(), 1);
+    }
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
@@ -136,3 +136,264 @@ fn test_search_by_attributes_empty() -> SzResult<()> {
     ExampleEnvironment::cleanup(env)?;
     Ok(())
 }
+
+/// Test that the *_bytes read methods return the same documents as the string methods
+#[test]
+#[serial]
+fn test_read_bytes_match_string_responses() -> SzResult<()> {
+    let env = ExampleEnvironment::initialize("test-read-bytes")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    engine.add_record(
+        "TEST",
+        "BYTES_1001",
+        r#"{"NAME_FULL": "Mary Jones", "PHONE_NUMBER": "555-3434"}"#,
+        None,
+    )?;
+
+    let entity = engine.get_entity(EntityRef::from_record("TEST", "BYTES_1001"), None)?;
+    let entity_bytes =
+        engine.get_entity_bytes(EntityRef::from_record("TEST", "BYTES_1001"), None)?;
+    assert_eq!(entity_bytes.to_str()?, entity);
+
+    let record = engine.get_record("TEST", "BYTES_1001", None)?;
+    let record_bytes = engine.get_record_bytes("TEST", "BYTES_1001", None)?;
+    assert_eq!(record_bytes.into_string()?, record);
+
+    let attrs = r#"{"NAME_FULL": "Mary Jones"}"#;
+    let search: serde_json::Value =
+        serde_json::from_str(&engine.search_by_attributes(attrs, None, None)?)?;
+    let search_bytes: serde_json::Value = engine
+        .search_by_attributes_bytes(attrs, None, None)?
+        .parse()?;
+    assert_eq!(search, search_bytes);
+
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that get_entity_relationships returns only the requested kinds
+#[test]
+#[serial]
+fn test_get_entity_relationships() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-entity-relationships")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    engine.add_record(
+        "TEST",
+        "REL_1",
+        r#"{"NAME_FULL": "Quinn Harlow", "PHONE_NUMBER": "555-0401"}"#,
+        None,
+    )?;
+    engine.add_record(
+        "TEST",
+        "REL_2",
+        r#"{"NAME_FULL": "Sasha Brook", "PHONE_NUMBER": "555-0401"}"#,
+        None,
+    )?;
+
+    let all = engine.get_entity_relationships(
+        EntityRef::from_record("TEST", "REL_1"),
+        RelationshipKind::all(),
+    )?;
+    for related in &all {
+        assert!(!related.relationship().is_empty());
+    }
+
+    let disclosed = engine.get_entity_relationships(
+        EntityRef::from_record("TEST", "REL_1"),
+        RelationshipKind::DISCLOSED,
+    )?;
+    assert!(disclosed.is_empty());
+    assert!(
+        engine
+            .get_entity_relationships(
+                EntityRef::from_record("TEST", "REL_1"),
+                RelationshipKind::empty()
+            )?
+            .is_empty()
+    );
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that get_entity_record_keys returns the entity's record keys
+#[test]
+#[serial]
+fn test_get_entity_record_keys() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-entity-record-keys")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let record = r#"{"NAME_FULL": "Morgan Vale", "PHONE_NUMBER": "555-0412"}"#;
+    engine.add_record("TEST", "KEYS_1", record, None)?;
+    let entity: serde_json::Value =
+        serde_json::from_str(&engine.get_entity(EntityRef::from_record("TEST", "KEYS_1"), None)?)?;
+    let entity_id = EntityId(entity["RESOLVED_ENTITY"]["ENTITY_ID"].as_i64().unwrap());
+    let before = engine.get_entity_record_keys(entity_id)?;
+    assert_eq!(before.len(), 1);
+
+    engine.add_record("TEST", "KEYS_2", record, None)?;
+    let after = engine.get_entity_record_keys(entity_id)?;
+    let change = sz_rust_sdk::analysis::membership_change(&before, &after);
+    assert!(change.removed.is_empty());
+    assert!(change.added.len() <= 1);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test a golden snapshot of a resolved entity's records
+#[test]
+#[serial]
+fn test_entity_snapshot() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-entity-snapshot-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let record = r#"{"NAME_FULL": "Morgan Snapshot", "PHONE_NUMBER": "555-0177", "EMAIL_ADDRESS": "morgan@example.com"}"#;
+    engine.add_record("TEST", "SNAP_1", record, None)?;
+    engine.add_record("TEST", "SNAP_2", record, None)?;
+
+    let entity = engine.get_entity(EntityRef::from_record("TEST", "SNAP_1"), None)?;
+    let entity_id = EntityId(
+        serde_json::from_str::<serde_json::Value>(&entity)?["RESOLVED_ENTITY"]["ENTITY_ID"]
+            .as_i64()
+            .expect("entity ID"),
+    );
+    sz_rust_sdk::testing::assert_entity_snapshot!(
+        engine,
+        entity_id,
+        Some(SzFlags::ENTITY_INCLUDE_RECORD_DATA)
+    );
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test repository counts and their caching
+#[test]
+#[serial]
+fn test_repository_counts() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-repository-counts-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let before = engine.repository_counts(std::time::Duration::ZERO)?;
+    let record = r#"{"NAME_FULL": "Casey Counter", "PHONE_NUMBER": "555-0142"}"#;
+    engine.add_record("TEST", "COUNT_1", record, None)?;
+    engine.add_record("TEST", "COUNT_2", record, None)?;
+
+    let cached = engine.repository_counts(std::time::Duration::from_secs(3600))?;
+    assert_eq!(cached, before);
+
+    let fresh = engine.repository_counts(std::time::Duration::ZERO)?;
+    assert!(fresh.counted_at >= before.counted_at);
+    assert_eq!(fresh.records, before.records + 2);
+    assert_eq!(fresh.entities, before.entities + 1);
+    assert_eq!(engine.count_records()?, fresh.records);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test recording searches and replaying them after the data changed
+#[test]
+#[serial]
+fn test_search_replay() -> SzResult<()> {
+    use sz_rust_sdk::testing::SearchReplay;
+
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-search-replay-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let record = r#"{"NAME_FULL": "Riley Replay", "PHONE_NUMBER": "555-0188"}"#;
+    engine.add_record("TEST", "REPLAY_1", record, None)?;
+
+    let mut replay = SearchReplay::new();
+    replay.record(&*engine, record, None)?;
+    let path = std::env::temp_dir().join(format!("sz_search_replay_{}.jsonl", std::process::id()));
+    replay.save(&path)?;
+    let replay = SearchReplay::load(&path)?;
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(replay.len(), 1);
+    assert!(replay.replay(&*engine)?.is_unchanged());
+
+    engine.add_record("TEST", "REPLAY_2", record, None)?;
+    let report = replay.replay(&*engine)?;
+    assert_eq!(report.searches, 1);
+    assert!(!report.is_unchanged());
+    assert!(report.to_string().contains("TEST:REPLAY_1, TEST:REPLAY_2"));
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test a stratified entity sample and its review file
+#[test]
+#[serial]
+fn test_sample_entities() -> SzResult<()> {
+    use sz_rust_sdk::analysis::{self, SampleStrategy};
+
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-sample-entities-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let pair = r#"{"NAME_FULL": "Sam Sample", "PHONE_NUMBER": "555-0199"}"#;
+    engine.add_record("TEST", "SAMPLE_1", pair, None)?;
+    engine.add_record("TEST", "SAMPLE_2", pair, None)?;
+    engine.add_record(
+        "TEST",
+        "SAMPLE_3",
+        r#"{"NAME_FULL": "Pat Single", "PHONE_NUMBER": "555-0133"}"#,
+        None,
+    )?;
+
+    let sample = analysis::sample_entities(&*engine, SampleStrategy::ByEntitySize, 2)?;
+    assert_eq!(sample.len(), 2);
+    assert!(sample.iter().any(|entity| entity.stratum == "2"));
+    let mut csv = Vec::new();
+    assert_eq!(analysis::write_review_csv(&sample, &mut csv)?, 2);
+    assert_eq!(String::from_utf8_lossy(&csv).lines().count(), 3);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_estimate_config_impact() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-config-impact-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    engine.add_record(
+        "TEST",
+        "IMPACT_1",
+        r#"{"NAME_FULL": "Ida Impact", "PHONE_NUMBER": "555-0144"}"#,
+        None,
+    )?;
+
+    let active = env.get_active_config_id()?;
+    let config = env
+        .get_config_manager()?
+        .create_config_from_id(active)?
+        .export()?;
+    let impact = sz_rust_sdk::analysis::estimate_config_impact(&env, &config, 10)?;
+    assert_eq!(impact.current_config_id, active);
+    assert!(impact.records_sampled >= 1);
+    assert!(impact.is_empty());
+    assert_eq!(env.get_active_config_id()?, active);
+
+    engine.delete_record("TEST", "IMPACT_1", None)?;
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
@@ -175,3 +175,139 @@ fn test_why_operations_error_recovery() -> SzResult<()> {
     ExampleEnvironment::cleanup(env)?;
     Ok(())
 }
+
+/// Test typed search explanations for a matching entity
+#[test]
+#[serial]
+fn test_explain_search() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-explain-search-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    engine.add_record(
+        "TEST",
+        "EXPLAIN_1",
+        r#"{"NAME_FULL":"Maria Explain","DATE_OF_BIRTH":"1980-04-05"}"#,
+        None,
+    )?;
+    let attributes = r#"{"NAME_FULL":"Maria Explain","DATE_OF_BIRTH":"1980-04-05"}"#;
+
+    let results = engine.search_by_attributes_explained(attributes, None, None)?;
+    let first = results.first().expect("search should find the record");
+    let entity_id = first.result.entity.resolved_entity.entity_id;
+    assert_eq!(first.explanation.entity_id, entity_id);
+    assert!(first.explanation.feature_scores.contains_key("NAME"));
+    assert!(!first.explanation.candidate_keys.is_empty());
+
+    let explanation = engine.explain_search(attributes, entity_id)?;
+    assert_eq!(explanation, first.explanation);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test why_pairs over matching, non-matching and missing record pairs
+#[test]
+#[serial]
+fn test_why_pairs() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-why-pairs-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let record = r#"{"NAME_FULL": "Rowan Pike", "PHONE_NUMBER": "555-0513"}"#;
+    engine.add_record("TEST", "PAIR_1", record, None)?;
+    engine.add_record("TEST", "PAIR_2", record, None)?;
+    engine.add_record(
+        "TEST",
+        "PAIR_3",
+        r#"{"NAME_FULL": "Ellis Moreno", "PHONE_NUMBER": "555-0999"}"#,
+        None,
+    )?;
+
+    let key = |id: &str| RecordKey::new("TEST", id);
+    let pairs = [
+        (key("PAIR_1")?, key("PAIR_2")?),
+        (key("PAIR_1")?, key("PAIR_3")?),
+        (key("PAIR_1")?, key("PAIR_MISSING")?),
+    ];
+    let results = sz_rust_sdk::analysis::why_pairs(engine.as_ref(), &pairs, 2);
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[1].record_2, key("PAIR_3")?);
+    assert!(
+        results[0]
+            .result
+            .as_ref()
+            .is_ok_and(|why| why.same_entity())
+    );
+    assert!(
+        results[1]
+            .result
+            .as_ref()
+            .is_ok_and(|why| !why.same_entity())
+    );
+    assert!(results[2].result.is_err());
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that repeated why_entities calls are answered from the analysis cache
+#[test]
+#[serial]
+fn test_analysis_cache() -> SzResult<()> {
+    use std::time::Duration;
+    use sz_rust_sdk::analysis_cache::AnalysisCache;
+
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-analysis-cache-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    engine.add_record(
+        "TEST",
+        "CACHE_1",
+        r#"{"NAME_FULL": "Quinn Cache", "PHONE_NUMBER": "555-0611"}"#,
+        None,
+    )?;
+    engine.add_record(
+        "TEST",
+        "CACHE_2",
+        r#"{"NAME_FULL": "Reese Other", "PHONE_NUMBER": "555-0622"}"#,
+        None,
+    )?;
+    let entity_id = |id: &str| -> SzResult<EntityId> {
+        let entity = engine.get_entity(EntityRef::from_record("TEST", id), None)?;
+        let entity: serde_json::Value = serde_json::from_str(&entity)?;
+        Ok(EntityId(
+            entity["RESOLVED_ENTITY"]["ENTITY_ID"]
+                .as_i64()
+                .expect("entity ID"),
+        ))
+    };
+    let (entity_1, entity_2) = (entity_id("CACHE_1")?, entity_id("CACHE_2")?);
+
+    let cache = AnalysisCache::new(Duration::from_secs(60));
+    env.add_interceptor(Box::new(cache.clone()));
+    let first = engine.why_entities(entity_1, entity_2, None)?;
+    assert_eq!(engine.why_entities(entity_1, entity_2, None)?, first);
+    assert_eq!(cache.stats().hits, 1);
+    assert_eq!(cache.stats().misses, 1);
+
+    let info = engine.add_record(
+        "TEST",
+        "CACHE_1",
+        r#"{"NAME_FULL": "Quinn Cache", "PHONE_NUMBER": "555-0633"}"#,
+        Some(SzFlags::WITH_INFO),
+    )?;
+    assert_eq!(cache.invalidate_info(&info)?, 1);
+    engine.why_entities(entity_1, entity_2, None)?;
+    assert_eq!(cache.stats().misses, 2);
+
+    env.clear_interceptors();
+    engine.delete_record("TEST", "CACHE_1", None)?;
+    engine.delete_record("TEST", "CACHE_2", None)?;
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
@@ -4,7 +4,12 @@
 //! mirroring the C# SzCoreEngineWriteTest.cs test patterns.
 
 use serial_test::serial;
+use std::sync::Arc;
 use sz_rust_sdk::helpers::ExampleEnvironment;
+use sz_rust_sdk::ingest::{MemoryRecordHashStore, RecordHashStore};
+use sz_rust_sdk::models::{ResolutionOutcome, WithInfoResult};
+use sz_rust_sdk::outbox::Outbox;
+use sz_rust_sdk::policy::{RecordValidation, SzPolicy};
 use sz_rust_sdk::prelude::*;
 
 /// Test add record with invalid data source
@@ -238,3 +243,623 @@ fn test_delete_record_info_dispatch() -> SzResult<()> {
     ExampleEnvironment::cleanup(env)?;
     Ok(())
 }
+
+/// Test that replace_record swaps the record and merges both info documents
+#[test]
+#[serial]
+fn test_replace_record() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-replace-record-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+
+    engine.add_record(
+        "TEST",
+        "REPL_1",
+        r#"{"NAME_FULL": "Replace Me", "PHONE_NUMBER": "555-0100"}"#,
+        None,
+    )?;
+
+    let no_info = engine.replace_record("TEST", "REPL_1", r#"{"NAME_FULL": "Replaced"}"#, None)?;
+    assert_eq!(no_info, SZ_NO_INFO);
+
+    let info = engine.replace_record(
+        "TEST",
+        "REPL_1",
+        r#"{"NAME_FULL": "Replaced Again"}"#,
+        Some(SzFlags::WITH_INFO),
+    )?;
+    let info: serde_json::Value = serde_json::from_str(&info)?;
+    assert_eq!(info["RECORD_ID"], "REPL_1");
+    assert!(info["AFFECTED_ENTITIES"].is_array());
+
+    let record = engine.get_record("TEST", "REPL_1", None)?;
+    assert!(record.contains("Replaced Again"));
+    assert!(!record.contains("555-0100"));
+
+    // A failed add restores the previous version
+    let result = engine.replace_record("TEST", "REPL_1", "not json", None);
+    assert!(result.is_err());
+    let record = engine.get_record("TEST", "REPL_1", None)?;
+    assert!(record.contains("Replaced Again"));
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that add_record_if_changed skips records whose content is unchanged
+#[test]
+#[serial]
+fn test_add_record_if_changed() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-add-if-changed-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+
+    let record = r#"{"NAME_FULL": "Upsert Person", "PHONE_NUMBER": "555-0101"}"#;
+    let outcome = engine.add_record_if_changed("TEST", "UPSERT_1", record, None)?;
+    assert!(matches!(outcome, UpsertOutcome::Added(_)));
+
+    // Same content with the key embedded and fields reordered
+    let same = r#"{"RECORD_ID":"UPSERT_1","PHONE_NUMBER":"555-0101","DATA_SOURCE":"TEST","NAME_FULL":"Upsert Person"}"#;
+    let outcome = engine.add_record_if_changed("TEST", "UPSERT_1", same, None)?;
+    assert_eq!(outcome, UpsertOutcome::Unchanged);
+    assert!(!outcome.is_changed());
+
+    let changed = r#"{"NAME_FULL": "Upsert Person", "PHONE_NUMBER": "555-0102"}"#;
+    let outcome =
+        engine.add_record_if_changed("TEST", "UPSERT_1", changed, Some(SzFlags::WITH_INFO))?;
+    assert!(matches!(outcome, UpsertOutcome::Updated(_)));
+    assert!(
+        outcome
+            .info()
+            .is_some_and(|info| info.contains("AFFECTED_ENTITIES"))
+    );
+    let stored = engine.get_record("TEST", "UPSERT_1", None)?;
+    assert!(stored.contains("555-0102"));
+
+    let reader = env.get_engine_scoped(EngineScope::ReadOnly)?;
+    let result = reader.add_record_if_changed("TEST", "UPSERT_1", changed, None);
+    assert!(matches!(result, Err(SzError::NotPermitted(_))));
+
+    drop(reader);
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test summarizing the WITH_INFO responses of an add that creates an entity,
+/// an add that merges into it, and a delete
+#[test]
+#[serial]
+fn test_with_info_summarize() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-with-info-summary-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let record = r#"{"NAME_FULL": "Summary Person", "PHONE_NUMBER": "555-0199", "EMAIL_ADDRESS": "summary@example.com"}"#;
+
+    let info = engine.add_record("TEST", "SUMMARY_1", record, Some(SzFlags::WITH_INFO))?;
+    let summary = WithInfoResult::from_json(&info)?.summarize(engine.as_ref())?;
+    assert!(matches!(
+        summary.outcomes[0],
+        ResolutionOutcome::Created { .. }
+    ));
+
+    let info = engine.add_record("TEST", "SUMMARY_2", record, Some(SzFlags::WITH_INFO))?;
+    let summary = WithInfoResult::from_json(&info)?.summarize(engine.as_ref())?;
+    assert!(matches!(
+        summary.outcomes[0],
+        ResolutionOutcome::Merged { .. }
+    ));
+    assert!(summary.to_string().contains("record merged into entity"));
+
+    let info = engine.delete_record("TEST", "SUMMARY_2", Some(SzFlags::WITH_INFO))?;
+    let summary = WithInfoResult::from_json(&info)?.summarize(engine.as_ref())?;
+    assert_eq!(summary.outcomes[0], ResolutionOutcome::RecordRemoved);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that dry-run mode predicts WITH_INFO results without writing
+#[test]
+#[serial]
+fn test_dry_run() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-dry-run-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let record = r#"{"NAME_FULL": "Dry Run Person", "PHONE_NUMBER": "555-0142", "EMAIL_ADDRESS": "dryrun@example.com"}"#;
+    engine.add_record("TEST", "DRY_RUN_1", record, None)?;
+    let existing = engine.get_entity(
+        EntityRef::Record {
+            data_source: "TEST",
+            record_id: "DRY_RUN_1",
+        },
+        None,
+    )?;
+    let existing_id =
+        serde_json::from_str::<serde_json::Value>(&existing)?["RESOLVED_ENTITY"]["ENTITY_ID"]
+            .as_i64()
+            .expect("entity ID");
+
+    env.set_dry_run(true);
+    assert!(env.dry_run());
+
+    let info = engine.add_record("TEST", "DRY_RUN_2", record, Some(SzFlags::WITH_INFO))?;
+    let document: serde_json::Value = serde_json::from_str(&info)?;
+    assert_eq!(document["DRY_RUN"], true);
+    let result = WithInfoResult::from_json(&info)?;
+    assert_eq!(result.affected_entity_ids(), vec![existing_id]);
+    assert!(engine.get_record("TEST", "DRY_RUN_2", None).is_err());
+
+    // Invalid records are still rejected
+    assert!(
+        engine
+            .add_record("TEST", "DRY_RUN_3", "not json", None)
+            .is_err()
+    );
+
+    engine.delete_record("TEST", "DRY_RUN_1", None)?;
+    assert!(engine.get_record("TEST", "DRY_RUN_1", None).is_ok());
+
+    env.set_dry_run(false);
+    engine.add_record("TEST", "DRY_RUN_2", record, None)?;
+    assert!(engine.get_record("TEST", "DRY_RUN_2", None).is_ok());
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that a data source policy supplies flags, validation, dry-run and
+/// info routing
+#[test]
+#[serial]
+fn test_data_source_policy() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-policy-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let outbox_path = std::env::temp_dir().join(format!("sz_policy_test_{}", std::process::id()));
+    let outbox = Arc::new(Outbox::open(&outbox_path)?);
+
+    env.set_policy(
+        "test",
+        Some(
+            SzPolicy::new()
+                .with_validation(RecordValidation::Keys)
+                .with_info_outbox(outbox.clone()),
+        ),
+    );
+    assert!(env.policy("TEST").is_some());
+
+    let info = engine.add_record("TEST", "POLICY_1", r#"{"NAME_FULL": "Pol Icy"}"#, None)?;
+    assert!(info.contains("AFFECTED_ENTITIES"));
+    assert_eq!(outbox.pending(), 1);
+
+    let mismatched = engine.add_record(
+        "TEST",
+        "POLICY_2",
+        r#"{"RECORD_ID": "OTHER", "NAME_FULL": "Pol Icy"}"#,
+        None,
+    );
+    assert!(matches!(mismatched, Err(SzError::BadInput(_))));
+    assert!(engine.get_record("TEST", "POLICY_2", None).is_err());
+
+    env.set_policy("TEST", Some(SzPolicy::new().with_dry_run(true)));
+    engine.delete_record("TEST", "POLICY_1", None)?;
+    assert!(engine.get_record("TEST", "POLICY_1", None).is_ok());
+    let info = engine.reevaluate_record("test", "POLICY_1", Some(SzFlags::WITH_INFO))?;
+    assert!(info.contains("DRY_RUN"));
+    let entity: serde_json::Value = serde_json::from_str(&engine.get_entity(
+        EntityRef::Record {
+            data_source: "TEST",
+            record_id: "POLICY_1",
+        },
+        Some(SzFlags::empty()),
+    )?)?;
+    let entity_id = EntityId(entity["RESOLVED_ENTITY"]["ENTITY_ID"].as_i64().unwrap());
+    let info = engine.reevaluate_entity(entity_id, Some(SzFlags::WITH_INFO))?;
+    assert!(info.contains("DRY_RUN"));
+
+    env.set_policy("TEST", None);
+    assert!(env.policy("TEST").is_none());
+    engine.delete_record("TEST", "POLICY_1", None)?;
+    assert!(engine.get_record("TEST", "POLICY_1", None).is_err());
+    assert_eq!(outbox.pending(), 1);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    let _ = std::fs::remove_file(&outbox_path);
+    let _ = std::fs::remove_file(outbox_path.with_extension("ack"));
+    Ok(())
+}
+
+/// Test that add_record_if_changed_with_hashes compares against stored hashes
+/// without reading the record back
+#[test]
+#[serial]
+fn test_add_record_if_changed_with_hashes() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-add-if-changed-hashes-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let hashes = MemoryRecordHashStore::new();
+
+    let record = r#"{"NAME_FULL": "Hashed Person", "PHONE_NUMBER": "555-0201"}"#;
+    let outcome =
+        engine.add_record_if_changed_with_hashes("TEST", "HASHED_1", record, None, &hashes)?;
+    assert!(matches!(outcome, UpsertOutcome::Added(_)));
+    assert_eq!(hashes.len(), 1);
+
+    // The stored hash answers without the engine: the deleted record still
+    // compares as unchanged until its hash is removed
+    engine.delete_record("TEST", "HASHED_1", None)?;
+    let outcome =
+        engine.add_record_if_changed_with_hashes("TEST", "HASHED_1", record, None, &hashes)?;
+    assert_eq!(outcome, UpsertOutcome::Unchanged);
+    hashes.remove(&RecordKey::new("TEST", "HASHED_1")?)?;
+    let outcome =
+        engine.add_record_if_changed_with_hashes("TEST", "HASHED_1", record, None, &hashes)?;
+    assert!(matches!(outcome, UpsertOutcome::Added(_)));
+
+    let changed = r#"{"NAME_FULL": "Hashed Person", "PHONE_NUMBER": "555-0202"}"#;
+    let outcome =
+        engine.add_record_if_changed_with_hashes("TEST", "HASHED_1", changed, None, &hashes)?;
+    assert!(matches!(outcome, UpsertOutcome::Updated(_)));
+    assert!(
+        engine
+            .get_record("TEST", "HASHED_1", None)?
+            .contains("555-0202")
+    );
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that a failed batch reverses its applied operations when asked to
+#[test]
+#[serial]
+fn test_apply_batch_compensation() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-apply-batch-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    engine.add_record("TEST", "BATCH_OLD", r#"{"NAME_FULL": "Old Timer"}"#, None)?;
+
+    let ops = || {
+        vec![
+            RecordOp::delete("TEST", "BATCH_OLD"),
+            RecordOp::add("TEST", "BATCH_NEW", r#"{"NAME_FULL": "New Comer"}"#),
+            RecordOp::add("TEST", "BATCH_BAD", "not json"),
+        ]
+    };
+
+    let report = engine.apply_batch(ops(), BatchFailureAction::Compensate);
+    assert_eq!(report.applied, 2);
+    assert_eq!(report.failure.as_ref().map(|f| f.index), Some(2));
+    assert_eq!(report.compensated, 2);
+    assert!(report.is_rolled_back());
+    assert!(
+        engine
+            .get_record("TEST", "BATCH_OLD", None)?
+            .contains("Old Timer")
+    );
+    assert!(engine.get_record("TEST", "BATCH_NEW", None).is_err());
+
+    let report = engine.apply_batch(ops(), BatchFailureAction::Stop);
+    assert_eq!(report.applied, 2);
+    assert!(!report.is_complete());
+    assert!(!report.is_rolled_back());
+    assert!(engine.get_record("TEST", "BATCH_OLD", None).is_err());
+    engine.get_record("TEST", "BATCH_NEW", None)?;
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that stewardship overrides split, merge and relate records
+#[test]
+#[serial]
+fn test_stewardship_overrides() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-stewardship-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let record = r#"{"NAME_FULL": "Stew Ard", "PHONE_NUMBER": "555-0177", "EMAIL_ADDRESS": "stew@example.com"}"#;
+    engine.add_record("TEST", "STEW_1", record, None)?;
+    engine.add_record("TEST", "STEW_2", record, None)?;
+    engine.add_record(
+        "TEST",
+        "STEW_ORG",
+        r#"{"NAME_ORG": "Stewart Holdings"}"#,
+        None,
+    )?;
+    let first = RecordKey::new("TEST", "STEW_1")?;
+    let second = RecordKey::new("TEST", "STEW_2")?;
+    let org = RecordKey::new("TEST", "STEW_ORG")?;
+    let entity_of = |key: &RecordKey| -> SzResult<i64> {
+        let entity = engine.get_entity(EntityRef::from(key), None)?;
+        Ok(
+            serde_json::from_str::<serde_json::Value>(&entity)?["RESOLVED_ENTITY"]["ENTITY_ID"]
+                .as_i64()
+                .expect("entity ID"),
+        )
+    };
+    assert_eq!(entity_of(&first)?, entity_of(&second)?);
+
+    let info = engine.force_unmerge(&second, Some(SzFlags::WITH_INFO))?;
+    assert!(info.contains("AFFECTED_ENTITIES"));
+    assert_ne!(entity_of(&first)?, entity_of(&second)?);
+    assert!(
+        engine
+            .get_record("TEST", "STEW_2", None)?
+            .contains("STEWARD")
+    );
+
+    engine.force_merge(&first, &org, None)?;
+    assert_eq!(entity_of(&first)?, entity_of(&org)?);
+
+    engine.force_relate(&second, &org, "EMPLOYER", None)?;
+    let related = engine.get_entity(EntityRef::from(&second), None)?;
+    assert!(related.contains("DISCLOSED"));
+    assert!(matches!(
+        engine.force_relate(&org, &org, "SELF", None),
+        Err(SzError::BadInput(_))
+    ));
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that the G2 method names call their v4 replacements
+#[test]
+#[serial]
+#[allow(deprecated)]
+fn test_g2_compat_methods() -> SzResult<()> {
+    use sz_rust_sdk::compat::G2EngineCompat;
+
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-g2-compat-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+
+    let info: serde_json::Value = serde_json::from_str(&engine.add_record_with_info(
+        "TEST",
+        "G2_1",
+        r#"{"NAME_FULL": "Gina Two"}"#,
+    )?)?;
+    assert_eq!(info["RECORD_ID"], "G2_1");
+    let by_record = engine.get_entity_by_record_id("TEST", "G2_1", None)?;
+    let entity_id =
+        serde_json::from_str::<serde_json::Value>(&by_record)?["RESOLVED_ENTITY"]["ENTITY_ID"]
+            .as_i64()
+            .expect("entity ID");
+    assert_eq!(
+        engine.get_entity_by_entity_id(EntityId(entity_id), None)?,
+        by_record
+    );
+    assert!(
+        engine
+            .delete_record_with_info("TEST", "G2_1")?
+            .contains("G2_1")
+    );
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that WITH_INFO responses are turned into entity events
+#[test]
+#[serial]
+fn test_entity_change_stream() -> SzResult<()> {
+    use sz_rust_sdk::events::{EntityChangeStream, EntityEvent};
+
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-entity-events-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let record = r#"{"NAME_FULL": "Evan Events", "PHONE_NUMBER": "555-0161", "EMAIL_ADDRESS": "evan@example.com"}"#;
+
+    let mut changes = EntityChangeStream::new(&*engine);
+    let info = engine.add_record("TEST", "EVENT_1", record, Some(SzFlags::WITH_INFO))?;
+    let events = changes.process(&info)?;
+    let [EntityEvent::EntityUpdated { entity_id }] = events[..] else {
+        panic!("unexpected events {events:?}");
+    };
+    let info = engine.add_record("TEST", "EVENT_2", record, Some(SzFlags::WITH_INFO))?;
+    assert_eq!(
+        changes.process(&info)?,
+        [EntityEvent::EntityUpdated { entity_id }]
+    );
+    assert_eq!(changes.known_records(), 2);
+
+    engine.delete_record("TEST", "EVENT_1", None)?;
+    let info = engine.delete_record("TEST", "EVENT_2", Some(SzFlags::WITH_INFO))?;
+    assert_eq!(
+        changes.process(&info)?,
+        [EntityEvent::EntityRemoved { entity_id }]
+    );
+    assert_eq!(changes.known_records(), 0);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_entity_sync_to_json_files() -> SzResult<()> {
+    use sz_rust_sdk::sync::{EntitySync, JsonFileProjection};
+
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-entity-sync-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let record = r#"{"NAME_FULL": "Sybil Sync", "PHONE_NUMBER": "555-0162", "EMAIL_ADDRESS": "sybil@example.com"}"#;
+    let info = engine.add_record("TEST", "SYNC_1", record, Some(SzFlags::WITH_INFO))?;
+    let entity_id = WithInfoResult::from_json(&info)?.affected_entity_ids()[0];
+
+    let dir = std::env::temp_dir().join(format!("sz_entity_sync_{}", std::process::id()));
+    let mut sync = EntitySync::new(&*engine, JsonFileProjection::new(&dir)?);
+    assert!(sync.backfill()?.upserted >= 1);
+    assert!(sync.projection().path(entity_id).exists());
+
+    let info = engine.add_record("TEST", "SYNC_2", record, Some(SzFlags::WITH_INFO))?;
+    assert_eq!(sync.apply(&info)?.upserted, 1);
+    let written = std::fs::read_to_string(sync.projection().path(entity_id))
+        .map_err(|e| SzError::unknown(e.to_string()))?;
+    assert!(written.contains("SYNC_2"));
+
+    engine.delete_record("TEST", "SYNC_1", None)?;
+    let info = engine.delete_record("TEST", "SYNC_2", Some(SzFlags::WITH_INFO))?;
+    assert_eq!(sync.apply(&info)?.removed, 1);
+    assert!(!sync.projection().path(entity_id).exists());
+
+    let _ = std::fs::remove_dir_all(dir);
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_dual_write_stream_wins_over_older_backfill() -> SzResult<()> {
+    use sz_rust_sdk::ingest::DualWriteLoader;
+
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-dual-write-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let loader = DualWriteLoader::new(&*engine);
+
+    let stream = r#"{"DATA_SOURCE": "TEST", "RECORD_ID": "DUAL_1", "NAME_FULL": "Dana Newer", "RECORD_TIMESTAMP": "2026-03-02T00:00:00Z"}"#;
+    let old_backfill = r#"{"DATA_SOURCE": "TEST", "RECORD_ID": "DUAL_1", "NAME_FULL": "Dana Older", "RECORD_TIMESTAMP": "2026-03-01T00:00:00Z"}"#;
+    assert!(loader.stream_record(stream)?.is_some());
+    assert!(loader.backfill_record(old_backfill)?.is_none());
+    let stored = engine.get_record("TEST", "DUAL_1", None)?;
+    assert!(stored.contains("Dana Newer"));
+
+    let backfill = r#"{"DATA_SOURCE": "TEST", "RECORD_ID": "DUAL_2", "NAME_FULL": "Ezra Backfill", "RECORD_TIMESTAMP": "2026-03-02T00:00:00Z"}"#;
+    let old_stream = r#"{"DATA_SOURCE": "TEST", "RECORD_ID": "DUAL_2", "NAME_FULL": "Ezra Stale", "RECORD_TIMESTAMP": "2026-03-01T00:00:00Z"}"#;
+    assert!(loader.backfill_record(backfill)?.is_some());
+    assert!(loader.stream_record(old_stream)?.is_none());
+
+    assert!(loader.stream_delete("TEST", "DUAL_2", None)?.is_some());
+    assert!(loader.backfill_record(backfill)?.is_none());
+    // 2026-03-02T00:00:00Z
+    assert_eq!(loader.watermarks().get("TEST"), Some(&1_772_409_600_000));
+
+    let summary = loader.cutover();
+    assert_eq!(summary.backfill_applied, 1);
+    assert_eq!(summary.backfill_superseded, 2);
+    assert_eq!(summary.stream_applied, 2);
+    assert_eq!(summary.stream_superseded, 1);
+
+    engine.delete_record("TEST", "DUAL_1", None)?;
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+#[cfg(feature = "encryption")]
+#[test]
+#[serial]
+fn test_dual_write_encrypted_checkpoint() -> SzResult<()> {
+    use sz_rust_sdk::encryption::PayloadCipher;
+    use sz_rust_sdk::ingest::{DualWriteLoader, WatermarkStore};
+
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-dual-write-encrypted-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let path = std::env::temp_dir().join(format!(
+        "sz_dual_write_watermarks_{}.json",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_file(&path);
+    let cipher = PayloadCipher::new(&[8u8; 32])?;
+
+    let store = WatermarkStore::open_encrypted(&path, cipher.clone())?;
+    let loader = DualWriteLoader::new(&*engine).with_watermark_store(store);
+    let backfill = r#"{"DATA_SOURCE": "TEST", "RECORD_ID": "DUAL_ENC_1", "NAME_FULL": "Ivy Sealed", "RECORD_TIMESTAMP": 42}"#;
+    loader.backfill_from_reader(backfill.as_bytes(), None)?;
+
+    let stored = std::fs::read_to_string(&path).map_err(|e| SzError::unknown(e.to_string()))?;
+    assert!(!stored.contains("TEST"));
+    let reopened = WatermarkStore::open_encrypted(&path, cipher)?;
+    assert_eq!(reopened.get("TEST"), Some(42));
+
+    std::fs::remove_file(&path).ok();
+    engine.delete_record("TEST", "DUAL_ENC_1", None)?;
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+#[test]
+#[serial]
+fn test_call_context_adds_flags() -> SzResult<()> {
+    use sz_rust_sdk::context::SzCallContext;
+
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-call-context-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let record = r#"{"NAME_FULL": "Cleo Context"}"#;
+
+    assert_eq!(
+        engine.add_record("TEST", "CTX_1", record, None)?,
+        SZ_NO_INFO
+    );
+    let info = SzCallContext::with_flags(SzFlags::WITH_INFO, || {
+        engine.add_record("TEST", "CTX_1", record, None)
+    })?;
+    assert!(info.contains("AFFECTED_ENTITIES"));
+    // Flags passed to the call replace the context's
+    let explicit = SzCallContext::with_flags(SzFlags::WITH_INFO, || {
+        engine.add_record("TEST", "CTX_1", record, Some(SzFlags::empty()))
+    })?;
+    assert_eq!(explicit, SZ_NO_INFO);
+
+    engine.delete_record("TEST", "CTX_1", None)?;
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test the record methods that take a typed `RecordKey`
+#[test]
+#[serial]
+fn test_record_methods_by_key() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-record-by-key-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let key = RecordKey::new("test", "KEY_1")?;
+
+    engine.add_record_by_key(&key, r#"{"NAME_FULL": "Kim Key"}"#, None)?;
+    assert!(engine.get_record_by_key(&key, None)?.contains("KEY_1"));
+    let info = engine.reevaluate_record_by_key(&key, Some(SzFlags::WITH_INFO))?;
+    assert!(info.contains("AFFECTED_ENTITIES"));
+    engine.replace_record_by_key(&key, r#"{"NAME_FULL": "Kim Keyes"}"#, None)?;
+    assert!(engine.get_record_by_key(&key, None)?.contains("Keyes"));
+
+    engine.delete_record_by_key(&key, None)?;
+    assert!(matches!(
+        engine.get_record_by_key(&key, None),
+        Err(SzError::NotFound(_))
+    ));
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
@@ -4,6 +4,7 @@
 //! repository information, and system diagnostics, mirroring the C# SzDiagnosticTest.cs test patterns.
 
 use serial_test::serial;
+use sz_rust_sdk::health::{HealthComponent, HealthStatus, SzHealth};
 use sz_rust_sdk::helpers::ExampleEnvironment;
 use sz_rust_sdk::prelude::*;
 
@@ -96,9 +97,36 @@ fn test_purge_repository() -> SzResult<()> {
     let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
 
     let env = ExampleEnvironment::initialize("sz-rust-sdk-diagnostic-purge-test")?;
-    let _diagnostic = env.get_diagnostic()?;
+    let diagnostic = env.get_diagnostic()?;
     eprintln!("Diagnostic available for purge testing");
 
+    assert!(env.purge_enabled());
+    diagnostic.purge_repository(PurgeConfirmation::I_UNDERSTAND_THIS_DELETES_ALL_DATA)?;
+
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that purge is refused when disabled on the environment
+#[test]
+#[serial]
+fn test_purge_repository_disabled() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-diagnostic-purge-disabled-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    engine.add_record("TEST", "PURGE_1001", r#"{"NAME_FULL":"Kept Record"}"#, None)?;
+
+    env.set_purge_enabled(false);
+    let diagnostic = env.get_diagnostic()?;
+    let result = diagnostic.purge_repository(PurgeConfirmation::I_UNDERSTAND_THIS_DELETES_ALL_DATA);
+    assert!(matches!(result, Err(SzError::BadInput(_))));
+
+    // The record survives the refused purge
+    engine.get_record("TEST", "PURGE_1001", None)?;
+
+    env.set_purge_enabled(true);
+    drop(engine);
     ExampleEnvironment::cleanup(env)?;
     Ok(())
 }
@@ -198,3 +226,77 @@ fn test_diagnostic_extreme_parameters() -> SzResult<()> {
     ExampleEnvironment::cleanup(env)?;
     Ok(())
 }
+
+/// Test that a healthy environment passes every health check
+#[test]
+#[serial]
+fn test_health_check() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-health-check-test")?;
+    let report = SzHealth::check(&env);
+    assert!(report.is_live(), "{report:?}");
+    assert!(report.is_ready(), "{report:?}");
+    for component in [
+        HealthComponent::Engine,
+        HealthComponent::Search,
+        HealthComponent::License,
+        HealthComponent::Database,
+        HealthComponent::Config,
+    ] {
+        let check = report.check(component).expect("every check should run");
+        assert_ne!(check.status, HealthStatus::Unhealthy, "{check:?}");
+    }
+
+    let json = serde_json::to_value(&report)?;
+    assert!(json["checks"].as_array().is_some_and(|c| c.len() == 5));
+
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that a support bundle holds every entry and the redacted error history
+#[cfg(feature = "support-bundle")]
+#[test]
+#[serial]
+fn test_collect_support_bundle() -> SzResult<()> {
+    use sz_rust_sdk::support::SzDiagnostics;
+
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-support-bundle-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    assert!(engine.get_record("TEST", "NO_SUCH_RECORD", None).is_err());
+    let failure = env
+        .recent_errors()
+        .pop()
+        .expect("the failure should be recorded");
+    assert_eq!(failure.operation, "get_record");
+
+    let path = std::env::temp_dir().join(format!("sz-support-{}.zip", std::process::id()));
+    let manifest = SzDiagnostics::new(env.clone()).collect_support_bundle(&path)?;
+    assert!(manifest.is_complete(), "{manifest:?}");
+
+    let archive = zip::ZipArchive::new(std::fs::File::open(&path).expect("open bundle"))
+        .expect("read bundle");
+    let mut names: Vec<_> = archive.file_names().collect();
+    names.sort();
+    assert_eq!(
+        names,
+        [
+            "config.json",
+            "errors.json",
+            "license.json",
+            "manifest.json",
+            "repository_info.json",
+            "stats.json",
+            "version.json",
+        ]
+    );
+    drop(archive);
+    let _ = std::fs::remove_file(&path);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
@@ -207,6 +207,124 @@ fn test_environment_lifecycle() -> SzResult<()> {
     Ok(())
 }
 
+/// Test that the init report covers every component and the database
+#[test]
+#[serial]
+fn test_environment_init_report() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-environment-report-test")?;
+    let report = env.init_report();
+    let names: Vec<_> = report.components.iter().map(|c| c.name).collect();
+    assert_eq!(names, ["engine", "config_manager", "product"]);
+    assert!(report.components[1].already_initialized);
+    assert!(report.is_ok(), "{report:?}");
+    assert_eq!(report.active_config_id, Some(env.get_active_config_id()?));
+    assert!(report.warnings.is_empty(), "{:?}", report.warnings);
+
+    let (same, again) = SzEnvironmentCore::get_instance_with_report(
+        "sz-rust-sdk-environment-report-test",
+        env.get_ini_params(),
+        false,
+    )?;
+    assert!(std::sync::Arc::ptr_eq(&env, &same));
+    assert!(again.components.iter().all(|c| c.already_initialized));
+    assert!(again.to_string().contains("database connected"));
+    drop(same);
+
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that failed engine calls are kept in a bounded, queryable history
+#[test]
+#[serial]
+fn test_environment_error_history() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-environment-errors-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    env.clear_error_history();
+    let started = std::time::SystemTime::now();
+    for record_id in ["MISSING_1", "MISSING_2", "MISSING_3"] {
+        assert!(engine.get_record("TEST", record_id, None).is_err());
+    }
+    assert!(
+        engine
+            .get_entity(EntityRef::Id(EntityId(-1)), None)
+            .is_err()
+    );
+
+    let errors = env.recent_errors();
+    assert_eq!(errors.len(), 4);
+    assert_eq!(errors[0].operation, "get_record");
+    assert!(errors[0].code.is_some());
+    assert_eq!(errors[0].component, Some(SzComponent::Engine));
+    assert!(errors.iter().all(|error| error.occurred_at >= started));
+
+    let get_record = ErrorFilter::new().operation("get_record").since(started);
+    assert_eq!(env.query_errors(&get_record).len(), 3);
+    assert_eq!(
+        env.query_errors(&ErrorFilter::new().code(errors[0].code.unwrap_or_default()))
+            .len(),
+        3
+    );
+
+    env.set_error_history_capacity(2);
+    assert_eq!(env.error_history_capacity(), 2);
+    let kept = env.recent_errors();
+    assert_eq!(kept.len(), 2);
+    assert_eq!(kept[1].operation, "get_entity");
+    env.clear_error_history();
+    assert!(env.recent_errors().is_empty());
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that a failure is counted once when the call goes through nested
+/// engine calls or a timeout worker thread
+#[test]
+#[serial]
+fn test_environment_error_history_counts_each_failure_once() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-environment-error-count-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    env.clear_error_history();
+
+    let missing = EntityRef::Id(EntityId(-1));
+    assert!(engine.get_entity(missing.clone(), None).is_err());
+    assert!(engine.search_by_attributes("{", None, None).is_err());
+    env.set_operation_timeout(
+        TimedOperation::GetEntity,
+        Some(std::time::Duration::from_secs(60)),
+    );
+    assert!(engine.get_entity(missing, None).is_err());
+    env.set_operation_timeout(TimedOperation::GetEntity, None);
+
+    let operations: Vec<&str> = env
+        .recent_errors()
+        .iter()
+        .map(|error| error.operation)
+        .collect();
+    assert_eq!(
+        operations,
+        ["get_entity", "search_by_attributes", "get_entity"]
+    );
+    let get_entity = ErrorFilter::new().operation("get_entity");
+    assert_eq!(env.query_errors(&get_entity).len(), 2);
+    assert!(
+        env.query_errors(&ErrorFilter::new().operation("get_entity_bytes"))
+            .is_empty()
+    );
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
 /// Test environment error recovery
 /// Tests error handling and recovery scenarios
 #[test]
@@ -427,3 +545,207 @@ fn test_config_manager_reads_from_database_after_destroy() -> SzResult<()> {
     eprintln!("ConfigManager correctly reads from database after destroy");
     Ok(())
 }
+
+/// Test that repeated add_record calls reuse argument buffers from the pool
+#[test]
+#[serial]
+fn test_buffer_pool_reuses_buffers_across_calls() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-buffer-pool")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    env.set_buffer_pool_config(BufferPoolConfig::default());
+    let before = env.buffer_pool_stats();
+
+    for i in 0..10 {
+        engine.add_record(
+            "TEST",
+            &format!("POOL_{i}"),
+            &format!(r#"{{"NAME_FULL":"Pool Person {i}"}}"#),
+            None,
+        )?;
+    }
+
+    let after = env.buffer_pool_stats();
+    assert!(after.hits - before.hits >= 9);
+    assert!(after.pooled_buffers >= 1);
+
+    env.set_buffer_pool_config(BufferPoolConfig {
+        max_buffers: 0,
+        max_buffer_bytes: 0,
+    });
+    assert_eq!(env.buffer_pool_stats().pooled_buffers, 0);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that scoped engines only allow the methods of their scope
+#[test]
+#[serial]
+fn test_get_engine_scoped() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-engine-scoped")?;
+    let loader = env.get_engine_scoped(EngineScope::Loader)?;
+    let reader = env.get_engine_scoped(EngineScope::ReadOnly)?;
+    let admin = env.get_engine_scoped(EngineScope::Admin)?;
+
+    loader.add_record("TEST", "SCOPE_1", r#"{"NAME_FULL":"Scope Person"}"#, None)?;
+    reader.get_record("TEST", "SCOPE_1", None)?;
+    reader.get_entity(EntityRef::from_record("TEST", "SCOPE_1"), None)?;
+
+    let denied = reader.delete_record("TEST", "SCOPE_1", None);
+    assert!(matches!(denied, Err(SzError::NotPermitted(_))));
+    let denied = loader.prime_engine();
+    assert!(matches!(denied, Err(SzError::NotPermitted(_))));
+    admin.prime_engine()?;
+
+    // The refused delete did not reach the engine
+    reader.get_record("TEST", "SCOPE_1", None)?;
+
+    drop((loader, reader, admin));
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test the engines restricted to the read and load method sets at compile time
+#[test]
+#[serial]
+fn test_get_engine_read_and_load() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-engine-read-load")?;
+    let loader: Box<dyn SzEngineLoad> = env.get_engine_load()?;
+    let reader: Box<dyn SzEngineRead> = env.get_engine_read()?;
+
+    loader.add_record("TEST", "NARROW_1", r#"{"NAME_FULL":"Narrow Person"}"#, None)?;
+    loader.get_record("TEST", "NARROW_1", None)?;
+    reader.get_entity(EntityRef::from_record("TEST", "NARROW_1"), None)?;
+
+    // A read engine works with code written against the read methods
+    let export = sz_rust_sdk::export::ExportGuard::json(&*reader, None)?;
+    export.fetch_next()?;
+    drop(export);
+
+    drop((loader, reader));
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test the fork guard: forking is refused once native modules are initialized
+#[test]
+#[serial]
+fn test_prepare_fork_after_initialization() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    // initialize() already sets up the config manager
+    let env = ExampleEnvironment::initialize("test-prepare-fork")?;
+    let err = env
+        .prepare_fork()
+        .expect_err("fork should be refused after native initialization");
+    assert!(matches!(err, SzError::NotPermitted(_)));
+    assert!(err.to_string().contains("config manager"));
+
+    // Not a forked child: the environment stays usable
+    SzEnvironmentCore::after_fork_child();
+    assert!(!env.is_destroyed());
+    let engine = env.get_engine()?;
+    engine.add_record("TEST", "FORK_1", r#"{"NAME_FULL":"Fork Guard"}"#, None)?;
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that an engine inherited by a forked child refuses native calls
+#[cfg(unix)]
+#[test]
+#[serial]
+fn test_engine_refused_in_forked_child() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-forked-engine")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    // SAFETY: the child only makes one engine call and exits
+    match unsafe { libc::fork() } {
+        -1 => panic!("fork failed"),
+        0 => {
+            let refused = matches!(engine.get_stats(), Err(SzError::Unrecoverable(_)));
+            unsafe { libc::_exit(if refused { 0 } else { 1 }) }
+        }
+        child => {
+            let mut status = 0;
+            assert_eq!(unsafe { libc::waitpid(child, &mut status, 0) }, child);
+            assert!(libc::WIFEXITED(status));
+            assert_eq!(libc::WEXITSTATUS(status), 0);
+        }
+    }
+    // The parent keeps its environment
+    engine.get_stats()?;
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that repeated failures at the native boundary degrade the environment
+#[test]
+#[serial]
+fn test_repeated_boundary_failures_degrade() -> SzResult<()> {
+    use sz_rust_sdk::intercept::{CallOutcome, EngineCall, SzInterceptor};
+
+    /// Panics while the response of a call is handled
+    struct Faulty;
+
+    impl SzInterceptor for Faulty {
+        fn after(&self, _call: &EngineCall<'_>, _outcome: &CallOutcome<'_>) {
+            panic!("faulty response handling");
+        }
+    }
+
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-degraded")?;
+    env.set_poison_threshold(2);
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    env.add_interceptor(Box::new(Faulty));
+    for _ in 0..2 {
+        let err = engine.get_stats().expect_err("panicking call should fail");
+        assert!(matches!(err, SzError::Unhandled(_)));
+    }
+    assert!(env.is_degraded());
+    assert!(env.degraded_reason().unwrap().contains("get_stats"));
+    assert!(matches!(env.get_engine(), Err(SzError::Unrecoverable(_))));
+
+    env.set_poison_threshold(3);
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that ordinary failures do not degrade the environment
+#[test]
+#[serial]
+fn test_bad_input_does_not_degrade() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-not-degraded")?;
+    env.set_poison_threshold(1);
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    for _ in 0..3 {
+        let err = engine
+            .get_record("TEST", "NO_SUCH_RECORD", None)
+            .expect_err("missing record should fail");
+        assert!(err.is_bad_input());
+    }
+    assert!(!env.is_degraded());
+    assert_eq!(env.degraded_reason(), None);
+    env.get_engine()?;
+
+    env.set_poison_threshold(3);
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
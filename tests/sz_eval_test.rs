@@ -0,0 +1,35 @@
+//! Resolution quality evaluation integration tests.
+
+use serial_test::serial;
+use sz_rust_sdk::eval::{self, TruthSet};
+use sz_rust_sdk::helpers::ExampleEnvironment;
+use sz_rust_sdk::prelude::*;
+use sz_rust_sdk::testing::datasets::DatasetSpec;
+
+/// Test scoring a synthetic dataset against its ground truth
+#[test]
+#[serial]
+fn test_load_and_evaluate_dataset() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-eval-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+
+    let dataset = DatasetSpec::new(11)
+        .with_people(40)
+        .with_duplicate_rate(0.3)
+        .generate()?;
+    let truth = TruthSet::from_dataset(&dataset)?;
+    let pairs = dataset.len() * (dataset.len() - 1) / 2;
+    assert_eq!(truth.len(), pairs);
+
+    let report = eval::load_and_evaluate(&*engine, dataset.to_jsonl().as_bytes(), &truth)?;
+    assert_eq!(report.records, dataset.len());
+    assert_eq!(report.overall.total(), pairs as u64);
+    let precision = report.overall.precision().unwrap_or(1.0);
+    assert!(precision > 0.5, "{report}");
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
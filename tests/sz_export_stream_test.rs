@@ -0,0 +1,325 @@
+//! Streaming export integration tests.
+//!
+//! These tests run `SzExporter` against the live engine with the in-memory
+//! `internal://` datastore and check that every loaded record comes back
+//! through the typed export stream.
+
+use serial_test::serial;
+use std::collections::HashSet;
+use sz_rust_sdk::export::{ExportFormat, ExportGuard, SzExporter};
+use sz_rust_sdk::helpers::ExampleEnvironment;
+use sz_rust_sdk::prelude::*;
+
+/// Test that the stream yields every loaded record exactly once
+#[test]
+#[serial]
+fn test_export_entities_stream_returns_all_records() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-export-stream-all")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    for i in 0..20 {
+        engine.add_record(
+            "TEST",
+            &format!("EXS_{i}"),
+            &format!(r#"{{"NAME_FULL":"Person {i}","PHONE_NUMBER":"555-01{i:02}"}}"#),
+            None,
+        )?;
+    }
+
+    let exporter = SzExporter::new(env.clone()).with_prefetch(2);
+    let mut record_ids = HashSet::new();
+    for entity in exporter.export_entities_stream(None)? {
+        for record in entity?.resolved_entity.records {
+            assert!(record_ids.insert(record.record_id));
+        }
+    }
+    assert_eq!(record_ids.len(), 20);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that the async stream yields every loaded record exactly once
+#[cfg(feature = "async-export")]
+#[test]
+#[serial]
+fn test_export_entities_async_returns_all_records() -> SzResult<()> {
+    use futures_util::StreamExt;
+
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-export-stream-async")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    for i in 0..20 {
+        engine.add_record(
+            "TEST",
+            &format!("EXAS_{i}"),
+            &format!(r#"{{"NAME_FULL":"Async Person {i}","PHONE_NUMBER":"555-02{i:02}"}}"#),
+            None,
+        )?;
+    }
+
+    let exporter = SzExporter::new(env.clone()).with_prefetch(2);
+    let mut entities = exporter.export_entities_async(None)?;
+    let mut record_ids = HashSet::new();
+    tokio_test::block_on(async {
+        while let Some(entity) = entities.next().await {
+            for record in entity?.resolved_entity.records {
+                assert!(record_ids.insert(record.record_id));
+            }
+        }
+        Ok::<(), SzError>(())
+    })?;
+    assert_eq!(record_ids.len(), 20);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that the Arrow stream yields a row for every loaded record
+#[cfg(feature = "arrow")]
+#[test]
+#[serial]
+fn test_export_entities_arrow() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-export-arrow")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    for i in 0..15 {
+        engine.add_record(
+            "TEST",
+            &format!("EXA_{i}"),
+            &format!(r#"{{"NAME_FULL":"Arrow Person {i}"}}"#),
+            None,
+        )?;
+    }
+
+    let mut rows = 0;
+    for batch in SzExporter::new(env.clone()).export_entities_arrow(None)? {
+        let batch = batch?;
+        assert_eq!(batch.schema(), sz_rust_sdk::report::record_schema());
+        rows += batch.num_rows();
+    }
+    assert_eq!(rows, 15);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that dropping a partially consumed stream closes the export cleanly
+#[test]
+#[serial]
+fn test_export_entities_stream_early_drop() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-export-stream-drop")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    for i in 0..10 {
+        engine.add_record(
+            "TEST",
+            &format!("EXD_{i}"),
+            &format!(r#"{{"NAME_FULL":"Other Person {i}"}}"#),
+            None,
+        )?;
+    }
+
+    let exporter = SzExporter::new(env.clone()).with_prefetch(1);
+    let mut stream = exporter.export_entities_stream(None)?;
+    assert!(stream.next().transpose()?.is_some());
+    drop(stream);
+
+    // A second export still works after the first was abandoned.
+    let count = exporter.export_entities_stream(None)?.count();
+    assert!(count > 0);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test export_to_writer in both JSON lines and CSV formats
+#[test]
+#[serial]
+fn test_export_to_writer_formats() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-export-to-writer")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    for i in 0..5 {
+        engine.add_record(
+            "TEST",
+            &format!("EXW_{i}"),
+            &format!(r#"{{"NAME_FULL":"Writer Person {i}"}}"#),
+            None,
+        )?;
+    }
+
+    let exporter = SzExporter::new(env.clone());
+
+    let mut json = Vec::new();
+    let written = exporter.export_to_writer(&mut json, ExportFormat::JsonLines, None)?;
+    assert_eq!(written, json.len() as u64);
+    let text = String::from_utf8(json).expect("export is UTF-8");
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).collect();
+    assert!(!lines.is_empty());
+    for line in lines {
+        serde_json::from_str::<serde_json::Value>(line)?;
+    }
+
+    let mut csv = Vec::new();
+    exporter.export_to_writer(
+        &mut csv,
+        ExportFormat::Csv("RESOLVED_ENTITY_ID,DATA_SOURCE,RECORD_ID".to_string()),
+        None,
+    )?;
+    let text = String::from_utf8(csv).expect("export is UTF-8");
+    assert!(
+        text.lines()
+            .next()
+            .unwrap_or_default()
+            .contains("RESOLVED_ENTITY_ID")
+    );
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test loading from and exporting to object storage URLs, using `file://`
+/// objects in place of a bucket
+#[cfg(feature = "object-store")]
+#[test]
+#[serial]
+fn test_object_store_load_and_export() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-export-object-store")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+
+    let dir = std::env::temp_dir();
+    let source = dir.join(format!(
+        "sz_object_store_source_{}.jsonl",
+        std::process::id()
+    ));
+    let target = dir.join(format!(
+        "sz_object_store_export_{}.jsonl",
+        std::process::id()
+    ));
+    let records: String = (0..5)
+        .map(|i| {
+            format!(
+                "{{\"DATA_SOURCE\":\"TEST\",\"RECORD_ID\":\"EXOS_{i}\",\"NAME_FULL\":\"Bucket Person {i}\"}}\n"
+            )
+        })
+        .collect();
+    std::fs::write(&source, records).expect("write source object");
+
+    let summary = sz_rust_sdk::ingest::load_from_object_store(
+        &*engine,
+        &format!("file://{}", source.display()),
+    )?;
+    assert_eq!(summary.records_loaded, 5);
+
+    let written = SzExporter::new(env.clone()).export_to_object_store(
+        &format!("file://{}", target.display()),
+        ExportFormat::JsonLines,
+        None,
+    )?;
+    let text = std::fs::read_to_string(&target).expect("read exported object");
+    assert_eq!(written, text.len() as u64);
+    for i in 0..5 {
+        assert!(text.contains(&format!("EXOS_{i}")));
+    }
+
+    assert!(
+        sz_rust_sdk::ingest::load_from_object_store(&*engine, "ftp://host/records.jsonl")
+            .unwrap_err()
+            .is_bad_input()
+    );
+
+    let _ = std::fs::remove_file(source);
+    let _ = std::fs::remove_file(target);
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that reinitializing closes open exports and invalidates their handles
+#[test]
+#[serial]
+fn test_export_invalidated_by_reinitialize() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-export-invalidated")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    for i in 0..3 {
+        engine.add_record(
+            "TEST",
+            &format!("EXI_{i}"),
+            &format!(r#"{{"NAME_FULL":"Invalidated Person {i}"}}"#),
+            None,
+        )?;
+    }
+
+    let handle = engine.export_json_entity_report(None)?;
+    assert!(!engine.fetch_next(handle)?.is_empty());
+    let sessions = env.open_exports();
+    assert_eq!(sessions.len(), 1);
+    assert_eq!(sessions[0].handle, handle);
+    assert_eq!(sessions[0].rows_fetched, 1);
+
+    env.reinitialize(env.get_active_config_id()?)?;
+    assert!(env.open_exports().is_empty());
+    let err = engine.fetch_next(handle).unwrap_err();
+    assert!(matches!(err, SzError::ExportInvalidated(_)));
+    assert!(err.message().contains("after 1 rows"));
+    engine.close_export_report(handle)?;
+
+    // New exports work after the reinitialize.
+    let handle = engine.export_json_entity_report(None)?;
+    assert!(!engine.fetch_next(handle)?.is_empty());
+    engine.close_export_report(handle)?;
+    assert!(env.open_exports().is_empty());
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that export and config handles are closed when their owners drop
+#[test]
+#[serial]
+fn test_native_handles_closed_on_drop() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-export-guard")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    engine.add_record("TEST", "EXG_1", r#"{"NAME_FULL":"Guarded Person"}"#, None)?;
+    let before = env.native_handle_stats();
+
+    let export = ExportGuard::json(engine.as_ref(), None)?;
+    assert!(!export.fetch_next()?.is_empty());
+    assert_eq!(env.open_exports().len(), 1);
+    drop(export);
+    assert!(env.open_exports().is_empty());
+
+    ExportGuard::csv(engine.as_ref(), "RESOLVED_ENTITY_ID", None)?.close()?;
+
+    let config = env.get_config_manager()?.create_config()?;
+    config.export()?;
+    drop(config);
+
+    let after = env.native_handle_stats();
+    assert_eq!(after.exports_opened, before.exports_opened + 2);
+    assert_eq!(after.open_exports(), before.open_exports());
+    assert!(after.configs_opened > before.configs_opened);
+    assert_eq!(after.open_configs(), before.open_configs());
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
@@ -472,3 +472,30 @@ fn test_default_flag_composition() {
         SzFlags::SEARCH_BY_ATTRIBUTES_ALL.bits()
     );
 }
+
+/// Test that relationship kinds map to the matching relation flags
+#[test]
+#[serial]
+fn test_relationship_kind_entity_flags() {
+    let kinds = RelationshipKind::DISCLOSED | RelationshipKind::POSSIBLY_SAME;
+    assert_eq!(
+        kinds.entity_flags(),
+        SzFlags::ENTITY_INCLUDE_DISCLOSED_RELATIONS
+            | SzFlags::ENTITY_INCLUDE_POSSIBLY_SAME_RELATIONS
+    );
+    assert_eq!(
+        RelationshipKind::all().entity_flags(),
+        SzFlags::ENTITY_INCLUDE_ALL_RELATIONS
+    );
+    assert!(RelationshipKind::empty().entity_flags().is_empty());
+
+    assert_eq!(
+        RelationshipKind::classify(Some("POSSIBLY_RELATED"), false),
+        RelationshipKind::POSSIBLY_RELATED
+    );
+    assert_eq!(
+        RelationshipKind::classify(Some("POSSIBLY_RELATED"), true),
+        RelationshipKind::DISCLOSED
+    );
+    assert!(RelationshipKind::classify(Some("RESOLVED"), false).is_empty());
+}
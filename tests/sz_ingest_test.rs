@@ -0,0 +1,356 @@
+//! Line-delimited JSON ingest integration tests.
+//!
+//! These tests load records through `ingest::load_from_reader` into the live
+//! engine with the in-memory `internal://` datastore.
+
+use serial_test::serial;
+use std::time::Duration;
+use sz_rust_sdk::control::{ControlHandle, Deadline, RunState, SzCancellationToken};
+use sz_rust_sdk::helpers::ExampleEnvironment;
+use sz_rust_sdk::ingest::{
+    self, FieldMapping, LicenseAction, LicenseGuard, Mapping, WatermarkStore,
+};
+use sz_rust_sdk::normalize::Normalizer;
+use sz_rust_sdk::prelude::*;
+use sz_rust_sdk::provenance::{LoadMetadata, LoadSession};
+
+/// Test that every non-blank line is loaded and can be read back
+#[test]
+#[serial]
+fn test_load_from_reader() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-ingest-load")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+
+    let data = concat!(
+        r#"{"DATA_SOURCE":"TEST","RECORD_ID":"ING_1","NAME_FULL":"Ann Lee"}"#,
+        "\n\n",
+        r#"{"DATA_SOURCE":"TEST","RECORD_ID":"ING_2","NAME_FULL":"Bo Chen"}"#,
+        "\n",
+    );
+    let summary = ingest::load_from_reader(&*engine, data.as_bytes())?;
+    assert_eq!(summary.records_loaded, 2);
+    assert_eq!(summary.blank_lines, 1);
+
+    engine.get_record("TEST", "ING_2", None)?;
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that a record without RECORD_ID stops the load with the line number
+#[test]
+#[serial]
+fn test_load_from_reader_reports_bad_line() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-ingest-bad-line")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+
+    let data = concat!(
+        r#"{"DATA_SOURCE":"TEST","RECORD_ID":"ING_3","NAME_FULL":"Cy Diaz"}"#,
+        "\n",
+        r#"{"DATA_SOURCE":"TEST","NAME_FULL":"No Id"}"#,
+        "\n",
+    );
+    let err = ingest::load_from_reader(&*engine, data.as_bytes())
+        .expect_err("record without RECORD_ID should be rejected");
+    assert!(err.is_bad_input());
+    assert!(err.to_string().contains("Line 2"));
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that duplicate records are skipped and never reach the engine
+#[test]
+#[serial]
+fn test_load_from_reader_deduplicated() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-ingest-dedup")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+
+    let data = concat!(
+        r#"{"DATA_SOURCE":"TEST","RECORD_ID":"DUP_1","NAME_FULL":"Ann Lee","EMAIL_ADDRESS":"ann@example.com"}"#,
+        "\n",
+        r#"{"DATA_SOURCE":"TEST","RECORD_ID":"DUP_2","NAME_FULL":"ANN LEE","EMAIL_ADDRESS":" Ann@Example.com"}"#,
+        "\n",
+        r#"{"DATA_SOURCE":"TEST","RECORD_ID":"DUP_3","NAME_FULL":"Bo Chen"}"#,
+        "\n",
+    );
+    let mut filter = ingest::DuplicateFilter::new();
+    let summary = ingest::load_from_reader_deduplicated(&*engine, data.as_bytes(), &mut filter)?;
+    assert_eq!(summary.records_loaded, 2);
+    assert_eq!(summary.duplicates_skipped, 1);
+    assert_eq!(filter.stats().skipped, 1);
+
+    engine.get_record("TEST", "DUP_3", None)?;
+    assert!(engine.get_record("TEST", "DUP_2", None).is_err());
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that a load with a normalizer adds the cleaned records
+#[test]
+#[serial]
+fn test_load_from_reader_with_options_normalized() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-ingest-normalize")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+
+    let data = concat!(
+        r#"{"DATA_SOURCE":"TEST","RECORD_ID":"NORM_1","NAME_FULL":" Ann  Lee ","DATE_OF_BIRTH":"01/02/1980"}"#,
+        "\n",
+        r#"{"DATA_SOURCE":"TEST","RECORD_ID":"NORM_2","NAME_FULL":"Ann Lee","DATE_OF_BIRTH":"1980-01-02"}"#,
+        "\n",
+    );
+    let normalizer = Normalizer::standard();
+    let mut filter = ingest::DuplicateFilter::new();
+    let options = ingest::LoadOptions::new()
+        .with_normalizer(&normalizer)
+        .with_duplicate_filter(&mut filter);
+    let summary = ingest::load_from_reader_with_options(&*engine, data.as_bytes(), options)?;
+    assert_eq!(summary.records_loaded, 1);
+    assert_eq!(summary.duplicates_skipped, 1);
+
+    let record = engine.get_record("TEST", "NORM_1", None)?;
+    assert!(record.contains("1980-01-02"));
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that a paused load waits and a stop request ends it early
+#[test]
+#[serial]
+fn test_load_from_reader_with_control_stop() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-ingest-control")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+
+    let data = concat!(
+        r#"{"DATA_SOURCE":"TEST","RECORD_ID":"ING_4","NAME_FULL":"Di Eng"}"#,
+        "\n",
+        r#"{"DATA_SOURCE":"TEST","RECORD_ID":"ING_5","NAME_FULL":"Ed Fox"}"#,
+        "\n",
+    );
+    let control = ControlHandle::new();
+    control.pause();
+    let summary = std::thread::scope(|scope| {
+        let load = scope
+            .spawn(|| ingest::load_from_reader_with_control(&*engine, data.as_bytes(), &control));
+        std::thread::sleep(Duration::from_millis(100));
+        assert_eq!(control.state(), RunState::Paused);
+        assert!(control.stop(Deadline::after(Duration::from_secs(10))));
+        load.join().expect("load panicked")
+    })?;
+
+    assert!(summary.stopped);
+    assert_eq!(summary.records_loaded, 0);
+    assert_eq!(control.state(), RunState::Stopped);
+    assert!(engine.get_record("TEST", "ING_4", None).is_err());
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that a cancelled token stops a load and refuses further engine calls
+#[test]
+#[serial]
+fn test_load_cancelled_by_token() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-ingest-cancel")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+
+    let data = concat!(
+        r#"{"DATA_SOURCE":"TEST","RECORD_ID":"ING_6","NAME_FULL":"Fa Gil"}"#,
+        "\n",
+    );
+    let token = SzCancellationToken::new();
+    env.set_cancellation_token(Some(token.clone()));
+    token.cancel();
+    let options = ingest::LoadOptions::new().with_cancellation(&token);
+    let summary = ingest::load_from_reader_with_options(&*engine, data.as_bytes(), options)?;
+    assert!(summary.stopped);
+    assert_eq!(summary.records_loaded, 0);
+
+    let result = engine.get_record("TEST", "ING_6", None);
+    assert!(matches!(result, Err(SzError::Cancelled(_))));
+    env.set_cancellation_token(None);
+    assert!(matches!(
+        engine.get_record("TEST", "ING_6", None),
+        Err(SzError::NotFound(_))
+    ));
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that a license guard halts a load before the record limit
+#[test]
+#[serial]
+fn test_load_halts_at_license_limit() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-ingest-license")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+
+    let data = concat!(
+        r#"{"DATA_SOURCE":"TEST","RECORD_ID":"ING_7","NAME_FULL":"Gus Hale"}"#,
+        "\n",
+        r#"{"DATA_SOURCE":"TEST","RECORD_ID":"ING_8","NAME_FULL":"Hal Ives"}"#,
+        "\n",
+        r#"{"DATA_SOURCE":"TEST","RECORD_ID":"ING_9","NAME_FULL":"Ida Jost"}"#,
+        "\n",
+    );
+    let existing = u64::try_from(engine.count_records()?).unwrap_or_default();
+    let guard = LicenseGuard::new(existing + 2, existing);
+    let options = ingest::LoadOptions::new().with_license_guard(&guard);
+    let summary = ingest::load_from_reader_with_options(&*engine, data.as_bytes(), options)?;
+    assert!(summary.stopped);
+    assert!(summary.license_warning);
+    assert!(summary.license_limit_reached);
+    assert_eq!(summary.records_loaded, 2);
+    assert!(matches!(
+        engine.get_record("TEST", "ING_9", None),
+        Err(SzError::NotFound(_))
+    ));
+
+    let guard = guard.with_action(LicenseAction::Warn);
+    let options = ingest::LoadOptions::new().with_license_guard(&guard);
+    let summary = ingest::load_from_reader_with_options(&*engine, data.as_bytes(), options)?;
+    assert!(!summary.stopped);
+    assert!(summary.license_limit_reached);
+    assert_eq!(summary.records_loaded, 3);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that a load session tags records and the tags can be read back
+#[test]
+#[serial]
+fn test_load_session_tags_records() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-ingest-load-session")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+
+    let metadata = LoadMetadata::new("load-test")
+        .with_source_file("people.jsonl")
+        .with_batch_id("1");
+    let session = LoadSession::new(&*engine, metadata.clone());
+    session.add_record("TEST", "PROV_1", r#"{"NAME_FULL":"Eve Ford"}"#, None)?;
+    let data = concat!(
+        r#"{"DATA_SOURCE":"TEST","RECORD_ID":"PROV_2","NAME_FULL":"Gus Hale"}"#,
+        "\n",
+    );
+    session.load_from_reader(data.as_bytes())?;
+    assert_eq!(session.records_added(), 2);
+
+    for record_id in ["PROV_1", "PROV_2"] {
+        let record = engine.get_record("TEST", record_id, None)?;
+        assert_eq!(LoadMetadata::from_record(&record)?, Some(metadata.clone()));
+    }
+
+    drop(session);
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that CSV rows are converted by a mapping and loaded
+#[test]
+#[serial]
+fn test_load_csv_with_mapping() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-ingest-csv-mapping")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+
+    let data = "id,first,last,city\nCSV_1,Ann,Lee,\"Oslo, Norway\"\n\nCSV_2,Bo,Chen,\n";
+    let mapping = Mapping::new()
+        .with_field(FieldMapping::constant("DATA_SOURCE", "TEST"))
+        .with_field(FieldMapping::from("RECORD_ID", "id"))
+        .with_field(FieldMapping::concat("NAME_FULL", ["first", "last"]))
+        .with_field(FieldMapping::from("ADDR_CITY", "city"));
+    let options = ingest::LoadOptions::new().with_mapping(&mapping);
+    let summary = ingest::load_csv_with_options(&*engine, data.as_bytes(), options)?;
+    assert_eq!(summary.records_loaded, 2);
+    assert_eq!(summary.blank_lines, 1);
+
+    let record = engine.get_record("TEST", "CSV_1", None)?;
+    assert!(record.contains("Oslo, Norway"));
+
+    let bad = "id\nA,B\n";
+    let result =
+        ingest::load_csv_with_options(&*engine, bad.as_bytes(), ingest::LoadOptions::new());
+    assert!(matches!(result, Err(SzError::BadInput(_))));
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that a load tracks and checkpoints per-data-source watermarks
+#[test]
+#[serial]
+fn test_load_tracks_watermarks() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-ingest-watermarks")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let path =
+        std::env::temp_dir().join(format!("sz_ingest_watermarks_{}.json", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let data = concat!(
+        r#"{"DATA_SOURCE":"TEST","RECORD_ID":"WM_1","NAME_FULL":"Ann Lee","UPDATED":30}"#,
+        "\n",
+        r#"{"DATA_SOURCE":"TEST","RECORD_ID":"WM_2","NAME_FULL":"Bo Chen","UPDATED":"2026-03-01T00:00:00Z"}"#,
+        "\n",
+        r#"{"DATA_SOURCE":"TEST","RECORD_ID":"WM_3","NAME_FULL":"Cy Diaz"}"#,
+        "\n",
+    );
+    let store = WatermarkStore::open(&path)?;
+    let options = ingest::LoadOptions::new()
+        .with_watermarks(&store)
+        .with_timestamp_field("UPDATED");
+    let summary = ingest::load_from_reader_with_options(&*engine, data.as_bytes(), options)?;
+    assert_eq!(summary.records_loaded, 3);
+    assert_eq!(summary.watermarks().get("TEST"), Some(&1_772_323_200_000));
+    assert_eq!(
+        WatermarkStore::open(&path)?.get("TEST"),
+        Some(1_772_323_200_000)
+    );
+
+    // An invalid timestamp fails before the record is added
+    let bad = r#"{"DATA_SOURCE":"TEST","RECORD_ID":"WM_4","UPDATED":"soon"}"#;
+    let options = ingest::LoadOptions::new()
+        .with_watermarks(&store)
+        .with_timestamp_field("UPDATED");
+    let result = ingest::load_from_reader_with_options(&*engine, bad.as_bytes(), options);
+    assert!(matches!(result, Err(SzError::BadInput(_))));
+    assert!(matches!(
+        engine.get_record("TEST", "WM_4", None),
+        Err(SzError::NotFound(_))
+    ));
+
+    std::fs::remove_file(&path).ok();
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
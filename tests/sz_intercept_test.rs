@@ -0,0 +1,87 @@
+//! Engine interceptor integration tests.
+
+use serial_test::serial;
+use std::sync::{Arc, Mutex};
+use sz_rust_sdk::helpers::ExampleEnvironment;
+use sz_rust_sdk::intercept::{CallOutcome, EngineCall, Intercept, SzInterceptor};
+use sz_rust_sdk::prelude::*;
+
+/// Records `operation result_code` for every call it sees.
+#[derive(Clone, Default)]
+struct CallLog(Arc<Mutex<Vec<String>>>);
+
+impl SzInterceptor for CallLog {
+    fn after(&self, call: &EngineCall<'_>, outcome: &CallOutcome<'_>) {
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("{} {}", call.operation, outcome.result_code));
+    }
+}
+
+/// Rejects deletes from the TEST data source.
+struct ReadOnlyTest;
+
+impl SzInterceptor for ReadOnlyTest {
+    fn before(&self, call: &EngineCall<'_>) -> SzResult<Intercept> {
+        if call.operation == "delete_record" && call.parameters["data_source"] == "TEST" {
+            return Err(SzError::bad_input("TEST is read-only"));
+        }
+        Ok(Intercept::Proceed)
+    }
+}
+
+/// Answers `get_entity` for entity 0 without calling the engine.
+struct CannedEntity;
+
+impl SzInterceptor for CannedEntity {
+    fn before(&self, call: &EngineCall<'_>) -> SzResult<Intercept> {
+        if call.operation == "get_entity" && call.parameters["entity"]["entity_id"] == 0 {
+            return Ok(Intercept::Respond(
+                r#"{"RESOLVED_ENTITY":{"ENTITY_ID":0}}"#.into(),
+            ));
+        }
+        Ok(Intercept::Proceed)
+    }
+}
+
+/// Test logging, rejecting and answering engine calls
+#[test]
+#[serial]
+fn test_interceptors() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-interceptors")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let log = CallLog::default();
+    env.add_interceptor(Box::new(log.clone()));
+    env.add_interceptor(Box::new(ReadOnlyTest));
+    env.add_interceptor(Box::new(CannedEntity));
+
+    engine.add_record(
+        "TEST",
+        "INTERCEPT1",
+        r#"{"NAME_FULL": "Ina Tercept"}"#,
+        None,
+    )?;
+    engine.get_entity(EntityRef::from_record("TEST", "INTERCEPT1"), None)?;
+    assert!(engine.delete_record("TEST", "INTERCEPT1", None).is_err());
+    let canned = engine.get_entity(EntityRef::Id(EntityId(0)), None)?;
+    assert!(canned.contains(r#""ENTITY_ID":0"#));
+
+    let calls = log.0.lock().unwrap().clone();
+    assert_eq!(calls[0], "add_record 0");
+    // get_entity reads through get_entity_bytes, which is not logged again
+    assert_eq!(calls[1], "get_entity 0");
+    assert!(calls[2].starts_with("delete_record ") && calls[2] != "delete_record 0");
+    assert_eq!(calls[3], "get_entity 0");
+    assert_eq!(calls.len(), 4);
+
+    env.clear_interceptors();
+    engine.delete_record("TEST", "INTERCEPT1", None)?;
+    assert_eq!(log.0.lock().unwrap().len(), 4);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
@@ -0,0 +1,114 @@
+//! Maintenance scheduler integration tests.
+//!
+//! These tests run the scheduler against a real environment and check that
+//! stats samples are delivered and that the scheduler stops cleanly.
+
+use serial_test::serial;
+use std::sync::mpsc;
+use std::time::Duration;
+use sz_rust_sdk::helpers::ExampleEnvironment;
+use sz_rust_sdk::maintenance::{MaintenanceTask, Schedule, SzMaintenanceScheduler};
+use sz_rust_sdk::prelude::*;
+
+/// Test that scheduled stats collection delivers samples to the callback
+#[test]
+#[serial]
+fn test_maintenance_collects_stats() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-maintenance-stats")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    engine.add_record("TEST", "MAINT_1001", r#"{"NAME_FULL":"Mae Tenance"}"#, None)?;
+
+    let (tx, rx) = mpsc::channel();
+    let (err_tx, err_rx) = mpsc::channel();
+    let handle = SzMaintenanceScheduler::new(env.clone())
+        .prime_engine_every(Duration::from_secs(3600))
+        .collect_stats_every(Duration::from_millis(50), move |stats| {
+            let _ = tx.send(stats);
+        })
+        .on_error(move |task, err| {
+            let _ = err_tx.send((task, err.to_string()));
+        })
+        .start()?;
+
+    for _ in 0..2 {
+        let stats = rx
+            .recv_timeout(Duration::from_secs(30))
+            .expect("stats sample should be delivered");
+        let value: serde_json::Value = serde_json::from_str(&stats)?;
+        assert!(value.is_object());
+    }
+    let stats = handle.stats();
+    assert_eq!(stats.len(), 2);
+    assert_eq!(stats[0].task, MaintenanceTask::PrimeEngine);
+    assert_eq!(stats[0].runs, 1);
+    let collect = &stats[1];
+    assert_eq!(collect.task, MaintenanceTask::CollectStats);
+    assert!(collect.runs >= 1);
+    assert_eq!(collect.failures, 0);
+    assert!(collect.last_run.is_some() && collect.next_run.is_some());
+    handle.stop();
+    assert!(
+        err_rx.try_recv().is_err(),
+        "no maintenance task should fail"
+    );
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that a cron schedule reports its next run
+#[test]
+#[serial]
+fn test_maintenance_cron_schedule() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-maintenance-cron")?;
+    let started = std::time::SystemTime::now();
+    let handle = SzMaintenanceScheduler::new(env.clone())
+        .schedule(MaintenanceTask::PrimeEngine, Schedule::cron("0 3 * * *")?)
+        .start()?;
+    let stats = handle.stats();
+    assert_eq!(stats[0].runs, 0);
+    let next_run = stats[0].next_run.expect("cron schedule should run");
+    assert!(next_run > started);
+    assert!(next_run <= started + Duration::from_secs(24 * 3600));
+    handle.stop();
+
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that a scheduler without tasks or with a zero interval is rejected
+#[test]
+#[serial]
+fn test_maintenance_rejects_invalid_schedule() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-maintenance-invalid")?;
+
+    let empty = SzMaintenanceScheduler::new(env.clone()).start();
+    assert!(matches!(empty, Err(SzError::BadInput(_))));
+
+    let zero = SzMaintenanceScheduler::new(env.clone())
+        .schedule(
+            MaintenanceTask::PrimeEngine,
+            Schedule::every(Duration::ZERO),
+        )
+        .start();
+    assert!(matches!(zero, Err(SzError::BadInput(_))));
+
+    assert!(matches!(
+        Schedule::cron("0 3 * *"),
+        Err(SzError::BadInput(_))
+    ));
+    let never = SzMaintenanceScheduler::new(env.clone())
+        .schedule(MaintenanceTask::PrimeEngine, Schedule::cron("0 0 30 2 *")?)
+        .start();
+    assert!(matches!(never, Err(SzError::BadInput(_))));
+
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
@@ -0,0 +1,77 @@
+//! Operation timeout integration tests.
+//!
+//! These tests configure per-operation timeouts on the environment and check
+//! that calls either complete normally or fail with `SzError::Timeout` and are
+//! recorded in `timed_out_calls`.
+
+use serial_test::serial;
+use std::time::Duration;
+use sz_rust_sdk::helpers::ExampleEnvironment;
+use sz_rust_sdk::prelude::*;
+
+/// Test that a generous timeout does not change results
+#[test]
+#[serial]
+fn test_operation_timeout_allows_fast_calls() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-operation-timeout-fast")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    engine.add_record("TEST", "TMO_1001", r#"{"NAME_FULL":"Tim Out"}"#, None)?;
+
+    let untimed =
+        engine.find_network_by_record_id(&[RecordKey::new("TEST", "TMO_1001")?], 1, 0, 10, None)?;
+
+    env.set_operation_timeout(TimedOperation::FindNetwork, Some(Duration::from_secs(60)));
+    env.set_operation_timeout(TimedOperation::GetEntity, Some(Duration::from_secs(60)));
+    assert_eq!(
+        env.operation_timeout(TimedOperation::FindNetwork),
+        Some(Duration::from_secs(60))
+    );
+
+    let timed =
+        engine.find_network_by_record_id(&[RecordKey::new("TEST", "TMO_1001")?], 1, 0, 10, None)?;
+    assert_eq!(timed, untimed);
+    engine.get_entity(EntityRef::from_record("TEST", "TMO_1001"), None)?;
+
+    env.set_operation_timeout(TimedOperation::FindNetwork, None);
+    env.set_operation_timeout(TimedOperation::GetEntity, None);
+    assert!(env.operation_timeout(TimedOperation::FindNetwork).is_none());
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that an exceeded timeout returns SzError::Timeout and is recorded
+#[test]
+#[serial]
+fn test_operation_timeout_exceeded() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-operation-timeout-exceeded")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    engine.add_record("TEST", "TMO_2001", r#"{"NAME_FULL":"Slow Call"}"#, None)?;
+
+    env.set_operation_timeout(TimedOperation::FindNetwork, Some(Duration::from_nanos(1)));
+    let result =
+        engine.find_network_by_record_id(&[RecordKey::new("TEST", "TMO_2001")?], 3, 1, 100, None);
+    env.set_operation_timeout(TimedOperation::FindNetwork, None);
+
+    // A one-nanosecond budget is effectively always exceeded.
+    let err = result.expect_err("call should exceed a one-nanosecond timeout");
+    assert!(matches!(err, SzError::Timeout(_)));
+    assert_eq!(err.category(), "timeout");
+
+    let timed_out = env.timed_out_calls();
+    let last = timed_out.last().expect("timeout should be recorded");
+    assert_eq!(last.operation, "find_network_by_record_id");
+    assert_eq!(last.timeout, Duration::from_nanos(1));
+
+    // Let the abandoned call finish before tearing the environment down.
+    std::thread::sleep(Duration::from_millis(500));
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
@@ -0,0 +1,172 @@
+//! Declarative pipeline integration tests.
+//!
+//! Built with the `pipeline` feature. Each test writes its spec and data
+//! files to a fresh temporary directory.
+#![cfg(feature = "pipeline")]
+
+use serial_test::serial;
+use std::path::PathBuf;
+use std::time::Duration;
+use sz_rust_sdk::control::{ControlHandle, Tuning};
+use sz_rust_sdk::helpers::ExampleEnvironment;
+use sz_rust_sdk::pipeline::{Pipeline, PipelineSpec, RedoPolicy};
+use sz_rust_sdk::prelude::*;
+
+/// Creates an empty temporary directory for one test.
+fn test_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("sz-pipeline-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create test directory");
+    dir
+}
+
+fn people(prefix: &str, count: usize) -> String {
+    (0..count)
+        .map(|i| {
+            format!(
+                r#"{{"DATA_SOURCE":"PEOPLE","RECORD_ID":"{prefix}{i}","NAME_FULL":"Pipe Person {}","PHONE_NUMBER":"555-01{:02}"}}"#,
+                i % 10,
+                i % 10
+            ) + "\n"
+        })
+        .collect()
+}
+
+/// Test a two-source load with a data source mapping, info sink and redo
+#[test]
+#[serial]
+fn test_pipeline_run() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let dir = test_dir("run");
+    std::fs::write(dir.join("a.jsonl"), people("A", 20)).expect("write a.jsonl");
+    std::fs::write(
+        dir.join("b.jsonl"),
+        format!("{}\n{{\"NAME_FULL\":\"No Record Id\"}}\n", people("B", 5)),
+    )
+    .expect("write b.jsonl");
+    std::fs::write(
+        dir.join("load.toml"),
+        r#"
+workers = 3
+batch_size = 4
+info_sink = { file = "info.jsonl" }
+
+[data_source_map]
+people = "TEST"
+
+[[sources]]
+path = "a.jsonl"
+
+[[sources]]
+path = "b.jsonl"
+"#,
+    )
+    .expect("write load.toml");
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-pipeline-run-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let spec = PipelineSpec::from_path(dir.join("load.toml"))?;
+    let report = Pipeline::new(env.clone(), spec).run()?;
+
+    assert_eq!(report.records_loaded(), 25);
+    assert_eq!(report.records_failed(), 1);
+    assert_eq!(report.sources[1].blank_lines, 1);
+    assert_eq!(report.failures[0].line, 7);
+    assert!(!report.stopped);
+    assert_eq!(report.redo.map(|redo| redo.remaining), Some(0));
+    engine.get_record("TEST", "A7", None)?;
+    let info = std::fs::read_to_string(dir.join("info.jsonl")).expect("read info sink");
+    assert_eq!(info.lines().count(), 25);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    let _ = std::fs::remove_dir_all(&dir);
+    Ok(())
+}
+
+/// Test retuning a running pipeline through its control handle
+#[test]
+#[serial]
+fn test_pipeline_runtime_tuning() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let dir = test_dir("tuning");
+    std::fs::write(dir.join("a.jsonl"), people("T", 200)).expect("write a.jsonl");
+    let mut spec = PipelineSpec::from_toml_str(
+        "workers = 1\nbatch_size = 1\n[[sources]]\npath = \"a.jsonl\"\n[data_source_map]\nPEOPLE = \"TEST\"",
+    )?
+    .relative_to(&dir);
+    spec.redo.policy = RedoPolicy::Skip;
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-pipeline-tuning-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let control = ControlHandle::new();
+    control.pause();
+    let pipeline = Pipeline::new(env.clone(), spec).with_control(control.clone());
+
+    let report = std::thread::scope(|scope| {
+        let run = scope.spawn(|| pipeline.run());
+        std::thread::sleep(Duration::from_millis(50));
+        control.tune(Tuning::default().with_workers(4).with_batch_size(25));
+        control.resume();
+        run.join().expect("pipeline thread")
+    })?;
+
+    assert_eq!(report.records_loaded(), 200);
+    assert!(report.redo.is_none());
+    assert_eq!(control.state(), sz_rust_sdk::control::RunState::Stopped);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    let _ = std::fs::remove_dir_all(&dir);
+    Ok(())
+}
+
+/// Test that a watched spec file retunes the pipeline and bad edits are ignored
+#[test]
+#[serial]
+fn test_pipeline_watch_spec() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let dir = test_dir("watch");
+    std::fs::write(dir.join("a.jsonl"), people("W", 100)).expect("write a.jsonl");
+    let spec_path = dir.join("load.toml");
+    let spec_text = |workers: usize| {
+        format!(
+            "workers = {workers}\nbatch_size = 5\n[[sources]]\npath = \"a.jsonl\"\n[data_source_map]\nPEOPLE = \"TEST\"\n[redo]\npolicy = \"skip\""
+        )
+    };
+    std::fs::write(&spec_path, spec_text(1)).expect("write load.toml");
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-pipeline-watch-test")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    let control = ControlHandle::new();
+    control.pause();
+    let pipeline = Pipeline::new(env.clone(), PipelineSpec::from_path(&spec_path)?)
+        .with_control(control.clone())
+        .watch_spec(&spec_path);
+
+    let report = std::thread::scope(|scope| {
+        let run = scope.spawn(|| pipeline.run());
+        // Coarse file timestamps need a visible gap between writes
+        std::thread::sleep(Duration::from_millis(1100));
+        std::fs::write(&spec_path, "not = [valid").expect("write broken spec");
+        std::thread::sleep(Duration::from_millis(1100));
+        std::fs::write(&spec_path, spec_text(3)).expect("write new spec");
+        let deadline = std::time::Instant::now() + Duration::from_secs(10);
+        while control.tuning().workers != Some(3) && std::time::Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        assert_eq!(control.tuning().workers, Some(3));
+        control.resume();
+        run.join().expect("pipeline thread")
+    })?;
+
+    assert_eq!(report.records_loaded(), 100);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    let _ = std::fs::remove_dir_all(&dir);
+    Ok(())
+}
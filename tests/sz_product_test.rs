@@ -126,3 +126,22 @@ fn test_product_content_validation() -> SzResult<()> {
     ExampleEnvironment::cleanup(env)?;
     Ok(())
 }
+
+/// Test that the installation probe finds the library the tests run against
+#[test]
+#[serial]
+fn test_native_probe_matches_product_version() -> SzResult<()> {
+    let report = sz_rust_sdk::native::probe();
+    assert!(report.library_path.is_some(), "{:?}", report.problems);
+
+    let env = ExampleEnvironment::initialize("sz-rust-sdk-native-probe-test")?;
+    let product = env.get_product()?;
+    let version: serde_json::Value = serde_json::from_str(&product.get_version()?)?;
+    if let Some(probed) = &report.version {
+        assert_eq!(version["VERSION"].as_str(), Some(probed.version.as_str()));
+    }
+
+    drop(product);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
@@ -0,0 +1,83 @@
+//! Redo processor integration tests.
+//!
+//! These tests load records that share features, so the engine queues redo
+//! records, and then drain the queue with the processor.
+
+use serial_test::serial;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use sz_rust_sdk::helpers::ExampleEnvironment;
+use sz_rust_sdk::prelude::*;
+use sz_rust_sdk::redo::SzRedoProcessor;
+
+fn load_shared_features(engine: &dyn SzEngine) -> SzResult<()> {
+    for i in 0..20 {
+        engine.add_record(
+            "TEST",
+            &format!("REDO_{i:02}"),
+            &format!(
+                r#"{{"NAME_FULL":"Redo Person {i}","PHONE_NUMBER":"555-0100","ADDR_FULL":"1 Main St"}}"#
+            ),
+            None,
+        )?;
+    }
+    Ok(())
+}
+
+/// Test draining the redo queue completely
+#[test]
+#[serial]
+fn test_redo_drain_until_empty() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-redo-drain")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    load_shared_features(engine.as_ref())?;
+    let queued = engine.count_redo_records()?;
+
+    let summary = SzRedoProcessor::new(env.clone())
+        .with_workers(1, 3)
+        .poll_interval(Duration::from_millis(50))
+        .drain_until(0)?;
+
+    assert_eq!(summary.remaining, 0);
+    assert_eq!(summary.failed, 0);
+    assert!(summary.processed >= queued as u64);
+    assert_eq!(engine.count_redo_records()?, 0);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that continuous mode processes the queue and returns when stopped
+#[test]
+#[serial]
+fn test_redo_run_continuous_stops() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-redo-continuous")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    load_shared_features(engine.as_ref())?;
+
+    let processor = SzRedoProcessor::new(env.clone())
+        .with_workers(1, 2)
+        .target_lag(0)
+        .poll_interval(Duration::from_millis(50));
+    let stop = AtomicBool::new(false);
+    let summary = std::thread::scope(|scope| {
+        let run = scope.spawn(|| processor.run_continuous(&stop));
+        while engine.count_redo_records().is_ok_and(|count| count > 0) {
+            std::thread::sleep(Duration::from_millis(50));
+        }
+        stop.store(true, Ordering::SeqCst);
+        run.join().expect("redo processor panicked")
+    })?;
+
+    assert_eq!(summary.remaining, 0);
+    assert_eq!(summary.failed, 0);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
@@ -0,0 +1,91 @@
+//! Reevaluation campaign integration tests.
+//!
+//! These tests run campaigns over records and entities in a real environment
+//! and check progress reporting, failure collection, and resuming.
+
+use serial_test::serial;
+use std::sync::{Arc, Mutex};
+use sz_rust_sdk::helpers::ExampleEnvironment;
+use sz_rust_sdk::prelude::*;
+use sz_rust_sdk::reevaluate::{ReevaluationCampaign, ReevaluationTarget};
+
+/// Test reevaluating every record of a data source
+#[test]
+#[serial]
+fn test_reevaluate_data_source() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-reevaluate-data-source")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    for i in 0..10 {
+        engine.add_record(
+            "TEST",
+            &format!("REEVAL_{i:02}"),
+            &format!(r#"{{"NAME_FULL":"Re Eval {i}"}}"#),
+            None,
+        )?;
+    }
+
+    let campaign = ReevaluationCampaign::for_data_source(env.clone(), "TEST")?;
+    assert_eq!(campaign.targets().len(), 10);
+
+    let reports = Arc::new(Mutex::new(Vec::new()));
+    let sink = Arc::clone(&reports);
+    let summary = campaign
+        .with_concurrency(3)
+        .on_progress(move |p| sink.lock().unwrap().push(*p))
+        .run()?;
+
+    assert_eq!(summary.reevaluated, 10);
+    assert!(summary.failures.is_empty());
+    assert_eq!(summary.checkpoint, 10);
+    let reports = reports.lock().unwrap();
+    assert_eq!(reports.len(), 10);
+    assert_eq!(reports.last().map(|p| p.completed), Some(10));
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test resuming from a checkpoint and collecting failures
+#[test]
+#[serial]
+fn test_reevaluate_resume_and_failures() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-reevaluate-resume")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    engine.add_record("TEST", "REEVAL_R1", r#"{"NAME_FULL":"Res Ume"}"#, None)?;
+    let entity: serde_json::Value = serde_json::from_str(
+        &engine.get_entity(EntityRef::from_record("TEST", "REEVAL_R1"), None)?,
+    )?;
+    let entity_id = EntityId(entity["RESOLVED_ENTITY"]["ENTITY_ID"].as_i64().unwrap());
+
+    let targets = vec![
+        ReevaluationTarget::Entity(entity_id),
+        ReevaluationTarget::Record {
+            data_source: "NOT_A_SOURCE".to_string(),
+            record_id: "X".to_string(),
+        },
+        ReevaluationTarget::Record {
+            data_source: "TEST".to_string(),
+            record_id: "REEVAL_R1".to_string(),
+        },
+    ];
+
+    let summary = ReevaluationCampaign::new(env.clone(), targets)
+        .resume_from(1)
+        .run()?;
+    assert_eq!(summary.reevaluated, 1);
+    assert_eq!(summary.failures.len(), 1);
+    assert!(matches!(
+        summary.failures[0].0,
+        ReevaluationTarget::Record { ref data_source, .. } if data_source == "NOT_A_SOURCE"
+    ));
+    assert_eq!(summary.checkpoint, 3);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
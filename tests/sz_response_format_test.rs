@@ -0,0 +1,55 @@
+//! Response format integration tests.
+//!
+//! These tests run against the live engine with the in-memory `internal://`
+//! datastore. They set a response format on the environment and check that
+//! engine responses come back with sorted keys in compact or pretty form.
+
+use serial_test::serial;
+use sz_rust_sdk::helpers::ExampleEnvironment;
+use sz_rust_sdk::prelude::*;
+
+const RECORD: &str = r#"{"NAME_FULL":"Jane Smith","ADDR_FULL":"42 Elm St, Las Vegas, NV 89132","PHONE_NUMBER":"702-555-3434"}"#;
+
+/// Test that Compact and Pretty responses parse to the native document and
+/// are stable across calls
+#[test]
+#[serial]
+fn test_response_format() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-response-format")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    engine.add_record("TEST", "RF_1001", RECORD, None)?;
+    let entity = EntityRef::from_record("TEST", "RF_1001");
+
+    assert_eq!(env.response_format(), ResponseFormat::Native);
+    let native: serde_json::Value =
+        serde_json::from_str(&engine.get_entity(entity.clone(), None)?)?;
+
+    env.set_response_format(ResponseFormat::Compact);
+    let compact = engine.get_entity(entity.clone(), None)?;
+    assert!(!compact.contains('\n'));
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&compact)?, native);
+    assert_eq!(compact, engine.get_entity(entity.clone(), None)?);
+    let keys: Vec<&String> = native.as_object().expect("object").keys().collect();
+    let mut sorted = keys.clone();
+    sorted.sort();
+    let first = format!("{{\"{}\":", sorted[0]);
+    assert!(compact.starts_with(&first));
+
+    env.set_response_format(ResponseFormat::Pretty);
+    let pretty = engine.get_entity(entity.clone(), None)?;
+    assert!(pretty.contains("\n  "));
+    assert_eq!(serde_json::from_str::<serde_json::Value>(&pretty)?, native);
+
+    // Mutations without WITH_INFO still return the empty no-info response
+    assert_eq!(
+        engine.add_record("TEST", "RF_1002", RECORD, None)?,
+        SZ_NO_INFO
+    );
+
+    env.set_response_format(ResponseFormat::Native);
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
@@ -0,0 +1,116 @@
+//! Result size limit integration tests.
+//!
+//! These tests run against the live engine with the in-memory `internal://`
+//! datastore. They set a limit on the environment and check that oversized
+//! `get_entity` / `find_network_*` responses are rejected with
+//! `SzError::ResultTooLarge` or downgraded to smaller flags.
+
+use serial_test::serial;
+use sz_rust_sdk::helpers::ExampleEnvironment;
+use sz_rust_sdk::prelude::*;
+
+const RECORD: &str = r#"{"NAME_FULL":"John Smith","ADDR_FULL":"123 Main St, Las Vegas, NV 89132","PHONE_NUMBER":"702-555-1212","EMAIL_ADDRESS":"jsmith@example.com"}"#;
+
+/// Test that an oversized get_entity fails with ResultTooLarge and suggests smaller flags
+#[test]
+#[serial]
+fn test_get_entity_over_limit_returns_result_too_large() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-result-size-limit-error")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    engine.add_record("TEST", "RSL_1001", RECORD, None)?;
+
+    let full = engine.get_entity(EntityRef::from_record("TEST", "RSL_1001"), None)?;
+
+    env.set_result_size_limit(Some(ResultSizeLimit {
+        max_bytes: full.len() - 1,
+        action: OversizeAction::Error,
+    }));
+
+    let err = engine
+        .get_entity(EntityRef::from_record("TEST", "RSL_1001"), None)
+        .expect_err("response larger than the limit should be rejected");
+    assert!(matches!(err, SzError::ResultTooLarge { .. }));
+    assert_eq!(err.category(), "result_too_large");
+    assert_eq!(
+        err.suggested_flags(),
+        Some(SzFlags::ENTITY_DEFAULT_FLAGS & SzFlags::ENTITY_BRIEF_DEFAULT_FLAGS)
+    );
+
+    // The suggested flags produce a response that fits.
+    let brief = engine.get_entity(
+        EntityRef::from_record("TEST", "RSL_1001"),
+        err.suggested_flags(),
+    )?;
+    assert!(brief.len() < full.len());
+
+    env.set_result_size_limit(None);
+    assert!(env.result_size_limit().is_none());
+    engine.get_entity(EntityRef::from_record("TEST", "RSL_1001"), None)?;
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that DowngradeFlags retries get_entity with reduced flags
+#[test]
+#[serial]
+fn test_get_entity_over_limit_downgrades_flags() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-result-size-limit-downgrade")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    engine.add_record("TEST", "RSL_2001", RECORD, None)?;
+
+    let full = engine.get_entity(EntityRef::from_record("TEST", "RSL_2001"), None)?;
+    let brief = engine.get_entity(
+        EntityRef::from_record("TEST", "RSL_2001"),
+        Some(SzFlags::ENTITY_BRIEF_DEFAULT_FLAGS),
+    )?;
+
+    env.set_result_size_limit(Some(ResultSizeLimit {
+        max_bytes: brief.len(),
+        action: OversizeAction::DowngradeFlags,
+    }));
+
+    let downgraded = engine.get_entity(EntityRef::from_record("TEST", "RSL_2001"), None)?;
+    assert!(downgraded.len() < full.len());
+    assert!(downgraded.len() <= brief.len());
+
+    env.set_result_size_limit(None);
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that the limit also applies to find_network_by_record_id
+#[test]
+#[serial]
+fn test_find_network_over_limit_returns_result_too_large() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-result-size-limit-network")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    engine.add_record("TEST", "RSL_3001", RECORD, None)?;
+
+    env.set_result_size_limit(Some(ResultSizeLimit {
+        max_bytes: 1,
+        action: OversizeAction::Error,
+    }));
+
+    let err = engine
+        .find_network_by_record_id(&[RecordKey::new("TEST", "RSL_3001")?], 1, 0, 10, None)
+        .expect_err("network response larger than one byte should be rejected");
+    assert!(matches!(err, SzError::ResultTooLarge { .. }));
+    assert_eq!(
+        err.suggested_flags(),
+        Some(SzFlags::FIND_NETWORK_INCLUDE_MATCHING_INFO)
+    );
+
+    env.set_result_size_limit(None);
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
@@ -0,0 +1,150 @@
+//! Data maintenance tool integration tests.
+
+use serial_test::serial;
+use sz_rust_sdk::helpers::ExampleEnvironment;
+use sz_rust_sdk::prelude::*;
+use sz_rust_sdk::tools;
+
+/// Registers the MIGRATED data source and activates the new configuration.
+fn register_target_data_source(env: &std::sync::Arc<SzEnvironmentCore>) -> SzResult<()> {
+    let config_mgr = env.get_config_manager()?;
+    let config = config_mgr.create_config()?;
+    config.register_data_source("MIGRATED")?;
+    let definition = config.export()?;
+    let config_id =
+        config_mgr.set_default_config(&definition, Some("Migration test config with MIGRATED"))?;
+    env.reinitialize(config_id)?;
+    Ok(())
+}
+
+/// Test moving all records from one data source to another
+#[test]
+#[serial]
+fn test_migrate_data_source() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-migrate-data-source")?;
+    register_target_data_source(&env)?;
+    let engine = env.get_engine()?;
+    for i in 0..5 {
+        engine.add_record(
+            "TEST",
+            &format!("MIG_{i}"),
+            &format!(
+                r#"{{"DATA_SOURCE":"TEST","RECORD_ID":"MIG_{i}","NAME_FULL":"Mig Rate {i}"}}"#
+            ),
+            None,
+        )?;
+    }
+
+    let summary = tools::migrate_data_source(&*engine, "TEST", "MIGRATED")?;
+    assert_eq!(summary.records_migrated, 5);
+
+    for i in 0..5 {
+        let record = engine.get_record("MIGRATED", &format!("MIG_{i}"), None)?;
+        assert!(record.contains(&format!("Mig Rate {i}")));
+        let old = engine.get_record("TEST", &format!("MIG_{i}"), None);
+        assert!(matches!(old, Err(SzError::NotFound(_))));
+    }
+
+    // Running again finds nothing left to move
+    let again = tools::migrate_data_source(&*engine, "TEST", "MIGRATED")?;
+    assert_eq!(again.records_migrated, 0);
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that migrating to an unregistered data source leaves records in place
+#[test]
+#[serial]
+fn test_migrate_data_source_unknown_target() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-migrate-unknown-target")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    engine.add_record("TEST", "MIG_X", r#"{"NAME_FULL":"Stay Put"}"#, None)?;
+
+    let result = tools::migrate_data_source(&*engine, "TEST", "NOT_REGISTERED");
+    assert!(result.is_err());
+    engine.get_record("TEST", "MIG_X", None)?;
+
+    assert!(tools::migrate_data_source(&*engine, "TEST", "TEST").is_err());
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test purging and reloading the repository from two sources
+#[test]
+#[serial]
+fn test_reload_repository() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-reload-repository")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    engine.add_record("TEST", "STALE_1", r#"{"NAME_FULL":"Gone Soon"}"#, None)?;
+
+    let first = concat!(
+        r#"{"DATA_SOURCE":"TEST","RECORD_ID":"R1","NAME_FULL":"Robin Reload","PHONE_NUMBER":"555-0101"}"#,
+        "\n",
+        r#"{"DATA_SOURCE":"TEST","RECORD_ID":"R2","NAME_FULL":"Robin Reload","PHONE_NUMBER":"555-0101"}"#,
+        "\n",
+    );
+    let second = concat!(
+        r#"{"DATA_SOURCE":"RELOADED","RECORD_ID":"R3","NAME_FULL":"Sasha Other"}"#,
+        "\n",
+    );
+    let options = tools::ReloadOptions::new(PurgeConfirmation::I_UNDERSTAND_THIS_DELETES_ALL_DATA)
+        .with_data_sources(["TEST", "RELOADED"])
+        .with_redo_workers(2);
+    let report = tools::reload_repository(
+        &env,
+        [
+            tools::ReloadSource::new("first", first.as_bytes()),
+            tools::ReloadSource::new("second", second.as_bytes()),
+        ],
+        options,
+    )?;
+
+    assert_eq!(report.records_loaded(), 3);
+    assert_eq!(report.sources[0].0, "first");
+    assert_eq!(report.counts.records, 3);
+    assert_eq!(report.counts.entities, 2);
+    assert_eq!(report.redo.remaining, 0);
+    assert_eq!(env.get_active_config_id()?, report.config_id);
+
+    let engine = env.get_engine()?;
+    assert!(matches!(
+        engine.get_record("TEST", "STALE_1", None),
+        Err(SzError::NotFound(_))
+    ));
+
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}
+
+/// Test that a reload does nothing when purging is disabled
+#[test]
+#[serial]
+fn test_reload_repository_purge_disabled() -> SzResult<()> {
+    let _ = SzEnvironmentCore::try_get_instance().map(|e| e.destroy());
+
+    let env = ExampleEnvironment::initialize("test-reload-purge-disabled")?;
+    let engine = ExampleEnvironment::get_engine_with_setup(&env)?;
+    engine.add_record("TEST", "KEEP_1", r#"{"NAME_FULL":"Keep Me"}"#, None)?;
+    env.set_purge_enabled(false);
+
+    let options = tools::ReloadOptions::new(PurgeConfirmation::I_UNDERSTAND_THIS_DELETES_ALL_DATA);
+    let result = tools::reload_repository(&env, Vec::new(), options);
+    assert!(matches!(result, Err(SzError::BadInput(_))));
+    engine.get_record("TEST", "KEEP_1", None)?;
+
+    env.set_purge_enabled(true);
+    drop(engine);
+    ExampleEnvironment::cleanup(env)?;
+    Ok(())
+}